@@ -1,8 +1,11 @@
 //! When typing the opening character of one of the possible pairs defined below,
 //! this module provides the functionality to insert the paired closing character.
 
-use crate::{graphemes, movement::Direction, Change, Deletion, Range, Rope, Tendril};
-use std::collections::HashMap;
+use crate::{
+    graphemes, movement::Direction, tree_sitter::Node, Change, Deletion, Range, Rope, RopeSlice,
+    Syntax, Tendril,
+};
+use std::{collections::HashMap, ops};
 
 // Heavily based on https://github.com/codemirror/closebrackets/
 pub const DEFAULT_PAIRS: &[(char, char)] = &[
@@ -415,3 +418,109 @@ fn handle_insert_same(doc: &Rope, range: &Range, pair: &Pair) -> Option<(Change,
 
     Some(result)
 }
+
+/// The node kinds of the elements of markup grammars: the element, its opening tag and its
+/// closing tag. The name of a tag is its first named child.
+const TAG_NODE_KINDS: &[(&str, &str, &str)] = &[
+    // HTML and the grammars based on it (Vue, Svelte, Astro, ...)
+    ("element", "start_tag", "end_tag"),
+    // XML
+    ("element", "STag", "ETag"),
+    // JSX
+    ("jsx_element", "jsx_opening_element", "jsx_closing_element"),
+];
+
+/// HTML elements which never have a closing tag.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+fn tag_name<'a>(tag: &Node<'a>) -> Option<Node<'a>> {
+    tag.children().find(|child| child.is_named())
+}
+
+fn node_text(text: RopeSlice, node: &Node) -> String {
+    text.byte_slice(node.start_byte() as usize..node.end_byte() as usize)
+        .to_string()
+}
+
+/// Finds the opening tag `node` belongs to (or is) and the kinds of its element.
+fn find_opening_tag<'a>(
+    mut node: Node<'a>,
+) -> Option<(
+    Node<'a>,
+    &'static (&'static str, &'static str, &'static str),
+)> {
+    loop {
+        if let Some(kinds) = TAG_NODE_KINDS
+            .iter()
+            .find(|(_, open, _)| *open == node.kind())
+        {
+            return Some((node, kinds));
+        }
+        node = node.parent()?;
+    }
+}
+
+/// Finds the name of the closing tag of the element of `opening_tag`.
+fn closing_tag_name<'a>(opening_tag: &Node<'a>, element: &str, close: &str) -> Option<Node<'a>> {
+    let element = opening_tag
+        .parent()
+        .filter(|parent| parent.kind() == element)?;
+    let closing_tag = element.children().find(|child| child.kind() == close)?;
+    tag_name(&closing_tag)
+}
+
+/// Returns the closing tag for the opening tag completed by the `>` right before `cursor`,
+/// unless the element is already closed or is an HTML void element.
+pub fn close_tag(syntax: &Syntax, text: RopeSlice, cursor: usize) -> Option<String> {
+    let end = text.char_to_byte(cursor) as u32;
+    let node = syntax.descendant_for_byte_range(end.checked_sub(1)?, end)?;
+    let (opening_tag, (element, _, close)) = find_opening_tag(node)?;
+    if opening_tag.end_byte() != end {
+        return None;
+    }
+    let name = node_text(text, &tag_name(&opening_tag)?);
+    if VOID_ELEMENTS
+        .iter()
+        .any(|void| name.eq_ignore_ascii_case(void))
+    {
+        return None;
+    }
+    if closing_tag_name(&opening_tag, element, close)
+        .is_some_and(|closing_name| node_text(text, &closing_name) == name)
+    {
+        return None;
+    }
+    Some(format!("</{name}>"))
+}
+
+/// Finds the name of the opening tag touching `cursor` and the name of its closing tag, as char
+/// ranges. The names are only linked while they are equal, so that the closing tag can follow
+/// the edits of the opening tag.
+pub fn linked_tag(
+    syntax: &Syntax,
+    text: RopeSlice,
+    cursor: usize,
+) -> Option<(ops::Range<usize>, ops::Range<usize>)> {
+    let byte = text.char_to_byte(cursor) as u32;
+    // the cursor may also be right after the name when appending to it
+    [byte, byte.saturating_sub(1)].into_iter().find_map(|pos| {
+        let node = syntax.descendant_for_byte_range(pos, pos)?;
+        let (opening_tag, (element, _, close)) = find_opening_tag(node)?;
+        let name = tag_name(&opening_tag)?;
+        if !name.byte_range().contains(&byte) && name.end_byte() != byte {
+            return None;
+        }
+        let closing_name = closing_tag_name(&opening_tag, element, close)?;
+        if node_text(text, &name) != node_text(text, &closing_name) {
+            return None;
+        }
+        let char_range = |node: &Node| {
+            text.byte_to_char(node.start_byte() as usize)
+                ..text.byte_to_char(node.end_byte() as usize)
+        };
+        Some((char_range(&name), char_range(&closing_name)))
+    })
+}
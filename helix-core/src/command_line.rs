@@ -130,6 +130,10 @@ pub struct Signature {
     /// * `:toggle --bar foo` has one positional "foo" and one flag "--bar".
     /// * `:toggle --bar foo --baz` has two positionals `["foo", "--baz"]` and one flag "--bar".
     pub raw_after: Option<u8>,
+    /// Whether the command line is a shell command. Variables and registers expanded in a shell
+    /// command are quoted so that each expansion is passed to the shell as a single word, for
+    /// example `:sh cargo test %{selection}`.
+    pub shell: bool,
     /// A set of flags that a command may accept.
     ///
     /// See the `Flag` struct for more info.
@@ -147,6 +151,7 @@ impl Signature {
     pub const DEFAULT: Self = Self {
         positionals: (0, None),
         raw_after: None,
+        shell: false,
         flags: &[],
         _dummy: (),
     };
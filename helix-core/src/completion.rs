@@ -6,6 +6,8 @@
 pub struct CompletionItem {
     pub transaction: Transaction,
     pub label: Cow<'static, str>,
+    /// The text matched against the typed word, defaults to `label`.
+    pub filter_text: Option<Cow<'static, str>>,
     pub kind: Cow<'static, str>,
     /// Containing Markdown
     pub documentation: Option<String>,
@@ -17,6 +19,7 @@ pub enum CompletionProvider {
     Lsp(LanguageServerId),
     Path,
     Word,
+    Spelling,
 }
 
 impl From<LanguageServerId> for CompletionProvider {
@@ -64,6 +64,8 @@ pub enum DiagnosticProvider {
         /// not clear the pull diagnostics and vice-versa.
         identifier: Option<Arc<str>>,
     },
+    /// Misspelled words found by the spell checker.
+    Spelling,
     // Future internal features can go here...
 }
 
@@ -71,7 +73,7 @@ impl DiagnosticProvider {
     pub fn language_server_id(&self) -> Option<LanguageServerId> {
         match self {
             Self::Lsp { server_id, .. } => Some(*server_id),
-            // _ => None,
+            Self::Spelling => None,
         }
     }
 }
@@ -1,11 +1,13 @@
 use std::ops::Range;
 use std::time::Instant;
 
-use imara_diff::{Algorithm, Diff, Hunk, IndentHeuristic, IndentLevel, InternedInput};
+use imara_diff::{Algorithm, Diff, IndentHeuristic, IndentLevel, InternedInput};
 use ropey::RopeSlice;
 
 use crate::{ChangeSet, Rope, Tendril, Transaction};
 
+pub use imara_diff::Hunk;
+
 struct ChangeSetBuilder<'a> {
     res: ChangeSet,
     after: RopeSlice<'a>,
@@ -136,6 +138,22 @@ fn estimate_tokens(&self) -> u32 {
     }
 }
 
+fn line_diff(file: &InternedInput<RopeSlice>) -> Diff {
+    let mut diff = Diff::compute(Algorithm::Histogram, file);
+    diff.postprocess_with_heuristic(
+        file,
+        IndentHeuristic::new(|token| IndentLevel::for_ascii_line(file.interner[token].bytes(), 4)),
+    );
+    diff
+}
+
+/// Compares the lines of `before` and `after` and returns the changed line ranges in ascending
+/// order.
+pub fn compare_lines(before: &Rope, after: &Rope) -> Vec<Hunk> {
+    let file = InternedInput::new(RopeLines(before.slice(..)), RopeLines(after.slice(..)));
+    line_diff(&file).hunks().collect()
+}
+
 /// Compares `old` and `new` to generate a [`Transaction`] describing
 /// the steps required to get from `old` to `new`.
 pub fn compare_ropes(before: &Rope, after: &Rope) -> Transaction {
@@ -151,11 +169,7 @@ pub fn compare_ropes(before: &Rope, after: &Rope) -> Transaction {
         current_hunk: InternedInput::default(),
         char_diff: Diff::default(),
     };
-    let mut diff = Diff::compute(Algorithm::Histogram, &file);
-    diff.postprocess_with_heuristic(
-        &file,
-        IndentHeuristic::new(|token| IndentLevel::for_ascii_line(file.interner[token].bytes(), 4)),
-    );
+    let diff = line_diff(&file);
     for hunk in diff.hunks() {
         builder.process_hunk(hunk.before, hunk.after)
     }
@@ -188,6 +202,17 @@ fn test_compare_ropes(a: String, b: String) -> bool {
         }
     }
 
+    #[test]
+    fn line_hunks() {
+        let before = Rope::from("a\nb\nc\nd\n");
+        let after = Rope::from("a\nc\nx\nd\ne\n");
+        let hunks: Vec<_> = compare_lines(&before, &after)
+            .into_iter()
+            .map(|hunk| (hunk.before, hunk.after))
+            .collect();
+        assert_eq!(hunks, vec![(1..2, 1..1), (3..3, 2..3), (4..4, 4..5)]);
+    }
+
     #[test]
     fn equal_files() {
         test_identity("foo", "foo");
@@ -0,0 +1,496 @@
+//! Digraphs from [RFC 1345](https://www.rfc-editor.org/rfc/rfc1345): two characters that stand
+//! for a character that is hard to type, like `a*` for `α` or `->` for `→`. The table covers the
+//! commonly used part of RFC 1345: accented Latin letters, Greek, Cyrillic, punctuation, currency,
+//! arrows, mathematical operators and box drawing.
+
+/// The digraphs with the character they stand for and its Unicode name.
+#[rustfmt::skip]
+pub const DIGRAPHS: &[([char; 2], char, &str)] = &[
+    (['a', '!'], 'à', "latin small letter a with grave"),
+    (['a', '\''], 'á', "latin small letter a with acute"),
+    (['a', '>'], 'â', "latin small letter a with circumflex"),
+    (['a', '?'], 'ã', "latin small letter a with tilde"),
+    (['a', ':'], 'ä', "latin small letter a with diaeresis"),
+    (['e', '!'], 'è', "latin small letter e with grave"),
+    (['e', '\''], 'é', "latin small letter e with acute"),
+    (['e', '>'], 'ê', "latin small letter e with circumflex"),
+    (['e', '?'], 'ẽ', "latin small letter e with tilde"),
+    (['e', ':'], 'ë', "latin small letter e with diaeresis"),
+    (['i', '!'], 'ì', "latin small letter i with grave"),
+    (['i', '\''], 'í', "latin small letter i with acute"),
+    (['i', '>'], 'î', "latin small letter i with circumflex"),
+    (['i', '?'], 'ĩ', "latin small letter i with tilde"),
+    (['i', ':'], 'ï', "latin small letter i with diaeresis"),
+    (['o', '!'], 'ò', "latin small letter o with grave"),
+    (['o', '\''], 'ó', "latin small letter o with acute"),
+    (['o', '>'], 'ô', "latin small letter o with circumflex"),
+    (['o', '?'], 'õ', "latin small letter o with tilde"),
+    (['o', ':'], 'ö', "latin small letter o with diaeresis"),
+    (['u', '!'], 'ù', "latin small letter u with grave"),
+    (['u', '\''], 'ú', "latin small letter u with acute"),
+    (['u', '>'], 'û', "latin small letter u with circumflex"),
+    (['u', '?'], 'ũ', "latin small letter u with tilde"),
+    (['u', ':'], 'ü', "latin small letter u with diaeresis"),
+    (['A', '!'], 'À', "latin capital letter a with grave"),
+    (['A', '\''], 'Á', "latin capital letter a with acute"),
+    (['A', '>'], 'Â', "latin capital letter a with circumflex"),
+    (['A', '?'], 'Ã', "latin capital letter a with tilde"),
+    (['A', ':'], 'Ä', "latin capital letter a with diaeresis"),
+    (['E', '!'], 'È', "latin capital letter e with grave"),
+    (['E', '\''], 'É', "latin capital letter e with acute"),
+    (['E', '>'], 'Ê', "latin capital letter e with circumflex"),
+    (['E', '?'], 'Ẽ', "latin capital letter e with tilde"),
+    (['E', ':'], 'Ë', "latin capital letter e with diaeresis"),
+    (['I', '!'], 'Ì', "latin capital letter i with grave"),
+    (['I', '\''], 'Í', "latin capital letter i with acute"),
+    (['I', '>'], 'Î', "latin capital letter i with circumflex"),
+    (['I', '?'], 'Ĩ', "latin capital letter i with tilde"),
+    (['I', ':'], 'Ï', "latin capital letter i with diaeresis"),
+    (['O', '!'], 'Ò', "latin capital letter o with grave"),
+    (['O', '\''], 'Ó', "latin capital letter o with acute"),
+    (['O', '>'], 'Ô', "latin capital letter o with circumflex"),
+    (['O', '?'], 'Õ', "latin capital letter o with tilde"),
+    (['O', ':'], 'Ö', "latin capital letter o with diaeresis"),
+    (['U', '!'], 'Ù', "latin capital letter u with grave"),
+    (['U', '\''], 'Ú', "latin capital letter u with acute"),
+    (['U', '>'], 'Û', "latin capital letter u with circumflex"),
+    (['U', '?'], 'Ũ', "latin capital letter u with tilde"),
+    (['U', ':'], 'Ü', "latin capital letter u with diaeresis"),
+    (['n', '?'], 'ñ', "latin small letter n with tilde"),
+    (['N', '?'], 'Ñ', "latin capital letter n with tilde"),
+    (['y', '\''], 'ý', "latin small letter y with acute"),
+    (['Y', '\''], 'Ý', "latin capital letter y with acute"),
+    (['y', ':'], 'ÿ', "latin small letter y with diaeresis"),
+    (['Y', ':'], 'Ÿ', "latin capital letter y with diaeresis"),
+    (['a', 'a'], 'å', "latin small letter a with ring above"),
+    (['A', 'A'], 'Å', "latin capital letter a with ring above"),
+    (['a', 'e'], 'æ', "latin small letter ae"),
+    (['A', 'E'], 'Æ', "latin capital letter ae"),
+    (['c', ','], 'ç', "latin small letter c with cedilla"),
+    (['C', ','], 'Ç', "latin capital letter c with cedilla"),
+    (['o', '/'], 'ø', "latin small letter o with stroke"),
+    (['O', '/'], 'Ø', "latin capital letter o with stroke"),
+    (['s', 's'], 'ß', "latin small letter sharp s"),
+    (['D', '-'], 'Ð', "latin capital letter eth"),
+    (['d', '-'], 'ð', "latin small letter eth"),
+    (['T', 'H'], 'Þ', "latin capital letter thorn"),
+    (['t', 'h'], 'þ', "latin small letter thorn"),
+    (['o', 'e'], 'œ', "latin small ligature oe"),
+    (['O', 'E'], 'Œ', "latin capital ligature oe"),
+    (['D', '/'], 'Đ', "latin capital letter d with stroke"),
+    (['d', '/'], 'đ', "latin small letter d with stroke"),
+    (['l', '/'], 'ł', "latin small letter l with stroke"),
+    (['L', '/'], 'Ł', "latin capital letter l with stroke"),
+    (['c', '<'], 'č', "latin small letter c with caron"),
+    (['C', '<'], 'Č', "latin capital letter c with caron"),
+    (['s', '<'], 'š', "latin small letter s with caron"),
+    (['S', '<'], 'Š', "latin capital letter s with caron"),
+    (['z', '<'], 'ž', "latin small letter z with caron"),
+    (['Z', '<'], 'Ž', "latin capital letter z with caron"),
+    (['e', '<'], 'ě', "latin small letter e with caron"),
+    (['E', '<'], 'Ě', "latin capital letter e with caron"),
+    (['r', '<'], 'ř', "latin small letter r with caron"),
+    (['R', '<'], 'Ř', "latin capital letter r with caron"),
+    (['n', '<'], 'ň', "latin small letter n with caron"),
+    (['N', '<'], 'Ň', "latin capital letter n with caron"),
+    (['a', ';'], 'ą', "latin small letter a with ogonek"),
+    (['A', ';'], 'Ą', "latin capital letter a with ogonek"),
+    (['e', ';'], 'ę', "latin small letter e with ogonek"),
+    (['E', ';'], 'Ę', "latin capital letter e with ogonek"),
+    (['z', '.'], 'ż', "latin small letter z with dot above"),
+    (['Z', '.'], 'Ż', "latin capital letter z with dot above"),
+    (['c', '\''], 'ć', "latin small letter c with acute"),
+    (['C', '\''], 'Ć', "latin capital letter c with acute"),
+    (['n', '\''], 'ń', "latin small letter n with acute"),
+    (['N', '\''], 'Ń', "latin capital letter n with acute"),
+    (['s', '\''], 'ś', "latin small letter s with acute"),
+    (['S', '\''], 'Ś', "latin capital letter s with acute"),
+    (['z', '\''], 'ź', "latin small letter z with acute"),
+    (['Z', '\''], 'Ź', "latin capital letter z with acute"),
+    (['g', '('], 'ğ', "latin small letter g with breve"),
+    (['G', '('], 'Ğ', "latin capital letter g with breve"),
+    (['s', ','], 'ş', "latin small letter s with cedilla"),
+    (['S', ','], 'Ş', "latin capital letter s with cedilla"),
+    (['i', '.'], 'ı', "latin small letter dotless i"),
+    (['I', '.'], 'İ', "latin capital letter i with dot above"),
+    (['a', '-'], 'ā', "latin small letter a with macron"),
+    (['A', '-'], 'Ā', "latin capital letter a with macron"),
+    (['e', '-'], 'ē', "latin small letter e with macron"),
+    (['E', '-'], 'Ē', "latin capital letter e with macron"),
+    (['i', '-'], 'ī', "latin small letter i with macron"),
+    (['I', '-'], 'Ī', "latin capital letter i with macron"),
+    (['o', '-'], 'ō', "latin small letter o with macron"),
+    (['O', '-'], 'Ō', "latin capital letter o with macron"),
+    (['u', '-'], 'ū', "latin small letter u with macron"),
+    (['U', '-'], 'Ū', "latin capital letter u with macron"),
+    (['o', '"'], 'ő', "latin small letter o with double acute"),
+    (['O', '"'], 'Ő', "latin capital letter o with double acute"),
+    (['u', '"'], 'ű', "latin small letter u with double acute"),
+    (['U', '"'], 'Ű', "latin capital letter u with double acute"),
+    (['u', '0'], 'ů', "latin small letter u with ring above"),
+    (['U', '0'], 'Ů', "latin capital letter u with ring above"),
+    (['a', '*'], 'α', "greek small letter alpha"),
+    (['A', '*'], 'Α', "greek capital letter alpha"),
+    (['b', '*'], 'β', "greek small letter beta"),
+    (['B', '*'], 'Β', "greek capital letter beta"),
+    (['g', '*'], 'γ', "greek small letter gamma"),
+    (['G', '*'], 'Γ', "greek capital letter gamma"),
+    (['d', '*'], 'δ', "greek small letter delta"),
+    (['D', '*'], 'Δ', "greek capital letter delta"),
+    (['e', '*'], 'ε', "greek small letter epsilon"),
+    (['E', '*'], 'Ε', "greek capital letter epsilon"),
+    (['z', '*'], 'ζ', "greek small letter zeta"),
+    (['Z', '*'], 'Ζ', "greek capital letter zeta"),
+    (['y', '*'], 'η', "greek small letter eta"),
+    (['Y', '*'], 'Η', "greek capital letter eta"),
+    (['h', '*'], 'θ', "greek small letter theta"),
+    (['H', '*'], 'Θ', "greek capital letter theta"),
+    (['i', '*'], 'ι', "greek small letter iota"),
+    (['I', '*'], 'Ι', "greek capital letter iota"),
+    (['k', '*'], 'κ', "greek small letter kappa"),
+    (['K', '*'], 'Κ', "greek capital letter kappa"),
+    (['l', '*'], 'λ', "greek small letter lamda"),
+    (['L', '*'], 'Λ', "greek capital letter lamda"),
+    (['m', '*'], 'μ', "greek small letter mu"),
+    (['M', '*'], 'Μ', "greek capital letter mu"),
+    (['n', '*'], 'ν', "greek small letter nu"),
+    (['N', '*'], 'Ν', "greek capital letter nu"),
+    (['c', '*'], 'ξ', "greek small letter xi"),
+    (['C', '*'], 'Ξ', "greek capital letter xi"),
+    (['o', '*'], 'ο', "greek small letter omicron"),
+    (['O', '*'], 'Ο', "greek capital letter omicron"),
+    (['p', '*'], 'π', "greek small letter pi"),
+    (['P', '*'], 'Π', "greek capital letter pi"),
+    (['r', '*'], 'ρ', "greek small letter rho"),
+    (['R', '*'], 'Ρ', "greek capital letter rho"),
+    (['s', '*'], 'σ', "greek small letter sigma"),
+    (['S', '*'], 'Σ', "greek capital letter sigma"),
+    (['t', '*'], 'τ', "greek small letter tau"),
+    (['T', '*'], 'Τ', "greek capital letter tau"),
+    (['u', '*'], 'υ', "greek small letter upsilon"),
+    (['U', '*'], 'Υ', "greek capital letter upsilon"),
+    (['f', '*'], 'φ', "greek small letter phi"),
+    (['F', '*'], 'Φ', "greek capital letter phi"),
+    (['x', '*'], 'χ', "greek small letter chi"),
+    (['X', '*'], 'Χ', "greek capital letter chi"),
+    (['q', '*'], 'ψ', "greek small letter psi"),
+    (['Q', '*'], 'Ψ', "greek capital letter psi"),
+    (['w', '*'], 'ω', "greek small letter omega"),
+    (['W', '*'], 'Ω', "greek capital letter omega"),
+    (['*', 's'], 'ς', "greek small letter final sigma"),
+    (['A', '='], 'А', "cyrillic capital letter a"),
+    (['a', '='], 'а', "cyrillic small letter a"),
+    (['B', '='], 'Б', "cyrillic capital letter be"),
+    (['b', '='], 'б', "cyrillic small letter be"),
+    (['V', '='], 'В', "cyrillic capital letter ve"),
+    (['v', '='], 'в', "cyrillic small letter ve"),
+    (['G', '='], 'Г', "cyrillic capital letter ghe"),
+    (['g', '='], 'г', "cyrillic small letter ghe"),
+    (['D', '='], 'Д', "cyrillic capital letter de"),
+    (['d', '='], 'д', "cyrillic small letter de"),
+    (['E', '='], 'Е', "cyrillic capital letter ie"),
+    (['e', '='], 'е', "cyrillic small letter ie"),
+    (['Z', '='], 'З', "cyrillic capital letter ze"),
+    (['z', '='], 'з', "cyrillic small letter ze"),
+    (['I', '='], 'И', "cyrillic capital letter i"),
+    (['i', '='], 'и', "cyrillic small letter i"),
+    (['J', '='], 'Й', "cyrillic capital letter short i"),
+    (['j', '='], 'й', "cyrillic small letter short i"),
+    (['K', '='], 'К', "cyrillic capital letter ka"),
+    (['k', '='], 'к', "cyrillic small letter ka"),
+    (['L', '='], 'Л', "cyrillic capital letter el"),
+    (['l', '='], 'л', "cyrillic small letter el"),
+    (['M', '='], 'М', "cyrillic capital letter em"),
+    (['m', '='], 'м', "cyrillic small letter em"),
+    (['N', '='], 'Н', "cyrillic capital letter en"),
+    (['n', '='], 'н', "cyrillic small letter en"),
+    (['O', '='], 'О', "cyrillic capital letter o"),
+    (['o', '='], 'о', "cyrillic small letter o"),
+    (['P', '='], 'П', "cyrillic capital letter pe"),
+    (['p', '='], 'п', "cyrillic small letter pe"),
+    (['R', '='], 'Р', "cyrillic capital letter er"),
+    (['r', '='], 'р', "cyrillic small letter er"),
+    (['S', '='], 'С', "cyrillic capital letter es"),
+    (['s', '='], 'с', "cyrillic small letter es"),
+    (['T', '='], 'Т', "cyrillic capital letter te"),
+    (['t', '='], 'т', "cyrillic small letter te"),
+    (['U', '='], 'У', "cyrillic capital letter u"),
+    (['u', '='], 'у', "cyrillic small letter u"),
+    (['F', '='], 'Ф', "cyrillic capital letter ef"),
+    (['f', '='], 'ф', "cyrillic small letter ef"),
+    (['H', '='], 'Х', "cyrillic capital letter ha"),
+    (['h', '='], 'х', "cyrillic small letter ha"),
+    (['C', '='], 'Ц', "cyrillic capital letter tse"),
+    (['c', '='], 'ц', "cyrillic small letter tse"),
+    (['Z', '%'], 'Ж', "cyrillic capital letter zhe"),
+    (['z', '%'], 'ж', "cyrillic small letter zhe"),
+    (['C', '%'], 'Ч', "cyrillic capital letter che"),
+    (['c', '%'], 'ч', "cyrillic small letter che"),
+    (['S', '%'], 'Ш', "cyrillic capital letter sha"),
+    (['s', '%'], 'ш', "cyrillic small letter sha"),
+    (['S', 'c'], 'Щ', "cyrillic capital letter shcha"),
+    (['s', 'c'], 'щ', "cyrillic small letter shcha"),
+    (['=', '"'], 'Ъ', "cyrillic capital letter hard sign"),
+    (['=', '\''], 'ъ', "cyrillic small letter hard sign"),
+    (['Y', '='], 'Ы', "cyrillic capital letter yeru"),
+    (['y', '='], 'ы', "cyrillic small letter yeru"),
+    (['%', '"'], 'Ь', "cyrillic capital letter soft sign"),
+    (['%', '\''], 'ь', "cyrillic small letter soft sign"),
+    (['J', 'E'], 'Э', "cyrillic capital letter e"),
+    (['j', 'e'], 'э', "cyrillic small letter e"),
+    (['J', 'U'], 'Ю', "cyrillic capital letter yu"),
+    (['j', 'u'], 'ю', "cyrillic small letter yu"),
+    (['J', 'A'], 'Я', "cyrillic capital letter ya"),
+    (['j', 'a'], 'я', "cyrillic small letter ya"),
+    (['I', 'O'], 'Ё', "cyrillic capital letter io"),
+    (['i', 'o'], 'ё', "cyrillic small letter io"),
+    (['N', 'S'], '\u{a0}', "no-break space"),
+    (['!', 'I'], '¡', "inverted exclamation mark"),
+    (['?', 'I'], '¿', "inverted question mark"),
+    (['<', '<'], '«', "left-pointing double angle quotation mark"),
+    (['>', '>'], '»', "right-pointing double angle quotation mark"),
+    (['S', 'E'], '§', "section sign"),
+    (['P', 'I'], '¶', "pilcrow sign"),
+    (['C', 'o'], '©', "copyright sign"),
+    (['R', 'g'], '®', "registered sign"),
+    (['T', 'M'], '™', "trade mark sign"),
+    (['D', 'G'], '°', "degree sign"),
+    (['+', '-'], '±', "plus-minus sign"),
+    (['M', 'y'], 'µ', "micro sign"),
+    (['.', 'M'], '·', "middle dot"),
+    (['1', '4'], '¼', "vulgar fraction one quarter"),
+    (['1', '2'], '½', "vulgar fraction one half"),
+    (['3', '4'], '¾', "vulgar fraction three quarters"),
+    (['1', 'S'], '¹', "superscript one"),
+    (['2', 'S'], '²', "superscript two"),
+    (['3', 'S'], '³', "superscript three"),
+    (['N', 'O'], '¬', "not sign"),
+    (['B', 'B'], '¦', "broken bar"),
+    (['\'', ':'], '¨', "diaeresis"),
+    (['-', 'a'], 'ª', "feminine ordinal indicator"),
+    (['-', 'o'], 'º', "masculine ordinal indicator"),
+    (['*', 'X'], '×', "multiplication sign"),
+    (['-', ':'], '÷', "division sign"),
+    (['-', 'N'], '–', "en dash"),
+    (['-', 'M'], '—', "em dash"),
+    (['\'', '6'], '‘', "left single quotation mark"),
+    (['\'', '9'], '’', "right single quotation mark"),
+    (['"', '6'], '“', "left double quotation mark"),
+    (['"', '9'], '”', "right double quotation mark"),
+    (['.', '9'], '‚', "single low-9 quotation mark"),
+    ([':', '9'], '„', "double low-9 quotation mark"),
+    (['/', '-'], '†', "dagger"),
+    (['/', '='], '‡', "double dagger"),
+    (['%', '0'], '‰', "per mille sign"),
+    (['.', '3'], '…', "horizontal ellipsis"),
+    (['o', 'o'], '•', "bullet"),
+    (['1', '\''], '′', "prime"),
+    (['2', '\''], '″', "double prime"),
+    (['<', '1'], '‹', "single left-pointing angle quotation mark"),
+    (['>', '1'], '›', "single right-pointing angle quotation mark"),
+    (['E', 'u'], '€', "euro sign"),
+    (['P', 'd'], '£', "pound sign"),
+    (['Y', 'e'], '¥', "yen sign"),
+    (['C', 't'], '¢', "cent sign"),
+    (['C', 'u'], '¤', "currency sign"),
+    (['=', 'R'], '₽', "ruble sign"),
+    (['R', 's'], '₨', "rupee sign"),
+    (['=', 'P'], '₽', "ruble sign"),
+    (['0', 'S'], '⁰', "superscript zero"),
+    (['4', 'S'], '⁴', "superscript four"),
+    (['5', 'S'], '⁵', "superscript five"),
+    (['6', 'S'], '⁶', "superscript six"),
+    (['7', 'S'], '⁷', "superscript seven"),
+    (['8', 'S'], '⁸', "superscript eight"),
+    (['9', 'S'], '⁹', "superscript nine"),
+    (['n', 'S'], 'ⁿ', "superscript latin small letter n"),
+    (['0', 's'], '₀', "subscript zero"),
+    (['1', 's'], '₁', "subscript one"),
+    (['2', 's'], '₂', "subscript two"),
+    (['3', 's'], '₃', "subscript three"),
+    (['4', 's'], '₄', "subscript four"),
+    (['5', 's'], '₅', "subscript five"),
+    (['6', 's'], '₆', "subscript six"),
+    (['7', 's'], '₇', "subscript seven"),
+    (['8', 's'], '₈', "subscript eight"),
+    (['9', 's'], '₉', "subscript nine"),
+    (['<', '-'], '←', "leftwards arrow"),
+    (['-', '>'], '→', "rightwards arrow"),
+    (['-', '!'], '↑', "upwards arrow"),
+    (['-', 'v'], '↓', "downwards arrow"),
+    (['<', '>'], '↔', "left right arrow"),
+    (['U', 'D'], '↕', "up down arrow"),
+    (['<', '='], '⇐', "leftwards double arrow"),
+    (['=', '>'], '⇒', "rightwards double arrow"),
+    (['=', '='], '⇔', "left right double arrow"),
+    (['F', 'A'], '∀', "for all"),
+    (['d', 'P'], '∂', "partial differential"),
+    (['T', 'E'], '∃', "there exists"),
+    (['/', '0'], '∅', "empty set"),
+    (['D', 'E'], '∆', "increment"),
+    (['N', 'B'], '∇', "nabla"),
+    (['(', '-'], '∈', "element of"),
+    (['-', ')'], '∋', "contains as member"),
+    (['*', 'P'], '∏', "n-ary product"),
+    (['+', 'Z'], '∑', "n-ary summation"),
+    (['-', '2'], '−', "minus sign"),
+    (['-', '+'], '∓', "minus-or-plus sign"),
+    (['*', '-'], '∗', "asterisk operator"),
+    (['O', 'b'], '∘', "ring operator"),
+    (['S', 'b'], '∙', "bullet operator"),
+    (['R', 'T'], '√', "square root"),
+    (['0', '('], '∝', "proportional to"),
+    (['0', '0'], '∞', "infinity"),
+    (['-', 'L'], '∟', "right angle"),
+    (['-', 'V'], '∠', "angle"),
+    (['P', 'P'], '∥', "parallel to"),
+    (['A', 'N'], '∧', "logical and"),
+    (['O', 'R'], '∨', "logical or"),
+    (['(', 'U'], '∩', "intersection"),
+    ([')', 'U'], '∪', "union"),
+    (['I', 'n'], '∫', "integral"),
+    (['D', 'I'], '∬', "double integral"),
+    (['I', 'o'], '∮', "contour integral"),
+    (['.', ':'], '∴', "therefore"),
+    ([':', '.'], '∵', "because"),
+    ([':', 'R'], '∶', "ratio"),
+    ([':', ':'], '∷', "proportion"),
+    (['?', '1'], '∼', "tilde operator"),
+    (['C', 'G'], '∾', "inverted lazy s"),
+    (['?', '-'], '≃', "asymptotically equal to"),
+    (['?', '='], '≅', "approximately equal to"),
+    (['?', '2'], '≈', "almost equal to"),
+    (['=', '?'], '≌', "all equal to"),
+    (['H', 'I'], '≓', "image of or approximately equal to"),
+    (['!', '='], '≠', "not equal to"),
+    (['=', '3'], '≡', "identical to"),
+    (['=', '<'], '≤', "less-than or equal to"),
+    (['>', '='], '≥', "greater-than or equal to"),
+    (['<', '*'], '≪', "much less-than"),
+    (['*', '>'], '≫', "much greater-than"),
+    (['!', '<'], '≮', "not less-than"),
+    (['!', '>'], '≯', "not greater-than"),
+    (['(', 'C'], '⊂', "subset of"),
+    ([')', 'C'], '⊃', "superset of"),
+    (['(', '_'], '⊆', "subset of or equal to"),
+    ([')', '_'], '⊇', "superset of or equal to"),
+    (['0', '.'], '⊙', "circled dot operator"),
+    (['0', '2'], '⊚', "circled ring operator"),
+    (['-', 'T'], '⊥', "up tack"),
+    (['.', 'P'], '⋅', "dot operator"),
+    ([':', '3'], '⋮', "vertical ellipsis"),
+    (['O', 'K'], '✓', "check mark"),
+    (['X', 'X'], '✗', "ballot x"),
+    (['c', 'H'], '♥', "black heart suit"),
+    (['c', 'S'], '♠', "black spade suit"),
+    (['c', 'D'], '♦', "black diamond suit"),
+    (['c', 'C'], '♣', "black club suit"),
+    (['*', '2'], '★', "black star"),
+    (['*', '1'], '☆', "white star"),
+    (['M', 'd'], '♩', "quarter note"),
+    (['M', '8'], '♪', "eighth note"),
+    (['h', 'h'], '─', "box drawings light horizontal"),
+    (['H', 'H'], '━', "box drawings heavy horizontal"),
+    (['v', 'v'], '│', "box drawings light vertical"),
+    (['V', 'V'], '┃', "box drawings heavy vertical"),
+    (['d', 'r'], '┌', "box drawings light down and right"),
+    (['d', 'l'], '┐', "box drawings light down and left"),
+    (['u', 'r'], '└', "box drawings light up and right"),
+    (['u', 'l'], '┘', "box drawings light up and left"),
+    (['v', 'r'], '├', "box drawings light vertical and right"),
+    (['v', 'l'], '┤', "box drawings light vertical and left"),
+    (['d', 'h'], '┬', "box drawings light down and horizontal"),
+    (['u', 'h'], '┴', "box drawings light up and horizontal"),
+    (['v', 'h'], '┼', "box drawings light vertical and horizontal"),
+    (['D', 'R'], '┏', "box drawings heavy down and right"),
+    (['D', 'L'], '┓', "box drawings heavy down and left"),
+    (['U', 'R'], '┗', "box drawings heavy up and right"),
+    (['U', 'L'], '┛', "box drawings heavy up and left"),
+    (['V', 'R'], '┣', "box drawings heavy vertical and right"),
+    (['V', 'L'], '┫', "box drawings heavy vertical and left"),
+    (['D', 'H'], '┳', "box drawings heavy down and horizontal"),
+    (['U', 'H'], '┻', "box drawings heavy up and horizontal"),
+    (['V', 'H'], '╋', "box drawings heavy vertical and horizontal"),
+    (['F', 'B'], '█', "full block"),
+    (['T', 'B'], '▀', "upper half block"),
+    (['L', 'B'], '▄', "lower half block"),
+    (['l', 'B'], '▌', "left half block"),
+    (['R', 'B'], '▐', "right half block"),
+    (['.', 'S'], '░', "light shade"),
+    ([':', 'S'], '▒', "medium shade"),
+    (['?', 'S'], '▓', "dark shade"),
+    (['O', 'S'], '□', "white square"),
+    (['f', 'S'], '■', "black square"),
+    (['D', 'b'], '◆', "black diamond"),
+    (['D', 'w'], '◇', "white diamond"),
+    (['0', 'm'], '○', "white circle"),
+    (['0', 'M'], '●', "black circle"),
+    (['U', 'T'], '▲', "black up-pointing triangle"),
+    (['u', 'T'], '△', "white up-pointing triangle"),
+    (['D', 't'], '▼', "black down-pointing triangle"),
+    (['d', 'T'], '▽', "white down-pointing triangle"),
+    (['P', 'R'], '▶', "black right-pointing triangle"),
+    (['P', 'L'], '◀', "black left-pointing triangle"),
+];
+
+/// Returns the character of the digraph `first` `second`. Like in Vim, the characters can also
+/// be typed in reverse order if that isn't a digraph itself.
+pub fn lookup(first: char, second: char) -> Option<char> {
+    let find = |keys: [char; 2]| {
+        DIGRAPHS
+            .iter()
+            .find(|(digraph, _, _)| *digraph == keys)
+            .map(|(_, ch, _)| *ch)
+    };
+    find([first, second]).or_else(|| find([second, first]))
+}
+
+/// Returns the character with the Unicode name `name`, ignoring case, if it is in the digraph
+/// table.
+pub fn by_name(name: &str) -> Option<char> {
+    DIGRAPHS
+        .iter()
+        .find(|(_, _, digraph_name)| digraph_name.eq_ignore_ascii_case(name))
+        .map(|(_, ch, _)| *ch)
+}
+
+/// Parses a codepoint written as hexadecimal digits, optionally prefixed by `U+` or `0x`, like
+/// `U+03BB` or `3bb`.
+pub fn parse_codepoint(codepoint: &str) -> Option<char> {
+    let digits = ["U+", "u+", "0x", "0X"]
+        .iter()
+        .find_map(|prefix| codepoint.strip_prefix(prefix))
+        .unwrap_or(codepoint);
+    if digits.is_empty() || digits.len() > 8 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(digits, 16)
+        .ok()
+        .and_then(char::from_u32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn digraphs() {
+        assert_eq!(lookup('l', '*'), Some('λ'));
+        assert_eq!(lookup('*', 'l'), Some('λ'));
+        assert_eq!(lookup('e', ':'), Some('ë'));
+        assert_eq!(lookup('-', '>'), Some('→'));
+        assert_eq!(lookup('<', '-'), Some('←'));
+        assert_eq!(lookup('v', 'h'), Some('┼'));
+        assert_eq!(lookup('q', 'q'), None);
+        assert_eq!(by_name("Greek Small Letter Lamda"), Some('λ'));
+        assert_eq!(parse_codepoint("U+03BB"), Some('λ'));
+        assert_eq!(parse_codepoint("2500"), Some('─'));
+        assert_eq!(parse_codepoint("d800"), None);
+        assert_eq!(parse_codepoint("U+"), None);
+
+        // every digraph is unique
+        for (i, (keys, _, _)) in DIGRAPHS.iter().enumerate() {
+            assert!(!DIGRAPHS[..i].iter().any(|(other, _, _)| other == keys));
+        }
+    }
+}
@@ -176,6 +176,7 @@ pub struct DocumentFormatter<'t> {
 
     /// The visual position at the end of the last yielded word boundary
     visual_pos: Position,
+    text: RopeSlice<'t>,
     graphemes: RopeGraphemes<'t>,
     /// The character pos of the `graphemes` iter used for inserting annotations
     char_pos: usize,
@@ -212,14 +213,20 @@ pub fn new_at_prev_checkpoint(
         char_idx: usize,
     ) -> Self {
         // TODO divide long lines into blocks to avoid bad performance for long lines
-        let block_line_idx = text.char_to_line(char_idx.min(text.len_chars()));
-        let block_char_idx = text.line_to_char(block_line_idx);
+        let mut block_line_idx = text.char_to_line(char_idx.min(text.len_chars()));
+        let mut block_char_idx = text.line_to_char(block_line_idx);
+        // hidden lines are rendered as part of the header line of their fold
+        if let Some(fold) = annotations.fold_containing(block_char_idx) {
+            block_line_idx = text.char_to_line(fold.start).saturating_sub(1);
+            block_char_idx = text.line_to_char(block_line_idx);
+        }
         annotations.reset_pos(block_char_idx);
 
         DocumentFormatter {
             text_fmt,
             annotations,
             visual_pos: Position { row: 0, col: 0 },
+            text,
             graphemes: text.slice(block_char_idx..).graphemes(),
             char_pos: block_char_idx,
             exhausted: false,
@@ -263,7 +270,7 @@ fn advance_grapheme(&mut self, col: usize, char_pos: usize) -> Option<GraphemeWi
             if let Some((grapheme, highlight)) = self.next_inline_annotation_grapheme(char_pos) {
                 (grapheme.into(), GraphemeSource::VirtualText { highlight })
             } else if let Some(grapheme) = self.graphemes.next() {
-                let codepoints = grapheme.len_chars() as u32;
+                let mut codepoints = grapheme.len_chars() as u32;
 
                 let overlay = self.annotations.overlay_at(char_pos);
                 let grapheme = match overlay {
@@ -271,6 +278,16 @@ fn advance_grapheme(&mut self, col: usize, char_pos: usize) -> Option<GraphemeWi
                     None => Cow::from(grapheme).into(),
                 };
 
+                // the line ending of a fold header also spans all hidden lines
+                let line_end = char_pos + codepoints as usize;
+                if let Some(fold) = self.annotations.fold_starting_at(line_end) {
+                    if fold.end <= self.text.len_chars() {
+                        codepoints += (fold.end - fold.start) as u32;
+                        self.graphemes = self.text.slice(fold.end..).graphemes();
+                        self.annotations.skip_folded(fold.end);
+                    }
+                }
+
                 (grapheme, GraphemeSource::Document { codepoints })
             } else {
                 if self.exhausted {
@@ -469,7 +486,11 @@ fn next(&mut self) -> Option<Self::Item> {
             self.visual_pos.row += 1 + virtual_lines;
             self.visual_pos.col = 0;
             if !grapheme.is_virtual() {
-                self.line_pos += 1;
+                if self.annotations.has_folds() {
+                    self.line_pos = self.text.char_to_line(self.char_pos);
+                } else {
+                    self.line_pos += 1;
+                }
             }
         } else {
             self.visual_pos.col += grapheme.width();
@@ -1,5 +1,5 @@
 use crate::doc_formatter::{DocumentFormatter, TextFormat};
-use crate::text_annotations::{InlineAnnotation, Overlay, TextAnnotations};
+use crate::text_annotations::{Fold, InlineAnnotation, Overlay, TextAnnotations};
 
 impl TextFormat {
     fn new_test(softwrap: bool) -> Self {
@@ -211,3 +211,59 @@ fn annotation_and_overlay() {
         "fooo  bar "
     );
 }
+
+fn fold_text(text: &str, char_pos: usize, softwrap: bool, folds: &[Fold]) -> String {
+    DocumentFormatter::new_at_prev_checkpoint(
+        text.into(),
+        &TextFormat::new_test(softwrap),
+        TextAnnotations::default().set_folds(folds),
+        char_pos,
+    )
+    .collect_to_str()
+}
+
+#[test]
+fn fold() {
+    let folds = [Fold::new(2, 6)];
+    assert_eq!(fold_text("a\nb\nc\nd\n", 0, false, &folds), "a \nd \n ");
+    assert_eq!(fold_text("a\nb\nc\nd\n", 0, true, &folds), "a \nd \n ");
+    // starting inside of a fold starts at the fold header
+    assert_eq!(fold_text("a\nb\nc\nd\n", 4, false, &folds), "a \nd \n ");
+    // folding the end of the document
+    assert_eq!(fold_text("a\nb\nc", 0, false, &[Fold::new(2, 5)]), "a \n ");
+}
+
+#[test]
+fn fold_skips_annotations() {
+    let folds = [Fold::new(2, 6)];
+    let annotations = [
+        InlineAnnotation::new(1, "x"),
+        InlineAnnotation::new(3, "y"),
+        InlineAnnotation::new(6, "z"),
+    ];
+    let mut annotations_with_folds = TextAnnotations::default();
+    annotations_with_folds
+        .add_inline_annotations(&annotations, None)
+        .set_folds(&folds);
+    let text_fmt = TextFormat::new_test(false);
+    let lines: Vec<_> = DocumentFormatter::new_at_prev_checkpoint(
+        "a\nb\nc\nd\n".into(),
+        &text_fmt,
+        &annotations_with_folds,
+        0,
+    )
+    .filter(|grapheme| !grapheme.is_virtual())
+    .map(|grapheme| (grapheme.char_idx, grapheme.line_idx))
+    .collect();
+    assert_eq!(lines, [(0, 0), (1, 0), (6, 3), (7, 3), (8, 4)]);
+    assert_eq!(
+        DocumentFormatter::new_at_prev_checkpoint(
+            "a\nb\nc\nd\n".into(),
+            &text_fmt,
+            &annotations_with_folds,
+            0,
+        )
+        .collect_to_str(),
+        "ax \nzd \n "
+    );
+}
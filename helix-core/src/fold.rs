@@ -0,0 +1,364 @@
+//! Folding hides ranges of lines below a visible header line.
+//!
+//! A [`FoldMap`] stores the folds of a single `(Document, View)` combo as a
+//! sorted list of non-overlapping [`Fold`]s. The folds are consumed by the
+//! [`DocumentFormatter`](crate::doc_formatter::DocumentFormatter) through
+//! [`TextAnnotations::set_folds`](crate::text_annotations::TextAnnotations::set_folds)
+//! which means that rendering and all visual positioning automatically skip
+//! folded text.
+
+use helix_stdx::rope::RopeSliceExt;
+
+use crate::indent::indent_level_for_line;
+use crate::line_ending::line_end_char_index;
+use crate::syntax::Loader;
+use crate::text_annotations::{Fold, InlineAnnotation};
+use crate::{Assoc, ChangeSet, RopeSlice, Syntax};
+
+/// The virtual text displayed at the end of the header line of a fold.
+pub const FOLD_MARKER: &str = " ⋯";
+
+#[derive(Debug, Clone, Default)]
+pub struct FoldMap {
+    folds: Vec<Fold>,
+    /// Virtual text markers placed at the line ending of each fold header.
+    /// Kept in sync with `folds`.
+    markers: Vec<InlineAnnotation>,
+}
+
+impl FoldMap {
+    pub fn is_empty(&self) -> bool {
+        self.folds.is_empty()
+    }
+
+    pub fn folds(&self) -> &[Fold] {
+        &self.folds
+    }
+
+    pub fn markers(&self) -> &[InlineAnnotation] {
+        &self.markers
+    }
+
+    pub fn clear(&mut self) {
+        self.folds.clear();
+        self.markers.clear();
+    }
+
+    /// Returns the fold whose header is `line` or which hides `line`.
+    pub fn fold_at_line(&self, text: RopeSlice, line: usize) -> Option<Fold> {
+        if line + 1 >= text.len_lines() {
+            return self.fold_hiding(text.line_to_char(line));
+        }
+        let next_line_start = text.line_to_char(line + 1);
+        self.folds
+            .iter()
+            .find(|fold| fold.start == next_line_start || fold.contains(text.line_to_char(line)))
+            .copied()
+    }
+
+    /// Returns the fold hiding `char_idx`.
+    pub fn fold_hiding(&self, char_idx: usize) -> Option<Fold> {
+        let i = self.folds.partition_point(|fold| fold.end <= char_idx);
+        self.folds
+            .get(i)
+            .filter(|fold| fold.contains(char_idx))
+            .copied()
+    }
+
+    /// Folds the lines `header + 1..=last` below the `header` line.
+    ///
+    /// Folds that overlap the new fold or whose header would be hidden by it
+    /// are merged into the new fold. Returns `false` if nothing changed.
+    pub fn fold_lines(&mut self, text: RopeSlice, header: usize, last: usize) -> bool {
+        let last = last.min(text.len_lines() - 1);
+        if last <= header {
+            return false;
+        }
+        let end = if last + 1 >= text.len_lines() {
+            text.len_chars()
+        } else {
+            text.line_to_char(last + 1)
+        };
+        self.insert(text, Fold::new(text.line_to_char(header + 1), end))
+    }
+
+    fn insert(&mut self, text: RopeSlice, mut fold: Fold) -> bool {
+        if self
+            .folds
+            .iter()
+            .any(|existing| existing.start <= fold.start && fold.end <= existing.end)
+        {
+            return false;
+        }
+        // overlapping or touching folds hide each others headers so they are merged
+        self.folds.retain(|existing| {
+            let overlaps = existing.start <= fold.end && fold.start <= existing.end;
+            if overlaps {
+                fold.start = fold.start.min(existing.start);
+                fold.end = fold.end.max(existing.end);
+            }
+            !overlaps
+        });
+        let i = self
+            .folds
+            .partition_point(|existing| existing.start < fold.start);
+        self.folds.insert(i, fold);
+        self.update_markers(text);
+        true
+    }
+
+    /// Removes the fold whose header is `line` or which hides `line`.
+    /// Returns `false` if there was no such fold.
+    pub fn unfold_line(&mut self, text: RopeSlice, line: usize) -> bool {
+        let Some(fold) = self.fold_at_line(text, line) else {
+            return false;
+        };
+        self.folds.retain(|existing| *existing != fold);
+        self.update_markers(text);
+        true
+    }
+
+    /// Removes all folds that hide any of the `positions`.
+    /// Returns `false` if no fold was removed.
+    pub fn reveal(&mut self, text: RopeSlice, positions: impl IntoIterator<Item = usize>) -> bool {
+        let len = self.folds.len();
+        for pos in positions {
+            self.folds.retain(|fold| !fold.contains(pos));
+        }
+        if self.folds.len() == len {
+            return false;
+        }
+        self.update_markers(text);
+        true
+    }
+
+    /// Maps the folds through `changes`. `text` is the text **after** the
+    /// changes were applied.
+    ///
+    /// Folds that contain an edit are removed so that text is never changed
+    /// without being visible.
+    pub fn update(&mut self, text: RopeSlice, changes: &ChangeSet) {
+        if self.folds.is_empty() {
+            return;
+        }
+        for (from, to, _) in changes.changes_iter() {
+            self.folds.retain(|fold| {
+                to < fold.start || fold.end <= from || (from == to && from == fold.end)
+            });
+        }
+        changes.update_positions(self.folds.iter_mut().flat_map(|fold| {
+            [
+                (&mut fold.start, Assoc::After),
+                (&mut fold.end, Assoc::Before),
+            ]
+        }));
+
+        // ensure that the folds still span whole lines
+        let len_chars = text.len_chars();
+        self.folds.retain_mut(|fold| {
+            let start_line = text.char_to_line(fold.start.min(len_chars));
+            if text.line_to_char(start_line) != fold.start {
+                if start_line + 1 >= text.len_lines() {
+                    return false;
+                }
+                fold.start = text.line_to_char(start_line + 1);
+            }
+            if fold.end < len_chars {
+                fold.end = text.line_to_char(text.char_to_line(fold.end));
+            }
+            fold.start != 0 && fold.start < fold.end
+        });
+        self.update_markers(text);
+    }
+
+    fn update_markers(&mut self, text: RopeSlice) {
+        self.markers = self
+            .folds
+            .iter()
+            .map(|fold| {
+                let header = text.char_to_line(fold.start) - 1;
+                InlineAnnotation::new(line_end_char_index(&text, header), FOLD_MARKER)
+            })
+            .collect();
+    }
+}
+
+/// Returns the last line of the indentation based fold below the `header` line:
+/// the block of lines directly below `header` that are indented further than
+/// `header`. Trailing blank lines are not part of the fold.
+pub fn indent_fold(text: RopeSlice, header: usize, tab_width: usize) -> Option<usize> {
+    let line = text.line(header);
+    line.first_non_whitespace_char()?;
+    let indent = indent_level_for_line(line, tab_width, 1);
+    let mut last = header;
+    for line_idx in header + 1..text.len_lines() {
+        let line = text.line(line_idx);
+        if line.first_non_whitespace_char().is_none() {
+            continue;
+        }
+        if indent_level_for_line(line, tab_width, 1) <= indent {
+            break;
+        }
+        last = line_idx;
+    }
+    (last > header).then_some(last)
+}
+
+/// Returns the last line of the largest syntax node that starts on the
+/// `header` line and spans multiple lines.
+pub fn syntax_fold(syntax: &Syntax, text: RopeSlice, header: usize) -> Option<usize> {
+    let line_start = text.line_to_char(header);
+    let start = line_start + text.line(header).first_non_whitespace_char()?;
+    let start_byte = text.char_to_byte(start) as u32;
+    let mut node = syntax.descendant_for_byte_range(start_byte, start_byte)?;
+    let starts_on_header = |byte: u32| text.byte_to_line(byte as usize) == header;
+    while let Some(parent) = node.parent() {
+        // the root node usually starts at the first line
+        if parent.parent().is_none() || !starts_on_header(parent.start_byte()) {
+            break;
+        }
+        node = parent;
+    }
+    let last = node_last_line(text, node.start_byte(), node.end_byte());
+    (last > header).then_some(last)
+}
+
+/// Returns the header and last line of all multi-line nodes captured by the
+/// `folds.scm` queries of the document. Languages without `folds.scm` queries
+/// fold their `class` and `function` text objects instead.
+pub fn syntax_folds(syntax: &Syntax, text: RopeSlice, loader: &Loader) -> Vec<(usize, usize)> {
+    let ranges = match syntax.fold_ranges(text, loader) {
+        Some(ranges) => ranges,
+        None => {
+            let Some(query) = loader.textobject_query(syntax.root_language()) else {
+                return Vec::new();
+            };
+            let root = syntax.tree().root_node();
+            ["class.around", "function.around"]
+                .into_iter()
+                .filter_map(|capture| query.capture_nodes(capture, &root, text))
+                .flatten()
+                .map(|node| node.start_byte() as u32..node.end_byte() as u32)
+                .collect()
+        }
+    };
+    ranges
+        .into_iter()
+        .filter_map(|range| {
+            let header = text.byte_to_line(range.start as usize);
+            let last = node_last_line(text, range.start, range.end);
+            (last > header).then_some((header, last))
+        })
+        .collect()
+}
+
+/// Nodes that include their trailing line ending end at the start of the next line.
+fn node_last_line(text: RopeSlice, start_byte: u32, end_byte: u32) -> usize {
+    let end_byte = (end_byte as usize).min(text.len_bytes());
+    let last_byte = end_byte.saturating_sub(1).max(start_byte as usize);
+    text.byte_to_line(last_byte)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Rope, Transaction};
+
+    fn text() -> Rope {
+        Rope::from("fn a() {\n    1\n    2\n}\nfn b() {\n    3\n}\n")
+    }
+
+    #[test]
+    fn fold_lines() {
+        let text = text();
+        let text = text.slice(..);
+        let mut folds = FoldMap::default();
+        assert!(!folds.fold_lines(text, 1, 1));
+        assert!(folds.fold_lines(text, 0, 3));
+        assert_eq!(folds.folds(), &[Fold::new(9, 23)]);
+        assert_eq!(folds.markers()[0].char_idx, 8);
+        // already folded
+        assert!(!folds.fold_lines(text, 1, 2));
+        assert_eq!(folds.fold_at_line(text, 0), Some(Fold::new(9, 23)));
+        assert_eq!(folds.fold_at_line(text, 2), Some(Fold::new(9, 23)));
+        assert_eq!(folds.fold_at_line(text, 4), None);
+    }
+
+    #[test]
+    fn merge_overlapping() {
+        let text = text();
+        let text = text.slice(..);
+        let mut folds = FoldMap::default();
+        assert!(folds.fold_lines(text, 4, 5));
+        assert!(folds.fold_lines(text, 1, 2));
+        assert_eq!(folds.folds().len(), 2);
+        // hides the header of the second fold
+        assert!(folds.fold_lines(text, 0, 4));
+        assert_eq!(folds.folds(), &[Fold::new(9, 38)]);
+        assert!(folds.unfold_line(text, 3));
+        assert!(folds.is_empty());
+    }
+
+    #[test]
+    fn reveal() {
+        let text = text();
+        let text = text.slice(..);
+        let mut folds = FoldMap::default();
+        folds.fold_lines(text, 0, 3);
+        assert!(!folds.reveal(text, [0, 8, 23]));
+        assert!(folds.reveal(text, [10]));
+        assert!(folds.is_empty());
+    }
+
+    #[test]
+    fn indent() {
+        let text = Rope::from("a:\n  b\n\n    c\n\nd\n  e\n");
+        let text = text.slice(..);
+        assert_eq!(indent_fold(text, 0, 4), Some(3));
+        assert_eq!(indent_fold(text, 1, 4), Some(3));
+        assert_eq!(indent_fold(text, 2, 4), None);
+        assert_eq!(indent_fold(text, 3, 4), None);
+        assert_eq!(indent_fold(text, 5, 4), Some(6));
+    }
+
+    #[test]
+    fn update() {
+        let mut text = text();
+        let mut folds = FoldMap::default();
+        folds.fold_lines(text.slice(..), 4, 5);
+
+        // edits before the fold move it
+        let transaction = Transaction::insert(&text, &crate::Selection::point(0), "// a\n".into());
+        transaction.apply(&mut text);
+        folds.update(text.slice(..), transaction.changes());
+        assert_eq!(folds.folds(), &[Fold::new(37, 43)]);
+        assert_eq!(folds.markers()[0].char_idx, 36);
+
+        // edits inside of the fold open it
+        let transaction = Transaction::insert(&text, &crate::Selection::point(39), "x".into());
+        transaction.apply(&mut text);
+        folds.update(text.slice(..), transaction.changes());
+        assert!(folds.is_empty());
+    }
+
+    #[test]
+    fn syntax_folds_use_fold_queries() {
+        let loader = crate::config::default_lang_loader();
+
+        // lua has a folds.scm query, single-line nodes don't fold
+        let text = Rope::from("function a()\n  local t = {\n    1,\n  }\nend\nlocal b = { 2 }\n");
+        let text = text.slice(..);
+        let language = loader.language_for_name("lua").unwrap();
+        let syntax = Syntax::new(text, language, &loader).unwrap();
+        let mut folds = syntax_folds(&syntax, text, &loader);
+        folds.sort_unstable();
+        assert_eq!(folds, vec![(0, 4), (1, 3)]);
+
+        // rust has no folds.scm query and folds its function text objects
+        let text = text();
+        let text = text.slice(..);
+        let language = loader.language_for_name("rust").unwrap();
+        let syntax = Syntax::new(text, language, &loader).unwrap();
+        assert_eq!(syntax_folds(&syntax, text, &loader), vec![(0, 3), (4, 6)]);
+    }
+}
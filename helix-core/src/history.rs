@@ -1,6 +1,7 @@
-use crate::{Assoc, ChangeSet, Range, Rope, Selection, Transaction};
+use crate::{Assoc, ChangeSet, Operation, Range, Rope, Selection, Transaction};
 use once_cell::sync::Lazy;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::num::NonZeroUsize;
 use std::time::{Duration, Instant};
 
@@ -53,6 +54,107 @@ pub struct History {
     current: usize,
 }
 
+/// A summary of a revision for listing the history, see [History::revisions].
+#[derive(Debug, Clone)]
+pub struct RevisionSummary {
+    pub revision: usize,
+    pub parent: usize,
+    pub timestamp: Instant,
+    /// Whether the revision is the current revision or one of its ancestors, so that it can be
+    /// reached with undo.
+    pub is_ancestor: bool,
+    /// The number of chars inserted and deleted by the revision.
+    pub inserted: usize,
+    pub deleted: usize,
+    /// The first line of the first text inserted by the revision.
+    pub excerpt: String,
+}
+
+/// A [History] in a form that can be saved to disk so that undo survives restarts, see
+/// [History::to_persistent] and [History::from_persistent]. Timestamps are stored as the age of
+/// the revisions at the time the history was saved.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PersistentHistory {
+    revisions: Vec<PersistentRevision>,
+    current: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct PersistentRevision {
+    parent: usize,
+    last_child: Option<usize>,
+    transaction: PersistentTransaction,
+    inversion: PersistentTransaction,
+    age_secs: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct PersistentTransaction {
+    changes: Vec<PersistentOperation>,
+    /// The `(anchor, head)` pairs of the ranges and the index of the primary range.
+    selection: Option<(Vec<(usize, usize)>, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum PersistentOperation {
+    Retain(usize),
+    Delete(usize),
+    Insert(String),
+}
+
+impl From<&Transaction> for PersistentTransaction {
+    fn from(transaction: &Transaction) -> Self {
+        let changes = transaction
+            .changes()
+            .changes()
+            .iter()
+            .map(|operation| match operation {
+                Operation::Retain(n) => PersistentOperation::Retain(*n),
+                Operation::Delete(n) => PersistentOperation::Delete(*n),
+                Operation::Insert(text) => PersistentOperation::Insert(text.to_string()),
+            })
+            .collect();
+        let selection = transaction.selection().map(|selection| {
+            let ranges = selection
+                .ranges()
+                .iter()
+                .map(|range| (range.anchor, range.head))
+                .collect();
+            (ranges, selection.primary_index())
+        });
+        Self { changes, selection }
+    }
+}
+
+impl PersistentTransaction {
+    fn to_transaction(&self) -> Option<Transaction> {
+        let mut changes = ChangeSet::with_capacity(self.changes.len());
+        for operation in &self.changes {
+            match operation {
+                PersistentOperation::Retain(n) => changes.retain(*n),
+                PersistentOperation::Delete(n) => changes.delete(*n),
+                PersistentOperation::Insert(text) => changes.insert(text.as_str().into()),
+            }
+        }
+        let mut transaction = Transaction::from(changes);
+        if let Some((ranges, primary)) = &self.selection {
+            if *primary >= ranges.len() {
+                return None;
+            }
+            let ranges = ranges
+                .iter()
+                .map(|&(anchor, head)| Range::new(anchor, head))
+                .collect();
+            transaction = transaction.with_selection(Selection::new(ranges, *primary));
+        }
+        Some(transaction)
+    }
+}
+
 /// A single point in history. See [History] for more information.
 #[derive(Debug, Clone)]
 struct Revision {
@@ -119,6 +221,119 @@ pub const fn at_root(&self) -> bool {
         self.current == 0
     }
 
+    /// Converts the history to a form that can be serialized.
+    pub fn to_persistent(&self) -> PersistentHistory {
+        let revisions = self
+            .revisions
+            .iter()
+            .map(|revision| PersistentRevision {
+                parent: revision.parent,
+                last_child: revision.last_child.map(NonZeroUsize::get),
+                transaction: (&revision.transaction).into(),
+                inversion: (&revision.inversion).into(),
+                age_secs: revision.timestamp.elapsed().as_secs(),
+            })
+            .collect();
+        PersistentHistory {
+            revisions,
+            current: self.current,
+        }
+    }
+
+    /// Restores a history saved with [History::to_persistent]. Returns `None` if the revisions
+    /// don't form a valid history. The caller is responsible for checking that the current
+    /// revision matches the text of the document.
+    pub fn from_persistent(history: PersistentHistory) -> Option<Self> {
+        let now = Instant::now();
+        let count = history.revisions.len();
+        if history.current >= count {
+            return None;
+        }
+        let mut revisions: Vec<Revision> = Vec::with_capacity(count);
+        for (i, revision) in history.revisions.into_iter().enumerate() {
+            let last_child = match revision.last_child {
+                Some(child) if child <= i || child >= count => return None,
+                child => child.and_then(NonZeroUsize::new),
+            };
+            let transaction = revision.transaction.to_transaction()?;
+            let inversion = revision.inversion.to_transaction()?;
+            if i == 0 {
+                if revision.parent != 0 {
+                    return None;
+                }
+            } else {
+                let parent = revisions.get(revision.parent)?;
+                let (changes, inverted) = (transaction.changes(), inversion.changes());
+                if changes.len != inverted.len_after
+                    || changes.len_after != inverted.len
+                    || (revision.parent != 0
+                        && changes.len != parent.transaction.changes().len_after)
+                {
+                    return None;
+                }
+            }
+            // timestamps must not decrease for the binary search of `jump_instant`
+            let timestamp = now
+                .checked_sub(Duration::from_secs(revision.age_secs))
+                .unwrap_or(now);
+            let timestamp = revisions
+                .last()
+                .map_or(timestamp, |prev| timestamp.max(prev.timestamp));
+            revisions.push(Revision {
+                parent: revision.parent,
+                last_child,
+                transaction,
+                inversion,
+                timestamp,
+            });
+        }
+        Some(Self {
+            revisions,
+            current: history.current,
+        })
+    }
+
+    /// Returns a summary of every revision in the order they were committed, starting with the
+    /// root revision.
+    pub fn revisions(&self) -> Vec<RevisionSummary> {
+        let mut ancestors = self.path_up(self.current, 0);
+        ancestors.push(0);
+        self.revisions
+            .iter()
+            .enumerate()
+            .map(|(i, revision)| {
+                let mut inserted = 0;
+                let mut deleted = 0;
+                let mut excerpt = None;
+                if i != 0 {
+                    for (from, to, fragment) in revision.transaction.changes_iter() {
+                        deleted += to - from;
+                        if let Some(fragment) = fragment {
+                            inserted += fragment.chars().count();
+                            excerpt.get_or_insert_with(|| {
+                                fragment
+                                    .trim_start()
+                                    .lines()
+                                    .next()
+                                    .unwrap_or("")
+                                    .to_string()
+                            });
+                        }
+                    }
+                }
+                RevisionSummary {
+                    revision: i,
+                    parent: revision.parent,
+                    timestamp: revision.timestamp,
+                    is_ancestor: ancestors.contains(&i),
+                    inserted,
+                    deleted,
+                    excerpt: excerpt.unwrap_or_default(),
+                }
+            })
+            .collect()
+    }
+
     /// Returns the changes since the given revision composed into a transaction.
     /// Returns None if there are no changes between the current and given revisions.
     pub fn changes_since(&self, revision: usize) -> Option<Transaction> {
@@ -223,6 +438,12 @@ fn jump_to(&mut self, to: usize) -> Vec<Transaction> {
         up_txns.chain(down_txns).collect()
     }
 
+    /// Creates the [`Transaction`]s that jump to `revision`, which may be on another branch of
+    /// the history.
+    pub fn jump_to_revision(&mut self, revision: usize) -> Vec<Transaction> {
+        self.jump_to(revision.min(self.revisions.len() - 1))
+    }
+
     /// Creates a [`Transaction`] that will undo `delta` revisions.
     fn jump_backward(&mut self, delta: usize) -> Vec<Transaction> {
         self.jump_to(self.current.saturating_sub(delta))
@@ -630,4 +851,86 @@ fn test_parse_undo_kind() {
             Err("duration too large".to_string())
         );
     }
+
+    #[test]
+    fn test_revisions_and_branches() {
+        let mut history = History::default();
+        let mut state = State {
+            doc: Rope::from("a"),
+            selection: Selection::point(0),
+        };
+        fn commit(history: &mut History, state: &mut State, change: (usize, usize, &str)) {
+            let (from, to, text) = change;
+            let transaction =
+                Transaction::change(&state.doc, [(from, to, Some(text.into()))].into_iter());
+            history.commit_revision(&transaction, state);
+            transaction.apply(&mut state.doc);
+        }
+
+        commit(&mut history, &mut state, (1, 1, "b"));
+        commit(&mut history, &mut state, (2, 2, "c"));
+        assert_eq!("abc", state.doc);
+        // undo "c" and start a new branch
+        history.undo().unwrap().apply(&mut state.doc);
+        commit(&mut history, &mut state, (0, 1, "xy\nz"));
+        assert_eq!("xy\nzb", state.doc);
+
+        let revisions = history.revisions();
+        assert_eq!(revisions.len(), 4);
+        let parents: Vec<_> = revisions.iter().map(|rev| rev.parent).collect();
+        assert_eq!(parents, [0, 0, 1, 1]);
+        let ancestors: Vec<_> = revisions.iter().map(|rev| rev.is_ancestor).collect();
+        assert_eq!(ancestors, [true, true, false, true]);
+        assert_eq!((revisions[3].inserted, revisions[3].deleted), (4, 1));
+        assert_eq!(revisions[3].excerpt, "xy");
+
+        // jump to the other branch
+        for transaction in history.jump_to_revision(2) {
+            transaction.apply(&mut state.doc);
+        }
+        assert_eq!("abc", state.doc);
+        assert_eq!(history.current_revision(), 2);
+        for transaction in history.jump_to_revision(0) {
+            transaction.apply(&mut state.doc);
+        }
+        assert_eq!("a", state.doc);
+    }
+
+    #[test]
+    fn test_persistent_history() {
+        let mut history = History::default();
+        let mut state = State {
+            doc: Rope::from("hello"),
+            selection: Selection::point(0),
+        };
+        let transaction =
+            Transaction::change(&state.doc, [(5, 5, Some(" world".into()))].into_iter());
+        history.commit_revision(&transaction, &state);
+        transaction.apply(&mut state.doc);
+        let transaction = Transaction::change(&state.doc, [(0, 1, Some("H".into()))].into_iter());
+        history.commit_revision(&transaction, &state);
+        transaction.apply(&mut state.doc);
+
+        let json = serde_json::to_string(&history.to_persistent()).unwrap();
+        let persistent: PersistentHistory = serde_json::from_str(&json).unwrap();
+        let mut restored = History::from_persistent(persistent.clone()).unwrap();
+        assert_eq!(restored.current_revision(), 2);
+        assert_eq!(restored.to_persistent(), persistent);
+
+        restored.undo().unwrap().apply(&mut state.doc);
+        assert_eq!("hello world", state.doc);
+        restored.undo().unwrap().apply(&mut state.doc);
+        assert_eq!("hello", state.doc);
+        assert!(restored.undo().is_none());
+        restored.redo().unwrap().apply(&mut state.doc);
+        assert_eq!("hello world", state.doc);
+
+        // revisions that don't fit together are rejected
+        let mut invalid = persistent.clone();
+        invalid.revisions[2].transaction = invalid.revisions[1].transaction.clone();
+        assert!(History::from_persistent(invalid).is_none());
+        let mut invalid = persistent;
+        invalid.current = 3;
+        assert!(History::from_persistent(invalid).is_none());
+    }
 }
@@ -1,6 +1,7 @@
 use std::{
     borrow::Cow,
     collections::{HashMap, HashSet},
+    ops::RangeInclusive,
 };
 
 use helix_stdx::rope::RopeSliceExt;
@@ -210,6 +211,34 @@ pub fn indent_level_for_line(line: RopeSlice, tab_width: usize, indent_width: us
     len / indent_width
 }
 
+/// Returns the indent level of the syntactic scope around `pos` and the lines within the scope,
+/// which is used to highlight the indent guide of the scope of the cursor.
+///
+/// The scope is the smallest syntax node around `pos` that spans multiple lines and doesn't
+/// start on a line that is indented deeper than the line of `pos`.
+pub fn indent_guide_scope(
+    syntax: &Syntax,
+    text: RopeSlice,
+    pos: usize,
+    tab_width: usize,
+    indent_width: usize,
+) -> Option<(usize, RangeInclusive<usize>)> {
+    let indent_level = |line| indent_level_for_line(text.line(line), tab_width, indent_width);
+    let line = text.char_to_line(pos);
+    let byte = text.char_to_byte(pos) as u32;
+    let mut node = syntax.descendant_for_byte_range(byte, byte)?;
+    loop {
+        let start_line = text.byte_to_line(node.start_byte() as usize);
+        let end_byte = (node.end_byte() as usize).min(text.len_bytes());
+        let end_line =
+            text.byte_to_line(end_byte.saturating_sub(1).max(node.start_byte() as usize));
+        if start_line < end_line && indent_level(start_line) <= indent_level(line) {
+            return Some((indent_level(start_line), start_line + 1..=end_line));
+        }
+        node = node.parent()?;
+    }
+}
+
 /// Create a string of tabs & spaces that has the same visual width as the given RopeSlice (independent of the tab width).
 fn whitespace_with_same_width(text: RopeSlice) -> String {
     let mut s = String::new();
@@ -8,9 +8,11 @@
 pub mod completion;
 pub mod config;
 pub mod diagnostic;
+pub mod digraph;
 pub mod diff;
 pub mod doc_formatter;
 pub mod editor_config;
+pub mod fold;
 pub mod fuzzy;
 pub mod graphemes;
 pub mod history;
@@ -19,12 +21,15 @@
 pub mod line_ending;
 pub mod macros;
 pub mod match_brackets;
+pub mod merge_conflict;
 pub mod movement;
 pub mod object;
 mod position;
 pub mod search;
 pub mod selection;
 pub mod snippets;
+pub mod spelling;
+pub mod structural_search;
 pub mod surround;
 pub mod syntax;
 pub mod test;
@@ -0,0 +1,199 @@
+//! Detection and resolution of the conflicts that version control tools write into files when a
+//! merge fails:
+//!
+//! ```text
+//! <<<<<<< ours
+//! our lines
+//! ||||||| base
+//! the lines of the common ancestor, only in the diff3 style
+//! =======
+//! their lines
+//! >>>>>>> theirs
+//! ```
+
+use std::ops::Range;
+
+use crate::{Rope, RopeSlice, Tendril, Transaction};
+
+const OURS_MARKER: &str = "<<<<<<<";
+const BASE_MARKER: &str = "|||||||";
+const SEPARATOR_MARKER: &str = "=======";
+const THEIRS_MARKER: &str = ">>>>>>>";
+
+/// The lines of the markers of a conflict.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MergeConflict {
+    /// The line of the `<<<<<<<` marker.
+    pub start: usize,
+    /// The line of the `|||||||` marker if the conflict includes the common ancestor.
+    pub base: Option<usize>,
+    /// The line of the `=======` marker.
+    pub separator: usize,
+    /// The line of the `>>>>>>>` marker.
+    pub end: usize,
+}
+
+impl MergeConflict {
+    pub fn ours(&self) -> Range<usize> {
+        self.start + 1..self.base.unwrap_or(self.separator)
+    }
+
+    pub fn base_lines(&self) -> Option<Range<usize>> {
+        self.base.map(|base| base + 1..self.separator)
+    }
+
+    pub fn theirs(&self) -> Range<usize> {
+        self.separator + 1..self.end
+    }
+
+    /// The lines of the markers.
+    pub fn markers(&self) -> impl Iterator<Item = usize> {
+        [
+            Some(self.start),
+            self.base,
+            Some(self.separator),
+            Some(self.end),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    pub fn contains_line(&self, line: usize) -> bool {
+        (self.start..=self.end).contains(&line)
+    }
+}
+
+/// Which side of a conflict is kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Ours,
+    Theirs,
+    /// Our lines followed by their lines.
+    Both,
+}
+
+fn is_marker(line: RopeSlice, marker: &str) -> bool {
+    let mut chars = line.chars();
+    marker.chars().all(|c| chars.next() == Some(c))
+        && chars.next().is_none_or(|c| c.is_whitespace())
+}
+
+/// Finds the conflicts in the text in ascending order. Markers that aren't part of a complete
+/// conflict are ignored.
+pub fn find_conflicts(text: RopeSlice) -> Vec<MergeConflict> {
+    let mut conflicts = Vec::new();
+    let mut start = None;
+    let mut base = None;
+    let mut separator = None;
+    for (i, line) in text.lines().enumerate() {
+        if is_marker(line, OURS_MARKER) {
+            start = Some(i);
+            base = None;
+            separator = None;
+        } else if start.is_none() {
+            continue;
+        } else if is_marker(line, BASE_MARKER) && separator.is_none() {
+            base = Some(i);
+        } else if is_marker(line, SEPARATOR_MARKER) && separator.is_none() {
+            separator = Some(i);
+        } else if is_marker(line, THEIRS_MARKER) {
+            if let (Some(start), Some(separator)) = (start, separator) {
+                conflicts.push(MergeConflict {
+                    start,
+                    base,
+                    separator,
+                    end: i,
+                });
+            }
+            start = None;
+        }
+    }
+    conflicts
+}
+
+/// Replaces the conflict, including its markers, with the kept lines.
+pub fn resolve(text: &Rope, conflict: &MergeConflict, resolution: Resolution) -> Transaction {
+    let lines = |range: Range<usize>| {
+        text.slice(text.line_to_char(range.start)..text.line_to_char(range.end))
+    };
+    let mut kept = Tendril::new();
+    if matches!(resolution, Resolution::Ours | Resolution::Both) {
+        for chunk in lines(conflict.ours()).chunks() {
+            kept.push_str(chunk);
+        }
+    }
+    if matches!(resolution, Resolution::Theirs | Resolution::Both) {
+        for chunk in lines(conflict.theirs()).chunks() {
+            kept.push_str(chunk);
+        }
+    }
+    let from = text.line_to_char(conflict.start);
+    let to = text.line_to_char((conflict.end + 1).min(text.len_lines()));
+    Transaction::change(text, [(from, to, Some(kept))].into_iter())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "a
+<<<<<<< HEAD
+ours
+||||||| base
+base
+=======
+theirs 1
+theirs 2
+>>>>>>> feature
+b
+<<<<<<< HEAD
+=======
+>>>>>>> feature
+";
+
+    #[test]
+    fn find() {
+        let text = Rope::from(TEXT);
+        let conflicts = find_conflicts(text.slice(..));
+        assert_eq!(
+            conflicts,
+            vec![
+                MergeConflict {
+                    start: 1,
+                    base: Some(3),
+                    separator: 5,
+                    end: 8,
+                },
+                MergeConflict {
+                    start: 10,
+                    base: None,
+                    separator: 11,
+                    end: 12,
+                },
+            ]
+        );
+        assert_eq!(conflicts[0].ours(), 2..3);
+        assert_eq!(conflicts[0].base_lines(), Some(4..5));
+        assert_eq!(conflicts[0].theirs(), 6..8);
+
+        // incomplete conflicts and marker-like lines are ignored
+        let text = Rope::from("<<<<<<< HEAD\na\n>>>>>>> x\n<<<<<<<<\n=======\n");
+        assert!(find_conflicts(text.slice(..)).is_empty());
+    }
+
+    #[test]
+    fn resolve_conflict() {
+        let text = Rope::from(TEXT);
+        let conflict = find_conflicts(text.slice(..))[0];
+        let cases = [
+            (Resolution::Ours, "a\nours\nb\n"),
+            (Resolution::Theirs, "a\ntheirs 1\ntheirs 2\nb\n"),
+            (Resolution::Both, "a\nours\ntheirs 1\ntheirs 2\nb\n"),
+        ];
+        for (resolution, expected) in cases {
+            let mut doc = text.clone();
+            resolve(&text, &conflict, resolution).apply(&mut doc);
+            assert!(doc.to_string().starts_with(expected));
+        }
+    }
+}
@@ -10,7 +10,7 @@
         next_grapheme_boundary, nth_next_grapheme_boundary, nth_prev_grapheme_boundary,
         prev_grapheme_boundary,
     },
-    line_ending::rope_is_line_ending,
+    line_ending::{line_end_char_index, rope_is_line_ending},
     position::char_idx_at_visual_block_offset,
     syntax,
     text_annotations::TextAnnotations,
@@ -126,9 +126,13 @@ pub fn move_vertically(
     let line_idx = slice.char_to_line(pos);
 
     // Compute the new position.
-    let mut new_line_idx = match dir {
-        Direction::Forward => line_idx.saturating_add(count),
-        Direction::Backward => line_idx.saturating_sub(count),
+    let mut new_line_idx = if annotations.has_folds() {
+        move_vertically_over_folds(slice, line_idx, dir, count, annotations)
+    } else {
+        match dir {
+            Direction::Forward => line_idx.saturating_add(count),
+            Direction::Backward => line_idx.saturating_sub(count),
+        }
     };
 
     let line = if new_line_idx >= slice.len_lines() - 1 {
@@ -165,6 +169,94 @@ pub fn move_vertically(
     new_range
 }
 
+/// Moves to the first grapheme of the visual (soft-wrapped) line containing the cursor.
+///
+/// Falls back to the start of the document line if soft wrap is disabled.
+pub fn move_to_visual_line_start(
+    slice: RopeSlice,
+    range: Range,
+    behaviour: Movement,
+    text_fmt: &TextFormat,
+    annotations: &mut TextAnnotations,
+) -> Range {
+    let pos = range.cursor(slice);
+    let new_pos = if text_fmt.soft_wrap {
+        annotations.clear_line_annotations();
+        let (visual_pos, block_off) =
+            visual_offset_from_block(slice, pos, pos, text_fmt, annotations);
+        char_idx_at_visual_block_offset(slice, block_off, visual_pos.row, 0, text_fmt, annotations)
+            .0
+    } else {
+        slice.line_to_char(slice.char_to_line(pos))
+    };
+    range.put_cursor(slice, new_pos, behaviour == Movement::Extend)
+}
+
+/// Moves to the last grapheme of the visual (soft-wrapped) line containing the cursor.
+/// Like `goto_line_end` the line ending itself is never selected.
+///
+/// Falls back to the end of the document line if soft wrap is disabled.
+pub fn move_to_visual_line_end(
+    slice: RopeSlice,
+    range: Range,
+    behaviour: Movement,
+    text_fmt: &TextFormat,
+    annotations: &mut TextAnnotations,
+) -> Range {
+    let pos = range.cursor(slice);
+    let line = slice.char_to_line(pos);
+    let line_start = slice.line_to_char(line);
+    let line_end = line_end_char_index(&slice, line);
+    let new_pos = if text_fmt.soft_wrap {
+        annotations.clear_line_annotations();
+        let (visual_pos, block_off) =
+            visual_offset_from_block(slice, pos, pos, text_fmt, annotations);
+        let (new_pos, _) = char_idx_at_visual_block_offset(
+            slice,
+            block_off,
+            visual_pos.row,
+            usize::MAX,
+            text_fmt,
+            annotations,
+        );
+        new_pos.min(line_end)
+    } else {
+        line_end
+    };
+    let new_pos = if new_pos == line_end {
+        prev_grapheme_boundary(slice, line_end).max(line_start)
+    } else {
+        new_pos
+    };
+    range.put_cursor(slice, new_pos, behaviour == Movement::Extend)
+}
+
+/// Moves `count` lines from `line_idx` while skipping over lines hidden by folds.
+fn move_vertically_over_folds(
+    slice: RopeSlice,
+    mut line_idx: usize,
+    dir: Direction,
+    count: usize,
+    annotations: &TextAnnotations,
+) -> usize {
+    let last_line = slice.len_lines() - 1;
+    for _ in 0..count {
+        let next_line_idx = match dir {
+            Direction::Forward if line_idx < last_line => line_idx + 1,
+            Direction::Backward if line_idx > 0 => line_idx - 1,
+            _ => break,
+        };
+        line_idx = match annotations.fold_containing(slice.line_to_char(next_line_idx)) {
+            Some(fold) if dir == Direction::Backward => slice.char_to_line(fold.start) - 1,
+            // the fold hides everything until the end of the document
+            Some(fold) if fold.end >= slice.len_chars() => break,
+            Some(fold) => slice.char_to_line(fold.end),
+            None => next_line_idx,
+        };
+    }
+    line_idx
+}
+
 pub fn move_next_word_start(slice: RopeSlice, range: Range, count: usize) -> Range {
     word_move(slice, range, count, WordMotionTarget::NextWordStart)
 }
@@ -745,6 +837,57 @@ fn test_vertical_move() {
         );
     }
 
+    #[test]
+    fn visual_line_start_and_end_follow_soft_wrap() {
+        let text = Rope::from("the quick brown fox jumps over the lazy dog\nnext\n");
+        let slice = text.slice(..);
+        let soft_wrap = TextFormat {
+            soft_wrap: true,
+            ..TextFormat::default()
+        };
+        let mut annotations = TextAnnotations::default();
+        let row = |pos, annotations: &TextAnnotations| {
+            visual_offset_from_block(slice, 0, pos, &soft_wrap, annotations)
+                .0
+                .row
+        };
+        let range = Range::point(20);
+        let wrapped_row = row(20, &annotations);
+        assert!(wrapped_row > 0);
+
+        let start =
+            move_to_visual_line_start(slice, range, Movement::Move, &soft_wrap, &mut annotations)
+                .head;
+        assert!(start > 0 && start <= 20);
+        assert_eq!(row(start, &annotations), wrapped_row);
+        assert_eq!(row(start - 1, &annotations), wrapped_row - 1);
+
+        let end =
+            move_to_visual_line_end(slice, range, Movement::Move, &soft_wrap, &mut annotations)
+                .cursor(slice);
+        assert!(end >= 20 && end < 43);
+        assert_eq!(row(end, &annotations), wrapped_row);
+        assert_eq!(row(end + 1, &annotations), wrapped_row + 1);
+
+        // without soft wrap the document line is used
+        let start = move_to_visual_line_start(
+            slice,
+            range,
+            Movement::Move,
+            &TextFormat::default(),
+            &mut annotations,
+        );
+        assert_eq!(start.head, 0);
+        let end = move_to_visual_line_end(
+            slice,
+            range,
+            Movement::Move,
+            &TextFormat::default(),
+            &mut annotations,
+        );
+        assert_eq!(end.cursor(slice), 42);
+    }
+
     #[test]
     fn horizontal_moves_through_single_line_text() {
         let text = Rope::from(SINGLE_LINE_SAMPLE);
@@ -873,6 +1016,41 @@ fn vertical_moves_in_single_column() {
         }
     }
 
+    #[test]
+    fn vertical_moves_skip_folds() {
+        let text = Rope::from(MULTILINE_SAMPLE);
+        let slice = text.slice(..);
+        let position = pos_at_coords(slice, (0, 0).into(), true);
+        let mut range = Range::point(position);
+        // hide lines 2 and 3
+        let folds = [crate::text_annotations::Fold::new(
+            slice.line_to_char(2),
+            slice.line_to_char(4),
+        )];
+        let moves_and_expected_coordinates = [
+            ((Direction::Forward, 1usize), (1, 0)),
+            ((Direction::Forward, 1usize), (4, 0)),
+            ((Direction::Backward, 1usize), (1, 0)),
+            ((Direction::Forward, 2usize), (5, 0)),
+            ((Direction::Backward, 3usize), (0, 0)),
+        ];
+
+        for ((direction, amount), coordinates) in moves_and_expected_coordinates {
+            let mut annotations = TextAnnotations::default();
+            annotations.set_folds(&folds);
+            range = move_vertically(
+                slice,
+                range,
+                direction,
+                amount,
+                Movement::Move,
+                &TextFormat::default(),
+                &mut annotations,
+            );
+            assert_eq!(coords_at_pos(slice, range.head), coordinates.into());
+        }
+    }
+
     #[test]
     fn vertical_moves_jumping_column() {
         let text = Rope::from(MULTILINE_SAMPLE);
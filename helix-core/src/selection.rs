@@ -7,10 +7,11 @@
         ensure_grapheme_boundary_next, ensure_grapheme_boundary_prev, next_grapheme_boundary,
         prev_grapheme_boundary,
     },
-    line_ending::get_line_ending,
+    line_ending::{get_line_ending, line_end_char_index},
     movement::Direction,
+    position::pos_at_visual_coords,
     tree_sitter::Node,
-    Assoc, ChangeSet, RopeSlice,
+    Assoc, ChangeSet, Position, RopeSlice,
 };
 use helix_stdx::range::is_subset;
 use helix_stdx::rope::{self, RopeSliceExt};
@@ -879,6 +880,48 @@ pub fn split_on_matches(text: RopeSlice, selection: &Selection, regex: &rope::Re
     Selection::new(result, 0)
 }
 
+/// Selects the rectangular block between the visual coordinates `anchor` and `head` (both
+/// inclusive) as one range per line. Lines that end before the block are skipped, except for
+/// the line of `head` which holds the primary range with its cursor at `head`.
+pub fn select_block(
+    text: RopeSlice,
+    anchor: Position,
+    head: Position,
+    tab_width: usize,
+) -> Selection {
+    let (left, right) = (anchor.col.min(head.col), anchor.col.max(head.col));
+    let last_line = text.len_lines() - 1;
+    let head_line = head.row.min(last_line);
+    let lines = anchor.row.min(head.row).min(last_line)..=anchor.row.max(head.row).min(last_line);
+
+    let mut ranges = SmallVec::with_capacity(lines.clone().count());
+    let mut primary_index = 0;
+    for line in lines {
+        let line_end = line_end_char_index(&text, line);
+        let start = pos_at_visual_coords(text, Position::new(line, left), tab_width);
+        let end = pos_at_visual_coords(text, Position::new(line, right), tab_width);
+        let end = if end < line_end {
+            next_grapheme_boundary(text, end)
+        } else {
+            line_end
+        };
+
+        if line == head_line {
+            primary_index = ranges.len();
+        } else if start == end {
+            continue;
+        }
+        let range = if head.col < anchor.col {
+            Range::new(end, start)
+        } else {
+            Range::new(start, end)
+        };
+        ranges.push(range);
+    }
+
+    Selection::new(ranges, primary_index)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -1455,4 +1498,32 @@ fn contains(a: Vec<(usize, usize)>, b: Vec<(usize, usize)>) -> bool {
             vec!((1, 2), (3, 4), (7, 9))
         ));
     }
+
+    #[test]
+    fn test_select_block() {
+        let text = Rope::from("abcdef\nab\n\tcdef\nabcdef");
+        let text = text.slice(..);
+
+        let selection = select_block(text, Position::new(0, 1), Position::new(3, 3), 4);
+        assert_eq!(
+            selection.ranges(),
+            &[
+                Range::new(1, 4),
+                Range::new(8, 9),
+                Range::new(10, 11),
+                Range::new(17, 20)
+            ]
+        );
+        assert_eq!(selection.primary_index(), 3);
+        assert_eq!(selection.primary().cursor(text), 19);
+
+        // the head is left of the anchor and on a line that ends before the block
+        let selection = select_block(text, Position::new(0, 5), Position::new(1, 3), 4);
+        assert_eq!(selection.ranges(), &[Range::new(6, 3), Range::new(9, 9)]);
+        assert_eq!(selection.primary_index(), 1);
+
+        // the tab spans the block
+        let selection = select_block(text, Position::new(2, 1), Position::new(2, 2), 4);
+        assert_eq!(selection.ranges(), &[Range::new(10, 11)]);
+    }
 }
@@ -1,5 +1,6 @@
 mod active;
 mod elaborate;
+mod library;
 mod parser;
 mod render;
 
@@ -9,5 +10,6 @@
 
 pub use active::ActiveSnippet;
 pub use elaborate::{Snippet, SnippetElement, Transform};
+pub use library::{SnippetLibrary, UserSnippet, GLOBAL_SCOPE};
 pub use render::RenderedSnippet;
 pub use render::SnippetRenderCtx;
@@ -0,0 +1,206 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::ErrorKind;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::chars::char_is_word;
+use crate::snippets::Snippet;
+
+/// The scope of the snippets that are available in every language.
+pub const GLOBAL_SCOPE: &str = "global";
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum SnippetBody {
+    Text(String),
+    Lines(Vec<String>),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct SnippetDefinition {
+    prefix: Option<String>,
+    body: SnippetBody,
+    description: Option<String>,
+}
+
+/// A snippet defined in the user's `snippets.toml`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserSnippet {
+    pub name: String,
+    /// The text that is replaced by the snippet when it is expanded. Defaults to the name.
+    pub prefix: String,
+    /// The snippet in LSP snippet syntax.
+    pub body: String,
+    pub description: Option<String>,
+}
+
+impl UserSnippet {
+    pub fn parse(&self) -> Result<Snippet> {
+        Snippet::parse(&self.body)
+    }
+}
+
+/// The snippets of the user's `snippets.toml`, grouped by the language they are available in.
+///
+/// ```toml
+/// [rust.test]
+/// prefix = "test"
+/// body = ["#[test]", "fn ${1:name}() {", "\t$0", "}"]
+/// description = "Test function"
+///
+/// [global.todo]
+/// body = "TODO(${1:user}): $0"
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SnippetLibrary {
+    scopes: HashMap<String, Vec<UserSnippet>>,
+}
+
+impl SnippetLibrary {
+    pub fn from_toml(toml: &str) -> Result<Self> {
+        let definitions: HashMap<String, BTreeMap<String, SnippetDefinition>> =
+            toml::from_str(toml)?;
+        let mut scopes = HashMap::with_capacity(definitions.len());
+        for (scope, definitions) in definitions {
+            let snippets = definitions
+                .into_iter()
+                .map(|(name, definition)| {
+                    let body = match definition.body {
+                        SnippetBody::Text(text) => text,
+                        SnippetBody::Lines(lines) => lines.join("\n"),
+                    };
+                    let snippet = UserSnippet {
+                        prefix: definition.prefix.unwrap_or_else(|| name.clone()),
+                        name,
+                        body,
+                        description: definition.description,
+                    };
+                    snippet
+                        .parse()
+                        .with_context(|| format!("invalid snippet '{scope}.{}'", snippet.name))?;
+                    Ok(snippet)
+                })
+                .collect::<Result<_>>()?;
+            scopes.insert(scope, snippets);
+        }
+        Ok(SnippetLibrary { scopes })
+    }
+
+    /// Loads the snippets from `snippets.toml` in the config directory. A missing file results
+    /// in an empty library.
+    pub fn load() -> Result<Self> {
+        let path = helix_loader::snippets_file();
+        match std::fs::read_to_string(&path) {
+            Ok(toml) => Self::from_toml(&toml).with_context(|| format!("failed to load {path:?}")),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err).with_context(|| format!("failed to read {path:?}")),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scopes.values().all(Vec::is_empty)
+    }
+
+    /// Returns the snippets available in `language` followed by the global snippets.
+    pub fn snippets<'a>(
+        &'a self,
+        language: Option<&'a str>,
+    ) -> impl Iterator<Item = &'a UserSnippet> {
+        language
+            .filter(|&language| language != GLOBAL_SCOPE)
+            .and_then(|language| self.scopes.get(language))
+            .into_iter()
+            .chain(self.scopes.get(GLOBAL_SCOPE))
+            .flatten()
+    }
+
+    /// Finds the snippet whose prefix ends `line`, the text of the cursor line before the
+    /// cursor. The prefix must not continue a word. The longest prefix wins, language snippets
+    /// are preferred over global ones with the same prefix length.
+    pub fn find_expandable<'a>(
+        &'a self,
+        language: Option<&'a str>,
+        line: &str,
+    ) -> Option<&'a UserSnippet> {
+        let mut best: Option<&UserSnippet> = None;
+        for snippet in self.snippets(language) {
+            let Some(before) = line.strip_suffix(snippet.prefix.as_str()) else {
+                continue;
+            };
+            if snippet.prefix.is_empty()
+                || (snippet.prefix.starts_with(char_is_word) && before.ends_with(char_is_word))
+            {
+                continue;
+            }
+            if best.is_none_or(|best| snippet.prefix.len() > best.prefix.len()) {
+                best = Some(snippet);
+            }
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SNIPPETS: &str = r##"
+        [rust.test]
+        body = ["#[test]", "fn ${1:name}() {", "\t$0", "}"]
+        description = "Test function"
+
+        [rust.println]
+        prefix = "pl"
+        body = "println!(\"$1\");"
+
+        [global.todo]
+        body = "TODO: $0"
+
+        [global.arrow]
+        prefix = "->"
+        body = "→"
+
+        [global.long-arrow]
+        prefix = "-->"
+        body = "⟶"
+    "##;
+
+    #[test]
+    fn parse_library() {
+        let library = SnippetLibrary::from_toml(SNIPPETS).unwrap();
+        let names: Vec<_> = library
+            .snippets(Some("rust"))
+            .map(|snippet| snippet.name.as_str())
+            .collect();
+        assert_eq!(names, ["println", "test", "arrow", "long-arrow", "todo"]);
+        let test = library.snippets(Some("rust")).nth(1).unwrap();
+        assert_eq!(test.prefix, "test");
+        assert_eq!(test.body, "#[test]\nfn ${1:name}() {\n\t$0\n}");
+        assert_eq!(library.snippets(Some("python")).count(), 3);
+        assert_eq!(library.snippets(None).count(), 3);
+    }
+
+    #[test]
+    fn invalid_snippets_are_rejected() {
+        assert!(SnippetLibrary::from_toml("[rust.empty]\nbody = \"\"").is_err());
+        assert!(SnippetLibrary::from_toml("[rust.missing]\nprefix = \"m\"").is_err());
+    }
+
+    #[test]
+    fn find_expandable_snippet() {
+        let library = SnippetLibrary::from_toml(SNIPPETS).unwrap();
+        let find = |language, line| {
+            library
+                .find_expandable(language, line)
+                .map(|snippet| snippet.name.as_str())
+        };
+        assert_eq!(find(Some("rust"), "    pl"), Some("println"));
+        assert_eq!(find(Some("rust"), "    apl"), None);
+        assert_eq!(find(Some("python"), "    pl"), None);
+        assert_eq!(find(Some("python"), "# todo"), Some("todo"));
+        assert_eq!(find(None, "a ->"), Some("arrow"));
+        assert_eq!(find(None, "a-->"), Some("long-arrow"));
+    }
+}
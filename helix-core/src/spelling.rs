@@ -0,0 +1,692 @@
+//! Spell checking of the prose in a document: comments, strings and markup.
+//!
+//! Dictionaries use the hunspell format: a `.dic` word list whose entries may carry affix
+//! flags and an `.aff` file with the prefix and suffix rules these flags refer to. The word
+//! list is expanded with the affix rules when the dictionary is loaded. Compounding and the
+//! options that tune hunspell's suggestions are not supported.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Write as _;
+use std::ops::Range;
+use std::path::PathBuf;
+
+use anyhow::{Context as _, Result};
+use ropey::RopeSlice;
+
+use crate::syntax::{HighlightEvent, Loader, Syntax};
+
+/// Languages whose text outside of any highlight is prose.
+const PROSE_LANGUAGES: &[&str] = &["markdown", "markdown.inline", "git-commit", "text"];
+
+/// Directories searched for hunspell dictionaries, in priority order.
+fn dictionary_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![helix_loader::config_dir().join("dictionaries")];
+    dirs.extend(
+        helix_loader::runtime_dirs()
+            .iter()
+            .map(|dir| dir.join("dictionaries")),
+    );
+    dirs.extend(
+        [
+            "/usr/share/hunspell",
+            "/usr/share/myspell",
+            "/usr/local/share/hunspell",
+        ]
+        .map(PathBuf::from),
+    );
+    dirs
+}
+
+/// The file storing the words added with `:spell-add` for `language`.
+pub fn personal_word_list(language: &str) -> PathBuf {
+    helix_loader::config_dir()
+        .join("spell")
+        .join(format!("{language}.txt"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FlagFormat {
+    Char,
+    Long,
+    Num,
+}
+
+impl FlagFormat {
+    fn split(self, flags: &str) -> Vec<String> {
+        match self {
+            FlagFormat::Char => flags.chars().map(String::from).collect(),
+            FlagFormat::Long => {
+                let chars: Vec<char> = flags.chars().collect();
+                chars.chunks(2).map(|flag| flag.iter().collect()).collect()
+            }
+            FlagFormat::Num => flags
+                .split(',')
+                .map(str::trim)
+                .filter(|flag| !flag.is_empty())
+                .map(String::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CharClass {
+    Any,
+    Char(char),
+    Set { chars: Vec<char>, negated: bool },
+}
+
+impl CharClass {
+    fn matches(&self, ch: char) -> bool {
+        match self {
+            CharClass::Any => true,
+            CharClass::Char(c) => *c == ch,
+            CharClass::Set { chars, negated } => chars.contains(&ch) != *negated,
+        }
+    }
+}
+
+fn parse_condition(condition: &str) -> Vec<CharClass> {
+    let mut classes = Vec::new();
+    let mut chars = condition.chars();
+    while let Some(ch) = chars.next() {
+        let class = match ch {
+            '.' => CharClass::Any,
+            '[' => {
+                let mut set: Vec<char> = chars.by_ref().take_while(|&c| c != ']').collect();
+                let negated = set.first() == Some(&'^');
+                if negated {
+                    set.remove(0);
+                }
+                CharClass::Set {
+                    chars: set,
+                    negated,
+                }
+            }
+            ch => CharClass::Char(ch),
+        };
+        classes.push(class);
+    }
+    classes
+}
+
+#[derive(Debug)]
+struct AffixRule {
+    strip: String,
+    add: String,
+    condition: Vec<CharClass>,
+}
+
+impl AffixRule {
+    fn apply_suffix(&self, word: &str) -> Option<String> {
+        let stem = word.strip_suffix(&*self.strip)?;
+        let chars: Vec<char> = word.chars().collect();
+        let start = chars.len().checked_sub(self.condition.len())?;
+        let matches = self
+            .condition
+            .iter()
+            .zip(&chars[start..])
+            .all(|(class, &ch)| class.matches(ch));
+        (matches && !stem.is_empty()).then(|| format!("{stem}{}", self.add))
+    }
+
+    fn apply_prefix(&self, word: &str) -> Option<String> {
+        let stem = word.strip_prefix(&*self.strip)?;
+        if word.chars().count() < self.condition.len() {
+            return None;
+        }
+        let matches = self
+            .condition
+            .iter()
+            .zip(word.chars())
+            .all(|(class, ch)| class.matches(ch));
+        (matches && !stem.is_empty()).then(|| format!("{}{stem}", self.add))
+    }
+}
+
+#[derive(Debug)]
+struct Affix {
+    cross_product: bool,
+    rules: Vec<AffixRule>,
+}
+
+#[derive(Debug)]
+struct AffixFile {
+    flag_format: FlagFormat,
+    prefixes: HashMap<String, Affix>,
+    suffixes: HashMap<String, Affix>,
+    forbidden: Option<String>,
+    need_affix: Option<String>,
+}
+
+impl AffixFile {
+    fn parse(aff: &str) -> Self {
+        let mut file = AffixFile {
+            flag_format: FlagFormat::Char,
+            prefixes: HashMap::new(),
+            suffixes: HashMap::new(),
+            forbidden: None,
+            need_affix: None,
+        };
+        for line in aff.lines() {
+            let mut fields = line.split_whitespace();
+            match fields.next() {
+                Some("FLAG") => {
+                    file.flag_format = match fields.next() {
+                        Some("long") => FlagFormat::Long,
+                        Some("num") => FlagFormat::Num,
+                        _ => FlagFormat::Char,
+                    }
+                }
+                Some("FORBIDDENWORD") => file.forbidden = fields.next().map(String::from),
+                Some("NEEDAFFIX") => file.need_affix = fields.next().map(String::from),
+                Some(kind @ ("PFX" | "SFX")) => {
+                    let (Some(flag), Some(strip), Some(add)) =
+                        (fields.next(), fields.next(), fields.next())
+                    else {
+                        continue;
+                    };
+                    let table = if kind == "PFX" {
+                        &mut file.prefixes
+                    } else {
+                        &mut file.suffixes
+                    };
+                    // the first line of an affix is a header: `SFX <flag> <cross product> <count>`
+                    let Some(affix) = table.get_mut(flag) else {
+                        table.insert(
+                            flag.to_string(),
+                            Affix {
+                                cross_product: strip == "Y",
+                                rules: Vec::new(),
+                            },
+                        );
+                        continue;
+                    };
+                    let empty = |field: &str| match field {
+                        "0" => String::new(),
+                        field => field.to_string(),
+                    };
+                    // continuation classes (`add/flags`) are not supported
+                    let add = add.split('/').next().unwrap_or_default();
+                    affix.rules.push(AffixRule {
+                        strip: empty(strip),
+                        add: empty(add),
+                        condition: parse_condition(fields.next().unwrap_or(".")),
+                    });
+                }
+                _ => (),
+            }
+        }
+        file
+    }
+
+    fn expand(&self, stem: &str, flags: &[String], words: &mut HashSet<String>) {
+        let has_flag = |flag: &Option<String>| flag.as_ref().is_some_and(|f| flags.contains(f));
+        if has_flag(&self.forbidden) {
+            return;
+        }
+        if !has_flag(&self.need_affix) {
+            words.insert(stem.to_string());
+        }
+
+        let mut cross_product = Vec::new();
+        for affix in flags.iter().filter_map(|flag| self.suffixes.get(flag)) {
+            for word in affix
+                .rules
+                .iter()
+                .filter_map(|rule| rule.apply_suffix(stem))
+            {
+                if affix.cross_product {
+                    cross_product.push(word.clone());
+                }
+                words.insert(word);
+            }
+        }
+        for affix in flags.iter().filter_map(|flag| self.prefixes.get(flag)) {
+            for rule in &affix.rules {
+                words.extend(rule.apply_prefix(stem));
+                if affix.cross_product {
+                    words.extend(cross_product.iter().filter_map(|w| rule.apply_prefix(w)));
+                }
+            }
+        }
+    }
+}
+
+/// Decodes a dictionary file with the encoding declared by the `SET` option of the affix file.
+fn decode<'a>(
+    bytes: &'a [u8],
+    encoding: &'static encoding_rs::Encoding,
+) -> std::borrow::Cow<'a, str> {
+    encoding.decode_with_bom_removal(bytes).0
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Dictionary {
+    words: HashSet<String>,
+}
+
+impl Dictionary {
+    /// Builds a dictionary from the contents of a hunspell affix (`.aff`) and dictionary (`.dic`)
+    /// file. Lines that can't be parsed are skipped.
+    pub fn from_hunspell(aff: &str, dic: &str) -> Self {
+        let affixes = AffixFile::parse(aff);
+        let mut words = HashSet::new();
+        let mut lines = dic.lines().peekable();
+        // the first line is the approximate number of words
+        lines.next_if(|line| line.trim().parse::<usize>().is_ok());
+        for line in lines {
+            let Some(entry) = line.split_whitespace().next() else {
+                continue;
+            };
+            let (stem, flags) = entry.split_once('/').unwrap_or((entry, ""));
+            affixes.expand(stem, &affixes.flag_format.split(flags), &mut words);
+        }
+        Dictionary { words }
+    }
+
+    /// Loads the hunspell dictionary for `language` (for example `en_US`) together with the
+    /// words previously added with [`Dictionary::add_personal_word`].
+    pub fn load(language: &str) -> Result<Self> {
+        let dir = dictionary_dirs()
+            .into_iter()
+            .find(|dir| dir.join(format!("{language}.dic")).is_file())
+            .with_context(|| format!("no dictionary found for '{language}'"))?;
+        let aff = fs::read(dir.join(format!("{language}.aff"))).unwrap_or_default();
+        let dic = fs::read(dir.join(format!("{language}.dic")))?;
+
+        let aff_encoding = encoding_rs::UTF_8.decode_with_bom_removal(&aff).0;
+        let encoding = aff_encoding
+            .lines()
+            .find_map(|line| line.strip_prefix("SET "))
+            .and_then(|label| encoding_rs::Encoding::for_label(label.trim().as_bytes()))
+            .unwrap_or(encoding_rs::UTF_8);
+
+        let mut dictionary =
+            Dictionary::from_hunspell(&decode(&aff, encoding), &decode(&dic, encoding));
+        if let Ok(personal) = fs::read_to_string(personal_word_list(language)) {
+            for word in personal.lines().map(str::trim).filter(|w| !w.is_empty()) {
+                dictionary.add_word(word);
+            }
+        }
+        Ok(dictionary)
+    }
+
+    pub fn add_word(&mut self, word: &str) {
+        self.words.insert(word.to_string());
+    }
+
+    /// Adds `word` to the dictionary and appends it to the personal word list of `language`.
+    pub fn add_personal_word(&mut self, language: &str, word: &str) -> Result<()> {
+        let path = personal_word_list(language);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        writeln!(file, "{word}")?;
+        self.add_word(word);
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Whether `word` is spelled correctly. Capitalized and upper case words are also accepted
+    /// if their lower case form is in the dictionary.
+    pub fn check(&self, word: &str) -> bool {
+        if self.words.contains(word) {
+            return true;
+        }
+        let mut chars = word.chars();
+        let Some(first) = chars.next() else {
+            return true;
+        };
+        if !first.is_uppercase() {
+            return false;
+        }
+        let rest = chars.as_str();
+        if rest.chars().all(|c| !c.is_uppercase()) {
+            return self.words.contains(&word.to_lowercase());
+        }
+        if rest.chars().all(|c| !c.is_lowercase()) {
+            let lower = word.to_lowercase();
+            let capitalized = capitalize(&lower);
+            return self.words.contains(&lower) || self.words.contains(&capitalized);
+        }
+        false
+    }
+
+    /// Returns up to `limit` dictionary words that are at most two edits away from `word`, the
+    /// closest first. The suggestions follow the capitalization of `word`.
+    pub fn suggest(&self, word: &str, limit: usize) -> Vec<String> {
+        let lower: Vec<char> = word.to_lowercase().chars().collect();
+        let mut suggestions: Vec<(usize, &String)> = self
+            .words
+            .iter()
+            .filter_map(|candidate| {
+                let len = candidate.chars().count();
+                if len.abs_diff(lower.len()) > 2 {
+                    return None;
+                }
+                let candidate_lower: Vec<char> = candidate.to_lowercase().chars().collect();
+                let distance = edit_distance(&lower, &candidate_lower, 2)?;
+                (distance > 0 || candidate != word).then_some((distance, candidate))
+            })
+            .collect();
+        suggestions.sort_unstable_by(|(d1, w1), (d2, w2)| d1.cmp(d2).then_with(|| w1.cmp(w2)));
+
+        let is_capitalized = word.chars().next().is_some_and(char::is_uppercase);
+        let is_upper = is_capitalized && word.chars().all(|c| !c.is_lowercase());
+        let mut seen = HashSet::new();
+        suggestions
+            .into_iter()
+            .map(|(_, suggestion)| {
+                if is_upper {
+                    suggestion.to_uppercase()
+                } else if is_capitalized {
+                    capitalize(suggestion)
+                } else {
+                    suggestion.clone()
+                }
+            })
+            .filter(|suggestion| suggestion != word && seen.insert(suggestion.clone()))
+            .take(limit)
+            .collect()
+    }
+
+    /// Returns the char ranges and words inside `ranges` of `text` that aren't in the dictionary.
+    pub fn misspelled_words(
+        &self,
+        text: RopeSlice,
+        ranges: &[Range<usize>],
+    ) -> Vec<(Range<usize>, String)> {
+        ranges
+            .iter()
+            .flat_map(|range| words(text, range.clone()))
+            .filter(|(_, word)| !self.check(word))
+            .collect()
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// The optimal string alignment distance between `a` and `b` or `None` if it exceeds `max`.
+fn edit_distance(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    let mut prev2 = vec![0; b.len() + 1];
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        cur[0] = i;
+        let mut row_min = cur[0];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            cur[j] = (prev[j] + 1).min(cur[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                cur[j] = cur[j].min(prev2[j - 2] + 1);
+            }
+            row_min = row_min.min(cur[j]);
+        }
+        if row_min > max {
+            return None;
+        }
+        std::mem::swap(&mut prev2, &mut prev);
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    let distance = prev[b.len()];
+    (distance <= max).then_some(distance)
+}
+
+/// Whether a word is checked: identifiers like `snake_case` or `camelCase`, words with digits,
+/// acronyms and single letters are skipped.
+fn is_checked_word(word: &[char]) -> bool {
+    word.len() > 1
+        && word.iter().all(|&c| c.is_alphabetic() || c == '\'')
+        && !word.iter().all(|c| !c.is_lowercase())
+        && !word[1..].iter().any(|c| c.is_uppercase())
+}
+
+/// Splits `range` of `text` into the words that are spell checked.
+///
+/// Whitespace separated chunks that look like paths, URLs, email addresses or code are
+/// skipped entirely.
+pub fn words(text: RopeSlice, range: Range<usize>) -> Vec<(Range<usize>, String)> {
+    let mut words = Vec::new();
+    let mut chunk = Vec::new();
+    let mut chunk_start = range.start;
+    let chars = text
+        .slice(range.clone())
+        .chars()
+        .chain(std::iter::once(' '));
+    for (i, ch) in (range.start..).zip(chars) {
+        if !ch.is_whitespace() {
+            if chunk.is_empty() {
+                chunk_start = i;
+            }
+            chunk.push(ch);
+            continue;
+        }
+        if chunk.is_empty() {
+            continue;
+        }
+        chunk_words(&chunk, chunk_start, &mut words);
+        chunk.clear();
+    }
+    words
+}
+
+fn chunk_words(chunk: &[char], start: usize, words: &mut Vec<(Range<usize>, String)>) {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_' || c == '\'';
+    let Some(first) = chunk.iter().position(|c| c.is_alphanumeric()) else {
+        return;
+    };
+    let last = chunk
+        .iter()
+        .rposition(|c| c.is_alphanumeric())
+        .unwrap_or(first);
+    let inner = &chunk[first..=last];
+    if inner.iter().any(|c| {
+        matches!(
+            c,
+            '/' | '\\'
+                | '@'
+                | '.'
+                | ':'
+                | '='
+                | '#'
+                | '$'
+                | '%'
+                | '&'
+                | '<'
+                | '>'
+                | '('
+                | '{'
+                | '['
+        )
+    }) {
+        return;
+    }
+
+    let mut i = 0;
+    while i < inner.len() {
+        if !is_word_char(inner[i]) {
+            i += 1;
+            continue;
+        }
+        let mut word_start = i;
+        while i < inner.len() && is_word_char(inner[i]) {
+            i += 1;
+        }
+        let mut word_end = i;
+        while word_start < word_end && inner[word_start] == '\'' {
+            word_start += 1;
+        }
+        while word_end > word_start && inner[word_end - 1] == '\'' {
+            word_end -= 1;
+        }
+        let word = &inner[word_start..word_end];
+        if is_checked_word(word) {
+            let offset = start + first;
+            words.push((
+                offset + word_start..offset + word_end,
+                word.iter().collect(),
+            ));
+        }
+    }
+}
+
+/// Returns whether text highlighted with `scope` is prose or `None` if the scope doesn't tell.
+fn is_prose_scope(scope: &str) -> Option<bool> {
+    const EXCLUDED: &[&str] = &[
+        "string.regexp",
+        "string.special",
+        "markup.raw",
+        "markup.link.url",
+    ];
+    match scope.split('.').next() {
+        Some("comment" | "string" | "markup") => {
+            Some(!EXCLUDED.iter().any(|excluded| scope.starts_with(excluded)))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the char ranges of `text` that are spell checked: comments, strings and markup
+/// according to the syntax highlights of all injection layers and all other text of prose
+/// languages like markdown. Documents without syntax are checked entirely.
+pub fn checked_ranges(
+    syntax: Option<&Syntax>,
+    text: RopeSlice,
+    loader: &Loader,
+) -> Vec<Range<usize>> {
+    let Some(syntax) = syntax else {
+        return vec![0..text.len_chars()];
+    };
+    let scopes = loader.scopes();
+    let len = text.len_bytes() as u32;
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut highlighter = syntax.highlighter(text, loader, ..);
+    let mut highlights = Vec::new();
+    let mut pos = 0;
+
+    while pos < len {
+        if pos == highlighter.next_event_offset() {
+            let (event, new_highlights) = highlighter.advance();
+            if event == HighlightEvent::Refresh {
+                highlights.clear();
+            }
+            highlights.extend(new_highlights);
+        }
+        let start = pos;
+        pos = highlighter.next_event_offset().min(len);
+        if pos <= start {
+            // the highlighter should always move forward, stop if it malfunctions
+            if pos < start {
+                break;
+            }
+            continue;
+        }
+
+        let is_prose = highlights
+            .iter()
+            .rev()
+            .find_map(|highlight| is_prose_scope(scopes.get(highlight.idx())?))
+            .unwrap_or_else(|| {
+                let layer = syntax.layer_for_byte_range(start, start);
+                let language = loader.language(syntax.layer(layer).language);
+                PROSE_LANGUAGES.contains(&language.config().language_id.as_str())
+            });
+        if !is_prose {
+            continue;
+        }
+        let range = text.byte_to_char(start as usize)..text.byte_to_char(pos as usize);
+        match ranges.last_mut() {
+            Some(last) if last.end == range.start => last.end = range.end,
+            _ => ranges.push(range),
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ropey::Rope;
+
+    const AFF: &str = "SET UTF-8
+FLAG UTF-8
+
+PFX U Y 1
+PFX U 0 un .
+
+SFX S Y 2
+SFX S y ies [^aeiou]y
+SFX S 0 s [^y]
+
+SFX D N 2
+SFX D 0 ed [^e]
+SFX D 0 d e
+";
+
+    const DIC: &str = "5
+do/U
+fly/S
+hope/D
+lock/USD
+word/S
+";
+
+    #[test]
+    fn expand_affixes() {
+        let dictionary = Dictionary::from_hunspell(AFF, DIC);
+        for word in [
+            "do", "undo", "fly", "flies", "hope", "hoped", "lock", "locks", "locked", "unlock",
+            "unlocks", "words",
+        ] {
+            assert!(dictionary.check(word), "{word} should be valid");
+        }
+        for word in ["flys", "hopeed", "unlocked", "undos", "wordies"] {
+            assert!(!dictionary.check(word), "{word} should be invalid");
+        }
+    }
+
+    #[test]
+    fn check_capitalization() {
+        let dictionary = Dictionary::from_hunspell(AFF, DIC);
+        assert!(dictionary.check("Word"));
+        assert!(dictionary.check("WORDS"));
+        assert!(!dictionary.check("wOrd"));
+    }
+
+    #[test]
+    fn suggestions() {
+        let dictionary = Dictionary::from_hunspell(AFF, DIC);
+        assert_eq!(dictionary.suggest("wrod", 3), ["word", "words"]);
+        assert_eq!(dictionary.suggest("Flyes", 1), ["Flies"]);
+    }
+
+    #[test]
+    fn split_words() {
+        let text = Rope::from("// Unlcok the_door and fooBar, see http://x.org or (don't) 42x\n");
+        assert_eq!(
+            words(text.slice(..), 0..text.len_chars()),
+            [
+                (3..9, "Unlcok".to_string()),
+                (19..22, "and".to_string()),
+                (31..34, "see".to_string()),
+                (48..50, "or".to_string()),
+                (52..57, "don't".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! Syntax aware search and replace with tree-sitter queries.
+//!
+//! The nodes to search for are captured with `@match`, all other captures of the query can be
+//! referenced by the replacement template:
+//!
+//! ```query
+//! (call_expression
+//!   function: (field_expression
+//!     value: (_) @receiver
+//!     field: (field_identifier) @method (#eq? @method "unwrap"))) @match
+//! ```
+//!
+//! Replacing the matches of this query with `${receiver}.expect("TODO")` rewrites all
+//! `unwrap()` calls.
+
+use std::ops::Range;
+
+use anyhow::{anyhow, bail, Result};
+use ropey::RopeSlice;
+use tree_house::tree_sitter::{
+    query::InvalidPredicateError, Capture, Grammar, InactiveQueryCursor, Query, RopeInput,
+};
+
+use crate::syntax::{Syntax, TREE_SITTER_MATCH_LIMIT};
+
+/// The name of the capture that marks the nodes matched by a [StructuralQuery].
+pub const MATCH_CAPTURE: &str = "match";
+
+/// A tree-sitter query that captures the nodes to search for with `@match`.
+#[derive(Debug)]
+pub struct StructuralQuery {
+    query: Query,
+    target: Capture,
+}
+
+/// A node matched by a [StructuralQuery] together with the other captures of the match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructuralMatch {
+    /// The char range of the matched node.
+    pub range: Range<usize>,
+    captures: Vec<(Capture, Range<usize>)>,
+}
+
+impl StructuralQuery {
+    pub fn new(grammar: Grammar, source: &str) -> Result<Self> {
+        let query = Query::new(grammar, source, |_pattern, predicate| {
+            Err(InvalidPredicateError::unknown(predicate))
+        })
+        .map_err(|err| anyhow!("invalid query: {err}"))?;
+        let Some(target) = query.get_capture(MATCH_CAPTURE) else {
+            bail!("the query must capture the nodes to search for with @{MATCH_CAPTURE}");
+        };
+        Ok(Self { query, target })
+    }
+
+    /// Returns the matches of the query in the root layer of `syntax`, ordered by their
+    /// position. Matches that overlap an earlier match are skipped.
+    pub fn matches(&self, syntax: &Syntax, text: RopeSlice) -> Vec<StructuralMatch> {
+        let root = syntax.tree().root_node();
+        let mut cursor = InactiveQueryCursor::new(0..u32::MAX, TREE_SITTER_MATCH_LIMIT)
+            .execute_query(&self.query, &root, RopeInput::new(text));
+        let char_range = |range: Range<u32>| {
+            text.byte_to_char(range.start as usize)..text.byte_to_char(range.end as usize)
+        };
+
+        let mut matches = Vec::new();
+        while let Some(mat) = cursor.next_match() {
+            let captures: Vec<_> = mat
+                .matched_nodes()
+                .map(|matched| (matched.capture, char_range(matched.node.byte_range())))
+                .collect();
+            for node in mat.nodes_for_capture(self.target) {
+                matches.push(StructuralMatch {
+                    range: char_range(node.byte_range()),
+                    captures: captures.clone(),
+                });
+            }
+        }
+
+        matches.sort_by_key(|mat| (mat.range.start, std::cmp::Reverse(mat.range.end)));
+        let mut end = 0;
+        matches.retain(|mat| {
+            let keep = mat.range.start >= end;
+            if keep {
+                end = mat.range.end;
+            }
+            keep
+        });
+        matches
+    }
+
+    /// Parses a replacement template. `$name` and `${name}` are replaced by the text of the
+    /// capture `@name` and `$$` by a literal `$`.
+    pub fn template(&self, template: &str) -> Result<Template> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.char_indices().peekable();
+        while let Some((i, ch)) = chars.next() {
+            if ch != '$' {
+                literal.push(ch);
+                continue;
+            }
+            let name = match chars.peek() {
+                Some((_, '$')) => {
+                    chars.next();
+                    literal.push('$');
+                    continue;
+                }
+                Some((_, '{')) => {
+                    let Some(len) = template[i + 2..].find('}') else {
+                        bail!("unterminated capture reference at offset {i}");
+                    };
+                    let name = &template[i + 2..i + 2 + len];
+                    while chars.next_if(|&(j, _)| j <= i + 2 + len).is_some() {}
+                    name
+                }
+                _ => {
+                    let start = i + 1;
+                    let mut end = start;
+                    while let Some((j, ch)) =
+                        chars.next_if(|&(_, ch)| ch.is_alphanumeric() || ch == '_')
+                    {
+                        end = j + ch.len_utf8();
+                    }
+                    &template[start..end]
+                }
+            };
+            if name.is_empty() {
+                literal.push('$');
+                continue;
+            }
+            let Some(capture) = self.query.get_capture(name) else {
+                bail!("the query has no capture @{name}");
+            };
+            if !literal.is_empty() {
+                parts.push(TemplatePart::Literal(std::mem::take(&mut literal)));
+            }
+            parts.push(TemplatePart::Capture(capture));
+        }
+        if !literal.is_empty() {
+            parts.push(TemplatePart::Literal(literal));
+        }
+        Ok(Template { parts })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TemplatePart {
+    Literal(String),
+    Capture(Capture),
+}
+
+/// A replacement template for the matches of a [StructuralQuery], see
+/// [StructuralQuery::template].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<TemplatePart>,
+}
+
+impl StructuralMatch {
+    /// Returns the replacement of the match. Captures that did not participate in the match
+    /// are replaced by nothing.
+    pub fn expand(&self, template: &Template, text: RopeSlice) -> String {
+        let mut res = String::new();
+        for part in &template.parts {
+            match part {
+                TemplatePart::Literal(literal) => res.push_str(literal),
+                TemplatePart::Capture(capture) => {
+                    if let Some((_, range)) = self.captures.iter().find(|(cap, _)| cap == capture) {
+                        res.extend(text.slice(range.clone()).chunks());
+                    }
+                }
+            }
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use once_cell::sync::Lazy;
+
+    use super::*;
+    use crate::syntax::{LanguageLoader, Loader};
+    use crate::Rope;
+
+    static LOADER: Lazy<Loader> = Lazy::new(crate::config::default_lang_loader);
+
+    #[test]
+    fn search_and_expand() {
+        let source = Rope::from_str("fn main() {\n    a.unwrap();\n    b.unwrap().c.len();\n}\n");
+        let language = LOADER.language_for_name("rust").unwrap();
+        let grammar = LOADER.get_config(language).unwrap().grammar;
+        let query = StructuralQuery::new(
+            grammar,
+            r#"(call_expression
+                 function: (field_expression
+                   value: (_) @receiver
+                   field: (field_identifier) @method (#eq? @method "unwrap"))) @match"#,
+        )
+        .unwrap();
+        let syntax = Syntax::new(source.slice(..), language, &LOADER).unwrap();
+
+        let text = source.slice(..);
+        let matches = query.matches(&syntax, text);
+        let template = query.template("${receiver}.expect(\"$$$method\")").unwrap();
+        let replaced: Vec<_> = matches
+            .iter()
+            .map(|mat| mat.expand(&template, text))
+            .collect();
+        assert_eq!(replaced, ["a.expect(\"$unwrap\")", "b.expect(\"$unwrap\")"]);
+        assert_eq!(text.slice(matches[1].range.clone()), "b.unwrap()");
+
+        assert!(query.template("$missing").is_err());
+        assert!(StructuralQuery::new(grammar, "(identifier) @ident").is_err());
+    }
+}
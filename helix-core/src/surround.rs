@@ -1,4 +1,4 @@
-use std::fmt::Display;
+use std::{fmt::Display, ops};
 
 use crate::{
     graphemes::next_grapheme_boundary,
@@ -50,6 +50,44 @@ pub fn find_nth_closest_pairs_pos(
     }
 }
 
+/// Finds the innermost function call around the range using tree-sitter.
+///
+/// # Returns
+///
+/// Tuple of the char ranges of the callee with the opening parenthesis, e.g. `name(`, and of the
+/// closing parenthesis.
+pub fn find_function_call(
+    syntax: &Syntax,
+    text: RopeSlice,
+    range: Range,
+) -> Result<(ops::Range<usize>, ops::Range<usize>)> {
+    let (from, to) = range.into_byte_range(text);
+    let mut node = syntax
+        .descendant_for_byte_range(from as u32, to as u32)
+        .ok_or(Error::PairNotFound)?;
+    loop {
+        let kind = node.kind();
+        if kind.contains("call") || kind.contains("invocation") {
+            let arguments = node
+                .children()
+                .find(|child| child.kind().contains("argument"))
+                .map(|arguments| {
+                    let start = text.byte_to_char(arguments.start_byte() as usize);
+                    let end = text.byte_to_char(arguments.end_byte() as usize);
+                    (start, end)
+                })
+                .filter(|&(start, end)| {
+                    end > start + 1 && text.char(start) == '(' && text.char(end - 1) == ')'
+                });
+            if let Some((start, end)) = arguments {
+                let call_start = text.byte_to_char(node.start_byte() as usize);
+                return Ok((call_start..start + 1, end - 1..end));
+            }
+        }
+        node = node.parent().ok_or(Error::PairNotFound)?;
+    }
+}
+
 fn find_nth_closest_pairs_ts(
     syntax: &Syntax,
     text: RopeSlice,
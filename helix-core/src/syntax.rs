@@ -44,6 +44,8 @@ pub struct LanguageData {
     textobject_query: OnceCell<Option<TextObjectQuery>>,
     tag_query: OnceCell<Option<TagQuery>>,
     rainbow_query: OnceCell<Option<RainbowQuery>>,
+    context_query: OnceCell<Option<ContextQuery>>,
+    fold_query: OnceCell<Option<FoldQuery>>,
 }
 
 impl LanguageData {
@@ -55,6 +57,8 @@ fn new(config: LanguageConfiguration) -> Self {
             textobject_query: OnceCell::new(),
             tag_query: OnceCell::new(),
             rainbow_query: OnceCell::new(),
+            context_query: OnceCell::new(),
+            fold_query: OnceCell::new(),
         }
     }
 
@@ -231,6 +235,73 @@ fn rainbow_query(&self, loader: &Loader) -> Option<&RainbowQuery> {
             .as_ref()
     }
 
+    /// Compiles the context.scm query for a language.
+    /// This function should only be used by this module or the xtask crate.
+    pub fn compile_context_query(
+        grammar: Grammar,
+        config: &LanguageConfiguration,
+    ) -> Result<Option<ContextQuery>> {
+        let name = &config.language_id;
+        let text = read_query(name, "context.scm");
+        if text.is_empty() {
+            return Ok(None);
+        }
+        let query = Query::new(grammar, &text, |_pattern, predicate| {
+            Err(InvalidPredicateError::unknown(predicate))
+        })
+        .with_context(|| format!("Failed to compile context.scm query for '{name}'"))?;
+        Ok(Some(ContextQuery { query }))
+    }
+
+    fn context_query(&self, loader: &Loader) -> Option<&ContextQuery> {
+        self.context_query
+            .get_or_init(|| {
+                let grammar = self.syntax_config(loader)?.grammar;
+                Self::compile_context_query(grammar, &self.config)
+                    .map_err(|err| {
+                        log::error!("{err}");
+                    })
+                    .ok()
+                    .flatten()
+            })
+            .as_ref()
+    }
+
+    /// Compiles the folds.scm query for a language.
+    /// This function should only be used by this module or the xtask crate.
+    pub fn compile_fold_query(
+        grammar: Grammar,
+        config: &LanguageConfiguration,
+    ) -> Result<Option<FoldQuery>> {
+        let name = &config.language_id;
+        let text = read_query(name, "folds.scm");
+        if text.is_empty() {
+            return Ok(None);
+        }
+        // Some queries are shared with other editors and use their predicates, only the
+        // captured nodes matter for folding.
+        let query = Query::new(grammar, &text, |_, _| Ok(()))
+            .with_context(|| format!("Failed to compile folds.scm query for '{name}'"))?;
+        Ok(Some(FoldQuery {
+            fold_capture: query.get_capture("fold"),
+            query,
+        }))
+    }
+
+    fn fold_query(&self, loader: &Loader) -> Option<&FoldQuery> {
+        self.fold_query
+            .get_or_init(|| {
+                let grammar = self.syntax_config(loader)?.grammar;
+                Self::compile_fold_query(grammar, &self.config)
+                    .map_err(|err| {
+                        log::error!("{err}");
+                    })
+                    .ok()
+                    .flatten()
+            })
+            .as_ref()
+    }
+
     fn reconfigure(&self, scopes: &[String]) {
         if let Some(Some(config)) = self.syntax.get() {
             reconfigure_highlights(config, scopes);
@@ -424,6 +495,14 @@ fn rainbow_query(&self, lang: Language) -> Option<&RainbowQuery> {
         self.language(lang).rainbow_query(self)
     }
 
+    fn context_query(&self, lang: Language) -> Option<&ContextQuery> {
+        self.language(lang).context_query(self)
+    }
+
+    fn fold_query(&self, lang: Language) -> Option<&FoldQuery> {
+        self.language(lang).fold_query(self)
+    }
+
     pub fn language_server_configs(&self) -> &HashMap<String, LanguageServerConfiguration> {
         &self.language_server_configs
     }
@@ -510,7 +589,7 @@ fn language_for_path(&self, path: &Path) -> Option<Language> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Syntax {
     inner: tree_house::Syntax,
 }
@@ -627,6 +706,60 @@ pub fn tags<'a>(
         )
     }
 
+    /// Returns the first lines of the nodes captured by the `context.scm` queries that start
+    /// before `line` and contain it, for example the enclosing functions and classes. The lines
+    /// are sorted from the outermost to the innermost node.
+    pub fn context_lines(&self, source: RopeSlice, line: usize, loader: &Loader) -> Vec<usize> {
+        let byte = source.line_to_byte(line) as u32;
+        let mut query_iter = self.query_iter::<_, (), _>(
+            source,
+            |lang| loader.context_query(lang).map(|q| &q.query),
+            byte..byte + 1,
+        );
+
+        let mut lines = Vec::new();
+        while let Some(event) = query_iter.next() {
+            let QueryIterEvent::Match(mat) = event else {
+                continue;
+            };
+            let start_line = source.byte_to_line(mat.node.start_byte() as usize);
+            let end_byte = (mat.node.end_byte() as usize).min(source.len_bytes());
+            let end_line = source.byte_to_line(end_byte.saturating_sub(1));
+            if start_line < line && line <= end_line {
+                lines.push(start_line);
+            }
+        }
+        lines.sort_unstable();
+        lines.dedup();
+        lines
+    }
+
+    /// Returns the byte ranges of the nodes captured as `@fold` by the `folds.scm` queries,
+    /// including the ones of injected languages. Returns `None` if the root language has no
+    /// `folds.scm` query.
+    pub fn fold_ranges(&self, source: RopeSlice, loader: &Loader) -> Option<Vec<ops::Range<u32>>> {
+        loader.fold_query(self.root_language())?;
+        let mut query_iter = self.query_iter::<_, (), _>(
+            source,
+            |lang| loader.fold_query(lang).map(|q| &q.query),
+            ..,
+        );
+
+        let mut ranges = Vec::new();
+        while let Some(event) = query_iter.next() {
+            let QueryIterEvent::Match(mat) = event else {
+                continue;
+            };
+            let fold_query = loader
+                .fold_query(query_iter.current_language())
+                .expect("language must have a fold query to emit matches");
+            if Some(mat.capture) == fold_query.fold_capture {
+                ranges.push(mat.node.byte_range());
+            }
+        }
+        Some(ranges)
+    }
+
     pub fn rainbow_highlights(
         &self,
         source: RopeSlice,
@@ -1074,6 +1207,17 @@ pub struct TagQuery {
     pub query: Query,
 }
 
+#[derive(Debug)]
+pub struct ContextQuery {
+    query: Query,
+}
+
+#[derive(Debug)]
+pub struct FoldQuery {
+    query: Query,
+    fold_capture: Option<Capture>,
+}
+
 pub fn pretty_print_tree<W: fmt::Write>(fmt: &mut W, node: Node) -> fmt::Result {
     if node.child_count() == 0 {
         if node_is_visible(&node) {
@@ -1252,6 +1396,24 @@ fn test_textobject_queries() {
         // test("multiple_nodes_grouped", 1..37);
     }
 
+    #[test]
+    fn test_context_lines() {
+        let source = Rope::from_str(
+            "impl Foo {\n    fn foo() {\n        let a = 1;\n\n        let b = 2;\n    }\n}\n",
+        );
+        let text = source.slice(..);
+        let language = LOADER.language_for_name("rust").unwrap();
+        let syntax = Syntax::new(text, language, &LOADER).unwrap();
+
+        // the first lines of the impl and the function that contain the line
+        assert_eq!(syntax.context_lines(text, 4, &LOADER), vec![0, 1]);
+        assert_eq!(syntax.context_lines(text, 5, &LOADER), vec![0, 1]);
+        // a node is not the context of its own first line
+        assert_eq!(syntax.context_lines(text, 1, &LOADER), vec![0]);
+        assert!(syntax.context_lines(text, 0, &LOADER).is_empty());
+        assert!(syntax.context_lines(text, 7, &LOADER).is_empty());
+    }
+
     #[test]
     fn test_input_edits() {
         use tree_sitter::{InputEdit, Point};
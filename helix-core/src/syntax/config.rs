@@ -56,8 +56,14 @@ pub struct LanguageConfiguration {
     #[serde(default)]
     pub auto_format: bool,
 
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub formatter: Option<FormatterConfiguration>,
+    /// The external formatters of the language, run as a pipeline where each formatter
+    /// receives the output of the previous one.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "deserialize_formatters"
+    )]
+    pub formatter: Vec<FormatterConfiguration>,
 
     /// If set, overrides `editor.path-completion`.
     pub path_completion: Option<bool>,
@@ -321,6 +327,9 @@ pub enum LanguageServerFeature {
     InlayHints,
     DocumentColors,
     CallHierarchy,
+    TypeHierarchy,
+    SemanticTokens,
+    InlineCompletion,
 }
 
 impl Display for LanguageServerFeature {
@@ -348,6 +357,9 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             InlayHints => "inlay-hints",
             DocumentColors => "document-colors",
             CallHierarchy => "call-hierarchy",
+            TypeHierarchy => "type-hierarchy",
+            SemanticTokens => "semantic-tokens",
+            InlineCompletion => "inline-completion",
         };
         write!(f, "{feature}",)
     }
@@ -454,6 +466,26 @@ pub struct FormatterConfiguration {
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub args: Vec<String>,
+    #[serde(default)]
+    pub mode: FormatterMode,
+    /// Arguments appended to `args` to format only the selected lines, for example
+    /// `["--lines=%{selection_line_start}:%{selection_line_end}"]`. Formatters without range
+    /// arguments are given only the selected text when formatting a selection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range_args: Option<Vec<String>>,
+}
+
+/// How the text is passed to an external formatter.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FormatterMode {
+    /// The text is written to the formatter's stdin and the formatted text is read from its
+    /// stdout.
+    #[default]
+    Stdin,
+    /// The text is written to a temporary file whose path is passed as the last argument. The
+    /// formatter rewrites the file in place.
+    TempFile,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
@@ -605,6 +637,7 @@ pub struct SoftWrap {
 pub struct WordCompletion {
     pub enable: Option<bool>,
     pub trigger_length: Option<NonZeroU8>,
+    pub fallback: Option<bool>,
 }
 
 fn deserialize_regex<'de, D>(deserializer: D) -> Result<Option<rope::Regex>, D::Error>
@@ -625,6 +658,23 @@ fn deserialize_lsp_config<'de, D>(deserializer: D) -> Result<Option<serde_json::
         .transpose()
 }
 
+fn deserialize_formatters<'de, D>(deserializer: D) -> Result<Vec<FormatterConfiguration>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Formatters {
+        Single(FormatterConfiguration),
+        Pipeline(Vec<FormatterConfiguration>),
+    }
+
+    Ok(match Formatters::deserialize(deserializer)? {
+        Formatters::Single(formatter) => vec![formatter],
+        Formatters::Pipeline(formatters) => formatters,
+    })
+}
+
 fn deserialize_tab_width<'de, D>(deserializer: D) -> Result<usize, D::Error>
 where
     D: serde::Deserializer<'de>,
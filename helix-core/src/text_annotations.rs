@@ -79,6 +79,31 @@ pub fn new(char_idx: usize, grapheme: impl Into<Tendril>) -> Self {
     }
 }
 
+/// A fold hides the lines of the document between `start` and `end`.
+///
+/// Folds always span whole lines: `start` is the first char of the first
+/// hidden line and `end` is the first char of the line after the last hidden
+/// line (or the end of the document). The line before `start` stays visible
+/// and acts as the header of the fold. When the document is rendered the
+/// hidden text is skipped entirely, the header line is followed directly by
+/// the line at `end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fold {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Fold {
+    pub fn new(start: usize, end: usize) -> Self {
+        debug_assert!(start < end);
+        Self { start, end }
+    }
+
+    pub fn contains(&self, char_idx: usize) -> bool {
+        self.start <= char_idx && char_idx < self.end
+    }
+}
+
 /// Line annotations allow inserting virtual text lines between normal text
 /// lines.  These lines can be filled with text in the rendering code as their
 /// contents have no effect beyond visual appearance.
@@ -279,6 +304,7 @@ pub struct TextAnnotations<'a> {
     inline_annotations: Vec<Layer<'a, InlineAnnotation, Option<Highlight>>>,
     overlays: Vec<Layer<'a, Overlay, Option<Highlight>>>,
     line_annotations: Vec<(Cell<usize>, RawBox<dyn LineAnnotation + 'a>)>,
+    folds: &'a [Fold],
 }
 
 impl Debug for TextAnnotations<'_> {
@@ -286,6 +312,7 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("TextAnnotations")
             .field("inline_annotations", &self.inline_annotations)
             .field("overlays", &self.overlays)
+            .field("folds", &self.folds)
             .finish_non_exhaustive()
     }
 }
@@ -364,6 +391,38 @@ pub fn add_line_annotation(&mut self, layer: Box<dyn LineAnnotation + 'a>) -> &m
         self
     }
 
+    /// Set the folded ranges of the document.
+    ///
+    /// The folds **must be sorted** by their `start` and **must not overlap**.
+    pub fn set_folds(&mut self, folds: &'a [Fold]) -> &mut Self {
+        self.folds = folds;
+        self
+    }
+
+    pub fn has_folds(&self) -> bool {
+        !self.folds.is_empty()
+    }
+
+    /// Returns the fold that hides `char_idx`, if any.
+    pub fn fold_containing(&self, char_idx: usize) -> Option<&Fold> {
+        let i = self.folds.partition_point(|fold| fold.end <= char_idx);
+        self.folds.get(i).filter(|fold| fold.contains(char_idx))
+    }
+
+    /// Returns the fold whose hidden range starts at `char_idx`, if any.
+    pub(crate) fn fold_starting_at(&self, char_idx: usize) -> Option<&Fold> {
+        let i = self.folds.partition_point(|fold| fold.start < char_idx);
+        self.folds.get(i).filter(|fold| fold.start == char_idx)
+    }
+
+    /// Skips the inline annotations and overlays that are anchored
+    /// inside of a fold ending at `char_idx`. Line annotations are skipped
+    /// by the `skip_concealed_anchors` mechanism instead.
+    pub(crate) fn skip_folded(&self, char_idx: usize) {
+        reset_pos(&self.inline_annotations, char_idx, |annot| annot.char_idx);
+        reset_pos(&self.overlays, char_idx, |annot| annot.char_idx);
+    }
+
     /// Removes all line annotations, useful for vertical motions
     /// so that virtual text lines are automatically skipped.
     pub fn clear_line_annotations(&mut self) {
@@ -252,6 +252,24 @@ fn textobject_pair_surround_impl(
         .unwrap_or(range)
 }
 
+/// Selects the region that contains the cursor of the range, like the range of a diagnostic or
+/// a search match. `regions` must be sorted by their start. Inside and around select the same
+/// text. The range is returned unchanged if no region contains the cursor.
+pub fn textobject_region(
+    text: RopeSlice,
+    range: Range,
+    regions: impl IntoIterator<Item = std::ops::Range<usize>>,
+) -> Range {
+    let cursor = range.cursor(text);
+    regions
+        .into_iter()
+        .take_while(|region| region.start <= cursor)
+        .find(|region| cursor < region.end || region.is_empty() && cursor == region.start)
+        .map_or(range, |region| {
+            Range::new(region.start, region.end).with_direction(range.direction())
+        })
+}
+
 /// Transform the given range to select text objects based on tree-sitter.
 /// `object_name` is a query capture base name like "function", "class", etc.
 /// `slice_tree` is the tree-sitter node corresponding to given text slice.
@@ -415,6 +433,26 @@ fn test_textobject_word() {
         }
     }
 
+    #[test]
+    fn test_textobject_region() {
+        let regions = [2..5, 4..9, 12..12];
+        let tests = [
+            ("ab#[c|]#defghijklmn", "ab#[cde|]#fghijklmn"),
+            ("abcde#[f|]#ghijklmn", "abcd#[efghi|]#jklmn"),
+            ("abcdefghij#[k|]#lmn", "abcdefghij#[k|]#lmn"),
+            ("abcdefghijkl#[m|]#n", "abcdefghijkl#[|]#mn"),
+        ];
+
+        for (before, expected) in tests {
+            let (s, selection) = crate::test::print(before);
+            let text = Rope::from(s.as_str());
+            let selection = selection
+                .transform(|r| textobject_region(text.slice(..), r, regions.iter().cloned()));
+            let actual = crate::test::plain(s.as_ref(), &selection);
+            assert_eq!(actual, expected, "\nbefore: `{:?}`", before);
+        }
+    }
+
     #[test]
     fn test_textobject_paragraph_inside_single() {
         let tests = [
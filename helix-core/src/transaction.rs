@@ -73,8 +73,8 @@ pub fn sticky(self) -> bool {
 pub struct ChangeSet {
     pub(crate) changes: Vec<Operation>,
     /// The required document length. Will refuse to apply changes unless it matches.
-    len: usize,
-    len_after: usize,
+    pub(crate) len: usize,
+    pub(crate) len_after: usize,
 }
 
 impl ChangeSet {
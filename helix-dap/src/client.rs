@@ -47,6 +47,8 @@ pub struct Client {
     pub quirks: DebuggerQuirks,
     /// The config which was used to start this debugger.
     pub config: Option<DebugAdapterConfig>,
+    /// The name of the template or launch configuration that started the session.
+    pub name: Option<String>,
 }
 
 impl Client {
@@ -95,6 +97,7 @@ pub fn streams(
             active_frame: None,
             quirks: DebuggerQuirks::default(),
             config: None,
+            name: None,
         };
 
         tokio::spawn(Self::recv(id, server_rx, client_tx));
@@ -561,6 +564,22 @@ pub async fn eval(
         self.request::<requests::Evaluate>(args).await
     }
 
+    /// Evaluates a watch expression, which adapters may evaluate without side effects.
+    pub async fn watch(
+        &self,
+        expression: String,
+        frame_id: Option<usize>,
+    ) -> Result<requests::EvaluateResponse> {
+        let args = requests::EvaluateArguments {
+            expression,
+            frame_id,
+            context: Some("watch".to_owned()),
+            format: None,
+        };
+
+        self.request::<requests::Evaluate>(args).await
+    }
+
     pub fn set_exception_breakpoints(
         &self,
         filters: Vec<String>,
@@ -0,0 +1,265 @@
+//! Debug configurations from a VS Code style `.vscode/launch.json`, see
+//! <https://code.visualstudio.com/docs/debugtest/debugging-configuration>.
+
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf, MAIN_SEPARATOR_STR};
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::Result;
+
+/// The variable that asks the user for the process to attach to.
+pub const PICK_PROCESS: &str = "${command:pickProcess}";
+
+/// A configuration of `launch.json`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LaunchConfiguration {
+    pub name: String,
+    /// The type of the debugger, matched against the names of the configured debug adapters.
+    #[serde(rename = "type")]
+    pub ty: String,
+    /// `launch` or `attach`.
+    pub request: String,
+    /// The port of a debug adapter that is already running, which is connected to instead of
+    /// starting the adapter.
+    #[serde(rename = "debugServer")]
+    pub debug_server: Option<u16>,
+    /// The other attributes, which are only understood by the debug adapter.
+    #[serde(flatten)]
+    pub attributes: Map<String, Value>,
+}
+
+impl LaunchConfiguration {
+    /// The arguments of the launch or attach request: the whole configuration like VS Code
+    /// sends it, with the variables replaced.
+    pub fn arguments(&self, variables: &Variables) -> Value {
+        let mut arguments = self.attributes.clone();
+        arguments.insert("name".into(), self.name.clone().into());
+        arguments.insert("type".into(), self.ty.clone().into());
+        arguments.insert("request".into(), self.request.clone().into());
+        let mut arguments = Value::Object(arguments);
+        variables.substitute(&mut arguments);
+        arguments
+    }
+
+    /// Whether the configuration asks for the process to attach to with [PICK_PROCESS].
+    pub fn picks_process(&self) -> bool {
+        fn contains(value: &Value) -> bool {
+            match value {
+                Value::String(string) => string.contains(PICK_PROCESS),
+                Value::Array(values) => values.iter().any(contains),
+                Value::Object(map) => map.values().any(contains),
+                _ => false,
+            }
+        }
+        self.attributes.values().any(contains)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct LaunchFile {
+    #[serde(default)]
+    configurations: Vec<LaunchConfiguration>,
+}
+
+/// Parses the configurations of a `launch.json`, which may contain comments and trailing commas.
+pub fn parse(text: &str) -> Result<Vec<LaunchConfiguration>> {
+    let file: LaunchFile = serde_json::from_str(&strip_comments(text))?;
+    Ok(file.configurations)
+}
+
+/// Reads the configurations of the `.vscode/launch.json` of `workspace`. A missing file is not an
+/// error.
+pub fn load(workspace: &Path) -> Result<Vec<LaunchConfiguration>> {
+    let path = workspace.join(".vscode").join("launch.json");
+    match std::fs::read_to_string(path) {
+        Ok(text) => parse(&text),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Removes the comments and trailing commas of JSONC, keeping the strings intact.
+fn strip_comments(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                output.push(c);
+                while let Some(c) = chars.next() {
+                    output.push(c);
+                    match c {
+                        '\\' => output.extend(chars.next()),
+                        '"' => break,
+                        _ => (),
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => while chars.next_if(|&c| c != '\n').is_some() {},
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last = ' ';
+                for c in chars.by_ref() {
+                    if last == '*' && c == '/' {
+                        break;
+                    }
+                    last = c;
+                }
+            }
+            ']' | '}' => {
+                // drop the trailing comma before the closing bracket
+                let trimmed = output.trim_end().len();
+                if output[..trimmed].ends_with(',') {
+                    output.truncate(trimmed - 1);
+                }
+                output.push(c);
+            }
+            c => output.push(c),
+        }
+    }
+    output
+}
+
+/// The values of the variables of the configurations, like `${workspaceFolder}`. Unknown
+/// variables are left as they are.
+#[derive(Debug, Default, Clone)]
+pub struct Variables {
+    pub workspace_folder: PathBuf,
+    pub cwd: PathBuf,
+    /// The file of the current document.
+    pub file: Option<PathBuf>,
+    /// The 1-based line of the cursor.
+    pub line_number: Option<usize>,
+    pub selected_text: Option<String>,
+    /// The process picked for [PICK_PROCESS].
+    pub process_id: Option<u32>,
+}
+
+impl Variables {
+    fn get(&self, name: &str) -> Option<String> {
+        if let Some(name) = name.strip_prefix("env:") {
+            return Some(std::env::var(name).unwrap_or_default());
+        }
+        let path = |path: &Path| path.to_string_lossy().into_owned();
+        let file = || self.file.as_deref();
+        let relative =
+            || file().map(|file| file.strip_prefix(&self.workspace_folder).unwrap_or(file));
+        let value = match name {
+            "workspaceFolder" | "workspaceRoot" => path(&self.workspace_folder),
+            "workspaceFolderBasename" => path(Path::new(self.workspace_folder.file_name()?)),
+            "cwd" => path(&self.cwd),
+            "userHome" => path(&helix_stdx::path::expand_tilde(Path::new("~"))),
+            "file" => path(file()?),
+            "fileBasename" => path(Path::new(file()?.file_name()?)),
+            "fileBasenameNoExtension" => path(Path::new(file()?.file_stem()?)),
+            "fileExtname" => format!(".{}", file()?.extension()?.to_string_lossy()),
+            "fileDirname" => path(file()?.parent()?),
+            "relativeFile" => path(relative()?),
+            "relativeFileDirname" => path(relative()?.parent()?),
+            "lineNumber" => self.line_number?.to_string(),
+            "selectedText" => self.selected_text.clone()?,
+            "pathSeparator" | "/" => MAIN_SEPARATOR_STR.to_string(),
+            "command:pickProcess" => self.process_id?.to_string(),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Replaces the variables in the strings of `value`. A string that is just [PICK_PROCESS]
+    /// becomes the number of the process.
+    pub fn substitute(&self, value: &mut Value) {
+        match value {
+            Value::String(string) if string == PICK_PROCESS => {
+                if let Some(pid) = self.process_id {
+                    *value = pid.into();
+                }
+            }
+            Value::String(string) => *string = self.substitute_str(string),
+            Value::Array(values) => values.iter_mut().for_each(|value| self.substitute(value)),
+            Value::Object(map) => map.values_mut().for_each(|value| self.substitute(value)),
+            _ => (),
+        }
+    }
+
+    fn substitute_str(&self, mut rest: &str) -> String {
+        let mut output = String::with_capacity(rest.len());
+        while let Some(start) = rest.find("${") {
+            output.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let value = rest
+                .find('}')
+                .and_then(|end| Some((end, self.get(&rest[2..end])?)));
+            match value {
+                Some((end, value)) => {
+                    output.push_str(&value);
+                    rest = &rest[end + 1..];
+                }
+                None => {
+                    output.push_str("${");
+                    rest = &rest[2..];
+                }
+            }
+        }
+        output.push_str(rest);
+        output
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn launch_configurations() {
+        let text = r#"{
+            // the version of the format
+            "version": "0.2.0",
+            "configurations": [
+                {
+                    "type": "lldb-dap",
+                    "request": "launch",
+                    "name": "Debug // tests",
+                    "program": "${workspaceFolder}/target/debug/${fileBasenameNoExtension}",
+                    "args": ["--line", "${lineNumber}", "${unknown}", "${env:HELIX_DAP_UNSET}"],
+                },
+                /* attach to a running process */
+                {
+                    "type": "lldb-dap",
+                    "request": "attach",
+                    "name": "Attach",
+                    "pid": "${command:pickProcess}",
+                    "debugServer": 4711,
+                },
+            ],
+        }"#;
+        let configurations = parse(text).unwrap();
+        assert_eq!(configurations.len(), 2);
+        assert_eq!(configurations[0].name, "Debug // tests");
+        assert!(!configurations[0].picks_process());
+        assert!(configurations[1].picks_process());
+        assert_eq!(configurations[1].debug_server, Some(4711));
+
+        let variables = Variables {
+            workspace_folder: PathBuf::from("/project"),
+            file: Some(PathBuf::from("/project/src/main.rs")),
+            line_number: Some(12),
+            process_id: Some(42),
+            ..Default::default()
+        };
+        assert_eq!(
+            configurations[0].arguments(&variables),
+            json!({
+                "type": "lldb-dap",
+                "request": "launch",
+                "name": "Debug // tests",
+                "program": "/project/target/debug/main",
+                "args": ["--line", "12", "${unknown}", ""],
+            })
+        );
+        assert_eq!(configurations[1].arguments(&variables)["pid"], json!(42));
+    }
+}
@@ -0,0 +1,96 @@
+//! The running processes that a debugger can attach to.
+
+use crate::Result;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Process {
+    pub pid: u32,
+    /// The command line of the process, or its name if the command line isn't available.
+    pub command: String,
+}
+
+/// Lists the running processes of the system, except for the editor itself.
+pub fn processes() -> Result<Vec<Process>> {
+    let mut processes = list()?;
+    processes.retain(|process| process.pid != std::process::id());
+    Ok(processes)
+}
+
+#[cfg(target_os = "linux")]
+fn list() -> Result<Vec<Process>> {
+    let mut processes = Vec::new();
+    for entry in std::fs::read_dir("/proc")? {
+        let entry = entry?;
+        let Some(pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.parse().ok())
+        else {
+            continue;
+        };
+        // processes may exit while they are listed
+        let command = match std::fs::read(entry.path().join("cmdline")) {
+            Ok(cmdline) if !cmdline.is_empty() => cmdline
+                .split(|&b| b == 0)
+                .filter(|arg| !arg.is_empty())
+                .map(String::from_utf8_lossy)
+                .collect::<Vec<_>>()
+                .join(" "),
+            // kernel threads have no command line
+            Ok(_) => match std::fs::read_to_string(entry.path().join("comm")) {
+                Ok(comm) => format!("[{}]", comm.trim_end()),
+                Err(_) => continue,
+            },
+            Err(_) => continue,
+        };
+        processes.push(Process { pid, command });
+    }
+    processes.sort_by_key(|process| process.pid);
+    Ok(processes)
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn list() -> Result<Vec<Process>> {
+    let output = run(std::process::Command::new("ps").args(["-A", "-o", "pid=,command="]))?;
+    let processes = output
+        .lines()
+        .filter_map(|line| {
+            let (pid, command) = line.trim_start().split_once(' ')?;
+            Some(Process {
+                pid: pid.parse().ok()?,
+                command: command.trim().to_string(),
+            })
+        })
+        .collect();
+    Ok(processes)
+}
+
+#[cfg(windows)]
+fn list() -> Result<Vec<Process>> {
+    let output = run(std::process::Command::new("tasklist").args(["/fo", "csv", "/nh"]))?;
+    // "name","pid","session name","session","memory"
+    let processes = output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split("\",\"");
+            let command = fields.next()?.trim_start_matches('"').to_string();
+            let pid = fields.next()?.parse().ok()?;
+            Some(Process { pid, command })
+        })
+        .collect();
+    Ok(processes)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn run(command: &mut std::process::Command) -> Result<String> {
+    use anyhow::{anyhow, Context};
+
+    let output = command
+        .output()
+        .with_context(|| format!("failed to run {:?}", command.get_program()))?;
+    if !output.status.success() {
+        let err = anyhow!("{:?} failed: {}", command.get_program(), output.status);
+        return Err(err.into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
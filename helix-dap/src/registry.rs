@@ -11,10 +11,8 @@
 /// plus showing the heirarcihical nature betweeen them
 pub struct Registry {
     inner: SlotMap<DebugAdapterId, Client>,
-    /// The active debugger client
-    ///
-    /// TODO: You can have multiple active debuggers, so the concept of a single active debugger
-    /// may need to be changed
+    /// The active debugger client, the one that the debugging commands and the UI act on. Each
+    /// client keeps its own threads and stack frames, switching clients switches between them.
     current_client_id: Option<DebugAdapterId>,
     /// A stream of incoming messages from all debuggers
     pub incoming: SelectAll<UnboundedReceiverStream<(DebugAdapterId, Payload)>>,
@@ -58,8 +56,30 @@ pub fn start_client(
         })
     }
 
+    /// Removes a client. If it was the active client, another running client becomes active.
     pub fn remove_client(&mut self, id: DebugAdapterId) {
         self.inner.remove(id);
+        self.deactivate_client(id);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (DebugAdapterId, &Client)> {
+        self.inner.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (DebugAdapterId, &mut Client)> {
+        self.inner.iter_mut()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub fn active_client_id(&self) -> Option<DebugAdapterId> {
+        self.current_client_id
     }
 
     pub fn get_client(&self, id: DebugAdapterId) -> Option<&Client> {
@@ -91,6 +111,13 @@ pub fn unset_active_client(&mut self) {
         self.current_client_id = None;
     }
 
+    /// Makes another client active if `id` is the active client, or none if it is the only one.
+    pub fn deactivate_client(&mut self, id: DebugAdapterId) {
+        if self.current_client_id == Some(id) {
+            self.current_client_id = self.inner.keys().find(|&other| other != id);
+        }
+    }
+
     pub fn current_stack_frame(&self) -> Option<&StackFrame> {
         self.get_active_client()
             .and_then(|debugger| debugger.current_stack_frame())
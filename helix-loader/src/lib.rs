@@ -1,5 +1,7 @@
 pub mod config;
 pub mod grammar;
+pub mod tasks;
+pub mod watcher;
 pub mod workspace_trust;
 
 use helix_stdx::{env::current_working_dir, path};
@@ -140,6 +142,16 @@ pub fn data_dir() -> PathBuf {
     path
 }
 
+/// The directory for state that should persist between restarts but is not important enough
+/// for the data directory, like histories. Falls back to the data directory on platforms
+/// without a dedicated state directory.
+pub fn state_dir() -> PathBuf {
+    let strategy = choose_base_strategy().expect("Unable to find the state directory!");
+    let mut path = strategy.state_dir().unwrap_or_else(|| strategy.data_dir());
+    path.push("helix");
+    path
+}
+
 pub fn config_file() -> PathBuf {
     CONFIG_FILE.get().map(|path| path.to_path_buf()).unwrap()
 }
@@ -156,10 +168,51 @@ pub fn workspace_lang_config_file() -> PathBuf {
     find_workspace().0.join(".helix").join("languages.toml")
 }
 
+/// Returns the `.helix/config.toml` files in the directories between the workspace and `path`,
+/// ordered from the outermost to the innermost directory. The config file of the workspace
+/// itself is not included, it is loaded together with the global config. Like the config of the
+/// workspace, these files are only returned for trusted workspaces.
+pub fn directory_config_files(path: &Path, trust: workspace_trust::TrustStatus) -> Vec<PathBuf> {
+    directory_config_files_in(&find_workspace().0, path, trust)
+}
+
+fn directory_config_files_in(
+    workspace: &Path,
+    path: &Path,
+    trust: workspace_trust::TrustStatus,
+) -> Vec<PathBuf> {
+    let workspace_trust::TrustStatus::Trusted = trust else {
+        return Vec::new();
+    };
+    let Some(dir) = path.parent() else {
+        return Vec::new();
+    };
+    let mut files: Vec<_> = dir
+        .ancestors()
+        .take_while(|ancestor| *ancestor != workspace && ancestor.starts_with(workspace))
+        .map(|ancestor| ancestor.join(".helix").join("config.toml"))
+        .filter(|file| file.is_file())
+        .collect();
+    files.reverse();
+    files
+}
+
 pub fn lang_config_file() -> PathBuf {
     config_dir().join("languages.toml")
 }
 
+pub fn snippets_file() -> PathBuf {
+    config_dir().join("snippets.toml")
+}
+
+pub fn tasks_file() -> PathBuf {
+    config_dir().join("tasks.toml")
+}
+
+pub fn workspace_tasks_file() -> PathBuf {
+    find_workspace().0.join(".helix").join("tasks.toml")
+}
+
 pub fn default_log_file() -> PathBuf {
     cache_dir().join("helix.log")
 }
@@ -172,6 +225,42 @@ pub fn workspace_exclude_file() -> PathBuf {
     data_dir().join("excluded_workspaces")
 }
 
+pub fn session_dir() -> PathBuf {
+    data_dir().join("sessions")
+}
+
+pub fn command_history_file() -> PathBuf {
+    state_dir().join("command_history.json")
+}
+
+pub fn marks_file() -> PathBuf {
+    state_dir().join("marks.json")
+}
+
+pub fn macros_file() -> PathBuf {
+    state_dir().join("macros.json")
+}
+
+pub fn undo_dir() -> PathBuf {
+    state_dir().join("undo")
+}
+
+pub fn recovery_dir() -> PathBuf {
+    state_dir().join("recovery")
+}
+
+/// Replaces the contents of a state file like [marks_file] at once by writing them to a
+/// temporary file first and renaming it. Other instances that read the file at the same time
+/// either see the old or the new contents.
+pub fn write_state_file(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    std::fs::create_dir_all(dir)?;
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+    std::io::Write::write_all(&mut file, contents)?;
+    file.persist(path)?;
+    Ok(())
+}
+
 /// Merge two TOML documents, merging values from `right` onto `left`
 ///
 /// `merge_depth` sets the nesting depth up to which values are merged instead
@@ -294,6 +383,46 @@ fn ensure_parent_dir(path: &Path) {
     }
 }
 
+#[cfg(test)]
+mod directory_config_tests {
+    use std::fs;
+
+    use super::directory_config_files_in;
+    use crate::workspace_trust::TrustStatus;
+
+    #[test]
+    fn directory_config_files_are_ordered_and_trust_gated() {
+        let workspace = tempfile::tempdir().unwrap();
+        let workspace = workspace.path();
+        let file = workspace.join("a/b/c/main.rs");
+        for dir in ["", "a", "a/b/c"] {
+            let dir = workspace.join(dir).join(".helix");
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("config.toml"), "").unwrap();
+        }
+        fs::write(&file, "").unwrap();
+
+        // the config of the workspace itself is not included, closer files come last
+        assert_eq!(
+            directory_config_files_in(workspace, &file, TrustStatus::Trusted),
+            [
+                workspace.join("a/.helix/config.toml"),
+                workspace.join("a/b/c/.helix/config.toml"),
+            ]
+        );
+        assert!(directory_config_files_in(workspace, &file, TrustStatus::Untrusted).is_empty());
+
+        // files outside of the workspace don't get directory config
+        let outside = tempfile::tempdir().unwrap();
+        let outside_file = outside.path().join("main.rs");
+        fs::create_dir_all(outside.path().join(".helix")).unwrap();
+        fs::write(outside.path().join(".helix/config.toml"), "").unwrap();
+        assert!(
+            directory_config_files_in(workspace, &outside_file, TrustStatus::Trusted).is_empty()
+        );
+    }
+}
+
 #[cfg(test)]
 mod merge_toml_tests {
     use std::str;
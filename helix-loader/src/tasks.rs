@@ -0,0 +1,81 @@
+//! Shell tasks defined in `tasks.toml` in the config directory and in the `.helix` directory
+//! of the workspace.
+//!
+//! ```toml
+//! [build]
+//! command = "cargo build"
+//! problem-matchers = ['^(?P<file>[^:\s]+):(?P<line>\d+):(?P<column>\d+): (?P<severity>\w+): (?P<message>.*)$']
+//! ```
+
+use std::{collections::BTreeMap, io::ErrorKind, path::PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::workspace_trust::{quick_query_workspace, TrustStatus};
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Task {
+    /// The command, run with the `shell` of the editor config.
+    pub command: String,
+    /// The working directory of the command, relative to the workspace. Defaults to the
+    /// workspace.
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+    /// Regexes that turn lines of the output into problems. The `file` and `line` groups are
+    /// required, `column`, `severity` and `message` are optional.
+    #[serde(default)]
+    pub problem_matchers: Vec<String>,
+}
+
+pub fn parse_tasks(toml: &str) -> Result<BTreeMap<String, Task>> {
+    Ok(toml::from_str(toml)?)
+}
+
+/// Loads the tasks of the config directory and, in trusted workspaces, the tasks of the
+/// workspace, which replace global tasks with the same name.
+pub fn load_tasks(insecure: bool) -> Result<BTreeMap<String, Task>> {
+    let mut files = vec![crate::tasks_file()];
+    if let TrustStatus::Trusted = quick_query_workspace(insecure) {
+        files.push(crate::workspace_tasks_file());
+    }
+
+    let mut tasks = BTreeMap::new();
+    for file in files {
+        match std::fs::read_to_string(&file) {
+            Ok(toml) => tasks
+                .extend(parse_tasks(&toml).with_context(|| format!("failed to load {file:?}"))?),
+            Err(err) if err.kind() == ErrorKind::NotFound => (),
+            Err(err) => return Err(err).with_context(|| format!("failed to read {file:?}")),
+        }
+    }
+    Ok(tasks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_task_definitions() {
+        let tasks = parse_tasks(
+            r#"
+            [build]
+            command = "make"
+            problem-matchers = ['^(?P<file>[^:]+):(?P<line>\d+): (?P<message>.*)$']
+
+            [test]
+            command = "cargo test"
+            cwd = "crates/core"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks["build"].command, "make");
+        assert_eq!(tasks["build"].problem_matchers.len(), 1);
+        assert_eq!(tasks["test"].cwd, Some(PathBuf::from("crates/core")));
+
+        assert!(parse_tasks("[build]\ncmd = \"make\"").is_err());
+    }
+}
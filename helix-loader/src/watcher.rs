@@ -0,0 +1,103 @@
+//! Watches files for changes by polling their modification times.
+//!
+//! Polling is used instead of the notification APIs of the operating systems because only a
+//! handful of files are watched, which is cheap to check and behaves the same on all platforms.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+type WatchedFiles = HashMap<PathBuf, Option<SystemTime>>;
+
+/// Watches a set of files (or directories) on a background thread. The thread exits when the
+/// watcher is dropped.
+#[derive(Debug)]
+pub struct Watcher {
+    files: Arc<Mutex<WatchedFiles>>,
+}
+
+impl Watcher {
+    /// Spawns a thread that checks the watched files every `interval` and calls `on_change`
+    /// with the files that were modified, created or removed since the last check.
+    pub fn new(interval: Duration, on_change: impl Fn(Vec<PathBuf>) + Send + 'static) -> Self {
+        let files = Arc::new(Mutex::new(WatchedFiles::new()));
+        let weak_files = Arc::downgrade(&files);
+        thread::Builder::new()
+            .name("file-watcher".to_string())
+            .spawn(move || loop {
+                thread::sleep(interval);
+                let Some(files) = weak_files.upgrade() else {
+                    break;
+                };
+                let changed = poll(&mut files.lock().unwrap());
+                drop(files);
+                if !changed.is_empty() {
+                    on_change(changed);
+                }
+            })
+            .expect("failed to spawn the file watcher thread");
+        Self { files }
+    }
+
+    /// Replaces the watched files. Files that were already watched keep the modification time
+    /// of the last check, so a change that happened since then is still reported.
+    pub fn watch(&self, paths: impl IntoIterator<Item = PathBuf>) {
+        let mut files = self.files.lock().unwrap();
+        let mut watched = WatchedFiles::new();
+        for path in paths {
+            let last_modified = files.remove(&path).unwrap_or_else(|| modified(&path));
+            watched.insert(path, last_modified);
+        }
+        *files = watched;
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Updates the modification times of `files` and returns the files that changed.
+fn poll(files: &mut WatchedFiles) -> Vec<PathBuf> {
+    files
+        .iter_mut()
+        .filter_map(|(path, last_modified)| {
+            let modified = modified(path);
+            if modified == *last_modified {
+                return None;
+            }
+            *last_modified = modified;
+            Some(path.clone())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn poll_reports_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("theme.toml");
+        let missing = dir.path().join("missing.toml");
+
+        let mut files = WatchedFiles::new();
+        files.insert(file.clone(), None);
+        files.insert(missing.clone(), None);
+
+        fs::write(&file, "inherits = \"default\"").unwrap();
+        assert_eq!(poll(&mut files), vec![file.clone()]);
+        assert!(poll(&mut files).is_empty());
+
+        fs::remove_file(&file).unwrap();
+        assert_eq!(poll(&mut files), vec![file]);
+        assert_eq!(files[&missing], None);
+    }
+}
@@ -104,15 +104,23 @@ fn write_exclusion_to_file(&self) {
 
     /// Mark current workspace trusted
     pub fn trust_workspace(&mut self) {
-        let workspace = crate::find_workspace().0;
-        self.trusted.insert(workspace);
-        self.write_trust_to_file();
+        self.trust_path(crate::find_workspace().0);
     }
 
     /// Remove trusted mark from current workspace
     pub fn untrust_workspace(&mut self) {
-        let workspace = crate::find_workspace().0;
-        self.trusted.remove(&workspace);
+        self.untrust_path(&crate::find_workspace().0);
+    }
+
+    /// Mark `workspace` trusted, used for additional workspace roots
+    pub fn trust_path(&mut self, workspace: PathBuf) {
+        self.trusted.insert(workspace);
+        self.write_trust_to_file();
+    }
+
+    /// Remove trusted mark from `workspace`
+    pub fn untrust_path(&mut self, workspace: &Path) {
+        self.trusted.remove(workspace);
         self.write_trust_to_file();
     }
 
@@ -140,11 +148,15 @@ pub enum TrustUntrustStatus {
 }
 
 pub fn quick_query_workspace(insecure: bool) -> TrustStatus {
+    query_workspace(&crate::find_workspace().0, insecure)
+}
+
+/// Like [quick_query_workspace] but for any `workspace`, such as an additional workspace root.
+pub fn query_workspace(workspace: &Path, insecure: bool) -> TrustStatus {
     if insecure {
         return TrustStatus::Trusted;
     }
 
-    let workspace = crate::find_workspace().0;
     match fs::read_to_string(workspace_trust_file()) {
         Ok(workspace_trust_file) => {
             for line in workspace_trust_file.split('\n') {
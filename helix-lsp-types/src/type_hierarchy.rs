@@ -24,6 +24,13 @@ pub struct TypeHierarchyRegistrationOptions {
     pub static_registration_options: StaticRegistrationOptions,
 }
 
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum TypeHierarchyServerCapabilities {
+    Options(TypeHierarchyOptions),
+    RegistrationOptions(TypeHierarchyRegistrationOptions),
+}
+
 #[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
 pub struct TypeHierarchyPrepareParams {
     #[serde(flatten)]
@@ -1,6 +1,6 @@
 use crate::{
     file_operations::FileOperationsInterest,
-    find_lsp_workspace, jsonrpc,
+    find_doc_workspace, find_lsp_workspace, jsonrpc,
     transport::{Payload, Transport},
     Call, Error, LanguageServerId, OffsetEncoding, Result,
 };
@@ -12,12 +12,10 @@
     WorkspaceFolder, WorkspaceFoldersChangeEvent,
 };
 use helix_core::{
-    find_workspace,
     syntax::config::{LanguageServerFeature, RootMarkers},
     ChangeSet, Rope,
 };
 use helix_loader::VERSION_AND_GIT_HASH;
-use helix_stdx::path;
 use parking_lot::Mutex;
 use serde::Deserialize;
 use serde_json::Value;
@@ -76,10 +74,10 @@ pub fn try_add_doc(
         root_markers: &RootMarkers,
         manual_roots: &[PathBuf],
         doc_path: Option<&std::path::Path>,
+        workspace_roots: &[PathBuf],
         may_support_workspace: bool,
     ) -> bool {
-        let (workspace, workspace_is_cwd) = find_workspace();
-        let workspace = path::normalize(workspace);
+        let (workspace, workspace_is_cwd) = find_doc_workspace(doc_path, workspace_roots);
         let root = find_lsp_workspace(
             doc_path
                 .and_then(|x| x.parent().and_then(|x| x.to_str()))
@@ -99,7 +97,7 @@ pub fn try_add_doc(
                     .lock()
                     .iter()
                     .any(|workspace| &workspace.uri == root_uri)
-            })
+            }) && self.supports_workspace_folders()
         {
             // workspace URI is already registered so we can use this client
             return true;
@@ -170,9 +168,16 @@ fn add_workspace_folder(
         };
 
         // server supports workspace folders, let's add the new root to the list
-        self.workspace_folders
-            .lock()
-            .push(workspace_for_uri(root_uri.clone()));
+        {
+            let mut workspace_folders = self.workspace_folders.lock();
+            if workspace_folders
+                .iter()
+                .any(|workspace| workspace.uri == root_uri)
+            {
+                return;
+            }
+            workspace_folders.push(workspace_for_uri(root_uri.clone()));
+        }
         if Some(&OneOf::Left(false)) == change_notifications {
             // server specifically opted out of DidWorkspaceChange notifications
             // let's assume the server will request the workspace folders itself
@@ -182,6 +187,63 @@ fn add_workspace_folder(
         self.did_change_workspace(vec![workspace_for_uri(root_uri)], Vec::new())
     }
 
+    fn workspace_folders_capabilities(&self) -> Option<&lsp::WorkspaceFoldersServerCapabilities> {
+        self.capabilities
+            .get()?
+            .workspace
+            .as_ref()?
+            .workspace_folders
+            .as_ref()
+            .filter(|cap| cap.supported.unwrap_or(false))
+    }
+
+    /// Whether the server has finished initializing and handles multiple workspace folders.
+    ///
+    /// Servers that don't support workspace folders ignore all folders but the root they
+    /// were started in, even though all editor workspace roots are sent during initialization.
+    fn supports_workspace_folders(&self) -> bool {
+        self.workspace_folders_capabilities().is_some()
+    }
+
+    /// Adds an editor workspace root to the workspace folders of the server.
+    pub fn add_workspace_root(&self, root: &Path) {
+        let Some(caps) = self.workspace_folders_capabilities() else {
+            return;
+        };
+        self.add_workspace_folder(
+            lsp::Url::from_file_path(root).ok(),
+            caps.change_notifications.as_ref(),
+        );
+    }
+
+    /// Removes an editor workspace root from the workspace folders of the server.
+    ///
+    /// The root the server was started in is never removed.
+    pub fn remove_workspace_root(&self, root: &Path) {
+        let Some(caps) = self.workspace_folders_capabilities() else {
+            return;
+        };
+        let Ok(uri) = lsp::Url::from_file_path(root) else {
+            return;
+        };
+        if self.root_uri.as_ref() == Some(&uri) {
+            return;
+        }
+        let removed = {
+            let mut workspace_folders = self.workspace_folders.lock();
+            let Some(idx) = workspace_folders
+                .iter()
+                .position(|workspace| workspace.uri == uri)
+            else {
+                return;
+            };
+            workspace_folders.remove(idx)
+        };
+        if Some(&OneOf::Left(false)) != caps.change_notifications.as_ref() {
+            self.did_change_workspace(Vec::new(), vec![removed]);
+        }
+    }
+
     /// Merge FormattingOptions with 'config.format' and return it
     fn get_merged_formatting_options(
         &self,
@@ -213,6 +275,7 @@ pub fn start(
         server_environment: impl IntoIterator<Item = (impl AsRef<OsStr>, impl AsRef<OsStr>)>,
         root_path: PathBuf,
         root_uri: Option<lsp::Url>,
+        workspace_roots: &[PathBuf],
         id: LanguageServerId,
         name: String,
         req_timeout: u64,
@@ -246,10 +309,17 @@ pub fn start(
         let (server_rx, server_tx, initialize_notify) =
             Transport::start(reader, writer, stderr, id, name.clone());
 
-        let workspace_folders = root_uri
-            .clone()
-            .map(|root| vec![workspace_for_uri(root)])
-            .unwrap_or_default();
+        // the root of the server comes first, followed by all other editor workspace roots
+        let mut workspace_folders: Vec<_> =
+            root_uri.iter().cloned().map(workspace_for_uri).collect();
+        for uri in workspace_roots
+            .iter()
+            .filter_map(|root| lsp::Url::from_file_path(root).ok())
+        {
+            if !workspace_folders.iter().any(|folder| folder.uri == uri) {
+                workspace_folders.push(workspace_for_uri(uri));
+            }
+        }
 
         let client = Self {
             id,
@@ -404,6 +474,26 @@ pub fn supports_feature(&self, feature: LanguageServerFeature) -> bool {
                         | CallHierarchyServerCapability::Options(_)
                 )
             ),
+            LanguageServerFeature::SemanticTokens => {
+                self.semantic_tokens_options().is_some_and(|options| {
+                    options.range == Some(true)
+                        || matches!(
+                            options.full,
+                            Some(
+                                lsp::SemanticTokensFullOptions::Bool(true)
+                                    | lsp::SemanticTokensFullOptions::Delta { .. }
+                            )
+                        )
+                })
+            }
+            LanguageServerFeature::TypeHierarchy => matches!(
+                capabilities.type_hierarchy_provider,
+                Some(OneOf::Left(true) | OneOf::Right(_))
+            ),
+            LanguageServerFeature::InlineCompletion => matches!(
+                capabilities.inline_completion_provider,
+                Some(OneOf::Left(true) | OneOf::Right(_))
+            ),
         }
     }
 
@@ -601,6 +691,9 @@ pub(crate) async fn initialize(&self, enable_snippets: bool) -> Result<lsp::Init
                     inlay_hint: Some(lsp::InlayHintWorkspaceClientCapabilities {
                         refresh_support: Some(false),
                     }),
+                    semantic_tokens: Some(lsp::SemanticTokensWorkspaceClientCapabilities {
+                        refresh_support: Some(true),
+                    }),
                     workspace_edit: Some(lsp::WorkspaceEditClientCapabilities {
                         document_changes: Some(true),
                         resource_operations: Some(vec![
@@ -722,7 +815,9 @@ pub(crate) async fn initialize(&self, enable_snippets: bool) -> Result<lsp::Init
                     }),
                     inlay_hint: Some(lsp::InlayHintClientCapabilities {
                         dynamic_registration: Some(false),
-                        resolve_support: None,
+                        resolve_support: Some(lsp::InlayHintResolveClientCapabilities {
+                            properties: vec![String::from("tooltip")],
+                        }),
                     }),
                     document_link: Some(lsp::DocumentLinkClientCapabilities {
                         dynamic_registration: Some(false),
@@ -731,6 +826,55 @@ pub(crate) async fn initialize(&self, enable_snippets: bool) -> Result<lsp::Init
                     call_hierarchy: Some(lsp::DynamicRegistrationClientCapabilities {
                         dynamic_registration: Some(false),
                     }),
+                    type_hierarchy: Some(lsp::DynamicRegistrationClientCapabilities {
+                        dynamic_registration: Some(false),
+                    }),
+                    semantic_tokens: Some(lsp::SemanticTokensClientCapabilities {
+                        dynamic_registration: Some(false),
+                        requests: lsp::SemanticTokensClientCapabilitiesRequests {
+                            range: Some(true),
+                            full: Some(lsp::SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                        },
+                        token_types: vec![
+                            lsp::SemanticTokenType::NAMESPACE,
+                            lsp::SemanticTokenType::TYPE,
+                            lsp::SemanticTokenType::CLASS,
+                            lsp::SemanticTokenType::ENUM,
+                            lsp::SemanticTokenType::INTERFACE,
+                            lsp::SemanticTokenType::STRUCT,
+                            lsp::SemanticTokenType::TYPE_PARAMETER,
+                            lsp::SemanticTokenType::PARAMETER,
+                            lsp::SemanticTokenType::VARIABLE,
+                            lsp::SemanticTokenType::PROPERTY,
+                            lsp::SemanticTokenType::ENUM_MEMBER,
+                            lsp::SemanticTokenType::EVENT,
+                            lsp::SemanticTokenType::FUNCTION,
+                            lsp::SemanticTokenType::METHOD,
+                            lsp::SemanticTokenType::MACRO,
+                            lsp::SemanticTokenType::KEYWORD,
+                            lsp::SemanticTokenType::MODIFIER,
+                            lsp::SemanticTokenType::COMMENT,
+                            lsp::SemanticTokenType::STRING,
+                            lsp::SemanticTokenType::NUMBER,
+                            lsp::SemanticTokenType::REGEXP,
+                            lsp::SemanticTokenType::OPERATOR,
+                            lsp::SemanticTokenType::DECORATOR,
+                        ],
+                        token_modifiers: vec![
+                            lsp::SemanticTokenModifier::READONLY,
+                            lsp::SemanticTokenModifier::STATIC,
+                            lsp::SemanticTokenModifier::DEPRECATED,
+                            lsp::SemanticTokenModifier::DEFAULT_LIBRARY,
+                        ],
+                        formats: vec![lsp::TokenFormat::RELATIVE],
+                        overlapping_token_support: Some(false),
+                        multiline_token_support: Some(false),
+                        server_cancel_support: Some(false),
+                        augments_syntax_tokens: Some(true),
+                    }),
+                    inline_completion: Some(lsp::InlineCompletionClientCapabilities {
+                        dynamic_registration: Some(false),
+                    }),
                     document_symbol: Some(lsp::DocumentSymbolClientCapabilities {
                         dynamic_registration: Some(false),
                         symbol_kind: Some(lsp::SymbolKindCapability {
@@ -1235,6 +1379,55 @@ pub fn text_document_range_inlay_hints(
         Some(self.call::<lsp::request::InlayHintRequest>(params))
     }
 
+    pub fn resolve_inlay_hint(
+        &self,
+        inlay_hint: &lsp::InlayHint,
+    ) -> Option<impl Future<Output = Result<lsp::InlayHint>>> {
+        let capabilities = self.capabilities.get().unwrap();
+
+        // Return early if the server does not support resolving inlay hints.
+        match capabilities.inlay_hint_provider {
+            Some(lsp::OneOf::Right(lsp::InlayHintServerCapabilities::Options(
+                lsp::InlayHintOptions {
+                    resolve_provider: Some(true),
+                    ..
+                },
+            ))) => (),
+            _ => return None,
+        }
+
+        Some(self.call_with_ref::<lsp::request::InlayHintResolveRequest>(inlay_hint))
+    }
+
+    pub fn text_document_inline_completion(
+        &self,
+        text_document: lsp::TextDocumentIdentifier,
+        position: lsp::Position,
+        trigger_kind: lsp::InlineCompletionTriggerKind,
+        work_done_token: Option<lsp::ProgressToken>,
+    ) -> Option<impl Future<Output = Result<Option<lsp::InlineCompletionResponse>>>> {
+        let capabilities = self.capabilities.get().unwrap();
+
+        match capabilities.inline_completion_provider {
+            Some(lsp::OneOf::Left(true) | lsp::OneOf::Right(_)) => (),
+            _ => return None,
+        }
+
+        let params = lsp::InlineCompletionParams {
+            work_done_progress_params: lsp::WorkDoneProgressParams { work_done_token },
+            text_document_position: lsp::TextDocumentPositionParams {
+                text_document,
+                position,
+            },
+            context: lsp::InlineCompletionContext {
+                trigger_kind,
+                selected_completion_info: None,
+            },
+        };
+
+        Some(self.call::<lsp::request::InlineCompletionRequest>(params))
+    }
+
     pub fn text_document_document_color(
         &self,
         text_document: lsp::TextDocumentIdentifier,
@@ -1254,6 +1447,105 @@ pub fn text_document_document_color(
         Some(self.call::<lsp::request::DocumentColor>(params))
     }
 
+    pub fn text_document_color_presentation(
+        &self,
+        text_document: lsp::TextDocumentIdentifier,
+        color: lsp::Color,
+        range: lsp::Range,
+    ) -> Option<impl Future<Output = Result<Vec<lsp::ColorPresentation>>>> {
+        self.capabilities.get().unwrap().color_provider.as_ref()?;
+        let params = lsp::ColorPresentationParams {
+            text_document,
+            color,
+            range,
+            work_done_progress_params: lsp::WorkDoneProgressParams::default(),
+            partial_result_params: lsp::PartialResultParams::default(),
+        };
+
+        Some(self.call::<lsp::request::ColorPresentationRequest>(params))
+    }
+
+    fn semantic_tokens_options(&self) -> Option<&lsp::SemanticTokensOptions> {
+        match self.capabilities().semantic_tokens_provider.as_ref()? {
+            lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(options) => Some(options),
+            lsp::SemanticTokensServerCapabilities::SemanticTokensRegistrationOptions(options) => {
+                Some(&options.semantic_tokens_options)
+            }
+        }
+    }
+
+    /// The token types and modifiers referred to by the semantic tokens of the server.
+    pub fn semantic_tokens_legend(&self) -> Option<&lsp::SemanticTokensLegend> {
+        self.semantic_tokens_options()
+            .map(|options| &options.legend)
+    }
+
+    pub fn text_document_semantic_tokens_full(
+        &self,
+        text_document: lsp::TextDocumentIdentifier,
+    ) -> Option<impl Future<Output = Result<Option<lsp::SemanticTokensResult>>>> {
+        let options = self.semantic_tokens_options()?;
+        if !matches!(
+            options.full,
+            Some(
+                lsp::SemanticTokensFullOptions::Bool(true)
+                    | lsp::SemanticTokensFullOptions::Delta { .. }
+            )
+        ) {
+            return None;
+        }
+
+        let params = lsp::SemanticTokensParams {
+            text_document,
+            work_done_progress_params: lsp::WorkDoneProgressParams::default(),
+            partial_result_params: lsp::PartialResultParams::default(),
+        };
+
+        Some(self.call::<lsp::request::SemanticTokensFullRequest>(params))
+    }
+
+    pub fn text_document_semantic_tokens_full_delta(
+        &self,
+        text_document: lsp::TextDocumentIdentifier,
+        previous_result_id: String,
+    ) -> Option<impl Future<Output = Result<Option<lsp::SemanticTokensFullDeltaResult>>>> {
+        let options = self.semantic_tokens_options()?;
+        if !matches!(
+            options.full,
+            Some(lsp::SemanticTokensFullOptions::Delta { delta: Some(true) })
+        ) {
+            return None;
+        }
+
+        let params = lsp::SemanticTokensDeltaParams {
+            text_document,
+            previous_result_id,
+            work_done_progress_params: lsp::WorkDoneProgressParams::default(),
+            partial_result_params: lsp::PartialResultParams::default(),
+        };
+
+        Some(self.call::<lsp::request::SemanticTokensFullDeltaRequest>(params))
+    }
+
+    pub fn text_document_semantic_tokens_range(
+        &self,
+        text_document: lsp::TextDocumentIdentifier,
+        range: lsp::Range,
+    ) -> Option<impl Future<Output = Result<Option<lsp::SemanticTokensRangeResult>>>> {
+        if self.semantic_tokens_options()?.range != Some(true) {
+            return None;
+        }
+
+        let params = lsp::SemanticTokensRangeParams {
+            text_document,
+            range,
+            work_done_progress_params: lsp::WorkDoneProgressParams::default(),
+            partial_result_params: lsp::PartialResultParams::default(),
+        };
+
+        Some(self.call::<lsp::request::SemanticTokensRangeRequest>(params))
+    }
+
     pub fn text_document_document_link(
         &self,
         text_document: lsp::TextDocumentIdentifier,
@@ -1664,6 +1956,69 @@ pub fn call_hierarchy_outgoing(
         Some(self.call::<lsp::request::CallHierarchyOutgoingCalls>(params))
     }
 
+    pub fn prepare_type_hierarchy(
+        &self,
+        text_document: lsp::TextDocumentIdentifier,
+        position: lsp::Position,
+    ) -> Option<impl Future<Output = Result<Option<Vec<lsp::TypeHierarchyItem>>>>> {
+        let capabilities = self.capabilities.get().unwrap();
+
+        match capabilities.type_hierarchy_provider {
+            Some(OneOf::Left(true) | OneOf::Right(_)) => (),
+            _ => return None,
+        }
+
+        let params = lsp::TypeHierarchyPrepareParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document,
+                position,
+            },
+            work_done_progress_params: lsp::WorkDoneProgressParams::default(),
+        };
+
+        Some(self.call::<lsp::request::TypeHierarchyPrepare>(params))
+    }
+
+    pub fn type_hierarchy_supertypes(
+        &self,
+        item: lsp::TypeHierarchyItem,
+    ) -> Option<impl Future<Output = Result<Option<Vec<lsp::TypeHierarchyItem>>>>> {
+        let capabilities = self.capabilities.get().unwrap();
+
+        match capabilities.type_hierarchy_provider {
+            Some(OneOf::Left(true) | OneOf::Right(_)) => (),
+            _ => return None,
+        }
+
+        let params = lsp::TypeHierarchySupertypesParams {
+            item,
+            work_done_progress_params: lsp::WorkDoneProgressParams::default(),
+            partial_result_params: lsp::PartialResultParams::default(),
+        };
+
+        Some(self.call::<lsp::request::TypeHierarchySupertypes>(params))
+    }
+
+    pub fn type_hierarchy_subtypes(
+        &self,
+        item: lsp::TypeHierarchyItem,
+    ) -> Option<impl Future<Output = Result<Option<Vec<lsp::TypeHierarchyItem>>>>> {
+        let capabilities = self.capabilities.get().unwrap();
+
+        match capabilities.type_hierarchy_provider {
+            Some(OneOf::Left(true) | OneOf::Right(_)) => (),
+            _ => return None,
+        }
+
+        let params = lsp::TypeHierarchySubtypesParams {
+            item,
+            work_done_progress_params: lsp::WorkDoneProgressParams::default(),
+            partial_result_params: lsp::PartialResultParams::default(),
+        };
+
+        Some(self.call::<lsp::request::TypeHierarchySubtypes>(params))
+    }
+
     pub fn prepare_rename(
         &self,
         text_document: lsp::TextDocumentIdentifier,
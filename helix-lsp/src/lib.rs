@@ -284,6 +284,60 @@ pub fn lsp_range_to_range(
         Some(Range::new(start, end))
     }
 
+    /// Converts the relative positions of semantic tokens to char ranges in `doc`, yielding
+    /// the range, token type and token modifiers of each token. Tokens outside of the document
+    /// are skipped.
+    pub fn semantic_token_ranges<'a>(
+        doc: &'a Rope,
+        tokens: &'a [lsp::SemanticToken],
+        offset_encoding: OffsetEncoding,
+    ) -> impl Iterator<Item = (std::ops::Range<usize>, u32, u32)> + 'a {
+        let mut line = 0;
+        let mut character = 0;
+        tokens.iter().filter_map(move |token| {
+            if token.delta_line == 0 {
+                character += token.delta_start;
+            } else {
+                line += token.delta_line;
+                character = token.delta_start;
+            }
+            if line as usize >= doc.len_lines() {
+                return None;
+            }
+            let start = lsp_pos_to_pos(doc, lsp::Position::new(line, character), offset_encoding)?;
+            let end = lsp_pos_to_pos(
+                doc,
+                lsp::Position::new(line, character + token.length),
+                offset_encoding,
+            )?;
+            Some((start..end, token.token_type, token.token_modifiers_bitset))
+        })
+    }
+
+    /// Applies the edits of a `textDocument/semanticTokens/full/delta` response to the
+    /// previous tokens. Returns `false` if the edits do not apply to `tokens`, in which case
+    /// the full tokens have to be requested again.
+    pub fn apply_semantic_token_edits(
+        tokens: &mut Vec<lsp::SemanticToken>,
+        mut edits: Vec<lsp::SemanticTokensEdit>,
+    ) -> bool {
+        // the edits refer to the original tokens so they are applied back to front
+        edits.sort_unstable_by_key(|edit| std::cmp::Reverse(edit.start));
+        for edit in edits {
+            // the edits index into the flat array of integers, five per token
+            if edit.start % 5 != 0 || edit.delete_count % 5 != 0 {
+                return false;
+            }
+            let start = edit.start as usize / 5;
+            let end = start + edit.delete_count as usize / 5;
+            if end > tokens.len() {
+                return false;
+            }
+            tokens.splice(start..end, edit.data.unwrap_or_default());
+        }
+        true
+    }
+
     /// If the LS did not provide a range for the completion or the range of the
     /// primary cursor can not be used for the secondary cursor, this function
     /// can be used to find the completion range for a cursor
@@ -487,6 +541,7 @@ pub enum MethodCall {
     UnregisterCapability(lsp::UnregistrationParams),
     ShowDocument(lsp::ShowDocumentParams),
     WorkspaceDiagnosticRefresh,
+    SemanticTokensRefresh,
     ShowMessageRequest(lsp::ShowMessageRequestParams),
 }
 
@@ -520,6 +575,7 @@ pub fn parse(method: &str, params: jsonrpc::Params) -> Result<MethodCall> {
                 Self::ShowDocument(params)
             }
             lsp::request::WorkspaceDiagnosticRefresh::METHOD => Self::WorkspaceDiagnosticRefresh,
+            lsp::request::SemanticTokensRefresh::METHOD => Self::SemanticTokensRefresh,
             lsp::request::ShowMessageRequest::METHOD => {
                 let params: lsp::ShowMessageRequestParams = params.parse()?;
                 Self::ShowMessageRequest(params)
@@ -623,6 +679,7 @@ fn start_client(
         ls_config: &LanguageConfiguration,
         doc_path: Option<&std::path::Path>,
         root_dirs: &[PathBuf],
+        workspace_roots: &[PathBuf],
         enable_snippets: bool,
     ) -> Result<Arc<Client>, StartupError> {
         let syn_loader = self.syn_loader.load();
@@ -638,6 +695,7 @@ fn start_client(
                 config,
                 doc_path,
                 root_dirs,
+                workspace_roots,
                 enable_snippets,
             )
             .map(|client| {
@@ -656,6 +714,7 @@ pub fn restart_server(
         language_config: &LanguageConfiguration,
         doc_path: Option<&std::path::Path>,
         root_dirs: &[PathBuf],
+        workspace_roots: &[PathBuf],
         enable_snippets: bool,
     ) -> Option<Result<Arc<Client>>> {
         if let Some(old_clients) = self.inner_by_name.remove(name) {
@@ -677,6 +736,7 @@ pub fn restart_server(
             language_config,
             doc_path,
             root_dirs,
+            workspace_roots,
             enable_snippets,
         ) {
             Ok(client) => client,
@@ -711,6 +771,7 @@ pub fn get<'a>(
         language_config: &'a LanguageConfiguration,
         doc_path: Option<&'a std::path::Path>,
         root_dirs: &'a [PathBuf],
+        workspace_roots: &'a [PathBuf],
         enable_snippets: bool,
     ) -> impl Iterator<Item = (LanguageServerName, Result<Arc<Client>>)> + 'a {
         language_config.language_servers.iter().filter_map(
@@ -729,7 +790,13 @@ pub fn get<'a>(
                             .workspace_lsp_roots
                             .as_deref()
                             .unwrap_or(root_dirs);
-                        client.try_add_doc(&language_config.roots, manual_roots, doc_path, *i == 0)
+                        client.try_add_doc(
+                            &language_config.roots,
+                            manual_roots,
+                            doc_path,
+                            workspace_roots,
+                            *i == 0,
+                        )
                     }) {
                         return Some((name.to_owned(), Ok(client.clone())));
                     }
@@ -739,6 +806,7 @@ pub fn get<'a>(
                     language_config,
                     doc_path,
                     root_dirs,
+                    workspace_roots,
                     enable_snippets,
                 ) {
                     Ok(client) => {
@@ -755,6 +823,22 @@ pub fn get<'a>(
         )
     }
 
+    /// Notifies all running language servers that support multiple workspace folders
+    /// that an editor workspace root was added.
+    pub fn add_workspace_root(&self, root: &std::path::Path) {
+        for client in self.inner.values() {
+            client.add_workspace_root(root);
+        }
+    }
+
+    /// Notifies all running language servers that support multiple workspace folders
+    /// that an editor workspace root was removed.
+    pub fn remove_workspace_root(&self, root: &std::path::Path) {
+        for client in self.inner.values() {
+            client.remove_workspace_root(root);
+        }
+    }
+
     pub fn iter_clients(&self) -> impl Iterator<Item = &Arc<Client>> {
         self.inner.values()
     }
@@ -899,10 +983,10 @@ fn start_client(
     ls_config: &LanguageServerConfiguration,
     doc_path: Option<&std::path::Path>,
     root_dirs: &[PathBuf],
+    workspace_roots: &[PathBuf],
     enable_snippets: bool,
 ) -> Result<NewClient, StartupError> {
-    let (workspace, workspace_is_cwd) = helix_loader::find_workspace();
-    let workspace = path::normalize(workspace);
+    let (workspace, workspace_is_cwd) = find_doc_workspace(doc_path, workspace_roots);
     let root = find_lsp_workspace(
         doc_path
             .and_then(|x| x.parent().and_then(|x| x.to_str()))
@@ -938,6 +1022,7 @@ fn start_client(
         &ls_config.environment,
         root_path,
         root_uri,
+        &all_workspace_roots(workspace_roots),
         id,
         name,
         ls_config.timeout,
@@ -972,6 +1057,43 @@ fn start_client(
     Ok(NewClient(client, incoming))
 }
 
+/// Returns the editor workspace `doc_path` belongs to and whether that workspace is the
+/// current working directory.
+///
+/// This is the innermost of the additional `workspace_roots` that contains the document,
+/// or the workspace of the current working directory if none does.
+pub fn find_doc_workspace(
+    doc_path: Option<&std::path::Path>,
+    workspace_roots: &[PathBuf],
+) -> (PathBuf, bool) {
+    let root = doc_path.and_then(|doc_path| {
+        workspace_roots
+            .iter()
+            .map(path::normalize)
+            .filter(|root| doc_path.starts_with(root))
+            .max_by_key(|root| root.components().count())
+    });
+    match root {
+        Some(root) => (root, false),
+        None => {
+            let (workspace, workspace_is_cwd) = helix_loader::find_workspace();
+            (path::normalize(workspace), workspace_is_cwd)
+        }
+    }
+}
+
+/// The workspace of the current working directory followed by the additional
+/// `workspace_roots`. All of them are sent as workspace folders to new language servers.
+fn all_workspace_roots(workspace_roots: &[PathBuf]) -> Vec<PathBuf> {
+    let mut roots = vec![path::normalize(helix_loader::find_workspace().0)];
+    for root in workspace_roots.iter().map(path::normalize) {
+        if !roots.contains(&root) {
+            roots.push(root);
+        }
+    }
+    roots
+}
+
 /// Find an LSP workspace of a file using the following mechanism:
 /// * if the file is outside `workspace` return `None`
 /// * start at `file` and search the file tree upward
@@ -1067,6 +1189,100 @@ macro_rules! test_case {
         test_case!("", (u32::MAX, u32::MAX) => Some(0));
     }
 
+    #[test]
+    fn semantic_tokens() {
+        let token = |delta_line, delta_start, length, token_type| lsp::SemanticToken {
+            delta_line,
+            delta_start,
+            length,
+            token_type,
+            token_modifiers_bitset: 0,
+        };
+        let doc = Rope::from("fn main() {\n    let a = 1;\n}\n");
+        let mut tokens = vec![token(0, 0, 2, 0), token(0, 3, 4, 1), token(1, 8, 1, 2)];
+        let ranges: Vec<_> = semantic_token_ranges(&doc, &tokens, OffsetEncoding::Utf8)
+            .map(|(range, token_type, _)| (range, token_type))
+            .collect();
+        assert_eq!(ranges, [(0..2, 0), (3..7, 1), (20..21, 2)]);
+
+        // replace the `main` token and append a token for `1`
+        let edits = vec![
+            lsp::SemanticTokensEdit {
+                start: 15,
+                delete_count: 0,
+                data: Some(vec![token(0, 4, 1, 3)]),
+            },
+            lsp::SemanticTokensEdit {
+                start: 5,
+                delete_count: 5,
+                data: Some(vec![token(0, 3, 4, 4)]),
+            },
+        ];
+        assert!(apply_semantic_token_edits(&mut tokens, edits));
+        let ranges: Vec<_> = semantic_token_ranges(&doc, &tokens, OffsetEncoding::Utf8)
+            .map(|(range, token_type, _)| (range, token_type))
+            .collect();
+        assert_eq!(ranges, [(0..2, 0), (3..7, 4), (20..21, 2), (24..25, 3)]);
+
+        let misaligned = vec![lsp::SemanticTokensEdit {
+            start: 3,
+            delete_count: 0,
+            data: None,
+        }];
+        assert!(!apply_semantic_token_edits(&mut tokens, misaligned));
+    }
+
+    #[test]
+    fn doc_workspace_is_innermost_root() {
+        use super::find_doc_workspace;
+        use std::path::{Path, PathBuf};
+
+        let roots = [
+            PathBuf::from("/projects/app"),
+            PathBuf::from("/projects/app/vendor/lib/"),
+            PathBuf::from("/projects/other"),
+        ];
+        let doc = Path::new("/projects/app/vendor/lib/src/main.rs");
+        assert_eq!(
+            find_doc_workspace(Some(doc), &roots),
+            (PathBuf::from("/projects/app/vendor/lib"), false)
+        );
+        let doc = Path::new("/projects/app/src/main.rs");
+        assert_eq!(
+            find_doc_workspace(Some(doc), &roots),
+            (PathBuf::from("/projects/app"), false)
+        );
+
+        // documents outside of the roots and scratch buffers belong to the workspace of the cwd
+        let (workspace, workspace_is_cwd) = helix_loader::find_workspace();
+        let cwd_workspace = (helix_stdx::path::normalize(workspace), workspace_is_cwd);
+        let doc = Path::new("/elsewhere/main.rs");
+        assert_eq!(find_doc_workspace(Some(doc), &roots), cwd_workspace);
+        assert_eq!(find_doc_workspace(None, &roots), cwd_workspace);
+    }
+
+    #[test]
+    fn workspace_roots_are_deduplicated() {
+        use super::all_workspace_roots;
+        use std::path::PathBuf;
+
+        let workspace = helix_stdx::path::normalize(helix_loader::find_workspace().0);
+        let roots = [
+            PathBuf::from("/projects/other"),
+            workspace.clone(),
+            PathBuf::from("/projects/./other/"),
+            PathBuf::from("/projects/third"),
+        ];
+        assert_eq!(
+            all_workspace_roots(&roots),
+            [
+                workspace,
+                PathBuf::from("/projects/other"),
+                PathBuf::from("/projects/third"),
+            ]
+        );
+    }
+
     #[test]
     fn emoji_format_gh_4791() {
         use lsp::{Position, Range, TextEdit};
@@ -6,6 +6,7 @@
 pub mod path;
 pub mod range;
 pub mod rope;
+pub mod segment;
 pub mod uri;
 
 pub use range::Range;
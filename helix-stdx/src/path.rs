@@ -7,9 +7,11 @@
 
 use std::{
     borrow::Cow,
+    collections::HashMap,
     ffi::OsString,
     ops::Range,
     path::{Component, Path, PathBuf, MAIN_SEPARATOR_STR},
+    sync::Mutex,
 };
 
 use crate::env::current_working_dir;
@@ -142,6 +144,126 @@ pub fn canonicalize(path: impl AsRef<Path>) -> PathBuf {
     normalize(path)
 }
 
+/// Whether the file systems treat paths that differ only in case as the same path by default.
+const CASE_INSENSITIVE: bool = cfg!(any(windows, target_os = "macos"));
+
+/// Removes the `\\?\` prefix of a Windows path if the path is valid without it, for example
+/// `\\?\C:\foo` becomes `C:\foo` and `\\?\UNC\server\share` becomes `\\server\share`. Long
+/// paths that require the prefix keep it. Other paths are returned unchanged.
+pub fn simplify(path: &Path) -> &Path {
+    dunce::simplified(path)
+}
+
+fn component_eq(a: Component, b: Component) -> bool {
+    if CASE_INSENSITIVE {
+        a.as_os_str().eq_ignore_ascii_case(b.as_os_str())
+    } else {
+        a == b
+    }
+}
+
+/// Compares paths the way the file system does: ignoring the `\\?\` prefix, see [simplify],
+/// and ignoring case on Windows and macOS. The paths are compared as they are, without
+/// normalizing or resolving them.
+pub fn paths_equal(a: impl AsRef<Path>, b: impl AsRef<Path>) -> bool {
+    let mut a = simplify(a.as_ref()).components();
+    let mut b = simplify(b.as_ref()).components();
+    loop {
+        match (a.next(), b.next()) {
+            (None, None) => return true,
+            (Some(a), Some(b)) if component_eq(a, b) => (),
+            _ => return false,
+        }
+    }
+}
+
+/// Like [Path::strip_prefix], but compares the components like [paths_equal].
+pub fn strip_prefix<'a>(path: &'a Path, base: &Path) -> Option<&'a Path> {
+    let mut components = simplify(path).components();
+    for base in simplify(base).components() {
+        match components.next() {
+            Some(component) if component_eq(component, base) => (),
+            _ => return None,
+        }
+    }
+    Some(components.as_path())
+}
+
+/// Returns `path` relative to the directory `base`, going up with `..` components where `path`
+/// is outside of `base`. Both paths are expected to be normalized. Paths that have no relative
+/// form, like paths on different drives or network shares, are returned unchanged.
+pub fn relative_to(path: impl AsRef<Path>, base: impl AsRef<Path>) -> PathBuf {
+    let (path, base) = (simplify(path.as_ref()), simplify(base.as_ref()));
+    let path_components: Vec<_> = path.components().collect();
+    let base_components: Vec<_> = base.components().collect();
+    let common = path_components
+        .iter()
+        .zip(&base_components)
+        .take_while(|(a, b)| component_eq(**a, **b))
+        .count();
+    if common == 0 && (path.has_root() || base.has_root()) {
+        return path.to_path_buf();
+    }
+    let mut relative = PathBuf::new();
+    for _ in common..base_components.len() {
+        relative.push("..");
+    }
+    for component in &path_components[common..] {
+        relative.push(component);
+    }
+    relative
+}
+
+/// The resolved form of the paths passed to [resolve].
+static RESOLVED_PATHS: Lazy<Mutex<HashMap<PathBuf, PathBuf>>> = Lazy::new(Default::default);
+
+/// Returns the canonical form of a path with symlinks resolved, like [std::fs::canonicalize]
+/// but without the `\\?\` prefix where possible. Paths that don't exist are only normalized,
+/// see [canonicalize].
+///
+/// The results are cached because document identity checks resolve the paths of all open
+/// documents, use [forget_resolved_paths] after files were moved or symlinks changed.
+pub fn resolve(path: impl AsRef<Path>) -> PathBuf {
+    let path = path.as_ref();
+    let mut resolved_paths = RESOLVED_PATHS.lock().unwrap();
+    if let Some(resolved) = resolved_paths.get(path) {
+        return resolved.clone();
+    }
+    let canonical = canonicalize(path);
+    match dunce::canonicalize(&canonical) {
+        Ok(resolved) => {
+            resolved_paths.insert(path.to_path_buf(), resolved.clone());
+            resolved
+        }
+        // not cached: the file may be created later
+        Err(_) => canonical,
+    }
+}
+
+/// Clears the cache of [resolve].
+pub fn forget_resolved_paths() {
+    RESOLVED_PATHS.lock().unwrap().clear();
+}
+
+/// Whether two paths refer to the same file: either the paths are equal, see [paths_equal], or
+/// they resolve to the same file, see [resolve].
+pub fn same_file(a: impl AsRef<Path>, b: impl AsRef<Path>) -> bool {
+    let (a, b) = (a.as_ref(), b.as_ref());
+    paths_equal(a, b) || paths_equal(resolve(a), resolve(b))
+}
+
+/// A hash of `path` that stays the same across runs, platforms and Rust versions, unlike
+/// [std::hash::DefaultHasher]. Files that store state about a path are named after it.
+pub fn stable_hash(path: &Path) -> u64 {
+    // 64-bit FNV-1a
+    path.as_os_str()
+        .as_encoded_bytes()
+        .iter()
+        .fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+        })
+}
+
 /// Convert path into a relative path
 pub fn get_relative_path<'a, P>(path: P) -> Cow<'a, Path>
 where
@@ -150,7 +272,7 @@ pub fn get_relative_path<'a, P>(path: P) -> Cow<'a, Path>
     let path = path.into();
     if path.is_absolute() {
         let cwdir = normalize(current_working_dir());
-        if let Ok(stripped) = normalize(&path).strip_prefix(cwdir) {
+        if let Some(stripped) = strip_prefix(&normalize(&path), &cwdir) {
             return Cow::Owned(PathBuf::from(stripped));
         }
 
@@ -312,6 +434,82 @@ mod tests {
 
     use crate::path::{self, compile_path_regex};
 
+    #[test]
+    fn stable_hash() {
+        assert_eq!(
+            path::stable_hash(Path::new("/home/user/file.rs")),
+            0x1b2afb497868f0d1
+        );
+        assert_ne!(
+            path::stable_hash(Path::new("/home/user/file.rs")),
+            path::stable_hash(Path::new("/home/user/file.r"))
+        );
+    }
+
+    #[test]
+    fn compare_paths() {
+        assert!(path::paths_equal("/foo/bar", "/foo/bar"));
+        assert!(!path::paths_equal("/foo/bar", "/foo/baz"));
+        assert!(!path::paths_equal("/foo/bar", "/foo"));
+        assert_eq!(
+            path::paths_equal("/foo/Bar", "/foo/bar"),
+            cfg!(any(windows, target_os = "macos"))
+        );
+        assert_eq!(
+            path::strip_prefix(Path::new("/foo/bar/baz"), Path::new("/foo")),
+            Some(Path::new("bar/baz"))
+        );
+        assert_eq!(
+            path::strip_prefix(Path::new("/foo/bar"), Path::new("/baz")),
+            None
+        );
+    }
+
+    #[test]
+    fn relative_to() {
+        let root = if cfg!(windows) { "C:\\" } else { "/" };
+        let path = |path: &str| Path::new(root).join(path);
+        assert_eq!(
+            path::relative_to(path("foo/bar/baz.rs"), path("foo")),
+            Path::new("bar/baz.rs")
+        );
+        assert_eq!(
+            path::relative_to(path("foo/bar.rs"), path("foo/baz/qux")),
+            Path::new("../../bar.rs")
+        );
+        assert_eq!(path::relative_to(path("foo"), path("foo")), Path::new(""));
+        #[cfg(windows)]
+        {
+            assert_eq!(
+                path::relative_to(r"D:\foo", r"C:\foo"),
+                Path::new(r"D:\foo")
+            );
+            assert_eq!(
+                path::relative_to(r"\\?\C:\foo\bar", r"C:\foo"),
+                Path::new("bar")
+            );
+        }
+    }
+
+    #[test]
+    fn resolve_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file");
+        std::fs::write(&file, "").unwrap();
+        #[cfg(unix)]
+        {
+            let link = dir.path().join("link");
+            std::os::unix::fs::symlink(&file, &link).unwrap();
+            assert!(path::same_file(&link, &file));
+            assert!(!path::paths_equal(&link, &file));
+        }
+        assert!(!path::same_file(&file, dir.path().join("missing")));
+        assert_eq!(
+            path::resolve(dir.path().join("missing/..")),
+            path::resolve(dir.path())
+        );
+    }
+
     #[test]
     fn expand_tilde() {
         for path in ["~", "~/foo"] {
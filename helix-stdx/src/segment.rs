@@ -0,0 +1,228 @@
+//! Unicode text segmentation of ropes into graphemes, words and sentences, following
+//! [UAX #29](https://www.unicode.org/reports/tr29/).
+//!
+//! All three kinds of boundaries occur after every line break, so a rope is segmented line by
+//! line, which gives the same boundaries as segmenting the whole text at once.
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+use ropey::RopeSlice;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The unit that text is segmented into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    /// Extended grapheme clusters: the characters as perceived by the user.
+    Grapheme,
+    /// Words, and the whitespace and punctuation between them, see [Segment::is_word].
+    Word,
+    Sentence,
+}
+
+/// A segment of a rope with its position as both byte and char indices.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    pub bytes: Range<usize>,
+    pub chars: Range<usize>,
+}
+
+impl Segment {
+    pub fn slice<'a>(&self, text: RopeSlice<'a>) -> RopeSlice<'a> {
+        text.slice(self.chars.clone())
+    }
+
+    /// Whether the segment is a word rather than whitespace or punctuation, like the words of
+    /// [UnicodeSegmentation::unicode_words].
+    pub fn is_word(&self, text: RopeSlice) -> bool {
+        self.slice(text).chars().any(char::is_alphanumeric)
+    }
+}
+
+/// The byte ranges of the segments of a line, relative to the start of the line.
+fn line_segments(line: &str, unit: Unit) -> Vec<Range<usize>> {
+    let range = |(start, segment): (usize, &str)| start..start + segment.len();
+    match unit {
+        Unit::Grapheme => line.grapheme_indices(true).map(range).collect(),
+        Unit::Word => line.split_word_bound_indices().map(range).collect(),
+        Unit::Sentence => line.split_sentence_bound_indices().map(range).collect(),
+    }
+}
+
+/// An iterator over the segments of a rope, see [segments].
+#[derive(Debug, Clone)]
+pub struct Segments<'a> {
+    text: RopeSlice<'a>,
+    unit: Unit,
+    /// The index of the next line to segment.
+    line: usize,
+    line_start: usize,
+    /// The remaining segments of the current line.
+    segments: std::vec::IntoIter<Range<usize>>,
+    /// The char index of the start of the next segment.
+    char_idx: usize,
+}
+
+impl Segments<'_> {
+    fn load_line(&mut self) {
+        let line = self.text.line(self.line);
+        self.line_start = self.text.line_to_byte(self.line);
+        self.char_idx = self.text.line_to_char(self.line);
+        self.segments = line_segments(&Cow::from(line), self.unit).into_iter();
+        self.line += 1;
+    }
+}
+
+impl Iterator for Segments<'_> {
+    type Item = Segment;
+
+    fn next(&mut self) -> Option<Segment> {
+        loop {
+            if let Some(range) = self.segments.next() {
+                let bytes = self.line_start + range.start..self.line_start + range.end;
+                let len_chars = self.text.byte_slice(bytes.clone()).len_chars();
+                let chars = self.char_idx..self.char_idx + len_chars;
+                self.char_idx = chars.end;
+                return Some(Segment { bytes, chars });
+            }
+            if self.line >= self.text.len_lines() {
+                return None;
+            }
+            self.load_line();
+        }
+    }
+}
+
+/// Returns an iterator over the segments of `text`.
+pub fn segments(text: RopeSlice<'_>, unit: Unit) -> Segments<'_> {
+    segments_at(text, unit, 0)
+}
+
+/// Returns an iterator over the segments of `text` that starts with the segment that contains
+/// `char_idx`. Starts at the end of the text if `char_idx` is at the end of the text.
+pub fn segments_at(text: RopeSlice<'_>, unit: Unit, char_idx: usize) -> Segments<'_> {
+    let mut segments = Segments {
+        text,
+        unit,
+        line: text.char_to_line(char_idx),
+        line_start: 0,
+        segments: Vec::new().into_iter(),
+        char_idx: text.len_chars(),
+    };
+    if char_idx >= text.len_chars() {
+        segments.line = text.len_lines();
+        return segments;
+    }
+    segments.load_line();
+    let line_start = segments.line_start;
+    let skipped = segments
+        .segments
+        .as_slice()
+        .iter()
+        .take_while(|range| text.byte_to_char(line_start + range.end) <= char_idx)
+        .count();
+    for _ in 0..skipped {
+        segments.next();
+    }
+    segments
+}
+
+/// Returns the segment that contains `char_idx`, `None` at the end of the text.
+pub fn segment_at(text: RopeSlice, unit: Unit, char_idx: usize) -> Option<Segment> {
+    segments_at(text, unit, char_idx).next()
+}
+
+/// Returns the first boundary after `char_idx`, or the end of the text.
+pub fn next_boundary(text: RopeSlice, unit: Unit, char_idx: usize) -> usize {
+    segment_at(text, unit, char_idx).map_or(text.len_chars(), |segment| segment.chars.end)
+}
+
+/// Returns the last boundary before `char_idx`, or the start of the text.
+pub fn prev_boundary(text: RopeSlice, unit: Unit, char_idx: usize) -> usize {
+    match char_idx.checked_sub(1) {
+        Some(char_idx) => segment_at(text, unit, char_idx).map_or(0, |segment| segment.chars.start),
+        None => 0,
+    }
+}
+
+/// Whether `char_idx` is a boundary between two segments, or the start or end of the text.
+pub fn is_boundary(text: RopeSlice, unit: Unit, char_idx: usize) -> bool {
+    segment_at(text, unit, char_idx).is_none_or(|segment| segment.chars.start == char_idx)
+}
+
+#[cfg(test)]
+mod tests {
+    use ropey::Rope;
+
+    use super::*;
+
+    fn strings(text: &Rope, unit: Unit) -> Vec<String> {
+        segments(text.slice(..), unit)
+            .map(|segment| segment.slice(text.slice(..)).to_string())
+            .collect()
+    }
+
+    #[test]
+    fn segment() {
+        let text = Rope::from("Hello, wörld! How are\r\nyou? 👩‍👩‍👧 e\u{301}.\n");
+        assert_eq!(
+            strings(&text, Unit::Word),
+            [
+                "Hello",
+                ",",
+                " ",
+                "wörld",
+                "!",
+                " ",
+                "How",
+                " ",
+                "are",
+                "\r\n",
+                "you",
+                "?",
+                " ",
+                "👩‍👩‍👧",
+                " ",
+                "e\u{301}",
+                ".",
+                "\n"
+            ]
+        );
+        assert_eq!(
+            strings(&text, Unit::Sentence),
+            ["Hello, wörld! ", "How are\r\n", "you? ", "👩‍👩‍👧 e\u{301}.\n"]
+        );
+        let graphemes = strings(&text, Unit::Grapheme);
+        assert!(graphemes.contains(&"\r\n".to_string()));
+        assert!(graphemes.contains(&"👩‍👩‍👧".to_string()));
+        assert!(graphemes.contains(&"e\u{301}".to_string()));
+
+        // byte and char indices agree with the rope
+        for segment in segments(text.slice(..), Unit::Grapheme) {
+            assert_eq!(text.byte_to_char(segment.bytes.start), segment.chars.start);
+            assert_eq!(text.byte_to_char(segment.bytes.end), segment.chars.end);
+        }
+    }
+
+    #[test]
+    fn boundaries() {
+        let text = Rope::from("one two\nthree");
+        let text = text.slice(..);
+        let word = segment_at(text, Unit::Word, 5).unwrap();
+        assert_eq!(word.chars, 4..7);
+        assert!(word.is_word(text));
+        assert!(!segment_at(text, Unit::Word, 3).unwrap().is_word(text));
+        assert_eq!(next_boundary(text, Unit::Word, 5), 7);
+        assert_eq!(prev_boundary(text, Unit::Word, 5), 4);
+        assert_eq!(prev_boundary(text, Unit::Word, 4), 3);
+        assert_eq!(next_boundary(text, Unit::Word, 13), 13);
+        assert!(is_boundary(text, Unit::Word, 8));
+        assert!(!is_boundary(text, Unit::Word, 9));
+        assert_eq!(
+            segments_at(text, Unit::Word, 9)
+                .map(|segment| segment.chars)
+                .collect::<Vec<_>>(),
+            [8..13]
+        );
+    }
+}
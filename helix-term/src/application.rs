@@ -1,6 +1,7 @@
 use arc_swap::{access::Map, ArcSwap};
 use futures_util::Stream;
 use helix_core::{diagnostic::Severity, pos_at_coords, syntax, Range, Selection};
+use helix_loader::watcher::Watcher;
 use helix_lsp::{
     lsp::{self, notification::Notification},
     util::lsp_range_to_range,
@@ -10,8 +11,9 @@
 use helix_view::{
     align_view,
     document::{DocumentOpenError, DocumentSavedEventResult},
-    editor::{ConfigEvent, EditorEvent},
+    editor::{ConfigEvent, EditorEvent, TrueColorConfig},
     graphics::Rect,
+    session::Session,
     theme,
     tree::Layout,
     Align, Editor,
@@ -21,10 +23,11 @@
 
 use crate::{
     args::Args,
+    commands::{self, typed::reload_theme},
     compositor::{Compositor, Event},
     config::Config,
     handlers,
-    job::Jobs,
+    job::{self, Jobs},
     keymap::Keymaps,
     ui::{self, overlay::overlaid},
 };
@@ -32,8 +35,9 @@
 use log::{debug, error, info, warn};
 use std::{
     io::{stdin, IsTerminal},
-    path::Path,
+    path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 
 #[cfg_attr(windows, allow(unused_imports))]
@@ -67,6 +71,13 @@
 
 type Terminal = tui::terminal::Terminal<TerminalBackend>;
 
+/// How often the files of the current theme are checked for changes.
+const THEME_WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The time between the frames rendered while views scroll smoothly, see
+/// [helix_view::animation::ScrollAnimations].
+const FRAME_INTERVAL: Duration = Duration::from_millis(16);
+
 pub struct Application {
     compositor: Compositor,
     terminal: Terminal,
@@ -79,6 +90,8 @@ pub struct Application {
     lsp_progress: LspProgressMap,
 
     theme_mode: Option<theme::Mode>,
+    /// Reloads the current theme when one of its files changes.
+    theme_watcher: Watcher,
 }
 
 #[cfg(feature = "integration")]
@@ -126,6 +139,22 @@ pub fn new(args: Args, config: Config, lang_loader: syntax::Loader) -> Result<Se
             handlers,
         );
         Self::load_configured_theme(&mut editor, &config.load(), &mut terminal, theme_mode);
+        // integration tests must not share the history of the user
+        #[cfg(not(feature = "integration"))]
+        if let Err(err) = editor.registers.load_command_history() {
+            log::warn!("failed to load the command history: {err}");
+        }
+        #[cfg(not(feature = "integration"))]
+        if let Err(err) = editor.registers.load_macros() {
+            log::warn!("failed to load the recorded macros: {err}");
+        }
+        #[cfg(not(feature = "integration"))]
+        if let Err(err) = editor.marks.load() {
+            log::warn!("failed to load the marks: {err}");
+        }
+        for root in &args.workspace_roots {
+            editor.add_workspace_root(root);
+        }
 
         let keys = Box::new(Map::new(Arc::clone(&config), |config: &Config| {
             &config.keys
@@ -133,13 +162,20 @@ pub fn new(args: Args, config: Config, lang_loader: syntax::Loader) -> Result<Se
         let editor_view = Box::new(ui::EditorView::new(Keymaps::new(keys)));
         compositor.push(editor_view);
 
-        let jobs = Jobs::new();
+        let mut jobs = Jobs::new();
 
         if args.load_tutor {
             let path = helix_loader::runtime_file(Path::new("tutor"));
             editor.open(&path, Action::VerticalSplit)?;
             // Unset path to prevent accidentally saving to the original tutor file.
             doc_mut!(editor).set_path(None);
+        } else if let Some(name) = args.session {
+            editor.new_file(Action::VerticalSplit);
+            let path = Session::file(&name)?;
+            if path.exists() {
+                Session::load(&path)?.restore(&mut editor)?;
+            }
+            editor.session = Some(name);
         } else if !args.files.is_empty() {
             let mut files_it = args.files.into_iter().peekable();
 
@@ -152,6 +188,7 @@ pub fn new(args: Args, config: Config, lang_loader: syntax::Loader) -> Result<Se
             // If there are any more files specified, open them
             if files_it.peek().is_some() {
                 let mut nr_of_files = 0;
+                let mut remote_files = Vec::new();
                 for (file, pos) in files_it {
                     nr_of_files += 1;
                     if file.is_dir() {
@@ -170,6 +207,12 @@ pub fn new(args: Args, config: Config, lang_loader: syntax::Loader) -> Result<Se
                             Some(Layout::Horizontal) => Action::HorizontalSplit,
                             None => Action::Load,
                         };
+                        // remote files are opened once they're read in the background
+                        if let Some(backend) = helix_view::remote::backend_for_url(&file) {
+                            remote_files.push((backend?, pos));
+                            nr_of_files -= 1;
+                            continue;
+                        }
                         let old_id = editor.document_id_by_path(&file);
                         let doc_id = match editor.open(&file, action) {
                             // Ignore irregular files during application init.
@@ -201,6 +244,18 @@ pub fn new(args: Args, config: Config, lang_loader: syntax::Loader) -> Result<Se
                     }
                 }
 
+                let split_action = match args.split {
+                    Some(Layout::Vertical) => Action::VerticalSplit,
+                    Some(Layout::Horizontal) => Action::HorizontalSplit,
+                    None => Action::Load,
+                };
+                // the empty buffer is replaced by the first remote file if there are no other
+                // files
+                let mut remote_action = if nr_of_files == 0 {
+                    Action::Replace
+                } else {
+                    split_action
+                };
                 // if all files were invalid, replace with empty buffer
                 if nr_of_files == 0 {
                     editor.new_file(Action::VerticalSplit);
@@ -215,6 +270,16 @@ pub fn new(args: Args, config: Config, lang_loader: syntax::Loader) -> Result<Se
                     let (view, doc) = current!(editor);
                     align_view(doc, view, Align::Center);
                 }
+                for (backend, pos) in remote_files {
+                    commands::typed::open_remote(
+                        &mut editor,
+                        &mut jobs,
+                        backend,
+                        remote_action,
+                        pos,
+                    );
+                    remote_action = split_action;
+                }
             } else {
                 editor.new_file(Action::VerticalSplit);
             }
@@ -226,6 +291,17 @@ pub fn new(args: Args, config: Config, lang_loader: syntax::Loader) -> Result<Se
                 .unwrap_or_else(|_| editor.new_file(Action::VerticalSplit));
         }
 
+        #[cfg(not(feature = "integration"))]
+        {
+            let recoverable = helix_view::recovery::list().len();
+            if recoverable > 0 {
+                let plural = if recoverable == 1 { "" } else { "s" };
+                editor.set_status(format!(
+                    "Unsaved changes of {recoverable} file{plural} can be restored with :recover"
+                ));
+            }
+        }
+
         #[cfg(windows)]
         let signals = futures_util::stream::empty();
         #[cfg(not(windows))]
@@ -247,12 +323,26 @@ pub fn new(args: Args, config: Config, lang_loader: syntax::Loader) -> Result<Se
             jobs,
             lsp_progress: LspProgressMap::new(),
             theme_mode,
+            theme_watcher: Watcher::new(THEME_WATCH_INTERVAL, |_| {
+                job::dispatch_blocking(|editor, _| {
+                    if let Err(err) = reload_theme(editor) {
+                        editor.set_error(err.to_string());
+                    }
+                })
+            }),
         };
 
         Ok(app)
     }
 
     async fn render(&mut self) {
+        let smooth_scroll = self.editor.config().smooth_scroll;
+        self.editor.scroll_animations.update(
+            &self.editor.tree,
+            &mut self.editor.documents,
+            smooth_scroll,
+        );
+
         if self.compositor.full_redraw {
             self.terminal.clear().expect("Cannot clear the terminal");
             self.compositor.full_redraw = false;
@@ -339,6 +429,9 @@ pub async fn event_loop_until_idle<S>(&mut self, input_stream: &mut S) -> bool
                     self.jobs.handle_callback(&mut self.editor, &mut self.compositor, callback);
                     self.render().await;
                 }
+                _ = tokio::time::sleep(FRAME_INTERVAL), if self.editor.scroll_animations.is_running() => {
+                    self.render().await;
+                }
                 event = self.editor.wait_event() => {
                     let _idle_handled = self.handle_editor_event(event).await;
 
@@ -348,7 +441,11 @@ pub async fn event_loop_until_idle<S>(&mut self, input_stream: &mut S) -> bool
                         // set in `handle_document_write`) can run before the `DocumentSavedEvent` is processed. Slow file I/O on Windows
                         // (atomic_save's rename/fsync dance over the still-open temp file) makes this race observable.
                         // Errors produce an event too, so it cannot hang.
-                        if _idle_handled && self.editor.write_count == 0 {
+                        // Shell commands run in the background as well.
+                        if _idle_handled
+                            && self.editor.write_count == 0
+                            && !self.editor.shell_controller.is_running()
+                        {
                             return true;
                         }
                     }
@@ -388,6 +485,17 @@ pub fn handle_config_events(&mut self, config_event: ConfigEvent) {
                         .try_get_exact("ui.background")
                         .and_then(|style| style.bg),
                 );
+                // the themes directory is watched as well to notice a new theme that shadows
+                // the current theme of a lower priority directory
+                let theme_dir = helix_loader::config_dir().join("themes");
+                self.theme_watcher.watch(
+                    self.editor
+                        .theme
+                        .paths()
+                        .iter()
+                        .cloned()
+                        .chain(std::iter::once(theme_dir)),
+                );
                 return;
             }
         }
@@ -435,6 +543,8 @@ fn refresh_config(&mut self) {
                 document.replace_diagnostics(diagnostics, &[], None);
             }
 
+            self.editor.reload_snippets();
+
             self.terminal.reconfigure((&default_config.editor).into())?;
             // Store new config
             self.config.store(Arc::new(default_config));
@@ -458,9 +568,13 @@ fn load_configured_theme(
         terminal: &mut Terminal,
         mode: Option<theme::Mode>,
     ) {
-        let true_color = terminal.backend().supports_true_color()
-            || config.editor.true_color
-            || crate::true_color();
+        // the default theme uses RGB colors, the 16 color default theme looks better than its
+        // downsampled colors on terminals without true color support
+        let true_color = match config.editor.true_color {
+            TrueColorConfig::Auto => terminal.backend().supports_true_color(),
+            TrueColorConfig::Enabled => true,
+            TrueColorConfig::Disabled => false,
+        };
         let theme = config
             .theme
             .as_ref()
@@ -474,17 +588,6 @@ fn load_configured_theme(
                         e
                     })
                     .ok()
-                    .filter(|theme| {
-                        let colors_ok = true_color || theme.is_16_color();
-                        if !colors_ok {
-                            log::warn!(
-                                "loaded theme `{}` but cannot use it because true color \
-                                support is not enabled",
-                                theme.name()
-                            );
-                        }
-                        colors_ok
-                    })
             })
             .unwrap_or_else(|| editor.theme_loader.default_theme(true_color));
         let _ = editor.set_theme(theme);
@@ -601,6 +704,14 @@ pub fn handle_document_write(&mut self, doc_save_event: DocumentSavedEventResult
         );
 
         doc.set_last_saved_revision(doc_save_event.revision, doc_save_event.save_time);
+        doc.set_current_encoding(doc_save_event.encoding);
+        if !doc.is_modified() {
+            if let Some(path) = doc.path() {
+                if let Err(err) = helix_view::recovery::remove(path) {
+                    log::error!("Error removing the recovery file: {}", err);
+                }
+            }
+        }
 
         let lines = doc_save_event.text.len_lines();
         let size = doc_save_event.text.len_bytes();
@@ -632,13 +743,53 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             Size::HumanReadable(size, SUFFIX[i])
         };
 
-        self.editor
-            .set_doc_path(doc_save_event.doc_id, &doc_save_event.path);
+        let name = match &doc_save_event.path {
+            Some(path) => {
+                self.editor.set_doc_path(doc_save_event.doc_id, path);
+                get_relative_path(path).to_string_lossy().into_owned()
+            }
+            None => doc.display_name().into_owned(),
+        };
         // TODO: fix being overwritten by lsp
-        self.editor.set_status(format!(
-            "'{}' written, {lines}L {size}",
-            get_relative_path(&doc_save_event.path).to_string_lossy(),
-        ));
+        self.editor
+            .set_status(format!("'{name}' written, {lines}L {size}"));
+    }
+
+    /// Reloads the documents whose files were changed by another program. Documents with
+    /// unsaved changes are only reloaded once the user confirms it.
+    fn handle_changed_files(&mut self, paths: Vec<PathBuf>) {
+        for path in paths {
+            let Some(doc) = self.editor.document_by_path(&path) else {
+                continue;
+            };
+            // writes of the editor itself update the last saved time of the document
+            if !doc.is_modified_externally() {
+                continue;
+            }
+            let doc_id = doc.id();
+            let name = get_relative_path(&path).to_string_lossy().into_owned();
+            if !doc.is_modified() {
+                match self.editor.reload_document(doc_id) {
+                    Ok(()) => self.editor.set_status(format!("'{name}' reloaded")),
+                    Err(err) => self.editor.set_error(err.to_string()),
+                }
+                continue;
+            }
+            let prompt = ui::Prompt::new(
+                format!("'{name}' changed on disk, reload and discard your changes? (y/n):").into(),
+                None,
+                ui::completers::none,
+                move |cx, input, event| {
+                    if event != ui::PromptEvent::Validate || input != "y" {
+                        return;
+                    }
+                    if let Err(err) = cx.editor.reload_document(doc_id) {
+                        cx.editor.set_error(err.to_string());
+                    }
+                },
+            );
+            self.compositor.push(Box::new(prompt));
+        }
     }
 
     #[inline(always)]
@@ -665,6 +816,12 @@ pub async fn handle_editor_event(&mut self, event: EditorEvent) -> bool {
                     self.render().await;
                 }
             }
+            EditorEvent::FilesChanged(paths) => {
+                // files may have been moved or replaced by symlinks
+                helix_stdx::path::forget_resolved_paths();
+                self.handle_changed_files(paths);
+                self.render().await;
+            }
             EditorEvent::Redraw => {
                 self.render().await;
             }
@@ -686,6 +843,11 @@ pub async fn handle_terminal_events(&mut self, event: std::io::Result<TerminalEv
         #[cfg(not(windows))]
         use termina::escape::csi;
 
+        // input is handled with the final positions of the views
+        self.editor
+            .scroll_animations
+            .finish(&mut self.editor.documents);
+
         let mut cx = crate::compositor::Context {
             editor: &mut self.editor,
             jobs: &mut self.jobs,
@@ -1124,6 +1286,26 @@ macro_rules! language_server {
 
                         Ok(serde_json::Value::Null)
                     }
+                    Ok(MethodCall::SemanticTokensRefresh) => {
+                        let language_server = language_server!().id();
+
+                        let documents: Vec<_> = self
+                            .editor
+                            .documents
+                            .values()
+                            .filter(|doc| doc.supports_language_server(language_server))
+                            .map(|doc| doc.id())
+                            .collect();
+
+                        for document in documents {
+                            handlers::semantic_tokens::request_semantic_tokens(
+                                &mut self.editor,
+                                document,
+                            );
+                        }
+
+                        Ok(serde_json::Value::Null)
+                    }
                     Ok(MethodCall::ShowMessageRequest(params)) => {
                         if let Some(actions) = params.actions.filter(|a| !a.is_empty()) {
                             let id = id.clone();
@@ -1345,6 +1527,39 @@ pub async fn close(&mut self) -> Vec<anyhow::Error> {
             errs.push(err);
         }
 
+        #[cfg(not(feature = "integration"))]
+        if let Err(err) = self.editor.registers.save_command_history() {
+            log::error!("Error saving the command history: {}", err);
+        }
+        #[cfg(not(feature = "integration"))]
+        if let Err(err) = self.editor.registers.save_macros() {
+            log::error!("Error saving the recorded macros: {}", err);
+        }
+        #[cfg(not(feature = "integration"))]
+        {
+            for doc in self.editor.documents.values_mut() {
+                self.editor.marks.detach(doc);
+            }
+            if let Err(err) = self.editor.marks.save() {
+                log::error!("Error saving the marks: {}", err);
+            }
+        }
+        // the changes of documents that are still modified were discarded on purpose
+        #[cfg(not(feature = "integration"))]
+        for path in self.editor.documents().filter_map(|doc| doc.path()) {
+            if let Err(err) = helix_view::recovery::remove(path) {
+                log::error!("Error removing the recovery file: {}", err);
+            }
+        }
+        #[cfg(not(feature = "integration"))]
+        if self.editor.config().persistent_undo {
+            for doc in self.editor.documents() {
+                if let Err(err) = helix_view::undo_file::save(doc) {
+                    log::error!("Error saving the undo history: {}", err);
+                }
+            }
+        }
+
         if self.editor.close_language_servers(None).await.is_err() {
             log::error!("Timed out waiting for language servers to shutdown");
             errs.push(anyhow::format_err!(
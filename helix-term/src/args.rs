@@ -20,6 +20,8 @@ pub struct Args {
     pub config_file: Option<PathBuf>,
     pub files: IndexMap<PathBuf, Vec<Position>>,
     pub working_directory: Option<PathBuf>,
+    pub workspace_roots: Vec<PathBuf>,
+    pub session: Option<String>,
 }
 
 impl Args {
@@ -91,6 +93,19 @@ pub fn parse_args() -> Result<Args> {
                         anyhow::bail!("--working-dir must specify an initial working directory")
                     }
                 },
+                "--workspace" => match argv.next().as_deref() {
+                    Some(path) if Path::new(path).is_dir() => args
+                        .workspace_roots
+                        .push(helix_stdx::path::canonicalize(path)),
+                    Some(_) => {
+                        anyhow::bail!("--workspace specified does not exist or is not a directory")
+                    }
+                    None => anyhow::bail!("--workspace must specify a directory"),
+                },
+                "--session" => match argv.next() {
+                    Some(name) => args.session = Some(name),
+                    None => anyhow::bail!("--session must specify a session name"),
+                },
                 arg if arg.starts_with("--") => {
                     anyhow::bail!("unexpected double dash argument: {}", arg)
                 }
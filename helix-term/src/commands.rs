@@ -5,7 +5,7 @@
 
 pub use dap::*;
 use futures_util::FutureExt;
-use helix_event::status;
+use helix_event::{status, TaskHandle};
 use helix_stdx::{
     path::{self, find_paths},
     rope::{self, RopeSliceExt},
@@ -24,25 +24,32 @@
     chars::char_is_word,
     command_line::{self, Args},
     comment,
+    diagnostic::Severity,
     doc_formatter::TextFormat,
-    encoding, find_workspace,
+    encoding, find_workspace, fold,
     graphemes::{self, next_grapheme_boundary},
-    history::UndoKind,
+    history::{RevisionSummary, UndoKind},
     increment,
     indent::{self, IndentStyle},
-    line_ending::{get_line_ending_of_str, line_end_char_index},
-    match_brackets,
+    line_ending::{
+        get_line_ending_of_str, line_end_char_index, line_without_line_ending,
+        rope_end_without_line_ending,
+    },
+    match_brackets, merge_conflict,
     movement::{self, move_vertically_visual, Direction},
     object, pos_at_coords,
     regex::{self, Regex},
     search::{self},
-    selection, surround,
+    selection,
+    snippets::{ActiveSnippet, UserSnippet},
+    surround,
     syntax::config::{BlockCommentToken, LanguageServerFeature},
     text_annotations::{Overlay, TextAnnotations},
     textobject,
     unicode::width::UnicodeWidthChar,
-    visual_offset_from_block, Deletion, LineEnding, Position, Range, Rope, RopeReader, RopeSlice,
-    Selection, SmallVec, Syntax, Tendril, Transaction,
+    visual_coords_at_pos, visual_offset_from_block, Assoc, ChangeSet, Deletion, LineEnding,
+    Position, Range, Rope, RopeReader, RopeSlice, Selection, SmallVec, Syntax, Tendril,
+    Transaction,
 };
 use helix_view::{
     document::{FormatterError, Mode, SCRATCH_BUFFER_NAME},
@@ -51,6 +58,7 @@
     info::Info,
     input::KeyEvent,
     keyboard::KeyCode,
+    register::{RegisterKind, Yank},
     theme::Style,
     tree,
     view::View,
@@ -66,7 +74,10 @@
     compositor::{self, Component, Compositor},
     filter_picker_entry,
     job::Callback,
-    ui::{self, overlay::overlaid, Picker, PickerColumn, Popup, Prompt, PromptEvent},
+    ui::{
+        self, overlay::overlaid, terminal::TerminalLayout, Picker, PickerColumn, Popup, Prompt,
+        PromptEvent,
+    },
 };
 
 use crate::job::{self, Jobs};
@@ -79,6 +90,7 @@
     future::Future,
     io::Read,
     num::NonZeroUsize,
+    ops,
 };
 
 use std::{
@@ -409,16 +421,24 @@ pub fn doc(&self) -> &str {
         code_action, "Perform code action",
         buffer_picker, "Open buffer picker",
         jumplist_picker, "Open jumplist picker",
+        marks_picker, "Open marks picker",
         symbol_picker, "Open symbol picker",
         syntax_symbol_picker, "Open symbol picker from syntax information",
         lsp_or_syntax_symbol_picker, "Open symbol picker from LSP or syntax information",
         changed_file_picker, "Open changed file picker",
+        hunk_picker, "Open picker of changes in the current buffer",
         select_references_to_symbol_under_cursor, "Select symbol references",
         workspace_symbol_picker, "Open workspace symbol picker",
         syntax_workspace_symbol_picker, "Open workspace symbol picker from syntax information",
         lsp_or_syntax_workspace_symbol_picker, "Open workspace symbol picker from LSP or syntax information",
+        incoming_calls_picker, "Open picker of calls to the symbol under the cursor",
+        outgoing_calls_picker, "Open picker of calls made by the symbol under the cursor",
+        type_hierarchy_picker, "Open picker of supertypes and subtypes of the type under the cursor",
         diagnostics_picker, "Open diagnostic picker",
         workspace_diagnostics_picker, "Open workspace diagnostic picker",
+        toggle_diagnostics_panel, "Toggle workspace diagnostics panel",
+        toggle_explorer_panel, "Toggle file explorer panel",
+        toggle_terminal_focus, "Move the focus between the editor and the terminal",
         last_picker, "Open last picker",
         insert_at_line_start, "Insert at start of line",
         insert_at_line_end, "Insert at end of line",
@@ -426,6 +446,7 @@ pub fn doc(&self) -> &str {
         open_above, "Open new line above selection",
         normal_mode, "Enter normal mode",
         select_mode, "Enter selection extend mode",
+        select_block, "Select a rectangular block in selection extend mode",
         exit_select_mode, "Exit selection mode",
         goto_definition, "Goto definition",
         goto_declaration, "Goto declaration",
@@ -447,6 +468,8 @@ pub fn doc(&self) -> &str {
         goto_last_accessed_file, "Goto last accessed file",
         goto_last_modified_file, "Goto last modified file",
         goto_last_modification, "Goto last modification",
+        set_mark, "Set mark at the cursor",
+        goto_mark, "Goto mark",
         goto_line, "Goto line",
         goto_last_line, "Goto last line",
         extend_to_last_line, "Extend to last line",
@@ -454,12 +477,23 @@ pub fn doc(&self) -> &str {
         goto_last_diag, "Goto last diagnostic",
         goto_next_diag, "Goto next diagnostic",
         goto_prev_diag, "Goto previous diagnostic",
+        goto_next_quickfix, "Goto next problem reported by the last task",
+        goto_prev_quickfix, "Goto previous problem reported by the last task",
         goto_next_change, "Goto next change",
         goto_prev_change, "Goto previous change",
         goto_first_change, "Goto first change",
         goto_last_change, "Goto last change",
+        conflict_next, "Goto next merge conflict",
+        conflict_prev, "Goto previous merge conflict",
+        conflict_keep_ours, "Resolve the merge conflict at the cursor keeping our side",
+        conflict_keep_theirs, "Resolve the merge conflict at the cursor keeping their side",
+        conflict_keep_both, "Resolve the merge conflict at the cursor keeping both sides",
         goto_line_start, "Goto line start",
         goto_line_end, "Goto line end",
+        goto_visual_line_start, "Goto start of visual line",
+        goto_visual_line_end, "Goto end of visual line",
+        extend_to_visual_line_start, "Extend to start of visual line",
+        extend_to_visual_line_end, "Extend to end of visual line",
         goto_column, "Goto column",
         extend_to_column, "Extend to column",
         goto_next_buffer, "Goto next buffer",
@@ -487,6 +521,9 @@ pub fn doc(&self) -> &str {
         redo, "Redo change",
         earlier, "Move backward in history",
         later, "Move forward in history",
+        select_prev, "Restore the previous selection",
+        select_next, "Restore the next selection",
+        undo_tree_picker, "Open undo tree picker",
         commit_undo_checkpoint, "Commit changes to new checkpoint",
         yank, "Yank selection",
         yank_to_clipboard, "Yank selections to clipboard",
@@ -501,6 +538,7 @@ pub fn doc(&self) -> &str {
         replace_selections_with_primary_clipboard, "Replace selections by primary clipboard",
         paste_after, "Paste after selection",
         paste_before, "Paste before selection",
+        paste_history_picker, "Open picker of the recent yanks to paste one after selection",
         paste_clipboard_after, "Paste clipboard after selections",
         paste_clipboard_before, "Paste clipboard before selections",
         paste_primary_clipboard_after, "Paste primary clipboard after selections",
@@ -513,10 +551,17 @@ pub fn doc(&self) -> &str {
         keep_selections, "Keep selections matching regex",
         remove_selections, "Remove selections matching regex",
         align_selections, "Align selections in column",
+        align_selections_on, "Align the first match of a regex on each selected line",
         keep_primary_selection, "Keep primary selection",
         remove_primary_selection, "Remove primary selection",
         completion, "Invoke completion popup",
+        inline_completion, "Request inline completion",
+        accept_inline_completion, "Accept inline completion",
+        accept_inline_completion_word, "Accept next word of inline completion",
+        next_inline_completion, "Show next inline completion",
+        prev_inline_completion, "Show previous inline completion",
         hover, "Show docs for item under cursor",
+        cycle_color_presentation, "Cycle through the presentations of the color under cursor",
         toggle_comments, "Comment/uncomment selections",
         toggle_line_comments, "Line comment/uncomment selections",
         toggle_block_comments, "Block comment/uncomment selections",
@@ -533,6 +578,8 @@ pub fn doc(&self) -> &str {
         select_all_children, "Select all children of the current node",
         jump_forward, "Jump forward on jumplist",
         jump_backward, "Jump backward on jumplist",
+        goto_previous_buffer_location, "Go to the previous location in the buffer history",
+        goto_next_buffer_location, "Go to the next location in the buffer history",
         save_selection, "Save current selection to jumplist",
         jump_view_right, "Jump to right split",
         jump_view_left, "Jump to left split",
@@ -543,6 +590,11 @@ pub fn doc(&self) -> &str {
         swap_view_up, "Swap with split above",
         swap_view_down, "Swap with split below",
         transpose_view, "Transpose splits",
+        grow_view_width, "Increase the width of the split",
+        shrink_view_width, "Decrease the width of the split",
+        grow_view_height, "Increase the height of the split",
+        shrink_view_height, "Decrease the height of the split",
+        equalize_views, "Make all splits equally sized",
         rotate_view, "Goto next window",
         rotate_view_reverse, "Goto previous window",
         hsplit, "Horizontal bottom split",
@@ -553,6 +605,9 @@ pub fn doc(&self) -> &str {
         wonly, "Close windows except current",
         select_register, "Select register",
         insert_register, "Insert register",
+        insert_unicode, "Insert a character by its codepoint, or the next key literally",
+        insert_digraph, "Insert a character by its digraph",
+        unicode_picker, "Open picker of the digraph characters to insert by name",
         copy_between_registers, "Copy between two registers",
         align_view_middle, "Align view middle",
         align_view_top, "Align view top",
@@ -560,6 +615,10 @@ pub fn doc(&self) -> &str {
         align_view_bottom, "Align view bottom",
         scroll_up, "Scroll view up",
         scroll_down, "Scroll view down",
+        fold_selection, "Fold lines spanned by selections",
+        fold_toggle, "Toggle fold of the syntax node or indented block at cursor",
+        fold_all_syntax, "Fold all foldable syntax nodes",
+        unfold_all, "Open all folds",
         match_brackets, "Goto matching bracket",
         surround_add, "Surround add",
         surround_replace, "Surround replace",
@@ -596,6 +655,7 @@ pub fn doc(&self) -> &str {
         dap_edit_log, "Edit breakpoint log message on current line",
         dap_switch_thread, "Switch current thread",
         dap_switch_stack_frame, "Switch stack frame",
+        dap_switch_session, "Switch debugging session",
         dap_enable_exceptions, "Enable exception breakpoints",
         dap_disable_exceptions, "Disable exception breakpoints",
         shell_pipe, "Pipe selections through shell command",
@@ -609,11 +669,14 @@ pub fn doc(&self) -> &str {
         decrement, "Decrement item under cursor",
         record_macro, "Record macro",
         replay_macro, "Replay macro",
+        replay_macro_on_each_selection, "Replay macro on each selection",
+        repeat_last_change, "Repeat last change",
         command_palette, "Open command palette",
         goto_word, "Jump to a two-character label",
         extend_to_word, "Extend to a two-character label",
         goto_next_tabstop, "Goto next snippet placeholder",
         goto_prev_tabstop, "Goto next snippet placeholder",
+        expand_snippet, "Expand snippet before cursor or pick a snippet",
         rotate_selections_first, "Make the first selection your primary one",
         rotate_selections_last, "Make the last selection your primary one",
     );
@@ -906,6 +969,51 @@ fn goto_line_start(cx: &mut Context) {
     )
 }
 
+type VisualLineMoveFn = fn(RopeSlice, Range, Movement, &TextFormat, &mut TextAnnotations) -> Range;
+
+fn goto_visual_line_impl(cx: &mut Context, move_fn: VisualLineMoveFn, movement: Option<Movement>) {
+    let movement = movement.unwrap_or(if cx.editor.mode == Mode::Select {
+        Movement::Extend
+    } else {
+        Movement::Move
+    });
+    let (view, doc) = current!(cx.editor);
+    let text = doc.text().slice(..);
+    let text_fmt = doc.text_format(view.inner_area(doc).width, None);
+    let mut annotations = view.text_annotations(doc, None);
+
+    let selection = doc
+        .selection(view.id)
+        .clone()
+        .transform(|range| move_fn(text, range, movement, &text_fmt, &mut annotations));
+    drop(annotations);
+    doc.set_selection(view.id, selection);
+}
+
+fn goto_visual_line_start(cx: &mut Context) {
+    goto_visual_line_impl(cx, movement::move_to_visual_line_start, None)
+}
+
+fn goto_visual_line_end(cx: &mut Context) {
+    goto_visual_line_impl(cx, movement::move_to_visual_line_end, None)
+}
+
+fn extend_to_visual_line_start(cx: &mut Context) {
+    goto_visual_line_impl(
+        cx,
+        movement::move_to_visual_line_start,
+        Some(Movement::Extend),
+    )
+}
+
+fn extend_to_visual_line_end(cx: &mut Context) {
+    goto_visual_line_impl(
+        cx,
+        movement::move_to_visual_line_end,
+        Some(Movement::Extend),
+    )
+}
+
 fn goto_next_buffer(cx: &mut Context) {
     goto_buffer(cx.editor, Direction::Forward, cx.count());
 }
@@ -916,27 +1024,16 @@ fn goto_previous_buffer(cx: &mut Context) {
 
 fn goto_buffer(editor: &mut Editor, direction: Direction, count: usize) {
     let current = view!(editor).doc;
+    // the buffers are cycled in the order of the bufferline
+    let buffers = editor.buffers();
+    let len = buffers.len();
+    let index = buffers.iter().position(|id| *id == current).unwrap_or(0);
+    let index = match direction {
+        Direction::Forward => (index + count) % len,
+        Direction::Backward => (index + len - count % len) % len,
+    };
 
-    let id = match direction {
-        Direction::Forward => {
-            let iter = editor.documents.keys();
-            // skip 'count' times past current buffer
-            iter.cycle().skip_while(|id| *id != &current).nth(count)
-        }
-        Direction::Backward => {
-            let iter = editor.documents.keys();
-            // skip 'count' times past current buffer
-            iter.rev()
-                .cycle()
-                .skip_while(|id| *id != &current)
-                .nth(count)
-        }
-    }
-    .unwrap();
-
-    let id = *id;
-
-    editor.switch(id, Action::Replace);
+    editor.switch(buffers[index], Action::Replace);
 }
 
 fn extend_to_line_start(cx: &mut Context) {
@@ -1141,6 +1238,69 @@ fn align_selections(cx: &mut Context) {
     exit_select_mode(cx);
 }
 
+/// Prompts for a regex and pads the lines of the selections so that the first match on each
+/// line starts at the same column. The prompt is prefilled with the primary selection, for
+/// example the `=` under the cursor.
+fn align_selections_on(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    let text = doc.text().slice(..);
+    let primary = doc.selection(view.id).primary().fragment(text);
+    let input = if primary.contains(['\n', '\r']) {
+        String::new()
+    } else {
+        regex::escape(&primary)
+    };
+
+    ui::prompt_with_input(
+        cx,
+        "align on:".into(),
+        input,
+        None,
+        ui::completers::none,
+        move |cx, input, event| {
+            if event != PromptEvent::Validate || input.is_empty() {
+                return;
+            }
+            match rope::Regex::new(input) {
+                Ok(regex) => align_on_matches(cx.editor, &regex),
+                Err(err) => cx.editor.set_error(format!("Invalid regex: {err}")),
+            }
+        },
+    );
+}
+
+fn align_on_matches(editor: &mut Editor, regex: &rope::Regex) {
+    let (view, doc) = current!(editor);
+    let text = doc.text().slice(..);
+    let tab_width = doc.tab_width();
+
+    let matches: Vec<_> = get_lines(doc, view.id)
+        .into_iter()
+        .filter_map(|line| {
+            let start = text.line_to_char(line);
+            let end = line_end_char_index(&text, line);
+            let mat = regex.find(text.regex_input_at(start..end))?;
+            let pos = text.byte_to_char(mat.start());
+            Some((pos, visual_coords_at_pos(text, pos, tab_width).col))
+        })
+        .collect();
+    let Some(column) = matches.iter().map(|&(_, col)| col).max() else {
+        editor.set_error("No matches on the selected lines");
+        return;
+    };
+
+    let changes = matches
+        .into_iter()
+        .filter(|&(_, col)| col < column)
+        .map(|(pos, col)| (pos, pos, Some(" ".repeat(column - col).into())));
+    let transaction = Transaction::change(doc.text(), changes);
+    doc.apply(&transaction, view.id);
+    doc.append_changes_to_history(view);
+    if editor.mode == Mode::Select {
+        editor.mode = Mode::Normal;
+    }
+}
+
 fn goto_window(cx: &mut Context, align: Align) {
     let count = cx.count() - 1;
     let config = cx.editor.config();
@@ -2616,6 +2776,7 @@ struct GlobalSearchConfig {
             return async { Err(anyhow::anyhow!("Current working directory does not exist")) }
                 .boxed();
         }
+        let search_roots = search_roots(editor, search_root);
 
         let documents: Vec<_> = editor
             .documents()
@@ -2639,9 +2800,10 @@ struct GlobalSearchConfig {
         };
 
         let dedup_symlinks = config.file_picker_config.deduplicate_links;
-        let absolute_root = search_root
-            .canonicalize()
-            .unwrap_or_else(|_| search_root.clone());
+        let absolute_roots: Vec<_> = search_roots
+            .iter()
+            .map(|root| root.canonicalize().unwrap_or_else(|_| root.clone()))
+            .collect();
 
         let injector = injector.clone();
         async move {
@@ -2649,7 +2811,11 @@ struct GlobalSearchConfig {
                 .binary_detection(BinaryDetection::quit(b'\x00'))
                 .multi_line(true)
                 .build();
-            WalkBuilder::new(search_root)
+            let mut walk_builder = WalkBuilder::new(&search_roots[0]);
+            for root in &search_roots[1..] {
+                walk_builder.add(root);
+            }
+            walk_builder
                 .hidden(config.file_picker_config.hidden)
                 .parents(config.file_picker_config.parents)
                 .ignore(config.file_picker_config.ignore)
@@ -2659,7 +2825,9 @@ struct GlobalSearchConfig {
                 .git_exclude(config.file_picker_config.git_exclude)
                 .max_depth(config.file_picker_config.max_depth)
                 .filter_entry(move |entry| {
-                    filter_picker_entry(entry, &absolute_root, dedup_symlinks)
+                    absolute_roots
+                        .iter()
+                        .all(|root| filter_picker_entry(entry, root, dedup_symlinks))
                 })
                 .add_custom_ignore_filename(helix_loader::config_dir().join("ignore"))
                 .add_custom_ignore_filename(".helix/ignore")
@@ -2784,11 +2952,77 @@ struct GlobalSearchConfig {
          }| { Some((path.as_ref().into(), Some((*line_start, *line_end)))) },
     )
     .with_history_register(Some(reg))
-    .with_dynamic_query(get_files, Some(275));
+    .with_dynamic_query(get_files, Some(275))
+    .with_matches_key_handler(crate::alt!('e'), |cx, results, _config| {
+        let lines = results.iter().flat_map(|result| {
+            (result.line_start..=result.line_end).map(|line| (&*result.path, line))
+        });
+        search_results_buffer(cx.editor, lines);
+        None
+    });
 
     cx.push_layer(Box::new(overlaid(picker)));
 }
 
+/// Opens a scratch buffer listing the given lines as `path:line:contents`. Editing the contents
+/// and running `:replace-apply` writes the changes back to the files.
+fn search_results_buffer<'a>(editor: &mut Editor, lines: impl Iterator<Item = (&'a Path, usize)>) {
+    use std::fmt::Write as _;
+
+    let mut lines: Vec<_> = lines.collect();
+    lines.sort_unstable();
+    lines.dedup();
+
+    let mut texts: HashMap<&Path, Option<Rope>> = HashMap::new();
+    let mut contents = String::new();
+    let mut originals = HashMap::new();
+    for (path, line) in lines {
+        let text = texts.entry(path).or_insert_with(|| {
+            let path = helix_stdx::path::canonicalize(path);
+            match editor.document_by_path(&path) {
+                Some(doc) => Some(doc.text().clone()),
+                None => std::fs::File::open(&path).and_then(Rope::from_reader).ok(),
+            }
+        });
+        let Some(text) = text.as_ref().filter(|text| line < text.len_lines()) else {
+            continue;
+        };
+        let text = text.slice(..);
+        let line_text = line_without_line_ending(&text, line).to_string();
+        let _ = writeln!(contents, "{}:{}:{}", path.display(), line + 1, line_text);
+        originals.insert((helix_stdx::path::canonicalize(path), line), line_text);
+    }
+
+    let doc_id = editor.new_file(Action::Replace);
+    editor.search_results.insert(doc_id, originals);
+    let view = view_mut!(editor);
+    let doc = doc_mut!(editor, &doc_id);
+    let transaction = Transaction::insert(doc.text(), doc.selection(view.id), contents.into())
+        .with_selection(Selection::point(0));
+    doc.apply(&transaction, view.id);
+    doc.append_changes_to_history(view);
+}
+
+/// Parses a `path:line:contents` line of a buffer created by [search_results_buffer]. The
+/// returned line is zero based.
+fn parse_search_result(line: &str) -> Option<(&Path, usize, &str)> {
+    let mut start = 0;
+    while let Some(sep) = line[start..].find(':').map(|i| start + i) {
+        let rest = &line[sep + 1..];
+        let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+        if digits > 0 && rest[digits..].starts_with(':') {
+            let line_nr: usize = rest[..digits].parse().ok()?;
+            return Some((
+                Path::new(&line[..sep]),
+                line_nr.checked_sub(1)?,
+                &rest[digits + 1..],
+            ));
+        }
+        start = sep + 1;
+    }
+    None
+}
+
 enum Extend {
     Above,
     Below,
@@ -2975,6 +3209,42 @@ fn selection_is_linewise(selection: &Selection, text: &Rope) -> bool {
     })
 }
 
+/// Returns how the fragments of `selection` should be pasted after yanking them.
+fn yank_kind(selection: &Selection, text: RopeSlice) -> RegisterKind {
+    let ranges = selection.ranges();
+    let whole_lines = ranges.iter().all(|range| {
+        let (start_line, end_line) = range.line_range(text);
+        let start = text.line_to_char(start_line);
+        let end = text.line_to_char((end_line + 1).min(text.len_lines()));
+        // a single character is only a line if it is a line ending
+        (range.len() > 1 || range.slice(text).len_lines() > 1)
+            && start == range.from()
+            && end == range.to()
+    });
+    if whole_lines {
+        return RegisterKind::Linewise;
+    }
+
+    // single line ranges starting at the same column of consecutive lines form a block
+    let row = |range: &Range| {
+        let line = text.char_to_line(range.from());
+        (line, range.from() - text.line_to_char(line))
+    };
+    let block = ranges.len() > 1
+        && ranges
+            .iter()
+            .all(|range| range.slice(text).len_lines() == 1)
+        && ranges.windows(2).all(|pair| {
+            let (line, column) = row(&pair[0]);
+            row(&pair[1]) == (line + 1, column)
+        });
+    if block {
+        RegisterKind::Blockwise
+    } else {
+        RegisterKind::Charwise
+    }
+}
+
 enum YankAction {
     Yank,
     NoYank,
@@ -2990,10 +3260,11 @@ fn delete_selection_impl(cx: &mut Context, op: Operation, yank: YankAction) {
         // yank the selection
         let text = doc.text().slice(..);
         let values: Vec<String> = selection.fragments(text).map(Cow::into_owned).collect();
+        let kind = yank_kind(selection, text);
         let reg_name = cx
             .register
             .unwrap_or_else(|| cx.editor.config.load().default_yank_register);
-        if let Err(err) = cx.editor.registers.write(reg_name, values) {
+        if let Err(err) = cx.editor.registers.write_yank(reg_name, values, kind) {
             cx.editor.set_error(err.to_string());
             return;
         }
@@ -3113,6 +3384,11 @@ fn ensure_selections_forward(cx: &mut Context) {
 }
 
 fn enter_insert_mode(cx: &mut Context) {
+    if doc!(cx.editor).readonly() {
+        cx.editor
+            .set_error("The buffer is read-only, see `:set-readonly`");
+        return;
+    }
     cx.editor.mode = Mode::Insert;
 }
 
@@ -3167,13 +3443,33 @@ fn append_mode(cx: &mut Context) {
     doc.set_selection(view.id, selection);
 }
 
+/// Returns `primary` followed by the additional workspace roots of the editor, leaving out
+/// missing roots and roots nested inside another one so that no file is listed twice.
+fn search_roots(editor: &Editor, primary: PathBuf) -> Vec<PathBuf> {
+    let mut roots = vec![primary];
+    roots.extend(editor.workspace_roots.iter().cloned());
+    roots
+        .iter()
+        .enumerate()
+        .filter(|&(i, root)| {
+            root.exists()
+                && !roots[..i].contains(root)
+                && !roots
+                    .iter()
+                    .any(|other| other != root && root.starts_with(other))
+        })
+        .map(|(_, root)| root.clone())
+        .collect()
+}
+
 fn file_picker(cx: &mut Context) {
     let root = find_workspace().0;
     if !root.exists() {
         cx.editor.set_error("Workspace directory does not exist");
         return;
     }
-    let picker = ui::file_picker(cx.editor, root);
+    let roots = search_roots(cx.editor, root);
+    let picker = ui::workspace_file_picker(cx.editor, roots);
     cx.push_layer(Box::new(overlaid(picker)));
 }
 
@@ -3221,7 +3517,7 @@ fn file_explorer(cx: &mut Context) {
         return;
     }
 
-    if let Ok(picker) = ui::file_explorer(root, cx.editor) {
+    if let Ok(picker) = ui::file_explorer(root, doc!(cx.editor).path(), cx.editor) {
         cx.push_layer(Box::new(overlaid(picker)));
     }
 }
@@ -3248,7 +3544,7 @@ fn file_explorer_in_current_buffer_directory(cx: &mut Context) {
         }
     };
 
-    if let Ok(picker) = ui::file_explorer(path, cx.editor) {
+    if let Ok(picker) = ui::file_explorer(path, doc!(cx.editor).path(), cx.editor) {
         cx.push_layer(Box::new(overlaid(picker)));
     }
 }
@@ -3261,7 +3557,7 @@ fn file_explorer_in_current_directory(cx: &mut Context) {
         return;
     }
 
-    if let Ok(picker) = ui::file_explorer(cwd, cx.editor) {
+    if let Ok(picker) = ui::file_explorer(cwd, doc!(cx.editor).path(), cx.editor) {
         cx.push_layer(Box::new(overlaid(picker)));
     }
 }
@@ -3387,6 +3683,13 @@ struct BufferMeta<'a> {
             (cursor_line, cursor_line)
         });
         Some((meta.id.into(), lines))
+    })
+    .with_matches_key_handler(crate::alt!('d'), |cx, metas, _| {
+        let doc_ids: Vec<_> = metas.iter().map(|meta| meta.id).collect();
+        if let Err(err) = typed::buffer_close_by_ids_impl(cx, &doc_ids, false) {
+            cx.editor.set_error(err.to_string());
+        }
+        None
     });
     cx.push_layer(Box::new(overlaid(picker)));
 }
@@ -3479,6 +3782,122 @@ struct JumpMeta<'a> {
     cx.push_layer(Box::new(overlaid(picker)));
 }
 
+fn marks_picker(cx: &mut Context) {
+    struct MarkItem {
+        name: char,
+        path: PathBuf,
+        relative_path: PathBuf,
+        line: usize,
+    }
+
+    let editor = &mut *cx.editor;
+    let items: Vec<_> = editor
+        .marks
+        .iter()
+        .map(|(name, mark)| {
+            let doc = editor.documents.values_mut().find(|doc| {
+                doc.path()
+                    .is_some_and(|path| helix_stdx::path::same_file(path, mark.path()))
+            });
+            MarkItem {
+                name,
+                path: mark.path().to_path_buf(),
+                relative_path: helix_stdx::path::get_relative_path(mark.path()).into_owned(),
+                line: mark.position(doc).row,
+            }
+        })
+        .collect();
+
+    let columns = [
+        ui::PickerColumn::new("name", |item: &MarkItem, _| item.name.to_string().into()),
+        ui::PickerColumn::new("path", |item: &MarkItem, config: &PathStyleConfig| {
+            config.stylize(Some(&item.relative_path), Some(item.line))
+        }),
+    ];
+
+    let picker = Picker::new(
+        columns,
+        1, // path
+        items,
+        PathStyleConfig::new(&cx.editor.theme),
+        |cx, item, action| goto_mark_impl(cx.editor, item.name, action),
+    )
+    .with_preview(|_editor, item| Some((item.path.as_path().into(), Some((item.line, item.line)))));
+    cx.push_layer(Box::new(overlaid(picker)));
+}
+
+fn undo_tree_picker(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    // pending changes would otherwise be missing from the tree
+    doc.append_changes_to_history(view);
+    let doc_id = doc.id();
+    let current = doc.get_current_revision();
+    // the newest revisions first
+    let revisions: Vec<_> = doc.revisions().into_iter().rev().collect();
+    let initial_cursor = revisions
+        .iter()
+        .position(|rev| rev.revision == current)
+        .unwrap_or(0);
+
+    let columns = [
+        ui::PickerColumn::new("revision", |rev: &RevisionSummary, current: &usize| {
+            // `@` marks the current revision, `|` the revisions that can be reached with undo
+            let marker = if rev.revision == *current {
+                '@'
+            } else if rev.is_ancestor {
+                '|'
+            } else {
+                ' '
+            };
+            format!("{marker} {}", rev.revision).into()
+        }),
+        ui::PickerColumn::new("parent", |rev: &RevisionSummary, _| {
+            if rev.revision == 0 {
+                "".into()
+            } else {
+                rev.parent.to_string().into()
+            }
+        }),
+        ui::PickerColumn::new("age", |rev: &RevisionSummary, _| {
+            format_elapsed(rev.timestamp.elapsed()).into()
+        }),
+        ui::PickerColumn::new("changes", |rev: &RevisionSummary, _| {
+            if rev.revision == 0 {
+                "original".into()
+            } else {
+                format!("+{} -{}", rev.inserted, rev.deleted).into()
+            }
+        }),
+        ui::PickerColumn::new("inserted", |rev: &RevisionSummary, _| {
+            rev.excerpt.as_str().into()
+        }),
+    ];
+
+    let picker = Picker::new(columns, 4, revisions, current, move |cx, rev, _action| {
+        let scrolloff = cx.editor.config().scrolloff;
+        let (view, doc) = current!(cx.editor);
+        if doc.id() != doc_id {
+            return;
+        }
+        if doc.jump_to_revision(view, rev.revision) {
+            view.ensure_cursor_in_view_center(doc, scrolloff);
+        }
+    })
+    .with_initial_cursor(initial_cursor);
+    cx.push_layer(Box::new(overlaid(picker)));
+}
+
+/// Formats the time since a revision was committed, for example `5m ago`.
+fn format_elapsed(elapsed: std::time::Duration) -> String {
+    let secs = elapsed.as_secs();
+    match secs {
+        0..60 => format!("{secs}s ago"),
+        60..3600 => format!("{}m ago", secs / 60),
+        3600..86400 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
+
 fn changed_file_picker(cx: &mut Context) {
     pub struct FileChangeData {
         cwd: PathBuf,
@@ -3573,6 +3992,108 @@ pub struct FileChangeData {
     cx.push_layer(Box::new(overlaid(picker)));
 }
 
+fn hunk_picker(cx: &mut Context) {
+    struct HunkMeta {
+        range: Range,
+        line: usize,
+        kind: HunkKind,
+        text: String,
+    }
+
+    #[derive(Clone, Copy)]
+    enum HunkKind {
+        Added,
+        Modified,
+        Removed,
+    }
+
+    struct HunkStyles {
+        added: Style,
+        modified: Style,
+        removed: Style,
+    }
+
+    let doc = doc!(cx.editor);
+    let Some(handle) = doc.diff_handle() else {
+        cx.editor
+            .set_error("Diff is not available in the current buffer");
+        return;
+    };
+    let doc_id = doc.id();
+    let diff = handle.load();
+    let text = doc.text().slice(..);
+    let diff_base = diff.diff_base().slice(..);
+    let items: Vec<_> = (0..diff.len())
+        .map(|i| {
+            let hunk = diff.nth_hunk(i);
+            let (kind, line) = if hunk.after.is_empty() {
+                (
+                    HunkKind::Removed,
+                    diff_base.line(hunk.before.start as usize),
+                )
+            } else if hunk.before.is_empty() {
+                (HunkKind::Added, text.line(hunk.after.start as usize))
+            } else {
+                (HunkKind::Modified, text.line(hunk.after.start as usize))
+            };
+            HunkMeta {
+                range: hunk_range(hunk.clone(), text),
+                line: hunk.after.start as usize,
+                kind,
+                text: line.to_string().trim().to_string(),
+            }
+        })
+        .collect();
+    drop(diff);
+
+    if items.is_empty() {
+        cx.editor
+            .set_status("There are no changes in the current buffer");
+        return;
+    }
+
+    let columns = [
+        PickerColumn::new("line", |item: &HunkMeta, _| {
+            (item.line + 1).to_string().into()
+        }),
+        PickerColumn::new("change", |item: &HunkMeta, styles: &HunkStyles| {
+            match item.kind {
+                HunkKind::Added => Span::styled("+ added", styles.added),
+                HunkKind::Modified => Span::styled("~ modified", styles.modified),
+                HunkKind::Removed => Span::styled("- removed", styles.removed),
+            }
+            .into()
+        }),
+        PickerColumn::new("contents", |item: &HunkMeta, _| item.text.as_str().into()),
+    ];
+
+    let picker = Picker::new(
+        columns,
+        2, // contents
+        items,
+        HunkStyles {
+            added: cx.editor.theme.get("diff.plus"),
+            modified: cx.editor.theme.get("diff.delta"),
+            removed: cx.editor.theme.get("diff.minus"),
+        },
+        move |cx, meta: &HunkMeta, action| {
+            cx.editor.switch(doc_id, action);
+            let config = cx.editor.config();
+            let (view, doc) = (view_mut!(cx.editor), doc_mut!(cx.editor, &doc_id));
+            push_jump(view, doc);
+            doc.set_selection(
+                view.id,
+                Selection::single(meta.range.anchor, meta.range.head),
+            );
+            if action.align_view(view, doc.id()) {
+                view.ensure_cursor_in_view_center(doc, config.scrolloff);
+            }
+        },
+    )
+    .with_preview(move |_editor, meta| Some((doc_id.into(), Some((meta.line, meta.line)))));
+    cx.push_layer(Box::new(overlaid(picker)));
+}
+
 pub fn command_palette(cx: &mut Context) {
     let register = cx.register;
     let count = cx.count;
@@ -3666,6 +4187,48 @@ fn last_picker(cx: &mut Context) {
     }));
 }
 
+fn toggle_diagnostics_panel(cx: &mut Context) {
+    cx.callback.push(Box::new(|compositor, _| {
+        if let Some(editor_view) = compositor.find::<ui::EditorView>() {
+            editor_view.toggle_diagnostics_panel();
+        }
+    }));
+}
+
+fn toggle_explorer_panel(cx: &mut Context) {
+    // a buffer outside of the workspace is shown in its own workspace
+    let workspace = find_workspace().0;
+    let root = match doc!(cx.editor).path().and_then(|path| path.parent()) {
+        Some(dir) if !dir.starts_with(&workspace) => helix_loader::find_workspace_in(dir).0,
+        _ => workspace,
+    };
+    if !root.exists() {
+        cx.editor.set_error("Workspace directory does not exist");
+        return;
+    }
+    cx.callback.push(Box::new(move |compositor, cx| {
+        if let Some(editor_view) = compositor.find::<ui::EditorView>() {
+            editor_view.toggle_explorer_panel(root, cx.editor);
+        }
+    }));
+}
+
+fn toggle_terminal_focus(cx: &mut Context) {
+    cx.callback.push(Box::new(|compositor, cx| {
+        let Some(editor_view) = compositor.find::<ui::EditorView>() else {
+            return;
+        };
+        match &mut editor_view.terminal {
+            Some(terminal) => terminal.set_focused(!terminal.is_focused()),
+            None => {
+                if let Err(err) = editor_view.open_terminal(cx.editor, TerminalLayout::Horizontal) {
+                    cx.editor.set_error(err.to_string());
+                }
+            }
+        }
+    }));
+}
+
 /// Fallback position to use for [`insert_with_indent`].
 enum IndentFallbackPos {
     LineStart,
@@ -4085,17 +4648,78 @@ fn goto_last_modified_file(cx: &mut Context) {
     }
 }
 
-fn select_mode(cx: &mut Context) {
-    let (view, doc) = current!(cx.editor);
-    let text = doc.text().slice(..);
-
-    // Make sure end-of-document selections are also 1-width.
-    // (With the exception of being in an empty document, of course.)
-    let selection = doc.selection(view.id).clone().transform(|range| {
-        if range.is_empty() && range.head == text.len_chars() {
-            Range::new(
-                graphemes::prev_grapheme_boundary(text, range.anchor),
-                range.head,
+fn set_mark(cx: &mut Context) {
+    cx.on_next_key(move |cx, event| {
+        let Some(name) = event.char() else {
+            return;
+        };
+        let (view, doc) = current!(cx.editor);
+        // the mark is set in the current text, which must match the recorded revision
+        doc.append_changes_to_history(view);
+        let pos = doc
+            .selection(view.id)
+            .primary()
+            .cursor(doc.text().slice(..));
+        if cx.editor.marks.set(name, doc, pos) {
+            cx.editor.set_status(format!("Set mark '{name}'"));
+        } else {
+            cx.editor
+                .set_error("Marks can only be set in buffers backed by a file");
+        }
+    })
+}
+
+fn goto_mark(cx: &mut Context) {
+    let marks: Vec<_> = cx
+        .editor
+        .marks
+        .iter()
+        .map(|(name, mark)| {
+            let path = helix_stdx::path::get_relative_path(mark.path());
+            (name.to_string(), path.to_string_lossy().into_owned())
+        })
+        .collect();
+    cx.editor.autoinfo = Some(Info::new("Goto mark", &marks));
+    cx.on_next_key(move |cx, event| {
+        cx.editor.autoinfo = None;
+        if let Some(name) = event.char() {
+            goto_mark_impl(cx.editor, name, Action::Replace);
+        }
+    })
+}
+
+fn goto_mark_impl(editor: &mut Editor, name: char, action: Action) {
+    let Some(path) = editor.marks.get(name).map(|mark| mark.path().to_path_buf()) else {
+        editor.set_error(format!("Mark '{name}' is not set"));
+        return;
+    };
+    let (view, doc) = current!(editor);
+    push_jump(view, doc);
+    let doc_id = match editor.open(&path, action) {
+        Ok(doc_id) => doc_id,
+        Err(err) => {
+            editor.set_error(format!("Failed to open {}: {err}", path.display()));
+            return;
+        }
+    };
+    let scrolloff = editor.config().scrolloff;
+    let (view, doc) = (view_mut!(editor), doc_mut!(editor, &doc_id));
+    let pos = editor.marks.get(name).map_or(0, |mark| mark.pos(doc));
+    doc.set_selection(view.id, Selection::point(pos));
+    view.ensure_cursor_in_view_center(doc, scrolloff);
+}
+
+fn select_mode(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    let text = doc.text().slice(..);
+
+    // Make sure end-of-document selections are also 1-width.
+    // (With the exception of being in an empty document, of course.)
+    let selection = doc.selection(view.id).clone().transform(|range| {
+        if range.is_empty() && range.head == text.len_chars() {
+            Range::new(
+                graphemes::prev_grapheme_boundary(text, range.anchor),
+                range.head,
             )
         } else {
             range
@@ -4106,6 +4730,25 @@ fn select_mode(cx: &mut Context) {
     cx.editor.mode = Mode::Select;
 }
 
+/// Starts selecting a rectangular block at the cursor, the block is kept in sync with the
+/// cursor until select mode is left. Stops selecting a block but keeps its ranges if a block
+/// is being selected.
+fn select_block(cx: &mut Context) {
+    let mode = cx.editor.mode;
+    let (view, doc) = current!(cx.editor);
+    if mode == Mode::Select && view.block_anchor.take().is_some() {
+        return;
+    }
+    let text = doc.text().slice(..);
+    let cursor = doc.selection(view.id).primary().cursor(text);
+    view.block_anchor = Some(visual_coords_at_pos(text, cursor, doc.tab_width()));
+    doc.set_selection(
+        view.id,
+        Selection::single(cursor, graphemes::next_grapheme_boundary(text, cursor)),
+    );
+    select_mode(cx);
+}
+
 fn exit_select_mode(cx: &mut Context) {
     if cx.editor.mode == Mode::Select {
         cx.editor.mode = Mode::Normal;
@@ -4192,6 +4835,49 @@ fn goto_prev_diag(cx: &mut Context) {
     cx.editor.apply_motion(motion)
 }
 
+fn goto_next_quickfix(cx: &mut Context) {
+    goto_quickfix_impl(cx, Direction::Forward)
+}
+
+fn goto_prev_quickfix(cx: &mut Context) {
+    goto_quickfix_impl(cx, Direction::Backward)
+}
+
+fn goto_quickfix_impl(cx: &mut Context, direction: Direction) {
+    let count = cx.count();
+    let entry = match direction {
+        Direction::Forward => cx.editor.quickfix.next(count),
+        Direction::Backward => cx.editor.quickfix.prev(count),
+    };
+    let Some(entry) = entry.cloned() else {
+        cx.editor.set_status("No more problems");
+        return;
+    };
+
+    let (view, doc) = current!(cx.editor);
+    push_jump(view, doc);
+    if let Err(err) = cx.editor.open(&entry.path, Action::Replace) {
+        cx.editor
+            .set_error(format!("failed to open {}: {err}", entry.path.display()));
+        return;
+    }
+    let (view, doc) = current!(cx.editor);
+    let text = doc.text().slice(..);
+    let line = entry.line.min(text.len_lines().saturating_sub(1));
+    let pos = (text.line_to_char(line) + entry.column).min(line_end_char_index(&text, line));
+    doc.set_selection(view.id, Selection::point(pos));
+    align_view(doc, view, Align::Center);
+
+    let index = cx.editor.quickfix.current().unwrap_or_default() + 1;
+    let total = cx.editor.quickfix.entries().len();
+    let message = format!("[{index}/{total}] {}", entry.message);
+    if entry.severity == Severity::Error {
+        cx.editor.set_error(message);
+    } else {
+        cx.editor.set_status(message);
+    }
+}
+
 fn goto_first_change(cx: &mut Context) {
     goto_first_change_impl(cx, false);
 }
@@ -4277,6 +4963,86 @@ fn goto_next_change_impl(cx: &mut Context, direction: Direction) {
     cx.editor.apply_motion(motion);
 }
 
+fn conflict_next(cx: &mut Context) {
+    goto_conflict_impl(cx, Direction::Forward)
+}
+
+fn conflict_prev(cx: &mut Context) {
+    goto_conflict_impl(cx, Direction::Backward)
+}
+
+fn goto_conflict_impl(cx: &mut Context, direction: Direction) {
+    let count = cx.count() - 1;
+    let motion = move |editor: &mut Editor| {
+        let (view, doc) = current!(editor);
+        let text = doc.text().slice(..);
+        let conflicts = merge_conflict::find_conflicts(text);
+        if conflicts.is_empty() {
+            editor.set_status("No merge conflicts in the current buffer");
+            return;
+        }
+
+        let selection = doc.selection(view.id).clone().transform(|range| {
+            let cursor_line = range.cursor_line(text);
+            let conflict = match direction {
+                Direction::Forward => conflicts
+                    .iter()
+                    .filter(|conflict| conflict.start > cursor_line)
+                    .nth(count),
+                Direction::Backward => conflicts
+                    .iter()
+                    .rev()
+                    .filter(|conflict| conflict.end < cursor_line)
+                    .nth(count),
+            };
+            let Some(conflict) = conflict else {
+                return range;
+            };
+            let end = (conflict.end + 1).min(text.len_lines());
+            let new_range = Range::new(text.line_to_char(conflict.start), text.line_to_char(end));
+            if editor.mode == Mode::Select {
+                Range::new(range.anchor, new_range.head)
+            } else {
+                new_range.with_direction(direction)
+            }
+        });
+
+        push_jump(view, doc);
+        doc.set_selection(view.id, selection)
+    };
+    cx.editor.apply_motion(motion);
+}
+
+fn conflict_keep_ours(cx: &mut Context) {
+    resolve_conflict(cx, merge_conflict::Resolution::Ours)
+}
+
+fn conflict_keep_theirs(cx: &mut Context) {
+    resolve_conflict(cx, merge_conflict::Resolution::Theirs)
+}
+
+fn conflict_keep_both(cx: &mut Context) {
+    resolve_conflict(cx, merge_conflict::Resolution::Both)
+}
+
+/// Resolves the merge conflict at the primary cursor.
+fn resolve_conflict(cx: &mut Context, resolution: merge_conflict::Resolution) {
+    let (view, doc) = current!(cx.editor);
+    let text = doc.text().slice(..);
+    let cursor_line = doc.selection(view.id).primary().cursor_line(text);
+    let Some(conflict) = merge_conflict::find_conflicts(text)
+        .into_iter()
+        .find(|conflict| conflict.contains_line(cursor_line))
+    else {
+        cx.editor.set_error("No merge conflict at the cursor");
+        return;
+    };
+    let transaction = merge_conflict::resolve(doc.text(), &conflict, resolution);
+    doc.apply(&transaction, view.id);
+    doc.append_changes_to_history(view);
+    doc.detect_merge_conflicts();
+}
+
 /// Returns the [Range] for a [Hunk] in the given text.
 /// Additions and modifications cover the added and modified ranges.
 /// Deletions are represented as the point at the start of the deletion hunk.
@@ -4312,7 +5078,132 @@ fn exclude_cursor(text: RopeSlice, range: Range, cursor: Range) -> Range {
     use helix_core::auto_pairs;
     use helix_view::editor::SmartTabConfig;
 
+    /// Finds the closing tags linked to the opening tag names at the cursors when tags are
+    /// closed automatically.
+    fn linked_tags(editor: &Editor) -> Vec<(ops::Range<usize>, ops::Range<usize>)> {
+        let (view, doc) = current_ref!(editor);
+        let Some(syntax) = doc.syntax().filter(|_| editor.config().auto_close_tags) else {
+            return Vec::new();
+        };
+        let text = doc.text().slice(..);
+        doc.selection(view.id)
+            .iter()
+            .filter_map(|range| auto_pairs::linked_tag(syntax, text, range.cursor(text)))
+            .collect()
+    }
+
+    /// Renames the linked closing tags to the opening tag names edited by `changes`.
+    fn rename_linked_tags(
+        editor: &mut Editor,
+        mut linked_tags: Vec<(ops::Range<usize>, ops::Range<usize>)>,
+        changes: &ChangeSet,
+    ) {
+        if linked_tags.is_empty() {
+            return;
+        }
+        linked_tags.sort_by_key(|(_, close)| close.start);
+        linked_tags.dedup_by_key(|(_, close)| close.start);
+
+        let (view, doc) = current!(editor);
+        let text = doc.text().slice(..);
+        let renames: Vec<_> = linked_tags
+            .into_iter()
+            .filter_map(|(open, close)| {
+                let open_start = changes.map_pos(open.start, Assoc::Before);
+                let open_end = changes.map_pos(open.end, Assoc::After);
+                let close_start = changes.map_pos(close.start, Assoc::After);
+                let close_end = changes.map_pos(close.end, Assoc::Before);
+                // text typed after the name, like the start of an attribute, isn't part of it
+                let name: Tendril = text
+                    .slice(open_start..open_end)
+                    .chars()
+                    .take_while(|&c| !c.is_whitespace() && c != '>' && c != '/')
+                    .collect();
+                (close_start <= close_end && text.slice(close_start..close_end) != name.as_str())
+                    .then_some((close_start, close_end, Some(name)))
+            })
+            .collect();
+        if renames.is_empty() {
+            return;
+        }
+        let transaction = Transaction::change(doc.text(), renames.into_iter());
+        doc.apply(&transaction, view.id);
+    }
+
+    /// Closes the opening tags completed by typing `>` at the cursors.
+    fn close_tags(editor: &mut Editor) {
+        if !editor.config().auto_close_tags {
+            return;
+        }
+        let (view, doc) = current!(editor);
+        let Some(syntax) = doc.syntax() else {
+            return;
+        };
+        let text = doc.text().slice(..);
+        let selection = doc.selection(view.id);
+        let closing_tags: Vec<_> = selection
+            .iter()
+            .map(|range| auto_pairs::close_tag(syntax, text, range.cursor(text)))
+            .collect();
+        if closing_tags.iter().all(Option::is_none) {
+            return;
+        }
+        let mut closing_tags = closing_tags.into_iter();
+        // the cursors stay in between the opening and the closing tags
+        let transaction =
+            Transaction::change_by_and_with_selection(doc.text(), selection, |range| {
+                let cursor = range.cursor(text);
+                let closing_tag = closing_tags.next().flatten().map(Tendril::from);
+                ((cursor, cursor, closing_tag), Some(*range))
+            });
+        doc.apply(&transaction, view.id);
+    }
+
+    /// Expands the abbreviations before the cursors when a word boundary is typed. An
+    /// abbreviation preceded by a backslash is kept as is and the backslash is removed.
+    fn expand_abbreviations(cx: &mut Context, boundary: char) {
+        let config = cx.editor.config();
+        if config.abbreviations.is_empty() || char_is_word(boundary) {
+            return;
+        }
+        let (view, doc) = current!(cx.editor);
+        let text = doc.text().slice(..);
+        let language = doc.language_name();
+
+        let mut changes = Vec::new();
+        for range in doc.selection(view.id) {
+            let cursor = range.cursor(text);
+            let start = cursor
+                - text
+                    .chars_at(cursor)
+                    .reversed()
+                    .take_while(|&c| char_is_word(c))
+                    .count();
+            if start == cursor {
+                continue;
+            }
+            let word = Cow::from(text.slice(start..cursor));
+            let Some(expansion) = config.abbreviations.get(language, &word) else {
+                continue;
+            };
+            if start > 0 && text.char(start - 1) == '\\' {
+                changes.push((start - 1, start, None));
+            } else {
+                changes.push((start, cursor, Some(Tendril::from(expansion))));
+            }
+        }
+        if changes.is_empty() {
+            return;
+        }
+        // selections may share a word
+        changes.dedup_by_key(|(from, ..)| *from);
+        let transaction = Transaction::change(doc.text(), changes.into_iter());
+        doc.apply(&transaction, view.id);
+    }
+
     pub fn insert_char(cx: &mut Context, c: char) {
+        expand_abbreviations(cx, c);
+        let linked_tags = linked_tags(cx.editor);
         let (view, doc) = current_ref!(cx.editor);
         let text = doc.text();
         let selection = doc.selection(view.id);
@@ -4339,6 +5230,10 @@ pub fn insert_char(cx: &mut Context, c: char) {
 
         let doc = doc_mut!(cx.editor, &doc.id());
         doc.apply(&transaction, view.id);
+        rename_linked_tags(cx.editor, linked_tags, transaction.changes());
+        if c == '>' {
+            close_tags(cx.editor);
+        }
 
         helix_event::dispatch(PostInsertChar { c, cx });
     }
@@ -4443,6 +5338,7 @@ fn insert_selection_interactive(cx: &mut Context, old_mode: Mode) {
     }
 
     pub fn insert_newline(cx: &mut Context) {
+        expand_abbreviations(cx, '\n');
         let config = cx.editor.config();
         let (view, doc) = current_ref!(cx.editor);
         let loader = cx.editor.syn_loader.load();
@@ -4654,6 +5550,7 @@ fn dedent(doc: &Document, range: &Range) -> Option<Deletion> {
 
     pub fn delete_char_backward(cx: &mut Context) {
         let count = cx.count();
+        let linked_tags = linked_tags(cx.editor);
         let (view, doc) = current_ref!(cx.editor);
         let text = doc.text().slice(..);
 
@@ -4693,6 +5590,7 @@ pub fn delete_char_backward(cx: &mut Context) {
 
         let doc = doc_mut!(cx.editor, &doc.id());
         doc.apply(&transaction, view.id);
+        rename_linked_tags(cx.editor, linked_tags, transaction.changes());
     }
 
     pub fn delete_char_forward(cx: &mut Context) {
@@ -4782,6 +5680,22 @@ fn later(cx: &mut Context) {
     }
 }
 
+fn select_prev(cx: &mut Context) {
+    let count = cx.count();
+    let (view, doc) = current!(cx.editor);
+    if !doc.select_prev(view.id, count) {
+        cx.editor.set_status("Already at oldest selection");
+    }
+}
+
+fn select_next(cx: &mut Context) {
+    let count = cx.count();
+    let (view, doc) = current!(cx.editor);
+    if !doc.select_next(view.id, count) {
+        cx.editor.set_status("Already at newest selection");
+    }
+}
+
 fn commit_undo_checkpoint(cx: &mut Context) {
     let (view, doc) = current!(cx.editor);
     doc.append_changes_to_history(view);
@@ -4812,14 +5726,12 @@ fn yank_impl(editor: &mut Editor, register: char) {
     let (view, doc) = current!(editor);
     let text = doc.text().slice(..);
 
-    let values: Vec<String> = doc
-        .selection(view.id)
-        .fragments(text)
-        .map(Cow::into_owned)
-        .collect();
+    let selection = doc.selection(view.id);
+    let values: Vec<String> = selection.fragments(text).map(Cow::into_owned).collect();
+    let kind = yank_kind(selection, text);
     let selections = values.len();
 
-    match editor.registers.write(register, values) {
+    match editor.registers.write_yank(register, values, kind) {
         Ok(_) => editor.set_status(format!(
             "yanked {selections} selection{} to register {register}",
             if selections == 1 { "" } else { "s" }
@@ -4844,7 +5756,10 @@ fn yank_joined_impl(editor: &mut Editor, separator: &str, register: char) {
             acc
         });
 
-    match editor.registers.write(register, vec![joined]) {
+    match editor
+        .registers
+        .write_yank(register, vec![joined], RegisterKind::Charwise)
+    {
         Ok(_) => editor.set_status(format!(
             "joined and yanked {selections} selection{} to register {register}",
             if selections == 1 { "" } else { "s" }
@@ -4880,9 +5795,11 @@ pub(crate) fn yank_main_selection_to_register(editor: &mut Editor, register: cha
     let (view, doc) = current!(editor);
     let text = doc.text().slice(..);
 
-    let selection = doc.selection(view.id).primary().fragment(text).to_string();
+    let range = doc.selection(view.id).primary();
+    let kind = yank_kind(&Selection::single(range.anchor, range.head), text);
+    let selection = range.fragment(text).to_string();
 
-    match editor.registers.write(register, vec![selection]) {
+    match editor.registers.write_yank(register, vec![selection], kind) {
         Ok(_) => editor.set_status(format!("yanked primary selection to register {register}",)),
         Err(err) => editor.set_error(err.to_string()),
     }
@@ -4909,6 +5826,7 @@ pub(crate) enum Paste {
 
 fn paste_impl(
     values: &[String],
+    kind: RegisterKind,
     doc: &mut Document,
     view: &mut View,
     action: Paste,
@@ -4923,13 +5841,23 @@ fn paste_impl(
         doc.append_changes_to_history(view);
     }
 
+    if kind == RegisterKind::Blockwise && values.len() > 1 && doc.selection(view.id).len() == 1 {
+        paste_block(values, doc, view, action, count, mode);
+        return;
+    }
+
     // if any of values ends with a line ending, it's linewise paste
-    let linewise = values
-        .iter()
-        .any(|value| get_line_ending_of_str(value).is_some());
+    let linewise = kind == RegisterKind::Linewise
+        || values
+            .iter()
+            .any(|value| get_line_ending_of_str(value).is_some());
 
-    let map_value = |value| {
-        let value = LINE_ENDING_REGEX.replace_all(value, doc.line_ending.as_str());
+    let map_value = |value: &String| {
+        let mut value = LINE_ENDING_REGEX.replace_all(value, doc.line_ending.as_str());
+        // a yanked last line has no line ending
+        if kind == RegisterKind::Linewise && get_line_ending_of_str(&value).is_none() {
+            value.to_mut().push_str(doc.line_ending.as_str());
+        }
         let mut out = Tendril::from(value.as_ref());
         for _ in 1..count {
             out.push_str(&value);
@@ -4990,6 +5918,75 @@ fn paste_impl(
     doc.append_changes_to_history(view);
 }
 
+/// Pastes the rows of a block at the same column of consecutive lines, starting at the line of
+/// the selection. Lines that are too short are padded with spaces and missing lines are added.
+fn paste_block(
+    values: &[String],
+    doc: &mut Document,
+    view: &mut View,
+    action: Paste,
+    count: usize,
+    mode: Mode,
+) {
+    let text = doc.text().slice(..);
+    let range = doc.selection(view.id).primary();
+    let pos = match action {
+        Paste::Before => range.from(),
+        Paste::After => range.to(),
+        Paste::Cursor => range.cursor(text),
+    };
+    let first_line = text.char_to_line(pos);
+    let column = pos - text.line_to_char(first_line);
+    let line_ending = doc.line_ending.as_str();
+    // the empty line after a trailing line ending can't be pasted into
+    let ends_with_line_ending = rope_end_without_line_ending(&text) != text.len_chars();
+    let lines = if ends_with_line_ending {
+        text.len_lines() - 1
+    } else {
+        text.len_lines()
+    };
+
+    let mut changes = Vec::with_capacity(values.len());
+    let mut ranges = SmallVec::with_capacity(values.len());
+    let mut offset = 0;
+    for (i, value) in values.iter().enumerate() {
+        let value = value.repeat(count);
+        let line = first_line + i;
+        let (pos, mut insert) = if line < lines {
+            let start = text.line_to_char(line);
+            let end = line_end_char_index(&text, line);
+            if end - start >= column {
+                (start + column, String::new())
+            } else {
+                (end, " ".repeat(column - (end - start)))
+            }
+        } else if ends_with_line_ending {
+            (text.len_chars(), " ".repeat(column))
+        } else {
+            (
+                text.len_chars(),
+                format!("{line_ending}{}", " ".repeat(column)),
+            )
+        };
+        let start = offset + pos + insert.chars().count();
+        let value_len = value.chars().count();
+        insert.push_str(&value);
+        if line >= lines && ends_with_line_ending {
+            insert.push_str(line_ending);
+        }
+        ranges.push(Range::new(start, start + value_len));
+        offset += insert.chars().count();
+        changes.push((pos, pos, Some(insert.into())));
+    }
+
+    let mut transaction = Transaction::change(doc.text(), changes.into_iter());
+    if mode == Mode::Normal {
+        transaction = transaction.with_selection(Selection::new(ranges, 0));
+    }
+    doc.apply(&transaction, view.id);
+    doc.append_changes_to_history(view);
+}
+
 pub(crate) fn paste_bracketed_value(cx: &mut Context, contents: String) {
     let count = cx.count();
     let paste = match cx.editor.mode {
@@ -4997,7 +5994,15 @@ pub(crate) fn paste_bracketed_value(cx: &mut Context, contents: String) {
         Mode::Normal => Paste::Before,
     };
     let (view, doc) = current!(cx.editor);
-    paste_impl(&[contents], doc, view, paste, count, cx.editor.mode);
+    paste_impl(
+        &[contents],
+        RegisterKind::Charwise,
+        doc,
+        view,
+        paste,
+        count,
+        cx.editor.mode,
+    );
     exit_select_mode(cx);
 }
 
@@ -5089,9 +6094,10 @@ pub(crate) fn paste(editor: &mut Editor, register: char, pos: Paste, count: usiz
         return;
     };
     let values: Vec<_> = values.map(|value| value.to_string()).collect();
+    let kind = editor.registers.kind(register);
 
     let (view, doc) = current!(editor);
-    paste_impl(&values, doc, view, pos, count, editor.mode);
+    paste_impl(&values, kind, doc, view, pos, count, editor.mode);
 }
 
 fn paste_after(cx: &mut Context) {
@@ -5116,6 +6122,53 @@ fn paste_before(cx: &mut Context) {
     exit_select_mode(cx);
 }
 
+fn paste_history_picker(cx: &mut Context) {
+    let yanks: Vec<Yank> = cx.editor.registers.yank_history().cloned().collect();
+    if yanks.is_empty() {
+        cx.editor.set_status("Yank history is empty");
+        return;
+    }
+
+    let columns = [
+        ui::PickerColumn::new("kind", |yank: &Yank, _| {
+            match yank.kind {
+                RegisterKind::Charwise => "char",
+                RegisterKind::Linewise => "line",
+                RegisterKind::Blockwise => "block",
+            }
+            .into()
+        }),
+        ui::PickerColumn::new("values", |yank: &Yank, _| {
+            yank.values.len().to_string().into()
+        }),
+        ui::PickerColumn::new("contents", |yank: &Yank, _| {
+            let contents = yank.values.first().map(String::as_str).unwrap_or_default();
+            let first_line = contents.lines().next().unwrap_or_default();
+            if first_line.len() < contents.trim_end().len() {
+                format!("{first_line} …").into()
+            } else {
+                first_line.to_string().into()
+            }
+        }),
+    ];
+
+    let count = cx.count();
+    let picker = Picker::new(columns, 2, yanks, (), move |cx, yank, _action| {
+        let mode = cx.editor.mode;
+        let (view, doc) = current!(cx.editor);
+        paste_impl(
+            &yank.values,
+            yank.kind,
+            doc,
+            view,
+            Paste::After,
+            count,
+            mode,
+        );
+    });
+    cx.push_layer(Box::new(overlaid(picker)));
+}
+
 fn get_lines(doc: &Document, view_id: ViewId) -> Vec<usize> {
     let mut lines = Vec::new();
 
@@ -5207,10 +6260,10 @@ fn unindent(cx: &mut Context) {
 fn format_selections(cx: &mut Context) {
     use helix_lsp::{lsp, util::range_to_lsp_range};
 
-    let (view, doc) = current!(cx.editor);
+    let (view, doc) = current_ref!(cx.editor);
     let view_id = view.id;
 
-    // via lsp if available
+    // via the external formatters or lsp if available
     // TODO: else via tree-sitter indentation calculations
 
     if doc.selection(view_id).len() != 1 {
@@ -5219,6 +6272,12 @@ fn format_selections(cx: &mut Context) {
         return;
     }
 
+    if let Some(format) = doc.format_selection(cx.editor, doc.selection(view_id).primary()) {
+        let callback = make_format_callback(doc.id(), doc.version(), view_id, format, None);
+        cx.jobs.callback(callback);
+        return;
+    }
+
     // TODO extra LanguageServerFeature::FormatSelections?
     // maybe such that LanguageServerFeature::Format contains it as well
     let Some(language_server) = doc
@@ -5465,6 +6524,73 @@ pub fn completion(cx: &mut Context) {
         .trigger_completions(cursor, doc.id(), view.id);
 }
 
+fn inline_completion(cx: &mut Context) {
+    let (view, doc) = current_ref!(cx.editor);
+    let (view_id, doc_id) = (view.id, doc.id());
+    crate::handlers::inline_completion::request_inline_completion(
+        cx.editor,
+        doc_id,
+        view_id,
+        helix_lsp::lsp::InlineCompletionTriggerKind::Invoked,
+    );
+}
+
+fn accept_inline_completion(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    let Some(completion) = doc
+        .inline_completion
+        .as_ref()
+        .filter(|completion| completion.view_id == view.id)
+    else {
+        return;
+    };
+    let transaction = completion.accept(doc.text());
+    doc.apply(&transaction, view.id);
+}
+
+fn accept_inline_completion_word(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    let Some(completion) = doc
+        .inline_completion
+        .take_if(|completion| completion.view_id == view.id)
+    else {
+        return;
+    };
+    let word = completion.next_word().to_string();
+    let cursor = completion.cursor;
+    let transaction = Transaction::change(
+        doc.text(),
+        [(cursor, cursor, Some(word.as_str().into()))].into_iter(),
+    )
+    .with_selection(Selection::point(cursor + word.chars().count()));
+    doc.apply(&transaction, view.id);
+    // applying the transaction discards the ghost text, restore what is left of it
+    doc.inline_completion = completion.advance(&word);
+}
+
+fn next_inline_completion(cx: &mut Context) {
+    cycle_inline_completion(cx, true)
+}
+
+fn prev_inline_completion(cx: &mut Context) {
+    cycle_inline_completion(cx, false)
+}
+
+fn cycle_inline_completion(cx: &mut Context, forward: bool) {
+    let (view, doc) = current!(cx.editor);
+    let Some(completion) = doc
+        .inline_completion
+        .as_mut()
+        .filter(|completion| completion.view_id == view.id)
+    else {
+        return;
+    };
+    completion.cycle(forward);
+    let (position, len) = completion.position();
+    cx.editor
+        .set_status(format!("Inline completion {position}/{len}"));
+}
+
 // comments
 type CommentTransactionFn = fn(
     line_token: Option<&str>,
@@ -5892,6 +7018,14 @@ fn jump_backward(cx: &mut Context) {
     cx.editor.jump_backward(cx.editor.tree.focus, cx.count());
 }
 
+fn goto_previous_buffer_location(cx: &mut Context) {
+    cx.editor.buffer_location_backward(cx.count());
+}
+
+fn goto_next_buffer_location(cx: &mut Context) {
+    cx.editor.buffer_location_forward(cx.count());
+}
+
 fn save_selection(cx: &mut Context) {
     let (view, doc) = current!(cx.editor);
     push_jump(view, doc);
@@ -5942,6 +7076,30 @@ fn transpose_view(cx: &mut Context) {
     cx.editor.transpose_view()
 }
 
+fn grow_view_width(cx: &mut Context) {
+    cx.editor
+        .resize_view(tree::Layout::Vertical, cx.count() as i32);
+}
+
+fn shrink_view_width(cx: &mut Context) {
+    cx.editor
+        .resize_view(tree::Layout::Vertical, -(cx.count() as i32));
+}
+
+fn grow_view_height(cx: &mut Context) {
+    cx.editor
+        .resize_view(tree::Layout::Horizontal, cx.count() as i32);
+}
+
+fn shrink_view_height(cx: &mut Context) {
+    cx.editor
+        .resize_view(tree::Layout::Horizontal, -(cx.count() as i32));
+}
+
+fn equalize_views(cx: &mut Context) {
+    cx.editor.equalize_views()
+}
+
 /// Open a new split in the given direction specified by the action.
 ///
 /// Maintain the current view (both the cursor's position and view in document).
@@ -6039,6 +7197,132 @@ fn insert_register(cx: &mut Context) {
     })
 }
 
+/// Inserts `text` at every cursor, like typing it in insert mode.
+pub(crate) fn insert_at_cursors(editor: &mut Editor, text: &str) {
+    let (view, doc) = current!(editor);
+    let contents = doc.text().slice(..);
+    let text = Tendril::from(text);
+    let transaction =
+        Transaction::change_by_selection(doc.text(), doc.selection(view.id), |range| {
+            let pos = range.cursor(contents);
+            (pos, pos, Some(text.clone()))
+        });
+    doc.apply(&transaction, view.id);
+    // in insert mode the text becomes part of the insert
+    if editor.mode != Mode::Insert {
+        doc.append_changes_to_history(view);
+    }
+}
+
+fn insert_unicode(cx: &mut Context) {
+    cx.editor.autoinfo = Some(Info::new(
+        "Insert unicode",
+        &[
+            ("u", "Codepoint with up to 4 hex digits"),
+            ("U", "Codepoint with up to 8 hex digits"),
+            ("x", "Codepoint with up to 2 hex digits"),
+            ("<key>", "The key itself, like a tab"),
+        ],
+    ));
+    cx.on_next_key(move |cx, event| {
+        cx.editor.autoinfo = None;
+        let digits = match event.char() {
+            Some('u') => 4,
+            Some('U') => 8,
+            Some('x') => 2,
+            _ => {
+                let ch = match event.code {
+                    KeyCode::Tab => Some('\t'),
+                    KeyCode::Char(ch) => Some(ch),
+                    _ => None,
+                };
+                if let Some(ch) = ch {
+                    insert_at_cursors(cx.editor, ch.encode_utf8(&mut [0; 4]));
+                }
+                return;
+            }
+        };
+        insert_codepoint(cx, String::new(), digits);
+    })
+}
+
+/// Reads the hex digits of a codepoint, up to `digits` of them. Enter or any other key that isn't
+/// a hex digit ends the codepoint early and escape cancels it.
+fn insert_codepoint(cx: &mut Context, mut hex: String, digits: usize) {
+    cx.editor.set_status(format!("U+{hex}"));
+    cx.on_next_key(move |cx, event| {
+        match event.char().filter(char::is_ascii_hexdigit) {
+            Some(digit) => {
+                hex.push(digit);
+                if hex.len() < digits {
+                    return insert_codepoint(cx, hex, digits);
+                }
+            }
+            None if event == key!(Esc) => {
+                cx.editor.status_msg = None;
+                return;
+            }
+            None => (),
+        }
+        match helix_core::digraph::parse_codepoint(&hex) {
+            Some(ch) => {
+                cx.editor.status_msg = None;
+                insert_at_cursors(cx.editor, ch.encode_utf8(&mut [0; 4]));
+            }
+            None => cx.editor.set_error(format!("Invalid codepoint U+{hex}")),
+        }
+    })
+}
+
+fn insert_digraph(cx: &mut Context) {
+    cx.editor.set_status("Digraph: ");
+    cx.on_next_key(move |cx, event| {
+        let Some(first) = event.char() else {
+            cx.editor.status_msg = None;
+            return;
+        };
+        cx.editor.set_status(format!("Digraph: {first}"));
+        cx.on_next_key(move |cx, event| {
+            cx.editor.status_msg = None;
+            let Some(second) = event.char() else {
+                return;
+            };
+            match helix_core::digraph::lookup(first, second) {
+                Some(ch) => insert_at_cursors(cx.editor, ch.encode_utf8(&mut [0; 4])),
+                None => cx
+                    .editor
+                    .set_error(format!("Unknown digraph `{first}{second}`")),
+            }
+        })
+    })
+}
+
+fn unicode_picker(cx: &mut Context) {
+    struct CharItem {
+        digraph: [char; 2],
+        ch: char,
+        name: &'static str,
+    }
+
+    let items = helix_core::digraph::DIGRAPHS
+        .iter()
+        .map(|&(digraph, ch, name)| CharItem { digraph, ch, name });
+    let columns = [
+        ui::PickerColumn::new("char", |item: &CharItem, _| item.ch.to_string().into()),
+        ui::PickerColumn::new("name", |item: &CharItem, _| item.name.into()),
+        ui::PickerColumn::new("codepoint", |item: &CharItem, _| {
+            format!("U+{:04X}", item.ch as u32).into()
+        }),
+        ui::PickerColumn::new("digraph", |item: &CharItem, _| {
+            item.digraph.iter().collect::<String>().into()
+        }),
+    ];
+    let picker = Picker::new(columns, 1, items, (), |cx, item, _action| {
+        insert_at_cursors(cx.editor, item.ch.encode_utf8(&mut [0; 4]));
+    });
+    cx.push_layer(Box::new(overlaid(picker)));
+}
+
 fn copy_between_registers(cx: &mut Context) {
     cx.editor.autoinfo = Some(Info::from_registers(
         "Copy from register",
@@ -6056,6 +7340,7 @@ fn copy_between_registers(cx: &mut Context) {
             return;
         };
         let values: Vec<_> = values.map(|value| value.to_string()).collect();
+        let kind = cx.editor.registers.kind(source);
 
         cx.editor.autoinfo = Some(Info::from_registers(
             "Copy into register",
@@ -6069,7 +7354,7 @@ fn copy_between_registers(cx: &mut Context) {
             };
 
             let n_values = values.len();
-            match cx.editor.registers.write(dest, values) {
+            match cx.editor.registers.write_with_kind(dest, values, kind) {
                 Ok(_) => cx.editor.set_status(format!(
                     "yanked {n_values} value{} from register {source} to {dest}",
                     if n_values == 1 { "" } else { "s" }
@@ -6129,6 +7414,96 @@ fn scroll_down(cx: &mut Context) {
     scroll(cx, cx.count(), Direction::Forward, false);
 }
 
+/// Moves the cursors that were hidden by a new fold to the start of the fold header.
+fn move_cursors_out_of_folds(view: &View, doc: &mut Document) {
+    let text = doc.text().slice(..);
+    let Some(folds) = doc.folds(view.id) else {
+        return;
+    };
+    let selection = doc.selection(view.id).clone().transform(|range| {
+        match folds.fold_hiding(range.cursor(text)) {
+            Some(fold) => {
+                let header = text.char_to_line(fold.start) - 1;
+                Range::point(text.line_to_char(header))
+            }
+            None => range,
+        }
+    });
+    doc.set_selection(view.id, selection);
+}
+
+fn fold_selection(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    let text = doc.text().slice(..);
+    let line_ranges: Vec<_> = doc
+        .selection(view.id)
+        .iter()
+        .map(|range| range.line_range(text))
+        .collect();
+    let mut folded = false;
+    for (header, last) in line_ranges {
+        folded |= doc.fold_lines(view.id, header, last);
+    }
+    if folded {
+        move_cursors_out_of_folds(view, doc);
+    } else {
+        cx.editor.set_status("Select multiple lines to fold them");
+    }
+}
+
+fn fold_toggle(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    let text = doc.text().slice(..);
+    let mut lines: Vec<_> = doc
+        .selection(view.id)
+        .iter()
+        .map(|range| range.cursor_line(text))
+        .collect();
+    lines.dedup();
+
+    let mut folded = false;
+    for line in lines {
+        if doc.unfold_line(view.id, line) {
+            continue;
+        }
+        let text = doc.text().slice(..);
+        let last = doc
+            .syntax()
+            .and_then(|syntax| fold::syntax_fold(syntax, text, line))
+            .or_else(|| fold::indent_fold(text, line, doc.tab_width()));
+        if let Some(last) = last {
+            folded |= doc.fold_lines(view.id, line, last);
+        }
+    }
+    if folded {
+        move_cursors_out_of_folds(view, doc);
+    }
+}
+
+fn fold_all_syntax(cx: &mut Context) {
+    let loader = cx.editor.syn_loader.load();
+    let (view, doc) = current!(cx.editor);
+    let Some(syntax) = doc.syntax() else {
+        cx.editor
+            .set_status("Syntax-tree is not available in current buffer");
+        return;
+    };
+    let folds = fold::syntax_folds(syntax, doc.text().slice(..), &loader);
+    if folds.is_empty() {
+        cx.editor.set_status("No foldable syntax nodes found");
+        return;
+    }
+    for (header, last) in folds {
+        doc.fold_lines(view.id, header, last);
+    }
+    move_cursors_out_of_folds(view, doc);
+}
+
+fn unfold_all(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    doc.unfold_all(view.id);
+}
+
 fn goto_ts_object_impl(cx: &mut Context, object: &'static str, direction: Direction) {
     let count = cx.count();
     let motion = move |editor: &mut Editor| {
@@ -6235,6 +7610,34 @@ fn select_textobject(cx: &mut Context, objtype: textobject::TextObject) {
         cx.editor.autoinfo = None;
         if let Some(ch) = event.char() {
             let textobject = move |editor: &mut Editor| {
+                let search_regex = if ch == 's' {
+                    let register = editor.registers.last_search_register;
+                    let Some(query) = editor.registers.first(register, editor) else {
+                        editor.set_status("No search pattern");
+                        return;
+                    };
+                    let case_insensitive =
+                        editor.config().search.smart_case && !query.chars().any(char::is_uppercase);
+                    match rope::RegexBuilder::new()
+                        .syntax(
+                            rope::Config::new()
+                                .case_insensitive(case_insensitive)
+                                .multi_line(true)
+                                .crlf(doc!(editor).line_ending == LineEnding::Crlf),
+                        )
+                        .build(&query)
+                    {
+                        Ok(regex) => Some(regex),
+                        Err(_) => {
+                            let error = format!("Invalid regex: {query}");
+                            editor.set_error(error);
+                            return;
+                        }
+                    }
+                } else {
+                    None
+                };
+
                 let (view, doc) = current!(editor);
                 let loader = editor.syn_loader.load();
                 let text = doc.text().slice(..);
@@ -6269,6 +7672,22 @@ fn select_textobject(cx: &mut Context, objtype: textobject::TextObject) {
                     Range::new(start, end).with_direction(range.direction())
                 };
 
+                let textobject_diagnostic = |range: Range| -> Range {
+                    let diagnostics = doc
+                        .diagnostics()
+                        .iter()
+                        .map(|diagnostic| diagnostic.range.start..diagnostic.range.end);
+                    textobject::textobject_region(text, range, diagnostics)
+                };
+
+                let textobject_search_match = |range: Range| -> Range {
+                    let regex = search_regex.as_ref().unwrap();
+                    let matches = regex
+                        .find_iter(text.regex_input())
+                        .map(|mat| text.byte_to_char(mat.start())..text.byte_to_char(mat.end()));
+                    textobject::textobject_region(text, range, matches)
+                };
+
                 let selection = doc.selection(view.id).clone().transform(|range| {
                     match ch {
                         'w' => textobject::textobject_word(text, range, objtype, count, false),
@@ -6289,6 +7708,8 @@ fn select_textobject(cx: &mut Context, objtype: textobject::TextObject) {
                             count,
                         ),
                         'g' => textobject_change(range),
+                        'd' => textobject_diagnostic(range),
+                        's' => textobject_search_match(range),
                         // TODO: cancel new ranges if inconsistent surround matches across lines
                         ch if !ch.is_ascii_alphanumeric() => textobject::textobject_pair_surround(
                             doc.syntax(),
@@ -6324,6 +7745,8 @@ fn select_textobject(cx: &mut Context, objtype: textobject::TextObject) {
         ("e", "Data structure entry (tree-sitter)"),
         ("m", "Closest surrounding pair (tree-sitter)"),
         ("g", "Change"),
+        ("d", "Diagnostic"),
+        ("s", "Search match"),
         ("x", "(X)HTML element (tree-sitter)"),
         (" ", "... or any character acting as a pair"),
     ];
@@ -6340,55 +7763,109 @@ fn select_textobject(cx: &mut Context, objtype: textobject::TextObject) {
     (" ", "... or any character"),
 ];
 
+static SURROUND_ADD_HELP_TEXT: [(&str, &str); 7] = [
+    ("( or )", "Parentheses"),
+    ("{ or }", "Curly braces"),
+    ("< or >", "Angled brackets"),
+    ("[ or ]", "Square brackets"),
+    ("t", "HTML tag"),
+    ("f", "Function call"),
+    (" ", "... or any character"),
+];
+
 fn surround_add(cx: &mut Context) {
     cx.on_next_key(move |cx, event| {
         cx.editor.autoinfo = None;
-        let (view, doc) = current!(cx.editor);
-        // surround_len is the number of new characters being added.
-        let (open, close, surround_len) = match event.char() {
+        let doc = doc!(cx.editor);
+        let (open, close) = match event.char() {
+            Some('t') => {
+                ui::prompt(
+                    cx,
+                    "tag:".into(),
+                    None,
+                    ui::completers::none,
+                    |cx, input, event| {
+                        let input = input.trim().trim_start_matches('<').trim_end_matches('>');
+                        if event != PromptEvent::Validate || input.is_empty() {
+                            return;
+                        }
+                        // attributes are only added to the opening tag
+                        let name = input.split_whitespace().next().unwrap_or(input);
+                        let open = format!("<{input}>").into();
+                        let close = format!("</{name}>").into();
+                        surround_add_impl(cx.editor, open, close);
+                    },
+                );
+                return;
+            }
+            Some('f') => {
+                ui::prompt(
+                    cx,
+                    "function:".into(),
+                    None,
+                    ui::completers::none,
+                    |cx, input, event| {
+                        let name = input.trim().trim_end_matches('(');
+                        if event != PromptEvent::Validate || name.is_empty() {
+                            return;
+                        }
+                        surround_add_impl(cx.editor, format!("{name}(").into(), ")".into());
+                    },
+                );
+                return;
+            }
             Some(ch) => {
                 let (o, c) = match_brackets::get_pair(ch);
                 let mut open = Tendril::new();
                 open.push(o);
                 let mut close = Tendril::new();
                 close.push(c);
-                (open, close, 2)
+                (open, close)
             }
             None if event.code == KeyCode::Enter => (
                 doc.line_ending.as_str().into(),
                 doc.line_ending.as_str().into(),
-                2 * doc.line_ending.len_chars(),
             ),
             None => return,
         };
+        surround_add_impl(cx.editor, open, close);
+    });
 
-        let selection = doc.selection(view.id);
-        let mut changes = Vec::with_capacity(selection.len() * 2);
-        let mut ranges = SmallVec::with_capacity(selection.len());
-        let mut offs = 0;
+    cx.editor.autoinfo = Some(Info::new(
+        "Surround selections with",
+        &SURROUND_ADD_HELP_TEXT,
+    ));
+}
 
-        for range in selection.iter() {
-            changes.push((range.from(), range.from(), Some(open.clone())));
-            changes.push((range.to(), range.to(), Some(close.clone())));
+fn surround_add_impl(editor: &mut Editor, open: Tendril, close: Tendril) {
+    let (view, doc) = current!(editor);
+    // surround_len is the number of new characters being added.
+    let surround_len = open.chars().count() + close.chars().count();
 
-            ranges.push(
-                Range::new(offs + range.from(), offs + range.to() + surround_len)
-                    .with_direction(range.direction()),
-            );
+    let selection = doc.selection(view.id);
+    let mut changes = Vec::with_capacity(selection.len() * 2);
+    let mut ranges = SmallVec::with_capacity(selection.len());
+    let mut offs = 0;
 
-            offs += surround_len;
-        }
+    for range in selection.iter() {
+        changes.push((range.from(), range.from(), Some(open.clone())));
+        changes.push((range.to(), range.to(), Some(close.clone())));
 
-        let transaction = Transaction::change(doc.text(), changes.into_iter())
-            .with_selection(Selection::new(ranges, selection.primary_index()));
-        doc.apply(&transaction, view.id);
-        exit_select_mode(cx);
-    });
+        ranges.push(
+            Range::new(offs + range.from(), offs + range.to() + surround_len)
+                .with_direction(range.direction()),
+        );
 
-    cx.editor.autoinfo = Some(Info::new(
-        "Surround selections with",
-        &SURROUND_HELP_TEXT[1..],
-    ));
+        offs += surround_len;
+    }
+
+    let transaction = Transaction::change(doc.text(), changes.into_iter())
+        .with_selection(Selection::new(ranges, selection.primary_index()));
+    doc.apply(&transaction, view.id);
+    doc.append_changes_to_history(view);
+    if editor.mode == Mode::Select {
+        editor.mode = Mode::Normal;
+    }
 }
 
 fn surround_replace(cx: &mut Context) {
@@ -6468,6 +7945,7 @@ fn surround_delete(cx: &mut Context) {
         cx.editor.autoinfo = None;
         let surround_ch = match event.char() {
             Some('m') => None, // m selects the closest surround pair
+            Some('f') => return surround_delete_function_call(cx),
             Some(ch) => Some(ch),
             None => return,
         };
@@ -6490,10 +7968,71 @@ fn surround_delete(cx: &mut Context) {
         exit_select_mode(cx);
     });
 
-    cx.editor.autoinfo = Some(Info::new("Delete surrounding pair of", &SURROUND_HELP_TEXT));
+    cx.editor.autoinfo = Some(Info::new(
+        "Delete surrounding pair of",
+        &SURROUND_DELETE_HELP_TEXT,
+    ));
+}
+
+static SURROUND_DELETE_HELP_TEXT: [(&str, &str); 7] = [
+    ("m", "Nearest matching pair"),
+    ("( or )", "Parentheses"),
+    ("{ or }", "Curly braces"),
+    ("< or >", "Angled brackets"),
+    ("[ or ]", "Square brackets"),
+    ("f", "Function call (tree-sitter)"),
+    (" ", "... or any character"),
+];
+
+/// Removes the innermost function call around each selection, keeping its arguments.
+fn surround_delete_function_call(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    let text = doc.text().slice(..);
+    let Some(syntax) = doc.syntax() else {
+        cx.editor
+            .set_error("Function calls can only be found with a tree-sitter grammar");
+        return;
+    };
+
+    let mut calls = Vec::new();
+    for range in doc.selection(view.id) {
+        match surround::find_function_call(syntax, text, *range) {
+            Ok(call) => calls.push(call),
+            Err(err) => {
+                cx.editor.set_error(err.to_string());
+                return;
+            }
+        }
+    }
+    // selections in the same call find the same call. Nested calls like `f(x)(y)` may overlap,
+    // the outer call is kept then as deleting only a part of it would unbalance the parentheses
+    calls.sort_unstable_by_key(|(callee, close)| (callee.start, callee.end, close.start));
+    calls.dedup();
+    let mut deletions: Vec<std::ops::Range<usize>> = Vec::with_capacity(calls.len() * 2);
+    for (callee, close) in calls {
+        let overlaps = |range: &std::ops::Range<usize>| {
+            deletions
+                .iter()
+                .any(|deletion| range.start < deletion.end && deletion.start < range.end)
+        };
+        if !overlaps(&callee) && !overlaps(&close) {
+            deletions.extend([callee, close]);
+        }
+    }
+    // the changeset has to be sorted
+    deletions.sort_unstable_by_key(|range| range.start);
+    let transaction = Transaction::change(
+        doc.text(),
+        deletions
+            .into_iter()
+            .map(|range| (range.start, range.end, None)),
+    );
+    doc.apply(&transaction, view.id);
+    doc.append_changes_to_history(view);
+    exit_select_mode(cx);
 }
 
-#[derive(Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 enum ShellBehavior {
     Replace,
     Ignore,
@@ -6554,7 +8093,7 @@ fn shell_impl(shell: &[String], cmd: &str, input: Option<Rope>) -> anyhow::Resul
     tokio::task::block_in_place(|| helix_lsp::block_on(shell_impl_async(shell, cmd, input)))
 }
 
-async fn shell_impl_async(
+pub(crate) async fn shell_impl_async(
     shell: &[String],
     cmd: &str,
     input: Option<Rope>,
@@ -6568,7 +8107,9 @@ async fn shell_impl_async(
         .args(&shell[1..])
         .arg(cmd)
         .stdout(Stdio::piped())
-        .stderr(Stdio::piped());
+        .stderr(Stdio::piped())
+        // cancelling the command drops its future
+        .kill_on_drop(true);
 
     if input.is_some() || cfg!(windows) {
         process.stdin(Stdio::piped());
@@ -6621,48 +8162,160 @@ async fn shell_impl_async(
     Ok(Tendril::from(output))
 }
 
+/// Runs `cmd` and passes its output, both stdout and stderr, to `on_output` as it arrives.
+/// Returns `None` if the command was cancelled through `handle`, in which case it is killed.
+pub(crate) async fn shell_stream_async<F: Future<Output = ()>>(
+    shell: &[String],
+    cmd: &str,
+    handle: &TaskHandle,
+    mut on_output: impl FnMut(String) -> F,
+) -> anyhow::Result<Option<std::process::ExitStatus>> {
+    use std::process::Stdio;
+    use tokio::io::AsyncReadExt;
+    use tokio::process::Command;
+    ensure!(!shell.is_empty(), "No shell set");
+
+    let mut process = Command::new(&shell[0])
+        .args(&shell[1..])
+        .arg(cmd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .inspect_err(|err| log::error!("Failed to start shell: {}", err))?;
+    let mut stdout = process.stdout.take().expect("stdout is piped");
+    let mut stderr = process.stderr.take().expect("stderr is piped");
+
+    let (mut stdout_buf, mut stderr_buf) = ([0u8; 4096], [0u8; 4096]);
+    // bytes of a char that is split between two reads
+    let (mut stdout_rest, mut stderr_rest) = (Vec::new(), Vec::new());
+    let (mut stdout_open, mut stderr_open) = (true, true);
+    while stdout_open || stderr_open {
+        tokio::select! {
+            read = stdout.read(&mut stdout_buf), if stdout_open => {
+                let read = read?;
+                stdout_open = read != 0;
+                stdout_rest.extend_from_slice(&stdout_buf[..read]);
+                let output = take_utf8(&mut stdout_rest, !stdout_open);
+                if !output.is_empty() {
+                    on_output(output).await;
+                }
+            }
+            read = stderr.read(&mut stderr_buf), if stderr_open => {
+                let read = read?;
+                stderr_open = read != 0;
+                stderr_rest.extend_from_slice(&stderr_buf[..read]);
+                let output = take_utf8(&mut stderr_rest, !stderr_open);
+                if !output.is_empty() {
+                    on_output(output).await;
+                }
+            }
+            _ = handle.canceled() => return Ok(None),
+        }
+    }
+
+    tokio::select! {
+        status = process.wait() => Ok(Some(status?)),
+        _ = handle.canceled() => Ok(None),
+    }
+}
+
+/// Takes the decoded text from the start of `bytes`, leaving an incomplete char at the end in
+/// `bytes` unless `end` is set. Invalid UTF-8 is replaced like [String::from_utf8_lossy].
+fn take_utf8(bytes: &mut Vec<u8>, end: bool) -> String {
+    let len = match std::str::from_utf8(bytes) {
+        Err(err) if err.error_len().is_none() && !end => err.valid_up_to(),
+        _ => bytes.len(),
+    };
+    let text = String::from_utf8_lossy(&bytes[..len]).into_owned();
+    bytes.drain(..len);
+    text
+}
+
+/// Runs `cmd` with the selections as input in the background, and applies its output to the
+/// selections once it exits. The command can be cancelled with Ctrl-C while it runs, see
+/// [Editor::shell_controller].
 fn shell(cx: &mut compositor::Context, cmd: &str, behavior: &ShellBehavior) {
     let pipe = match behavior {
         ShellBehavior::Replace | ShellBehavior::Ignore => true,
         ShellBehavior::Insert | ShellBehavior::Append => false,
     };
 
-    let config = cx.editor.config();
-    let shell = &config.shell;
-    let (view, doc) = current!(cx.editor);
-    let selection = doc.selection(view.id);
+    let shell = cx.editor.config().shell.clone();
+    let (view, doc) = current_ref!(cx.editor);
+    let selection = doc.selection(view.id).clone();
+    let text = doc.text().slice(..);
+    // insert and append run the command once and insert the same output at every selection
+    let inputs: Vec<Option<Rope>> = if pipe {
+        selection
+            .ranges()
+            .iter()
+            .map(|range| Some(range.slice(text).into()))
+            .collect()
+    } else {
+        vec![None]
+    };
+    let (doc_id, view_id, version) = (doc.id(), view.id, doc.version());
+
+    let cmd = cmd.to_string();
+    let behavior = *behavior;
+    let handle = cx.editor.shell_controller.restart();
+    cx.jobs.callback(async move {
+        let mut outputs = Vec::with_capacity(inputs.len());
+        for input in inputs {
+            tokio::select! {
+                output = shell_impl_async(&shell, &cmd, input) => outputs.push(output?),
+                _ = handle.canceled() => return Ok(Callback::Editor(Box::new(|_| ()))),
+            }
+        }
+        let call = move |editor: &mut Editor| {
+            // the command counts as running until its output is applied
+            let _handle = handle;
+            apply_shell_output(
+                editor, doc_id, view_id, version, &selection, behavior, outputs,
+            );
+        };
+        Ok(Callback::Editor(Box::new(call)))
+    });
+}
+
+fn apply_shell_output(
+    editor: &mut Editor,
+    doc_id: DocumentId,
+    view_id: ViewId,
+    version: i32,
+    selection: &Selection,
+    behavior: ShellBehavior,
+    outputs: Vec<Tendril>,
+) {
+    if behavior == ShellBehavior::Ignore {
+        return;
+    }
+    if !editor.tree.contains(view_id) || !editor.documents.contains_key(&doc_id) {
+        return;
+    }
+    let scrolloff = editor.config().scrolloff;
+    let view = view_mut!(editor, view_id);
+    let doc = doc_mut!(editor, &doc_id);
+    if view.doc != doc_id || doc.version() != version {
+        editor.set_error("The document changed while the shell command was running");
+        return;
+    }
 
     let mut changes = Vec::with_capacity(selection.len());
     let mut ranges = SmallVec::with_capacity(selection.len());
     let text = doc.text().slice(..);
 
-    let mut shell_output: Option<Tendril> = None;
     let mut offset = 0isize;
-    for range in selection.ranges() {
-        let output = if let Some(output) = shell_output.as_ref() {
-            output.clone()
-        } else {
-            let input = range.slice(text);
-            match shell_impl(shell, cmd, pipe.then(|| input.into())) {
-                Ok(mut output) => {
-                    if !input.ends_with("\n") && output.ends_with('\n') {
-                        output.pop();
-                        if output.ends_with('\r') {
-                            output.pop();
-                        }
-                    }
-
-                    if !pipe {
-                        shell_output = Some(output.clone());
-                    }
-                    output
-                }
-                Err(err) => {
-                    cx.editor.set_error(err.to_string());
-                    return;
-                }
+    for (i, range) in selection.ranges().iter().enumerate() {
+        let mut output = outputs[i.min(outputs.len() - 1)].clone();
+        if !range.slice(text).ends_with("\n") && output.ends_with('\n') {
+            output.pop();
+            if output.ends_with('\r') {
+                output.pop();
             }
-        };
+        }
 
         let output_len = output.chars().count();
 
@@ -6690,16 +8343,14 @@ fn shell(cx: &mut compositor::Context, cmd: &str, behavior: &ShellBehavior) {
         changes.push((from, to, Some(output)));
     }
 
-    if behavior != &ShellBehavior::Ignore {
-        let transaction = Transaction::change(doc.text(), changes.into_iter())
-            .with_selection(Selection::new(ranges, selection.primary_index()));
-        doc.apply(&transaction, view.id);
-        doc.append_changes_to_history(view);
-    }
+    let transaction = Transaction::change(doc.text(), changes.into_iter())
+        .with_selection(Selection::new(ranges, selection.primary_index()));
+    doc.apply(&transaction, view.id);
+    doc.append_changes_to_history(view);
 
     // after replace cursor may be out of bounds, do this to
     // make sure cursor is in view and update scroll as well
-    view.ensure_cursor_in_view(doc, config.scrolloff);
+    view.ensure_cursor_in_view(doc, scrolloff);
 }
 
 fn shell_prompt<F>(cx: &mut Context, prompt: Cow<'static, str>, mut callback_fn: F)
@@ -6716,7 +8367,7 @@ fn shell_prompt<F>(cx: &mut Context, prompt: Cow<'static, str>, mut callback_fn:
                 return;
             }
             match Args::parse(input, SHELL_SIGNATURE, true, |token| {
-                expansion::expand(cx.editor, token).map_err(|err| err.into())
+                expansion::expand_shell_command(cx.editor, token).map_err(|err| err.into())
             }) {
                 Ok(args) => callback_fn(cx, args),
                 Err(err) => cx.editor.set_error(err.to_string()),
@@ -6890,6 +8541,92 @@ fn goto_next_tabstop_impl(cx: &mut Context, direction: Direction) {
     }
 }
 
+fn expand_snippet(cx: &mut Context) {
+    let library = cx.editor.snippet_library();
+    let (view, doc) = current_ref!(cx.editor);
+    let language = doc.language_name();
+
+    if cx.editor.mode() == Mode::Insert {
+        let text = doc.text().slice(..);
+        let cursor = doc.selection(view.id).primary().cursor(text);
+        let line_start = text.line_to_char(text.char_to_line(cursor));
+        let line: Cow<str> = text.slice(line_start..cursor).into();
+        if let Some(snippet) = library.find_expandable(language, &line) {
+            let snippet = snippet.clone();
+            insert_user_snippet(cx.editor, &snippet, &snippet.prefix);
+            return;
+        }
+    }
+
+    let snippets: Vec<_> = library.snippets(language).cloned().collect();
+    if snippets.is_empty() {
+        let message = match language {
+            Some(language) => format!("No snippets defined for {language}"),
+            None => "No snippets defined".to_string(),
+        };
+        cx.editor.set_error(message);
+        return;
+    }
+
+    let columns = [
+        PickerColumn::new("prefix", |snippet: &UserSnippet, _| {
+            snippet.prefix.as_str().into()
+        }),
+        PickerColumn::new("name", |snippet: &UserSnippet, _| {
+            snippet.name.as_str().into()
+        }),
+        PickerColumn::new("description", |snippet: &UserSnippet, _| {
+            snippet.description.as_deref().unwrap_or_default().into()
+        }),
+    ];
+    let picker = Picker::new(columns, 0, snippets, (), |cx, snippet, _action| {
+        insert_user_snippet(cx.editor, snippet, "");
+        if cx.editor.mode() != Mode::Insert {
+            let (view, doc) = current!(cx.editor);
+            doc.append_changes_to_history(view);
+        }
+    });
+    cx.push_layer(Box::new(overlaid(picker)));
+}
+
+/// Inserts `snippet` at every cursor, replacing `prefix` where it precedes the cursor.
+fn insert_user_snippet(editor: &mut Editor, snippet: &UserSnippet, prefix: &str) {
+    let parsed = match snippet.parse() {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            editor.set_error(format!("Failed to parse snippet '{}': {err}", snippet.name));
+            return;
+        }
+    };
+    let (view, doc) = current!(editor);
+    let text = doc.text().slice(..);
+    let selection = doc.selection(view.id).clone();
+    let prefix_len = prefix.chars().count();
+    let (transaction, mapped_selection, rendered) = parsed.render(
+        doc.text(),
+        &selection,
+        |range| {
+            let cursor = range.cursor(text);
+            let start = cursor.saturating_sub(prefix_len);
+            if text.slice(start..cursor) == prefix {
+                (start, cursor)
+            } else {
+                (cursor, cursor)
+            }
+        },
+        &mut doc.snippet_ctx(),
+    );
+    let transaction = transaction.with_selection(rendered.first_selection(
+        selection.primary().direction(),
+        mapped_selection.primary_index(),
+    ));
+    doc.apply(&transaction, view.id);
+    doc.active_snippet = match doc.active_snippet.take() {
+        Some(active) => active.insert_subsnippet(rendered),
+        None => ActiveSnippet::new(rendered),
+    };
+}
+
 fn record_macro(cx: &mut Context) {
     if let Some((reg, mut keys)) = cx.editor.macro_recording.take() {
         // Remove the keypress which ends the recording
@@ -6905,7 +8642,7 @@ fn record_macro(cx: &mut Context) {
                 }
             })
             .collect::<String>();
-        match cx.editor.registers.write(reg, vec![s]) {
+        match cx.editor.registers.write_macro(reg, s) {
             Ok(_) => cx
                 .editor
                 .set_status(format!("Recorded to register [{}]", reg)),
@@ -6919,33 +8656,38 @@ fn record_macro(cx: &mut Context) {
     }
 }
 
-fn replay_macro(cx: &mut Context) {
-    let reg = cx.register.unwrap_or('@');
-
-    if cx.editor.macro_replaying.contains(&reg) {
-        cx.editor.set_error(format!(
+/// Reads and parses the macro in `reg`, reporting an error if it can't be replayed.
+fn macro_keys(editor: &mut Editor, reg: char) -> Option<Vec<KeyEvent>> {
+    if editor.macro_replaying.contains(&reg) {
+        editor.set_error(format!(
             "Cannot replay from register [{}] because already replaying from same register",
             reg
         ));
-        return;
+        return None;
     }
 
-    let keys: Vec<KeyEvent> = if let Some(keys) = cx
-        .editor
+    if let Some(keys) = editor
         .registers
-        .read(reg, cx.editor)
+        .read(reg, editor)
         .filter(|values| values.len() == 1)
         .map(|mut values| values.next().unwrap())
     {
         match helix_view::input::parse_macro(&keys) {
-            Ok(keys) => keys,
+            Ok(keys) => Some(keys),
             Err(err) => {
-                cx.editor.set_error(format!("Invalid macro: {}", err));
-                return;
+                editor.set_error(format!("Invalid macro: {}", err));
+                None
             }
         }
     } else {
-        cx.editor.set_error(format!("Register [{}] empty", reg));
+        editor.set_error(format!("Register [{}] empty", reg));
+        None
+    }
+}
+
+fn replay_macro(cx: &mut Context) {
+    let reg = cx.register.unwrap_or('@');
+    let Some(keys) = macro_keys(cx.editor, reg) else {
         return;
     };
 
@@ -6967,6 +8709,77 @@ fn replay_macro(cx: &mut Context) {
     }));
 }
 
+/// Replays the macro `count` times for each selection on its own, starting with only that
+/// selection. The selections the replays end with are combined.
+fn replay_macro_on_each_selection(cx: &mut Context) {
+    let reg = cx.register.unwrap_or('@');
+    let Some(keys) = macro_keys(cx.editor, reg) else {
+        return;
+    };
+
+    cx.editor.macro_replaying.push(reg);
+
+    let count = cx.count();
+    cx.callback.push(Box::new(move |compositor, cx| {
+        let (view, doc) = current_ref!(cx.editor);
+        let view_id = view.id;
+        let doc_id = doc.id();
+        let selection = doc.selection(view_id).clone();
+
+        let mut pending: Vec<Range> = selection.ranges().to_vec();
+        let mut ranges: Vec<Range> = Vec::with_capacity(pending.len());
+        let mut primary_index = 0;
+        for i in 0..pending.len() {
+            let (view, doc) = current!(cx.editor);
+            // the macro moved to another buffer or view, the remaining selections are lost
+            if view.id != view_id || doc.id() != doc_id {
+                break;
+            }
+            let text = doc.text().clone();
+            doc.set_selection(
+                view_id,
+                Selection::single(pending[i].anchor, pending[i].head),
+            );
+
+            for _ in 0..count {
+                for &key in keys.iter() {
+                    compositor.handle_event(&compositor::Event::Key(key), cx);
+                }
+            }
+
+            let (view, doc) = current_ref!(cx.editor);
+            if view.id != view_id || doc.id() != doc_id {
+                break;
+            }
+            // keep the selections of the earlier and later replays pointing at the same text
+            let changes = helix_core::diff::compare_ropes(&text, doc.text());
+            for range in ranges.iter_mut().chain(&mut pending[i + 1..]) {
+                *range = range.map(changes.changes());
+            }
+            if i == selection.primary_index() {
+                primary_index = ranges.len();
+            }
+            ranges.extend(doc.selection(view_id).iter().copied());
+        }
+
+        if !ranges.is_empty() && cx.editor.tree.contains(view_id) {
+            if let Some(doc) = cx.editor.documents.get_mut(&doc_id) {
+                doc.set_selection(view_id, Selection::new(ranges.into(), primary_index));
+            }
+        }
+        cx.editor.macro_replaying.pop();
+    }));
+}
+
+fn repeat_last_change(cx: &mut Context) {
+    let count = cx.count;
+    cx.callback.push(Box::new(move |compositor, cx| {
+        // `.` always repeats the last change, whatever it is mapped to
+        cx.editor.count = count;
+        compositor.handle_event(&compositor::Event::Key(key!('.')), cx);
+    }));
+}
+
 fn goto_word(cx: &mut Context) {
     jump_to_word(cx, Movement::Move)
 }
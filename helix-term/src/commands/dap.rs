@@ -1,12 +1,19 @@
 use super::{Context, Editor};
 use crate::{
-    compositor::{self, Compositor},
+    compositor::{self, Component, Compositor},
     job::{Callback, Jobs},
     ui::{self, overlay::overlaid, Picker, Popup, Prompt, PromptEvent, Text},
 };
 use dap::{StackFrame, Thread, ThreadStates};
-use helix_core::syntax::config::{DebugConfigCompletion, DebugTemplate};
-use helix_dap::{self as dap, requests::TerminateArguments};
+use helix_core::syntax::config::{DebugAdapterConfig, DebugConfigCompletion, DebugTemplate};
+use helix_dap::{
+    self as dap,
+    launch::{LaunchConfiguration, Variables},
+    process::Process,
+    registry::DebugAdapterId,
+    requests::TerminateArguments,
+};
+use helix_loader::workspace_trust::{quick_query_workspace, TrustStatus};
 use helix_lsp::block_on;
 use helix_view::editor::Breakpoint;
 
@@ -15,11 +22,12 @@
 
 use std::collections::HashMap;
 use std::future::Future;
+use std::net::SocketAddr;
 use std::path::PathBuf;
 
 use anyhow::{anyhow, bail};
 
-use helix_view::handlers::dap::{breakpoints_changed, jump_to_stack_frame, select_thread_id};
+use helix_view::handlers::dap::{jump_to_stack_frame, select_thread_id};
 
 fn thread_picker(
     cx: &mut Context,
@@ -116,22 +124,15 @@ fn dap_callback<T, F>(
 pub fn dap_start_impl(
     cx: &mut compositor::Context,
     name: Option<&str>,
-    socket: Option<std::net::SocketAddr>,
+    socket: Option<SocketAddr>,
     params: Option<Vec<std::borrow::Cow<str>>>,
 ) -> Result<(), anyhow::Error> {
     let doc = doc!(cx.editor);
     let config = doc
         .language_config()
-        .and_then(|config| config.debugger.as_ref())
+        .and_then(|config| config.debugger.clone())
         .ok_or_else(|| anyhow!("No debug adapter available for language"))?;
 
-    let id = cx
-        .editor
-        .debug_adapters
-        .start_client(socket, config)
-        .map_err(|e| anyhow!("Failed to start debug client: {}", e))?;
-
-    // TODO: avoid refetching all of this... pass a config in
     let template = match name {
         Some(name) => config.templates.iter().find(|t| t.name == name),
         None => config.templates.first(),
@@ -156,6 +157,35 @@ pub fn dap_start_impl(
     args.insert("cwd", to_value(helix_stdx::env::current_working_dir())?);
 
     let args = to_value(args).unwrap();
+    start_session(
+        cx,
+        &config,
+        socket,
+        template.name.clone(),
+        &template.request,
+        args,
+    )
+}
+
+/// Starts a debugging session with the debug adapter of `config`, or with the adapter listening
+/// on `socket`, and sends it the launch or attach `request`.
+fn start_session(
+    cx: &mut compositor::Context,
+    config: &DebugAdapterConfig,
+    socket: Option<SocketAddr>,
+    name: String,
+    request: &str,
+    args: Value,
+) -> Result<(), anyhow::Error> {
+    if !matches!(request, "launch" | "attach") {
+        bail!("Unsupported request '{}'", request);
+    }
+
+    let id = cx
+        .editor
+        .debug_adapters
+        .start_client(socket, config)
+        .map_err(|e| anyhow!("Failed to start debug client: {}", e))?;
 
     let callback = |_editor: &mut Editor, _compositor: &mut Compositor, _response: Value| {
         // if let Err(e) = result {
@@ -169,17 +199,17 @@ pub fn dap_start_impl(
             bail!("Failed to get child debugger.");
         }
     };
+    debugger.name = Some(name);
 
-    match &template.request[..] {
+    match request {
         "launch" => {
             let call = debugger.launch(args);
             dap_callback(cx.jobs, call, callback);
         }
-        "attach" => {
+        _ => {
             let call = debugger.attach(args);
             dap_callback(cx.jobs, call, callback);
         }
-        request => bail!("Unsupported request '{}'", request),
     };
 
     // TODO: either await "initialized" or buffer commands until event is received
@@ -231,61 +261,185 @@ fn map_value(value: &Value, params: &[String]) -> Value {
     }
 }
 
-pub fn dap_launch(cx: &mut Context) {
-    // TODO: Now that we support multiple Clients, we could run multiple debuggers at once but for now keep this as is
-    if cx.editor.debug_adapters.get_active_client().is_some() {
-        cx.editor.set_error("Debugger is already running");
-        return;
+/// A debug configuration offered by [dap_launch].
+#[derive(Debug, Clone)]
+enum DebugConfig {
+    /// A template of the debug adapter of the document's language.
+    Template(DebugTemplate),
+    /// A configuration of `.vscode/launch.json`.
+    Launch(LaunchConfiguration),
+}
+
+impl DebugConfig {
+    fn name(&self) -> &str {
+        match self {
+            Self::Template(template) => &template.name,
+            Self::Launch(config) => &config.name,
+        }
     }
+}
 
+pub fn dap_launch(cx: &mut Context) {
     let doc = doc!(cx.editor);
 
-    let config = match doc
+    let mut configs: Vec<_> = doc
         .language_config()
         .and_then(|config| config.debugger.as_ref())
-    {
-        Some(c) => c,
-        None => {
-            cx.editor
-                .set_error("No debug adapter available for language");
-            return;
+        .map(|config| config.templates.clone())
+        .unwrap_or_default()
+        .into_iter()
+        .map(DebugConfig::Template)
+        .collect();
+
+    // launch.json runs the programs it names, so it is only read in trusted workspaces
+    if let TrustStatus::Trusted = quick_query_workspace(cx.editor.config().insecure) {
+        match dap::launch::load(&helix_loader::find_workspace().0) {
+            Ok(launch_configs) => {
+                configs.extend(launch_configs.into_iter().map(DebugConfig::Launch))
+            }
+            Err(err) => {
+                cx.editor
+                    .set_error(format!("Failed to read launch.json: {err}"));
+                if configs.is_empty() {
+                    return;
+                }
+            }
         }
-    };
+    }
 
-    let templates = config.templates.clone();
+    if configs.is_empty() {
+        cx.editor
+            .set_error("No debug adapter available for language");
+        return;
+    }
 
-    let columns = [ui::PickerColumn::new(
-        "template",
-        |item: &DebugTemplate, _| item.name.as_str().into(),
-    )];
+    let columns = [
+        ui::PickerColumn::new("template", |item: &DebugConfig, _| item.name().into()),
+        ui::PickerColumn::new("source", |item: &DebugConfig, _| match item {
+            DebugConfig::Template(_) => "languages.toml".into(),
+            DebugConfig::Launch(config) => format!("launch.json ({})", config.ty).into(),
+        }),
+    ];
 
     cx.push_layer(Box::new(overlaid(Picker::new(
         columns,
         0,
-        templates,
+        configs,
         (),
-        |cx, template, _action| {
-            if template.completion.is_empty() {
+        |cx, config, _action| match config {
+            DebugConfig::Template(template) if template.completion.is_empty() => {
                 if let Err(err) = dap_start_impl(cx, Some(&template.name), None, None) {
                     cx.editor.set_error(err.to_string());
                 }
-            } else {
-                let completions = template.completion.clone();
-                let name = template.name.clone();
-                let callback = Box::pin(async move {
-                    let call: Callback =
-                        Callback::EditorCompositor(Box::new(move |_editor, compositor| {
-                            let prompt = debug_parameter_prompt(completions, name, Vec::new());
-                            compositor.push(Box::new(prompt));
-                        }));
-                    Ok(call)
+            }
+            DebugConfig::Template(template) => debug_parameter_next(
+                cx,
+                template.completion.clone(),
+                template.name.clone(),
+                Vec::new(),
+            ),
+            DebugConfig::Launch(config) if config.picks_process() => {
+                let config = config.clone();
+                push_process_picker(cx, move |cx, pid| {
+                    if let Err(err) = dap_start_launch_config(cx, &config, Some(pid)) {
+                        cx.editor.set_error(err.to_string());
+                    }
                 });
-                cx.jobs.callback(callback);
+            }
+            DebugConfig::Launch(config) => {
+                if let Err(err) = dap_start_launch_config(cx, config, None) {
+                    cx.editor.set_error(err.to_string());
+                }
             }
         },
     ))));
 }
 
+/// Starts a debugging session from a configuration of `launch.json`. The debug adapter is the one
+/// named like the type of the configuration, or else the one of the document's language.
+fn dap_start_launch_config(
+    cx: &mut compositor::Context,
+    config: &LaunchConfiguration,
+    process_id: Option<u32>,
+) -> Result<(), anyhow::Error> {
+    let adapter = {
+        let loader = cx.editor.syn_loader.load();
+        loader
+            .language_configs()
+            .filter_map(|language| language.debugger.as_ref())
+            .find(|adapter| adapter.name == config.ty)
+            .or_else(|| doc!(cx.editor).language_config()?.debugger.as_ref())
+            .cloned()
+    }
+    .ok_or_else(|| anyhow!("No debug adapter available for type '{}'", config.ty))?;
+
+    let (view, doc) = current_ref!(cx.editor);
+    let text = doc.text().slice(..);
+    let selection = doc.selection(view.id).primary();
+    let variables = Variables {
+        workspace_folder: helix_loader::find_workspace().0,
+        cwd: helix_stdx::env::current_working_dir(),
+        file: doc.path().cloned(),
+        line_number: Some(selection.cursor_line(text) + 1),
+        selected_text: Some(selection.fragment(text).into()),
+        process_id,
+    };
+    let args = config.arguments(&variables);
+    let socket = config
+        .debug_server
+        .map(|port| SocketAddr::from(([127, 0, 0, 1], port)));
+    start_session(
+        cx,
+        &adapter,
+        socket,
+        config.name.clone(),
+        &config.request,
+        args,
+    )
+}
+
+/// Shows the running processes in a picker, `callback_fn` is called with the id of the picked
+/// process.
+fn push_process_picker(
+    cx: &mut compositor::Context,
+    callback_fn: impl Fn(&mut compositor::Context, u32) + Send + 'static,
+) {
+    let processes = match dap::process::processes() {
+        Ok(processes) => processes,
+        Err(err) => {
+            cx.editor
+                .set_error(format!("Failed to list processes: {err}"));
+            return;
+        }
+    };
+    let callback = Box::pin(async move {
+        let call: Callback = Callback::EditorCompositor(Box::new(move |_editor, compositor| {
+            compositor.push(process_picker(processes, callback_fn));
+        }));
+        Ok(call)
+    });
+    cx.jobs.callback(callback);
+}
+
+fn process_picker(
+    processes: Vec<Process>,
+    callback_fn: impl Fn(&mut compositor::Context, u32) + 'static,
+) -> Box<dyn Component> {
+    let columns = [
+        ui::PickerColumn::new("pid", |process: &Process, _| process.pid.to_string().into()),
+        ui::PickerColumn::new("command", |process: &Process, _| {
+            process.command.as_str().into()
+        }),
+    ];
+    Box::new(overlaid(Picker::new(
+        columns,
+        1,
+        processes,
+        (),
+        move |cx, process, _action| callback_fn(cx, process.pid),
+    )))
+}
+
 pub fn dap_restart(cx: &mut Context) {
     let debugger = match cx.editor.debug_adapters.get_active_client() {
         Some(debugger) => debugger,
@@ -319,7 +473,7 @@ pub fn dap_restart(cx: &mut Context) {
 fn debug_parameter_prompt(
     completions: Vec<DebugConfigCompletion>,
     config_name: String,
-    mut params: Vec<String>,
+    params: Vec<String>,
 ) -> Prompt {
     let completion = completions.get(params.len()).unwrap();
     let field_type = if let DebugConfigCompletion::Advanced(cfg) = completion {
@@ -360,33 +514,56 @@ fn debug_parameter_prompt(
             if value.is_empty() {
                 value = default_val.clone();
             }
+            let mut params = params.clone();
             params.push(value);
-
-            if params.len() < completions.len() {
-                let completions = completions.clone();
-                let config_name = config_name.clone();
-                let params = params.clone();
-                let callback = Box::pin(async move {
-                    let call: Callback =
-                        Callback::EditorCompositor(Box::new(move |_editor, compositor| {
-                            let prompt = debug_parameter_prompt(completions, config_name, params);
-                            compositor.push(Box::new(prompt));
-                        }));
-                    Ok(call)
-                });
-                cx.jobs.callback(callback);
-            } else if let Err(err) = dap_start_impl(
-                cx,
-                Some(&config_name),
-                None,
-                Some(params.iter().map(|x| x.into()).collect()),
-            ) {
-                cx.editor.set_error(err.to_string());
-            }
+            debug_parameter_next(cx, completions.clone(), config_name.clone(), params);
         },
     )
 }
 
+/// Asks for the next parameter of a template, or starts the debugging session once all the
+/// parameters are known. A `pid` parameter is picked from the running processes.
+fn debug_parameter_next(
+    cx: &mut compositor::Context,
+    completions: Vec<DebugConfigCompletion>,
+    config_name: String,
+    params: Vec<String>,
+) {
+    let Some(completion) = completions.get(params.len()) else {
+        if let Err(err) = dap_start_impl(
+            cx,
+            Some(&config_name),
+            None,
+            Some(params.iter().map(|x| x.into()).collect()),
+        ) {
+            cx.editor.set_error(err.to_string());
+        }
+        return;
+    };
+
+    let is_pid = match completion {
+        DebugConfigCompletion::Named(name) => name == "pid",
+        DebugConfigCompletion::Advanced(cfg) => cfg.completion.as_deref() == Some("pid"),
+    };
+    if is_pid {
+        push_process_picker(cx, move |cx, pid| {
+            let mut params = params.clone();
+            params.push(pid.to_string());
+            debug_parameter_next(cx, completions.clone(), config_name.clone(), params);
+        });
+        return;
+    }
+
+    let callback = Box::pin(async move {
+        let call: Callback = Callback::EditorCompositor(Box::new(move |_editor, compositor| {
+            let prompt = debug_parameter_prompt(completions, config_name, params);
+            compositor.push(Box::new(prompt));
+        }));
+        Ok(call)
+    });
+    cx.jobs.callback(callback);
+}
+
 pub fn dap_toggle_breakpoint(cx: &mut Context) {
     let (view, doc) = current!(cx.editor);
 
@@ -419,9 +596,7 @@ pub fn dap_toggle_breakpoint_impl(cx: &mut Context, path: PathBuf, line: usize)
         });
     }
 
-    let debugger = debugger!(cx.editor);
-
-    if let Err(e) = breakpoints_changed(debugger, path, breakpoints) {
+    if let Err(e) = cx.editor.sync_breakpoints(path) {
         cx.editor
             .set_error(format!("Failed to set breakpoints: {}", e));
     }
@@ -587,6 +762,7 @@ pub fn dap_variables(cx: &mut Context) {
 pub fn dap_terminate(cx: &mut Context) {
     cx.editor.set_status("Terminating debug session...");
     let debugger = debugger!(cx.editor);
+    let id = debugger.id();
 
     if debugger
         .caps
@@ -598,12 +774,16 @@ pub fn dap_terminate(cx: &mut Context) {
         });
 
         let request = debugger.terminate(terminate_arguments);
-        dap_callback(cx.jobs, request, |editor, _compositor, _response: ()| {
-            // editor.set_error(format!("Failed to disconnect: {}", e));
-            editor.debug_adapters.unset_active_client();
-        });
+        dap_callback(
+            cx.jobs,
+            request,
+            move |editor, _compositor, _response: ()| {
+                // editor.set_error(format!("Failed to disconnect: {}", e));
+                editor.debug_adapters.deactivate_client(id);
+            },
+        );
     } else {
-        cx.editor.debug_adapters.unset_active_client();
+        cx.editor.debug_adapters.deactivate_client(id);
     }
 }
 
@@ -663,9 +843,7 @@ pub fn dap_edit_condition(cx: &mut Context) {
                             input => Some(input.to_owned()),
                         };
 
-                        let debugger = debugger!(cx.editor);
-
-                        if let Err(e) = breakpoints_changed(debugger, path.clone(), breakpoints) {
+                        if let Err(e) = cx.editor.sync_breakpoints(path.clone()) {
                             cx.editor
                                 .set_error(format!("Failed to set breakpoints: {}", e));
                         }
@@ -704,8 +882,7 @@ pub fn dap_edit_log(cx: &mut Context) {
                             input => Some(input.to_owned()),
                         };
 
-                        let debugger = debugger!(cx.editor);
-                        if let Err(e) = breakpoints_changed(debugger, path.clone(), breakpoints) {
+                        if let Err(e) = cx.editor.sync_breakpoints(path.clone()) {
                             cx.editor
                                 .set_error(format!("Failed to set breakpoints: {}", e));
                         }
@@ -781,3 +958,80 @@ pub fn dap_switch_stack_frame(cx: &mut Context) {
     });
     cx.push_layer(Box::new(picker))
 }
+
+/// A debugging session listed by [dap_switch_session].
+struct DebugSession {
+    id: DebugAdapterId,
+    name: String,
+    adapter: String,
+    state: String,
+    frame: Option<StackFrame>,
+}
+
+pub fn dap_switch_session(cx: &mut Context) {
+    let sessions: Vec<_> = cx
+        .editor
+        .debug_adapters
+        .iter()
+        .map(|(id, debugger)| {
+            let state = match debugger.thread_id {
+                Some(thread_id) => debugger
+                    .thread_states
+                    .get(&thread_id)
+                    .cloned()
+                    .unwrap_or_else(|| "unknown".to_string()),
+                None => "running".to_string(),
+            };
+            DebugSession {
+                id,
+                name: debugger.name.clone().unwrap_or_default(),
+                adapter: debugger
+                    .config
+                    .as_ref()
+                    .map(|config| config.name.clone())
+                    .unwrap_or_default(),
+                state,
+                frame: debugger.current_stack_frame().cloned(),
+            }
+        })
+        .collect();
+    if sessions.is_empty() {
+        cx.editor.set_error("Debugger is not running");
+        return;
+    }
+
+    let active = cx.editor.debug_adapters.active_client_id();
+    let columns = [
+        ui::PickerColumn::new(
+            "session",
+            |item: &DebugSession, active: &Option<DebugAdapterId>| {
+                if *active == Some(item.id) {
+                    format!("{} (active)", item.name).into()
+                } else {
+                    item.name.as_str().into()
+                }
+            },
+        ),
+        ui::PickerColumn::new("adapter", |item: &DebugSession, _| {
+            item.adapter.as_str().into()
+        }),
+        ui::PickerColumn::new("state", |item: &DebugSession, _| item.state.as_str().into()),
+    ];
+    let picker = Picker::new(columns, 0, sessions, active, |cx, session, _action| {
+        cx.editor.debug_adapters.set_active_client(session.id);
+        if let Some(frame) = &session.frame {
+            jump_to_stack_frame(cx.editor, frame);
+        }
+        block_on(cx.editor.refresh_debug_watches());
+    })
+    .with_preview(|_editor, session| {
+        let frame = session.frame.as_ref()?;
+        let path = frame.source.as_ref()?.path.as_ref()?.as_path();
+        let pos = Some((
+            frame.line.saturating_sub(1),
+            frame.end_line.unwrap_or(frame.line).saturating_sub(1),
+        ));
+        Some((path.into(), pos))
+    });
+    cx.push_layer(Box::new(picker))
+}
@@ -1,11 +1,11 @@
-use futures_util::{stream::FuturesUnordered, FutureExt};
+use futures_util::{future::BoxFuture, stream::FuturesUnordered, FutureExt};
 use helix_lsp::{
     block_on,
     lsp::{
         self, CodeAction, CodeActionOrCommand, CodeActionTriggerKind, DiagnosticSeverity,
         NumberOrString,
     },
-    util::{diagnostic_to_lsp_diagnostic, lsp_range_to_range, range_to_lsp_range},
+    util::{diagnostic_to_lsp_diagnostic, lsp_range_to_range, pos_to_lsp_pos, range_to_lsp_range},
     Client, LanguageServerId, OffsetEncoding,
 };
 use tokio_stream::StreamExt;
@@ -14,25 +14,30 @@
 use super::{align_view, push_jump, Align, Context, Editor};
 
 use helix_core::{
-    diagnostic::DiagnosticProvider, syntax::config::LanguageServerFeature,
-    text_annotations::InlineAnnotation, Selection, Uri,
+    diagnostic::{DiagnosticProvider, Severity},
+    syntax::config::LanguageServerFeature,
+    text_annotations::InlineAnnotation,
+    Position, RopeSlice, Selection, Uri,
 };
 use helix_stdx::path;
 use helix_view::{
     document::{DocumentInlayHints, DocumentInlayHintsId},
     editor::Action,
     handlers::lsp::SignatureHelpInvoked,
+    quickfix::QuickfixEntry,
     theme::Style,
-    Document, View,
+    Document, DocumentId, View,
 };
 
 use crate::{
     compositor::{self, Compositor},
-    job::Callback,
+    job::{self, Callback},
     ui::{self, overlay::overlaid, FileLocation, Picker, Popup, PromptEvent},
 };
 
-use std::{cmp::Ordering, collections::HashSet, fmt::Display, future::Future, path::Path};
+use std::{
+    cmp::Ordering, collections::HashSet, fmt::Display, future::Future, path::Path, sync::Arc,
+};
 
 /// Gets the first language server that is attached to a document which supports a specific feature.
 /// If there is no configured language server that supports the feature, this displays a status message.
@@ -88,6 +93,27 @@ struct SymbolInformationItem {
     symbol: lsp::SymbolInformation,
 }
 
+/// Converts a `WorkspaceSymbol` into the `SymbolInformation` shown in the workspace symbol picker.
+/// Symbols without a range are located at the start of their file.
+#[allow(deprecated)]
+fn workspace_symbol_to_symbol_information(symbol: lsp::WorkspaceSymbol) -> lsp::SymbolInformation {
+    let location = match symbol.location {
+        lsp::OneOf::Left(location) => location,
+        lsp::OneOf::Right(lsp::WorkspaceLocation { uri }) => lsp::Location {
+            uri,
+            range: lsp::Range::default(),
+        },
+    };
+    lsp::SymbolInformation {
+        name: symbol.name,
+        kind: symbol.kind,
+        tags: symbol.tags,
+        deprecated: None,
+        location,
+        container_name: symbol.container_name,
+    }
+}
+
 struct DiagnosticStyles {
     hint: Style,
     info: Style,
@@ -127,6 +153,30 @@ fn jump_to_location(editor: &mut Editor, location: &Location, action: Action) {
     );
 }
 
+/// Jumps to a diagnostic of the workspace diagnostics, see [ui::DiagnosticsPanel].
+pub(crate) fn jump_to_diagnostic(
+    editor: &mut Editor,
+    uri: Uri,
+    range: lsp::Range,
+    provider: &DiagnosticProvider,
+) {
+    let Some(language_server) = provider
+        .language_server_id()
+        .and_then(|id| editor.language_server_by_id(id))
+    else {
+        return;
+    };
+    let location = Location {
+        uri,
+        range,
+        offset_encoding: language_server.offset_encoding(),
+    };
+    jump_to_location(editor, &location, Action::Replace);
+    let (view, doc) = current!(editor);
+    view.diagnostics_handler
+        .immediately_show_diagnostic(doc, view.id);
+}
+
 fn jump_to_position(
     editor: &mut Editor,
     path: &Path,
@@ -306,9 +356,43 @@ fn diag_picker(
         },
     )
     .with_preview(move |_editor, diag| location_to_file_location(&diag.location))
+    .with_matches_key_handler(crate::alt!('q'), |cx, diags, _| {
+        set_quickfix_diagnostics(cx.editor, diags);
+        None
+    })
     .truncate_start(false)
 }
 
+/// Replaces the quickfix list with the given diagnostics, see `]q`.
+fn set_quickfix_diagnostics(editor: &mut Editor, diags: &[&PickerDiagnostic]) {
+    let entries: Vec<_> = diags
+        .iter()
+        .filter_map(|diag| quickfix_entry(diag))
+        .collect();
+    editor.set_status(format!(
+        "{} diagnostics sent to the quickfix list",
+        entries.len()
+    ));
+    editor.quickfix.set(entries);
+}
+
+/// The quickfix entry of a diagnostic, `None` for diagnostics of documents that aren't files.
+fn quickfix_entry(diag: &PickerDiagnostic) -> Option<QuickfixEntry> {
+    let start = diag.diag.range.start;
+    Some(QuickfixEntry {
+        path: diag.location.uri.as_path()?.to_path_buf(),
+        line: start.line as usize,
+        column: start.character as usize,
+        severity: match diag.diag.severity {
+            Some(DiagnosticSeverity::HINT) => Severity::Hint,
+            Some(DiagnosticSeverity::INFORMATION) => Severity::Info,
+            Some(DiagnosticSeverity::WARNING) => Severity::Warning,
+            _ => Severity::Error,
+        },
+        message: diag.diag.message.clone(),
+    })
+}
+
 pub fn symbol_picker(cx: &mut Context) {
     fn nested_to_flat(
         list: &mut Vec<SymbolInformationItem>,
@@ -471,9 +555,12 @@ pub fn workspace_symbol_picker(cx: &mut Context) {
                 async move {
                     let symbols = request
                         .await?
-                        .and_then(|resp| match resp {
-                            lsp::WorkspaceSymbolResponse::Flat(symbols) => Some(symbols),
-                            lsp::WorkspaceSymbolResponse::Nested(_) => None,
+                        .map(|resp| match resp {
+                            lsp::WorkspaceSymbolResponse::Flat(symbols) => symbols,
+                            lsp::WorkspaceSymbolResponse::Nested(symbols) => symbols
+                                .into_iter()
+                                .map(workspace_symbol_to_symbol_information)
+                                .collect(),
                         })
                         .unwrap_or_default();
 
@@ -582,6 +669,359 @@ pub fn workspace_diagnostics_picker(cx: &mut Context) {
     cx.push_layer(Box::new(overlaid(picker)));
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CallHierarchyDirection {
+    Incoming,
+    Outgoing,
+}
+
+/// An item of the call hierarchy picker: either the item the hierarchy was requested for
+/// (at depth 0) or one of its incoming or outgoing calls (at depth 1).
+struct CallHierarchyEntry {
+    item: lsp::CallHierarchyItem,
+    /// Where the entry jumps to: the call site for incoming calls and the called item
+    /// otherwise.
+    location: Location,
+    depth: usize,
+}
+
+struct CallHierarchyData {
+    cwdir: std::path::PathBuf,
+    language_server: LanguageServerId,
+}
+
+fn call_hierarchy_location(
+    item: &lsp::CallHierarchyItem,
+    range: lsp::Range,
+    offset_encoding: OffsetEncoding,
+) -> Option<Location> {
+    lsp_location_to_location(lsp::Location::new(item.uri.clone(), range), offset_encoding)
+}
+
+/// Requests the calls of `item` and opens a picker with the item followed by its calls.
+async fn call_hierarchy_calls(
+    language_server: Arc<Client>,
+    item: lsp::CallHierarchyItem,
+    direction: CallHierarchyDirection,
+) -> anyhow::Result<Callback> {
+    let offset_encoding = language_server.offset_encoding();
+    let calls: Vec<_> = match direction {
+        CallHierarchyDirection::Incoming => {
+            let Some(future) = language_server.call_hierarchy_incoming(item.clone()) else {
+                anyhow::bail!("Language server does not support call hierarchy");
+            };
+            future
+                .await?
+                .unwrap_or_default()
+                .into_iter()
+                .map(|call| {
+                    // the ranges of incoming calls are relative to the caller
+                    let range = call
+                        .from_ranges
+                        .first()
+                        .copied()
+                        .unwrap_or(call.from.selection_range);
+                    (call.from, range)
+                })
+                .collect()
+        }
+        CallHierarchyDirection::Outgoing => {
+            let Some(future) = language_server.call_hierarchy_outgoing(item.clone()) else {
+                anyhow::bail!("Language server does not support call hierarchy");
+            };
+            future
+                .await?
+                .unwrap_or_default()
+                .into_iter()
+                .map(|call| {
+                    let range = call.to.selection_range;
+                    (call.to, range)
+                })
+                .collect()
+        }
+    };
+
+    let mut entries: Vec<_> = call_hierarchy_location(&item, item.selection_range, offset_encoding)
+        .map(|location| CallHierarchyEntry {
+            item,
+            location,
+            depth: 0,
+        })
+        .into_iter()
+        .collect();
+    let has_calls = !calls.is_empty();
+    entries.extend(calls.into_iter().filter_map(|(item, range)| {
+        Some(CallHierarchyEntry {
+            location: call_hierarchy_location(&item, range, offset_encoding)?,
+            item,
+            depth: 1,
+        })
+    }));
+
+    let language_server = language_server.id();
+    let call = move |editor: &mut Editor, compositor: &mut Compositor| {
+        if !has_calls {
+            editor.set_error(match direction {
+                CallHierarchyDirection::Incoming => "No incoming calls found.",
+                CallHierarchyDirection::Outgoing => "No outgoing calls found.",
+            });
+            return;
+        }
+        let picker = call_hierarchy_picker(entries, language_server);
+        compositor.push(Box::new(overlaid(picker)));
+    };
+    Ok(Callback::EditorCompositor(Box::new(call)))
+}
+
+/// Reopens the call hierarchy picker for the item of `entry`.
+fn expand_call_hierarchy(
+    cx: &mut compositor::Context,
+    entry: &CallHierarchyEntry,
+    data: &CallHierarchyData,
+    direction: CallHierarchyDirection,
+) {
+    let Some(language_server) = cx.editor.language_servers.get_by_id(data.language_server) else {
+        cx.editor
+            .set_error("The language server of the call hierarchy is no longer running");
+        return;
+    };
+    cx.jobs.callback(call_hierarchy_calls(
+        language_server.clone(),
+        entry.item.clone(),
+        direction,
+    ));
+}
+
+fn call_hierarchy_picker(
+    entries: Vec<CallHierarchyEntry>,
+    language_server: LanguageServerId,
+) -> Picker<CallHierarchyEntry, CallHierarchyData> {
+    let columns = [
+        ui::PickerColumn::new("kind", |entry: &CallHierarchyEntry, _| {
+            display_symbol_kind(entry.item.kind).into()
+        }),
+        ui::PickerColumn::new("name", |entry: &CallHierarchyEntry, _| {
+            if entry.depth == 0 {
+                entry.item.name.as_str().into()
+            } else {
+                format!("{}└ {}", "  ".repeat(entry.depth - 1), entry.item.name).into()
+            }
+        }),
+        ui::PickerColumn::new("detail", |entry: &CallHierarchyEntry, _| {
+            entry.item.detail.as_deref().unwrap_or_default().into()
+        }),
+        ui::PickerColumn::new(
+            "location",
+            |entry: &CallHierarchyEntry, data: &CallHierarchyData| {
+                let location = &entry.location;
+                let path = if let Some(path) = location.uri.as_path() {
+                    path.strip_prefix(&data.cwdir)
+                        .unwrap_or(path)
+                        .to_string_lossy()
+                } else {
+                    location.uri.to_string().into()
+                };
+                format!("{path}:{}", location.range.start.line + 1).into()
+            },
+        ),
+    ];
+    let data = CallHierarchyData {
+        cwdir: helix_stdx::env::current_working_dir(),
+        language_server,
+    };
+
+    Picker::new(
+        columns,
+        1, // name column
+        entries,
+        data,
+        |cx, entry, action| jump_to_location(cx.editor, &entry.location, action),
+    )
+    .with_preview(|_editor, entry| location_to_file_location(&entry.location))
+    .with_key_handler(crate::alt!('i'), |cx, entry, data| {
+        expand_call_hierarchy(cx, entry, data, CallHierarchyDirection::Incoming);
+        None
+    })
+    .with_key_handler(crate::alt!('o'), |cx, entry, data| {
+        expand_call_hierarchy(cx, entry, data, CallHierarchyDirection::Outgoing);
+        None
+    })
+    .truncate_start(false)
+}
+
+fn call_hierarchy_impl(cx: &mut Context, direction: CallHierarchyDirection) {
+    let (view, doc) = current_ref!(cx.editor);
+    let language_server =
+        language_server_with_feature!(cx.editor, doc, LanguageServerFeature::CallHierarchy);
+    let pos = doc.position(view.id, language_server.offset_encoding());
+    let future = language_server
+        .prepare_call_hierarchy(doc.identifier(), pos)
+        .unwrap();
+    let language_server = cx
+        .editor
+        .language_servers
+        .get_by_id(language_server.id())
+        .cloned()
+        .expect("language servers of documents are registered");
+
+    cx.jobs.callback(async move {
+        let item = future.await?.and_then(|items| items.into_iter().next());
+        let Some(item) = item else {
+            let call = |editor: &mut Editor| editor.set_error("No call hierarchy item found.");
+            return Ok(Callback::Editor(Box::new(call)));
+        };
+        call_hierarchy_calls(language_server, item, direction).await
+    });
+}
+
+pub fn incoming_calls_picker(cx: &mut Context) {
+    call_hierarchy_impl(cx, CallHierarchyDirection::Incoming);
+}
+
+pub fn outgoing_calls_picker(cx: &mut Context) {
+    call_hierarchy_impl(cx, CallHierarchyDirection::Outgoing);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TypeRelation {
+    Current,
+    Supertype,
+    Subtype,
+}
+
+struct TypeHierarchyEntry {
+    item: lsp::TypeHierarchyItem,
+    relation: TypeRelation,
+    location: Location,
+}
+
+/// Requests the supertypes and subtypes of `item` and opens a picker with the supertypes,
+/// the item and its subtypes.
+async fn type_hierarchy_types(
+    language_server: Arc<Client>,
+    item: lsp::TypeHierarchyItem,
+) -> anyhow::Result<Callback> {
+    let offset_encoding = language_server.offset_encoding();
+    let (Some(supertypes), Some(subtypes)) = (
+        language_server.type_hierarchy_supertypes(item.clone()),
+        language_server.type_hierarchy_subtypes(item.clone()),
+    ) else {
+        anyhow::bail!("Language server does not support type hierarchy");
+    };
+    let (supertypes, subtypes) = futures_util::future::try_join(supertypes, subtypes).await?;
+    let has_relatives = supertypes.as_ref().is_some_and(|types| !types.is_empty())
+        || subtypes.as_ref().is_some_and(|types| !types.is_empty());
+
+    let entries: Vec<_> = supertypes
+        .into_iter()
+        .flatten()
+        .map(|item| (item, TypeRelation::Supertype))
+        .chain([(item, TypeRelation::Current)])
+        .chain(
+            subtypes
+                .into_iter()
+                .flatten()
+                .map(|item| (item, TypeRelation::Subtype)),
+        )
+        .filter_map(|(item, relation)| {
+            let location = lsp_location_to_location(
+                lsp::Location::new(item.uri.clone(), item.selection_range),
+                offset_encoding,
+            )?;
+            Some(TypeHierarchyEntry {
+                item,
+                relation,
+                location,
+            })
+        })
+        .collect();
+
+    let language_server = language_server.id();
+    let call = move |editor: &mut Editor, compositor: &mut Compositor| {
+        if !has_relatives {
+            editor.set_error("No supertypes or subtypes found.");
+            return;
+        }
+        let picker = type_relatives_picker(entries, language_server);
+        compositor.push(Box::new(overlaid(picker)));
+    };
+    Ok(Callback::EditorCompositor(Box::new(call)))
+}
+
+fn type_relatives_picker(
+    entries: Vec<TypeHierarchyEntry>,
+    language_server: LanguageServerId,
+) -> Picker<TypeHierarchyEntry, LanguageServerId> {
+    let columns = [
+        ui::PickerColumn::new("kind", |entry: &TypeHierarchyEntry, _| {
+            display_symbol_kind(entry.item.kind).into()
+        }),
+        ui::PickerColumn::new("name", |entry: &TypeHierarchyEntry, _| {
+            let relation = match entry.relation {
+                TypeRelation::Current => "•",
+                TypeRelation::Supertype => "↑",
+                TypeRelation::Subtype => "↓",
+            };
+            format!("{relation} {}", entry.item.name).into()
+        }),
+        ui::PickerColumn::new("detail", |entry: &TypeHierarchyEntry, _| {
+            entry.item.detail.as_deref().unwrap_or_default().into()
+        }),
+    ];
+    let initial_cursor = entries
+        .iter()
+        .position(|entry| entry.relation == TypeRelation::Current)
+        .unwrap_or_default();
+
+    Picker::new(
+        columns,
+        1, // name column
+        entries,
+        language_server,
+        |cx, entry, action| jump_to_location(cx.editor, &entry.location, action),
+    )
+    .with_preview(|_editor, entry| location_to_file_location(&entry.location))
+    .with_initial_cursor(initial_cursor as u32)
+    .with_key_handler(crate::alt!('t'), |cx, entry, language_server| {
+        let Some(language_server) = cx.editor.language_servers.get_by_id(*language_server) else {
+            cx.editor
+                .set_error("The language server of the type hierarchy is no longer running");
+            return None;
+        };
+        cx.jobs.callback(type_hierarchy_types(
+            language_server.clone(),
+            entry.item.clone(),
+        ));
+        None
+    })
+    .truncate_start(false)
+}
+
+pub fn type_hierarchy_picker(cx: &mut Context) {
+    let (view, doc) = current_ref!(cx.editor);
+    let language_server =
+        language_server_with_feature!(cx.editor, doc, LanguageServerFeature::TypeHierarchy);
+    let pos = doc.position(view.id, language_server.offset_encoding());
+    let future = language_server
+        .prepare_type_hierarchy(doc.identifier(), pos)
+        .unwrap();
+    let language_server = cx
+        .editor
+        .language_servers
+        .get_by_id(language_server.id())
+        .cloned()
+        .expect("language servers of documents are registered");
+
+    cx.jobs.callback(async move {
+        let item = future.await?.and_then(|items| items.into_iter().next());
+        let Some(item) = item else {
+            let call = |editor: &mut Editor| editor.set_error("No type hierarchy item found.");
+            return Ok(Callback::Editor(Box::new(call)));
+        };
+        type_hierarchy_types(language_server, item).await
+    });
+}
+
 struct CodeActionOrCommandItem {
     lsp_item: lsp::CodeActionOrCommand,
     language_server_id: LanguageServerId,
@@ -675,8 +1115,10 @@ pub fn code_action(cx: &mut Context) {
                     .diagnostics()
                     .iter()
                     .filter(|&diag| {
-                        selection_range
-                            .overlaps(&helix_core::Range::new(diag.range.start, diag.range.end))
+                        // internal diagnostics like spelling mistakes are unknown to servers
+                        diag.provider.language_server_id().is_some()
+                            && selection_range
+                                .overlaps(&helix_core::Range::new(diag.range.start, diag.range.end))
                     })
                     .map(|diag| diagnostic_to_lsp_diagnostic(doc.text(), diag, offset_encoding))
                     .collect(),
@@ -1033,28 +1475,87 @@ pub fn signature_help(cx: &mut Context) {
         .trigger_signature_help(SignatureHelpInvoked::Manual, cx.editor)
 }
 
-pub fn hover(cx: &mut Context) {
-    use ui::lsp::hover::Hover;
-
-    let (view, doc) = current!(cx.editor);
-    if doc
-        .language_servers_with_feature(LanguageServerFeature::Hover)
-        .count()
-        == 0
-    {
-        cx.editor
-            .set_error("No configured language server supports hover");
-        return;
+/// Gets the tooltips of the inlay hints displayed right before or after the cursor, resolving
+/// them if the language server only sends them on request.
+fn inlay_hint_tooltips(
+    view: &View,
+    doc: &Document,
+) -> Option<BoxFuture<'static, Vec<(String, lsp::Hover)>>> {
+    let language_server = doc
+        .language_servers_with_feature(LanguageServerFeature::InlayHints)
+        .next()?;
+    let cursor = doc
+        .selection(view.id)
+        .primary()
+        .cursor(doc.text().slice(..));
+    let mut futures: FuturesUnordered<_> = doc
+        .inlay_hints(view.id)?
+        .lsp_inlay_hints
+        .iter()
+        .filter(|(char_idx, _)| *char_idx == cursor || *char_idx == cursor + 1)
+        .map(|(_, hint)| {
+            let server_name = language_server.name().to_string();
+            let resolve = hint
+                .tooltip
+                .is_none()
+                .then(|| language_server.resolve_inlay_hint(hint))
+                .flatten();
+            let hint = hint.clone();
+            async move {
+                let hint = match resolve {
+                    Some(future) => future.await.unwrap_or_else(|err| {
+                        log::error!("Error resolving inlay hint: {err}");
+                        hint
+                    }),
+                    None => hint,
+                };
+                let contents = match hint.tooltip? {
+                    lsp::InlayHintTooltip::String(tooltip) => {
+                        lsp::HoverContents::Scalar(lsp::MarkedString::String(tooltip))
+                    }
+                    lsp::InlayHintTooltip::MarkupContent(tooltip) => {
+                        lsp::HoverContents::Markup(tooltip)
+                    }
+                };
+                Some((
+                    server_name,
+                    lsp::Hover {
+                        contents,
+                        range: None,
+                    },
+                ))
+            }
+        })
+        .collect();
+    if futures.is_empty() {
+        return None;
     }
 
+    Some(
+        async move {
+            let mut tooltips = Vec::new();
+            while let Some(tooltip) = futures.next().await {
+                tooltips.extend(tooltip);
+            }
+            tooltips
+        }
+        .boxed(),
+    )
+}
+
+/// Requests the hover information for the character at `pos` from the language servers of the
+/// document.
+fn request_hovers(
+    doc: &Document,
+    pos: usize,
+) -> impl Future<Output = Vec<(String, lsp::Hover)>> + Send + 'static {
     let mut seen_language_servers = HashSet::new();
     let mut futures: FuturesUnordered<_> = doc
         .language_servers_with_feature(LanguageServerFeature::Hover)
         .filter(|ls| seen_language_servers.insert(ls.id()))
         .map(|language_server| {
             let server_name = language_server.name().to_string();
-            // TODO: factor out a doc.position_identifier() that returns lsp::TextDocumentPositionIdentifier
-            let pos = doc.position(view.id, language_server.offset_encoding());
+            let pos = pos_to_lsp_pos(doc.text(), pos, language_server.offset_encoding());
             let request = language_server
                 .text_document_hover(doc.identifier(), pos, None)
                 .unwrap();
@@ -1063,7 +1564,7 @@ pub fn hover(cx: &mut Context) {
         })
         .collect();
 
-    cx.jobs.callback(async move {
+    async move {
         let mut hovers: Vec<(String, lsp::Hover)> = Vec::new();
 
         while let Some(response) = futures.next().await {
@@ -1073,6 +1574,37 @@ pub fn hover(cx: &mut Context) {
                 Err(err) => log::error!("Error requesting hover: {err}"),
             }
         }
+        hovers
+    }
+}
+
+pub fn hover(cx: &mut Context) {
+    use ui::lsp::hover::Hover;
+
+    let (view, doc) = current!(cx.editor);
+    let inlay_hint_tooltips = inlay_hint_tooltips(view, doc);
+    if doc
+        .language_servers_with_feature(LanguageServerFeature::Hover)
+        .count()
+        == 0
+        && inlay_hint_tooltips.is_none()
+    {
+        cx.editor
+            .set_error("No configured language server supports hover");
+        return;
+    }
+
+    let cursor = doc
+        .selection(view.id)
+        .primary()
+        .cursor(doc.text().slice(..));
+    let hovers = request_hovers(doc, cursor);
+
+    cx.jobs.callback(async move {
+        let mut hovers = hovers.await;
+        if let Some(inlay_hint_tooltips) = inlay_hint_tooltips {
+            hovers.extend(inlay_hint_tooltips.await);
+        }
 
         let call = move |editor: &mut Editor, compositor: &mut Compositor| {
             if hovers.is_empty() {
@@ -1089,6 +1621,127 @@ pub fn hover(cx: &mut Context) {
     });
 }
 
+/// Shows the hover information for the character at `pos` in the document next to `anchor`, the
+/// screen position of the mouse. Nothing is shown if there is no hover information.
+pub fn hover_at(editor: &Editor, doc_id: DocumentId, pos: usize, anchor: Position) {
+    use ui::lsp::hover::Hover;
+
+    let Some(doc) = editor.document(doc_id) else {
+        return;
+    };
+    let hovers = request_hovers(doc, pos);
+
+    tokio::spawn(async move {
+        let hovers = hovers.await;
+        if hovers.is_empty() {
+            return;
+        }
+        job::dispatch(move |editor, compositor| {
+            let contents = Hover::new(hovers, editor.syn_loader.clone());
+            let popup = Popup::new(Hover::ID, contents)
+                .position(Some(anchor))
+                .follow_cursor(false)
+                .auto_close(true);
+            compositor.replace_or_push(Hover::ID, popup);
+        })
+        .await;
+    });
+}
+
+/// Returns the presentation after the one that is `current`ly in the document, wrapping around
+/// to the first presentation.
+fn next_color_presentation(
+    mut presentations: Vec<lsp::ColorPresentation>,
+    current: RopeSlice,
+) -> Option<lsp::ColorPresentation> {
+    let next = presentations
+        .iter()
+        .position(|presentation| {
+            let text = presentation
+                .text_edit
+                .as_ref()
+                .map_or(presentation.label.as_str(), |edit| edit.new_text.as_str());
+            current == text
+        })
+        .map_or(0, |idx| idx + 1);
+    if next >= presentations.len() {
+        return presentations.into_iter().next();
+    }
+    Some(presentations.swap_remove(next))
+}
+
+/// Replaces the color under the cursor with the next of its presentations offered by the
+/// language server, e.g. `#ff0000` with `rgb(255, 0, 0)`.
+pub fn cycle_color_presentation(cx: &mut Context) {
+    let (view, doc) = current_ref!(cx.editor);
+    let language_server =
+        language_server_with_feature!(cx.editor, doc, LanguageServerFeature::DocumentColors);
+    let offset_encoding = language_server.offset_encoding();
+    let text = doc.text().clone();
+    let cursor = doc.selection(view.id).primary().cursor(text.slice(..));
+    let identifier = doc.versioned_identifier();
+    let future = language_server
+        .text_document_document_color(doc.identifier(), None)
+        .unwrap();
+    let language_server = cx
+        .editor
+        .language_servers
+        .get_by_id(language_server.id())
+        .cloned()
+        .expect("language servers of documents are registered");
+
+    cx.jobs.callback(async move {
+        let color_info = future.await?.into_iter().find(|color_info| {
+            lsp_range_to_range(&text, color_info.range, offset_encoding)
+                .is_some_and(|range| range.from() <= cursor && cursor < range.to())
+        });
+        let Some(color_info) = color_info else {
+            let call = |editor: &mut Editor| editor.set_error("No color under the cursor");
+            return Ok(Callback::Editor(Box::new(call)));
+        };
+        let presentations = language_server
+            .text_document_color_presentation(
+                lsp::TextDocumentIdentifier::new(identifier.uri.clone()),
+                color_info.color,
+                color_info.range,
+            )
+            .unwrap()
+            .await?;
+
+        let current = lsp_range_to_range(&text, color_info.range, offset_encoding)
+            .map(|range| text.slice(range.from()..range.to()))
+            .unwrap_or_else(|| text.slice(..0));
+        let Some(presentation) = next_color_presentation(presentations, current) else {
+            let call =
+                |editor: &mut Editor| editor.set_error("No presentations available for the color");
+            return Ok(Callback::Editor(Box::new(call)));
+        };
+
+        let label = presentation.label.clone();
+        let mut edits = vec![presentation.text_edit.clone().unwrap_or(lsp::TextEdit {
+            range: color_info.range,
+            new_text: presentation.label,
+        })];
+        edits.extend(presentation.additional_text_edits.into_iter().flatten());
+        let edit = lsp::WorkspaceEdit {
+            document_changes: Some(lsp::DocumentChanges::Edits(vec![lsp::TextDocumentEdit {
+                text_document: lsp::OptionalVersionedTextDocumentIdentifier {
+                    uri: identifier.uri,
+                    version: Some(identifier.version),
+                },
+                edits: edits.into_iter().map(lsp::OneOf::Left).collect(),
+            }])),
+            ..Default::default()
+        };
+        let call =
+            move |editor: &mut Editor| match editor.apply_workspace_edit(offset_encoding, &edit) {
+                Ok(()) => editor.set_status(label),
+                Err(err) => editor.set_error(format!("Failed to change the color: {}", err.kind)),
+            };
+        Ok(Callback::Editor(Box::new(call)))
+    });
+}
+
 pub fn rename_symbol(cx: &mut Context) {
     fn get_prefill_from_word_boundary(editor: &Editor) -> String {
         let (view, doc) = current_ref!(editor);
@@ -1160,6 +1813,23 @@ fn create_rename_prompt(
                     .unwrap();
 
                 match block_on(future) {
+                    Ok(edits) if cx.editor.config().lsp.preview_rename => {
+                        let preview = ui::RenamePreview::new(
+                            cx.editor,
+                            edits.unwrap_or_default(),
+                            offset_encoding,
+                        );
+                        if preview.is_empty() {
+                            cx.editor.set_status("Nothing to rename");
+                            return;
+                        }
+                        cx.jobs.callback(async move {
+                            let call = move |_: &mut Editor, compositor: &mut Compositor| {
+                                compositor.push(Box::new(overlaid(preview)));
+                            };
+                            Ok(Callback::EditorCompositor(Box::new(call)))
+                        });
+                    }
                     Ok(edits) => {
                         let _ = cx
                             .editor
@@ -1373,11 +2043,17 @@ fn compute_inlay_hints_for_view(
             let mut parameter_inlay_hints = Vec::new();
             let mut other_inlay_hints = Vec::new();
             let mut padding_after_inlay_hints = Vec::new();
+            let mut lsp_inlay_hints = Vec::new();
 
             let doc_text = doc.text();
-            let inlay_hints_length_limit = doc.config.load().lsp.inlay_hints_length_limit;
+            let config = doc.config.load();
+            let inlay_hints_length_limit = config.lsp.inlay_hints_length_limit;
+            let inlay_hints_config = config.lsp.inlay_hints;
 
             for hint in hints {
+                if !inlay_hints_config.shows(hint.kind) {
+                    continue;
+                }
                 let char_idx =
                     match helix_lsp::util::lsp_pos_to_pos(doc_text, hint.position, offset_encoding)
                     {
@@ -1385,6 +2061,7 @@ fn compute_inlay_hints_for_view(
                         // Skip inlay hints that have no "real" position
                         None => continue,
                     };
+                lsp_inlay_hints.push((char_idx, hint.clone()));
 
                 let mut label = match hint.label {
                     lsp::InlayHintLabel::String(s) => s,
@@ -1448,6 +2125,7 @@ fn compute_inlay_hints_for_view(
                     other_inlay_hints,
                     padding_before_inlay_hints,
                     padding_after_inlay_hints,
+                    lsp_inlay_hints,
                 },
             );
             doc.inlay_hints_oudated = false;
@@ -1456,3 +2134,105 @@ fn compute_inlay_hints_for_view(
 
     Some(callback)
 }
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use helix_core::Rope;
+
+    use super::*;
+
+    #[test]
+    fn color_presentations_cycle() {
+        let presentation = |label: &str, new_text: Option<&str>| lsp::ColorPresentation {
+            label: label.to_string(),
+            text_edit: new_text.map(|new_text| lsp::TextEdit {
+                range: lsp::Range::default(),
+                new_text: new_text.to_string(),
+            }),
+            additional_text_edits: None,
+        };
+        let presentations = vec![
+            presentation("#ff0000", None),
+            presentation("rgb", Some("rgb(255, 0, 0)")),
+            presentation("hsl", Some("hsl(0, 100%, 50%)")),
+        ];
+        let next = |current: &str| {
+            next_color_presentation(presentations.clone(), Rope::from(current).slice(..))
+                .map(|presentation| presentation.label)
+        };
+
+        assert_eq!(next("#ff0000").as_deref(), Some("rgb"));
+        // the text of the edit is compared rather than the label
+        assert_eq!(next("rgb(255, 0, 0)").as_deref(), Some("hsl"));
+        assert_eq!(next("rgb").as_deref(), Some("#ff0000"));
+        // wraps around after the last presentation
+        assert_eq!(next("hsl(0, 100%, 50%)").as_deref(), Some("#ff0000"));
+        assert!(next_color_presentation(Vec::new(), Rope::new().slice(..)).is_none());
+    }
+
+    #[test]
+    fn workspace_symbols_to_symbol_information() {
+        let uri = lsp::Url::parse("file:///project/src/main.rs").unwrap();
+        let range = lsp::Range::new(lsp::Position::new(4, 3), lsp::Position::new(4, 7));
+        let symbol = |location| lsp::WorkspaceSymbol {
+            name: "main".to_string(),
+            kind: lsp::SymbolKind::FUNCTION,
+            tags: Some(vec![lsp::SymbolTag::DEPRECATED]),
+            container_name: Some("crate".to_string()),
+            location,
+            data: None,
+        };
+
+        let info = workspace_symbol_to_symbol_information(symbol(lsp::OneOf::Left(
+            lsp::Location::new(uri.clone(), range),
+        )));
+        assert_eq!(info.name, "main");
+        assert_eq!(info.kind, lsp::SymbolKind::FUNCTION);
+        assert_eq!(info.tags, Some(vec![lsp::SymbolTag::DEPRECATED]));
+        assert_eq!(info.container_name.as_deref(), Some("crate"));
+        assert_eq!(info.location, lsp::Location::new(uri.clone(), range));
+
+        // symbols without a range are located at the start of their file
+        let info = workspace_symbol_to_symbol_information(symbol(lsp::OneOf::Right(
+            lsp::WorkspaceLocation { uri: uri.clone() },
+        )));
+        assert_eq!(
+            info.location,
+            lsp::Location::new(uri, lsp::Range::default())
+        );
+    }
+
+    #[test]
+    fn diagnostics_to_quickfix_entries() {
+        let diag = |severity| PickerDiagnostic {
+            location: Location {
+                uri: Uri::from(PathBuf::from("/project/src/main.rs")),
+                range: lsp::Range::default(),
+                offset_encoding: OffsetEncoding::Utf8,
+            },
+            diag: lsp::Diagnostic {
+                range: lsp::Range::new(lsp::Position::new(3, 7), lsp::Position::new(3, 9)),
+                severity,
+                message: "unused variable".to_string(),
+                ..Default::default()
+            },
+        };
+
+        let entry = quickfix_entry(&diag(Some(DiagnosticSeverity::WARNING))).unwrap();
+        assert_eq!(
+            entry,
+            QuickfixEntry {
+                path: PathBuf::from("/project/src/main.rs"),
+                line: 3,
+                column: 7,
+                severity: Severity::Warning,
+                message: "unused variable".to_string(),
+            }
+        );
+        // diagnostics without a severity are errors
+        let entry = quickfix_entry(&diag(None)).unwrap();
+        assert_eq!(entry.severity, Severity::Error);
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::fmt::Write;
 use std::io::BufReader;
 use std::ops::{self, Deref};
@@ -10,10 +11,15 @@
 use helix_core::fuzzy::fuzzy_match;
 use helix_core::indent::MAX_INDENT;
 use helix_core::line_ending;
+use helix_core::structural_search::StructuralQuery;
+use helix_core::syntax::LanguageLoader as _;
 use helix_stdx::path::home_dir;
 use helix_view::document::{read_to_string, DEFAULT_LANGUAGE_NAME};
-use helix_view::editor::{CloseError, ConfigEvent};
+use helix_view::editor::{Breakpoint, CloseError, ConfigEvent, WatchExpression};
 use helix_view::expansion;
+use helix_view::quickfix::{match_problems, ProblemMatcher};
+use helix_view::recovery::RecoveryFile;
+use helix_view::session::{Session, WindowLayout};
 use serde_json::Value;
 use ui::completers::{self, Completer};
 
@@ -73,10 +79,11 @@ fn exit(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow:
         return Ok(());
     }
 
-    if doc!(cx.editor).is_modified() {
-        write_impl(
+    // converting the file to another encoding writes it even without changes
+    if doc!(cx.editor).is_modified() || args.get_flag(ENCODING_FLAG.name).is_some() {
+        write_with_encoding(
             cx,
-            args.first(),
+            &args,
             WriteOptions {
                 force: false,
                 auto_format: !args.has_flag(WRITE_NO_FORMAT_FLAG.name),
@@ -92,10 +99,11 @@ fn force_exit(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> a
         return Ok(());
     }
 
-    if doc!(cx.editor).is_modified() {
-        write_impl(
+    // converting the file to another encoding writes it even without changes
+    if doc!(cx.editor).is_modified() || args.get_flag(ENCODING_FLAG.name).is_some() {
+        write_with_encoding(
             cx,
-            args.first(),
+            &args,
             WriteOptions {
                 force: true,
                 auto_format: !args.has_flag(WRITE_NO_FORMAT_FLAG.name),
@@ -161,6 +169,8 @@ fn open_impl(cx: &mut compositor::Context, args: Args, action: Action) -> anyhow
                 Ok(call)
             };
             cx.jobs.callback(callback);
+        } else if let Some(backend) = helix_view::remote::backend_for_url(&path) {
+            open_remote(cx.editor, cx.jobs, backend?, action, vec![pos]);
         } else {
             // Otherwise, just open the file
             let _ = cx.editor.open(&path, action)?;
@@ -174,7 +184,50 @@ fn open_impl(cx: &mut compositor::Context, args: Args, action: Action) -> anyhow
     Ok(())
 }
 
-fn buffer_close_by_ids_impl(
+/// Reads a remote file in the background and opens it once it's read, see
+/// [helix_view::remote]. An already open remote document is switched to right away. The cursors
+/// are placed at `pos`.
+pub(crate) fn open_remote(
+    editor: &mut Editor,
+    jobs: &mut Jobs,
+    backend: std::sync::Arc<dyn helix_view::remote::DocumentBackend>,
+    action: Action,
+    pos: Vec<Position>,
+) {
+    if let Some(doc_id) = editor.remote_document_id(backend.url()) {
+        editor.switch(doc_id, action);
+        select_positions(editor, doc_id, pos);
+        return;
+    }
+    let callback = async move {
+        let (remote, contents) = helix_view::remote::RemoteFile::load(backend)
+            .await
+            .context("failed to read the remote file")?;
+        let call: job::Callback =
+            Callback::Editor(Box::new(move |editor: &mut Editor| {
+                match editor.open_remote(remote, contents, action) {
+                    Ok(doc_id) => select_positions(editor, doc_id, pos),
+                    Err(err) => editor.set_error(err.to_string()),
+                }
+            }));
+        Ok(call)
+    };
+    jobs.callback(callback);
+}
+
+fn select_positions(editor: &mut Editor, doc_id: DocumentId, pos: Vec<Position>) {
+    let view_id = editor.tree.focus;
+    let doc = doc_mut!(editor, &doc_id);
+    let selection = pos
+        .into_iter()
+        .map(|coords| Range::point(pos_at_coords(doc.text().slice(..), coords, true)))
+        .collect();
+    doc.set_selection(view_id, selection);
+    let (view, doc) = current!(editor);
+    align_view(doc, view, Align::Center);
+}
+
+pub(super) fn buffer_close_by_ids_impl(
     cx: &mut compositor::Context,
     doc_ids: &[DocumentId],
     force: bool,
@@ -280,6 +333,7 @@ fn buffer_gather_others_impl(editor: &mut Editor, skip_visible: bool) -> Vec<Doc
             .collect::<HashSet<_>>();
         editor
             .documents()
+            .filter(|doc| !doc.pinned)
             .map(|doc| doc.id())
             .filter(|doc_id| !visible_document_ids.contains(doc_id))
             .collect()
@@ -287,6 +341,7 @@ fn buffer_gather_others_impl(editor: &mut Editor, skip_visible: bool) -> Vec<Doc
         let current_document = &doc!(editor).id();
         editor
             .documents()
+            .filter(|doc| !doc.pinned)
             .map(|doc| doc.id())
             .filter(|doc_id| doc_id != current_document)
             .collect()
@@ -320,7 +375,11 @@ fn force_buffer_close_others(
 }
 
 fn buffer_gather_all_impl(editor: &mut Editor) -> Vec<DocumentId> {
-    editor.documents().map(|doc| doc.id()).collect()
+    editor
+        .documents()
+        .filter(|doc| !doc.pinned)
+        .map(|doc| doc.id())
+        .collect()
 }
 
 fn buffer_close_all(
@@ -375,6 +434,66 @@ fn buffer_previous(
     Ok(())
 }
 
+fn buffer_goto(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let index: usize = args[0]
+        .parse()
+        .map_err(|_| anyhow!("invalid buffer number: {}", &args[0]))?;
+    let buffers = cx.editor.buffers();
+    let Some(&doc_id) = index.checked_sub(1).and_then(|index| buffers.get(index)) else {
+        bail!("no buffer {index}, {} buffers are open", buffers.len());
+    };
+    cx.editor.switch(doc_id, Action::Replace);
+    Ok(())
+}
+
+fn buffer_pin(cx: &mut compositor::Context, _args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let doc = doc_mut!(cx.editor);
+    doc.pinned = !doc.pinned;
+    let status = if doc.pinned { "Pinned" } else { "Unpinned" };
+    let name = doc.display_name().into_owned();
+    cx.editor.set_status(format!("{status} {name}"));
+    Ok(())
+}
+
+fn buffer_move(cx: &mut compositor::Context, offset: isize) {
+    let doc_id = doc!(cx.editor).id();
+    cx.editor.move_buffer(doc_id, offset);
+}
+
+fn buffer_move_left(
+    cx: &mut compositor::Context,
+    _args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    buffer_move(cx, -1);
+    Ok(())
+}
+
+fn buffer_move_right(
+    cx: &mut compositor::Context,
+    _args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    buffer_move(cx, 1);
+    Ok(())
+}
+
 fn write_impl(
     cx: &mut compositor::Context,
     path: Option<&str>,
@@ -387,7 +506,7 @@ fn write_impl(
     if doc.trim_trailing_whitespace() {
         trim_trailing_whitespace(doc, view.id);
     }
-    if config.trim_final_newlines {
+    if doc.config.load().trim_final_newlines {
         trim_final_newlines(doc, view.id);
     }
     if doc.insert_final_newline() {
@@ -492,9 +611,9 @@ fn write(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow
         return Ok(());
     }
 
-    write_impl(
+    write_with_encoding(
         cx,
-        args.first(),
+        &args,
         WriteOptions {
             force: false,
             auto_format: !args.has_flag(WRITE_NO_FORMAT_FLAG.name),
@@ -507,9 +626,9 @@ fn force_write(cx: &mut compositor::Context, args: Args, event: PromptEvent) ->
         return Ok(());
     }
 
-    write_impl(
+    write_with_encoding(
         cx,
-        args.first(),
+        &args,
         WriteOptions {
             force: true,
             auto_format: !args.has_flag(WRITE_NO_FORMAT_FLAG.name),
@@ -517,6 +636,23 @@ fn force_write(cx: &mut compositor::Context, args: Args, event: PromptEvent) ->
     )
 }
 
+/// Writes the current document like [write_impl], converting it to the encoding given with
+/// `--encoding`. The document only switches to the encoding once the write succeeded.
+fn write_with_encoding(
+    cx: &mut compositor::Context,
+    args: &Args,
+    options: WriteOptions,
+) -> anyhow::Result<()> {
+    if let Some(label) = args.get_flag(ENCODING_FLAG.name) {
+        doc_mut!(cx.editor).set_write_encoding(label)?;
+    }
+    let result = write_impl(cx, args.first(), options);
+    if result.is_err() {
+        doc_mut!(cx.editor).clear_write_encoding();
+    }
+    result
+}
+
 fn write_buffer_close(
     cx: &mut compositor::Context,
     args: Args,
@@ -736,9 +872,9 @@ fn write_quit(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> a
         return Ok(());
     }
 
-    write_impl(
+    write_with_encoding(
         cx,
-        args.first(),
+        &args,
         WriteOptions {
             force: false,
             auto_format: !args.has_flag(WRITE_NO_FORMAT_FLAG.name),
@@ -757,9 +893,9 @@ fn force_write_quit(
         return Ok(());
     }
 
-    write_impl(
+    write_with_encoding(
         cx,
-        args.first(),
+        &args,
         WriteOptions {
             force: true,
             auto_format: !args.has_flag(WRITE_NO_FORMAT_FLAG.name),
@@ -828,7 +964,7 @@ pub fn write_all_impl(
             if !doc.is_modified() {
                 return None;
             }
-            if doc.path().is_none() {
+            if doc.path().is_none() && doc.remote.is_none() {
                 if options.write_scratch {
                     errors.push("cannot write a buffer without a filename");
                 }
@@ -848,7 +984,7 @@ pub fn write_all_impl(
         if doc.trim_trailing_whitespace() {
             trim_trailing_whitespace(doc, target_view);
         }
-        if config.trim_final_newlines {
+        if doc.config.load().trim_final_newlines {
             trim_final_newlines(doc, target_view);
         }
         if doc.insert_final_newline() {
@@ -1022,7 +1158,6 @@ fn force_cquit(cx: &mut compositor::Context, args: Args, event: PromptEvent) ->
 }
 
 fn theme(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
-    let true_color = cx.editor.config.load().true_color || crate::true_color();
     match event {
         PromptEvent::Abort => {
             cx.editor.unset_theme_preview()?;
@@ -1033,9 +1168,6 @@ fn theme(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow
                 cx.editor.unset_theme_preview()?;
             } else if let Some(theme_name) = args.first() {
                 if let Ok(theme) = cx.editor.theme_loader.load(theme_name) {
-                    if !(true_color || theme.is_16_color()) {
-                        bail!("Unsupported theme: theme requires true color support");
-                    }
                     cx.editor.set_theme_preview(theme)?;
                 };
             };
@@ -1047,9 +1179,6 @@ fn theme(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow
                     .theme_loader
                     .load(theme_name)
                     .map_err(|err| anyhow::anyhow!("Could not load theme: {}", err))?;
-                if !(true_color || theme.is_16_color()) {
-                    bail!("Unsupported theme: theme requires true color support");
-                }
                 cx.editor.set_theme(theme)?;
             } else {
                 let name = cx.editor.theme.name().to_string();
@@ -1062,6 +1191,35 @@ fn theme(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow
     Ok(())
 }
 
+fn theme_reload(
+    cx: &mut compositor::Context,
+    _args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    reload_theme(cx.editor)?;
+    cx.editor
+        .set_status(format!("Reloaded theme '{}'", cx.editor.theme.name()));
+    Ok(())
+}
+
+/// Loads the current theme from its files again. A theme that is being previewed stays a
+/// preview.
+pub(crate) fn reload_theme(editor: &mut Editor) -> anyhow::Result<()> {
+    let theme = editor
+        .theme_loader
+        .load(editor.theme.name())
+        .map_err(|err| anyhow::anyhow!("Could not load theme: {}", err))?;
+    if editor.last_theme.is_some() {
+        editor.set_theme_preview(theme)
+    } else {
+        editor.set_theme(theme)
+    }
+}
+
 fn yank_main_selection_to_clipboard(
     cx: &mut compositor::Context,
     _args: Args,
@@ -1355,6 +1513,111 @@ fn show_current_directory(
     Ok(())
 }
 
+fn workspace_add(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    for arg in args.iter() {
+        let dir = helix_stdx::path::expand_tilde(Path::new(arg));
+        if !dir.is_dir() {
+            bail!("'{}' is not a directory", dir.display());
+        }
+        cx.editor.add_workspace_root(&dir);
+    }
+    refresh_workspace_language_servers(cx.editor);
+    show_workspaces(cx, Args::default(), event)
+}
+
+fn workspace_remove(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    for arg in args.iter() {
+        let dir = helix_stdx::path::expand_tilde(Path::new(arg));
+        if !cx.editor.remove_workspace_root(&dir) {
+            bail!("'{}' is not a workspace root", dir.display());
+        }
+    }
+    refresh_workspace_language_servers(cx.editor);
+    show_workspaces(cx, Args::default(), event)
+}
+
+/// Re-routes the documents to the language servers of the workspace roots they belong to.
+fn refresh_workspace_language_servers(editor: &mut Editor) {
+    let doc_ids: Vec<_> = editor
+        .documents()
+        .filter(|doc| doc.path().is_some())
+        .map(|doc| doc.id())
+        .collect();
+    for doc_id in doc_ids {
+        editor.refresh_language_servers(doc_id);
+    }
+}
+
+fn show_workspaces(
+    cx: &mut compositor::Context,
+    _args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let workspaces = cx
+        .editor
+        .workspaces()
+        .iter()
+        .map(|root| root.display().to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+    cx.editor
+        .set_status(format!("Workspace roots: {workspaces}"));
+    Ok(())
+}
+
+fn spell_add(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let words: Vec<String> = if args.is_empty() {
+        let (view, doc) = current_ref!(cx.editor);
+        let word = doc
+            .selection(view.id)
+            .primary()
+            .fragment(doc.text().slice(..))
+            .trim()
+            .to_string();
+        if word.is_empty() || word.contains(char::is_whitespace) {
+            bail!("Select a single word to add to the dictionary");
+        }
+        vec![word]
+    } else {
+        args.iter().map(|arg| arg.to_string()).collect()
+    };
+
+    for word in &words {
+        cx.editor.add_spelling_word(word)?;
+    }
+    let doc_ids: Vec<_> = cx.editor.documents().map(|doc| doc.id()).collect();
+    for doc_id in doc_ids {
+        crate::handlers::spelling::spell_check(cx.editor, doc_id);
+    }
+    cx.editor
+        .set_status(format!("Added {} to the dictionary", words.join(", ")));
+    Ok(())
+}
+
 /// Sets the [`Document`]'s encoding..
 fn set_encoding(
     cx: &mut compositor::Context,
@@ -1375,6 +1638,65 @@ fn set_encoding(
     }
 }
 
+fn set_readonly(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let doc = doc_mut!(cx.editor);
+    let readonly = match args.first() {
+        Some(arg) => arg
+            .parse()
+            .map_err(|_| anyhow!("Expected `true` or `false`, got `{arg}`"))?,
+        None => !doc.readonly(),
+    };
+    doc.set_readonly(readonly);
+    cx.editor.set_status(if readonly {
+        "The buffer is read-only"
+    } else {
+        "The buffer is editable"
+    });
+    Ok(())
+}
+
+/// Inserts characters given by their codepoints, digraphs or Unicode name at every cursor.
+fn insert_character(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    use helix_core::digraph;
+
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let parse = |arg: &str| {
+        let mut chars = arg.chars();
+        let ch = match (chars.next(), chars.next(), chars.next()) {
+            (Some(ch), None, _) => Some(ch),
+            (Some(first), Some(second), None) => {
+                digraph::lookup(first, second).or_else(|| digraph::parse_codepoint(arg))
+            }
+            _ => digraph::parse_codepoint(arg),
+        };
+        ch.ok_or_else(|| anyhow!("`{arg}` is not a codepoint, digraph or character name"))
+    };
+    let text = match digraph::by_name(&args.join(" ")) {
+        Some(ch) => ch.to_string(),
+        None => args
+            .iter()
+            .map(|arg| parse(arg))
+            .collect::<anyhow::Result<String>>()?,
+    };
+    insert_at_cursors(cx.editor, &text);
+    Ok(())
+}
+
 /// Shows info about the character under the primary cursor.
 fn get_character_info(
     cx: &mut compositor::Context,
@@ -1501,22 +1823,34 @@ fn get_character_info(
 }
 
 /// Reload the [`Document`] from its source file.
-fn reload(cx: &mut compositor::Context, _args: Args, event: PromptEvent) -> anyhow::Result<()> {
+fn reload(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
     if event != PromptEvent::Validate {
         return Ok(());
     }
 
     let scrolloff = cx.editor.config().scrolloff;
     let (view, doc) = current!(cx.editor);
-    doc.reload(view, &cx.editor.diff_providers).map(|_| {
-        view.ensure_cursor_in_view(doc, scrolloff);
-    })?;
+    // the file is decoded again with the given encoding, which is kept if that fails
+    let encoding = doc.encoding();
+    if let Some(label) = args.get_flag(ENCODING_FLAG.name) {
+        doc.set_encoding(label)?;
+    }
+    let large_file = doc.large_file;
+    if let Err(err) = doc.reload(view, &cx.editor.diff_providers) {
+        doc.set_current_encoding(encoding);
+        return Err(err);
+    }
+    view.ensure_cursor_in_view(doc, scrolloff);
+    let (doc_id, large_file_changed) = (doc.id(), doc.large_file != large_file);
     if let Some(path) = doc.path().map(ToOwned::to_owned) {
         cx.editor
             .language_servers
             .file_event_handler
             .file_changed(path);
     }
+    if large_file_changed {
+        cx.editor.refresh_language_servers(doc_id);
+    }
     Ok(())
 }
 
@@ -1552,10 +1886,12 @@ fn reload_all(cx: &mut compositor::Context, _args: Args, event: PromptEvent) ->
         // Ensure that the view is synced with the document's history.
         view.sync_changes(doc);
 
+        let large_file = doc.large_file;
         if let Err(error) = doc.reload(view, &cx.editor.diff_providers) {
             cx.editor.set_error(format!("{}", error));
             continue;
         }
+        let large_file_changed = doc.large_file != large_file;
 
         if let Some(path) = doc.path().map(ToOwned::to_owned) {
             cx.editor
@@ -1577,11 +1913,143 @@ fn reload_all(cx: &mut compositor::Context, _args: Args, event: PromptEvent) ->
                 view.ensure_cursor_in_view(doc, scrolloff);
             }
         }
+
+        if large_file_changed {
+            cx.editor.refresh_language_servers(doc_id);
+        }
+    }
+
+    Ok(())
+}
+
+fn recover(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let files = helix_view::recovery::list();
+    if files.is_empty() {
+        bail!("No unsaved changes to recover");
+    }
+    if args.has_flag("discard") {
+        for file in &files {
+            file.remove()?;
+        }
+        let plural = if files.len() == 1 { "" } else { "s" };
+        cx.editor
+            .set_status(format!("Discarded {} recovery file{plural}", files.len()));
+        return Ok(());
+    }
+
+    let callback = async move {
+        let call: job::Callback = Callback::EditorCompositor(Box::new(
+            move |_editor: &mut Editor, compositor: &mut Compositor| {
+                let columns = [
+                    ui::PickerColumn::new("path", |file: &RecoveryFile, _| {
+                        helix_stdx::path::get_relative_path(&file.path)
+                            .to_string_lossy()
+                            .into_owned()
+                            .into()
+                    }),
+                    ui::PickerColumn::new("written", |file: &RecoveryFile, _| {
+                        format_elapsed(file.modified.elapsed().unwrap_or_default()).into()
+                    }),
+                ];
+                let picker = ui::Picker::new(columns, 0, files, (), |cx, file, action| {
+                    if let Err(err) = restore_recovery_file(cx, file, action) {
+                        cx.editor.set_error(err.to_string());
+                    }
+                })
+                .with_preview(|_editor, file| Some((file.text_file.as_path().into(), None)));
+                compositor.push(Box::new(overlaid(picker)))
+            },
+        ));
+        Ok(call)
+    };
+    cx.jobs.callback(callback);
+    Ok(())
+}
+
+/// Opens the document of a recovery file and shows the recovered text next to it. The text is
+/// restored once confirmed, the restored changes can be reviewed with undo and redo before
+/// writing them.
+fn restore_recovery_file(
+    cx: &mut compositor::Context,
+    file: &RecoveryFile,
+    action: Action,
+) -> anyhow::Result<()> {
+    let text = file.read()?;
+    let doc_id = cx.editor.open(&file.path, action)?;
+    let doc = doc!(cx.editor);
+    if doc.id() != doc_id {
+        return Ok(());
+    }
+    if helix_core::diff::compare_ropes(doc.text(), &text)
+        .changes()
+        .is_empty()
+    {
+        file.remove()?;
+        cx.editor
+            .set_status("The file already contains the recovered changes");
+        return Ok(());
     }
 
+    let name = helix_stdx::path::get_relative_path(&file.path)
+        .to_string_lossy()
+        .into_owned();
+    let callback = async move {
+        let call: job::Callback = Callback::EditorCompositor(Box::new(
+            move |editor: &mut Editor, compositor: &mut Compositor| {
+                let source = ui::DiffSource::Text {
+                    name: format!("recovered:{name}"),
+                    text: text.clone(),
+                };
+                compositor.push(Box::new(overlaid(ui::DiffView::new(editor, source))));
+                let prompt = ui::Prompt::new(
+                    format!("restore the unsaved changes of '{name}'? (y/n):").into(),
+                    None,
+                    ui::completers::none,
+                    move |cx, input, event| {
+                        if event == ui::PromptEvent::Update {
+                            return;
+                        }
+                        close_recovery_diff(cx);
+                        if event != ui::PromptEvent::Validate || input != "y" {
+                            return;
+                        }
+                        let (view, doc) = current!(cx.editor);
+                        if doc.id() != doc_id {
+                            return;
+                        }
+                        let transaction = helix_core::diff::compare_ropes(doc.text(), &text);
+                        doc.apply(&transaction, view.id);
+                        doc.append_changes_to_history(view);
+                        cx.editor.set_status(format!(
+                            "Restored the unsaved changes of '{name}', write the file to keep them"
+                        ));
+                    },
+                );
+                compositor.push(Box::new(prompt));
+            },
+        ));
+        Ok(call)
+    };
+    cx.jobs.callback(callback);
     Ok(())
 }
 
+fn close_recovery_diff(cx: &mut compositor::Context) {
+    let callback = async move {
+        let call: job::Callback = Callback::EditorCompositor(Box::new(
+            |_editor: &mut Editor, compositor: &mut Compositor| {
+                compositor.remove_type::<ui::overlay::Overlay<ui::DiffView>>();
+            },
+        ));
+        Ok(call)
+    };
+    cx.jobs.callback(callback);
+}
+
 /// Update the [`Document`] if it has been modified.
 fn update(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
     if event != PromptEvent::Validate {
@@ -1708,8 +2176,10 @@ fn lsp_restart(cx: &mut compositor::Context, args: Args, event: PromptEvent) ->
         return Ok(());
     }
 
+    let workspace_roots = cx.editor.trusted_workspace_roots();
     let editor_config = cx.editor.config.load();
     let doc = doc!(cx.editor);
+    let doc_config = doc.config.load();
     let config = doc
         .language_config()
         .context("LSP not defined for the current document")?;
@@ -1742,7 +2212,8 @@ fn lsp_restart(cx: &mut compositor::Context, args: Args, event: PromptEvent) ->
                 server,
                 config,
                 doc.path(),
-                &editor_config.workspace_lsp_roots,
+                &doc_config.workspace_lsp_roots,
+                &workspace_roots,
                 editor_config.lsp.snippets,
             )
             .transpose()
@@ -2026,14 +2497,64 @@ fn hsplit_new(cx: &mut compositor::Context, _args: Args, event: PromptEvent) ->
     Ok(())
 }
 
-fn debug_eval(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
-    if event != PromptEvent::Validate {
-        return Ok(());
-    }
-
-    if let Some(debugger) = cx.editor.debug_adapters.get_active_client() {
-        let (frame, thread_id) = match (debugger.active_frame, debugger.thread_id) {
-            (Some(frame), Some(thread_id)) => (frame, thread_id),
+/// Resizes the focused split along `layout` by `+N`/`-N` cells or to `N` cells.
+fn resize_split(
+    cx: &mut compositor::Context,
+    args: Args,
+    layout: tree::Layout,
+) -> anyhow::Result<()> {
+    let arg = &args[0];
+    let value: i32 = arg
+        .parse()
+        .map_err(|_| anyhow!("invalid size '{arg}', expected +N, -N or N"))?;
+    let amount = if arg.starts_with(['+', '-']) {
+        value
+    } else {
+        let area = view!(cx.editor).area;
+        let size = match layout {
+            tree::Layout::Vertical => area.width,
+            tree::Layout::Horizontal => area.height,
+        };
+        value - size as i32
+    };
+    if amount != 0 && !cx.editor.resize_view(layout, amount) {
+        bail!("The split can't be resized");
+    }
+    Ok(())
+}
+
+fn vsplit_resize(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    resize_split(cx, args, tree::Layout::Vertical)
+}
+
+fn hsplit_resize(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    resize_split(cx, args, tree::Layout::Horizontal)
+}
+
+fn debug_eval(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    if let Some(debugger) = cx.editor.debug_adapters.get_active_client() {
+        let (frame, thread_id) = match (debugger.active_frame, debugger.thread_id) {
+            (Some(frame), Some(thread_id)) => (frame, thread_id),
             _ => {
                 bail!("Cannot find current stack frame to access variables")
             }
@@ -2048,6 +2569,129 @@ fn debug_eval(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> a
     Ok(())
 }
 
+fn debug_breakpoint_condition(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let (view, doc) = current!(cx.editor);
+    let Some(path) = doc.path().map(ToOwned::to_owned) else {
+        bail!("Can't set breakpoint: document has no path");
+    };
+    let line = doc
+        .selection(view.id)
+        .primary()
+        .cursor_line(doc.text().slice(..));
+
+    let condition = args.first().map(ToOwned::to_owned);
+    let hit_condition = args.get_flag("hits").map(ToOwned::to_owned);
+    let log_message = args.get_flag("log").map(ToOwned::to_owned);
+
+    let breakpoints = cx.editor.breakpoints.entry(path.clone()).or_default();
+    let pos = match breakpoints.iter().position(|b| b.line == line) {
+        Some(pos) => pos,
+        None => {
+            breakpoints.push(Breakpoint {
+                line,
+                ..Default::default()
+            });
+            breakpoints.len() - 1
+        }
+    };
+    let breakpoint = &mut breakpoints[pos];
+    if condition.is_none() && hit_condition.is_none() && log_message.is_none() {
+        breakpoint.condition = None;
+        breakpoint.hit_condition = None;
+        breakpoint.log_message = None;
+    } else {
+        breakpoint.condition = condition.or(breakpoint.condition.take());
+        breakpoint.hit_condition = hit_condition.or(breakpoint.hit_condition.take());
+        breakpoint.log_message = log_message.or(breakpoint.log_message.take());
+    }
+
+    cx.editor.sync_breakpoints(path)
+}
+
+fn debug_watch(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let expression = &args[0];
+    if cx
+        .editor
+        .debug_watches
+        .iter()
+        .any(|watch| watch.expression == expression)
+    {
+        return Ok(());
+    }
+    cx.editor.debug_watches.push(WatchExpression {
+        expression: expression.to_owned(),
+        value: None,
+    });
+    helix_lsp::block_on(cx.editor.refresh_debug_watches());
+    Ok(())
+}
+
+fn debug_unwatch(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    match args.first() {
+        Some(expression) => {
+            let len = cx.editor.debug_watches.len();
+            cx.editor
+                .debug_watches
+                .retain(|watch| watch.expression != expression);
+            ensure!(
+                cx.editor.debug_watches.len() != len,
+                "'{expression}' is not watched"
+            );
+        }
+        None => cx.editor.debug_watches.clear(),
+    }
+    Ok(())
+}
+
+fn debug_toggle_watches(
+    cx: &mut compositor::Context,
+    _args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    cx.editor.show_debug_watches = !cx.editor.show_debug_watches;
+    cx.editor.set_status(if cx.editor.show_debug_watches {
+        "Debug watch panel shown"
+    } else {
+        "Debug watch panel hidden"
+    });
+    Ok(())
+}
+
+fn debug_watch_completer(editor: &Editor, input: &str) -> Vec<ui::prompt::Completion> {
+    let expressions = editor
+        .debug_watches
+        .iter()
+        .map(|watch| watch.expression.clone());
+    fuzzy_match(input, expressions, false)
+        .into_iter()
+        .map(|(expression, _)| (0.., expression.into()))
+        .collect()
+}
+
 fn debug_start(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
     if event != PromptEvent::Validate {
         return Ok(());
@@ -2305,6 +2949,38 @@ fn toggle_option(
     Ok(())
 }
 
+fn toggle_inlay_hints_kind(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let mut config = (*cx.editor.config()).clone();
+    let inlay_hints = &mut config.lsp.inlay_hints;
+    let kind = args[0].to_lowercase();
+    let shown = match kind.as_str() {
+        "type" => &mut inlay_hints.type_hints,
+        "parameter" => &mut inlay_hints.parameter_hints,
+        "other" => &mut inlay_hints.other_hints,
+        _ => bail!("Unknown inlay hint kind `{kind}`, expected `type`, `parameter` or `other`"),
+    };
+    *shown = !*shown;
+    let status = format!(
+        "{kind} inlay hints are now {}",
+        if *shown { "shown" } else { "hidden" }
+    );
+
+    cx.editor
+        .config_events
+        .0
+        .send(ConfigEvent::Update(Box::new(config)))?;
+    cx.editor.set_status(status);
+    Ok(())
+}
+
 /// Change the language of the current buffer at runtime.
 fn language(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
     if event != PromptEvent::Validate {
@@ -2455,6 +3131,304 @@ fn tree_sitter_subtree(
     Ok(())
 }
 
+fn replace_apply(
+    cx: &mut compositor::Context,
+    _args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let mut edits: BTreeMap<PathBuf, Vec<(usize, String)>> = BTreeMap::new();
+    let doc = doc!(cx.editor);
+    let text = doc.text().slice(..);
+    for line in 0..text.len_lines() {
+        let line = line_ending::line_without_line_ending(&text, line).to_string();
+        if let Some((path, line, contents)) = parse_search_result(&line) {
+            edits
+                .entry(path.to_path_buf())
+                .or_default()
+                .push((line, contents.to_string()));
+        }
+    }
+    // results that weren't listed by a search, e.g. typed in by hand, aren't checked
+    let originals = cx
+        .editor
+        .search_results
+        .get(&doc.id())
+        .cloned()
+        .unwrap_or_default();
+    if edits.is_empty() {
+        bail!("The current buffer contains no search results");
+    }
+
+    // Open and check every file before changing any of them, so that an invalid result doesn't
+    // leave some of the files written.
+    let mut targets = Vec::new();
+    let mut errors = Vec::new();
+    for (path, mut lines) in edits {
+        lines.sort_by_key(|(line, _)| *line);
+        lines.dedup_by_key(|(line, _)| *line);
+
+        let path = helix_stdx::path::canonicalize(path);
+        let was_open = cx.editor.document_by_path(&path).map(|doc| doc.id());
+        if was_open.is_none() && !path.is_file() {
+            errors.push(format!("{}: file not found", path.display()));
+            continue;
+        }
+        let doc_id = match cx.editor.open(&path, Action::Load) {
+            Ok(doc_id) => doc_id,
+            Err(err) => {
+                errors.push(format!("{}: {err}", path.display()));
+                continue;
+            }
+        };
+        let doc = doc!(cx.editor, &doc_id);
+        let len_lines = doc.text().len_lines();
+        if doc.is_modified() {
+            errors.push(format!("{}: unsaved changes", path.display()));
+        } else if let Some((line, _)) = lines.iter().find(|(line, _)| *line >= len_lines) {
+            errors.push(format!(
+                "{}: line {} is past the end of the file",
+                path.display(),
+                line + 1
+            ));
+        }
+        targets.push((doc_id, path, was_open.is_none(), lines));
+    }
+    if !errors.is_empty() {
+        for (doc_id, _, opened, _) in targets {
+            if opened {
+                let _ = cx.editor.close_document(doc_id, false);
+            }
+        }
+        bail!("No files were changed: {}", errors.join(", "));
+    }
+
+    let mut changed_lines = 0;
+    let mut changed_files = 0;
+    let mut stale_lines = 0;
+    for (doc_id, path, _, lines) in targets {
+        let view_id = cx.editor.get_synced_view_id(doc_id);
+        let doc = doc_mut!(cx.editor, &doc_id);
+        let text = doc.text().slice(..);
+        let changes: Vec<_> = lines
+            .into_iter()
+            .filter(|(line, contents)| {
+                let current = line_ending::line_without_line_ending(&text, *line);
+                if current == contents.as_str() {
+                    return false;
+                }
+                // the line changed since the search, the edit was made to its old contents
+                let stale = originals
+                    .get(&(path.clone(), *line))
+                    .is_some_and(|original| current != original.as_str());
+                stale_lines += stale as usize;
+                !stale
+            })
+            .map(|(line, contents)| {
+                let start = text.line_to_char(line);
+                let end = line_ending::line_end_char_index(&text, line);
+                (start, end, Some(contents.into()))
+            })
+            .collect();
+        if changes.is_empty() {
+            continue;
+        }
+
+        let change_count = changes.len();
+        let transaction = Transaction::change(doc.text(), changes.into_iter());
+        let view = view_mut!(cx.editor, view_id);
+        doc.apply(&transaction, view.id);
+        doc.append_changes_to_history(view);
+        match cx.editor.save::<PathBuf>(doc_id, None, false) {
+            Ok(()) => {
+                changed_lines += change_count;
+                changed_files += 1;
+            }
+            Err(err) => errors.push(format!(
+                "{}: {err}",
+                doc!(cx.editor, &doc_id).display_name()
+            )),
+        }
+    }
+
+    let mut status = format!(
+        "Changed {changed_lines} line{} in {changed_files} file{}",
+        if changed_lines == 1 { "" } else { "s" },
+        if changed_files == 1 { "" } else { "s" }
+    );
+    if stale_lines > 0 {
+        status.push_str(&format!(
+            ", skipped {stale_lines} line{} that changed since the search",
+            if stale_lines == 1 { "" } else { "s" }
+        ));
+    }
+    if !errors.is_empty() {
+        bail!("{status}, failed to save {}", errors.join(", "));
+    }
+    cx.editor.set_status(status);
+    Ok(())
+}
+
+/// Compiles a structural search query for the root language of the current document.
+fn structural_query(editor: &Editor, source: &str) -> anyhow::Result<StructuralQuery> {
+    let doc = doc!(editor);
+    let Some(syntax) = doc.syntax() else {
+        bail!("Syntax information is not available in the current buffer");
+    };
+    let loader = editor.syn_loader.load();
+    let Some(config) = loader.get_config(syntax.root_language()) else {
+        bail!("No grammar is available for the current buffer");
+    };
+    StructuralQuery::new(config.grammar, source)
+}
+
+fn structural_search(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let query = structural_query(cx.editor, &args[0])?;
+    let (view, doc) = current!(cx.editor);
+    let text = doc.text().slice(..);
+    let matches = query.matches(doc.syntax().unwrap(), text);
+    if matches.is_empty() {
+        bail!("No matches");
+    }
+    let ranges = matches
+        .iter()
+        .map(|mat| Range::new(mat.range.start, mat.range.end))
+        .collect();
+    doc.set_selection(view.id, Selection::new(ranges, 0));
+    cx.editor.set_status(format!(
+        "{} match{}",
+        matches.len(),
+        if matches.len() == 1 { "" } else { "es" }
+    ));
+    Ok(())
+}
+
+fn structural_replace(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let query = structural_query(cx.editor, &args[1])?;
+    let template = query.template(&args[0])?;
+    let language = doc!(cx.editor).syntax().unwrap().root_language();
+    let doc_ids: Vec<_> = if args.has_flag("all") {
+        cx.editor
+            .documents()
+            .filter(|doc| {
+                doc.syntax()
+                    .is_some_and(|syntax| syntax.root_language() == language)
+            })
+            .map(|doc| doc.id())
+            .collect()
+    } else {
+        vec![doc!(cx.editor).id()]
+    };
+
+    let mut replacements = 0;
+    let mut changed_docs = 0;
+    for doc_id in doc_ids {
+        let doc = doc!(cx.editor, &doc_id);
+        let text = doc.text().slice(..);
+        let matches = query.matches(doc.syntax().unwrap(), text);
+        if matches.is_empty() {
+            continue;
+        }
+        let transaction = Transaction::change(
+            doc.text(),
+            matches.iter().map(|mat| {
+                let replacement = mat.expand(&template, text);
+                (mat.range.start, mat.range.end, Some(replacement.into()))
+            }),
+        );
+        replacements += matches.len();
+        changed_docs += 1;
+
+        let view_id = cx.editor.get_synced_view_id(doc_id);
+        let doc = doc_mut!(cx.editor, &doc_id);
+        let view = view_mut!(cx.editor, view_id);
+        doc.apply(&transaction, view.id);
+        doc.append_changes_to_history(view);
+    }
+    if replacements == 0 {
+        bail!("No matches");
+    }
+    cx.editor.set_status(format!(
+        "Replaced {replacements} match{} in {changed_docs} buffer{}",
+        if replacements == 1 { "" } else { "es" },
+        if changed_docs == 1 { "" } else { "s" }
+    ));
+    Ok(())
+}
+
+fn replace_interactive(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let pattern = &args[0];
+    let case_insensitive =
+        cx.editor.config().search.smart_case && !pattern.chars().any(char::is_uppercase);
+    let regex = helix_core::regex::RegexBuilder::new(pattern)
+        .case_insensitive(case_insensitive)
+        .multi_line(true)
+        .build()?;
+
+    let doc = doc!(cx.editor);
+    let text = doc.text();
+    let contents = text.to_string();
+    let replacements: Vec<_> = regex
+        .captures_iter(&contents)
+        .filter_map(|captures| {
+            let mat = captures.get(0)?;
+            // empty matches would be inserted between every char
+            if mat.is_empty() {
+                return None;
+            }
+            let mut replacement = String::new();
+            captures.expand(&args[1], &mut replacement);
+            Some(ui::Replacement {
+                range: text.byte_to_char(mat.start())..text.byte_to_char(mat.end()),
+                matched: mat.as_str().to_string(),
+                replacement,
+            })
+        })
+        .collect();
+    if replacements.is_empty() {
+        bail!("No matches");
+    }
+
+    let callback = async move {
+        let call: job::Callback = Callback::EditorCompositor(Box::new(
+            move |editor: &mut Editor, compositor: &mut Compositor| {
+                let confirm = ui::ReplaceConfirm::new(editor, replacements);
+                compositor.push(Box::new(confirm));
+            },
+        ));
+        Ok(call)
+    };
+    cx.jobs.callback(callback);
+    Ok(())
+}
+
 fn open_config(
     cx: &mut compositor::Context,
     _args: Args,
@@ -2492,6 +3466,18 @@ fn open_log(cx: &mut compositor::Context, _args: Args, event: PromptEvent) -> an
     Ok(())
 }
 
+fn log_panel(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+    let server = args.first().map(|server| server.to_string());
+    with_editor_view(cx, |_editor, editor_view| {
+        editor_view.toggle_log_panel(server);
+        Ok(())
+    });
+    Ok(())
+}
+
 fn refresh_config(
     cx: &mut compositor::Context,
     _args: Args,
@@ -2565,28 +3551,116 @@ fn run_shell_command(
     let shell = cx.editor.config().shell.clone();
     let args = args.join(" ");
 
+    // the output is streamed into a scratch buffer below the current view
+    let view_id = view!(cx.editor).id;
+    let doc_id = cx.editor.new_file(Action::HorizontalSplit);
+    cx.editor.focus(view_id);
+    let handle = cx.editor.shell_controller.restart();
+    cx.editor
+        .set_status(format!("Running `{args}`, Ctrl-C to cancel"));
+
+    let callback = async move {
+        let status = shell_stream_async(&shell, &args, &handle, |output| {
+            job::dispatch(move |editor, _| append_shell_output(editor, doc_id, &output))
+        })
+        .await;
+        let call = move |editor: &mut Editor| {
+            let _handle = handle;
+            // don't leave an empty buffer behind for commands without output
+            if editor
+                .documents
+                .get(&doc_id)
+                .is_some_and(|doc| doc.text().len_chars() == 0)
+            {
+                let _ = editor.close_document(doc_id, true);
+            }
+            match status {
+                Ok(Some(status)) if status.success() => editor.set_status("Command run"),
+                Ok(Some(status)) => match status.code() {
+                    Some(code) => editor.set_error(format!("Shell command failed: status {code}")),
+                    None => editor.set_error("Shell command failed"),
+                },
+                // cancelled with Ctrl-C
+                Ok(None) => (),
+                Err(err) => editor.set_error(err.to_string()),
+            }
+        };
+        Ok(Callback::Editor(Box::new(call)))
+    };
+    cx.jobs.callback(callback);
+
+    Ok(())
+}
+
+/// Appends output of `:sh` to its scratch buffer, unless the buffer was closed. The buffer is
+/// left unmodified so that it can be closed without discarding changes.
+fn append_shell_output(editor: &mut Editor, doc_id: DocumentId, output: &str) {
+    let Some(doc) = editor.documents.get_mut(&doc_id) else {
+        return;
+    };
+    let Some(view) = editor
+        .tree
+        .views_mut()
+        .find_map(|(view, _)| (view.doc == doc_id).then_some(view))
+    else {
+        return;
+    };
+    let end = doc.text().len_chars();
+    let transaction =
+        Transaction::change(doc.text(), [(end, end, Some(output.into()))].into_iter());
+    doc.apply(&transaction, view.id);
+    doc.append_changes_to_history(view);
+    doc.reset_modified();
+}
+
+fn diff(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let path = helix_stdx::path::canonicalize(helix_stdx::path::expand_tilde(Path::new(&args[0])));
+    let source = match cx.editor.document_by_path(&path) {
+        Some(doc) => ui::DiffSource::Document(doc.id()),
+        None => {
+            let mut file = std::fs::File::open(&path)
+                .with_context(|| format!("failed to open {}", path.display()))?;
+            let (text, ..) = helix_view::document::from_reader(&mut file, None)?;
+            let name = helix_stdx::path::get_relative_path(&path)
+                .to_string_lossy()
+                .into_owned();
+            ui::DiffSource::Text { name, text }
+        }
+    };
+    open_diff_view(cx, source);
+    Ok(())
+}
+
+fn diff_head(cx: &mut compositor::Context, _args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let doc = doc!(cx.editor);
+    let Some(handle) = doc.diff_handle() else {
+        bail!("Diff is not available in the current buffer");
+    };
+    let text = handle.load().diff_base().clone();
+    let name = format!("HEAD:{}", doc.display_name());
+    open_diff_view(cx, ui::DiffSource::Text { name, text });
+    Ok(())
+}
+
+fn open_diff_view(cx: &mut compositor::Context, source: ui::DiffSource) {
     let callback = async move {
-        let output = shell_impl_async(&shell, &args, None).await?;
         let call: job::Callback = Callback::EditorCompositor(Box::new(
             move |editor: &mut Editor, compositor: &mut Compositor| {
-                if !output.trim().is_empty() {
-                    let contents = ui::Markdown::new(
-                        format!("```sh\n{}\n```", output.trim_end()),
-                        editor.syn_loader.clone(),
-                    );
-                    let popup = Popup::new("shell", contents).position(Some(
-                        helix_core::Position::new(editor.cursor().0.unwrap_or_default().row, 2),
-                    ));
-                    compositor.replace_or_push("shell", popup);
-                }
-                editor.set_status("Command run");
+                let diff_view = ui::DiffView::new(editor, source);
+                compositor.push(Box::new(overlaid(diff_view)));
             },
         ));
         Ok(call)
     };
     cx.jobs.callback(callback);
-
-    Ok(())
 }
 
 fn reset_diff_change(
@@ -2627,15 +3701,63 @@ fn reset_diff_change(
             }),
     );
     if changes == 0 {
-        bail!("There are no changes under any selection");
+        bail!("There are no changes under any selection");
+    }
+
+    drop(diff); // make borrow check happy
+    doc.apply(&transaction, view.id);
+    doc.append_changes_to_history(view);
+    view.ensure_cursor_in_view(doc, scrolloff);
+    cx.editor.set_status(format!(
+        "Reset {changes} change{}",
+        if changes == 1 { "" } else { "s" }
+    ));
+    Ok(())
+}
+
+fn hunk_stage(cx: &mut compositor::Context, _args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+    stage_hunks_impl(cx.editor, true)
+}
+
+fn hunk_unstage(
+    cx: &mut compositor::Context,
+    _args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+    stage_hunks_impl(cx.editor, false)
+}
+
+fn stage_hunks_impl(editor: &mut Editor, stage: bool) -> anyhow::Result<()> {
+    let (view, doc) = current_ref!(editor);
+    let Some(path) = doc.path() else {
+        bail!("Buffer has no path");
+    };
+    let text = doc.text();
+    let line_ranges: Vec<_> = doc.selection(view.id).line_ranges(text.slice(..)).collect();
+    let changes = if stage {
+        editor
+            .diff_providers
+            .stage_hunks(path, text, &line_ranges)?
+    } else {
+        editor
+            .diff_providers
+            .unstage_hunks(path, text, &line_ranges)?
+    };
+    if changes == 0 {
+        bail!(
+            "There are no {} changes under any selection",
+            if stage { "unstaged" } else { "staged" }
+        );
     }
-
-    drop(diff); // make borrow check happy
-    doc.apply(&transaction, view.id);
-    doc.append_changes_to_history(view);
-    view.ensure_cursor_in_view(doc, scrolloff);
-    cx.editor.set_status(format!(
-        "Reset {changes} change{}",
+    editor.set_status(format!(
+        "{} {changes} change{}",
+        if stage { "Staged" } else { "Unstaged" },
         if changes == 1 { "" } else { "s" }
     ));
     Ok(())
@@ -2689,6 +3811,59 @@ fn set_register(
     cx.editor.registers.write(register, vec![args[1].into()])
 }
 
+fn macro_edit(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    ensure!(
+        args[0].chars().count() == 1,
+        format!("Invalid register {}", &args[0])
+    );
+    let register = args[0].chars().next().unwrap_or_default();
+    let keys = cx
+        .editor
+        .registers
+        .first(register, cx.editor)
+        .map(|keys| keys.into_owned())
+        .unwrap_or_default();
+
+    let doc_id = cx.editor.new_file(Action::Replace);
+    let view = view_mut!(cx.editor);
+    let doc = doc_mut!(cx.editor, &doc_id);
+    let transaction = Transaction::insert(doc.text(), doc.selection(view.id), keys.into())
+        .with_selection(Selection::point(0));
+    doc.apply(&transaction, view.id);
+    doc.append_changes_to_history(view);
+
+    cx.editor.set_status(format!(
+        "Editing the macro in register [{register}], save it with :macro-save {register}"
+    ));
+    Ok(())
+}
+
+fn macro_save(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    ensure!(
+        args[0].chars().count() == 1,
+        format!("Invalid register {}", &args[0])
+    );
+    let register = args[0].chars().next().unwrap_or_default();
+    // line breaks only separate the keys for readability, a newline key is written as <ret>
+    let text = doc!(cx.editor).text().to_string();
+    let keys: String = text.lines().map(str::trim).collect();
+    ensure!(!keys.is_empty(), "The macro is empty");
+    helix_view::input::parse_macro(&keys).context("Invalid macro")?;
+
+    cx.editor.registers.write_macro(register, keys)?;
+    cx.editor
+        .set_status(format!("Saved the macro to register [{register}]"));
+    Ok(())
+}
+
 fn redraw(cx: &mut compositor::Context, _args: Args, event: PromptEvent) -> anyhow::Result<()> {
     if event != PromptEvent::Validate {
         return Ok(());
@@ -2885,6 +4060,7 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
 pub const SHELL_SIGNATURE: Signature = Signature {
     positionals: (1, Some(2)),
     raw_after: Some(1),
+    shell: true,
     ..Signature::DEFAULT
 };
 
@@ -2901,6 +4077,26 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
     ..Flag::DEFAULT
 };
 
+const ENCODING_FLAG: Flag = Flag {
+    name: "encoding",
+    alias: Some('e'),
+    doc: "the encoding of the file, e.g. `shift_jis` or `latin1`",
+    completions: Some(&[
+        "utf-8",
+        "utf-16le",
+        "utf-16be",
+        "windows-1252",
+        "latin1",
+        "shift_jis",
+        "euc-jp",
+        "euc-kr",
+        "gbk",
+        "gb18030",
+        "big5",
+        "koi8-r",
+    ]),
+};
+
 pub const TYPABLE_COMMAND_LIST: &[TypableCommand] = &[
     TypableCommand {
         name: "exit",
@@ -2910,7 +4106,7 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
         completer: CommandCompleter::positional(&[completers::filename]),
         signature: Signature {
             positionals: (0, Some(1)),
-            flags: &[WRITE_NO_FORMAT_FLAG],
+            flags: &[WRITE_NO_FORMAT_FLAG, ENCODING_FLAG],
             ..Signature::DEFAULT
         },
     },
@@ -2922,7 +4118,7 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
         completer: CommandCompleter::positional(&[completers::filename]),
         signature: Signature {
             positionals: (0, Some(1)),
-            flags: &[WRITE_NO_FORMAT_FLAG],
+            flags: &[WRITE_NO_FORMAT_FLAG, ENCODING_FLAG],
             ..Signature::DEFAULT
         },
     },
@@ -3041,15 +4237,59 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
             ..Signature::DEFAULT
         },
     },
+    TypableCommand {
+        name: "buffer-goto",
+        aliases: &["bg"],
+        doc: "Goto the buffer with the given number in the bufferline, counting from 1.",
+        fun: buffer_goto,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (1, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "buffer-pin",
+        aliases: &[],
+        doc: "Pin or unpin the current buffer. Pinned buffers are listed first in the bufferline and aren't closed by :buffer-close-others and :buffer-close-all.",
+        fun: buffer_pin,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "buffer-move-left",
+        aliases: &[],
+        doc: "Move the current buffer one place to the left in the bufferline.",
+        fun: buffer_move_left,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "buffer-move-right",
+        aliases: &[],
+        doc: "Move the current buffer one place to the right in the bufferline.",
+        fun: buffer_move_right,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
     TypableCommand {
         name: "write",
         aliases: &["w"],
-        doc: "Write changes to disk. Accepts an optional path (:write some/path.txt)",
+        doc: "Write changes to disk. Accepts an optional path (:write some/path.txt) and converts the file with `--encoding`",
         fun: write,
         completer: CommandCompleter::positional(&[completers::filename]),
         signature: Signature {
             positionals: (0, Some(1)),
-            flags: &[WRITE_NO_FORMAT_FLAG],
+            flags: &[WRITE_NO_FORMAT_FLAG, ENCODING_FLAG],
             ..Signature::DEFAULT
         },
     },
@@ -3061,7 +4301,7 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
         completer: CommandCompleter::positional(&[completers::filename]),
         signature: Signature {
             positionals: (0, Some(1)),
-            flags: &[WRITE_NO_FORMAT_FLAG],
+            flags: &[WRITE_NO_FORMAT_FLAG, ENCODING_FLAG],
             ..Signature::DEFAULT
         },
     },
@@ -3166,7 +4406,7 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
         completer: CommandCompleter::positional(&[completers::filename]),
         signature: Signature {
             positionals: (0, Some(1)),
-            flags: &[WRITE_NO_FORMAT_FLAG],
+            flags: &[WRITE_NO_FORMAT_FLAG, ENCODING_FLAG],
             ..Signature::DEFAULT
         },
     },
@@ -3178,7 +4418,7 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
         completer: CommandCompleter::positional(&[completers::filename]),
         signature: Signature {
             positionals: (0, Some(1)),
-            flags: &[WRITE_NO_FORMAT_FLAG],
+            flags: &[WRITE_NO_FORMAT_FLAG, ENCODING_FLAG],
             ..Signature::DEFAULT
         },
     },
@@ -3285,6 +4525,17 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
             ..Signature::DEFAULT
         },
     },
+    TypableCommand {
+        name: "theme-reload",
+        aliases: &[],
+        doc: "Reload the current theme from its files.",
+        fun: theme_reload,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
     TypableCommand {
         name: "yank-join",
         aliases: &[],
@@ -3472,6 +4723,50 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
             ..Signature::DEFAULT
         },
     },
+    TypableCommand {
+        name: "workspace-add",
+        aliases: &[],
+        doc: "Open one or more directories as additional workspace roots.",
+        fun: workspace_add,
+        completer: CommandCompleter::all(completers::directory),
+        signature: Signature {
+            positionals: (1, None),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "workspace-remove",
+        aliases: &[],
+        doc: "Close one or more additional workspace roots.",
+        fun: workspace_remove,
+        completer: CommandCompleter::all(completers::directory),
+        signature: Signature {
+            positionals: (1, None),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "show-workspaces",
+        aliases: &[],
+        doc: "Show the workspace roots of the editor.",
+        fun: show_workspaces,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "spell-add",
+        aliases: &[],
+        doc: "Add words to the spell check dictionary. Adds the primary selection if no words are given.",
+        fun: spell_add,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, None),
+            ..Signature::DEFAULT
+        },
+    },
     TypableCommand {
         name: "encoding",
         aliases: &[],
@@ -3483,6 +4778,28 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
             ..Signature::DEFAULT
         },
     },
+    TypableCommand {
+        name: "set-readonly",
+        aliases: &[],
+        doc: "Make the current buffer read-only, or editable again with `false`. Toggles without an argument.",
+        fun: set_readonly,
+        completer: CommandCompleter::positional(&[completers::boolean]),
+        signature: Signature {
+            positionals: (0, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "insert-char",
+        aliases: &[],
+        doc: "Insert characters by their codepoint like `U+03BB`, their digraph like `l*` or, for digraph characters, their Unicode name.",
+        fun: insert_character,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (1, None),
+            ..Signature::DEFAULT
+        },
+    },
     TypableCommand {
         name: "character-info",
         aliases: &["char"],
@@ -3497,11 +4814,12 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
     TypableCommand {
         name: "reload",
         aliases: &["rl"],
-        doc: "Discard changes and reload from the source file.",
+        doc: "Discard changes and reload from the source file. Decodes the file with `--encoding` if given.",
         fun: reload,
         completer: CommandCompleter::none(),
         signature: Signature {
             positionals: (0, Some(0)),
+            flags: &[ENCODING_FLAG],
             ..Signature::DEFAULT
         },
     },
@@ -3516,6 +4834,25 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
             ..Signature::DEFAULT
         },
     },
+    TypableCommand {
+        name: "recover",
+        aliases: &[],
+        doc: "Restore the unsaved changes of files from the recovery files that were left over after a crash.",
+        fun: recover,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            flags: &[
+                Flag {
+                    name: "discard",
+                    alias: Some('d'),
+                    doc: "delete all recovery files instead of restoring them",
+                    ..Flag::DEFAULT
+                },
+            ],
+            ..Signature::DEFAULT
+        },
+    },
     TypableCommand {
         name: "update",
         aliases: &["u"],
@@ -3628,6 +4965,64 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
             ..Signature::DEFAULT
         },
     },
+    TypableCommand {
+        name: "debug-breakpoint-condition",
+        aliases: &[],
+        doc: "Set the condition, hit count condition (--hits) and log message (--log) of the breakpoint on the current line, creating it if needed. Clears them if no argument is given.",
+        fun: debug_breakpoint_condition,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(1)),
+            flags: &[
+                Flag {
+                    name: "hits",
+                    doc: "the hit count condition, e.g. `>5`",
+                    completions: Some(&[]),
+                    ..Flag::DEFAULT
+                },
+                Flag {
+                    name: "log",
+                    doc: "the message logged when the breakpoint is hit, turning it into a logpoint",
+                    completions: Some(&[]),
+                    ..Flag::DEFAULT
+                },
+            ],
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "debug-watch",
+        aliases: &[],
+        doc: "Add an expression to the debug watch panel. Watched expressions are re-evaluated whenever the debuggee stops.",
+        fun: debug_watch,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (1, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "debug-unwatch",
+        aliases: &[],
+        doc: "Remove an expression from the debug watch panel. Removes all expressions if none is given.",
+        fun: debug_unwatch,
+        completer: CommandCompleter::positional(&[debug_watch_completer]),
+        signature: Signature {
+            positionals: (0, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "debug-toggle-watches",
+        aliases: &[],
+        doc: "Show or hide the debug watch panel. The watched expressions are kept while it's hidden.",
+        fun: debug_toggle_watches,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
     TypableCommand {
         name: "vsplit",
         aliases: &["vs"],
@@ -3661,6 +5056,28 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
             ..Signature::DEFAULT
         },
     },
+    TypableCommand {
+        name: "vsplit-resize",
+        aliases: &[],
+        doc: "Change the width of the split by +N or -N columns, or set it to N columns.",
+        fun: vsplit_resize,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (1, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "hsplit-resize",
+        aliases: &[],
+        doc: "Change the height of the split by +N or -N lines, or set it to N lines.",
+        fun: hsplit_resize,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (1, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
     TypableCommand {
         name: "hsplit-new",
         aliases: &["hnew"],
@@ -3730,6 +5147,17 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
             ..Signature::DEFAULT
         },
     },
+    TypableCommand {
+        name: "toggle-inlay-hints-kind",
+        aliases: &[],
+        doc: "Show or hide the inlay hints of a kind: `type`, `parameter` or `other`.",
+        fun: toggle_inlay_hints_kind,
+        completer: CommandCompleter::positional(&[completers::inlay_hint_kind]),
+        signature: Signature {
+            positionals: (1, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
     TypableCommand {
         name: "get-option",
         aliases: &["get"],
@@ -3757,9 +5185,63 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
                     ..Flag::DEFAULT
                 },
                 Flag {
-                    name: "reverse",
-                    alias: Some('r'),
-                    doc: "sort ranges in reverse order",
+                    name: "reverse",
+                    alias: Some('r'),
+                    doc: "sort ranges in reverse order",
+                    ..Flag::DEFAULT
+                },
+            ],
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "reflow",
+        aliases: &[],
+        doc: "Hard-wrap the current selection of lines to a given width.",
+        fun: reflow,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "replace-apply",
+        aliases: &[],
+        doc: "Write the edited lines of a global search results buffer back to their files.",
+        fun: replace_apply,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "structural-search",
+        aliases: &[],
+        doc: "Select the nodes captured with @match by a tree-sitter query in the current buffer.",
+        fun: structural_search,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (1, Some(1)),
+            raw_after: Some(0),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "structural-replace",
+        aliases: &[],
+        doc: "Replace the nodes captured with @match by a tree-sitter query with a template. `$name` in the template refers to the capture @name. With `--all` the open buffers of the same language are changed too, files that aren't open are not searched.",
+        fun: structural_replace,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (2, Some(2)),
+            raw_after: Some(1),
+            flags: &[
+                Flag {
+                    name: "all",
+                    alias: Some('a'),
+                    doc: "replace in all open buffers of the same language, not in unopened files",
                     ..Flag::DEFAULT
                 },
             ],
@@ -3767,13 +5249,14 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
         },
     },
     TypableCommand {
-        name: "reflow",
+        name: "replace-interactive",
         aliases: &[],
-        doc: "Hard-wrap the current selection of lines to a given width.",
-        fun: reflow,
+        doc: "Replace the matches of a regex in the current buffer with a replacement, confirming each match with y(es), n(o), a(ll), l(ast) or q(uit). `$1` or `${name}` in the replacement refer to capture groups. The accepted replacements are applied as a single change.",
+        fun: replace_interactive,
         completer: CommandCompleter::none(),
         signature: Signature {
-            positionals: (0, Some(1)),
+            positionals: (2, Some(2)),
+            raw_after: Some(1),
             ..Signature::DEFAULT
         },
     },
@@ -3832,6 +5315,17 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
             ..Signature::DEFAULT
         },
     },
+    TypableCommand {
+        name: "log",
+        aliases: &[],
+        doc: "Toggle a panel that tails the log and the messages exchanged with language servers and debug adapters. Takes an optional language server name, or `dap#<id>` for a debug adapter, to only list its messages.",
+        fun: log_panel,
+        completer: CommandCompleter::all(completers::active_language_servers),
+        signature: Signature {
+            positionals: (0, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
     TypableCommand {
         name: "insert-output",
         aliases: &[],
@@ -3867,14 +5361,36 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
     TypableCommand {
         name: "run-shell-command",
         aliases: &["sh", "!"],
-        doc: "Run a shell command",
+        doc: "Run a shell command, streaming its output into a scratch buffer",
         fun: run_shell_command,
         completer: SHELL_COMPLETER,
         signature: SHELL_SIGNATURE,
     },
+    TypableCommand {
+        name: "diff",
+        aliases: &[],
+        doc: "Compare the current buffer with a file side by side. `o` obtains and `p` puts the hunk, `n`/`N` go to the next/previous hunk and `u` toggles the unified view.",
+        fun: diff,
+        completer: CommandCompleter::positional(&[completers::filename]),
+        signature: Signature {
+            positionals: (1, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "diff-head",
+        aliases: &[],
+        doc: "Compare the current buffer with its version in the VCS diff base (usually HEAD).",
+        fun: diff_head,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
     TypableCommand {
         name: "reset-diff-change",
-        aliases: &["diffget", "diffg"],
+        aliases: &["diffget", "diffg", "hunk-revert"],
         doc: "Reset the diff change at the cursor position.",
         fun: reset_diff_change,
         completer: CommandCompleter::none(),
@@ -3883,6 +5399,28 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
             ..Signature::DEFAULT
         },
     },
+    TypableCommand {
+        name: "hunk-stage",
+        aliases: &[],
+        doc: "Stage the changes under the selections in the git index.",
+        fun: hunk_stage,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "hunk-unstage",
+        aliases: &[],
+        doc: "Unstage the staged changes under the selections from the git index.",
+        fun: hunk_unstage,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
     TypableCommand {
         name: "clear-register",
         aliases: &[],
@@ -3906,6 +5444,28 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
             ..Signature::DEFAULT
         },
     },
+    TypableCommand {
+        name: "macro-edit",
+        aliases: &[],
+        doc: "Open the keys of the macro in the given register in a scratch buffer for editing.",
+        fun: macro_edit,
+        completer: CommandCompleter::positional(&[completers::register]),
+        signature: Signature {
+            positionals: (1, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "macro-save",
+        aliases: &[],
+        doc: "Save the keys in the current buffer as a macro to the given register.",
+        fun: macro_save,
+        completer: CommandCompleter::positional(&[completers::register]),
+        signature: Signature {
+            positionals: (1, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
     TypableCommand {
         name: "redraw",
         aliases: &[],
@@ -3986,7 +5546,7 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
     TypableCommand {
         name: "workspace-trust",
         aliases: &[],
-        doc: "Add current workspace to the list of trusted workspaces.",
+        doc: "Add the workspace of the current buffer to the list of trusted workspaces.",
         fun: trust_workspace,
         completer: CommandCompleter::none(),
         signature: Signature { positionals: (0, None), ..Signature::DEFAULT },
@@ -3994,11 +5554,110 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
     TypableCommand {
         name: "workspace-untrust",
         aliases: &[],
-        doc: "Remove current workspace from the list of trusted workspaces.",
+        doc: "Remove the workspace of the current buffer from the list of trusted workspaces.",
         fun: untrust_workspace,
         completer: CommandCompleter::none(),
         signature: Signature { positionals: (0, None), ..Signature::DEFAULT },
-    }
+    },
+    TypableCommand {
+        name: "terminal",
+        aliases: &["term"],
+        doc: "Open a terminal below the views or focus the open terminal. Ctrl-\\ moves the focus back to the editor.",
+        fun: terminal,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "vterminal",
+        aliases: &["vterm"],
+        doc: "Open a terminal to the right of the views or focus the open terminal.",
+        fun: vterminal,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "terminal-send",
+        aliases: &[],
+        doc: "Send the selections to the terminal, each selection on its own line.",
+        fun: terminal_send,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "terminal-close",
+        aliases: &[],
+        doc: "Close the terminal and stop its shell.",
+        fun: terminal_close,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "task",
+        aliases: &[],
+        doc: "Run a task of tasks.toml with `:task run <name>`, the problems in its output are added to the quickfix list. `:task list` lists the tasks.",
+        fun: task,
+        completer: CommandCompleter::positional(&[task_subcommand, completers::task]),
+        signature: Signature {
+            positionals: (1, Some(2)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "session-save",
+        aliases: &[],
+        doc: "Save the open buffers, window layout, selections, jumplists and registers as a session. Defaults to the current session.",
+        fun: session_save,
+        completer: CommandCompleter::positional(&[completers::session]),
+        signature: Signature {
+            positionals: (0, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "session-load",
+        aliases: &[],
+        doc: "Restore a session saved with :session-save.",
+        fun: session_load,
+        completer: CommandCompleter::positional(&[completers::session]),
+        signature: Signature {
+            positionals: (1, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "layout-save",
+        aliases: &[],
+        doc: "Save the window layout with the displayed buffers under a name.",
+        fun: layout_save,
+        completer: CommandCompleter::positional(&[completers::layout]),
+        signature: Signature {
+            positionals: (1, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "layout-load",
+        aliases: &[],
+        doc: "Replace the windows with a layout saved with :layout-save.",
+        fun: layout_load,
+        completer: CommandCompleter::positional(&[completers::layout]),
+        signature: Signature {
+            positionals: (1, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
 ];
 
 pub static TYPABLE_COMMAND_MAP: Lazy<HashMap<&'static str, &'static TypableCommand>> =
@@ -4043,7 +5702,11 @@ pub(super) fn execute_command(
 ) -> anyhow::Result<()> {
     let args = if event == PromptEvent::Validate {
         Args::parse(args, cmd.signature, true, |token| {
-            expansion::expand(cx.editor, token).map_err(|err| err.into())
+            if cmd.signature.shell {
+                expansion::expand_shell_command(cx.editor, token).map_err(|err| err.into())
+            } else {
+                expansion::expand(cx.editor, token).map_err(|err| err.into())
+            }
         })
         .map_err(|err| anyhow!("'{}': {err}", cmd.name))?
     } else {
@@ -4438,7 +6101,8 @@ fn trust_workspace(
         return Ok(());
     }
 
-    helix_loader::workspace_trust::WorkspaceTrust::load(false).trust_workspace();
+    let (workspace, _) = current_doc_workspace(cx.editor);
+    helix_loader::workspace_trust::WorkspaceTrust::load(false).trust_path(workspace);
 
     cx.editor.config_events.0.send(ConfigEvent::Refresh)?;
     // HACK
@@ -4446,7 +6110,7 @@ fn trust_workspace(
 }
 
 fn untrust_workspace(
-    _cx: &mut compositor::Context,
+    cx: &mut compositor::Context,
     _args: Args<'_>,
     event: PromptEvent,
 ) -> anyhow::Result<()> {
@@ -4454,6 +6118,244 @@ fn untrust_workspace(
         return Ok(());
     }
 
-    helix_loader::workspace_trust::WorkspaceTrust::load(false).untrust_workspace();
+    let (workspace, _) = current_doc_workspace(cx.editor);
+    helix_loader::workspace_trust::WorkspaceTrust::load(false).untrust_path(&workspace);
+    Ok(())
+}
+
+/// The workspace of the current buffer: the innermost additional workspace root that contains
+/// it, or the workspace of the current working directory.
+fn current_doc_workspace(editor: &Editor) -> (PathBuf, bool) {
+    helix_lsp::find_doc_workspace(
+        doc!(editor).path().map(PathBuf::as_path),
+        &editor.workspace_roots,
+    )
+}
+
+fn session_save(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let name = match args.first() {
+        Some(name) => name.to_string(),
+        None => cx
+            .editor
+            .session
+            .clone()
+            .ok_or_else(|| anyhow!("No session name given"))?,
+    };
+    Session::capture(cx.editor).save(&Session::file(&name)?)?;
+    cx.editor.set_status(format!("Saved session '{name}'"));
+    cx.editor.session = Some(name);
+    Ok(())
+}
+
+fn session_load(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let name = &args[0];
+    let session = Session::load(&Session::file(name)?)?;
+    session.restore(cx.editor)?;
+    cx.editor.set_status(format!("Loaded session '{name}'"));
+    cx.editor.session = Some(name.to_string());
+    Ok(())
+}
+
+fn layout_save(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let name = &args[0];
+    WindowLayout::capture(cx.editor).save(&WindowLayout::file(name)?)?;
+    cx.editor.set_status(format!("Saved layout '{name}'"));
+    Ok(())
+}
+
+fn layout_load(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let name = &args[0];
+    WindowLayout::load(&WindowLayout::file(name)?)?.restore(cx.editor);
+    cx.editor.set_status(format!("Loaded layout '{name}'"));
+    Ok(())
+}
+
+fn task_subcommand(_editor: &Editor, input: &str) -> Vec<ui::prompt::Completion> {
+    fuzzy_match(input, ["run", "list"], false)
+        .into_iter()
+        .map(|(name, _)| ((0..), name.into()))
+        .collect()
+}
+
+fn task(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let mut tasks = helix_loader::tasks::load_tasks(cx.editor.config().insecure)?;
+    match (&args[0], args.get(1)) {
+        ("list", None) => {
+            if tasks.is_empty() {
+                cx.editor.set_status("No tasks are defined");
+            } else {
+                let names: Vec<_> = tasks.keys().map(String::as_str).collect();
+                cx.editor.set_status(format!("Tasks: {}", names.join(", ")));
+            }
+        }
+        ("run", Some(name)) => {
+            let task = tasks
+                .remove(name)
+                .ok_or_else(|| anyhow!("No task named '{name}'"))?;
+            let matchers = task
+                .problem_matchers
+                .iter()
+                .map(|pattern| ProblemMatcher::new(pattern))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let cwd = match &task.cwd {
+                Some(cwd) => helix_loader::find_workspace().0.join(cwd),
+                None => helix_loader::find_workspace().0,
+            };
+            let shell = cx.editor.config().shell.clone();
+            ensure!(!shell.is_empty(), "No shell set");
+            let name = name.to_string();
+            cx.editor.set_status(format!("Running task '{name}'"));
+
+            let callback = async move {
+                let output = tokio::process::Command::new(&shell[0])
+                    .args(&shell[1..])
+                    .arg(&task.command)
+                    .current_dir(&cwd)
+                    .stdin(std::process::Stdio::null())
+                    .output()
+                    .await;
+                let call: job::Callback = Callback::Editor(Box::new(move |editor: &mut Editor| {
+                    let output = match output {
+                        Ok(output) => output,
+                        Err(err) => {
+                            editor.set_error(format!("Failed to run task '{name}': {err}"));
+                            return;
+                        }
+                    };
+                    // compilers usually report problems on stderr
+                    let text = format!(
+                        "{}{}",
+                        String::from_utf8_lossy(&output.stdout),
+                        String::from_utf8_lossy(&output.stderr)
+                    );
+                    let problems = match_problems(&text, &matchers, &cwd);
+                    let count = problems.len();
+                    editor.quickfix.set(problems);
+                    let status = if output.status.success() {
+                        "finished"
+                    } else {
+                        "failed"
+                    };
+                    let message = format!("Task '{name}' {status}: {count} problem(s)");
+                    if output.status.success() {
+                        editor.set_status(message);
+                    } else {
+                        editor.set_error(message);
+                    }
+                }));
+                Ok(call)
+            };
+            cx.jobs.callback(callback);
+        }
+        _ => bail!("Usage: :task run <name> or :task list"),
+    }
+    Ok(())
+}
+
+/// Runs `f` with the editor view once the command finished, the terminal is part of the view.
+fn with_editor_view(
+    cx: &mut compositor::Context,
+    f: impl FnOnce(&mut Editor, &mut ui::EditorView) -> anyhow::Result<()> + Send + 'static,
+) {
+    let callback = async move {
+        let call: job::Callback = Callback::EditorCompositor(Box::new(
+            move |editor: &mut Editor, compositor: &mut Compositor| {
+                if let Some(editor_view) = compositor.find::<ui::EditorView>() {
+                    if let Err(err) = f(editor, editor_view) {
+                        editor.set_error(err.to_string());
+                    }
+                }
+            },
+        ));
+        Ok(call)
+    };
+    cx.jobs.callback(callback);
+}
+
+fn terminal(cx: &mut compositor::Context, _args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+    with_editor_view(cx, |editor, editor_view| {
+        Ok(editor_view.open_terminal(editor, TerminalLayout::Horizontal)?)
+    });
+    Ok(())
+}
+
+fn vterminal(cx: &mut compositor::Context, _args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+    with_editor_view(cx, |editor, editor_view| {
+        Ok(editor_view.open_terminal(editor, TerminalLayout::Vertical)?)
+    });
+    Ok(())
+}
+
+fn terminal_send(
+    cx: &mut compositor::Context,
+    _args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+    let (view, doc) = current_ref!(cx.editor);
+    let mut text = String::new();
+    for fragment in doc.selection(view.id).fragments(doc.text().slice(..)) {
+        text.push_str(&fragment);
+        if !text.ends_with('\n') {
+            text.push('\n');
+        }
+    }
+    with_editor_view(cx, move |_editor, editor_view| {
+        match &mut editor_view.terminal {
+            Some(terminal) => Ok(terminal.send_text(&text)?),
+            None => bail!("No terminal is open"),
+        }
+    });
+    Ok(())
+}
+
+fn terminal_close(
+    cx: &mut compositor::Context,
+    _args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+    with_editor_view(cx, |_editor, editor_view| {
+        editor_view.terminal = None;
+        Ok(())
+    });
     Ok(())
 }
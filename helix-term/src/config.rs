@@ -183,6 +183,47 @@ fn parsing_keymaps_config_file() {
         );
     }
 
+    #[test]
+    fn parsing_abbreviations() {
+        let config = Config::load_test(
+            r#"
+            [editor.abbreviations]
+            teh = "the"
+            fn = "function"
+
+            [editor.abbreviations.rust]
+            fn = "fn"
+            "#,
+        );
+        let abbreviations = &config.editor.abbreviations;
+        assert_eq!(abbreviations.get(None, "teh"), Some("the"));
+        assert_eq!(abbreviations.get(Some("rust"), "teh"), Some("the"));
+        assert_eq!(abbreviations.get(None, "fn"), Some("function"));
+        assert_eq!(abbreviations.get(Some("rust"), "fn"), Some("fn"));
+        assert_eq!(abbreviations.get(None, "rust"), None);
+        assert_eq!(abbreviations.get(None, "the"), None);
+    }
+
+    #[test]
+    fn parsing_statusline_segments() {
+        let config = Config::load_test(
+            r#"
+            [editor.statusline.segments.search]
+            register = "/"
+            "#,
+        );
+        assert_eq!(
+            config.editor.statusline.segments["search"].register,
+            Some('/')
+        );
+
+        let config = r#"
+            [editor.statusline.segments.clipboard]
+            register = "+"
+        "#;
+        assert!(Config::load(Ok(&config.to_owned()), Err(ConfigLoadError::default())).is_err());
+    }
+
     #[test]
     fn keys_resolve_to_correct_defaults() {
         // From serde default
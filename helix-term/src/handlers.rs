@@ -12,29 +12,50 @@
 
 pub use helix_view::handlers::{word_index, Handlers};
 
+use self::blame::BlameHandler;
 use self::document_colors::DocumentColorsHandler;
 use self::document_links::DocumentLinksHandler;
+use self::inline_completion::InlineCompletionHandler;
+use self::recovery::RecoveryHandler;
+use self::semantic_tokens::SemanticTokensHandler;
+use self::spelling::SpellCheckHandler;
+use self::syntax::SyntaxHandler;
 
 mod auto_save;
+mod blame;
+mod block_selection;
 pub mod completion;
 pub mod diagnostics;
 mod document_colors;
 mod document_highlight;
 mod document_links;
+pub mod inline_completion;
 mod prompt;
+mod recovery;
+pub mod semantic_tokens;
 mod signature_help;
 mod snippet;
+pub mod spelling;
+mod statusline;
+mod syntax;
 mod workspace_trust;
 
 pub fn setup(config: Arc<ArcSwap<Config>>) -> Handlers {
     events::register();
 
-    let event_tx = completion::CompletionHandler::new(config).spawn();
+    statusline::spawn(config.clone());
+    let event_tx = completion::CompletionHandler::new(config.clone()).spawn();
     let signature_hints = SignatureHelpHandler::new().spawn();
     let auto_save = AutoSaveHandler::new().spawn();
     let document_colors = DocumentColorsHandler::default().spawn();
     let document_links = DocumentLinksHandler::default().spawn();
-    let word_index = word_index::Handler::spawn();
+    let semantic_tokens = SemanticTokensHandler::default().spawn();
+    let inline_completions = InlineCompletionHandler::default().spawn();
+    let spell_check = SpellCheckHandler::default().spawn();
+    let blame = BlameHandler::default().spawn();
+    let recovery = RecoveryHandler::default().spawn();
+    let syntax = SyntaxHandler::default().spawn();
+    let word_index = word_index::Handler::spawn(&config.load().editor.word_completion);
     let pull_diagnostics = PullDiagnosticsHandler::default().spawn();
     let pull_all_documents_diagnostics = PullAllDocumentsDiagnosticHandler::default().spawn();
 
@@ -44,6 +65,12 @@ pub fn setup(config: Arc<ArcSwap<Config>>) -> Handlers {
         auto_save,
         document_colors,
         document_links,
+        semantic_tokens,
+        inline_completions,
+        spell_check,
+        blame,
+        recovery,
+        syntax,
         word_index,
         pull_diagnostics,
         pull_all_documents_diagnostics,
@@ -58,6 +85,13 @@ pub fn setup(config: Arc<ArcSwap<Config>>) -> Handlers {
     snippet::register_hooks(&handlers);
     document_colors::register_hooks(&handlers);
     document_links::register_hooks(&handlers);
+    semantic_tokens::register_hooks(&handlers);
+    inline_completion::register_hooks(&handlers);
+    spelling::register_hooks(&handlers);
+    blame::register_hooks(&handlers);
+    block_selection::register_hooks(&handlers);
+    recovery::register_hooks(&handlers);
+    syntax::register_hooks(&handlers);
     prompt::register_hooks(&handlers);
     workspace_trust::register_hooks(&handlers);
     handlers
@@ -0,0 +1,93 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use helix_event::{register_hook, send_blocking};
+use helix_view::events::{ConfigDidChange, DocumentDidChange, DocumentDidOpen};
+use helix_view::handlers::{BlameEvent, Handlers};
+use helix_view::{DocumentId, Editor};
+use tokio::time::Instant;
+
+use crate::job;
+
+#[derive(Default)]
+pub(super) struct BlameHandler {
+    docs: HashSet<DocumentId>,
+}
+
+const DOCUMENT_CHANGE_DEBOUNCE: Duration = Duration::from_millis(1000);
+
+impl helix_event::AsyncHook for BlameHandler {
+    type Event = BlameEvent;
+
+    fn handle_event(&mut self, event: Self::Event, _timeout: Option<Instant>) -> Option<Instant> {
+        let BlameEvent(doc_id) = event;
+        self.docs.insert(doc_id);
+        Some(Instant::now() + DOCUMENT_CHANGE_DEBOUNCE)
+    }
+
+    fn finish_debounce(&mut self) {
+        let docs = std::mem::take(&mut self.docs);
+
+        job::dispatch_blocking(move |editor, _compositor| {
+            for doc in docs {
+                request_blame(editor, doc);
+            }
+        });
+    }
+}
+
+/// Loads the blame of a document in the background. Blames are cached by the diff providers
+/// until `HEAD` moves, so requesting the blame again after an edit is cheap.
+fn request_blame(editor: &mut Editor, doc_id: DocumentId) {
+    let Some(path) = editor
+        .documents
+        .get(&doc_id)
+        .and_then(|doc| doc.path())
+        .map(ToOwned::to_owned)
+    else {
+        return;
+    };
+    let diff_providers = editor.diff_providers.clone();
+
+    tokio::spawn(async move {
+        let Ok(blame) = tokio::task::spawn_blocking(move || diff_providers.get_blame(&path)).await
+        else {
+            return;
+        };
+
+        job::dispatch(move |editor, _| {
+            if let Some(doc) = editor.documents.get_mut(&doc_id) {
+                doc.set_blame(blame);
+            }
+        })
+        .await;
+    });
+}
+
+pub(super) fn register_hooks(handlers: &Handlers) {
+    let tx = handlers.blame.clone();
+    register_hook!(move |event: &mut DocumentDidOpen<'_>| {
+        if event.editor.config().inline_blame.enable {
+            send_blocking(&tx, BlameEvent(event.doc));
+        }
+        Ok(())
+    });
+
+    let tx = handlers.blame.clone();
+    register_hook!(move |event: &mut DocumentDidChange<'_>| {
+        if !event.ghost_transaction && event.doc.config.load().inline_blame.enable {
+            send_blocking(&tx, BlameEvent(event.doc.id()));
+        }
+        Ok(())
+    });
+
+    let tx = handlers.blame.clone();
+    register_hook!(move |event: &mut ConfigDidChange<'_>| {
+        if event.new.inline_blame.enable && !event.old.inline_blame.enable {
+            for doc in event.editor.documents() {
+                send_blocking(&tx, BlameEvent(doc.id()));
+            }
+        }
+        Ok(())
+    });
+}
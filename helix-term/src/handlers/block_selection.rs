@@ -0,0 +1,35 @@
+use helix_core::{selection::select_block, visual_coords_at_pos};
+use helix_event::register_hook;
+use helix_view::{document::Mode, handlers::Handlers};
+
+use crate::events::PostCommand;
+
+pub(super) fn register_hooks(_handlers: &Handlers) {
+    register_hook!(move |event: &mut PostCommand<'_, '_>| {
+        let editor = &mut *event.cx.editor;
+        if editor.mode != Mode::Select {
+            for (view, _) in editor.tree.views_mut() {
+                view.block_anchor = None;
+            }
+            return Ok(());
+        }
+
+        // rebuild the block from the cursor moved by the command
+        let (view, doc) = current!(editor);
+        let Some(anchor) = view.block_anchor else {
+            return Ok(());
+        };
+        let text = doc.text().slice(..);
+        let tab_width = doc.tab_width();
+        let primary = doc.selection(view.id).primary();
+        let mut head = visual_coords_at_pos(text, primary.cursor(text), tab_width);
+        // vertical movements remember the column of the cursor on shorter lines
+        if let Some((_, col)) = primary.old_visual_position {
+            head.col = col as usize;
+        }
+        let mut selection = select_block(text, anchor, head, tab_width);
+        selection.primary_mut().old_visual_position = primary.old_visual_position;
+        doc.set_selection(view.id, selection);
+        Ok(())
+    });
+}
@@ -25,11 +25,13 @@
 pub use item::{CompletionItem, CompletionItems, CompletionResponse, LspCompletionItem};
 pub use request::CompletionHandler;
 pub use resolve::ResolveHandler;
+pub use word::remove_duplicate_words;
 
 mod item;
 mod path;
 mod request;
 mod resolve;
+mod spelling;
 mod word;
 
 async fn handle_response(
@@ -103,6 +105,7 @@ fn show_completion(
         return;
     }
     word::retain_valid_completions(trigger, doc, view.id, &mut items);
+    remove_duplicate_words(&mut items);
     editor.handlers.completions.active_completions = context;
 
     let completion_area = ui.set_completion(editor, items, trigger.pos, size);
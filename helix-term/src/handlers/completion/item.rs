@@ -79,7 +79,7 @@ impl CompletionItem {
     pub fn filter_text(&self) -> &str {
         match self {
             CompletionItem::Lsp(item) => item.filter_text(),
-            CompletionItem::Other(item) => &item.label,
+            CompletionItem::Other(item) => item.filter_text.as_deref().unwrap_or(&item.label),
         }
     }
 }
@@ -112,6 +112,7 @@ pub(crate) fn path_completion(
                 Some(CompletionItem::Other(core::CompletionItem {
                     kind: Cow::Borrowed(kind),
                     label: file_name.into(),
+                    filter_text: None,
                     transaction,
                     documentation: Some(documentation),
                     provider: CompletionProvider::Path,
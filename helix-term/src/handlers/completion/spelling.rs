@@ -0,0 +1,77 @@
+use std::{borrow::Cow, sync::Arc};
+
+use helix_core::{self as core, completion::CompletionProvider, movement, Transaction};
+use helix_event::TaskHandle;
+use helix_view::{document::SavePoint, handlers::completion::ResponseContext, Editor};
+
+use super::{request::TriggerKind, CompletionItem, CompletionItems, CompletionResponse, Trigger};
+
+const COMPLETION_KIND: &str = "spelling";
+const MAX_SUGGESTIONS: usize = 10;
+
+/// Suggests corrections for a misspelled word before the cursor when completion is invoked
+/// manually.
+pub(super) fn completion(
+    editor: &mut Editor,
+    trigger: Trigger,
+    handle: TaskHandle,
+    savepoint: Arc<SavePoint>,
+) -> Option<impl FnOnce() -> CompletionResponse> {
+    if trigger.kind != TriggerKind::Manual {
+        return None;
+    }
+    let dictionary = editor.spell_dictionary()?;
+    let (view, doc) = current_ref!(editor);
+    let rope = doc.text().clone();
+    let text = doc.text().slice(..);
+    let selection = doc.selection(view.id).clone();
+    let pos = selection.primary().cursor(text);
+
+    let word_start = movement::move_prev_word_start(text, core::Range::point(pos), 1).head;
+    let typed_word: String = text.slice(word_start..pos).into();
+    if typed_word.is_empty()
+        || !typed_word.chars().all(char::is_alphabetic)
+        || dictionary.check(&typed_word)
+    {
+        return None;
+    }
+    let edit_diff = pos - word_start;
+
+    if handle.is_canceled() {
+        return None;
+    }
+
+    let future = move || {
+        let items = dictionary
+            .suggest(&typed_word, MAX_SUGGESTIONS)
+            .into_iter()
+            .map(|suggestion| {
+                let transaction = Transaction::change_by_selection(&rope, &selection, |range| {
+                    let cursor = range.cursor(rope.slice(..));
+                    (cursor - edit_diff, cursor, Some(suggestion.as_str().into()))
+                });
+                CompletionItem::Other(core::CompletionItem {
+                    transaction,
+                    label: suggestion.into(),
+                    // suggestions are corrections of the typed word and usually don't match it
+                    filter_text: Some(typed_word.clone().into()),
+                    kind: Cow::Borrowed(COMPLETION_KIND),
+                    documentation: None,
+                    provider: CompletionProvider::Spelling,
+                })
+            })
+            .collect();
+
+        CompletionResponse {
+            items: CompletionItems::Other(items),
+            provider: CompletionProvider::Spelling,
+            context: ResponseContext {
+                is_incomplete: false,
+                priority: 0,
+                savepoint,
+            },
+        }
+    };
+
+    Some(future)
+}
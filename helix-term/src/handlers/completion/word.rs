@@ -1,17 +1,41 @@
-use std::{borrow::Cow, sync::Arc};
+use std::{borrow::Cow, collections::HashSet, sync::Arc};
 
 use helix_core::{
-    self as core, chars::char_is_word, completion::CompletionProvider, movement, Transaction,
+    self as core, chars::char_is_word, completion::CompletionProvider, movement,
+    syntax::config::LanguageServerFeature, Transaction,
 };
 use helix_event::TaskHandle;
 use helix_stdx::rope::RopeSliceExt as _;
 use helix_view::{
-    document::SavePoint, handlers::completion::ResponseContext, Document, Editor, ViewId,
+    document::SavePoint,
+    editor::WordCompletion,
+    handlers::{completion::ResponseContext, word_index::WordSource},
+    Document, Editor, ViewId,
 };
 
 use super::{request::TriggerKind, CompletionItem, CompletionItems, CompletionResponse, Trigger};
 
-const COMPLETION_KIND: &str = "word";
+/// The kind shown in the completion menu, which tells where the word was found.
+fn completion_kind(source: WordSource) -> &'static str {
+    match source {
+        WordSource::Buffer => "word",
+        WordSource::Dictionary => "dictionary",
+        WordSource::Tags => "tag",
+    }
+}
+
+/// Whether words aren't completed because the `fallback` option leaves the completion to the
+/// language servers of the document.
+fn left_to_language_servers(
+    config: &WordCompletion,
+    doc_config: Option<core::syntax::config::WordCompletion>,
+    has_completion_server: bool,
+) -> bool {
+    let fallback = doc_config
+        .and_then(|c| c.fallback)
+        .unwrap_or(config.fallback);
+    fallback && has_completion_server
+}
 
 pub(super) fn completion(
     editor: &Editor,
@@ -22,7 +46,8 @@ pub(super) fn completion(
     if !doc!(editor).word_completion_enabled() {
         return None;
     }
-    let config = editor.config().word_completion;
+    let config = editor.config();
+    let config = &config.word_completion;
     let doc_config = doc!(editor)
         .language_config()
         .and_then(|config| config.word_completion);
@@ -30,6 +55,13 @@ pub(super) fn completion(
         .and_then(|c| c.trigger_length)
         .unwrap_or(config.trigger_length)
         .get() as usize;
+    let has_completion_server = doc!(editor)
+        .language_servers_with_feature(LanguageServerFeature::Completion)
+        .next()
+        .is_some();
+    if left_to_language_servers(config, doc_config, has_completion_server) {
+        return None;
+    }
 
     let (view, doc) = current_ref!(editor);
     let rope = doc.text().clone();
@@ -75,8 +107,8 @@ pub(super) fn completion(
         let items = word_index
             .matches(&typed_word)
             .into_iter()
-            .filter(|word| word.as_str() != typed_word.as_ref())
-            .map(|word| {
+            .filter(|(word, _)| word.as_str() != typed_word.as_ref())
+            .map(|(word, source)| {
                 let transaction = Transaction::change_by_selection(&rope, &selection, |range| {
                     let cursor = range.cursor(text);
                     (cursor - edit_diff, cursor, Some((&word).into()))
@@ -84,7 +116,8 @@ pub(super) fn completion(
                 CompletionItem::Other(core::CompletionItem {
                     transaction,
                     label: word.into(),
-                    kind: Cow::Borrowed(COMPLETION_KIND),
+                    filter_text: None,
+                    kind: Cow::Borrowed(completion_kind(source)),
                     documentation: None,
                     provider: CompletionProvider::Word,
                 })
@@ -132,3 +165,110 @@ pub(super) fn retain_valid_completions(
         });
     }
 }
+
+/// Removes the words that are also completed by another provider, like a language server, so
+/// that the words only add what the other providers don't know about.
+pub fn remove_duplicate_words(items: &mut Vec<CompletionItem>) {
+    let is_word = |item: &CompletionItem| item.provider() == CompletionProvider::Word;
+    let other_words: HashSet<String> = items
+        .iter()
+        .filter(|item| !is_word(item))
+        .flat_map(|item| {
+            let label = match item {
+                CompletionItem::Lsp(item) => item.item.label.as_str(),
+                CompletionItem::Other(item) => item.label.as_ref(),
+            };
+            [label.to_string(), item.filter_text().to_string()]
+        })
+        .collect();
+    if other_words.is_empty() {
+        return;
+    }
+    items.retain(|item| match item {
+        CompletionItem::Other(word) if is_word(item) => !other_words.contains(word.label.as_ref()),
+        _ => true,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use helix_core::Rope;
+    use helix_lsp::{lsp, LanguageServerId};
+
+    use super::*;
+    use crate::handlers::completion::LspCompletionItem;
+
+    fn word(label: &str) -> CompletionItem {
+        CompletionItem::Other(core::CompletionItem {
+            transaction: Transaction::new(&Rope::new()),
+            label: label.to_string().into(),
+            filter_text: None,
+            kind: Cow::Borrowed("word"),
+            documentation: None,
+            provider: CompletionProvider::Word,
+        })
+    }
+
+    fn lsp_item(label: &str, filter_text: Option<&str>) -> CompletionItem {
+        CompletionItem::Lsp(LspCompletionItem {
+            item: lsp::CompletionItem {
+                label: label.to_string(),
+                filter_text: filter_text.map(ToString::to_string),
+                ..Default::default()
+            },
+            provider: LanguageServerId::default(),
+            resolved: false,
+            provider_priority: 0,
+        })
+    }
+
+    fn labels(items: &[CompletionItem]) -> Vec<&str> {
+        items
+            .iter()
+            .map(|item| match item {
+                CompletionItem::Lsp(item) => item.item.label.as_str(),
+                CompletionItem::Other(item) => item.label.as_ref(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn words_duplicating_other_completions_are_removed() {
+        let mut items = vec![
+            word("println"),
+            word("format"),
+            word("vector"),
+            lsp_item("println!(…)", Some("println")),
+            lsp_item("format", None),
+        ];
+        remove_duplicate_words(&mut items);
+        assert_eq!(labels(&items), ["vector", "println!(…)", "format"]);
+
+        // words are kept if there are no other completions
+        let mut items = vec![word("println"), word("format")];
+        remove_duplicate_words(&mut items);
+        assert_eq!(labels(&items), ["println", "format"]);
+    }
+
+    #[test]
+    fn fallback_leaves_the_completion_to_language_servers() {
+        let config = WordCompletion {
+            fallback: true,
+            ..Default::default()
+        };
+        assert!(left_to_language_servers(&config, None, true));
+        assert!(!left_to_language_servers(&config, None, false));
+        assert!(!left_to_language_servers(
+            &WordCompletion::default(),
+            None,
+            true
+        ));
+
+        // the option of the language takes precedence
+        let doc_config = core::syntax::config::WordCompletion {
+            fallback: Some(false),
+            ..Default::default()
+        };
+        assert!(!left_to_language_servers(&config, Some(doc_config), true));
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use futures_util::{stream::FuturesUnordered, StreamExt};
+use helix_core::snippets::Snippet;
+use helix_core::syntax::config::LanguageServerFeature;
+use helix_event::{cancelable_future, register_hook, send_blocking};
+use helix_lsp::lsp;
+use helix_view::{
+    annotations::inline_completion::{InlineCompletion, InlineCompletionItem},
+    document::Mode,
+    events::{DocumentDidChange, SelectionDidChange},
+    handlers::{lsp::InlineCompletionEvent, Handlers},
+    DocumentId, Editor, ViewId,
+};
+use tokio::time::Instant;
+
+use crate::events::OnModeSwitch;
+use crate::job;
+
+#[derive(Default)]
+pub(super) struct InlineCompletionHandler {
+    trigger: Option<(DocumentId, ViewId)>,
+}
+
+const TYPING_DEBOUNCE: Duration = Duration::from_millis(150);
+
+impl helix_event::AsyncHook for InlineCompletionHandler {
+    type Event = InlineCompletionEvent;
+
+    fn handle_event(&mut self, event: Self::Event, _timeout: Option<Instant>) -> Option<Instant> {
+        self.trigger = Some((event.doc, event.view));
+        Some(Instant::now() + TYPING_DEBOUNCE)
+    }
+
+    fn finish_debounce(&mut self) {
+        let Some((doc, view)) = self.trigger.take() else {
+            return;
+        };
+
+        job::dispatch_blocking(move |editor, _compositor| {
+            if editor.config().lsp.auto_inline_completion {
+                request_inline_completion(
+                    editor,
+                    doc,
+                    view,
+                    lsp::InlineCompletionTriggerKind::Automatic,
+                );
+            }
+        });
+    }
+}
+
+/// Requests inline completions at the primary cursor of `view_id` and shows the
+/// suggestions as ghost text once they arrive.
+pub fn request_inline_completion(
+    editor: &mut Editor,
+    doc_id: DocumentId,
+    view_id: ViewId,
+    trigger_kind: lsp::InlineCompletionTriggerKind,
+) {
+    if editor.mode != Mode::Insert || !editor.tree.contains(view_id) {
+        return;
+    }
+    let Some(doc) = editor.documents.get_mut(&doc_id) else {
+        return;
+    };
+
+    let cancel = doc.inline_completion_controller.restart();
+    let text = doc.text().clone();
+    let cursor = doc.selection(view_id).primary().cursor(text.slice(..));
+
+    let mut seen_language_servers = HashSet::new();
+    let mut futures: FuturesUnordered<_> = doc
+        .language_servers_with_feature(LanguageServerFeature::InlineCompletion)
+        .filter(|ls| seen_language_servers.insert(ls.id()))
+        .filter_map(|language_server| {
+            let text = text.clone();
+            let offset_encoding = language_server.offset_encoding();
+            let position = helix_lsp::util::pos_to_lsp_pos(&text, cursor, offset_encoding);
+            let future = language_server.text_document_inline_completion(
+                doc.identifier(),
+                position,
+                trigger_kind,
+                None,
+            )?;
+
+            Some(async move {
+                let items = match future.await? {
+                    Some(lsp::InlineCompletionResponse::Array(items)) => items,
+                    Some(lsp::InlineCompletionResponse::List(list)) => list.items,
+                    None => Vec::new(),
+                };
+                let items: Vec<_> = items
+                    .into_iter()
+                    .filter_map(|item| {
+                        let range = match item.range {
+                            Some(range) => {
+                                let start = helix_lsp::util::lsp_pos_to_pos(
+                                    &text,
+                                    range.start,
+                                    offset_encoding,
+                                )?;
+                                let end = helix_lsp::util::lsp_pos_to_pos(
+                                    &text,
+                                    range.end,
+                                    offset_encoding,
+                                )?;
+                                start..end
+                            }
+                            None => cursor..cursor,
+                        };
+                        let is_snippet =
+                            item.insert_text_format == Some(lsp::InsertTextFormat::SNIPPET);
+                        Some((range, item.insert_text, is_snippet))
+                    })
+                    .collect();
+                anyhow::Ok(items)
+            })
+        })
+        .collect();
+
+    if futures.is_empty() {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut all_items = Vec::new();
+        loop {
+            match cancelable_future(futures.next(), &cancel).await {
+                Some(Some(Ok(items))) => all_items.extend(items),
+                Some(Some(Err(err))) => log::error!("inline completion request failed: {err}"),
+                Some(None) => break,
+                // The request was cancelled.
+                None => return,
+            }
+        }
+
+        job::dispatch(move |editor, _| {
+            show_inline_completion(editor, doc_id, view_id, cursor, all_items)
+        })
+        .await;
+    });
+}
+
+fn show_inline_completion(
+    editor: &mut Editor,
+    doc_id: DocumentId,
+    view_id: ViewId,
+    cursor: usize,
+    items: Vec<(std::ops::Range<usize>, String, bool)>,
+) {
+    if editor.mode != Mode::Insert {
+        return;
+    }
+    let Some(doc) = editor.documents.get_mut(&doc_id) else {
+        return;
+    };
+    let text = doc.text().slice(..);
+    if doc.selection(view_id).primary().cursor(text) != cursor {
+        return;
+    }
+
+    let items: Vec<_> = items
+        .into_iter()
+        .filter_map(|(range, insert_text, is_snippet)| {
+            let insert_text = if is_snippet {
+                let Ok(snippet) = Snippet::parse(&insert_text) else {
+                    log::error!("Failed to parse snippet: {insert_text:?}");
+                    return None;
+                };
+                let mut rendered = snippet.prepare_render();
+                let (text, _) = snippet.render_at(
+                    &mut rendered,
+                    "".into(),
+                    false,
+                    &mut doc.snippet_ctx(),
+                    range.start,
+                );
+                text.to_string()
+            } else {
+                insert_text
+            };
+            Some(InlineCompletionItem { range, insert_text })
+        })
+        .collect();
+
+    doc.inline_completion = InlineCompletion::new(view_id, text, cursor, items);
+}
+
+pub(super) fn register_hooks(handlers: &Handlers) {
+    let tx = handlers.inline_completions.clone();
+    register_hook!(move |event: &mut DocumentDidChange<'_>| {
+        if event.ghost_transaction {
+            return Ok(());
+        }
+        // Any edit invalidates the ghost text. Accepting a part of a suggestion restores the
+        // remaining text after the edit was applied.
+        event.doc.inline_completion = None;
+        event.doc.inline_completion_controller.cancel();
+        if event.doc.config.load().lsp.auto_inline_completion {
+            send_blocking(
+                &tx,
+                InlineCompletionEvent {
+                    doc: event.doc.id(),
+                    view: event.view,
+                },
+            );
+        }
+        Ok(())
+    });
+
+    register_hook!(move |event: &mut SelectionDidChange<'_>| {
+        let doc = &mut *event.doc;
+        let Some(completion) = &doc.inline_completion else {
+            return Ok(());
+        };
+        let cursor = doc
+            .selection(event.view)
+            .primary()
+            .cursor(doc.text().slice(..));
+        if completion.view_id == event.view && completion.cursor != cursor {
+            doc.inline_completion = None;
+            doc.inline_completion_controller.cancel();
+        }
+        Ok(())
+    });
+
+    let tx = handlers.inline_completions.clone();
+    register_hook!(move |event: &mut OnModeSwitch<'_, '_>| {
+        match (event.old_mode, event.new_mode) {
+            (Mode::Insert, _) => {
+                for doc in event.cx.editor.documents_mut() {
+                    doc.inline_completion = None;
+                    doc.inline_completion_controller.cancel();
+                }
+            }
+            (_, Mode::Insert) if event.cx.editor.config().lsp.auto_inline_completion => {
+                let (view, doc) = current_ref!(event.cx.editor);
+                send_blocking(
+                    &tx,
+                    InlineCompletionEvent {
+                        doc: doc.id(),
+                        view: view.id,
+                    },
+                );
+            }
+            _ => (),
+        }
+        Ok(())
+    });
+}
@@ -0,0 +1,78 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use helix_event::{register_hook, send_blocking};
+use helix_view::events::DocumentDidChange;
+use helix_view::handlers::{Handlers, RecoveryEvent};
+use helix_view::{recovery, DocumentId, Editor};
+use tokio::time::Instant;
+
+use crate::job;
+
+#[derive(Default)]
+pub(super) struct RecoveryHandler {
+    docs: HashSet<DocumentId>,
+}
+
+/// How often the recovery files are written while editing.
+const RECOVERY_INTERVAL: Duration = Duration::from_secs(5);
+
+impl helix_event::AsyncHook for RecoveryHandler {
+    type Event = RecoveryEvent;
+
+    fn handle_event(&mut self, event: Self::Event, timeout: Option<Instant>) -> Option<Instant> {
+        let RecoveryEvent(doc_id) = event;
+        self.docs.insert(doc_id);
+        // unlike a debounce the deadline is kept, so that the recovery files are written
+        // periodically during continuous editing
+        Some(timeout.unwrap_or_else(|| Instant::now() + RECOVERY_INTERVAL))
+    }
+
+    fn finish_debounce(&mut self) {
+        let docs = std::mem::take(&mut self.docs);
+
+        job::dispatch_blocking(move |editor, _compositor| {
+            write_recovery_files(editor, docs);
+        });
+    }
+}
+
+/// Writes the recovery files of the modified documents in the background and removes those
+/// of documents that no longer have unsaved changes.
+fn write_recovery_files(editor: &Editor, docs: HashSet<DocumentId>) {
+    let files: Vec<_> = docs
+        .into_iter()
+        .filter_map(|doc_id| {
+            let doc = editor.documents.get(&doc_id)?;
+            let path = doc.path()?.to_owned();
+            let text = doc.is_modified().then(|| doc.text().clone());
+            Some((path, text))
+        })
+        .collect();
+
+    tokio::task::spawn_blocking(move || {
+        for (path, text) in files {
+            let result = match text {
+                Some(text) => recovery::write(&path, &text),
+                None => recovery::remove(&path),
+            };
+            if let Err(err) = result {
+                log::error!("failed to update the recovery file of {path:?}: {err}");
+            }
+        }
+    });
+}
+
+pub(super) fn register_hooks(handlers: &Handlers) {
+    let tx = handlers.recovery.clone();
+    register_hook!(move |event: &mut DocumentDidChange<'_>| {
+        // integration tests must not write to the state directory
+        if cfg!(not(feature = "integration"))
+            && !event.ghost_transaction
+            && event.doc.config.load().recovery
+        {
+            send_blocking(&tx, RecoveryEvent(event.doc.id()));
+        }
+        Ok(())
+    });
+}
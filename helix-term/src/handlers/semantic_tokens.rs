@@ -0,0 +1,350 @@
+use std::{collections::HashSet, time::Duration};
+
+use futures_util::{future::BoxFuture, FutureExt};
+use helix_core::{syntax::config::LanguageServerFeature, Assoc, Rope};
+use helix_event::{cancelable_future, register_hook};
+use helix_lsp::{
+    lsp,
+    util::{apply_semantic_token_edits, pos_to_lsp_pos, semantic_token_ranges},
+    LanguageServerId, OffsetEncoding,
+};
+use helix_view::{
+    document::DocumentSemanticTokens,
+    events::{
+        ConfigDidChange, DocumentDidChange, DocumentDidOpen, LanguageServerExited,
+        LanguageServerInitialized,
+    },
+    handlers::{lsp::SemanticTokensEvent, Handlers},
+    DocumentId, Editor,
+};
+use tokio::time::Instant;
+
+use crate::job;
+
+#[derive(Default)]
+pub(super) struct SemanticTokensHandler {
+    docs: HashSet<DocumentId>,
+}
+
+const DOCUMENT_CHANGE_DEBOUNCE: Duration = Duration::from_millis(250);
+
+impl helix_event::AsyncHook for SemanticTokensHandler {
+    type Event = SemanticTokensEvent;
+
+    fn handle_event(&mut self, event: Self::Event, _timeout: Option<Instant>) -> Option<Instant> {
+        let SemanticTokensEvent(doc_id) = event;
+        self.docs.insert(doc_id);
+        Some(Instant::now() + DOCUMENT_CHANGE_DEBOUNCE)
+    }
+
+    fn finish_debounce(&mut self) {
+        let docs = std::mem::take(&mut self.docs);
+
+        job::dispatch_blocking(move |editor, _compositor| {
+            for doc in docs {
+                request_semantic_tokens(editor, doc);
+            }
+        });
+    }
+}
+
+enum SemanticTokensResponse {
+    Full(Option<lsp::SemanticTokensResult>),
+    Delta(Option<lsp::SemanticTokensFullDeltaResult>),
+    Range(Option<lsp::SemanticTokensRangeResult>),
+}
+
+/// Requests the semantic tokens of a document. Only the changes since the last response are
+/// requested if the language server supports it, servers that only provide tokens for ranges
+/// are asked for the tokens of the whole document.
+pub fn request_semantic_tokens(editor: &mut Editor, doc_id: DocumentId) {
+    if !editor.config().lsp.display_semantic_tokens {
+        return;
+    }
+    let Some(doc) = editor.document_mut(doc_id) else {
+        return;
+    };
+    let Some(language_server) = doc
+        .language_servers_with_feature(LanguageServerFeature::SemanticTokens)
+        .next()
+    else {
+        doc.semantic_tokens = None;
+        return;
+    };
+
+    let language_server_id = language_server.id();
+    let offset_encoding = language_server.offset_encoding();
+    let Some(legend) = language_server.semantic_tokens_legend().cloned() else {
+        return;
+    };
+    let previous_result_id = doc
+        .semantic_tokens
+        .as_ref()
+        .filter(|tokens| tokens.language_server_id == language_server_id)
+        .and_then(|tokens| tokens.result_id.clone());
+
+    let identifier = doc.identifier();
+    let future: BoxFuture<'static, helix_lsp::Result<SemanticTokensResponse>> =
+        if let Some(future) = previous_result_id.and_then(|result_id| {
+            language_server.text_document_semantic_tokens_full_delta(identifier.clone(), result_id)
+        }) {
+            future
+                .map(|res| res.map(SemanticTokensResponse::Delta))
+                .boxed()
+        } else if let Some(future) =
+            language_server.text_document_semantic_tokens_full(identifier.clone())
+        {
+            future
+                .map(|res| res.map(SemanticTokensResponse::Full))
+                .boxed()
+        } else {
+            let text = doc.text();
+            let end = pos_to_lsp_pos(text, text.len_chars(), offset_encoding);
+            let range = lsp::Range::new(lsp::Position::new(0, 0), end);
+            let Some(future) =
+                language_server.text_document_semantic_tokens_range(identifier, range)
+            else {
+                return;
+            };
+            future
+                .map(|res| res.map(SemanticTokensResponse::Range))
+                .boxed()
+        };
+
+    let version = doc.version();
+    let cancel = doc.semantic_tokens_controller.restart();
+
+    tokio::spawn(async move {
+        let response = match cancelable_future(future, &cancel).await {
+            Some(Ok(response)) => response,
+            Some(Err(err)) => {
+                log::error!("semantic tokens request failed: {err}");
+                return;
+            }
+            None => return,
+        };
+
+        job::dispatch(move |editor, _| {
+            attach_semantic_tokens(
+                editor,
+                doc_id,
+                version,
+                language_server_id,
+                offset_encoding,
+                &legend,
+                response,
+            )
+        })
+        .await;
+    });
+}
+
+fn attach_semantic_tokens(
+    editor: &mut Editor,
+    doc_id: DocumentId,
+    version: i32,
+    language_server_id: LanguageServerId,
+    offset_encoding: OffsetEncoding,
+    legend: &lsp::SemanticTokensLegend,
+    response: SemanticTokensResponse,
+) {
+    if !editor.config().lsp.display_semantic_tokens {
+        return;
+    }
+    let Some(doc) = editor.documents.get_mut(&doc_id) else {
+        return;
+    };
+    // the tokens were computed for an older version of the document, a new request is
+    // already scheduled by the change
+    if doc.version() != version {
+        return;
+    }
+
+    let previous_data = doc
+        .semantic_tokens
+        .take()
+        .filter(|tokens| tokens.language_server_id == language_server_id)
+        .map(|tokens| tokens.data)
+        .unwrap_or_default();
+    let apply_edits = |edits| {
+        let mut data = previous_data;
+        apply_semantic_token_edits(&mut data, edits).then_some(data)
+    };
+    let (result_id, data) = match response {
+        SemanticTokensResponse::Full(None)
+        | SemanticTokensResponse::Delta(None)
+        | SemanticTokensResponse::Range(None) => return,
+        SemanticTokensResponse::Full(Some(lsp::SemanticTokensResult::Tokens(tokens)))
+        | SemanticTokensResponse::Delta(Some(lsp::SemanticTokensFullDeltaResult::Tokens(tokens)))
+        | SemanticTokensResponse::Range(Some(lsp::SemanticTokensRangeResult::Tokens(tokens))) => {
+            (tokens.result_id, Some(tokens.data))
+        }
+        SemanticTokensResponse::Full(Some(lsp::SemanticTokensResult::Partial(tokens)))
+        | SemanticTokensResponse::Range(Some(lsp::SemanticTokensRangeResult::Partial(tokens))) => {
+            (None, Some(tokens.data))
+        }
+        SemanticTokensResponse::Delta(Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(
+            delta,
+        ))) => (delta.result_id, apply_edits(delta.edits)),
+        SemanticTokensResponse::Delta(Some(
+            lsp::SemanticTokensFullDeltaResult::PartialTokensDelta { edits },
+        )) => (None, apply_edits(edits)),
+    };
+    // without the previous tokens the next request asks for all tokens again
+    let Some(data) = data else {
+        log::warn!("discarding semantic tokens delta that does not apply to the tokens");
+        return;
+    };
+
+    let highlights = semantic_token_highlights(doc.text(), &data, offset_encoding, legend);
+    doc.semantic_tokens = Some(DocumentSemanticTokens {
+        language_server_id,
+        result_id,
+        data,
+        highlights,
+    });
+}
+
+fn semantic_token_highlights(
+    text: &Rope,
+    data: &[lsp::SemanticToken],
+    offset_encoding: OffsetEncoding,
+    legend: &lsp::SemanticTokensLegend,
+) -> Vec<(std::ops::Range<usize>, &'static str)> {
+    let mut highlights: Vec<_> = semantic_token_ranges(text, data, offset_encoding)
+        .filter(|(range, _, _)| range.start < range.end)
+        .filter_map(|(range, token_type, modifiers)| {
+            Some((range, token_scope(legend, token_type, modifiers)?))
+        })
+        .collect();
+    highlights.sort_by_key(|(range, _)| range.start);
+    let mut end = 0;
+    highlights.retain(|(range, _)| {
+        let keep = range.start >= end;
+        if keep {
+            end = range.end;
+        }
+        keep
+    });
+    highlights
+}
+
+/// Returns the theme scope a semantic token is highlighted with. Tokens without a
+/// corresponding scope keep their syntax highlighting.
+fn token_scope(
+    legend: &lsp::SemanticTokensLegend,
+    token_type: u32,
+    modifiers: u32,
+) -> Option<&'static str> {
+    let token_type = legend.token_types.get(token_type as usize)?;
+    let has_modifier = |modifier: &lsp::SemanticTokenModifier| {
+        legend
+            .token_modifiers
+            .iter()
+            .position(|legend_modifier| legend_modifier == modifier)
+            .is_some_and(|bit| bit < 32 && modifiers & (1 << bit) != 0)
+    };
+    let builtin = has_modifier(&lsp::SemanticTokenModifier::DEFAULT_LIBRARY);
+
+    let scope = match token_type.as_str() {
+        "namespace" => "namespace",
+        "type" | "class" | "struct" | "interface" if builtin => "type.builtin",
+        "type" | "class" | "struct" | "interface" => "type",
+        "enum" => "type.enum",
+        "typeParameter" => "type.parameter",
+        "enumMember" => "type.enum.variant",
+        "parameter" => "variable.parameter",
+        "variable" if builtin => "variable.builtin",
+        "variable" if has_modifier(&lsp::SemanticTokenModifier::READONLY) => "constant",
+        "variable" => "variable",
+        "property" => "variable.other.member",
+        "function" | "method" if builtin => "function.builtin",
+        "function" => "function",
+        "method" => "function.method",
+        "macro" => "function.macro",
+        "keyword" => "keyword",
+        "modifier" => "keyword.storage.modifier",
+        "comment" => "comment",
+        "string" => "string",
+        "number" => "constant.numeric",
+        "regexp" => "string.regexp",
+        "operator" => "operator",
+        "decorator" => "attribute",
+        "label" => "label",
+        _ => return None,
+    };
+    Some(scope)
+}
+
+fn clear_semantic_tokens(editor: &mut Editor) {
+    for doc in editor.documents_mut() {
+        doc.semantic_tokens_controller.cancel();
+        doc.semantic_tokens = None;
+    }
+}
+
+fn request_all_semantic_tokens(editor: &mut Editor) {
+    let doc_ids: Vec<_> = editor.documents().map(|doc| doc.id()).collect();
+    for doc_id in doc_ids {
+        request_semantic_tokens(editor, doc_id);
+    }
+}
+
+pub(super) fn register_hooks(handlers: &Handlers) {
+    register_hook!(move |event: &mut DocumentDidOpen<'_>| {
+        request_semantic_tokens(event.editor, event.doc);
+        Ok(())
+    });
+
+    let tx = handlers.semantic_tokens.clone();
+    register_hook!(move |event: &mut DocumentDidChange<'_>| {
+        if let Some(tokens) = &mut event.doc.semantic_tokens {
+            event
+                .changes
+                .update_positions(tokens.highlights.iter_mut().flat_map(|(range, _)| {
+                    [
+                        (&mut range.start, Assoc::After),
+                        (&mut range.end, Assoc::After),
+                    ]
+                }));
+        }
+
+        if !event.ghost_transaction && event.doc.config.load().lsp.display_semantic_tokens {
+            event.doc.semantic_tokens_controller.cancel();
+            helix_event::send_blocking(&tx, SemanticTokensEvent(event.doc.id()));
+        }
+
+        Ok(())
+    });
+
+    register_hook!(move |event: &mut LanguageServerInitialized<'_>| {
+        request_all_semantic_tokens(event.editor);
+        Ok(())
+    });
+
+    register_hook!(move |event: &mut LanguageServerExited<'_>| {
+        for doc in event.editor.documents_mut() {
+            if doc
+                .semantic_tokens
+                .as_ref()
+                .is_some_and(|tokens| tokens.language_server_id == event.server_id)
+            {
+                doc.semantic_tokens = None;
+            }
+        }
+        request_all_semantic_tokens(event.editor);
+        Ok(())
+    });
+
+    register_hook!(move |event: &mut ConfigDidChange<'_>| {
+        match (
+            event.old.lsp.display_semantic_tokens,
+            event.new.lsp.display_semantic_tokens,
+        ) {
+            (false, true) => request_all_semantic_tokens(event.editor),
+            (true, false) => clear_semantic_tokens(event.editor),
+            _ => (),
+        }
+        Ok(())
+    });
+}
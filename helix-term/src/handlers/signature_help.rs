@@ -137,16 +137,13 @@ pub fn request_signature_help(
     });
 }
 
-fn active_param_range(
-    signature: &SignatureInformation,
-    response_active_parameter: Option<u32>,
-) -> Option<(usize, usize)> {
-    let param_idx = signature
-        .active_parameter
-        .or(response_active_parameter)
-        .unwrap_or(0) as usize;
-    let param = signature.parameters.as_ref()?.get(param_idx)?;
-    match &param.label {
+/// The byte ranges of the parameters in the label of the signature. The range is empty if the
+/// parameter can't be found in the label.
+fn param_ranges(signature: &SignatureInformation) -> Vec<(usize, usize)> {
+    let Some(params) = signature.parameters.as_ref() else {
+        return Vec::new();
+    };
+    let param_range = |param: &lsp::ParameterInformation| match &param.label {
         lsp::ParameterLabel::Simple(string) => {
             let start = signature.label.find(string.as_str())?;
             Some((start, start + string.len()))
@@ -159,7 +156,11 @@ fn active_param_range(
             let to = char_to_byte_idx(&signature.label, *end as usize);
             Some((from, to))
         }
-    }
+    };
+    params
+        .iter()
+        .map(|param| param_range(param).unwrap_or_default())
+        .collect()
 }
 
 pub fn show_signature_help(
@@ -216,7 +217,13 @@ pub fn show_signature_help(
         .signatures
         .into_iter()
         .map(|s| {
-            let active_param_range = active_param_range(&s, response.active_parameter);
+            let parameters = param_ranges(&s);
+            let active_parameter = s
+                .active_parameter
+                .or(response.active_parameter)
+                .map(|param| param as usize)
+                .or((!parameters.is_empty()).then_some(0))
+                .filter(|&param| param < parameters.len());
 
             let signature_doc = if config.lsp.display_signature_help_docs {
                 s.documentation.map(|doc| match doc {
@@ -230,7 +237,8 @@ pub fn show_signature_help(
             Signature {
                 signature: s.label,
                 signature_doc,
-                active_param_range,
+                parameters,
+                active_parameter,
             }
         })
         .collect();
@@ -365,3 +373,34 @@ pub(super) fn register_hooks(handlers: &Handlers) {
         Ok(())
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn param_ranges_of_all_parameters() {
+        let param = |label| lsp::ParameterInformation {
+            label,
+            documentation: None,
+        };
+        let signature = SignatureInformation {
+            label: "fn f(a: u8, bb: u8, c: u8)".into(),
+            documentation: None,
+            parameters: Some(vec![
+                param(lsp::ParameterLabel::Simple("a: u8".into())),
+                param(lsp::ParameterLabel::LabelOffsets([12, 18])),
+                param(lsp::ParameterLabel::Simple("d: u8".into())),
+            ]),
+            active_parameter: None,
+        };
+        // parameters that aren't part of the label get an empty range
+        assert_eq!(param_ranges(&signature), [(5, 10), (12, 18), (0, 0)]);
+
+        let signature = SignatureInformation {
+            parameters: None,
+            ..signature
+        };
+        assert!(param_ranges(&signature).is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use helix_core::chars::char_is_word;
+use helix_core::diagnostic::{DiagnosticProvider, Range, Severity};
+use helix_core::spelling::{self, Dictionary};
+use helix_core::Diagnostic;
+use helix_event::{register_hook, send_blocking};
+use helix_view::events::{
+    ConfigDidChange, DiagnosticsDidChange, DocumentDidChange, DocumentDidOpen,
+};
+use helix_view::handlers::{Handlers, SpellCheckEvent};
+use helix_view::{DocumentId, Editor};
+use tokio::time::Instant;
+
+use crate::job;
+
+#[derive(Default)]
+pub(super) struct SpellCheckHandler {
+    docs: HashSet<DocumentId>,
+}
+
+const DOCUMENT_CHANGE_DEBOUNCE: Duration = Duration::from_millis(500);
+
+impl helix_event::AsyncHook for SpellCheckHandler {
+    type Event = SpellCheckEvent;
+
+    fn handle_event(&mut self, event: Self::Event, _timeout: Option<Instant>) -> Option<Instant> {
+        let SpellCheckEvent(doc_id) = event;
+        self.docs.insert(doc_id);
+        Some(Instant::now() + DOCUMENT_CHANGE_DEBOUNCE)
+    }
+
+    fn finish_debounce(&mut self) {
+        let docs = std::mem::take(&mut self.docs);
+
+        job::dispatch_blocking(move |editor, _compositor| {
+            for doc in docs {
+                spell_check(editor, doc);
+            }
+        });
+    }
+}
+
+/// Loads the dictionary of the spell check language in the background, then checks all
+/// documents with it.
+fn load_dictionary(editor: &mut Editor) {
+    let Some(language) = editor.load_spell_dictionary() else {
+        return;
+    };
+    tokio::spawn(async move {
+        let Ok((language, dictionary)) = tokio::task::spawn_blocking(move || {
+            let dictionary = Dictionary::load(&language).unwrap_or_else(|err| {
+                log::error!("Failed to load the spell check dictionary '{language}': {err}");
+                Dictionary::default()
+            });
+            (language, dictionary)
+        })
+        .await
+        else {
+            return;
+        };
+
+        job::dispatch(move |editor, _| {
+            editor.set_spell_dictionary(language, dictionary);
+            let doc_ids: Vec<_> = editor.documents().map(|doc| doc.id()).collect();
+            for doc_id in doc_ids {
+                spell_check(editor, doc_id);
+            }
+        })
+        .await;
+    });
+}
+
+/// Checks the spelling of the comments, strings and markup of a document in the background
+/// and replaces its spelling diagnostics with the result. The document is checked once the
+/// dictionary is loaded if it isn't yet.
+pub fn spell_check(editor: &mut Editor, doc_id: DocumentId) {
+    let Some(dictionary) = editor.spell_dictionary() else {
+        load_dictionary(editor);
+        return;
+    };
+    let loader = editor.syn_loader.load_full();
+    let Some(doc) = editor.documents.get(&doc_id) else {
+        return;
+    };
+    let text = doc.text().clone();
+    let version = doc.version();
+    let syntax = doc.syntax().cloned();
+
+    tokio::spawn(async move {
+        let Ok((text, words)) = tokio::task::spawn_blocking(move || {
+            // finding the checked ranges runs the highlighter over the whole document
+            let ranges = spelling::checked_ranges(syntax.as_ref(), text.slice(..), &loader);
+            let words = dictionary.misspelled_words(text.slice(..), &ranges);
+            (text, words)
+        })
+        .await
+        else {
+            return;
+        };
+
+        job::dispatch(move |editor, _| {
+            let Some(doc) = editor.documents.get_mut(&doc_id) else {
+                return;
+            };
+            if doc.version() != version {
+                return;
+            }
+            let text = text.slice(..);
+            let diagnostics = words.into_iter().map(|(range, word)| Diagnostic {
+                range: Range {
+                    start: range.start,
+                    end: range.end,
+                },
+                ends_at_word: text.get_char(range.end - 1).is_some_and(char_is_word),
+                starts_at_word: text.get_char(range.start).is_some_and(char_is_word),
+                zero_width: false,
+                line: text.char_to_line(range.start),
+                message: format!("Unknown word: {word}"),
+                severity: Some(Severity::Info),
+                code: None,
+                provider: DiagnosticProvider::Spelling,
+                tags: Vec::new(),
+                source: Some("spelling".to_string()),
+                data: None,
+            });
+            doc.replace_diagnostics(diagnostics, &[], Some(&DiagnosticProvider::Spelling));
+            helix_event::dispatch(DiagnosticsDidChange {
+                editor,
+                doc: doc_id,
+            });
+        })
+        .await;
+    });
+}
+
+/// Removes the spelling diagnostics of all documents.
+fn clear_spelling_diagnostics(editor: &mut Editor) {
+    for doc in editor.documents_mut() {
+        doc.replace_diagnostics([], &[], Some(&DiagnosticProvider::Spelling));
+    }
+}
+
+pub(super) fn register_hooks(handlers: &Handlers) {
+    let tx = handlers.spell_check.clone();
+    register_hook!(move |event: &mut DocumentDidOpen<'_>| {
+        if event.editor.config().spell_check.enable {
+            send_blocking(&tx, SpellCheckEvent(event.doc));
+        }
+        Ok(())
+    });
+
+    let tx = handlers.spell_check.clone();
+    register_hook!(move |event: &mut DocumentDidChange<'_>| {
+        if !event.ghost_transaction && event.doc.config.load().spell_check.enable {
+            send_blocking(&tx, SpellCheckEvent(event.doc.id()));
+        }
+        Ok(())
+    });
+
+    let tx = handlers.spell_check.clone();
+    register_hook!(move |event: &mut ConfigDidChange<'_>| {
+        if event.old.spell_check == event.new.spell_check {
+            return Ok(());
+        }
+        clear_spelling_diagnostics(event.editor);
+        if event.new.spell_check.enable {
+            for doc in event.editor.documents() {
+                send_blocking(&tx, SpellCheckEvent(doc.id()));
+            }
+        }
+        Ok(())
+    });
+}
@@ -0,0 +1,112 @@
+//! Runs the commands of the user-defined statusline segments in the background and stores their
+//! output in [Editor::statusline_segments](helix_view::Editor::statusline_segments).
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use arc_swap::ArcSwap;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use crate::commands::shell_impl_async;
+use crate::config::Config;
+use crate::job;
+
+/// How often the intervals of the segments are checked.
+const TICK: Duration = Duration::from_secs(1);
+
+/// The last run of the command of a segment.
+struct Run {
+    start: Instant,
+    task: JoinHandle<()>,
+}
+
+/// Whether the command of a segment should run again: it never ran, or its last run finished and
+/// started at least `interval` ago. A command that takes longer than its interval is not started
+/// again before it finished.
+fn is_due(last_run: Option<&Run>, interval: Duration, now: Instant) -> bool {
+    last_run.is_none_or(|run| run.task.is_finished() && now.duration_since(run.start) >= interval)
+}
+
+pub(super) fn spawn(config: Arc<ArcSwap<Config>>) {
+    tokio::spawn(async move {
+        let mut last_runs: HashMap<String, Run> = HashMap::new();
+        let mut ticks = tokio::time::interval(TICK);
+        loop {
+            ticks.tick().await;
+            let config = config.load();
+            let now = Instant::now();
+            for (name, segment) in &config.editor.statusline.segments {
+                let Some(command) = &segment.command else {
+                    continue;
+                };
+                let interval = Duration::from_secs(segment.interval);
+                if !is_due(last_runs.get(name), interval, now) {
+                    continue;
+                }
+                let task = tokio::spawn(run_segment(
+                    name.clone(),
+                    config.editor.shell.clone(),
+                    command.clone(),
+                ));
+                last_runs.insert(name.clone(), Run { start: now, task });
+            }
+        }
+    });
+}
+
+async fn run_segment(name: String, shell: Vec<String>, command: String) {
+    let output = match shell_impl_async(&shell, &command, None).await {
+        Ok(output) => output.lines().next().unwrap_or_default().trim().to_string(),
+        Err(err) => {
+            log::warn!("statusline segment '{name}' failed: {err}");
+            String::new()
+        }
+    };
+    job::dispatch(move |editor, _| {
+        if editor.statusline_segments.get(&name) != Some(&output) {
+            editor.statusline_segments.insert(name, output);
+            helix_event::request_redraw();
+        }
+    })
+    .await;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn segments_run_after_their_interval_and_last_run() {
+        let interval = Duration::from_secs(5);
+        let start = Instant::now();
+        assert!(is_due(None, interval, start));
+
+        let (finish, finished) = tokio::sync::oneshot::channel::<()>();
+        let run = Run {
+            start,
+            task: tokio::spawn(async move {
+                let _ = finished.await;
+            }),
+        };
+        assert!(!is_due(
+            Some(&run),
+            interval,
+            start + Duration::from_secs(1)
+        ));
+        // a run that is still in flight blocks the next one
+        assert!(!is_due(Some(&run), interval, start + interval));
+
+        finish.send(()).unwrap();
+        while !run.task.is_finished() {
+            tokio::task::yield_now().await;
+        }
+        assert!(!is_due(
+            Some(&run),
+            interval,
+            start + Duration::from_secs(1)
+        ));
+        assert!(is_due(Some(&run), interval, start + interval));
+    }
+}
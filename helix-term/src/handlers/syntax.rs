@@ -0,0 +1,109 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use helix_event::{register_hook, send_blocking};
+use helix_view::events::DocumentDidChange;
+use helix_view::handlers::{Handlers, SyntaxEvent};
+use helix_view::{DocumentId, Editor};
+use tokio::time::Instant;
+
+use crate::job;
+
+/// Updates the syntax trees of documents that are too slow to parse on each edit in the
+/// background, see [helix_view::Document::start_syntax_update].
+#[derive(Default)]
+pub(super) struct SyntaxHandler {
+    docs: HashSet<DocumentId>,
+}
+
+/// Collects the edits of a burst of keys, like a paste or a replayed macro, into a single parse.
+const SYNTAX_UPDATE_DEBOUNCE: Duration = Duration::from_millis(10);
+
+impl helix_event::AsyncHook for SyntaxHandler {
+    type Event = SyntaxEvent;
+
+    fn handle_event(&mut self, event: Self::Event, timeout: Option<Instant>) -> Option<Instant> {
+        let SyntaxEvent(doc_id) = event;
+        self.docs.insert(doc_id);
+        Some(timeout.unwrap_or_else(|| Instant::now() + SYNTAX_UPDATE_DEBOUNCE))
+    }
+
+    fn finish_debounce(&mut self) {
+        let mut docs: Vec<_> = self.docs.drain().collect();
+
+        job::dispatch_blocking(move |editor, _compositor| {
+            sort_by_visibility(editor, &mut docs);
+            for doc_id in docs {
+                update_syntax(editor, doc_id);
+            }
+        });
+    }
+}
+
+/// Sorts the document in the focused view first, followed by the other visible documents, so
+/// that the viewport is highlighted with an up to date tree as soon as possible.
+fn sort_by_visibility(editor: &Editor, docs: &mut [DocumentId]) {
+    let focused = editor.tree.get(editor.tree.focus).doc;
+    docs.sort_by_key(|&doc_id| {
+        if doc_id == focused {
+            0
+        } else if editor.tree.views().any(|(view, _)| view.doc == doc_id) {
+            1
+        } else {
+            2
+        }
+    });
+}
+
+fn update_syntax(editor: &mut Editor, doc_id: DocumentId) {
+    let Some(doc) = editor.documents.get_mut(&doc_id) else {
+        return;
+    };
+    let Some((mut syntax, source, text, changes)) = doc.start_syntax_update() else {
+        return;
+    };
+    let loader = editor.syn_loader.load_full();
+
+    tokio::spawn(async move {
+        let parse = tokio::task::spawn_blocking(move || {
+            let start = Instant::now();
+            syntax
+                .update(source.slice(..), text.slice(..), &changes, &loader)
+                .map(|_| (syntax, text, start.elapsed()))
+        });
+        let result = match parse.await {
+            Ok(result) => result.map_err(|err| err.to_string()),
+            Err(err) => Err(err.to_string()),
+        };
+
+        job::dispatch(move |editor, _compositor| {
+            let Some(doc) = editor.documents.get_mut(&doc_id) else {
+                return;
+            };
+            let outdated = match result {
+                Ok((syntax, text, parse_time)) => {
+                    doc.finish_syntax_update(Some(syntax), text, parse_time)
+                }
+                Err(err) => {
+                    log::error!("TS parser failed, disabling TS for the current buffer: {err}");
+                    doc.finish_syntax_update(None, doc.text().clone(), Duration::ZERO)
+                }
+            };
+            // the document changed during the parse
+            if outdated {
+                update_syntax(editor, doc_id);
+            }
+        })
+        .await;
+    });
+}
+
+pub(super) fn register_hooks(handlers: &Handlers) {
+    let tx = handlers.syntax.clone();
+    register_hook!(move |event: &mut DocumentDidChange<'_>| {
+        if event.doc.syntax_outdated() {
+            send_blocking(&tx, SyntaxEvent(event.doc.id()));
+        }
+        Ok(())
+    });
+}
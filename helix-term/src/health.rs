@@ -17,6 +17,7 @@ pub enum TsFeature {
     AutoIndent,
     Tags,
     RainbowBracket,
+    Context,
 }
 
 impl TsFeature {
@@ -27,6 +28,7 @@ pub fn all() -> &'static [Self] {
             Self::AutoIndent,
             Self::Tags,
             Self::RainbowBracket,
+            Self::Context,
         ]
     }
 
@@ -37,6 +39,7 @@ pub fn runtime_filename(&self) -> &'static str {
             Self::AutoIndent => "indents.scm",
             Self::Tags => "tags.scm",
             Self::RainbowBracket => "rainbows.scm",
+            Self::Context => "context.scm",
         }
     }
 
@@ -47,6 +50,7 @@ pub fn long_title(&self) -> &'static str {
             Self::AutoIndent => "Auto Indent",
             Self::Tags => "Code Navigation Tags",
             Self::RainbowBracket => "Rainbow Brackets",
+            Self::Context => "Sticky Context",
         }
     }
 
@@ -57,6 +61,7 @@ pub fn short_title(&self) -> &'static str {
             Self::AutoIndent => "Indent",
             Self::Tags => "Tags",
             Self::RainbowBracket => "Rainbow",
+            Self::Context => "Context",
         }
     }
 }
@@ -82,6 +87,11 @@ pub fn general() -> std::io::Result<()> {
         writeln!(stdout, "Language file: default")?;
     }
     writeln!(stdout, "Log file: {}", log_file.display())?;
+    writeln!(
+        stdout,
+        "Session directory: {}",
+        helix_loader::session_dir().display()
+    )?;
     writeln!(
         stdout,
         "Runtime directories: {}",
@@ -247,7 +257,7 @@ fn languages(selection: Option<HashSet<String>>) -> std::io::Result<()> {
 
         let formatter = lang
             .formatter
-            .as_ref()
+            .first()
             .map(|formatter| formatter.command.as_str());
         write!(stdout, "{}", check_binary(formatter))?;
 
@@ -342,11 +352,11 @@ pub fn language(lang_str: String) -> std::io::Result<()> {
         lang.debugger.as_ref().map(|dap| dap.command.to_string()),
     )?;
 
-    probe_protocol(
+    probe_protocols(
         "formatter",
         lang.formatter
-            .as_ref()
-            .map(|formatter| formatter.command.to_string()),
+            .iter()
+            .map(|formatter| (formatter.command.as_str(), formatter.command.as_str())),
     )?;
 
     probe_parser(lang.grammar.as_ref().unwrap_or(&lang.language_id))?;
@@ -3,7 +3,7 @@
 use helix_view::Editor;
 use once_cell::sync::OnceCell;
 
-use crate::compositor::Compositor;
+use crate::compositor::{self, Compositor};
 
 use futures_util::future::{BoxFuture, Future, FutureExt};
 use futures_util::stream::{FuturesUnordered, StreamExt};
@@ -11,6 +11,7 @@
 
 pub type EditorCompositorCallback = Box<dyn FnOnce(&mut Editor, &mut Compositor) + Send>;
 pub type EditorCallback = Box<dyn FnOnce(&mut Editor) + Send>;
+pub type CompositorCallback = Box<dyn FnOnce(&mut Compositor, &mut compositor::Context) + Send>;
 
 runtime_local! {
     static JOB_QUEUE: OnceCell<Sender<Callback>> = OnceCell::new();
@@ -35,6 +36,8 @@ pub fn dispatch_blocking(job: impl FnOnce(&mut Editor, &mut Compositor) + Send +
 pub enum Callback {
     EditorCompositor(EditorCompositorCallback),
     Editor(EditorCallback),
+    /// A callback with a context that can start further jobs, for example to run commands.
+    Compositor(CompositorCallback),
 }
 
 pub type JobFuture = BoxFuture<'static, anyhow::Result<Option<Callback>>>;
@@ -100,7 +103,7 @@ pub fn callback<F: Future<Output = anyhow::Result<Callback>> + Send + 'static>(
     }
 
     pub fn handle_callback(
-        &self,
+        &mut self,
         editor: &mut Editor,
         compositor: &mut Compositor,
         call: anyhow::Result<Option<Callback>>,
@@ -110,6 +113,14 @@ pub fn handle_callback(
             Ok(Some(call)) => match call {
                 Callback::EditorCompositor(call) => call(editor, compositor),
                 Callback::Editor(call) => call(editor),
+                Callback::Compositor(call) => call(
+                    compositor,
+                    &mut compositor::Context {
+                        editor,
+                        scroll: None,
+                        jobs: self,
+                    },
+                ),
             },
             Err(e) => {
                 editor.set_error(format!("Async job failed: {}", e));
@@ -153,6 +164,14 @@ pub async fn finish(
                                 call(editor, compositor.as_deref_mut().unwrap())
                             }
                             Callback::Editor(call) => call(editor),
+                            Callback::Compositor(call) if compositor.is_some() => call(
+                                compositor.as_deref_mut().unwrap(),
+                                &mut compositor::Context {
+                                    editor,
+                                    scroll: None,
+                                    jobs: self,
+                                },
+                            ),
 
                             // skip callbacks for which we don't have the necessary references
                             _ => (),
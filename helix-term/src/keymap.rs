@@ -288,6 +288,11 @@ pub fn pending(&self) -> &[KeyEvent] {
         &self.state
     }
 
+    /// Cancels the pending keys and returns them.
+    pub fn take_pending(&mut self) -> Vec<KeyEvent> {
+        std::mem::take(&mut self.state)
+    }
+
     pub fn sticky(&self) -> Option<&KeyTrieNode> {
         self.sticky.as_ref()
     }
@@ -392,6 +397,23 @@ fn duplicate_keys_should_panic() {
         });
     }
 
+    #[test]
+    fn take_pending_keys() {
+        let keymap = keymap!({ "Insert mode"
+            "j" => { "Chord"
+                "k" => normal_mode,
+            },
+        });
+        let mut keymaps = Keymaps::new(Box::new(Constant(hashmap!(Mode::Insert => keymap))));
+        assert!(matches!(
+            keymaps.get(Mode::Insert, key!('j')),
+            KeymapResult::Pending(_)
+        ));
+        assert_eq!(keymaps.pending(), &[key!('j')]);
+        assert_eq!(keymaps.take_pending(), vec![key!('j')]);
+        assert!(keymaps.pending().is_empty());
+    }
+
     #[test]
     fn check_duplicate_keys_in_default_keymap() {
         // will panic on duplicate keys, assumes that `Keymaps` uses keymap! macro
@@ -18,13 +18,15 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
         "r" => replace,
         "R" => replace_with_yanked,
         "A-." =>  repeat_last_motion,
+        "M" => set_mark,
+        "'" => goto_mark,
 
         "~" => switch_case,
         "`" => switch_to_lowercase,
         "A-`" => switch_to_uppercase,
 
-        "home" => goto_line_start,
-        "end" => goto_line_end,
+        "home" => goto_visual_line_start,
+        "end" => goto_visual_line_end,
 
         "w" => move_next_word_start,
         "b" => move_prev_word_start,
@@ -35,6 +37,7 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
         "E" => move_next_long_word_end,
 
         "v" => select_mode,
+        "C-v" => select_block,
         "G" => goto_line,
         "g" => { "Goto"
             "g" => goto_file_start,
@@ -111,8 +114,10 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
         "[" => { "Left bracket"
             "d" => goto_prev_diag,
             "D" => goto_first_diag,
+            "q" => goto_prev_quickfix,
             "g" => goto_prev_change,
             "G" => goto_first_change,
+            "n" => conflict_prev,
             "f" => goto_prev_function,
             "t" => goto_prev_class,
             "a" => goto_prev_parameter,
@@ -126,8 +131,10 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
         "]" => { "Right bracket"
             "d" => goto_next_diag,
             "D" => goto_last_diag,
+            "q" => goto_next_quickfix,
             "g" => goto_next_change,
             "G" => goto_last_change,
+            "n" => conflict_next,
             "f" => goto_next_function,
             "t" => goto_next_class,
             "a" => goto_next_parameter,
@@ -150,6 +157,8 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
         "U" => redo,
         "A-u" => earlier,
         "A-U" => later,
+        "A-z" => select_prev,
+        "A-Z" => select_next,
 
         "y" => yank,
         // yank_all
@@ -175,6 +184,7 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
         // "Q" => replay_macro,
 
         "&" => align_selections,
+        "A-&" => align_selections_on,
         "_" => trim_selections,
 
         "(" => rotate_selections_backward,
@@ -195,6 +205,11 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
             "C-s" | "s" => hsplit,
             "C-v" | "v" => vsplit,
             "C-t" | "t" => transpose_view,
+            ">" => grow_view_width,
+            "<" => shrink_view_width,
+            "+" => grow_view_height,
+            "minus" => shrink_view_height,
+            "=" => equalize_views,
             "f" => goto_file_hsplit,
             "F" => goto_file_vsplit,
             "C-q" | "q" => wclose,
@@ -226,13 +241,18 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
             "f" => file_picker,
             "F" => file_picker_in_current_directory,
             "e" => file_explorer,
+            "E" => toggle_explorer_panel,
             "." => file_explorer_in_current_buffer_directory,
             "b" => buffer_picker,
             "j" => jumplist_picker,
+            "m" => marks_picker,
+            "u" => undo_tree_picker,
+            "U" => unicode_picker,
             "s" => lsp_or_syntax_symbol_picker,
             "S" => lsp_or_syntax_workspace_symbol_picker,
             "d" => diagnostics_picker,
             "D" => workspace_diagnostics_picker,
+            "x" => toggle_diagnostics_panel,
             "g" => changed_file_picker,
             "a" => code_action,
             "'" => last_picker,
@@ -252,6 +272,7 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
                 "s" => { "Switch"
                     "t" => dap_switch_thread,
                     "f" => dap_switch_stack_frame,
+                    "s" => dap_switch_session,
                     // sl, sb
                 },
                 "e" => dap_enable_exceptions,
@@ -262,6 +283,11 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
                 "C-s" | "s" => hsplit,
                 "C-v" | "v" => vsplit,
                 "C-t" | "t" => transpose_view,
+                ">" => grow_view_width,
+                "<" => shrink_view_width,
+                "+" => grow_view_height,
+                "minus" => shrink_view_height,
+                "=" => equalize_views,
                 "f" => goto_file_hsplit,
                 "F" => goto_file_vsplit,
                 "C-q" | "q" => wclose,
@@ -309,6 +335,11 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
             "?" => rsearch,
             "n" => search_next,
             "N" => search_prev,
+
+            "f" => fold_selection,
+            "a" => fold_toggle,
+            "M" => fold_all_syntax,
+            "R" => unfold_all,
         },
         "Z" => { "View" sticky=true
             "z" | "c" => align_view_center,
@@ -326,6 +357,11 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
             "?" => rsearch,
             "n" => search_next,
             "N" => search_prev,
+
+            "f" => fold_selection,
+            "a" => fold_toggle,
+            "M" => fold_all_syntax,
+            "R" => unfold_all,
         },
 
         "\"" => select_register,
@@ -335,6 +371,7 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
         "A-!" => shell_append_output,
         "$" => shell_keep_pipe,
         "C-z" => suspend,
+        "C-\\" => toggle_terminal_focus,
 
         "C-a" => increment,
         "C-x" => decrement,
@@ -364,11 +401,12 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
         "T" => extend_till_prev_char,
         "F" => extend_prev_char,
 
-        "home" => extend_to_line_start,
-        "end" => extend_to_line_end,
+        "home" => extend_to_visual_line_start,
+        "end" => extend_to_visual_line_end,
         "esc" => exit_select_mode,
 
         "v" => normal_mode,
+        "C-v" => select_block,
         "g" => { "Goto"
             "g" => extend_to_file_start,
             "|" => extend_to_column,
@@ -384,6 +422,12 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
         "C-s" => commit_undo_checkpoint,
         "C-x" => completion,
         "C-r" => insert_register,
+        "C-v" => insert_unicode,
+        "A-k" => insert_digraph,
+        "A-l" => accept_inline_completion,
+        "A-w" => accept_inline_completion_word,
+        "A-n" => next_inline_completion,
+        "A-p" => prev_inline_completion,
 
         "C-w" | "A-backspace" => delete_word_backward,
         "A-d" | "A-del" => delete_word_forward,
@@ -1,10 +1,15 @@
 //! Logging support for `hx`.
 
+use std::collections::VecDeque;
 use std::io::Write;
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Mutex;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+/// The number of records kept in memory by [capture].
+const CAPTURE_CAPACITY: usize = 2000;
+
 /// Minimal `log::Log` implementation: a level filter plus a single line-buffered sink.
 struct Logger {
     level: log::LevelFilter,
@@ -13,22 +18,33 @@ struct Logger {
 
 impl log::Log for Logger {
     fn enabled(&self, metadata: &log::Metadata) -> bool {
-        metadata.level() <= self.level
+        metadata.level() <= self.level || metadata.level() <= capture_level()
     }
 
     fn log(&self, record: &log::Record) {
         if !self.enabled(record.metadata()) {
             return;
         }
-        if let Ok(mut sink) = self.sink.lock() {
-            let _ = writeln!(
-                sink,
-                "{} {} [{}] {}",
-                log_timestamp(),
-                record.target(),
-                record.level(),
-                record.args()
-            );
+        let timestamp = log_timestamp();
+        if record.level() <= self.level {
+            if let Ok(mut sink) = self.sink.lock() {
+                let _ = writeln!(
+                    sink,
+                    "{} {} [{}] {}",
+                    timestamp,
+                    record.target(),
+                    record.level(),
+                    record.args()
+                );
+            }
+        }
+        if record.level() <= capture_level() {
+            push(LogRecord {
+                timestamp,
+                level: record.level(),
+                target: record.target().to_string(),
+                message: record.args().to_string(),
+            });
         }
     }
 
@@ -47,7 +63,8 @@ fn install(
         level,
         sink: Mutex::new(sink),
     }))?;
-    log::set_max_level(level);
+    FILE_LEVEL.store(level as usize, Ordering::Relaxed);
+    log::set_max_level(level.max(capture_level()));
     Ok(())
 }
 
@@ -66,6 +83,114 @@ pub fn init_stdout(level: log::LevelFilter) {
     let _ = install(level, Box::new(std::io::stdout()));
 }
 
+/// A log message, either captured in memory or read back from a log file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    pub timestamp: String,
+    pub level: log::Level,
+    pub target: String,
+    pub message: String,
+}
+
+/// The records captured since [capture] was enabled, the oldest records are dropped once there
+/// are [CAPTURE_CAPACITY] of them.
+struct Capture {
+    records: VecDeque<LogRecord>,
+    /// The number of records captured so far, including the dropped ones.
+    count: usize,
+}
+
+static CAPTURE: Mutex<Capture> = Mutex::new(Capture {
+    records: VecDeque::new(),
+    count: 0,
+});
+/// The `log::LevelFilter`s of the sink and of the capture, as `usize`s.
+static FILE_LEVEL: AtomicUsize = AtomicUsize::new(0);
+static CAPTURE_LEVEL: AtomicUsize = AtomicUsize::new(0);
+
+fn level_filter(level: usize) -> log::LevelFilter {
+    log::LevelFilter::iter()
+        .nth(level)
+        .unwrap_or(log::LevelFilter::Off)
+}
+
+fn capture_level() -> log::LevelFilter {
+    level_filter(CAPTURE_LEVEL.load(Ordering::Relaxed))
+}
+
+fn push(record: LogRecord) {
+    if let Ok(mut capture) = CAPTURE.lock() {
+        if capture.records.len() == CAPTURE_CAPACITY {
+            capture.records.pop_front();
+        }
+        capture.records.push_back(record);
+        capture.count += 1;
+    }
+}
+
+/// Keeps the records of at least `level` in memory in addition to writing the records of the
+/// logger's level to its sink, for the log panel. `LevelFilter::Off` stops the capture and drops
+/// the captured records.
+pub fn capture(level: log::LevelFilter) {
+    CAPTURE_LEVEL.store(level as usize, Ordering::Relaxed);
+    log::set_max_level(level.max(level_filter(FILE_LEVEL.load(Ordering::Relaxed))));
+    if level == log::LevelFilter::Off {
+        if let Ok(mut capture) = CAPTURE.lock() {
+            capture.records.clear();
+        }
+    }
+}
+
+/// Returns the number of records captured so far and the captured records that come after the
+/// first `count` records, as far as they are still kept.
+pub fn captured_since(count: usize) -> (usize, Vec<LogRecord>) {
+    let Ok(capture) = CAPTURE.lock() else {
+        return (count, Vec::new());
+    };
+    let new = capture
+        .count
+        .saturating_sub(count)
+        .min(capture.records.len());
+    let skip = capture.records.len() - new;
+    (
+        capture.count,
+        capture.records.iter().skip(skip).cloned().collect(),
+    )
+}
+
+/// Parses the lines written by the logger back into records. Lines that don't start like a
+/// record continue the message of the previous record.
+pub fn parse_log(text: &str) -> Vec<LogRecord> {
+    let mut records: Vec<LogRecord> = Vec::new();
+    for line in text.lines() {
+        match parse_line(line) {
+            Some(record) => records.push(record),
+            None => {
+                if let Some(record) = records.last_mut() {
+                    record.message.push('\n');
+                    record.message.push_str(line);
+                }
+            }
+        }
+    }
+    records
+}
+
+fn parse_line(line: &str) -> Option<LogRecord> {
+    let (timestamp, rest) = line.split_once(' ')?;
+    let (target, rest) = rest.split_once(" [")?;
+    let (level, message) = rest.split_once("] ")?;
+    if !timestamp.starts_with(|c: char| c.is_ascii_digit()) || target.contains(' ') {
+        return None;
+    }
+    Some(LogRecord {
+        timestamp: timestamp.to_string(),
+        level: level.parse().ok()?,
+        target: target.to_string(),
+        message: message.to_string(),
+    })
+}
+
 /// RFC3339-style UTC timestamp for a log line: `YYYY-MM-DDTHH:MM:SS.mmm`.
 pub fn log_timestamp() -> String {
     let now = SystemTime::now()
@@ -100,7 +225,7 @@ fn civil_from_days(z: i64) -> (i64, u32, u32) {
 
 #[cfg(test)]
 mod tests {
-    use super::format_timestamp;
+    use super::{format_timestamp, parse_log, LogRecord};
 
     #[test]
     fn timestamp_matches_known_instants() {
@@ -119,4 +244,28 @@ fn timestamp_matches_known_instants() {
         // 2000-03-01 (the algorithm's era boundary)
         assert_eq!(format_timestamp(951_868_800, 0), "2000-03-01T00:00:00.000");
     }
+
+    #[test]
+    fn log_lines_parse_back_into_records() {
+        let text = "2024-02-29T12:30:45.123 helix_lsp::transport [INFO] rust-analyzer -> {}\n\
+                    2024-02-29T12:30:46.000 helix_term::application [ERROR] first line\n\
+                    second line\n";
+        assert_eq!(
+            parse_log(text),
+            [
+                LogRecord {
+                    timestamp: "2024-02-29T12:30:45.123".into(),
+                    level: log::Level::Info,
+                    target: "helix_lsp::transport".into(),
+                    message: "rust-analyzer -> {}".into(),
+                },
+                LogRecord {
+                    timestamp: "2024-02-29T12:30:46.000".into(),
+                    level: log::Level::Error,
+                    target: "helix_term::application".into(),
+                    message: "first line\nsecond line".into(),
+                },
+            ]
+        );
+    }
 }
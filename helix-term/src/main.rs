@@ -42,6 +42,7 @@ async fn main_impl() -> Result<i32> {
 
 ARGS:
     <files>...    Set the input file to use, position can also be specified via file[:row[:col]]
+                  Remote files can be opened with sftp://[user@]host[:port]/path
 
 FLAGS:
     -h, --help                     Print help information
@@ -61,6 +62,9 @@ async fn main_impl() -> Result<i32> {
     --vsplit                       Split all given files vertically into different windows
     --hsplit                       Split all given files horizontally into different windows
     -w, --working-dir <path>       Specify an initial working directory
+    --workspace <path>             Open <path> as an additional workspace root. Can be
+                                   passed multiple times
+    --session <name>               Restore the session <name>. `:session-save` saves to it
     +[N]                           Open the first given file at line number N, or the last line, if
                                    N is not specified.
 ",
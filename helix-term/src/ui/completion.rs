@@ -3,13 +3,19 @@
 use crate::{
     compositor::{Component, Context, Event, EventResult},
     handlers::completion::{
-        trigger_auto_completion, CompletionItem, CompletionResponse, ResolveHandler,
+        remove_duplicate_words, trigger_auto_completion, CompletionItem, CompletionResponse,
+        ResolveHandler,
     },
+    job,
 };
 use helix_core::snippets::{ActiveSnippet, RenderedSnippet, Snippet};
-use helix_core::{self as core, chars, fuzzy::MATCHER, Change, Transaction};
+use helix_core::{
+    self as core, chars, diff::compare_ropes, fuzzy::MATCHER, Assoc, Change, ChangeSet, Rope,
+    Transaction,
+};
 use helix_lsp::{lsp, util, OffsetEncoding};
 use helix_view::{
+    document::Mode,
     editor::CompleteAction,
     handlers::lsp::SignatureHelpInvoked,
     theme::{Color, Modifier, Style},
@@ -214,39 +220,18 @@ macro_rules! language_server {
                     doc.append_changes_to_history(view);
 
                     // item always present here
-                    let (transaction, additional_edits, snippet) = match item.clone() {
-                        CompletionItem::Lsp(mut item) => {
-                            let language_server = language_server!(item);
-
-                            // resolve item if not yet resolved
-                            if !item.resolved {
-                                if let Some(resolved_item) = Self::resolve_completion_item(
-                                    language_server,
-                                    item.item.clone(),
-                                ) {
-                                    item.item = resolved_item;
-                                }
-                            };
-
-                            let encoding = language_server.offset_encoding();
-                            let (transaction, snippet) = lsp_item_to_transaction(
-                                doc,
-                                view.id,
-                                &item.item,
-                                encoding,
-                                trigger_offset,
-                                replace_mode,
-                            );
-                            let add_edits = item.item.additional_text_edits;
-
-                            (
-                                transaction,
-                                add_edits.map(|edits| (edits, encoding)),
-                                snippet,
-                            )
-                        }
+                    let original = doc.text().clone();
+                    let (transaction, snippet) = match item {
+                        CompletionItem::Lsp(item) => lsp_item_to_transaction(
+                            doc,
+                            view.id,
+                            &item.item,
+                            language_server!(item).offset_encoding(),
+                            trigger_offset,
+                            replace_mode,
+                        ),
                         CompletionItem::Other(core::CompletionItem { transaction, .. }) => {
-                            (transaction, None, None)
+                            (transaction.clone(), None)
                         }
                     };
 
@@ -265,15 +250,68 @@ macro_rules! language_server {
                         placeholder,
                     });
 
-                    // TODO: add additional _edits to completion_changes?
-                    if let Some((additional_edits, offset_encoding)) = additional_edits {
-                        if !additional_edits.is_empty() {
-                            let transaction = util::generate_transaction_from_edits(
-                                doc.text(),
-                                additional_edits,
-                                offset_encoding, // TODO: should probably transcode in Client
-                            );
-                            doc.apply(&transaction, view.id);
+                    if let CompletionItem::Lsp(item) = item {
+                        let language_server = language_server!(item);
+                        let offset_encoding = language_server.offset_encoding();
+                        if item.resolved || !supports_resolve(language_server) {
+                            if let Some(edits) = item.item.additional_text_edits.clone() {
+                                apply_additional_edits(
+                                    doc,
+                                    view.id,
+                                    &original,
+                                    transaction.changes(),
+                                    edits,
+                                    offset_encoding,
+                                );
+                            }
+                        } else {
+                            // resolve the item in the background so that the text is inserted
+                            // right away, the additional edits are applied once they arrive
+                            let future = language_server.resolve_completion_item(&item.item);
+                            let (doc_id, view_id) = (doc.id(), view.id);
+                            let changes = transaction.changes().clone();
+                            let text = doc.text().clone();
+                            tokio::spawn(async move {
+                                let edits = match future.await {
+                                    Ok(item) => item.additional_text_edits.unwrap_or_default(),
+                                    Err(err) => {
+                                        log::error!("Failed to resolve completion item: {}", err);
+                                        return;
+                                    }
+                                };
+                                if edits.is_empty() {
+                                    return;
+                                }
+                                job::dispatch(move |editor, _| {
+                                    let insert_mode = editor.mode() == Mode::Insert;
+                                    if !editor.tree.contains(view_id) {
+                                        return;
+                                    }
+                                    let view = editor.tree.get_mut(view_id);
+                                    let Some(doc) = editor.documents.get_mut(&doc_id) else {
+                                        return;
+                                    };
+                                    // map the edits through the changes made in the meantime
+                                    let changes = if doc.text() == &text {
+                                        changes
+                                    } else {
+                                        let since = compare_ropes(&text, doc.text());
+                                        changes.compose(since.changes().clone())
+                                    };
+                                    apply_additional_edits(
+                                        doc,
+                                        view_id,
+                                        &original,
+                                        &changes,
+                                        edits,
+                                        offset_encoding,
+                                    );
+                                    if !insert_mode {
+                                        doc.append_changes_to_history(view);
+                                    }
+                                })
+                                .await;
+                            });
                         }
                     }
                     // we could have just inserted a trigger char (like a `crate::` completion for rust
@@ -379,32 +417,6 @@ fn score(&mut self, incremental: bool) {
         });
     }
 
-    /// Synchronously resolve the given completion item. This is used when
-    /// accepting a completion.
-    fn resolve_completion_item(
-        language_server: &helix_lsp::Client,
-        completion_item: lsp::CompletionItem,
-    ) -> Option<lsp::CompletionItem> {
-        if !matches!(
-            language_server.capabilities().completion_provider,
-            Some(lsp::CompletionOptions {
-                resolve_provider: Some(true),
-                ..
-            })
-        ) {
-            return None;
-        }
-        let future = language_server.resolve_completion_item(&completion_item);
-        let response = helix_lsp::block_on(future);
-        match response {
-            Ok(item) => Some(item),
-            Err(err) => {
-                log::error!("Failed to resolve completion item: {}", err);
-                None
-            }
-        }
-    }
-
     /// Appends (`c: Some(c)`) or removes (`c: None`) a character to/from the filter
     /// this should be called whenever the user types or deletes a character in insert mode.
     pub fn update_filter(&mut self, c: Option<char>) {
@@ -435,6 +447,7 @@ pub fn replace_provider_completions(
             options.retain(|item| item.provider() != response.provider)
         }
         response.take_items(options);
+        remove_duplicate_words(options);
         self.score(false);
         let menu = self.popup.contents_mut();
         menu.ensure_cursor_in_bounds();
@@ -500,28 +513,39 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
         };
 
         let mut markdown_doc = match option {
-            CompletionItem::Lsp(option) => match &option.item.documentation {
-                Some(lsp::Documentation::String(contents))
-                | Some(lsp::Documentation::MarkupContent(lsp::MarkupContent {
-                    kind: lsp::MarkupKind::PlainText,
-                    value: contents,
-                })) => {
-                    // TODO: convert to wrapped text
-                    markdowned(language, option.item.detail.as_deref(), Some(contents))
-                }
-                Some(lsp::Documentation::MarkupContent(lsp::MarkupContent {
-                    kind: lsp::MarkupKind::Markdown,
-                    value: contents,
-                })) => {
-                    // TODO: set language based on doc scope
-                    markdowned(language, option.item.detail.as_deref(), Some(contents))
-                }
-                None if option.item.detail.is_some() => {
-                    // TODO: set language based on doc scope
-                    markdowned(language, option.item.detail.as_deref(), None)
+            CompletionItem::Lsp(option) => {
+                let contents = match &option.item.documentation {
+                    Some(lsp::Documentation::String(contents))
+                    | Some(lsp::Documentation::MarkupContent(lsp::MarkupContent {
+                        value: contents,
+                        ..
+                    })) => Some(contents.clone()),
+                    None => None,
+                };
+                // show the imports or other edits that are made when accepting the completion
+                let additional_edits = option
+                    .item
+                    .additional_text_edits
+                    .iter()
+                    .flatten()
+                    .map(|edit| edit.new_text.trim())
+                    .filter(|text| !text.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let contents = if additional_edits.is_empty() {
+                    contents
+                } else {
+                    Some(format!(
+                        "{}\n\n**Auto import**\n```{language}\n{additional_edits}\n```",
+                        contents.unwrap_or_default()
+                    ))
+                };
+                if contents.is_none() && option.item.detail.is_none() {
+                    return;
                 }
-                None => return,
-            },
+                // TODO: set language based on doc scope
+                markdowned(language, option.item.detail.as_deref(), contents.as_deref())
+            }
             CompletionItem::Other(option) => {
                 let Some(doc) = option.documentation.as_deref() else {
                     return;
@@ -579,6 +603,45 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
         markdown_doc.render(doc_area, surface, cx);
     }
 }
+
+/// Whether the language server resolves additional information of completion items on request.
+fn supports_resolve(language_server: &helix_lsp::Client) -> bool {
+    matches!(
+        language_server.capabilities().completion_provider,
+        Some(lsp::CompletionOptions {
+            resolve_provider: Some(true),
+            ..
+        })
+    )
+}
+
+/// Applies the additional text edits of a completion item, like the import of the completed
+/// symbol. The edits refer to `original`, the text before the completion was accepted, and are
+/// mapped through `changes`, which lead from `original` to the current text of the document.
+fn apply_additional_edits(
+    doc: &mut Document,
+    view_id: ViewId,
+    original: &Rope,
+    changes: &ChangeSet,
+    edits: Vec<lsp::TextEdit>,
+    offset_encoding: OffsetEncoding,
+) {
+    if edits.is_empty() {
+        return;
+    }
+    let edits = util::generate_transaction_from_edits(original, edits, offset_encoding);
+    let mapped: Vec<Change> = edits
+        .changes_iter()
+        .map(|(from, to, text)| {
+            let from = changes.map_pos(from, Assoc::Before);
+            let to = changes.map_pos(to, Assoc::Before).max(from);
+            (from, to, text)
+        })
+        .collect();
+    let transaction = Transaction::change(doc.text(), mapped.into_iter());
+    doc.apply(&transaction, view_id);
+}
+
 fn lsp_item_to_transaction(
     doc: &Document,
     view_id: ViewId,
@@ -662,3 +725,53 @@ fn completion_changes(transaction: &Transaction, trigger_offset: usize) -> Vec<C
         .filter(|(start, end, _)| (*start..=*end).contains(&trigger_offset))
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arc_swap::ArcSwap;
+    use helix_core::syntax;
+
+    use super::*;
+
+    #[test]
+    fn additional_edits_are_mapped_through_later_changes() {
+        let mut doc = Document::from(
+            Rope::from("fn main() {\n    fo\n}\n"),
+            None,
+            Arc::new(ArcSwap::new(
+                Arc::new(helix_view::editor::Config::default()),
+            )),
+            Arc::new(ArcSwap::from_pointee(syntax::Loader::default())),
+        );
+        let view_id = ViewId::default();
+        doc.ensure_view_init(view_id);
+        let original = doc.text().clone();
+
+        // the completion itself and some text typed while the item is resolved
+        let completion =
+            Transaction::change(&original, [(16, 18, Some("foo()".into()))].into_iter());
+        doc.apply(&completion, view_id);
+        let typed = Transaction::change(doc.text(), [(21, 21, Some(";".into()))].into_iter());
+        doc.apply(&typed, view_id);
+        let changes = completion
+            .changes()
+            .clone()
+            .compose(typed.changes().clone());
+
+        let import = lsp::TextEdit {
+            range: lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(0, 0)),
+            new_text: "use foo::foo;\n".into(),
+        };
+        apply_additional_edits(
+            &mut doc,
+            view_id,
+            &original,
+            &changes,
+            vec![import],
+            OffsetEncoding::Utf8,
+        );
+        assert_eq!(doc.text(), "use foo::foo;\nfn main() {\n    foo();\n}\n");
+    }
+}
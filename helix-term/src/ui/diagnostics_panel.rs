@@ -0,0 +1,263 @@
+//! A panel at the bottom of the editor that lists the diagnostics of the workspace grouped by
+//! file. Unlike the diagnostics picker, the panel stays open while editing and is updated
+//! whenever a language server publishes diagnostics.
+
+use helix_core::{diagnostic::DiagnosticProvider, Uri};
+use helix_lsp::lsp::{self, DiagnosticSeverity};
+use helix_stdx::path;
+use helix_view::{graphics::Rect, Editor};
+use tui::buffer::Buffer as Surface;
+
+use crate::{
+    commands::lsp::jump_to_diagnostic,
+    compositor::{Callback, Component, Compositor, Context, Event, EventResult},
+    ctrl, key, ui,
+};
+
+/// The height of the panel including its title line.
+pub const PANEL_HEIGHT: u16 = 10;
+
+type FileDiagnostics<'a> = (&'a Uri, Vec<&'a (lsp::Diagnostic, DiagnosticProvider)>);
+
+#[derive(Debug, Default)]
+pub struct DiagnosticsPanel {
+    /// Whether the panel receives the key events.
+    focused: bool,
+    /// The index of the selected diagnostic among the listed diagnostics.
+    cursor: usize,
+    /// The first displayed row.
+    scroll: usize,
+    /// Only diagnostics that are at least as severe are listed, all diagnostics if `None`.
+    min_severity: Option<DiagnosticSeverity>,
+}
+
+impl DiagnosticsPanel {
+    pub fn new() -> Self {
+        Self {
+            focused: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    pub fn focus(&mut self) {
+        self.focused = true;
+    }
+
+    /// The listed diagnostics grouped by file, sorted by their position in the file.
+    fn diagnostics<'a>(&self, editor: &'a Editor) -> Vec<FileDiagnostics<'a>> {
+        editor
+            .diagnostics
+            .iter()
+            .filter_map(|(uri, diagnostics)| {
+                let mut diagnostics: Vec<_> = diagnostics
+                    .iter()
+                    .filter(|(diagnostic, provider)| {
+                        // the offset encoding of the language server is needed to jump to it
+                        provider.language_server_id().is_some()
+                            && self
+                                .min_severity
+                                .is_none_or(|min_severity| severity(diagnostic) <= min_severity)
+                    })
+                    .collect();
+                if diagnostics.is_empty() {
+                    return None;
+                }
+                diagnostics.sort_by_key(|(diagnostic, _)| {
+                    (
+                        diagnostic.range.start.line,
+                        diagnostic.range.start.character,
+                    )
+                });
+                Some((uri, diagnostics))
+            })
+            .collect()
+    }
+
+    fn cycle_severity_filter(&mut self) {
+        self.min_severity = match self.min_severity {
+            None => Some(DiagnosticSeverity::WARNING),
+            Some(DiagnosticSeverity::WARNING) => Some(DiagnosticSeverity::ERROR),
+            Some(_) => None,
+        };
+        self.cursor = 0;
+        self.scroll = 0;
+    }
+
+    fn jump_to_selected(&self, editor: &mut Editor) {
+        let selected = self
+            .diagnostics(editor)
+            .into_iter()
+            .flat_map(|(uri, diagnostics)| {
+                diagnostics
+                    .into_iter()
+                    .map(move |(diagnostic, provider)| (uri, diagnostic, provider))
+            })
+            .nth(self.cursor)
+            .map(|(uri, diagnostic, provider)| (uri.clone(), diagnostic.range, provider.clone()));
+        if let Some((uri, range, provider)) = selected {
+            jump_to_diagnostic(editor, uri, range, &provider);
+        }
+    }
+}
+
+fn severity(diagnostic: &lsp::Diagnostic) -> DiagnosticSeverity {
+    diagnostic.severity.unwrap_or(DiagnosticSeverity::HINT)
+}
+
+impl Component for DiagnosticsPanel {
+    fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        let Event::Key(event) = event else {
+            return EventResult::Ignored(None);
+        };
+        let count: usize = self
+            .diagnostics(cx.editor)
+            .iter()
+            .map(|(_, diagnostics)| diagnostics.len())
+            .sum();
+
+        match *event {
+            key!('j') | key!(Down) | ctrl!('n') => {
+                self.cursor = (self.cursor + 1).min(count.saturating_sub(1));
+            }
+            key!('k') | key!(Up) | ctrl!('p') => self.cursor = self.cursor.saturating_sub(1),
+            key!('g') | key!(Home) => self.cursor = 0,
+            key!('G') | key!(End) => self.cursor = count.saturating_sub(1),
+            key!('f') => self.cycle_severity_filter(),
+            key!(Enter) => {
+                self.jump_to_selected(cx.editor);
+                self.focused = false;
+            }
+            key!(Esc) => self.focused = false,
+            key!('q') => {
+                let close: Callback = Box::new(|compositor: &mut Compositor, _| {
+                    if let Some(editor_view) = compositor.find::<ui::EditorView>() {
+                        editor_view.diagnostics_panel = None;
+                    }
+                });
+                return EventResult::Consumed(Some(close));
+            }
+            _ => return EventResult::Ignored(None),
+        }
+        EventResult::Consumed(None)
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        let theme = &cx.editor.theme;
+        let text_style = theme.get("ui.text");
+        let directory_style = theme.get("ui.text.directory");
+        let selected_style = theme.get("ui.menu.selected");
+        let severity_style = |severity: DiagnosticSeverity| match severity {
+            DiagnosticSeverity::ERROR => theme.get("error"),
+            DiagnosticSeverity::WARNING => theme.get("warning"),
+            DiagnosticSeverity::INFORMATION => theme.get("info"),
+            _ => theme.get("hint"),
+        };
+        surface.clear_with(area, theme.get("ui.background"));
+
+        let diagnostics = self.diagnostics(cx.editor);
+        let count: usize = diagnostics.iter().map(|(_, diags)| diags.len()).sum();
+        self.cursor = self.cursor.min(count.saturating_sub(1));
+
+        let filter = match self.min_severity {
+            None => "all",
+            Some(DiagnosticSeverity::WARNING) => "warnings and errors",
+            Some(_) => "errors",
+        };
+        let title = format!(" Diagnostics ({count}), showing {filter}");
+        let title_style = if self.focused {
+            theme.get("ui.statusline")
+        } else {
+            theme.get("ui.statusline.inactive")
+        };
+        surface.set_style(area.with_height(1), title_style);
+        surface.set_stringn(area.x, area.y, &title, area.width as usize, title_style);
+
+        let list_area = area.clip_top(1);
+        if count == 0 {
+            surface.set_stringn(
+                list_area.x + 1,
+                list_area.y,
+                "No diagnostics",
+                list_area.width.saturating_sub(1) as usize,
+                text_style,
+            );
+            return;
+        }
+
+        // one row for the path of each file followed by one row per diagnostic
+        let mut rows = Vec::new();
+        let mut cursor_row = 0;
+        let mut index = 0;
+        for (uri, diagnostics) in &diagnostics {
+            rows.push((None, uri.as_path(), None));
+            for (diagnostic, _) in diagnostics {
+                if index == self.cursor {
+                    cursor_row = rows.len();
+                }
+                rows.push((Some(index), None, Some(diagnostic)));
+                index += 1;
+            }
+        }
+
+        // keep the selected diagnostic and the path of its file in view
+        let height = list_area.height as usize;
+        if cursor_row < self.scroll + 1 {
+            self.scroll = cursor_row.saturating_sub(1);
+        } else if cursor_row >= self.scroll + height {
+            self.scroll = cursor_row + 1 - height;
+        }
+
+        for (row, (i, path, diagnostic)) in rows.iter().skip(self.scroll).take(height).enumerate() {
+            let y = list_area.y + row as u16;
+            let width = list_area.width as usize;
+            match diagnostic {
+                None => {
+                    let path = path
+                        .map(|path| path::get_relative_path(path).to_string_lossy().into_owned())
+                        .unwrap_or_default();
+                    surface.set_stringn(list_area.x + 1, y, &path, width, directory_style);
+                }
+                Some(diagnostic) => {
+                    let position = format!(
+                        "    {}:{} ",
+                        diagnostic.range.start.line + 1,
+                        diagnostic.range.start.character + 1
+                    );
+                    let severity = severity(diagnostic);
+                    let label = match severity {
+                        DiagnosticSeverity::ERROR => "ERROR ",
+                        DiagnosticSeverity::WARNING => "WARN  ",
+                        DiagnosticSeverity::INFORMATION => "INFO  ",
+                        _ => "HINT  ",
+                    };
+                    let message = diagnostic.message.lines().next().unwrap_or_default();
+                    let (x, _) = surface.set_stringn(list_area.x, y, &position, width, text_style);
+                    let (x, _) = surface.set_stringn(
+                        x,
+                        y,
+                        label,
+                        list_area.right().saturating_sub(x) as usize,
+                        severity_style(severity),
+                    );
+                    surface.set_stringn(
+                        x,
+                        y,
+                        message,
+                        list_area.right().saturating_sub(x) as usize,
+                        text_style,
+                    );
+                    if *i == Some(self.cursor) && self.focused {
+                        surface.set_style(
+                            Rect::new(list_area.x, y, list_area.width, 1),
+                            selected_style,
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
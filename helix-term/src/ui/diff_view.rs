@@ -0,0 +1,562 @@
+//! A comparison of the current document with another file or with its VCS diff base. The lines
+//! of both sides are aligned so that they scroll together, either side by side or unified into a
+//! single pane, and the hunks can be copied from one side to the other.
+
+use std::ops::Range;
+
+use helix_core::{
+    diff::{compare_lines, Hunk},
+    unicode::width::UnicodeWidthChar,
+    Rope, Transaction,
+};
+use helix_vcs::word_diff;
+use helix_view::{
+    graphics::{Modifier, Rect, Style},
+    DocumentId, Editor, ViewId,
+};
+use tui::{
+    buffer::Buffer as Surface,
+    widgets::{Block, Widget},
+};
+
+use crate::{
+    compositor::{Component, Compositor, Context, Event, EventResult},
+    ctrl, key,
+};
+
+/// The file the document is compared with.
+pub enum DiffSource {
+    /// Another open document, hunks can be put into it.
+    Document(DocumentId),
+    /// A read-only text like the VCS diff base or a file that isn't open.
+    Text { name: String, text: Rope },
+}
+
+/// A displayed row, the line of each side or `None` if the row is padding for the other side.
+#[derive(Debug, PartialEq, Eq)]
+struct Row {
+    before: Option<u32>,
+    after: Option<u32>,
+    /// The index of the hunk the row belongs to.
+    hunk: Option<usize>,
+}
+
+pub struct DiffView {
+    doc_id: DocumentId,
+    view_id: ViewId,
+    source: DiffSource,
+    hunks: Vec<Hunk>,
+    rows: Vec<Row>,
+    /// The char ranges of the changed words of modified lines in the source and in the document.
+    words: (Vec<Range<usize>>, Vec<Range<usize>>),
+    /// The versions of the document and of the source document the hunks were computed for.
+    versions: (i32, Option<i32>),
+    /// The hunk that is obtained or put.
+    hunk: usize,
+    /// The first displayed row.
+    scroll: usize,
+    /// The number of rows displayed by the last render.
+    height: usize,
+    unified: bool,
+}
+
+impl DiffView {
+    pub fn new(editor: &Editor, source: DiffSource) -> Self {
+        let (view, doc) = current_ref!(editor);
+        let mut diff_view = Self {
+            doc_id: doc.id(),
+            view_id: view.id,
+            source,
+            hunks: Vec::new(),
+            rows: Vec::new(),
+            words: (Vec::new(), Vec::new()),
+            versions: (-1, None),
+            hunk: 0,
+            scroll: 0,
+            height: 0,
+            unified: false,
+        };
+        diff_view.update(editor);
+        diff_view
+    }
+
+    /// The text of the source followed by the text of the document.
+    fn texts<'a>(&'a self, editor: &'a Editor) -> Option<(&'a Rope, &'a Rope)> {
+        let doc = editor.documents.get(&self.doc_id)?;
+        let before = match &self.source {
+            DiffSource::Document(id) => editor.documents.get(id)?.text(),
+            DiffSource::Text { text, .. } => text,
+        };
+        Some((before, doc.text()))
+    }
+
+    fn source_version(&self, editor: &Editor) -> Option<i32> {
+        match self.source {
+            DiffSource::Document(id) => editor.documents.get(&id).map(|doc| doc.version()),
+            DiffSource::Text { .. } => None,
+        }
+    }
+
+    /// Recomputes the hunks if either side changed since they were computed.
+    fn update(&mut self, editor: &Editor) {
+        let Some(doc) = editor.documents.get(&self.doc_id) else {
+            return;
+        };
+        let versions = (doc.version(), self.source_version(editor));
+        if versions == self.versions {
+            return;
+        }
+        let Some((before, after)) = self.texts(editor) else {
+            return;
+        };
+        let hunks = compare_lines(before, after);
+        let (before_len, after_len) = (before.len_lines(), after.len_lines());
+        self.rows = rows(&hunks, before_len as u32, after_len as u32, self.unified);
+        self.words = changed_words(&hunks, before, after);
+        self.hunks = hunks;
+        self.versions = versions;
+        self.hunk = self.hunk.min(self.hunks.len().saturating_sub(1));
+    }
+
+    fn set_unified(&mut self, unified: bool) {
+        self.unified = unified;
+        // rebuild the rows on the next update
+        self.versions = (-1, None);
+    }
+
+    fn jump_to_hunk(&mut self, hunk: usize) {
+        self.hunk = hunk;
+        if let Some(row) = self.rows.iter().position(|row| row.hunk == Some(hunk)) {
+            self.scroll = row.saturating_sub(self.height / 3);
+        }
+    }
+
+    fn scroll_by(&mut self, delta: isize) {
+        let max = self.rows.len().saturating_sub(1);
+        self.scroll = self.scroll.saturating_add_signed(delta).min(max);
+        // the hunk that is obtained or put follows the scroll position
+        if let Some(hunk) = self.rows[self.scroll..].iter().find_map(|row| row.hunk) {
+            self.hunk = hunk;
+        }
+    }
+
+    /// Replaces the lines of the hunk in the document with the lines of the source.
+    fn obtain(&mut self, editor: &mut Editor) {
+        let Some(hunk) = self.hunks.get(self.hunk).cloned() else {
+            editor.set_error("No differences");
+            return;
+        };
+        let Some((before, _)) = self.texts(editor) else {
+            return;
+        };
+        let before = before.clone();
+        replace_lines(
+            editor,
+            self.doc_id,
+            self.view_id,
+            hunk.after,
+            &before,
+            hunk.before,
+        );
+    }
+
+    /// Replaces the lines of the hunk in the source with the lines of the document.
+    fn put(&mut self, editor: &mut Editor) {
+        let Some(hunk) = self.hunks.get(self.hunk).cloned() else {
+            editor.set_error("No differences");
+            return;
+        };
+        let DiffSource::Document(source_id) = self.source else {
+            editor.set_error("The compared file is read-only");
+            return;
+        };
+        let Some((_, after)) = self.texts(editor) else {
+            return;
+        };
+        let after = after.clone();
+        replace_lines(
+            editor,
+            source_id,
+            self.view_id,
+            hunk.before,
+            &after,
+            hunk.after,
+        );
+    }
+
+    fn title(&self, editor: &Editor) -> (String, String) {
+        let doc_name = editor
+            .documents
+            .get(&self.doc_id)
+            .map_or_else(String::new, |doc| doc.display_name().into_owned());
+        let source_name = match &self.source {
+            DiffSource::Document(id) => editor
+                .documents
+                .get(id)
+                .map_or_else(String::new, |doc| doc.display_name().into_owned()),
+            DiffSource::Text { name, .. } => name.clone(),
+        };
+        (source_name, doc_name)
+    }
+
+    fn render_pane(
+        &self,
+        area: Rect,
+        surface: &mut Surface,
+        cx: &Context,
+        before: &Rope,
+        after: &Rope,
+        side: Side,
+    ) {
+        let theme = &cx.editor.theme;
+        let text_style = theme.get("ui.text");
+        let line_nr_style = theme.get("ui.linenr");
+        let selected_line_nr_style = theme.get("ui.linenr.selected");
+        let style_for = |prefix: char| match prefix {
+            '-' => theme.get("diff.minus"),
+            '+' => theme.get("diff.plus"),
+            '~' => theme.get("diff.delta"),
+            _ => Style::default(),
+        };
+        let word_style = |scope: &str, style: Style| {
+            theme
+                .try_get_exact(scope)
+                .unwrap_or_else(|| style.add_modifier(Modifier::REVERSED))
+        };
+        let tab_width = cx
+            .editor
+            .documents
+            .get(&self.doc_id)
+            .map_or(4, |doc| doc.tab_width());
+        let tab = " ".repeat(tab_width);
+        let gutter_width = before.len_lines().max(after.len_lines()).to_string().len();
+
+        for (i, row) in self
+            .rows
+            .iter()
+            .skip(self.scroll)
+            .take(area.height as usize)
+            .enumerate()
+        {
+            let y = area.y + i as u16;
+            let (line, text, prefix, is_after) = match side {
+                Side::Before => (row.before, before, row_prefix(row, Side::Before), false),
+                Side::After => (row.after, after, row_prefix(row, Side::After), true),
+                Side::Unified => match row.after {
+                    Some(line) => (Some(line), after, row_prefix(row, Side::Unified), true),
+                    None => (row.before, before, row_prefix(row, Side::Unified), false),
+                },
+            };
+            let style = style_for(prefix);
+            if row.hunk.is_some() {
+                surface.set_style(Rect::new(area.x, y, area.width, 1), style);
+            }
+            let Some(line) = line else {
+                continue;
+            };
+            let line_nr = format!("{:>gutter_width$} {prefix} ", line + 1);
+            let line_nr_style = if row.hunk.is_some() && row.hunk == Some(self.hunk) {
+                selected_line_nr_style
+            } else {
+                line_nr_style
+            };
+            let (x, _) =
+                surface.set_stringn(area.x, y, &line_nr, area.width as usize, line_nr_style);
+            let content = text.line(line as usize).to_string().replace('\t', &tab);
+            surface.set_stringn(
+                x,
+                y,
+                content.trim_end_matches(['\n', '\r']),
+                area.right().saturating_sub(x) as usize,
+                text_style.patch(style),
+            );
+
+            if row.hunk.is_none() {
+                continue;
+            }
+            let (words, word_style) = if is_after {
+                (&self.words.1, word_style("diff.plus.word", style))
+            } else {
+                (&self.words.0, word_style("diff.minus.word", style))
+            };
+            let line_start = text.line_to_char(line as usize);
+            let words = &words[words.partition_point(|word| word.end <= line_start)..];
+            let mut x = x;
+            for (pos, c) in text.line(line as usize).chars().enumerate() {
+                if x >= area.right() || matches!(c, '\n' | '\r') {
+                    break;
+                }
+                let width = match c {
+                    '\t' => tab_width,
+                    c => c.width().unwrap_or(0),
+                } as u16;
+                let pos = line_start + pos;
+                if words
+                    .iter()
+                    .take_while(|word| word.start <= pos)
+                    .any(|word| word.contains(&pos))
+                {
+                    let width = width.min(area.right() - x);
+                    surface.set_style(Rect::new(x, y, width, 1), word_style);
+                }
+                x += width;
+            }
+        }
+    }
+}
+
+/// The char ranges of the changed words of the modified hunks in `before` and in `after`.
+fn changed_words(
+    hunks: &[Hunk],
+    before: &Rope,
+    after: &Rope,
+) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let lines = |text: &Rope, lines: &Range<u32>| {
+        let start = text.line_to_char(lines.start as usize);
+        let end = text.line_to_char(lines.end as usize);
+        (start, text.slice(start..end))
+    };
+    let offset = |start: usize| move |word: Range<usize>| word.start + start..word.end + start;
+    let mut words = (Vec::new(), Vec::new());
+    for hunk in hunks {
+        let (before_start, before_text) = lines(before, &hunk.before);
+        let (after_start, after_text) = lines(after, &hunk.after);
+        if let Some((before_words, after_words)) = word_diff(before_text, after_text) {
+            words
+                .0
+                .extend(before_words.into_iter().map(offset(before_start)));
+            words
+                .1
+                .extend(after_words.into_iter().map(offset(after_start)));
+        }
+    }
+    words
+}
+
+#[derive(Clone, Copy)]
+enum Side {
+    Before,
+    After,
+    Unified,
+}
+
+/// The marker of a row: `-` for removed, `+` for added and `~` for changed lines.
+fn row_prefix(row: &Row, side: Side) -> char {
+    if row.hunk.is_none() {
+        return ' ';
+    }
+    match (side, row.before, row.after) {
+        (Side::Before | Side::After, Some(_), Some(_)) => '~',
+        (Side::Before, Some(_), None) | (Side::Unified, Some(_), None) => '-',
+        (Side::After, None, Some(_)) | (Side::Unified, _, Some(_)) => '+',
+        _ => ' ',
+    }
+}
+
+/// Aligns the lines of both sides. Unchanged lines share a row. The lines of a hunk are paired
+/// side by side or, if `unified`, the removed lines are followed by the added lines.
+fn rows(hunks: &[Hunk], before_len: u32, after_len: u32, unified: bool) -> Vec<Row> {
+    let mut rows = Vec::new();
+    let (mut before, mut after) = (0, 0);
+    let unchanged = |rows: &mut Vec<Row>, before: &mut u32, after: &mut u32, end: u32| {
+        while *before < end && *after < after_len {
+            rows.push(Row {
+                before: Some(*before),
+                after: Some(*after),
+                hunk: None,
+            });
+            *before += 1;
+            *after += 1;
+        }
+    };
+    for (i, hunk) in hunks.iter().enumerate() {
+        unchanged(&mut rows, &mut before, &mut after, hunk.before.start);
+        if unified {
+            rows.extend(hunk.before.clone().map(|line| Row {
+                before: Some(line),
+                after: None,
+                hunk: Some(i),
+            }));
+            rows.extend(hunk.after.clone().map(|line| Row {
+                before: None,
+                after: Some(line),
+                hunk: Some(i),
+            }));
+        } else {
+            let len = hunk.before.len().max(hunk.after.len()) as u32;
+            rows.extend((0..len).map(|offset| Row {
+                before: Some(hunk.before.start + offset).filter(|line| hunk.before.contains(line)),
+                after: Some(hunk.after.start + offset).filter(|line| hunk.after.contains(line)),
+                hunk: Some(i),
+            }));
+        }
+        before = hunk.before.end;
+        after = hunk.after.end;
+    }
+    unchanged(&mut rows, &mut before, &mut after, before_len);
+    rows
+}
+
+/// Replaces the lines `range` of the document with the lines `source_range` of `source`.
+fn replace_lines(
+    editor: &mut Editor,
+    doc_id: DocumentId,
+    view_id: ViewId,
+    range: std::ops::Range<u32>,
+    source: &Rope,
+    source_range: std::ops::Range<u32>,
+) {
+    let Some(doc) = editor.documents.get_mut(&doc_id) else {
+        return;
+    };
+    let text = doc.text();
+    let line_to_char =
+        |text: &Rope, line: u32| text.line_to_char((line as usize).min(text.len_lines()));
+    let from = line_to_char(text, range.start);
+    let to = line_to_char(text, range.end);
+    let replacement = source
+        .slice(line_to_char(source, source_range.start)..line_to_char(source, source_range.end))
+        .to_string();
+    let transaction = Transaction::change(text, [(from, to, Some(replacement.into()))].into_iter());
+    doc.ensure_view_init(view_id);
+    doc.apply(&transaction, view_id);
+    let view = editor.tree.get_mut(view_id);
+    doc.append_changes_to_history(view);
+}
+
+impl Component for DiffView {
+    fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        let Event::Key(event) = event else {
+            return EventResult::Ignored(None);
+        };
+        self.update(cx.editor);
+        let half_page = (self.height / 2).max(1) as isize;
+        match *event {
+            key!('j') | key!(Down) => self.scroll_by(1),
+            key!('k') | key!(Up) => self.scroll_by(-1),
+            ctrl!('d') | key!(PageDown) => self.scroll_by(half_page),
+            ctrl!('u') | key!(PageUp) => self.scroll_by(-half_page),
+            key!('g') | key!(Home) => self.scroll_by(isize::MIN),
+            key!('G') | key!(End) => self.scroll_by(isize::MAX),
+            key!('n') | key!(']') => {
+                if !self.hunks.is_empty() {
+                    self.jump_to_hunk((self.hunk + 1) % self.hunks.len());
+                }
+            }
+            key!('N') | key!('[') => {
+                if !self.hunks.is_empty() {
+                    self.jump_to_hunk(self.hunk.checked_sub(1).unwrap_or(self.hunks.len() - 1));
+                }
+            }
+            key!('o') => self.obtain(cx.editor),
+            key!('p') => self.put(cx.editor),
+            key!('u') => self.set_unified(!self.unified),
+            key!('q') | key!(Esc) | ctrl!('c') => {
+                return EventResult::Consumed(Some(Box::new(|compositor: &mut Compositor, _| {
+                    compositor.pop();
+                })));
+            }
+            _ => (),
+        }
+        EventResult::Consumed(None)
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        self.update(cx.editor);
+        surface.clear_with(area, cx.editor.theme.get("ui.background"));
+        let (source_name, doc_name) = self.title(cx.editor);
+        let Some((before, after)) = self.texts(cx.editor) else {
+            return;
+        };
+        let (before, after) = (before.clone(), after.clone());
+        let hunks = match self.hunks.len() {
+            0 => "no differences".to_string(),
+            len => format!("hunk {}/{len}", self.hunk + 1),
+        };
+
+        if self.unified {
+            let block = Block::bordered().title(format!(" {source_name} → {doc_name} ({hunks}) "));
+            let inner = block.inner(area);
+            block.render(area, surface);
+            self.height = inner.height as usize;
+            self.render_pane(inner, surface, cx, &before, &after, Side::Unified);
+            return;
+        }
+
+        let left = area.with_width(area.width / 2);
+        let right = area.clip_left(left.width);
+        let block = Block::bordered().title(format!(" {source_name} "));
+        let inner = block.inner(left);
+        block.render(left, surface);
+        self.height = inner.height as usize;
+        self.render_pane(inner, surface, cx, &before, &after, Side::Before);
+
+        let block = Block::bordered().title(format!(" {doc_name} ({hunks}) "));
+        let inner = block.inner(right);
+        block.render(right, surface);
+        self.render_pane(inner, surface, cx, &before, &after, Side::After);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(before: Option<u32>, after: Option<u32>, hunk: Option<usize>) -> Row {
+        Row {
+            before,
+            after,
+            hunk,
+        }
+    }
+
+    #[test]
+    fn align_rows() {
+        // "a b c d" compared with "a c x y d"
+        let hunks = [
+            Hunk {
+                before: 1..2,
+                after: 1..1,
+            },
+            Hunk {
+                before: 3..3,
+                after: 2..4,
+            },
+        ];
+        assert_eq!(
+            rows(&hunks, 4, 5, false),
+            vec![
+                row(Some(0), Some(0), None),
+                row(Some(1), None, Some(0)),
+                row(Some(2), Some(1), None),
+                row(None, Some(2), Some(1)),
+                row(None, Some(3), Some(1)),
+                row(Some(3), Some(4), None),
+            ]
+        );
+
+        let hunks = [Hunk {
+            before: 1..2,
+            after: 1..3,
+        }];
+        assert_eq!(
+            rows(&hunks, 3, 4, false),
+            vec![
+                row(Some(0), Some(0), None),
+                row(Some(1), Some(1), Some(0)),
+                row(None, Some(2), Some(0)),
+                row(Some(2), Some(3), None),
+            ]
+        );
+        assert_eq!(
+            rows(&hunks, 3, 4, true),
+            vec![
+                row(Some(0), Some(0), None),
+                row(Some(1), None, Some(0)),
+                row(None, Some(1), Some(0)),
+                row(None, Some(2), Some(0)),
+                row(Some(2), Some(3), None),
+            ]
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use std::cmp::min;
+use std::ops::RangeInclusive;
 
 use helix_core::doc_formatter::{DocumentFormatter, FormattedGrapheme, GraphemeSource, TextFormat};
 use helix_core::graphemes::Grapheme;
@@ -38,6 +39,7 @@ pub fn render_document(
     overlay_highlights: Vec<syntax::OverlayHighlights>,
     theme: &Theme,
     decorations: DecorationManager,
+    active_indent_guide: Option<(usize, RangeInclusive<usize>)>,
 ) {
     let mut renderer = TextRenderer::new(
         surface,
@@ -46,6 +48,7 @@ pub fn render_document(
         Position::new(offset.vertical_offset, offset.horizontal_offset),
         viewport,
     );
+    renderer.active_indent_guide = active_indent_guide;
     render_text(
         &mut renderer,
         doc.text().slice(..),
@@ -119,7 +122,11 @@ pub fn render_text(
             // in that case we don't need to draw indent guides/virtual text
             if last_line_pos.doc_line != usize::MAX {
                 // draw indent guides for the last line
-                renderer.draw_indent_guides(last_line_indent_level, last_line_pos.visual_line);
+                renderer.draw_indent_guides(
+                    last_line_indent_level,
+                    last_line_pos.doc_line,
+                    last_line_pos.visual_line,
+                );
                 is_in_indent_area = true;
                 decorations.render_virtual_lines(renderer, last_line_pos, last_line_end)
             }
@@ -168,7 +175,11 @@ pub fn render_text(
         last_line_end = grapheme.visual_pos.col + grapheme_width;
     }
 
-    renderer.draw_indent_guides(last_line_indent_level, last_line_pos.visual_line);
+    renderer.draw_indent_guides(
+        last_line_indent_level,
+        last_line_pos.doc_line,
+        last_line_pos.visual_line,
+    );
     decorations.render_virtual_lines(renderer, last_line_pos, last_line_end)
 }
 
@@ -179,6 +190,10 @@ pub struct TextRenderer<'a> {
     pub whitespace_style: Style,
     pub indent_guide_char: String,
     pub indent_guide_style: Style,
+    pub indent_guide_active_style: Style,
+    /// The indent level and the lines of the indent guide of the scope of the cursor, see
+    /// `helix_core::indent::indent_guide_scope`.
+    pub active_indent_guide: Option<(usize, RangeInclusive<usize>)>,
     pub newline: String,
     pub nbsp: String,
     pub nnbsp: String,
@@ -245,6 +260,11 @@ pub fn new(
         let text_style = theme.get("ui.text");
 
         let indent_width = doc.indent_style.indent_width(tab_width) as u16;
+        let indent_guide_style = text_style.patch(
+            theme
+                .try_get("ui.virtual.indent-guide")
+                .unwrap_or_else(|| theme.get("ui.virtual.whitespace")),
+        );
 
         TextRenderer {
             surface,
@@ -260,11 +280,11 @@ pub fn new(
             starting_indent: offset.col / indent_width as usize
                 + !offset.col.is_multiple_of(indent_width as usize) as usize
                 + editor_config.indent_guides.skip_levels as usize,
-            indent_guide_style: text_style.patch(
-                theme
-                    .try_get("ui.virtual.indent-guide")
-                    .unwrap_or_else(|| theme.get("ui.virtual.whitespace")),
-            ),
+            indent_guide_style,
+            indent_guide_active_style: theme
+                .try_get_exact("ui.virtual.indent-guide.active")
+                .map_or(indent_guide_style, |style| text_style.patch(style)),
+            active_indent_guide: None,
             text_style,
             draw_indent_guides: editor_config.indent_guides.render,
             viewport,
@@ -391,7 +411,7 @@ pub fn column_in_bounds(&self, colum: usize, width: usize) -> bool {
     /// Overlay indentation guides ontop of a rendered line
     /// The indentation level is computed in `draw_lines`.
     /// Therefore this function must always be called afterwards.
-    pub fn draw_indent_guides(&mut self, indent_level: usize, mut row: u16) {
+    pub fn draw_indent_guides(&mut self, indent_level: usize, doc_line: usize, mut row: u16) {
         if !self.draw_indent_guides || self.offset.row > row as usize {
             return;
         }
@@ -410,8 +430,14 @@ pub fn draw_indent_guides(&mut self, indent_level: usize, mut row: u16) {
                 as u16;
             let y = self.viewport.y + row;
             debug_assert!(self.surface.in_bounds(x, y));
+            let style = match &self.active_indent_guide {
+                Some((level, lines)) if *level == i && lines.contains(&doc_line) => {
+                    self.indent_guide_active_style
+                }
+                _ => self.indent_guide_style,
+            };
             self.surface
-                .set_string(x, y, &self.indent_guide_char, self.indent_guide_style);
+                .set_string(x, y, &self.indent_guide_char, style);
         }
     }
 
@@ -1,37 +1,58 @@
 use crate::{
     commands::{self, OnKeyCallback, OnKeyCallbackKind},
-    compositor::{Component, Context, Event, EventResult},
+    compositor::{Component, Compositor, Context, Event, EventResult},
     events::{OnModeSwitch, PostCommand},
     handlers::completion::CompletionItem,
+    job::Callback,
     key,
     keymap::{KeymapResult, Keymaps},
     ui::{
-        document::{render_document, LinePos, TextRenderer},
+        diagnostics_panel::{self, DiagnosticsPanel},
+        document::{render_document, render_text, LinePos, TextRenderer},
+        explorer::{self, ExplorerPanel},
+        log_panel::{self, LogPanel},
         statusline,
-        text_decorations::{self, Decoration, DecorationManager, InlineDiagnostics},
-        Completion, ProgressSpinners,
+        terminal::{TerminalLayout, TerminalPane},
+        text_decorations::{
+            self, Decoration, DecorationManager, InlineBlame, InlineCompletionLines,
+            InlineDiagnostics,
+        },
+        Completion, Markdown, Popup, ProgressSpinners,
     },
 };
 
 use helix_core::{
     diagnostic::NumberOrString,
     graphemes::{next_grapheme_boundary, prev_grapheme_boundary},
+    indent, merge_conflict,
     movement::Direction,
     syntax::{self, OverlayHighlights},
     text_annotations::TextAnnotations,
     unicode::width::UnicodeWidthStr,
-    visual_offset_from_block, Change, Position, Range, Selection, Transaction,
+    visual_offset_from_block, Change, LineEnding, Position, Range, Selection, Transaction,
 };
+use helix_stdx::rope::{self, RopeSliceExt};
+use helix_vcs::LineBlame;
 use helix_view::{
     annotations::diagnostics::DiagnosticFilter,
     document::{Mode, SCRATCH_BUFFER_NAME},
-    editor::{CompleteAction, CursorShapeConfig},
+    editor::{Action, CloseError, CompleteAction, CursorShapeConfig, GutterType},
     graphics::{Color, CursorKind, Modifier, Rect, Style},
     input::{KeyEvent, MouseButton, MouseEvent, MouseEventKind},
     keyboard::{KeyCode, KeyModifiers},
-    Document, Editor, Theme, View,
+    view::scrollbar_row,
+    Document, DocumentId, Editor, Theme, View,
+};
+use std::{
+    collections::HashMap,
+    io,
+    mem::take,
+    num::NonZeroUsize,
+    ops,
+    path::PathBuf,
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
 };
-use std::{mem::take, num::NonZeroUsize, ops, path::PathBuf, rc::Rc};
 
 use tui::{buffer::Buffer as Surface, text::Span};
 
@@ -44,8 +65,56 @@ pub struct EditorView {
     spinners: ProgressSpinners,
     /// Tracks if the terminal window is focused by reaction to terminal focus events
     terminal_focused: bool,
+    pub(crate) diagnostics_panel: Option<DiagnosticsPanel>,
+    pub(crate) log_panel: Option<LogPanel>,
+    pub(crate) explorer_panel: Option<ExplorerPanel>,
+    pub(crate) terminal: Option<TerminalPane>,
+    /// The area of each buffer in the last rendered bufferline, used for mouse clicks.
+    bufferline: Vec<(Rect, DocumentId)>,
+    /// Counts the keys handled in insert mode so that a chord timeout only fires if no key was
+    /// pressed since it was scheduled.
+    insert_key_count: u64,
+    /// Counts the key, mouse and paste events so that the hover of a resting mouse is only shown
+    /// if nothing happened since the mouse moved.
+    input_count: u64,
+    /// The change repeated by `.`.
+    last_change: LastChange,
+    /// The keys of the normal or select mode command in progress, including its register but
+    /// not its count, and the document and its version when it started.
+    change_keys: Vec<KeyEvent>,
+    change_count: Option<NonZeroUsize>,
+    change_start: Option<(DocumentId, i32)>,
+    /// Set when the command in progress moves through the history, like `u`, which is not
+    /// repeated.
+    change_is_history: bool,
+    /// Set when the last change was repeated so that the repetition itself isn't recorded.
+    repeated_change: bool,
+    /// The search matches marked in the overview column of each document.
+    scrollbar_search: HashMap<DocumentId, ScrollbarSearch>,
+}
+
+/// The lines with matches of the last search in a document, see
+/// [EditorView::render_scrollbar].
+struct ScrollbarSearch {
+    /// The document version, search pattern and case insensitivity the lines were found for.
+    key: (i32, String, bool),
+    lines: Vec<usize>,
+}
+
+/// The last change made from normal or select mode.
+enum LastChange {
+    /// The insert mode session recorded in `last_insert`.
+    Insert,
+    /// The keys of a command that changed the document, without its count.
+    Keys {
+        keys: Vec<KeyEvent>,
+        count: Option<NonZeroUsize>,
+    },
 }
 
+/// The commands that move through the history rather than changing the document.
+const HISTORY_COMMANDS: &[&str] = &["undo", "redo", "earlier", "later"];
+
 #[derive(Debug, Clone)]
 pub enum InsertEvent {
     Key(KeyEvent),
@@ -67,6 +136,77 @@ pub fn new(keymaps: Keymaps) -> Self {
             completion: None,
             spinners: ProgressSpinners::default(),
             terminal_focused: true,
+            diagnostics_panel: None,
+            log_panel: None,
+            explorer_panel: None,
+            terminal: None,
+            bufferline: Vec::new(),
+            insert_key_count: 0,
+            input_count: 0,
+            last_change: LastChange::Insert,
+            change_keys: Vec::new(),
+            change_count: None,
+            change_start: None,
+            change_is_history: false,
+            repeated_change: false,
+            scrollbar_search: HashMap::new(),
+        }
+    }
+
+    /// Opens a terminal or focuses the open terminal and moves it to `layout`.
+    pub fn open_terminal(&mut self, editor: &Editor, layout: TerminalLayout) -> io::Result<()> {
+        match &mut self.terminal {
+            Some(terminal) if !terminal.has_exited() => {
+                terminal.layout = layout;
+                terminal.set_focused(true);
+            }
+            _ => {
+                let cwd = helix_stdx::env::current_working_dir();
+                self.terminal = Some(TerminalPane::spawn(editor, layout, &cwd)?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Opens the diagnostics panel, focuses it if it is open but not focused and closes it
+    /// otherwise.
+    pub fn toggle_diagnostics_panel(&mut self) {
+        match &mut self.diagnostics_panel {
+            Some(panel) if panel.is_focused() => self.diagnostics_panel = None,
+            Some(panel) => panel.focus(),
+            None => self.diagnostics_panel = Some(DiagnosticsPanel::new()),
+        }
+    }
+
+    /// Opens the log panel, focuses it if it is open but not focused and closes it otherwise.
+    /// With a `server`, the panel is opened or focused and lists the messages of that language
+    /// server or debug adapter.
+    pub fn toggle_log_panel(&mut self, server: Option<String>) {
+        match &mut self.log_panel {
+            Some(panel) if server.is_some() => {
+                panel.set_server(server);
+                panel.focus();
+            }
+            Some(panel) if panel.is_focused() => self.log_panel = None,
+            Some(panel) => panel.focus(),
+            None => self.log_panel = Some(LogPanel::new(server)),
+        }
+    }
+
+    /// Opens the explorer panel at `root`, focuses it if it is open but not focused and closes it
+    /// otherwise. Opening or focusing the panel reveals the current buffer.
+    pub fn toggle_explorer_panel(&mut self, root: PathBuf, editor: &Editor) {
+        let current = doc!(editor).path();
+        match &mut self.explorer_panel {
+            Some(panel) if panel.is_focused() => self.explorer_panel = None,
+            Some(panel) => {
+                match current {
+                    Some(path) => panel.reveal(path, editor),
+                    None => panel.refresh(editor),
+                }
+                panel.focus();
+            }
+            None => self.explorer_panel = Some(ExplorerPanel::new(root, current, editor)),
         }
     }
 
@@ -75,7 +215,7 @@ pub fn spinners_mut(&mut self) -> &mut ProgressSpinners {
     }
 
     pub fn render_view(
-        &self,
+        &mut self,
         editor: &Editor,
         doc: &Document,
         view: &View,
@@ -127,6 +267,12 @@ pub fn render_view(
             &text_annotations,
         ));
 
+        if let Some(overlay) =
+            Self::doc_semantic_token_highlights(doc, view_offset.anchor, inner.height, theme)
+        {
+            overlays.push(overlay);
+        }
+
         if doc
             .language_config()
             .and_then(|config| config.rainbow_brackets)
@@ -143,6 +289,10 @@ pub fn render_view(
             overlays.push(overlay);
         }
 
+        if doc.has_merge_conflicts {
+            overlays.push(Self::doc_merge_conflict_highlights(doc, theme));
+        }
+
         Self::doc_diagnostics_highlights_into(doc, theme, &mut overlays);
 
         if is_focused {
@@ -180,7 +330,7 @@ pub fn render_view(
             );
         }
 
-        Self::render_rulers(editor, doc, view, inner, surface, theme);
+        Self::render_rulers(doc, view, inner, surface, theme);
 
         let primary_cursor = doc
             .selection(view.id)
@@ -192,6 +342,19 @@ pub fn render_view(
                 primary_cursor,
             });
         }
+        if let Some(completion) = doc
+            .inline_completion
+            .as_ref()
+            .filter(|completion| completion.view_id == view.id)
+            .filter(|completion| completion.cursor == primary_cursor)
+        {
+            decorations.add_decoration(InlineCompletionLines::new(
+                doc,
+                completion,
+                view_offset.horizontal_offset,
+                theme.get("ui.virtual.inline-completion"),
+            ));
+        }
         let width = view.inner_width(doc);
         let config = doc.config.load();
         let enable_cursor_line = view
@@ -205,6 +368,30 @@ pub fn render_view(
             inline_diagnostic_config,
             config.end_of_line_diagnostics,
         ));
+        if is_focused && config.inline_blame.enable && editor.mode() != Mode::Insert {
+            let line = doc.text().char_to_line(primary_cursor);
+            if let Some(text) = Self::inline_blame_text(doc, line, &config.inline_blame.format) {
+                decorations.add_decoration(InlineBlame::new(
+                    line,
+                    text,
+                    theme.get("ui.virtual.inline-blame"),
+                ));
+            }
+        }
+        let active_indent_guide = if is_focused && config.indent_guides.render {
+            doc.syntax().and_then(|syntax| {
+                let tab_width = doc.tab_width();
+                indent::indent_guide_scope(
+                    syntax,
+                    doc.text().slice(..),
+                    primary_cursor,
+                    tab_width,
+                    doc.indent_style.indent_width(tab_width),
+                )
+            })
+        } else {
+            None
+        };
         render_document(
             surface,
             inner,
@@ -215,8 +402,15 @@ pub fn render_view(
             overlays,
             theme,
             decorations,
+            active_indent_guide,
         );
 
+        if config.sticky_context {
+            Self::render_sticky_context(doc, view, inner, surface, theme, &loader);
+        }
+
+        self.render_scrollbar(editor, doc, view, surface, theme);
+
         // if we're not at the edge of the screen, draw a right border
         if viewport.right() != view.area.right() {
             let x = area.right();
@@ -246,15 +440,77 @@ pub fn render_view(
         statusline::render(&mut context, statusline_area, surface);
     }
 
+    /// Renders the first lines of the functions and classes that contain the top of the view
+    /// over the first lines of the view, see [Document::sticky_context]. The cursor is kept
+    /// below these lines and clicks on them go to the context line.
+    pub fn render_sticky_context(
+        doc: &Document,
+        view: &View,
+        viewport: Rect,
+        surface: &mut Surface,
+        theme: &Theme,
+        loader: &syntax::Loader,
+    ) {
+        let lines = doc.sticky_context(view.id, viewport.height);
+        if lines.is_empty() {
+            return;
+        }
+        let text = doc.text().slice(..);
+        let view_offset = doc.view_offset(view.id);
+
+        let style = theme.get("ui.sticky-context");
+        let linenr_style = theme.get("ui.linenr").patch(style);
+        let text_format = doc.text_format(viewport.width, Some(theme));
+        let gutter_width = viewport.x - view.area.x;
+        for (row, &line) in lines.iter().enumerate() {
+            let y = viewport.y + row as u16;
+            let area = Rect::new(viewport.x, y, viewport.width, 1);
+            surface.clear_with(area, theme.get("ui.background"));
+
+            let anchor = text.line_to_char(line);
+            let mut renderer = TextRenderer::new(
+                surface,
+                doc,
+                theme,
+                Position::new(0, view_offset.horizontal_offset),
+                area,
+            );
+            render_text(
+                &mut renderer,
+                text,
+                anchor,
+                &text_format,
+                &TextAnnotations::default(),
+                Self::doc_syntax_highlighter(doc, anchor, 1, loader),
+                Vec::new(),
+                theme,
+                DecorationManager::default(),
+            );
+
+            // show the line number of the context in the gutter
+            let gutter = Rect::new(view.area.x, y, gutter_width, 1);
+            surface.clear_with(gutter, theme.get("ui.background"));
+            let line_number = format!("{:>1$} ", line + 1, gutter_width.saturating_sub(1) as usize);
+            surface.set_stringn(
+                gutter.x,
+                y,
+                &line_number,
+                gutter_width as usize,
+                linenr_style,
+            );
+            surface.set_style(gutter.union(area), style);
+        }
+    }
+
     pub fn render_rulers(
-        editor: &Editor,
         doc: &Document,
         view: &View,
         viewport: Rect,
         surface: &mut Surface,
         theme: &Theme,
     ) {
-        let editor_rulers = &editor.config().rulers;
+        let doc_config = doc.config.load();
+        let editor_rulers = &doc_config.rulers;
         let ruler_theme = theme
             .try_get("ui.virtual.ruler")
             .unwrap_or_else(|| Style::default().bg(Color::Red));
@@ -276,6 +532,124 @@ pub fn render_rulers(
             .for_each(|area| surface.set_style(area, ruler_theme))
     }
 
+    /// Renders the overview column at the right edge of the view, see [View::scrollbar_area].
+    /// Each row of the column covers an equal share of the lines of the document.
+    pub fn render_scrollbar(
+        &mut self,
+        editor: &Editor,
+        doc: &Document,
+        view: &View,
+        surface: &mut Surface,
+        theme: &Theme,
+    ) {
+        let Some(area) = view.scrollbar_area(doc) else {
+            return;
+        };
+        let config = doc.config.load();
+        let text = doc.text().slice(..);
+        let len_lines = text.len_lines();
+        let row = |line: usize| scrollbar_row(line, len_lines, area.height) as usize;
+
+        let mut marks = Vec::new();
+        if config.scrollbar.changes {
+            if let Some(diff_handle) = doc.diff_handle() {
+                let added = theme.get("diff.plus.gutter");
+                let deleted = theme.get("diff.minus.gutter");
+                let modified = theme.get("diff.delta.gutter");
+                let hunks = diff_handle.load();
+                for i in 0..hunks.len() {
+                    let hunk = hunks.nth_hunk(i);
+                    let style = if hunk.is_pure_insertion() {
+                        added
+                    } else if hunk.is_pure_removal() {
+                        deleted
+                    } else {
+                        modified
+                    };
+                    let start = hunk.after.start as usize;
+                    let end = (hunk.after.end as usize).max(start + 1);
+                    marks.push((start..=end - 1, 1, style));
+                }
+            }
+        }
+
+        if config.scrollbar.search && !doc.large_file {
+            let registers = &editor.registers;
+            if let Some(pattern) = registers.first(registers.last_search_register, editor) {
+                let case_insensitive =
+                    config.search.smart_case && !pattern.chars().any(char::is_uppercase);
+                let style = theme
+                    .try_get_exact("ui.scrollbar.search")
+                    .unwrap_or_else(|| theme.get("special"));
+                for &line in self.scrollbar_search_lines(doc, &pattern, case_insensitive) {
+                    marks.push((line..=line, 2, style));
+                }
+            }
+        }
+
+        if config.scrollbar.diagnostics {
+            use helix_core::diagnostic::Severity;
+            let warning = theme.get("warning");
+            let error = theme.get("error");
+            let info = theme.get("info");
+            let hint = theme.get("hint");
+            for diagnostic in doc.diagnostics() {
+                let (priority, style) = match diagnostic.severity() {
+                    Severity::Hint => (3, hint),
+                    Severity::Info => (4, info),
+                    Severity::Warning => (5, warning),
+                    Severity::Error => (6, error),
+                };
+                marks.push((diagnostic.line..=diagnostic.line, priority, style));
+            }
+        }
+        let marks = scrollbar_marks(area.height, len_lines, marks);
+
+        // `fg` sets the thumb color and `bg` the track color, like `ui.menu.scroll`
+        let scrollbar_style = theme
+            .try_get_exact("ui.scrollbar")
+            .unwrap_or_else(|| theme.get("ui.menu.scroll"));
+        let track = Style::default().bg(scrollbar_style.bg.unwrap_or(Color::Reset));
+        let thumb = Style::default().bg(scrollbar_style.fg.unwrap_or(Color::Reset));
+        let first_line = text.char_to_line(doc.view_offset(view.id).anchor.min(text.len_chars()));
+        let thumb_rows = row(first_line)..=row(view.estimate_last_doc_line(doc));
+
+        for (i, mark) in marks.into_iter().enumerate() {
+            let style = if thumb_rows.contains(&i) {
+                thumb
+            } else {
+                track
+            };
+            let cell = &mut surface[(area.x, area.y + i as u16)];
+            cell.set_symbol(" ").set_style(style);
+            if let Some((_, style)) = mark {
+                cell.set_symbol("━")
+                    .set_fg(style.fg.unwrap_or(Color::Reset));
+            }
+        }
+    }
+
+    /// The lines with matches of the search `pattern` in `doc`. They are only searched again
+    /// when the document or the pattern changed since the last call.
+    fn scrollbar_search_lines(
+        &mut self,
+        doc: &Document,
+        pattern: &str,
+        case_insensitive: bool,
+    ) -> &[usize] {
+        let key = (doc.version(), pattern.to_string(), case_insensitive);
+        if self
+            .scrollbar_search
+            .get(&doc.id())
+            .is_none_or(|search| search.key != key)
+        {
+            let lines = search_match_lines(doc, pattern, case_insensitive);
+            self.scrollbar_search
+                .insert(doc.id(), ScrollbarSearch { key, lines });
+        }
+        &self.scrollbar_search[&doc.id()].lines
+    }
+
     fn viewport_byte_range(
         text: helix_core::RopeSlice,
         row: usize,
@@ -300,13 +674,16 @@ pub fn doc_syntax_highlighter<'editor>(
         height: u16,
         loader: &'editor syntax::Loader,
     ) -> Option<syntax::Highlighter<'editor>> {
-        let syntax = doc.syntax()?;
+        // while the syntax tree is updated in the background, the outdated tree is highlighted
+        // against the text it was parsed from
+        let (syntax, source) = doc.highlight_syntax()?;
         let text = doc.text().slice(..);
         let row = text.char_to_line(anchor.min(text.len_chars()));
         let range = Self::viewport_byte_range(text, row, height);
-        let range = range.start as u32..range.end as u32;
+        let end = range.end.min(source.len_bytes());
+        let range = range.start.min(end) as u32..end as u32;
 
-        let highlighter = syntax.highlighter(text, loader, range);
+        let highlighter = syntax.highlighter(source, loader, range);
         Some(highlighter)
     }
 
@@ -325,6 +702,31 @@ pub fn overlay_syntax_highlights(
         text_annotations.collect_overlay_highlights(range)
     }
 
+    /// Get highlight spans for the semantic tokens of the document in the viewport
+    pub fn doc_semantic_token_highlights(
+        doc: &Document,
+        anchor: usize,
+        height: u16,
+        theme: &Theme,
+    ) -> Option<OverlayHighlights> {
+        let tokens = doc.semantic_tokens.as_ref()?;
+        let text = doc.text().slice(..);
+        let row = text.char_to_line(anchor.min(text.len_chars()));
+        let range = Self::viewport_byte_range(text, row, height);
+        let range = text.byte_to_char(range.start)..text.byte_to_char(range.end);
+
+        let first = tokens
+            .highlights
+            .partition_point(|(token, _)| token.end <= range.start);
+        let highlights = tokens.highlights[first..]
+            .iter()
+            .take_while(|(token, _)| token.start < range.end)
+            .filter(|(token, _)| token.start < token.end)
+            .filter_map(|(token, scope)| Some((theme.find_highlight(scope)?, token.clone())))
+            .collect();
+        Some(OverlayHighlights::Heterogenous { highlights })
+    }
+
     pub fn doc_rainbow_highlights(
         doc: &Document,
         anchor: usize,
@@ -527,6 +929,44 @@ pub fn doc_document_link_highlights(
         Some(OverlayHighlights::Homogeneous { highlight, ranges })
     }
 
+    /// Highlights the sides and the markers of the merge conflicts in the document.
+    pub fn doc_merge_conflict_highlights(doc: &Document, theme: &Theme) -> OverlayHighlights {
+        let text = doc.text().slice(..);
+        let marker = theme.find_highlight("diff.delta.conflict.marker");
+        let ours = theme.find_highlight("diff.delta.conflict.ours");
+        let base = theme.find_highlight("diff.delta.conflict.base");
+        let theirs = theme.find_highlight("diff.delta.conflict.theirs");
+
+        let mut highlights = Vec::new();
+        for conflict in merge_conflict::find_conflicts(text) {
+            let mut regions = vec![
+                (marker, conflict.start..conflict.start + 1),
+                (ours, conflict.ours()),
+            ];
+            if let (Some(line), Some(lines)) = (conflict.base, conflict.base_lines()) {
+                regions.extend([(marker, line..line + 1), (base, lines)]);
+            }
+            regions.extend([
+                (marker, conflict.separator..conflict.separator + 1),
+                (theirs, conflict.theirs()),
+                (marker, conflict.end..conflict.end + 1),
+            ]);
+            highlights.extend(
+                regions
+                    .into_iter()
+                    .filter(|(_, lines)| !lines.is_empty())
+                    .filter_map(|(highlight, lines)| {
+                        let end = lines.end.min(text.len_lines());
+                        Some((
+                            highlight?,
+                            text.line_to_char(lines.start)..text.line_to_char(end),
+                        ))
+                    }),
+            );
+        }
+        OverlayHighlights::Heterogenous { highlights }
+    }
+
     /// Get highlight spans for selections in a document view.
     pub fn doc_selection_highlights(
         mode: Mode,
@@ -659,8 +1099,87 @@ pub fn tabstop_highlights(doc: &Document, theme: &Theme) -> Option<OverlayHighli
         Some(OverlayHighlights::Homogeneous { highlight, ranges })
     }
 
-    /// Render bufferline at the top
-    pub fn render_bufferline(editor: &Editor, viewport: Rect, surface: &mut Surface) {
+    /// Formats the blame of `line` according to the `inline-blame.format` option.
+    fn inline_blame_text(doc: &Document, line: usize, format: &str) -> Option<String> {
+        match doc.line_blame(line)? {
+            LineBlame::Committed(commit) => Some(
+                format
+                    .replace("{author}", &commit.author)
+                    .replace("{date}", &relative_time(commit.time))
+                    .replace("{summary}", &commit.summary)
+                    .replace("{commit}", &commit.id),
+            ),
+            LineBlame::Uncommitted => Some("Not committed yet".to_string()),
+        }
+    }
+
+    /// Render the watch expressions of the active debugger at the top right
+    pub fn render_debug_watches(editor: &Editor, viewport: Rect, surface: &mut Surface) {
+        use helix_view::{graphics::Margin, info::Info};
+        use tui::{
+            text::Text,
+            widgets::{Block, Paragraph, Widget},
+        };
+
+        if !editor.show_debug_watches
+            || editor.debug_watches.is_empty()
+            || editor.debug_adapters.get_active_client().is_none()
+        {
+            return;
+        }
+
+        let body: Vec<_> = editor
+            .debug_watches
+            .iter()
+            .map(|watch| {
+                let value = match &watch.value {
+                    Some(Ok(value)) => value.clone(),
+                    Some(Err(err)) => format!("<{err}>"),
+                    None => "<not available>".to_string(),
+                };
+                (watch.expression.as_str(), value)
+            })
+            .collect();
+        // the watches are evaluated in the active session
+        let session = editor
+            .debug_adapters
+            .get_active_client()
+            .and_then(|debugger| debugger.name.as_deref());
+        let title = match session {
+            Some(session) if editor.debug_adapters.len() > 1 => format!("Watch ({session})"),
+            _ => "Watch".to_string(),
+        };
+        let info = Info::new(title, &body);
+
+        let text_style = editor.theme.get("ui.text.info");
+        let popup_style = editor.theme.get("ui.popup.info");
+        let width = (info.width + 2 + 2).min(viewport.width / 2); // +2 for border, +2 for margin
+        let height = info.height + 2; // +2 for border
+        let area = viewport.intersection(Rect::new(
+            viewport.right().saturating_sub(width),
+            viewport.y,
+            width,
+            height,
+        ));
+        surface.clear_with(area, popup_style);
+
+        let block = Block::bordered()
+            .title(info.title.as_ref())
+            .border_style(popup_style);
+        let inner = block.inner(area).inner(Margin::horizontal(1));
+        block.render(area, surface);
+
+        Paragraph::new(&Text::from(info.text.as_str()))
+            .style(text_style)
+            .render(inner, surface);
+    }
+
+    /// Render bufferline at the top. Returns the area of each rendered buffer.
+    pub fn render_bufferline(
+        editor: &Editor,
+        viewport: Rect,
+        surface: &mut Surface,
+    ) -> Vec<(Rect, DocumentId)> {
         let scratch = PathBuf::from(SCRATCH_BUFFER_NAME); // default filename to use for scratch buffer
         surface.clear_with(
             viewport,
@@ -682,8 +1201,11 @@ pub fn render_bufferline(editor: &Editor, viewport: Rect, surface: &mut Surface)
 
         let mut x = viewport.x;
         let current_doc = view!(editor).doc;
+        let numbers = editor.config().bufferline_numbers;
+        let mut tabs = Vec::new();
 
-        for doc in editor.documents() {
+        for (i, doc_id) in editor.buffers().into_iter().enumerate() {
+            let doc = &editor.documents[&doc_id];
             let fname = doc
                 .path()
                 .unwrap_or(&scratch)
@@ -692,24 +1214,48 @@ pub fn render_bufferline(editor: &Editor, viewport: Rect, surface: &mut Surface)
                 .to_str()
                 .unwrap_or_default();
 
-            let style = if current_doc == doc.id() {
+            let mut style = if current_doc == doc.id() {
                 bufferline_active
             } else {
                 bufferline_inactive
             };
+            // like the tabs of other editors, the preview buffer is shown in italics
+            if editor.preview_document == Some(doc_id) && !doc.is_modified() {
+                style = style.add_modifier(Modifier::ITALIC);
+            }
 
-            let text = format!(" {}{} ", fname, if doc.is_modified() { "[+]" } else { "" });
+            let text = format!(
+                " {}{}{}{} ",
+                if numbers {
+                    format!("{}:", i + 1)
+                } else {
+                    String::new()
+                },
+                if doc.pinned { "⚑ " } else { "" },
+                fname,
+                if doc.is_modified() { "[+]" } else { "" }
+            );
             let used_width = viewport.x.saturating_sub(x);
             let rem_width = surface.area.width.saturating_sub(used_width);
 
+            let start = x;
             x = surface
                 .set_stringn(x, viewport.y, &text, rem_width as usize, style)
                 .0;
+            tabs.push((Rect::new(start, viewport.y, x - start, 1), doc_id));
 
             if x >= surface.area.right() {
                 break;
             }
         }
+        tabs
+    }
+
+    /// The buffer whose name is displayed at the position in the bufferline.
+    fn bufferline_buffer_at(&self, row: u16, column: u16) -> Option<DocumentId> {
+        self.bufferline.iter().find_map(|(area, doc_id)| {
+            (area.y == row && (area.left()..area.right()).contains(&column)).then_some(*doc_id)
+        })
     }
 
     pub fn render_gutter<'d>(
@@ -942,6 +1488,9 @@ fn handle_keymap_event(
         cxt.editor.autoinfo = self.keymaps.sticky().map(|node| node.infobox());
 
         let mut execute_command = |command: &commands::MappableCommand| {
+            if HISTORY_COMMANDS.contains(&command.name()) {
+                self.change_is_history = true;
+            }
             command.execute(cxt);
             helix_event::dispatch(PostCommand { command, cx: cxt });
 
@@ -960,6 +1509,7 @@ fn handle_keymap_event(
                     // we can repeat the side effect.
                     self.last_insert.0 = command.clone();
                     self.last_insert.1.clear();
+                    self.last_change = LastChange::Insert;
                 }
             }
 
@@ -982,6 +1532,7 @@ fn handle_keymap_event(
     }
 
     fn insert_mode(&mut self, cx: &mut commands::Context, event: KeyEvent) {
+        self.insert_key_count = self.insert_key_count.wrapping_add(1);
         if let Some(keyresult) = self.handle_keymap_event(Mode::Insert, cx, event) {
             match keyresult {
                 KeymapResult::NotFound => {
@@ -991,25 +1542,194 @@ fn insert_mode(&mut self, cx: &mut commands::Context, event: KeyEvent) {
                         }
                     }
                 }
-                KeymapResult::Cancelled(pending) => {
-                    for ev in pending {
-                        match ev.char() {
-                            Some(ch) => commands::insert::insert_char(cx, ch),
-                            None => {
-                                if let KeymapResult::Matched(command) =
-                                    self.keymaps.get(Mode::Insert, ev)
-                                {
-                                    command.execute(cx);
-                                }
-                            }
-                        }
+                KeymapResult::Cancelled(pending) => self.insert_keys(cx, pending),
+                _ => unreachable!(),
+            }
+        } else if !self.keymaps.pending().is_empty() {
+            let key_count = self.insert_key_count;
+            let timeout = cx.editor.config().chord_timeout;
+            cx.jobs.callback(async move {
+                tokio::time::sleep(timeout).await;
+                let call = move |compositor: &mut Compositor, cx: &mut Context| {
+                    if let Some(editor_view) = compositor.find::<EditorView>() {
+                        editor_view.chord_timeout(cx, key_count);
+                    }
+                };
+                Ok(Callback::Compositor(Box::new(call)))
+            });
+        }
+    }
+
+    /// Inserts the keys of a key sequence that didn't match a mapping in insert mode.
+    fn insert_keys(&mut self, cx: &mut commands::Context, keys: Vec<KeyEvent>) {
+        for ev in keys {
+            match ev.char() {
+                Some(ch) => commands::insert::insert_char(cx, ch),
+                None => {
+                    if let KeymapResult::Matched(command) = self.keymaps.get(Mode::Insert, ev) {
+                        command.execute(cx);
                     }
                 }
-                _ => unreachable!(),
             }
         }
     }
 
+    /// Inserts the pending keys if no key was pressed in insert mode since the timeout was
+    /// scheduled at `key_count`.
+    fn chord_timeout(&mut self, cx: &mut Context, key_count: u64) {
+        if key_count != self.insert_key_count
+            || cx.editor.mode() != Mode::Insert
+            || self.keymaps.pending().is_empty()
+        {
+            return;
+        }
+        let pending = self.keymaps.take_pending();
+        cx.editor.autoinfo = None;
+        let mut cx = commands::Context {
+            register: None,
+            count: None,
+            editor: cx.editor,
+            callback: Vec::new(),
+            on_next_key_callback: None,
+            jobs: cx.jobs,
+        };
+        self.insert_keys(&mut cx, pending);
+
+        let scrolloff = cx.editor.config().scrolloff;
+        let (view, doc) = current!(cx.editor);
+        view.ensure_cursor_in_view(doc, scrolloff);
+    }
+
+    /// Repeats the last change: the last insert mode session is repeated `count` times and the
+    /// last command that changed the document is repeated once with `count` replacing its own
+    /// count, like in Vim.
+    fn repeat_last_change(&mut self, cxt: &mut commands::Context, count: Option<NonZeroUsize>) {
+        self.repeated_change = true;
+        match &self.last_change {
+            LastChange::Insert => {
+                for _ in 0..count.map_or(1, NonZeroUsize::get) {
+                    self.repeat_last_insert(cxt);
+                }
+            }
+            LastChange::Keys {
+                keys,
+                count: recorded_count,
+            } => {
+                let keys = repeated_keys(keys, count.or(*recorded_count));
+                for key in keys {
+                    if !self.on_next_key(OnKeyCallbackKind::PseudoPending, cxt, key) {
+                        self.command_mode(cxt.editor.mode(), cxt, key);
+                    }
+                    if let Some(on_next_key) = cxt.on_next_key_callback.take() {
+                        self.on_next_key = Some(on_next_key);
+                    }
+                }
+            }
+        }
+    }
+
+    fn repeat_last_insert(&mut self, cxt: &mut commands::Context) {
+        // first execute whatever put us into insert mode
+        self.last_insert.0.execute(cxt);
+        let mut last_savepoint = None;
+        let mut last_request_savepoint = None;
+        // then replay the inputs
+        for key in self.last_insert.1.clone() {
+            match key {
+                InsertEvent::Key(key) => self.insert_mode(cxt, key),
+                InsertEvent::CompletionApply {
+                    trigger_offset,
+                    changes,
+                } => {
+                    let (view, doc) = current!(cxt.editor);
+
+                    if let Some(last_savepoint) = last_savepoint.as_deref() {
+                        doc.restore(view, last_savepoint, true);
+                    }
+
+                    let text = doc.text().slice(..);
+                    let cursor = doc.selection(view.id).primary().cursor(text);
+
+                    let shift_position =
+                        |pos: usize| -> usize { (pos + cursor).saturating_sub(trigger_offset) };
+
+                    let tx = Transaction::change(
+                        doc.text(),
+                        changes
+                            .iter()
+                            .cloned()
+                            .map(|(start, end, t)| (shift_position(start), shift_position(end), t)),
+                    );
+                    doc.apply(&tx, view.id);
+                }
+                InsertEvent::TriggerCompletion => {
+                    last_savepoint = take(&mut last_request_savepoint);
+                }
+                InsertEvent::RequestCompletion => {
+                    let (view, doc) = current!(cxt.editor);
+                    last_request_savepoint = Some(doc.savepoint(view));
+                }
+            }
+        }
+    }
+
+    /// Records a key of a normal or select mode command, starting a new command when idle. The
+    /// digits of the count are kept apart so that `.` can replace the count.
+    fn record_change_key(&mut self, editor: &Editor, mode: Mode, key: KeyEvent) {
+        if self.is_idle(editor) {
+            let doc = doc!(editor);
+            self.change_keys.clear();
+            self.change_count = None;
+            self.change_start = Some((doc.id(), doc.version()));
+            self.change_is_history = false;
+        }
+        // the same conditions as in `command_mode`
+        let is_count = self.keymaps.pending().is_empty()
+            && self.on_next_key.is_none()
+            && match (key, editor.count) {
+                (key!('0'..='9'), Some(_)) => true,
+                (key!('1'..='9'), None) => !self.keymaps.contains_key(mode, key),
+                _ => false,
+            };
+        if let (true, Some(digit)) = (is_count, key.char().and_then(|c| c.to_digit(10))) {
+            let count = editor.count.map_or(0, NonZeroUsize::get) * 10 + digit as usize;
+            self.change_count = NonZeroUsize::new(count);
+        } else {
+            self.change_keys.push(key);
+        }
+    }
+
+    /// Once the command in progress has finished, keeps its keys as the last change if it
+    /// changed the document it started in without entering insert mode. Moving through the
+    /// history isn't a change.
+    fn finish_change(&mut self, editor: &Editor) {
+        if !self.is_idle(editor) {
+            return;
+        }
+        let changed = self.change_start.take().is_some_and(|(doc_id, version)| {
+            editor
+                .document(doc_id)
+                .is_some_and(|doc| doc.version() != version)
+        });
+        let keys = take(&mut self.change_keys);
+        let repeated = take(&mut self.repeated_change);
+        let is_history = take(&mut self.change_is_history);
+        if changed && !repeated && !is_history && editor.mode() != Mode::Insert {
+            self.last_change = LastChange::Keys {
+                keys,
+                count: self.change_count,
+            };
+        }
+    }
+
+    /// Whether no command is in progress, e.g. waiting for more keys, a count or a register.
+    fn is_idle(&self, editor: &Editor) -> bool {
+        self.keymaps.pending().is_empty()
+            && self.on_next_key.is_none()
+            && editor.count.is_none()
+            && editor.selected_register.is_none()
+    }
+
     fn command_mode(&mut self, mode: Mode, cxt: &mut commands::Context, event: KeyEvent) {
         match (event, cxt.editor.count) {
             // If the count is already started and the input is a number, always continue the count.
@@ -1028,51 +1748,8 @@ fn command_mode(&mut self, mode: Mode, cxt: &mut commands::Context, event: KeyEv
             }
             // special handling for repeat operator
             (key!('.'), _) if self.keymaps.pending().is_empty() => {
-                for _ in 0..cxt.editor.count.map_or(1, NonZeroUsize::into) {
-                    // first execute whatever put us into insert mode
-                    self.last_insert.0.execute(cxt);
-                    let mut last_savepoint = None;
-                    let mut last_request_savepoint = None;
-                    // then replay the inputs
-                    for key in self.last_insert.1.clone() {
-                        match key {
-                            InsertEvent::Key(key) => self.insert_mode(cxt, key),
-                            InsertEvent::CompletionApply {
-                                trigger_offset,
-                                changes,
-                            } => {
-                                let (view, doc) = current!(cxt.editor);
-
-                                if let Some(last_savepoint) = last_savepoint.as_deref() {
-                                    doc.restore(view, last_savepoint, true);
-                                }
-
-                                let text = doc.text().slice(..);
-                                let cursor = doc.selection(view.id).primary().cursor(text);
-
-                                let shift_position = |pos: usize| -> usize {
-                                    (pos + cursor).saturating_sub(trigger_offset)
-                                };
-
-                                let tx = Transaction::change(
-                                    doc.text(),
-                                    changes.iter().cloned().map(|(start, end, t)| {
-                                        (shift_position(start), shift_position(end), t)
-                                    }),
-                                );
-                                doc.apply(&tx, view.id);
-                            }
-                            InsertEvent::TriggerCompletion => {
-                                last_savepoint = take(&mut last_request_savepoint);
-                            }
-                            InsertEvent::RequestCompletion => {
-                                let (view, doc) = current!(cxt.editor);
-                                last_request_savepoint = Some(doc.savepoint(view));
-                            }
-                        }
-                    }
-                }
-                cxt.editor.count = None;
+                let count = cxt.editor.count.take();
+                self.repeat_last_change(cxt, count);
             }
             _ => {
                 // set the count
@@ -1215,6 +1892,15 @@ fn handle_mouse_event(
             })
         };
 
+        // the line covered by a row of the overview column, see `render_scrollbar`
+        let scrollbar_line_and_view = |editor: &Editor, row: u16, column: u16| {
+            editor.tree.views().find_map(|(view, _focus)| {
+                let doc = &editor.documents[&view.doc];
+                view.scrollbar_line_at_screen_coords(doc, row, column)
+                    .map(|line| (line, view.id))
+            })
+        };
+
         let gutter_coords_and_view = |editor: &Editor, row, column| {
             editor.tree.views().find_map(|(view, _focus)| {
                 view.gutter_coords_at_screen_coords(row, column)
@@ -1222,10 +1908,38 @@ fn handle_mouse_event(
             })
         };
 
+        // a click selects the buffer in the bufferline, a middle click closes it
+        if let Some(doc_id) = self.bufferline_buffer_at(row, column) {
+            match kind {
+                MouseEventKind::Down(MouseButton::Left) => {
+                    cxt.editor.switch(doc_id, Action::Replace);
+                }
+                MouseEventKind::Up(MouseButton::Middle) => {
+                    if let Err(CloseError::BufferModified(name)) =
+                        cxt.editor.close_document(doc_id, false)
+                    {
+                        cxt.editor
+                            .set_error(format!("Cannot close {name}: it has unsaved changes"));
+                    }
+                }
+                _ => return EventResult::Ignored(None),
+            }
+            return EventResult::Consumed(None);
+        }
+
         match kind {
             MouseEventKind::Down(MouseButton::Left) => {
                 let editor = &mut cxt.editor;
 
+                if let Some((line, view_id)) = scrollbar_line_and_view(editor, row, column) {
+                    editor.focus(view_id);
+                    let (view, doc) = current!(editor);
+                    let pos = doc.text().line_to_char(line);
+                    doc.set_selection(view_id, Selection::point(pos));
+                    view.ensure_cursor_in_view_center(doc, config.scrolloff);
+                    return EventResult::Consumed(None);
+                }
+
                 if let Some((pos, view_id)) = pos_and_view(editor, row, column, true) {
                     editor.focus(view_id);
 
@@ -1262,22 +1976,58 @@ fn handle_mouse_event(
 
                     let (view, doc) = current!(cxt.editor);
 
-                    let Some(path) = doc.path().map(ToOwned::to_owned) else {
+                    let Some(char_idx) =
+                        view.pos_at_visual_coords(doc, coords.row as u16, coords.col as u16, true)
+                    else {
                         return EventResult::Ignored(None);
                     };
+                    let line = doc.text().char_to_line(char_idx);
 
-                    if let Some(char_idx) =
-                        view.pos_at_visual_coords(doc, coords.row as u16, coords.col as u16, true)
-                    {
-                        let line = doc.text().char_to_line(char_idx);
-                        commands::dap_toggle_breakpoint_impl(cxt, path, line);
+                    let shown = match view.gutter_at(doc, coords.col) {
+                        Some(GutterType::Diagnostics) => show_line_diagnostics(cxt, line),
+                        Some(GutterType::Diff) => show_line_hunk(cxt, line),
+                        _ => false,
+                    };
+                    if shown {
                         return EventResult::Consumed(None);
                     }
+
+                    let Some(path) = doc!(cxt.editor).path().map(ToOwned::to_owned) else {
+                        return EventResult::Ignored(None);
+                    };
+                    commands::dap_toggle_breakpoint_impl(cxt, path, line);
+                    return EventResult::Consumed(None);
                 }
 
                 EventResult::Ignored(None)
             }
 
+            MouseEventKind::Moved => {
+                if !config.mouse_hover {
+                    return EventResult::Ignored(None);
+                }
+                let Some((pos, view_id)) = pos_and_view(cxt.editor, row, column, true) else {
+                    return EventResult::Ignored(None);
+                };
+                let doc_id = view!(cxt.editor, view_id).doc;
+                let anchor = helix_core::Position::new(row as usize, column as usize);
+                let input_count = self.input_count;
+                let delay = config.mouse_hover_delay;
+                cxt.jobs.callback(async move {
+                    tokio::time::sleep(delay).await;
+                    let call = move |editor: &mut Editor, compositor: &mut Compositor| {
+                        if compositor
+                            .find::<EditorView>()
+                            .is_some_and(|editor_view| editor_view.input_count == input_count)
+                        {
+                            commands::lsp::hover_at(editor, doc_id, pos, anchor);
+                        }
+                    };
+                    Ok(Callback::EditorCompositor(Box::new(call)))
+                });
+                EventResult::Ignored(None)
+            }
+
             MouseEventKind::Drag(MouseButton::Left) => {
                 let (view, doc) = current!(cxt.editor);
 
@@ -1438,6 +2188,43 @@ fn handle_event(
         event: &Event,
         context: &mut crate::compositor::Context,
     ) -> EventResult {
+        if let Some(terminal) = self
+            .terminal
+            .as_mut()
+            .filter(|terminal| terminal.is_focused())
+        {
+            if let EventResult::Consumed(callback) = terminal.handle_event(event, context) {
+                return EventResult::Consumed(callback);
+            }
+        }
+        if let Some(panel) = self
+            .diagnostics_panel
+            .as_mut()
+            .filter(|panel| panel.is_focused())
+        {
+            if let EventResult::Consumed(callback) = panel.handle_event(event, context) {
+                return EventResult::Consumed(callback);
+            }
+        }
+        if let Some(panel) = self.log_panel.as_mut().filter(|panel| panel.is_focused()) {
+            if let EventResult::Consumed(callback) = panel.handle_event(event, context) {
+                return EventResult::Consumed(callback);
+            }
+        }
+        if let Some(panel) = self
+            .explorer_panel
+            .as_mut()
+            .filter(|panel| panel.is_focused())
+        {
+            if let EventResult::Consumed(callback) = panel.handle_event(event, context) {
+                return EventResult::Consumed(callback);
+            }
+        }
+
+        if matches!(event, Event::Key(_) | Event::Mouse(_) | Event::Paste(_)) {
+            self.input_count = self.input_count.wrapping_add(1);
+        }
+
         let mut cx = commands::Context {
             editor: context.editor,
             count: None,
@@ -1479,8 +2266,20 @@ fn handle_event(
                 // clear status
                 cx.editor.status_msg = None;
 
+                if key == ctrl!('c') && cx.editor.shell_controller.cancel() {
+                    cx.editor.set_status("Shell command cancelled");
+                    return EventResult::Consumed(None);
+                }
+
                 let mode = cx.editor.mode();
 
+                if mode != Mode::Insert {
+                    self.record_change_key(cx.editor, mode, key);
+                    // also records the selections made since the last key, e.g. by a picker
+                    let (view, doc) = current!(cx.editor);
+                    doc.record_selection(view.id);
+                }
+
                 if !self.on_next_key(OnKeyCallbackKind::PseudoPending, &mut cx, key) {
                     match mode {
                         Mode::Insert => {
@@ -1543,6 +2342,10 @@ fn handle_event(
                     _ => self.pseudo_pending.clear(),
                 }
 
+                if mode != Mode::Insert {
+                    self.finish_change(cx.editor);
+                }
+
                 // appease borrowck
                 let callbacks = take(&mut cx.callback);
 
@@ -1617,20 +2420,91 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
         let mut editor_area = area.clip_bottom(1);
         if use_bufferline {
             editor_area = editor_area.clip_top(1);
+        } else {
+            self.bufferline.clear();
         }
+        let explorer_area = self.explorer_panel.as_ref().map(|_| {
+            let width = explorer::PANEL_WIDTH.min(editor_area.width / 3);
+            let explorer_area = editor_area.with_width(width);
+            // leave a column for the separator between the panel and the views
+            editor_area = editor_area.clip_left(width + 1);
+            explorer_area
+        });
+        let terminal_area = self
+            .terminal
+            .as_ref()
+            .map(|terminal| match terminal.layout {
+                TerminalLayout::Horizontal => {
+                    let height = editor_area.height / 3;
+                    let terminal_area = editor_area.clip_top(editor_area.height - height);
+                    editor_area = editor_area.clip_bottom(height);
+                    terminal_area
+                }
+                TerminalLayout::Vertical => {
+                    let width = editor_area.width / 2;
+                    let terminal_area = editor_area.clip_left(editor_area.width - width);
+                    // leave a column for the separator between the views and the terminal
+                    editor_area = editor_area.clip_right(width + 1);
+                    terminal_area
+                }
+            });
+        let panel_area = self.diagnostics_panel.as_ref().map(|_| {
+            let height = diagnostics_panel::PANEL_HEIGHT.min(editor_area.height / 2);
+            let panel_area = editor_area.clip_top(editor_area.height - height);
+            editor_area = editor_area.clip_bottom(height);
+            panel_area
+        });
+        let log_panel_area = self.log_panel.as_ref().map(|_| {
+            let height = log_panel::PANEL_HEIGHT.min(editor_area.height / 2);
+            let panel_area = editor_area.clip_top(editor_area.height - height);
+            editor_area = editor_area.clip_bottom(height);
+            panel_area
+        });
 
         // if the terminal size suddenly changed, we need to trigger a resize
         cx.editor.resize(editor_area);
 
         if use_bufferline {
-            Self::render_bufferline(cx.editor, area.with_height(1), surface);
+            self.bufferline = Self::render_bufferline(cx.editor, area.with_height(1), surface);
         }
 
+        let documents = &cx.editor.documents;
+        self.scrollbar_search
+            .retain(|doc_id, _| documents.contains_key(doc_id));
         for (view, is_focused) in cx.editor.tree.views() {
             let doc = cx.editor.document(view.doc).unwrap();
             self.render_view(cx.editor, doc, view, area, surface, is_focused);
         }
 
+        Self::render_debug_watches(cx.editor, editor_area, surface);
+
+        if let (Some(panel), Some(panel_area)) = (&mut self.diagnostics_panel, panel_area) {
+            panel.render(panel_area, surface, cx);
+        }
+        if let (Some(panel), Some(panel_area)) = (&mut self.log_panel, log_panel_area) {
+            panel.render(panel_area, surface, cx);
+        }
+        if let (Some(panel), Some(explorer_area)) = (&mut self.explorer_panel, explorer_area) {
+            let separator_style = cx.editor.theme.get("ui.window");
+            for y in explorer_area.top()..explorer_area.bottom() {
+                surface[(explorer_area.right(), y)]
+                    .set_symbol(tui::symbols::line::VERTICAL)
+                    .set_style(separator_style);
+            }
+            panel.render(explorer_area, surface, cx);
+        }
+        if let (Some(terminal), Some(terminal_area)) = (&mut self.terminal, terminal_area) {
+            if terminal.layout == TerminalLayout::Vertical && terminal_area.x > area.x {
+                let separator_style = cx.editor.theme.get("ui.window");
+                for y in terminal_area.top()..terminal_area.bottom() {
+                    surface[(terminal_area.x - 1, y)]
+                        .set_symbol(tui::symbols::line::VERTICAL)
+                        .set_style(separator_style);
+                }
+            }
+            terminal.render(terminal_area, surface, cx);
+        }
+
         if config.auto_info {
             if let Some(mut info) = cx.editor.autoinfo.take() {
                 info.render(area, surface, cx);
@@ -1718,6 +2592,94 @@ fn cursor(&self, _area: Rect, editor: &Editor) -> (Option<Position>, CursorKind)
     }
 }
 
+/// Selects the first diagnostic on the line and shows the messages of its diagnostics in a popup.
+/// Returns `false` if there are no diagnostics on the line.
+fn show_line_diagnostics(cx: &mut commands::Context, line: usize) -> bool {
+    let (view, doc) = current!(cx.editor);
+    let mut diagnostics = doc
+        .diagnostics()
+        .iter()
+        .filter(|diagnostic| diagnostic.line == line)
+        .peekable();
+    let Some(first) = diagnostics.peek() else {
+        return false;
+    };
+    let (start, end) = (first.range.start, first.range.end);
+    let contents = diagnostics
+        .map(|diagnostic| match &diagnostic.source {
+            Some(source) => format!("{} ({source})", diagnostic.message),
+            None => diagnostic.message.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n---\n\n");
+    doc.set_selection(view.id, Selection::single(start, end));
+
+    let contents = Markdown::new(contents, cx.editor.syn_loader.clone());
+    cx.push_layer(Box::new(
+        Popup::new("line-diagnostics", contents).auto_close(true),
+    ));
+    true
+}
+
+/// Moves the cursor to the start of the changes on the line and shows them in a popup. Returns
+/// `false` if the line isn't changed.
+fn show_line_hunk(cx: &mut commands::Context, line: usize) -> bool {
+    let (view, doc) = current!(cx.editor);
+    let Some(handle) = doc.diff_handle() else {
+        return false;
+    };
+    let diff = handle.load();
+    let Some(hunk) = diff.hunk_at(line as u32, true).map(|i| diff.nth_hunk(i)) else {
+        return false;
+    };
+
+    let mut contents = String::from("```diff\n");
+    for (prefix, text, lines) in [
+        ('-', diff.diff_base(), hunk.before.clone()),
+        ('+', diff.doc(), hunk.after.clone()),
+    ] {
+        for line in lines {
+            contents.push(prefix);
+            contents.extend(text.line(line as usize).chunks());
+        }
+    }
+    if !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+    contents.push_str("```");
+    drop(diff);
+
+    let pos = doc.text().line_to_char(hunk.after.start as usize);
+    doc.set_selection(view.id, Selection::point(pos));
+
+    let contents = Markdown::new(contents, cx.editor.syn_loader.clone());
+    cx.push_layer(Box::new(Popup::new("line-hunk", contents).auto_close(true)));
+    true
+}
+
+/// The keys that repeat a recorded change with `count`: the count is typed after the register
+/// selection, if any, and before the command.
+fn repeated_keys(keys: &[KeyEvent], count: Option<NonZeroUsize>) -> Vec<KeyEvent> {
+    let register_len = if keys.first() == Some(&key!('"')) {
+        keys.len().min(2)
+    } else {
+        0
+    };
+    let count_keys = count
+        .map(|count| count.to_string())
+        .unwrap_or_default()
+        .chars()
+        .map(|c| KeyEvent {
+            code: KeyCode::Char(c),
+            modifiers: KeyModifiers::NONE,
+        })
+        .collect::<Vec<_>>();
+    let mut repeated = keys[..register_len].to_vec();
+    repeated.extend(count_keys);
+    repeated.extend_from_slice(&keys[register_len..]);
+    repeated
+}
+
 fn canonicalize_key(key: &mut KeyEvent) {
     if let KeyEvent {
         code: KeyCode::Char(_),
@@ -1727,3 +2689,139 @@ fn canonicalize_key(key: &mut KeyEvent) {
         key.modifiers.remove(KeyModifiers::SHIFT)
     }
 }
+
+/// The mark shown in each row of an overview column of `height` rows from the `marks` of lines
+/// of a document of `len_lines` lines. A row covering several marks shows the one with the
+/// highest priority.
+fn scrollbar_marks(
+    height: u16,
+    len_lines: usize,
+    marks: impl IntoIterator<Item = (ops::RangeInclusive<usize>, u8, Style)>,
+) -> Vec<Option<(u8, Style)>> {
+    let last_line = len_lines.saturating_sub(1);
+    let row = |line: usize| scrollbar_row(line.min(last_line), len_lines, height) as usize;
+    let mut rows: Vec<Option<(u8, Style)>> = vec![None; height as usize];
+    for (lines, priority, style) in marks {
+        for mark in &mut rows[row(*lines.start())..=row(*lines.end())] {
+            if mark.is_none_or(|(prev, _)| prev < priority) {
+                *mark = Some((priority, style));
+            }
+        }
+    }
+    rows
+}
+
+/// The lines of `doc` with matches of the search `pattern`, each line once.
+fn search_match_lines(doc: &Document, pattern: &str, case_insensitive: bool) -> Vec<usize> {
+    let text = doc.text().slice(..);
+    let regex = rope::RegexBuilder::new()
+        .syntax(
+            rope::Config::new()
+                .case_insensitive(case_insensitive)
+                .multi_line(true)
+                .crlf(doc.line_ending == LineEnding::Crlf),
+        )
+        .build(pattern);
+    let Ok(regex) = regex else {
+        return Vec::new();
+    };
+    let mut lines: Vec<usize> = regex
+        .find_iter(text.regex_input())
+        .map(|mat| text.byte_to_line(mat.start()))
+        .collect();
+    lines.dedup();
+    lines
+}
+
+/// Formats a unix timestamp relative to now, for example `3 days ago`.
+fn relative_time(time: i64) -> String {
+    const UNITS: [(i64, &str); 6] = [
+        (365 * 24 * 60 * 60, "year"),
+        (30 * 24 * 60 * 60, "month"),
+        (7 * 24 * 60 * 60, "week"),
+        (24 * 60 * 60, "day"),
+        (60 * 60, "hour"),
+        (60, "minute"),
+    ];
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64);
+    let elapsed = now.saturating_sub(time).max(0);
+    for (unit_secs, unit) in UNITS {
+        let count = elapsed / unit_secs;
+        if count > 0 {
+            let plural = if count == 1 { "" } else { "s" };
+            return format!("{count} {unit}{plural} ago");
+        }
+    }
+    "just now".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arc_swap::ArcSwap;
+    use helix_core::Rope;
+
+    use super::*;
+
+    #[test]
+    fn scrollbar_marks_keep_the_highest_priority() {
+        let (low, high) = (
+            Style::default().fg(Color::Red),
+            Style::default().fg(Color::Blue),
+        );
+        let marks = scrollbar_marks(
+            4,
+            100,
+            [(0..=30, 1, low), (10..=10, 2, high), (99..=99, 1, low)],
+        );
+        assert_eq!(
+            marks,
+            [Some((2, high)), Some((1, low)), None, Some((1, low))]
+        );
+
+        // marks past the end of the document are shown in the last row
+        let marks = scrollbar_marks(2, 10, [(12..=15, 1, low)]);
+        assert_eq!(marks, [None, Some((1, low))]);
+    }
+
+    #[test]
+    fn scrollbar_search_lines_follow_the_document() {
+        let mut doc = Document::from(
+            Rope::from("foo\nbar\nfoo foo\n"),
+            None,
+            Arc::new(ArcSwap::new(
+                Arc::new(helix_view::editor::Config::default()),
+            )),
+            Arc::new(ArcSwap::from_pointee(syntax::Loader::default())),
+        );
+        let mut editor_view = EditorView::new(Keymaps::default());
+        assert_eq!(
+            editor_view.scrollbar_search_lines(&doc, "foo", false),
+            [0, 2]
+        );
+        assert_eq!(
+            editor_view.scrollbar_search_lines(&doc, "FOO", true),
+            [0, 2]
+        );
+        assert_eq!(editor_view.scrollbar_search_lines(&doc, "bar", false), [1]);
+
+        // the matches are searched again once the document changed
+        let view = helix_view::ViewId::default();
+        doc.ensure_view_init(view);
+        let transaction = Transaction::change(doc.text(), [(4, 4, Some("foo".into()))].into_iter());
+        assert!(doc.apply(&transaction, view));
+        assert_eq!(editor_view.scrollbar_search_lines(&doc, "bar", false), [1]);
+        assert_eq!(
+            editor_view.scrollbar_search_lines(&doc, "foo", false),
+            [0, 1, 2]
+        );
+        assert_eq!(
+            editor_view.scrollbar_search[&doc.id()].key,
+            (doc.version(), "foo".to_string(), false)
+        );
+    }
+}
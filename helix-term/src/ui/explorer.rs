@@ -0,0 +1,427 @@
+//! A panel at the left of the editor that shows the directory tree of the workspace. Unlike the
+//! file explorer picker, the panel stays open while editing and directories are expanded in
+//! place.
+
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use helix_view::{editor::Action, graphics::Rect, Editor};
+use tui::buffer::Buffer as Surface;
+
+use crate::{
+    compositor::{self, Component, Compositor, Context, Event, EventResult},
+    ctrl,
+    job::Callback,
+    key,
+    ui::{self, completers, directory_content, Prompt, PromptEvent},
+};
+
+/// The width of the panel excluding the separator to the views.
+pub const PANEL_WIDTH: u16 = 30;
+
+#[derive(Debug)]
+struct Entry {
+    path: PathBuf,
+    is_dir: bool,
+    /// The number of directories between the root and the entry.
+    depth: usize,
+}
+
+#[derive(Debug)]
+pub struct ExplorerPanel {
+    /// Whether the panel receives the key events.
+    focused: bool,
+    root: PathBuf,
+    /// The directories whose content is listed.
+    expanded: HashSet<PathBuf>,
+    /// The listed files and directories in tree order, rebuilt by [`Self::refresh`].
+    entries: Vec<Entry>,
+    /// The index of the selected entry.
+    cursor: usize,
+    /// The first displayed row.
+    scroll: usize,
+}
+
+impl ExplorerPanel {
+    /// Creates a panel listing `root` that selects `reveal` if it is inside of `root`.
+    pub fn new(root: PathBuf, reveal: Option<&Path>, editor: &Editor) -> Self {
+        let mut panel = Self {
+            focused: true,
+            root,
+            expanded: HashSet::new(),
+            entries: Vec::new(),
+            cursor: 0,
+            scroll: 0,
+        };
+        match reveal {
+            Some(path) => panel.reveal(path, editor),
+            None => panel.refresh(editor),
+        }
+        panel
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    pub fn focus(&mut self) {
+        self.focused = true;
+    }
+
+    fn selected(&self) -> Option<&Entry> {
+        self.entries.get(self.cursor)
+    }
+
+    /// Lists the expanded directories again from the disk, keeping the selected path selected
+    /// if it still exists.
+    pub fn refresh(&mut self, editor: &Editor) {
+        let selected = self.selected().map(|entry| entry.path.clone());
+        let mut entries = Vec::new();
+        self.list_directory(&self.root, 0, editor, &mut entries);
+        self.entries = entries;
+        self.cursor = selected
+            .and_then(|selected| self.entries.iter().position(|entry| entry.path == selected))
+            .unwrap_or(self.cursor)
+            .min(self.entries.len().saturating_sub(1));
+    }
+
+    fn list_directory(&self, dir: &Path, depth: usize, editor: &Editor, entries: &mut Vec<Entry>) {
+        // directories that can't be read are listed as empty
+        let Ok(content) = directory_content(dir, editor) else {
+            return;
+        };
+        for (path, is_dir) in content {
+            if path.ends_with("..") {
+                continue;
+            }
+            let expanded = is_dir && self.expanded.contains(&path);
+            entries.push(Entry {
+                path: path.clone(),
+                is_dir,
+                depth,
+            });
+            if expanded {
+                self.list_directory(&path, depth + 1, editor, entries);
+            }
+        }
+    }
+
+    /// Expands the directories containing `path` and selects the deepest listed entry
+    /// containing it.
+    pub fn reveal(&mut self, path: &Path, editor: &Editor) {
+        if let Some(parent) = path.strip_prefix(&self.root).ok().and_then(Path::parent) {
+            let mut dir = self.root.clone();
+            for component in parent.components() {
+                dir.push(component);
+                self.expanded.insert(dir.clone());
+            }
+        }
+        self.refresh(editor);
+        if let Some(index) = self
+            .entries
+            .iter()
+            .rposition(|entry| path.starts_with(&entry.path))
+        {
+            self.cursor = index;
+        }
+    }
+
+    /// Expands or collapses the selected directory.
+    fn toggle_selected(&mut self, editor: &Editor) {
+        let Some(entry) = self.selected().filter(|entry| entry.is_dir) else {
+            return;
+        };
+        let path = entry.path.clone();
+        if !self.expanded.remove(&path) {
+            self.expanded.insert(path);
+        }
+        self.refresh(editor);
+    }
+
+    /// Collapses the selected directory, or selects the parent directory if the selection is
+    /// a file or a collapsed directory.
+    fn collapse_selected(&mut self, editor: &Editor) {
+        let Some(entry) = self.selected() else {
+            return;
+        };
+        if entry.is_dir && self.expanded.contains(&entry.path) {
+            self.toggle_selected(editor);
+        } else if let Some(parent) = entry.path.parent() {
+            if let Some(index) = self.entries.iter().position(|entry| entry.path == parent) {
+                self.cursor = index;
+            }
+        }
+    }
+
+    /// Opens the selected file, or expands or collapses the selected directory.
+    fn open_selected(&mut self, editor: &mut Editor, action: Action) {
+        let Some(entry) = self.selected() else {
+            return;
+        };
+        if entry.is_dir {
+            self.toggle_selected(editor);
+            return;
+        }
+        let path = entry.path.clone();
+        match editor.open(&path, action) {
+            Ok(_) => self.focused = false,
+            Err(err) => editor.set_error(format!("unable to open \"{}\": {err}", path.display())),
+        }
+    }
+
+    /// The directory new files are created in: the selected directory or the directory of the
+    /// selected file.
+    fn selected_directory(&self) -> &Path {
+        match self.selected() {
+            Some(entry) if entry.is_dir => &entry.path,
+            Some(entry) => entry.path.parent().unwrap_or(&self.root),
+            None => &self.root,
+        }
+    }
+
+    fn relative_path<'a>(&self, path: &'a Path) -> Cow<'a, str> {
+        path.strip_prefix(&self.root)
+            .unwrap_or(path)
+            .to_string_lossy()
+    }
+
+    fn create(&self) -> Option<compositor::Callback> {
+        let mut line = self.relative_path(self.selected_directory()).into_owned();
+        if !line.is_empty() {
+            line.push(std::path::MAIN_SEPARATOR);
+        }
+        explorer_prompt(self.root.clone(), "create:", line, |root, input, editor| {
+            let path = root.join(input);
+            if path.exists() {
+                anyhow::bail!("'{}' already exists", path.display());
+            }
+            editor.create_path(&path, input.ends_with(std::path::is_separator))?;
+            Ok(path)
+        })
+    }
+
+    fn rename(&self) -> Option<compositor::Callback> {
+        let old_path = self.selected()?.path.clone();
+        let line = self.relative_path(&old_path).into_owned();
+        explorer_prompt(
+            self.root.clone(),
+            "move:",
+            line,
+            move |root, input, editor| {
+                let new_path = root.join(input);
+                if new_path.exists() {
+                    anyhow::bail!("'{}' already exists", new_path.display());
+                }
+                if let Some(parent) = new_path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                editor.move_path(&old_path, &new_path)?;
+                Ok(new_path)
+            },
+        )
+    }
+
+    fn delete(&self, editor: &mut Editor) -> Option<compositor::Callback> {
+        let entry = self.selected()?;
+        // refuse up front rather than after the confirmation
+        if let Err(err) = editor.ensure_unmodified(&helix_stdx::path::canonicalize(&entry.path)) {
+            editor.set_error(err.to_string());
+            return None;
+        }
+        let path = entry.path.clone();
+        let name = self.relative_path(&path);
+        let prompt = if entry.is_dir {
+            format!("delete {name}/ and its contents? (y/n):")
+        } else {
+            format!("delete {name}? (y/n):")
+        };
+        explorer_prompt(
+            self.root.clone(),
+            prompt,
+            String::new(),
+            move |root, input, editor| {
+                if input == "y" {
+                    // closes the buffers of the deleted files
+                    editor.delete_path(&path, true)?;
+                }
+                Ok(path.parent().unwrap_or(root).to_path_buf())
+            },
+        )
+    }
+}
+
+/// Prompts for the input of a file operation. The panel is refreshed once the prompt is closed,
+/// revealing the path returned by `action`.
+fn explorer_prompt(
+    root: PathBuf,
+    prompt: impl Into<Cow<'static, str>>,
+    line: String,
+    action: impl Fn(&Path, &str, &mut Editor) -> anyhow::Result<PathBuf> + 'static,
+) -> Option<compositor::Callback> {
+    let prompt = prompt.into();
+    Some(Box::new(move |compositor, cx| {
+        let prompt = Prompt::new(
+            prompt,
+            None,
+            completers::none,
+            move |cx, input: &str, event: PromptEvent| {
+                let reveal = match event {
+                    PromptEvent::Validate if !input.is_empty() => {
+                        match action(&root, input, cx.editor) {
+                            Ok(path) => Some(path),
+                            Err(err) => {
+                                cx.editor.set_error(err.to_string());
+                                None
+                            }
+                        }
+                    }
+                    PromptEvent::Validate | PromptEvent::Abort => None,
+                    PromptEvent::Update => return,
+                };
+                let callback = Box::pin(async move {
+                    let call: Callback =
+                        Callback::EditorCompositor(Box::new(move |editor, compositor| {
+                            let Some(panel) = compositor
+                                .find::<ui::EditorView>()
+                                .and_then(|editor_view| editor_view.explorer_panel.as_mut())
+                            else {
+                                return;
+                            };
+                            match reveal {
+                                Some(path) => panel.reveal(&path, editor),
+                                None => panel.refresh(editor),
+                            }
+                        }));
+                    Ok(call)
+                });
+                cx.jobs.callback(callback);
+            },
+        )
+        .with_line(line, cx.editor);
+        compositor.push(Box::new(prompt));
+    }))
+}
+
+impl Component for ExplorerPanel {
+    fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        let Event::Key(event) = event else {
+            return EventResult::Ignored(None);
+        };
+        let last = self.entries.len().saturating_sub(1);
+
+        match *event {
+            key!('j') | key!(Down) | ctrl!('n') => self.cursor = (self.cursor + 1).min(last),
+            key!('k') | key!(Up) | ctrl!('p') => self.cursor = self.cursor.saturating_sub(1),
+            key!('g') | key!(Home) => self.cursor = 0,
+            key!('G') | key!(End) => self.cursor = last,
+            key!(Enter) | key!('l') | key!(Right) => self.open_selected(cx.editor, Action::Replace),
+            ctrl!('s') => self.open_selected(cx.editor, Action::HorizontalSplit),
+            ctrl!('v') => self.open_selected(cx.editor, Action::VerticalSplit),
+            key!('h') | key!(Left) => self.collapse_selected(cx.editor),
+            key!('a') => return EventResult::Consumed(self.create()),
+            key!('r') => return EventResult::Consumed(self.rename()),
+            key!('d') => return EventResult::Consumed(self.delete(cx.editor)),
+            key!('R') => self.refresh(cx.editor),
+            key!('f') => {
+                let path = helix_view::doc!(cx.editor).path().cloned();
+                if let Some(path) = path {
+                    self.reveal(&path, cx.editor);
+                }
+            }
+            key!(Esc) => self.focused = false,
+            key!('q') => {
+                let close: compositor::Callback = Box::new(|compositor: &mut Compositor, _| {
+                    if let Some(editor_view) = compositor.find::<ui::EditorView>() {
+                        editor_view.explorer_panel = None;
+                    }
+                });
+                return EventResult::Consumed(Some(close));
+            }
+            _ => return EventResult::Ignored(None),
+        }
+        EventResult::Consumed(None)
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        let theme = &cx.editor.theme;
+        let text_style = theme.get("ui.text");
+        let directory_style = theme.get("ui.text.directory");
+        let selected_style = theme.get("ui.menu.selected");
+        surface.clear_with(area, theme.get("ui.background"));
+
+        let root = self.root.file_name().map_or_else(
+            || self.root.to_string_lossy(),
+            |name| name.to_string_lossy(),
+        );
+        let title = format!(" Explorer: {root}");
+        let title_style = if self.focused {
+            theme.get("ui.statusline")
+        } else {
+            theme.get("ui.statusline.inactive")
+        };
+        surface.set_style(area.with_height(1), title_style);
+        surface.set_stringn(area.x, area.y, &title, area.width as usize, title_style);
+
+        let list_area = area.clip_top(1);
+        if self.entries.is_empty() {
+            surface.set_stringn(
+                list_area.x + 1,
+                list_area.y,
+                "Empty directory",
+                list_area.width.saturating_sub(1) as usize,
+                text_style,
+            );
+            return;
+        }
+
+        // keep the selected entry in view
+        let height = list_area.height as usize;
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if self.cursor >= self.scroll + height {
+            self.scroll = self.cursor + 1 - height;
+        }
+
+        let current = helix_view::doc!(cx.editor).path();
+        for (row, (index, entry)) in self
+            .entries
+            .iter()
+            .enumerate()
+            .skip(self.scroll)
+            .take(height)
+            .enumerate()
+        {
+            let y = list_area.y + row as u16;
+            let indent = " ".repeat(entry.depth * 2 + 1);
+            // flattened directories show the path from their parent entry
+            let parent = entry.path.parent().unwrap_or(&entry.path);
+            let name = entry
+                .path
+                .strip_prefix(parent)
+                .unwrap_or(&entry.path)
+                .to_string_lossy();
+            let (label, style) = if entry.is_dir {
+                let marker = if self.expanded.contains(&entry.path) {
+                    "▾"
+                } else {
+                    "▸"
+                };
+                (format!("{indent}{marker} {name}/"), directory_style)
+            } else if current == Some(&entry.path) {
+                (format!("{indent}  {name}"), theme.get("ui.text.focus"))
+            } else {
+                (format!("{indent}  {name}"), text_style)
+            };
+            surface.set_stringn(list_area.x, y, &label, list_area.width as usize, style);
+            if index == self.cursor && self.focused {
+                surface.set_style(
+                    Rect::new(list_area.x, y, list_area.width, 1),
+                    selected_style,
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,582 @@
+//! A panel at the bottom of the editor that tails the log: the end of `helix.log` followed by the
+//! messages logged while the panel is open. The JSON-RPC messages exchanged with language servers
+//! and debug adapters are listed by server, and the selected message is shown pretty-printed next
+//! to the request or response that belongs to it.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+
+use helix_view::{
+    graphics::{Rect, Style},
+    Theme,
+};
+use log::{Level, LevelFilter};
+use serde_json::Value;
+use tui::buffer::Buffer as Surface;
+
+use crate::{
+    compositor::{Callback, Component, Compositor, Context, Event, EventResult},
+    ctrl, key,
+    logging::{self, LogRecord},
+    ui,
+};
+
+/// The height of the panel including its title line.
+pub const PANEL_HEIGHT: u16 = 16;
+/// How much of the end of the log file is read when the panel is opened.
+const LOG_TAIL_BYTES: u64 = 256 * 1024;
+/// The number of entries kept by the panel, the oldest entries are dropped first.
+const MAX_ENTRIES: usize = 5000;
+
+/// A JSON-RPC message sent to or received from a language server or debug adapter.
+#[derive(Debug)]
+struct Traffic {
+    outgoing: bool,
+    message: Value,
+}
+
+impl Traffic {
+    /// A short description of the message, like `-> textDocument/hover #3`.
+    fn summary(&self) -> String {
+        let arrow = if self.outgoing { "->" } else { "<-" };
+        let message = &self.message;
+        let id = |id: &Value| match id {
+            Value::String(id) => format!(" #{id}"),
+            Value::Null => String::new(),
+            id => format!(" #{id}"),
+        };
+        // debug adapter protocol
+        if let Some(ty) = message["type"].as_str() {
+            let name = match ty {
+                "request" | "response" => message["command"].as_str(),
+                "event" => message["event"].as_str(),
+                _ => None,
+            };
+            let seq = match ty {
+                "response" => &message["request_seq"],
+                "request" => &message["seq"],
+                _ => &Value::Null,
+            };
+            return format!("{arrow} {ty} {}{}", name.unwrap_or_default(), id(seq));
+        }
+        match message["method"].as_str() {
+            Some(method) => format!("{arrow} {method}{}", id(&message["id"])),
+            None if message.get("error").is_some() => {
+                format!("{arrow} error{}", id(&message["id"]))
+            }
+            None => format!("{arrow} response{}", id(&message["id"])),
+        }
+    }
+
+    fn is_request(&self) -> bool {
+        match self.message.get("type") {
+            Some(ty) => ty == "request",
+            None => self.message.get("method").is_some() && self.message.get("id").is_some(),
+        }
+    }
+
+    /// Whether `other` is the response to this request or the request of this response.
+    fn pairs_with(&self, other: &Traffic) -> bool {
+        if self.outgoing == other.outgoing {
+            return false;
+        }
+        let (a, b) = (&self.message, &other.message);
+        if a.get("type").is_some() {
+            let is_response_to = |response: &Value, request: &Value| {
+                response["type"] == "response"
+                    && request["type"] == "request"
+                    && response["request_seq"] == request["seq"]
+            };
+            return is_response_to(a, b) || is_response_to(b, a);
+        }
+        a.get("id").is_some()
+            && a["id"] == b["id"]
+            && (a.get("method").is_some() != b.get("method").is_some())
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    record: LogRecord,
+    /// The language server or debug adapter that the record is about.
+    server: Option<String>,
+    traffic: Option<Traffic>,
+}
+
+impl Entry {
+    fn new(record: LogRecord) -> Self {
+        let (server, traffic) = match parse_traffic(&record) {
+            Some((server, traffic)) => (Some(server), traffic),
+            None => (None, None),
+        };
+        Self {
+            record,
+            server,
+            traffic,
+        }
+    }
+}
+
+/// Finds the server of the records of the transports, and the JSON-RPC message that they
+/// contain: `<server> -> <json>` for language servers and `[<id>] -> DAP <json>` for debug
+/// adapters.
+fn parse_traffic(record: &LogRecord) -> Option<(String, Option<Traffic>)> {
+    let traffic = |outgoing, json: &str| {
+        serde_json::from_str(json)
+            .ok()
+            .map(|message| Traffic { outgoing, message })
+    };
+    let message = record.message.as_str();
+    if record.target.starts_with("helix_lsp") {
+        let (server, rest) = message.split_once(' ')?;
+        let traffic = if let Some(json) = rest.strip_prefix("-> ") {
+            traffic(true, json)
+        } else if let Some(json) = rest.strip_prefix("<- ") {
+            traffic(false, json)
+        } else if rest.starts_with("err") {
+            None
+        } else {
+            return None;
+        };
+        Some((server.to_string(), traffic))
+    } else if record.target.starts_with("helix_dap") {
+        let (id, rest) = message.trim_start().strip_prefix('[')?.split_once("] ")?;
+        let traffic = if let Some(json) = rest.strip_prefix("-> DAP ") {
+            traffic(true, json)
+        } else if let Some(json) = rest.strip_prefix("<- DAP ") {
+            traffic(false, json)
+        } else {
+            None
+        };
+        Some((format!("dap#{id}"), traffic))
+    } else {
+        None
+    }
+}
+
+/// Reads the records at the end of the log file, a missing file has no records.
+fn read_log_tail() -> Vec<LogRecord> {
+    let read = || -> std::io::Result<String> {
+        let mut file = File::open(helix_loader::log_file())?;
+        let len = file.metadata()?.len();
+        file.seek(SeekFrom::Start(len.saturating_sub(LOG_TAIL_BYTES)))?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        let mut text = String::from_utf8_lossy(&bytes).into_owned();
+        // drop the line that was cut off
+        if len > LOG_TAIL_BYTES {
+            let start = text.find('\n').map_or(text.len(), |i| i + 1);
+            text.drain(..start);
+        }
+        Ok(text)
+    };
+    read()
+        .map(|text| logging::parse_log(&text))
+        .unwrap_or_default()
+}
+
+#[derive(Debug)]
+pub struct LogPanel {
+    /// Whether the panel receives the key events.
+    focused: bool,
+    entries: Vec<Entry>,
+    /// The number of captured records that were added to the entries.
+    captured: usize,
+    /// The index of the selected entry among the listed entries.
+    cursor: usize,
+    /// Whether the cursor stays on the newest entry as entries are added.
+    follow: bool,
+    /// The first displayed row.
+    scroll: usize,
+    /// The first displayed row of the selected message.
+    detail_scroll: usize,
+    /// Only entries that are at least as severe are listed.
+    min_level: LevelFilter,
+    /// Only the entries of this language server or debug adapter are listed.
+    server: Option<String>,
+}
+
+impl LogPanel {
+    /// Opens the panel, listing the entries of `server` if given. The records logged while the
+    /// panel is open are captured until it is dropped.
+    pub fn new(server: Option<String>) -> Self {
+        logging::capture(LevelFilter::Debug);
+        let (captured, _) = logging::captured_since(usize::MAX);
+        let entries = read_log_tail().into_iter().map(Entry::new).collect();
+        Self {
+            focused: true,
+            entries,
+            captured,
+            cursor: 0,
+            follow: true,
+            scroll: 0,
+            detail_scroll: 0,
+            min_level: LevelFilter::Trace,
+            server,
+        }
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    pub fn focus(&mut self) {
+        self.focused = true;
+    }
+
+    pub fn set_server(&mut self, server: Option<String>) {
+        self.server = server;
+        self.reset_cursor();
+    }
+
+    /// Adds the records that were captured since the last update.
+    fn update(&mut self) {
+        let (captured, records) = logging::captured_since(self.captured);
+        self.captured = captured;
+        self.entries.extend(records.into_iter().map(Entry::new));
+        if self.entries.len() > MAX_ENTRIES {
+            let dropped = self.entries.len() - MAX_ENTRIES;
+            self.entries.drain(..dropped);
+            self.cursor = self.cursor.saturating_sub(dropped);
+        }
+    }
+
+    fn is_listed(&self, entry: &Entry) -> bool {
+        entry.record.level <= self.min_level
+            && self
+                .server
+                .as_ref()
+                .is_none_or(|server| entry.server.as_ref() == Some(server))
+    }
+
+    /// The indices of the listed entries.
+    fn listed(&self) -> Vec<usize> {
+        (0..self.entries.len())
+            .filter(|&i| self.is_listed(&self.entries[i]))
+            .collect()
+    }
+
+    /// The language servers and debug adapters that have entries, sorted by name.
+    fn servers(&self) -> Vec<&str> {
+        let mut servers: Vec<_> = self
+            .entries
+            .iter()
+            .filter_map(|entry| entry.server.as_deref())
+            .collect();
+        servers.sort_unstable();
+        servers.dedup();
+        servers
+    }
+
+    fn reset_cursor(&mut self) {
+        self.follow = true;
+        self.scroll = 0;
+        self.detail_scroll = 0;
+    }
+
+    fn cycle_level_filter(&mut self) {
+        self.min_level = match self.min_level {
+            LevelFilter::Trace => LevelFilter::Debug,
+            LevelFilter::Debug => LevelFilter::Info,
+            LevelFilter::Info => LevelFilter::Warn,
+            LevelFilter::Warn => LevelFilter::Error,
+            _ => LevelFilter::Trace,
+        };
+        self.reset_cursor();
+    }
+
+    fn cycle_server_filter(&mut self) {
+        let servers = self.servers();
+        let next = match &self.server {
+            None => servers.first(),
+            Some(server) => servers
+                .iter()
+                .position(|s| *s == server.as_str())
+                .and_then(|i| servers.get(i + 1)),
+        };
+        self.server = next.map(|server| server.to_string());
+        self.reset_cursor();
+    }
+
+    fn move_cursor(&mut self, cursor: usize, count: usize) {
+        self.cursor = cursor.min(count.saturating_sub(1));
+        self.follow = self.cursor + 1 >= count;
+        self.detail_scroll = 0;
+    }
+
+    /// The lines shown for the selected entry: the pretty-printed message followed by the
+    /// request or response that belongs to it.
+    fn detail(&self, listed: &[usize]) -> Vec<(String, bool)> {
+        let Some(&index) = listed.get(self.cursor) else {
+            return Vec::new();
+        };
+        let entry = &self.entries[index];
+        let Some(traffic) = &entry.traffic else {
+            return entry
+                .record
+                .message
+                .lines()
+                .map(|line| (line.to_string(), false))
+                .collect();
+        };
+        let pairs_with = |other: &&Entry| {
+            other.server == entry.server
+                && other
+                    .traffic
+                    .as_ref()
+                    .is_some_and(|other| traffic.pairs_with(other))
+        };
+        // requests are followed by their responses, responses are preceded by their requests
+        let pair = if traffic.is_request() {
+            self.entries[index + 1..].iter().find(pairs_with)
+        } else {
+            self.entries[..index].iter().rev().find(pairs_with)
+        };
+
+        let mut lines = Vec::new();
+        for traffic in std::iter::once(traffic).chain(pair.and_then(|pair| pair.traffic.as_ref())) {
+            if !lines.is_empty() {
+                lines.push((String::new(), false));
+            }
+            lines.push((traffic.summary(), true));
+            let pretty = serde_json::to_string_pretty(&traffic.message).unwrap_or_default();
+            lines.extend(pretty.lines().map(|line| (line.to_string(), false)));
+        }
+        lines
+    }
+}
+
+impl Drop for LogPanel {
+    fn drop(&mut self) {
+        logging::capture(LevelFilter::Off);
+    }
+}
+
+fn level_style(theme: &Theme, level: Level) -> Style {
+    match level {
+        Level::Error => theme.get("error"),
+        Level::Warn => theme.get("warning"),
+        Level::Info => theme.get("info"),
+        Level::Debug | Level::Trace => theme.get("hint"),
+    }
+}
+
+impl Component for LogPanel {
+    fn handle_event(&mut self, event: &Event, _cx: &mut Context) -> EventResult {
+        let Event::Key(event) = event else {
+            return EventResult::Ignored(None);
+        };
+        self.update();
+        let count = self.listed().len();
+
+        match *event {
+            key!('j') | key!(Down) | ctrl!('n') => self.move_cursor(self.cursor + 1, count),
+            key!('k') | key!(Up) | ctrl!('p') => {
+                self.move_cursor(self.cursor.saturating_sub(1), count)
+            }
+            key!('g') | key!(Home) => self.move_cursor(0, count),
+            key!('G') | key!(End) => self.move_cursor(count, count),
+            ctrl!('d') | key!(PageDown) => self.detail_scroll += PANEL_HEIGHT as usize / 2,
+            ctrl!('u') | key!(PageUp) => {
+                self.detail_scroll = self.detail_scroll.saturating_sub(PANEL_HEIGHT as usize / 2)
+            }
+            key!('f') => self.cycle_level_filter(),
+            key!('s') => self.cycle_server_filter(),
+            key!(Esc) => self.focused = false,
+            key!('q') => {
+                let close: Callback = Box::new(|compositor: &mut Compositor, _| {
+                    if let Some(editor_view) = compositor.find::<ui::EditorView>() {
+                        editor_view.log_panel = None;
+                    }
+                });
+                return EventResult::Consumed(Some(close));
+            }
+            _ => return EventResult::Ignored(None),
+        }
+        EventResult::Consumed(None)
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        self.update();
+        let theme = &cx.editor.theme;
+        let text_style = theme.get("ui.text");
+        let inactive_style = theme.get("ui.text.inactive");
+        let server_style = theme.get("ui.text.focus");
+        let selected_style = theme.get("ui.menu.selected");
+        surface.clear_with(area, theme.get("ui.background"));
+
+        let listed = self.listed();
+        let count = listed.len();
+        if self.follow {
+            self.cursor = count.saturating_sub(1);
+        }
+        self.cursor = self.cursor.min(count.saturating_sub(1));
+
+        let level = match self.min_level {
+            LevelFilter::Trace => "all levels".to_string(),
+            level => format!("{} and above", level.as_str().to_lowercase()),
+        };
+        let server = self.server.as_deref().unwrap_or("all servers");
+        let title = format!(" Log ({count}), showing {level}, {server}");
+        let title_style = if self.focused {
+            theme.get("ui.statusline")
+        } else {
+            theme.get("ui.statusline.inactive")
+        };
+        surface.set_style(area.with_height(1), title_style);
+        surface.set_stringn(area.x, area.y, &title, area.width as usize, title_style);
+
+        let mut list_area = area.clip_top(1);
+        if count == 0 {
+            surface.set_stringn(
+                list_area.x + 1,
+                list_area.y,
+                "No log messages",
+                list_area.width.saturating_sub(1) as usize,
+                text_style,
+            );
+            return;
+        }
+
+        // the selected message is shown on the right when there is room for it
+        if list_area.width >= 80 {
+            let width = list_area.width / 2;
+            let detail_area = list_area.clip_left(list_area.width - width);
+            list_area = list_area.clip_right(width + 1);
+            let separator_style = theme.get("ui.window");
+            for y in detail_area.top()..detail_area.bottom() {
+                surface[(detail_area.x - 1, y)]
+                    .set_symbol(tui::symbols::line::VERTICAL)
+                    .set_style(separator_style);
+            }
+
+            let detail = self.detail(&listed);
+            let height = detail_area.height as usize;
+            self.detail_scroll = self.detail_scroll.min(detail.len().saturating_sub(height));
+            for (row, (line, is_header)) in detail
+                .iter()
+                .skip(self.detail_scroll)
+                .take(height)
+                .enumerate()
+            {
+                let style = if *is_header { server_style } else { text_style };
+                surface.set_stringn(
+                    detail_area.x + 1,
+                    detail_area.y + row as u16,
+                    line,
+                    detail_area.width.saturating_sub(1) as usize,
+                    style,
+                );
+            }
+        }
+
+        // keep the selected entry in view
+        let height = list_area.height as usize;
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if self.cursor >= self.scroll + height {
+            self.scroll = self.cursor + 1 - height;
+        }
+
+        for (row, &index) in listed.iter().skip(self.scroll).take(height).enumerate() {
+            let entry = &self.entries[index];
+            let y = list_area.y + row as u16;
+            let right = list_area.right();
+            let remaining = |x: u16| right.saturating_sub(x) as usize;
+
+            let time = entry
+                .record
+                .timestamp
+                .split_once('T')
+                .map_or(entry.record.timestamp.as_str(), |(_, time)| time);
+            let (x, _) = surface.set_stringn(
+                list_area.x + 1,
+                y,
+                &format!("{time} "),
+                remaining(list_area.x + 1),
+                inactive_style,
+            );
+            let level = entry.record.level;
+            let (x, _) = surface.set_stringn(
+                x,
+                y,
+                &format!("{:<6}", level.as_str()),
+                remaining(x),
+                level_style(theme, level),
+            );
+            let (x, message) = match (&entry.server, &entry.traffic) {
+                (Some(server), traffic) => {
+                    let (x, _) = surface.set_stringn(
+                        x,
+                        y,
+                        &format!("{server} "),
+                        remaining(x),
+                        server_style,
+                    );
+                    let message = match traffic {
+                        Some(traffic) => traffic.summary(),
+                        None => entry.record.message.clone(),
+                    };
+                    (x, message)
+                }
+                (None, _) => (x, entry.record.message.clone()),
+            };
+            let message = message.lines().next().unwrap_or_default();
+            surface.set_stringn(x, y, message, remaining(x), text_style);
+            if row + self.scroll == self.cursor && self.focused {
+                surface.set_style(
+                    Rect::new(list_area.x, y, list_area.width, 1),
+                    selected_style,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(target: &str, message: &str) -> Entry {
+        Entry::new(LogRecord {
+            timestamp: "2024-02-29T12:30:45.123".into(),
+            level: Level::Info,
+            target: target.into(),
+            message: message.into(),
+        })
+    }
+
+    #[test]
+    fn traffic_is_parsed_and_paired() {
+        let request = entry(
+            "helix_lsp::transport",
+            r#"rust-analyzer -> {"jsonrpc":"2.0","method":"textDocument/hover","params":{},"id":3}"#,
+        );
+        let response = entry(
+            "helix_lsp::transport",
+            r#"rust-analyzer <- {"jsonrpc":"2.0","result":null,"id":3}"#,
+        );
+        assert_eq!(request.server.as_deref(), Some("rust-analyzer"));
+        let (request, response) = (request.traffic.unwrap(), response.traffic.unwrap());
+        assert_eq!(request.summary(), "-> textDocument/hover #3");
+        assert_eq!(response.summary(), "<- response #3");
+        assert!(request.pairs_with(&response));
+
+        let dap_request = entry(
+            "helix_dap::transport",
+            r#"[1] -> DAP {"seq":4,"type":"request","command":"threads"}"#,
+        );
+        let dap_response = entry(
+            "helix_dap::transport",
+            r#"[1] <- DAP {"seq":9,"type":"response","request_seq":4,"command":"threads"}"#,
+        );
+        assert_eq!(dap_request.server.as_deref(), Some("dap#1"));
+        let dap_request = dap_request.traffic.unwrap();
+        assert_eq!(dap_request.summary(), "-> request threads #4");
+        assert!(dap_request.pairs_with(&dap_response.traffic.unwrap()));
+        assert!(!dap_request.pairs_with(&request));
+
+        let other = entry("helix_term::application", "rust-analyzer -> is not traffic");
+        assert!(other.server.is_none() && other.traffic.is_none());
+    }
+}
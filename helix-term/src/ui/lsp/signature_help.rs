@@ -4,6 +4,7 @@
 use helix_core::syntax::{self, OverlayHighlights};
 use helix_view::graphics::{Margin, Rect, Style};
 use helix_view::input::Event;
+use helix_view::Theme;
 use tui::buffer::Buffer;
 use tui::layout::Alignment;
 use tui::text::Text;
@@ -19,8 +20,31 @@
 pub struct Signature {
     pub signature: String,
     pub signature_doc: Option<String>,
+    /// The parts of the signature text that are the parameters.
+    pub parameters: Vec<(usize, usize)>,
+    pub active_parameter: Option<usize>,
+}
+
+impl Signature {
     /// Part of signature text
-    pub active_param_range: Option<(usize, usize)>,
+    fn active_param_range(&self) -> Option<(usize, usize)> {
+        self.parameters.get(self.active_parameter?).copied()
+    }
+
+    /// Highlights the next parameter, or the previous one if `forward` is false.
+    fn cycle_parameter(&mut self, forward: bool) -> bool {
+        let len = self.parameters.len();
+        if len == 0 {
+            return false;
+        }
+        self.active_parameter = Some(match (self.active_parameter, forward) {
+            (Some(param), true) => (param + 1) % len,
+            (Some(param), false) => param.checked_sub(1).unwrap_or(len - 1),
+            (None, true) => 0,
+            (None, false) => len - 1,
+        });
+        true
+    }
 }
 
 pub struct SignatureHelp {
@@ -65,6 +89,45 @@ pub fn visible_popup(compositor: &mut Compositor) -> Option<&mut Popup<Self>> {
     fn signature_index(&self) -> String {
         format!("({}/{})", self.active_signature + 1, self.signatures.len())
     }
+
+    fn signature(&self) -> &Signature {
+        self.signatures
+            .get(self.active_signature)
+            .unwrap_or_else(|| &self.signatures[0])
+    }
+
+    /// The text of all overloads with the active one highlighted.
+    fn signatures_text(&self, theme: Option<&Theme>) -> Text<'static> {
+        let loader = self.config_loader.load();
+        let inactive_style = theme
+            .map(|theme| theme.get("ui.text.inactive"))
+            .unwrap_or_default();
+        let active_signature = self.active_signature.min(self.signatures.len() - 1);
+
+        let mut text = Text::default();
+        for (i, signature) in self.signatures.iter().enumerate() {
+            let lines = if i == active_signature {
+                let active_param_span =
+                    theme
+                        .zip(signature.active_param_range())
+                        .map(|(theme, (start, end))| {
+                            let highlight = theme.find_highlight_exact("ui.selection").unwrap();
+                            OverlayHighlights::single(highlight, start..end)
+                        });
+                crate::ui::markdown::highlighted_code_block(
+                    signature.signature.as_str(),
+                    &self.language,
+                    theme,
+                    &loader,
+                    active_param_span,
+                )
+            } else {
+                Text::styled(signature.signature.clone(), inactive_style)
+            };
+            text.lines.extend(lines.lines);
+        }
+        text
+    }
 }
 
 impl Component for SignatureHelp {
@@ -73,22 +136,27 @@ fn handle_event(&mut self, event: &Event, _cx: &mut Context) -> EventResult {
             return EventResult::Ignored(None);
         };
 
-        if self.signatures.len() <= 1 {
-            return EventResult::Ignored(None);
-        }
-
+        let signatures = self.signatures.len();
         match event {
-            alt!('p') => {
+            alt!('p') if signatures > 1 => {
                 self.active_signature = self
                     .active_signature
                     .checked_sub(1)
-                    .unwrap_or(self.signatures.len() - 1);
+                    .unwrap_or(signatures - 1);
                 EventResult::Consumed(None)
             }
-            alt!('n') => {
-                self.active_signature = (self.active_signature + 1) % self.signatures.len();
+            alt!('n') if signatures > 1 => {
+                self.active_signature = (self.active_signature + 1) % signatures;
                 EventResult::Consumed(None)
             }
+            alt!(',') | alt!('.') => {
+                let active_signature = self.active_signature.min(signatures - 1);
+                if self.signatures[active_signature].cycle_parameter(*event == alt!('.')) {
+                    EventResult::Consumed(None)
+                } else {
+                    EventResult::Ignored(None)
+                }
+            }
             _ => EventResult::Ignored(None),
         }
     }
@@ -97,32 +165,8 @@ fn render(&mut self, area: Rect, surface: &mut Buffer, cx: &mut Context) {
         let margin = Margin::all(1);
         let area = area.inner(margin);
 
-        let signature = self
-            .signatures
-            .get(self.active_signature)
-            .unwrap_or_else(|| &self.signatures[0]);
-
-        let active_param_span = signature.active_param_range.map(|(start, end)| {
-            let highlight = cx
-                .editor
-                .theme
-                .find_highlight_exact("ui.selection")
-                .unwrap();
-            OverlayHighlights::single(highlight, start..end)
-        });
-
-        let signature = self
-            .signatures
-            .get(self.active_signature)
-            .unwrap_or_else(|| &self.signatures[0]);
-
-        let sig_text = crate::ui::markdown::highlighted_code_block(
-            signature.signature.as_str(),
-            &self.language,
-            Some(&cx.editor.theme),
-            &self.config_loader.load(),
-            active_param_span,
-        );
+        let sig_text = self.signatures_text(Some(&cx.editor.theme));
+        let signature = self.signature();
 
         if self.signatures.len() > 1 {
             let signature_index = self.signature_index();
@@ -169,20 +213,11 @@ fn required_size(&mut self, viewport: (u16, u16)) -> Option<(u16, u16)> {
         const PADDING: u16 = 2;
         const SEPARATOR_HEIGHT: u16 = 1;
 
-        let signature = self
-            .signatures
-            .get(self.active_signature)
-            .unwrap_or_else(|| &self.signatures[0]);
+        let signature = self.signature();
 
         let max_text_width = viewport.0.saturating_sub(PADDING).clamp(10, 120);
 
-        let signature_text = crate::ui::markdown::highlighted_code_block(
-            signature.signature.as_str(),
-            &self.language,
-            None,
-            &self.config_loader.load(),
-            None,
-        );
+        let signature_text = self.signatures_text(None);
         let sig_text_para = Paragraph::new(&signature_text).wrap(Wrap { trim: false });
         let (sig_width, sig_height) = sig_text_para.required_size(max_text_width);
 
@@ -209,3 +244,31 @@ fn required_size(&mut self, viewport: (u16, u16)) -> Option<(u16, u16)> {
         Some((width + PADDING + sig_index_width as u16, height + PADDING))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycle_parameter_wraps_around() {
+        let mut signature = Signature {
+            signature: "fn f(a: u8, b: u8)".into(),
+            signature_doc: None,
+            parameters: vec![(5, 10), (12, 17)],
+            active_parameter: None,
+        };
+        assert_eq!(signature.active_param_range(), None);
+        assert!(signature.cycle_parameter(false));
+        assert_eq!(signature.active_param_range(), Some((12, 17)));
+        assert!(signature.cycle_parameter(true));
+        assert_eq!(signature.active_param_range(), Some((5, 10)));
+        assert!(signature.cycle_parameter(false));
+        assert_eq!(signature.active_parameter, Some(1));
+
+        // signatures without parameters can't be cycled
+        signature.parameters.clear();
+        signature.active_parameter = None;
+        assert!(!signature.cycle_parameter(true));
+        assert_eq!(signature.active_parameter, None);
+    }
+}
@@ -2,10 +2,21 @@
 use arc_swap::ArcSwap;
 use tui::{
     buffer::Buffer as Surface,
+    image::Image,
     text::{Span, Spans, Text},
 };
 
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
 
 use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
 
@@ -137,8 +148,34 @@ pub fn highlighted_code_block<'a>(
     Text::from(lines)
 }
 
+/// The images referenced by markdown contents by their path. Images are read in the background
+/// and only once, `None` while an image is read or if it can't be displayed.
+static IMAGES: Lazy<Mutex<HashMap<PathBuf, Option<Image>>>> = Lazy::new(Default::default);
+/// Whether the terminal could display images when markdown was last rendered. Markdown
+/// components are often created anew for every render, so this can't be stored in them.
+static SHOW_IMAGES: AtomicBool = AtomicBool::new(false);
+
+/// Returns the image at `path` if it was read already, otherwise starts reading it.
+fn cached_image(path: &Path) -> Option<Image> {
+    let mut images = IMAGES.lock();
+    if let Some(image) = images.get(path) {
+        return image.clone();
+    }
+    images.insert(path.to_path_buf(), None);
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        if let Some(image) = Image::load(&path) {
+            IMAGES.lock().insert(path, Some(image));
+            helix_event::request_redraw();
+        }
+    });
+    None
+}
+
 pub struct Markdown {
     contents: String,
+    /// The local images referenced by the contents, displayed below the text.
+    image_paths: Vec<PathBuf>,
 
     config_loader: Arc<ArcSwap<syntax::Loader>>,
 }
@@ -161,14 +198,62 @@ impl Markdown {
         "markup.heading.6",
     ];
     const INDENT: &'static str = "  ";
+    /// The maximum number of rows of an image.
+    const IMAGE_HEIGHT: u16 = 12;
 
     pub fn new(contents: String, config_loader: Arc<ArcSwap<syntax::Loader>>) -> Self {
+        let image_paths = Self::image_paths(&contents);
         Self {
             contents,
+            image_paths,
             config_loader,
         }
     }
 
+    /// The images with an absolute path or a `file://` URL. Remote images are not downloaded.
+    fn image_paths(contents: &str) -> Vec<PathBuf> {
+        if !contents.contains("![") {
+            return Vec::new();
+        }
+        Parser::new(contents)
+            .filter_map(|event| match event {
+                Event::Start(Tag::Image { dest_url, .. }) => {
+                    let path = dest_url.strip_prefix("file://").unwrap_or(&dest_url);
+                    let path = Path::new(path);
+                    path.is_absolute().then(|| path.to_path_buf())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The images that were read already, if the terminal can display them.
+    fn images(&self) -> Vec<Image> {
+        if !SHOW_IMAGES.load(Ordering::Relaxed) {
+            return Vec::new();
+        }
+        self.image_paths
+            .iter()
+            .filter_map(|path| cached_image(path))
+            .collect()
+    }
+
+    /// The areas of the images below `text_height` rows of text, relative to the top left of
+    /// the text.
+    fn image_areas(images: &[Image], width: u16, text_height: u16) -> Vec<Rect> {
+        let mut y = text_height;
+        images
+            .iter()
+            .map(|image| {
+                // leave an empty line between the text and each image
+                y = y.saturating_add(1);
+                let image_area = image.fit(Rect::new(0, y, width, Self::IMAGE_HEIGHT));
+                y = y.saturating_add(image_area.height);
+                image_area
+            })
+            .collect()
+    }
+
     pub fn parse(&self, theme: Option<&Theme>) -> tui::text::Text<'_> {
         fn push_line<'a>(spans: &mut Vec<Span<'a>>, lines: &mut Vec<Spans<'a>>) {
             let spans = std::mem::take(spans);
@@ -376,7 +461,37 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
             .scroll((cx.scroll.unwrap_or_default() as u16, 0));
 
         let margin = Margin::all(1);
-        par.render(area.inner(margin), surface);
+        let inner = area.inner(margin);
+        par.render(inner, surface);
+
+        if self.image_paths.is_empty() {
+            return;
+        }
+        let show_images = surface.supports_images();
+        if SHOW_IMAGES.swap(show_images, Ordering::Relaxed) != show_images {
+            // the size of the popup depends on whether the images are displayed
+            helix_event::request_redraw();
+        }
+        let images = self.images();
+        if !images.is_empty() {
+            let (_, text_height) = crate::ui::text::required_size(&text, inner.width);
+            let scroll = cx.scroll.unwrap_or_default() as u16;
+            for (image_area, image) in Self::image_areas(&images, inner.width, text_height)
+                .into_iter()
+                .zip(images)
+            {
+                // images that are scrolled out of view or don't fit are left out
+                if image_area.top() < scroll || image_area.bottom() - scroll > inner.height {
+                    continue;
+                }
+                let image_area = Rect {
+                    x: inner.x,
+                    y: inner.y + image_area.y - scroll,
+                    ..image_area
+                };
+                surface.set_image(image_area, image);
+            }
+        }
     }
 
     fn required_size(&mut self, viewport: (u16, u16)) -> Option<(u16, u16)> {
@@ -385,7 +500,14 @@ fn required_size(&mut self, viewport: (u16, u16)) -> Option<(u16, u16)> {
 
         // TODO: account for tab width
         let max_text_width = (viewport.0.saturating_sub(padding)).min(120);
-        let (width, height) = crate::ui::text::required_size(&contents, max_text_width);
+        let (mut width, mut height) = crate::ui::text::required_size(&contents, max_text_width);
+        let images = self.images();
+        if !images.is_empty() {
+            for image_area in Self::image_areas(&images, max_text_width, height) {
+                width = width.max(image_area.width);
+                height = image_area.bottom();
+            }
+        }
 
         Some((width + padding, height + padding))
     }
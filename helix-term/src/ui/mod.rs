@@ -1,7 +1,11 @@
 mod completion;
+mod diagnostics_panel;
+mod diff_view;
 mod document;
 pub(crate) mod editor;
+mod explorer;
 mod info;
+mod log_panel;
 pub mod lsp;
 mod markdown;
 pub mod menu;
@@ -9,24 +13,33 @@
 pub mod picker;
 pub mod popup;
 pub mod prompt;
+mod rename_preview;
+mod replace_confirm;
 mod select;
 mod spinner;
 mod statusline;
+pub mod terminal;
 mod text;
 mod text_decorations;
 
 use crate::compositor::Compositor;
-use crate::filter_picker_entry;
 use crate::job::{self, Callback};
+use crate::{alt, filter_picker_entry};
 pub use completion::Completion;
+pub use diagnostics_panel::DiagnosticsPanel;
+pub use diff_view::{DiffSource, DiffView};
 pub use editor::EditorView;
+pub use explorer::ExplorerPanel;
 use helix_stdx::rope;
 use helix_view::theme::Style;
+pub use log_panel::LogPanel;
 pub use markdown::Markdown;
 pub use menu::Menu;
 pub use picker::{Column as PickerColumn, FileLocation, Picker};
 pub use popup::Popup;
 pub use prompt::{Prompt, PromptEvent};
+pub use rename_preview::RenamePreview;
+pub use replace_confirm::{ReplaceConfirm, Replacement};
 pub use select::Select;
 pub use spinner::{ProgressSpinners, Spinner};
 pub use text::Text;
@@ -34,7 +47,10 @@
 use helix_view::Editor;
 use tui::text::{Span, Spans};
 
+use std::borrow::Cow;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::{error::Error, path::PathBuf};
 
 struct Utf8PathBuf {
@@ -103,6 +119,11 @@ pub fn raw_regex_prompt(
     let snapshot = doc.selection(view.id).clone();
     let offset_snapshot = doc.view_offset(view.id);
     let config = cx.editor.config();
+    let case_sensitive = Arc::new(AtomicBool::new(false));
+    let whole_word = Arc::new(AtomicBool::new(false));
+    let (case_sensitive_toggle, whole_word_toggle) = (case_sensitive.clone(), whole_word.clone());
+    let (case_sensitive_history, whole_word_history) = (case_sensitive.clone(), whole_word.clone());
+    let smart_case = config.search.smart_case;
 
     let mut prompt = Prompt::new(
         prompt,
@@ -122,11 +143,17 @@ pub fn raw_regex_prompt(
                         return;
                     }
 
-                    let case_insensitive = if config.search.smart_case {
-                        !input.chars().any(char::is_uppercase)
-                    } else {
-                        false
-                    };
+                    let case_sensitive = case_sensitive.load(Ordering::Relaxed);
+                    let case_insensitive = config.search.smart_case
+                        && !case_sensitive
+                        && !input.chars().any(char::is_uppercase);
+
+                    let pattern = toggled_pattern(
+                        input,
+                        case_sensitive,
+                        whole_word.load(Ordering::Relaxed),
+                        config.search.smart_case,
+                    );
 
                     let is_crlf = doc!(cx.editor).line_ending == helix_core::LineEnding::Crlf;
                     match rope::RegexBuilder::new()
@@ -136,7 +163,7 @@ pub fn raw_regex_prompt(
                                 .multi_line(true)
                                 .crlf(is_crlf),
                         )
-                        .build(input)
+                        .build(&pattern)
                     {
                         Ok(regex) => {
                             let doc = doc_mut!(cx.editor, &doc_id);
@@ -150,7 +177,7 @@ pub fn raw_regex_prompt(
                                 view.push_jump(doc, (doc_id, snapshot.clone()));
                             }
 
-                            fun(cx, regex, input, event);
+                            fun(cx, regex, &pattern, event);
 
                             let (view, doc) = current!(cx.editor);
                             view.ensure_cursor_in_view(doc, config.scrolloff);
@@ -187,13 +214,43 @@ pub fn raw_regex_prompt(
             }
         },
     )
-    .with_language("regex", std::sync::Arc::clone(&cx.editor.syn_loader));
+    .with_language("regex", std::sync::Arc::clone(&cx.editor.syn_loader))
+    .with_toggle(alt!('c'), "case", case_sensitive_toggle)
+    .with_toggle(alt!('w'), "word", whole_word_toggle)
+    // searching for the next match with the history register keeps the toggles
+    .with_history_fn(move |input| {
+        toggled_pattern(
+            input,
+            case_sensitive_history.load(Ordering::Relaxed),
+            whole_word_history.load(Ordering::Relaxed),
+            smart_case,
+        )
+        .into_owned()
+    });
     // Calculate initial completion
     prompt.recalculate_completion(cx.editor);
     // prompt
     cx.push_layer(Box::new(prompt));
 }
 
+/// Applies the toggles of a regex prompt to its input. Case sensitive matching only needs a flag
+/// when smart case would otherwise match the input case insensitively.
+fn toggled_pattern(
+    input: &str,
+    case_sensitive: bool,
+    whole_word: bool,
+    smart_case: bool,
+) -> Cow<'_, str> {
+    let mut pattern = Cow::Borrowed(input);
+    if whole_word {
+        pattern = Cow::Owned(format!(r"\b(?:{input})\b"));
+    }
+    if case_sensitive && smart_case && !input.chars().any(char::is_uppercase) {
+        pattern = Cow::Owned(format!("(?-i){pattern}"));
+    }
+    pattern
+}
+
 /// We want to exclude files that the editor can't handle yet
 fn get_excluded_types() -> ignore::types::Types {
     use ignore::types::TypesBuilder;
@@ -212,27 +269,41 @@ fn get_excluded_types() -> ignore::types::Types {
 
 #[derive(Debug)]
 pub struct FilePickerData {
-    root: PathBuf,
+    roots: Vec<PathBuf>,
     directory_style: Style,
 }
 type FilePicker = Picker<PathBuf, FilePickerData>;
 
 pub fn file_picker(editor: &Editor, root: PathBuf) -> FilePicker {
+    workspace_file_picker(editor, vec![root])
+}
+
+/// Opens a file picker listing the files of all `roots`.
+///
+/// When there is more than one root, paths are shown relative to the root that contains
+/// them, prefixed with the name of that root.
+pub fn workspace_file_picker(editor: &Editor, roots: Vec<PathBuf>) -> FilePicker {
     use ignore::WalkBuilder;
     use std::time::Instant;
 
     let config = editor.config();
     let data = FilePickerData {
-        root: root.clone(),
+        roots: roots.clone(),
         directory_style: editor.theme.get("ui.text.directory"),
     };
 
     let now = Instant::now();
 
     let dedup_symlinks = config.file_picker.deduplicate_links;
-    let absolute_root = root.canonicalize().unwrap_or_else(|_| root.clone());
+    let absolute_roots: Vec<_> = roots
+        .iter()
+        .map(|root| root.canonicalize().unwrap_or_else(|_| root.clone()))
+        .collect();
 
-    let mut walk_builder = WalkBuilder::new(&root);
+    let mut walk_builder = WalkBuilder::new(&roots[0]);
+    for root in &roots[1..] {
+        walk_builder.add(root);
+    }
 
     let mut files = walk_builder
         .hidden(config.file_picker.hidden)
@@ -244,7 +315,11 @@ pub fn file_picker(editor: &Editor, root: PathBuf) -> FilePicker {
         .git_exclude(config.file_picker.git_exclude)
         .sort_by_file_name(|name1, name2| name1.cmp(name2))
         .max_depth(config.file_picker.max_depth)
-        .filter_entry(move |entry| filter_picker_entry(entry, &absolute_root, dedup_symlinks))
+        .filter_entry(move |entry| {
+            absolute_roots
+                .iter()
+                .all(|root| filter_picker_entry(entry, root, dedup_symlinks))
+        })
         .add_custom_ignore_filename(helix_loader::config_dir().join("ignore"))
         .add_custom_ignore_filename(".helix/ignore")
         .types(get_excluded_types())
@@ -261,8 +336,20 @@ pub fn file_picker(editor: &Editor, root: PathBuf) -> FilePicker {
     let columns = [PickerColumn::new(
         "path",
         |item: &PathBuf, data: &FilePickerData| {
-            let path = item.strip_prefix(&data.root).unwrap_or(item);
-            let mut spans = Vec::with_capacity(3);
+            let root = data.roots.iter().find(|root| item.starts_with(root));
+            let path = root
+                .and_then(|root| item.strip_prefix(root).ok())
+                .unwrap_or(item);
+            let mut spans = Vec::with_capacity(5);
+            if let Some(name) = root
+                .filter(|_| data.roots.len() > 1)
+                .and_then(|root| root.file_name())
+            {
+                spans.extend([
+                    Span::styled(name.to_string_lossy(), data.directory_style),
+                    Span::styled(std::path::MAIN_SEPARATOR_STR, data.directory_style),
+                ]);
+            }
             if let Some(dirs) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
                 spans.extend([
                     Span::styled(dirs.to_string_lossy(), data.directory_style),
@@ -315,9 +402,21 @@ pub fn file_picker(editor: &Editor, root: PathBuf) -> FilePicker {
 
 type FileExplorer = Picker<(PathBuf, bool), (PathBuf, Style)>;
 
-pub fn file_explorer(root: PathBuf, editor: &Editor) -> Result<FileExplorer, std::io::Error> {
+pub fn file_explorer(
+    root: PathBuf,
+    reveal: Option<&Path>,
+    editor: &Editor,
+) -> Result<FileExplorer, std::io::Error> {
     let directory_style = editor.theme.get("ui.text.directory");
     let directory_content = directory_content(&root, editor)?;
+    // select the entry containing `reveal`
+    let cursor = reveal
+        .and_then(|reveal| {
+            directory_content
+                .iter()
+                .position(|(path, _)| reveal.starts_with(path))
+        })
+        .unwrap_or(0);
 
     let columns = [PickerColumn::new(
         "path",
@@ -341,7 +440,7 @@ pub fn file_explorer(root: PathBuf, editor: &Editor) -> Result<FileExplorer, std
                 let callback = Box::pin(async move {
                     let call: Callback =
                         Callback::EditorCompositor(Box::new(move |editor, compositor| {
-                            if let Ok(picker) = file_explorer(new_root, editor) {
+                            if let Ok(picker) = file_explorer(new_root, None, editor) {
                                 compositor.push(Box::new(overlay::overlaid(picker)));
                             }
                         }));
@@ -358,11 +457,124 @@ pub fn file_explorer(root: PathBuf, editor: &Editor) -> Result<FileExplorer, std
             }
         },
     )
-    .with_preview(|_editor, (path, _is_dir)| Some((path.as_path().into(), None)));
+    .with_preview(|_editor, (path, _is_dir)| Some((path.as_path().into(), None)))
+    .with_initial_cursor(cursor as u32)
+    .with_key_handler(alt!('c'), |_cx, _, (root, _)| {
+        file_explorer_prompt(root.clone(), "create:", String::new(), |root, input, _| {
+            let path = root.join(input);
+            if input.ends_with(std::path::is_separator) {
+                std::fs::create_dir_all(&path)?;
+            } else {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::File::create_new(&path)?;
+            }
+            Ok(path)
+        })
+    })
+    .with_key_handler(alt!('r'), |_cx, (path, _), (root, _)| {
+        if path.ends_with("..") {
+            return None;
+        }
+        let old_path = path.clone();
+        let name = path.strip_prefix(root).unwrap_or(path);
+        let line = name.to_string_lossy().into_owned();
+        file_explorer_prompt(root.clone(), "move:", line, move |root, input, editor| {
+            let new_path = root.join(input);
+            if new_path.exists() {
+                anyhow::bail!("'{}' already exists", new_path.display());
+            }
+            if let Some(parent) = new_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            editor.move_path(&old_path, &new_path)?;
+            Ok(new_path)
+        })
+    })
+    .with_key_handler(alt!('x'), |cx, (path, is_dir), (root, _)| {
+        if path.ends_with("..") {
+            return None;
+        }
+        // refuse up front rather than after the confirmation
+        if let Err(err) = cx
+            .editor
+            .ensure_unmodified(&helix_stdx::path::canonicalize(path))
+        {
+            cx.editor.set_error(err.to_string());
+            return None;
+        }
+        let path = path.clone();
+        let is_dir = *is_dir;
+        let name = path.strip_prefix(root).unwrap_or(&path).display();
+        let prompt = if is_dir {
+            format!("delete {name}/ and its contents? (y/n):")
+        } else {
+            format!("delete {name}? (y/n):")
+        };
+        file_explorer_prompt(
+            root.clone(),
+            prompt,
+            String::new(),
+            move |_, input, editor| {
+                if input == "y" {
+                    // closes the buffers of the deleted files
+                    editor.delete_path(&path, true)?;
+                }
+                Ok(path.clone())
+            },
+        )
+    });
 
     Ok(picker)
 }
 
+/// Prompts for the input of a file explorer action. The explorer is reopened
+/// at `root` once the prompt is closed, selecting the path returned by `action`.
+fn file_explorer_prompt(
+    root: PathBuf,
+    prompt: impl Into<std::borrow::Cow<'static, str>>,
+    line: String,
+    action: impl Fn(&Path, &str, &mut Editor) -> anyhow::Result<PathBuf> + 'static,
+) -> Option<crate::compositor::Callback> {
+    let prompt = prompt.into();
+    Some(Box::new(move |compositor, cx| {
+        let prompt = Prompt::new(
+            prompt,
+            None,
+            completers::none,
+            move |cx, input: &str, event: PromptEvent| {
+                let reveal = match event {
+                    PromptEvent::Validate if !input.is_empty() => {
+                        match action(&root, input, cx.editor) {
+                            Ok(path) => Some(path),
+                            Err(err) => {
+                                cx.editor.set_error(err.to_string());
+                                None
+                            }
+                        }
+                    }
+                    PromptEvent::Validate | PromptEvent::Abort => None,
+                    PromptEvent::Update => return,
+                };
+                let root = root.clone();
+                let callback = Box::pin(async move {
+                    let call: Callback =
+                        Callback::EditorCompositor(Box::new(move |editor, compositor| {
+                            if let Ok(picker) = file_explorer(root, reveal.as_deref(), editor) {
+                                compositor.push(Box::new(overlay::overlaid(picker)));
+                            }
+                        }));
+                    Ok(call)
+                });
+                cx.jobs.callback(callback);
+            },
+        )
+        .with_line(line, cx.editor);
+        compositor.push(Box::new(prompt));
+    }))
+}
+
 fn directory_content(root: &Path, editor: &Editor) -> Result<Vec<(PathBuf, bool)>, std::io::Error> {
     use ignore::WalkBuilder;
 
@@ -470,6 +682,37 @@ pub fn theme(_editor: &Editor, input: &str) -> Vec<Completion> {
             .collect()
     }
 
+    pub fn session(_editor: &Editor, input: &str) -> Vec<Completion> {
+        let mut names = helix_view::session::Session::read_names();
+        names.sort();
+
+        fuzzy_match(input, names, false)
+            .into_iter()
+            .map(|(name, _)| ((0..), name.into()))
+            .collect()
+    }
+
+    pub fn layout(_editor: &Editor, input: &str) -> Vec<Completion> {
+        let mut names = helix_view::session::WindowLayout::read_names();
+        names.sort();
+
+        fuzzy_match(input, names, false)
+            .into_iter()
+            .map(|(name, _)| ((0..), name.into()))
+            .collect()
+    }
+
+    pub fn task(editor: &Editor, input: &str) -> Vec<Completion> {
+        let names = helix_loader::tasks::load_tasks(editor.config().insecure)
+            .map(|tasks| tasks.into_keys().collect())
+            .unwrap_or_else(|_| Vec::new());
+
+        fuzzy_match(input, names, false)
+            .into_iter()
+            .map(|(name, _)| ((0..), name.into()))
+            .collect()
+    }
+
     /// Recursive function to get all keys from this value and add them to vec
     fn get_keys(value: &serde_json::Value, vec: &mut Vec<String>, scope: Option<&str>) {
         if let Some(map) = value.as_object() {
@@ -525,6 +768,20 @@ pub fn setting(_editor: &Editor, input: &str) -> Vec<Completion> {
             .collect()
     }
 
+    pub fn inlay_hint_kind(_editor: &Editor, input: &str) -> Vec<Completion> {
+        fuzzy_match(input, ["type", "parameter", "other"], false)
+            .into_iter()
+            .map(|(name, _)| ((0..), Span::raw(name)))
+            .collect()
+    }
+
+    pub fn boolean(_editor: &Editor, input: &str) -> Vec<Completion> {
+        fuzzy_match(input, ["true", "false"], false)
+            .into_iter()
+            .map(|(name, _)| ((0..), Span::raw(name)))
+            .collect()
+    }
+
     pub fn filename(editor: &Editor, input: &str) -> Vec<Completion> {
         filename_with_git_ignore(editor, input, true)
     }
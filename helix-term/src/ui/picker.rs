@@ -21,6 +21,7 @@
 use tokio::sync::mpsc::Sender;
 use tui::{
     buffer::Buffer as Surface,
+    image::Image,
     layout::Constraint,
     text::{Span, Spans},
     widgets::{Block, BorderType, Cell, Row, Table},
@@ -47,6 +48,7 @@
 use helix_view::{
     editor::Action,
     graphics::{CursorKind, Margin, Modifier, Rect},
+    input::KeyEvent,
     theme::Style,
     view::ViewPosition,
     Document, DocumentId, Editor,
@@ -86,6 +88,7 @@ fn from(v: DocumentId) -> Self {
 pub enum CachedPreview {
     Document(Box<Document>),
     Directory(Vec<(String, bool)>),
+    Image(Image),
     Binary,
     LargeFile,
     NotFound,
@@ -114,6 +117,13 @@ fn dir_content(&self) -> Option<&Vec<(String, bool)>> {
         }
     }
 
+    fn image(&self) -> Option<&Image> {
+        match self {
+            Preview::Cached(CachedPreview::Image(image)) => Some(image),
+            _ => None,
+        }
+    }
+
     /// Alternate text to show for the preview.
     fn placeholder(&self) -> &str {
         match *self {
@@ -121,6 +131,7 @@ fn placeholder(&self) -> &str {
             Self::Cached(preview) => match preview {
                 CachedPreview::Document(_) => "<Invalid file location>",
                 CachedPreview::Directory(_) => "<Invalid directory location>",
+                CachedPreview::Image(_) => "<Image file>",
                 CachedPreview::Binary => "<Binary file>",
                 CachedPreview::LargeFile => "<File too large to preview>",
                 CachedPreview::NotFound => "<File not found>",
@@ -249,6 +260,8 @@ pub struct Picker<T: 'static + Send + Sync, D: 'static> {
     completion_height: u16,
 
     cursor: u32,
+    /// The indices of the marked items in the order they were marked.
+    marked: Vec<u32>,
     prompt: Prompt,
     query: PickerQuery,
 
@@ -259,6 +272,9 @@ pub struct Picker<T: 'static + Send + Sync, D: 'static> {
 
     callback_fn: PickerCallback<T>,
     default_action: Action,
+    /// Picker specific actions that are run on the selected item before the picker is closed.
+    custom_key_handlers: HashMap<KeyEvent, PickerKeyHandler<T, D>>,
+    matches_key_handlers: HashMap<KeyEvent, PickerMatchesKeyHandler<T, D>>,
 
     pub truncate_start: bool,
     /// Caches paths to documents
@@ -381,12 +397,15 @@ fn with(
             editor_data,
             version,
             cursor: 0,
+            marked: Vec::new(),
             prompt,
             query,
             truncate_start: true,
             show_preview: true,
             callback_fn: Box::new(callback_fn),
             default_action: Action::Replace,
+            custom_key_handlers: HashMap::new(),
+            matches_key_handlers: HashMap::new(),
             completion_height: 0,
             widths,
             preview_cache: HashMap::new(),
@@ -455,6 +474,38 @@ pub fn with_default_action(mut self, action: Action) -> Self {
         self
     }
 
+    /// The action of Enter and Alt-Enter: files are opened as a preview buffer if
+    /// [helix_view::editor::Config::preview_buffers] is enabled.
+    fn open_action(&self, editor: &Editor) -> Action {
+        match self.default_action {
+            Action::Replace if editor.config().preview_buffers => Action::Preview,
+            action => action,
+        }
+    }
+
+    /// Runs `handler` on the selected item and closes the picker when `key` is pressed.
+    /// The compositor callback returned by the `handler` is run after the picker is closed.
+    pub fn with_key_handler(
+        mut self,
+        key: KeyEvent,
+        handler: impl Fn(&mut Context, &T, &D) -> Option<compositor::Callback> + 'static,
+    ) -> Self {
+        self.custom_key_handlers.insert(key, Box::new(handler));
+        self
+    }
+
+    /// Runs `handler` on the marked items, or on all items matching the current query if no item
+    /// is marked, and closes the picker when `key` is pressed. The compositor callback returned
+    /// by the `handler` is run after the picker is closed.
+    pub fn with_matches_key_handler(
+        mut self,
+        key: KeyEvent,
+        handler: impl Fn(&mut Context, &[&T], &D) -> Option<compositor::Callback> + 'static,
+    ) -> Self {
+        self.matches_key_handlers.insert(key, Box::new(handler));
+        self
+    }
+
     /// Move the cursor by a number of lines, either down (`Forward`) or up (`Backward`)
     pub fn move_by(&mut self, amount: u32, direction: Direction) {
         let len = self.matcher.snapshot().matched_item_count();
@@ -505,6 +556,44 @@ pub fn selection(&self) -> Option<&T> {
             .map(|item| item.data)
     }
 
+    /// Marks the selected item or removes its mark.
+    fn toggle_mark(&mut self) {
+        let Some(idx) = self
+            .matcher
+            .snapshot()
+            .matches()
+            .get(self.cursor as usize)
+            .map(|m| m.idx)
+        else {
+            return;
+        };
+        match self.marked.iter().position(|&marked| marked == idx) {
+            Some(pos) => {
+                self.marked.remove(pos);
+            }
+            None => self.marked.push(idx),
+        }
+    }
+
+    /// The marked items in the order they were marked.
+    fn marked_items(&self) -> Vec<&T> {
+        let snapshot = self.matcher.snapshot();
+        self.marked
+            .iter()
+            .filter_map(|&idx| snapshot.get_item(idx))
+            .map(|item| item.data)
+            .collect()
+    }
+
+    /// The marked items or, if no item is marked, the selected item.
+    fn marked_or_selected(&self) -> Vec<&T> {
+        if self.marked.is_empty() {
+            self.selection().into_iter().collect()
+        } else {
+            self.marked_items()
+        }
+    }
+
     fn primary_query(&self) -> Arc<str> {
         self.query
             .get(&self.columns[self.primary_column].name)
@@ -639,7 +728,8 @@ fn get_preview<'picker, 'editor>(
                                 Ok(is_binary)
                             })?;
                             if is_binary {
-                                return Ok(CachedPreview::Binary);
+                                return Ok(Image::load(&path)
+                                    .map_or(CachedPreview::Binary, CachedPreview::Image));
                             }
                             let mut doc = Document::open(
                                 &path,
@@ -692,6 +782,11 @@ fn render_picker(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context)
         let text_style = cx.editor.theme.get("ui.text");
         let selected = cx.editor.theme.get("ui.text.focus");
         let highlight_style = cx.editor.theme.get("special").add_modifier(Modifier::BOLD);
+        let marked_style = cx
+            .editor
+            .theme
+            .try_get_exact("ui.picker.marked")
+            .unwrap_or_else(|| cx.editor.theme.get("ui.selection"));
 
         // -- Render the frame:
         // clear area
@@ -757,9 +852,17 @@ fn render_picker(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context)
             matcher.config.set_match_paths()
         }
 
+        let mut marked = snapshot.matches()[offset as usize..end as usize]
+            .iter()
+            .map(|m| self.marked.contains(&m.idx));
         let options = snapshot.matched_items(offset..end).map(|item| {
             let mut widths = self.widths.iter_mut();
             let mut matcher_index = 0;
+            let style = if marked.next() == Some(true) {
+                marked_style
+            } else {
+                Style::default()
+            };
 
             Row::new(self.columns.iter().map(|column| {
                 if column.hidden {
@@ -832,6 +935,7 @@ fn render_picker(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context)
 
                 cell
             }))
+            .style(style)
         });
 
         let mut table = Table::new(options)
@@ -922,6 +1026,11 @@ fn render_preview(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context
                         return;
                     }
 
+                    if let Some(image) = preview.image().filter(|_| surface.supports_images()) {
+                        surface.set_image(image.fit(inner), image.clone());
+                        return;
+                    }
+
                     let alt_text = preview.placeholder();
                     let x = inner.x + inner.width.saturating_sub(alt_text.len() as u16) / 2;
                     let y = inner.y + inner.height / 2;
@@ -1018,6 +1127,7 @@ fn render_preview(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context
                 overlay_highlights,
                 &cx.editor.theme,
                 decorations,
+                None,
             );
         }
     }
@@ -1086,11 +1196,47 @@ fn handle_event(&mut self, event: &Event, ctx: &mut Context) -> EventResult {
             EventResult::Consumed(Some(callback))
         };
 
+        let custom_callback = if let Some(handler) = self.custom_key_handlers.get(&key_event) {
+            Some(
+                self.selection()
+                    .and_then(|option| handler(ctx, option, &self.editor_data)),
+            )
+        } else if let Some(handler) = self.matches_key_handlers.get(&key_event) {
+            let items = if self.marked.is_empty() {
+                let snapshot = self.matcher.snapshot();
+                snapshot.matched_items(..).map(|item| item.data).collect()
+            } else {
+                self.marked_items()
+            };
+            Some(handler(ctx, &items, &self.editor_data))
+        } else {
+            None
+        };
+        if let Some(callback) = custom_callback {
+            return match (close_fn(self), callback) {
+                (EventResult::Consumed(Some(close)), Some(callback)) => {
+                    EventResult::Consumed(Some(Box::new(move |compositor, cx| {
+                        close(compositor, cx);
+                        callback(compositor, cx);
+                    })))
+                }
+                (result, _) => result,
+            };
+        }
+
         match key_event {
-            shift!(Tab) | key!(Up) | ctrl!('p') => {
+            key!(Tab) => {
+                self.toggle_mark();
+                self.move_by(1, Direction::Forward);
+            }
+            shift!(Tab) => {
+                self.toggle_mark();
+                self.move_by(1, Direction::Backward);
+            }
+            key!(Up) | ctrl!('p') => {
                 self.move_by(1, Direction::Backward);
             }
-            key!(Tab) | key!(Down) | ctrl!('n') => {
+            key!(Down) | ctrl!('n') => {
                 self.move_by(1, Direction::Forward);
             }
             key!(PageDown) | ctrl!('d') => {
@@ -1107,8 +1253,9 @@ fn handle_event(&mut self, event: &Event, ctx: &mut Context) -> EventResult {
             }
             key!(Esc) | ctrl!('c') => return close_fn(self),
             alt!(Enter) => {
-                if let Some(option) = self.selection() {
-                    (self.callback_fn)(ctx, option, self.default_action);
+                let action = self.open_action(ctx.editor);
+                for option in self.marked_or_selected() {
+                    (self.callback_fn)(ctx, option, action);
                 }
             }
             key!(Enter) => {
@@ -1131,8 +1278,9 @@ fn handle_event(&mut self, event: &Event, ctx: &mut Context) -> EventResult {
                     // Inserting from the history register is a paste.
                     self.handle_prompt_change(true);
                 } else {
-                    if let Some(option) = self.selection() {
-                        (self.callback_fn)(ctx, option, self.default_action);
+                    let action = self.open_action(ctx.editor);
+                    for option in self.marked_or_selected() {
+                        (self.callback_fn)(ctx, option, action);
                     }
                     if let Some(history_register) = self.prompt.history_register() {
                         if let Err(err) = ctx
@@ -1147,13 +1295,13 @@ fn handle_event(&mut self, event: &Event, ctx: &mut Context) -> EventResult {
                 }
             }
             ctrl!('s') => {
-                if let Some(option) = self.selection() {
+                for option in self.marked_or_selected() {
                     (self.callback_fn)(ctx, option, Action::HorizontalSplit);
                 }
                 return close_fn(self);
             }
             ctrl!('v') => {
-                if let Some(option) = self.selection() {
+                for option in self.marked_or_selected() {
                     (self.callback_fn)(ctx, option, Action::VerticalSplit);
                 }
                 return close_fn(self);
@@ -1205,3 +1353,6 @@ fn drop(&mut self) {
 }
 
 type PickerCallback<T> = Box<dyn Fn(&mut Context, &T, Action)>;
+type PickerKeyHandler<T, D> = Box<dyn Fn(&mut Context, &T, &D) -> Option<compositor::Callback>>;
+type PickerMatchesKeyHandler<T, D> =
+    Box<dyn Fn(&mut Context, &[&T], &D) -> Option<compositor::Callback>>;
@@ -176,6 +176,7 @@ fn finish_debounce(&mut self) {
             // Increment the version number to cancel any ongoing requests.
             picker.version.fetch_add(1, atomic::Ordering::Relaxed);
             picker.matcher.restart(false);
+            picker.marked.clear();
             let injector = picker.injector();
             let get_options = (callback)(&query, editor, picker.editor_data.clone(), &injector);
             tokio::spawn(async move {
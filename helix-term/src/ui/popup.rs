@@ -36,6 +36,7 @@ pub struct Popup<T: Component> {
     position_bias: Open,
     scroll_half_pages: usize,
     auto_close: bool,
+    follow_cursor: bool,
     ignore_escape_key: bool,
     id: &'static str,
     has_scrollbar: bool,
@@ -50,6 +51,7 @@ pub fn new(id: &'static str, contents: T) -> Self {
             area: Rect::new(0, 0, 0, 0),
             scroll_half_pages: 0,
             auto_close: false,
+            follow_cursor: true,
             ignore_escape_key: false,
             id,
             has_scrollbar: true,
@@ -69,6 +71,13 @@ pub fn get_position(&self) -> Option<Position> {
         self.position
     }
 
+    /// Keeps the popup next to the anchor position set with [`Popup::position`] instead of
+    /// moving it to the cursor when the cursor is on another line.
+    pub fn follow_cursor(mut self, follow_cursor: bool) -> Self {
+        self.follow_cursor = follow_cursor;
+        self
+    }
+
     /// Set the popup to prefer to render above or below the anchor position.
     ///
     /// This preference will be ignored if the viewport doesn't have enough space in the
@@ -127,7 +136,7 @@ fn render_info(&mut self, viewport: Rect, editor: &Editor) -> RenderInfo {
         let mut position = editor.cursor().0.unwrap_or_default();
         if let Some(old_position) = self
             .position
-            .filter(|old_position| old_position.row == position.row)
+            .filter(|old_position| !self.follow_cursor || old_position.row == position.row)
         {
             position = old_position;
         } else {
@@ -1,10 +1,13 @@
 use crate::compositor::{Component, Compositor, Context, Event, EventResult};
 use crate::{alt, ctrl, key, shift, ui};
 use arc_swap::ArcSwap;
+use helix_core::fuzzy::fuzzy_match;
 use helix_core::syntax;
 use helix_view::document::Mode;
 use helix_view::input::KeyEvent;
 use helix_view::keyboard::KeyCode;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::{borrow::Cow, ops::RangeFrom};
 use tui::buffer::Buffer as Surface;
@@ -27,6 +30,15 @@
 type CompletionFn = Box<dyn FnMut(&Editor, &str) -> Vec<Completion>>;
 type CallbackFn = Box<dyn FnMut(&mut Context, &str, PromptEvent)>;
 pub type DocFn = Box<dyn Fn(&str) -> Option<Cow<str>>>;
+type HistoryFn = Box<dyn Fn(&str) -> String>;
+
+/// A flag that is switched with a key while the prompt is open, for example the case
+/// sensitivity of a search. The labels of the active flags are shown in the prompt.
+struct PromptToggle {
+    key: KeyEvent,
+    label: &'static str,
+    state: Arc<AtomicBool>,
+}
 
 pub struct Prompt {
     prompt: Cow<'static, str>,
@@ -42,11 +54,16 @@ pub struct Prompt {
     selection: Option<usize>,
     history_register: Option<char>,
     history_pos: Option<usize>,
+    /// Whether the completions are the history entries fuzzy matching the line.
+    history_search: bool,
     completion_fn: CompletionFn,
     callback_fn: CallbackFn,
     pub doc_fn: DocFn,
     next_char_handler: Option<PromptCharHandler>,
     language: Option<(&'static str, Arc<ArcSwap<syntax::Loader>>)>,
+    toggles: Vec<PromptToggle>,
+    /// Maps the input to the entry that is pushed into the history register.
+    history_fn: Option<HistoryFn>,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -98,11 +115,14 @@ pub fn new(
             selection: None,
             history_register,
             history_pos: None,
+            history_search: false,
             completion_fn: Box::new(completion_fn),
             callback_fn: Box::new(callback_fn),
             doc_fn: Box::new(|_| None),
             next_char_handler: None,
             language: None,
+            toggles: Vec::new(),
+            history_fn: None,
         }
     }
 
@@ -124,6 +144,42 @@ pub fn set_line(&mut self, line: String, editor: &Editor) {
         self.recalculate_completion(editor);
     }
 
+    /// Adds a flag that is switched with `key`. The callback is called with an update event
+    /// whenever the flag changes.
+    pub fn with_toggle(
+        mut self,
+        key: KeyEvent,
+        label: &'static str,
+        state: Arc<AtomicBool>,
+    ) -> Self {
+        self.toggles.push(PromptToggle { key, label, state });
+        self
+    }
+
+    /// Pushes `history_fn(input)` into the history register instead of the input, for example
+    /// to keep the active toggles.
+    pub fn with_history_fn(mut self, history_fn: impl Fn(&str) -> String + 'static) -> Self {
+        self.history_fn = Some(Box::new(history_fn));
+        self
+    }
+
+    /// The prompt followed by the labels of the active toggles, for example `search (word):`.
+    fn prompt_text(&self) -> Cow<'_, str> {
+        let labels: Vec<_> = self
+            .toggles
+            .iter()
+            .filter(|toggle| toggle.state.load(Ordering::Relaxed))
+            .map(|toggle| toggle.label)
+            .collect();
+        if labels.is_empty() {
+            return Cow::Borrowed(&self.prompt);
+        }
+        match self.prompt.strip_suffix(':') {
+            Some(prompt) => format!("{prompt} ({}):", labels.join(", ")).into(),
+            None => format!("{} ({})", self.prompt, labels.join(", ")).into(),
+        }
+    }
+
     pub fn with_language(
         mut self,
         language: &'static str,
@@ -156,7 +212,28 @@ pub(crate) fn first_history_completion<'a>(
 
     pub fn recalculate_completion(&mut self, editor: &Editor) {
         self.exit_selection();
-        self.completion = (self.completion_fn)(editor, &self.line);
+        self.completion = if self.history_search {
+            self.history_completion(editor)
+        } else {
+            (self.completion_fn)(editor, &self.line)
+        };
+    }
+
+    /// Returns the distinct history entries that fuzzy match the line, best match first.
+    fn history_completion(&self, editor: &Editor) -> Vec<Completion> {
+        let Some(values) = self
+            .history_register
+            .and_then(|register| editor.registers.read(register, editor))
+        else {
+            return Vec::new();
+        };
+        // the most recent entries come first and win ties
+        let mut seen = HashSet::new();
+        let entries: Vec<_> = values.filter(|entry| seen.insert(entry.clone())).collect();
+        fuzzy_match(&self.line, entries.iter(), false)
+            .into_iter()
+            .map(|(entry, _)| (0.., entry.to_string().into()))
+            .collect()
     }
 
     /// Compute the cursor position after applying movement
@@ -513,12 +590,11 @@ pub fn render_prompt(&mut self, area: Rect, surface: &mut Surface, cx: &mut Cont
         let line = area.height - 1;
         surface.clear_with(area.clip_top(line), background);
         // render buffer text
-        surface.set_string(area.x, area.y + line, &self.prompt, prompt_color);
+        let prompt = self.prompt_text();
+        let prompt_width = prompt.width() as u16;
+        surface.set_string(area.x, area.y + line, &prompt, prompt_color);
 
-        self.line_area = area
-            .clip_left(self.prompt.len() as u16)
-            .clip_top(line)
-            .clip_right(2);
+        self.line_area = area.clip_left(prompt_width).clip_top(line).clip_right(2);
 
         if self.line.is_empty() {
             self.anchor = 0;
@@ -623,6 +699,12 @@ fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
             compositor.pop();
         })));
 
+        if let Some(toggle) = self.toggles.iter().find(|toggle| toggle.key == event) {
+            toggle.state.fetch_xor(true, Ordering::Relaxed);
+            (self.callback_fn)(cx, &self.line, PromptEvent::Update);
+            return EventResult::Consumed(None);
+        }
+
         match event {
             ctrl!('c') | key!(Esc) => {
                 (self.callback_fn)(cx, &self.line, PromptEvent::Abort);
@@ -689,12 +771,14 @@ fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
                     let input = if self.line.is_empty() {
                         &last_item
                     } else {
-                        if last_item != self.line {
+                        let entry = match &self.history_fn {
+                            Some(history_fn) => history_fn(&self.line),
+                            None => self.line.clone(),
+                        };
+                        if last_item != entry {
                             // store in history
                             if let Some(register) = self.history_register {
-                                if let Err(err) =
-                                    cx.editor.registers.push(register, self.line.clone())
-                                {
+                                if let Err(err) = cx.editor.registers.push(register, entry) {
                                     cx.editor.set_error(err.to_string());
                                 }
                             };
@@ -731,6 +815,11 @@ fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
                 (self.callback_fn)(cx, &self.line, PromptEvent::Update)
             }
             ctrl!('q') => self.exit_selection(),
+            alt!('r') if self.history_register.is_some() => {
+                self.history_search = !self.history_search;
+                self.recalculate_completion(cx.editor);
+                (self.callback_fn)(cx, &self.line, PromptEvent::Update);
+            }
             ctrl!('r') => {
                 self.completion = cx
                     .editor
@@ -771,7 +860,7 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
 
     fn cursor(&self, area: Rect, editor: &Editor) -> (Option<Position>, CursorKind) {
         let area = area
-            .clip_left(self.prompt.len() as u16)
+            .clip_left(self.prompt_text().width() as u16)
             .clip_right(if self.prompt.is_empty() { 2 } else { 0 });
 
         let mut col = area.left() as usize + self.line[self.anchor..self.cursor].width();
@@ -798,3 +887,27 @@ fn cursor(&self, area: Rect, editor: &Editor) -> (Option<Position>, CursorKind)
         )
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn prompt_text_shows_active_toggles() {
+        let case = Arc::new(AtomicBool::new(false));
+        let word = Arc::new(AtomicBool::new(false));
+        let prompt = Prompt::new("search:".into(), None, |_, _| Vec::new(), |_, _, _| {})
+            .with_toggle(alt!('c'), "case", case.clone())
+            .with_toggle(alt!('w'), "word", word.clone());
+        assert_eq!(prompt.prompt_text(), "search:");
+
+        word.store(true, Ordering::Relaxed);
+        assert_eq!(prompt.prompt_text(), "search (word):");
+        case.store(true, Ordering::Relaxed);
+        assert_eq!(prompt.prompt_text(), "search (case, word):");
+
+        let prompt = Prompt::new("select".into(), None, |_, _| Vec::new(), |_, _, _| {})
+            .with_toggle(alt!('c'), "case", case);
+        assert_eq!(prompt.prompt_text(), "select (case)");
+    }
+}
@@ -0,0 +1,479 @@
+//! Preview of the edits of a rename before they are applied. The edits are listed grouped by
+//! file next to the lines they change, files can be deselected and only the edits of the selected
+//! files are applied.
+
+use std::collections::HashSet;
+
+use helix_core::{line_ending::line_end_char_index, Rope, Uri};
+use helix_lsp::{lsp, util::lsp_range_to_range, OffsetEncoding};
+use helix_stdx::path;
+use helix_view::{graphics::Rect, Editor};
+use tui::{
+    buffer::Buffer as Surface,
+    widgets::{Block, Widget},
+};
+
+use crate::{
+    compositor::{Component, Compositor, Context, Event, EventResult},
+    ctrl, key,
+};
+
+/// A group of edits that overlap the same lines, shown as the old and new lines.
+#[derive(Debug, PartialEq, Eq)]
+struct ChangedLines {
+    /// The zero-based index of the first changed line.
+    line: usize,
+    old: String,
+    new: String,
+}
+
+struct FileEdits {
+    uri: lsp::Url,
+    /// The path of the file as it is displayed.
+    name: String,
+    edit_count: usize,
+    changes: Vec<ChangedLines>,
+    selected: bool,
+}
+
+pub struct RenamePreview {
+    edit: lsp::WorkspaceEdit,
+    offset_encoding: OffsetEncoding,
+    files: Vec<FileEdits>,
+    /// The index of the file under the cursor.
+    cursor: usize,
+}
+
+impl RenamePreview {
+    pub fn new(editor: &Editor, edit: lsp::WorkspaceEdit, offset_encoding: OffsetEncoding) -> Self {
+        let files = text_edits(&edit)
+            .into_iter()
+            .map(|(url, edits)| {
+                let path = Uri::try_from(&url)
+                    .ok()
+                    .and_then(|uri| uri.as_path().map(ToOwned::to_owned));
+                let text = path
+                    .as_ref()
+                    .and_then(|path| match editor.document_by_path(path) {
+                        Some(doc) => Some(doc.text().clone()),
+                        None => std::fs::read_to_string(path).ok().map(Rope::from),
+                    });
+                let name = match &path {
+                    Some(path) => path::get_relative_path(path).to_string_lossy().into_owned(),
+                    None => url.to_string(),
+                };
+                FileEdits {
+                    name,
+                    edit_count: edits.len(),
+                    changes: text
+                        .map(|text| changed_lines(&text, &edits, offset_encoding))
+                        .unwrap_or_default(),
+                    uri: url,
+                    selected: true,
+                }
+            })
+            .collect();
+        Self {
+            edit,
+            offset_encoding,
+            files,
+            cursor: 0,
+        }
+    }
+
+    /// Whether the edit doesn't change the text of any file.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    fn apply(&self, editor: &mut Editor) {
+        let excluded: HashSet<_> = self
+            .files
+            .iter()
+            .filter(|file| !file.selected)
+            .map(|file| &file.uri)
+            .collect();
+        let selected = self.files.len() - excluded.len();
+        let edit = filter_edit(&self.edit, &excluded);
+        match editor.apply_workspace_edit(self.offset_encoding, &edit) {
+            Ok(()) => {
+                let plural = if selected == 1 { "" } else { "s" };
+                editor.set_status(format!("Renamed in {selected} file{plural}"));
+            }
+            Err(err) => editor.set_error(format!("Failed to apply the rename: {}", err.kind)),
+        }
+    }
+}
+
+/// The text edits of the workspace edit grouped by file in the order in which the files appear.
+fn text_edits(edit: &lsp::WorkspaceEdit) -> Vec<(lsp::Url, Vec<lsp::TextEdit>)> {
+    let mut files: Vec<(lsp::Url, Vec<lsp::TextEdit>)> = Vec::new();
+    let mut push = |uri: &lsp::Url, edits: &mut dyn Iterator<Item = lsp::TextEdit>| match files
+        .iter_mut()
+        .find(|(file, _)| file == uri)
+    {
+        Some((_, file_edits)) => file_edits.extend(edits),
+        None => files.push((uri.clone(), edits.collect())),
+    };
+    let document_edit_texts = |edit: &lsp::TextDocumentEdit| {
+        edit.edits
+            .iter()
+            .map(|edit| match edit {
+                lsp::OneOf::Left(text_edit) => text_edit.clone(),
+                lsp::OneOf::Right(annotated) => annotated.text_edit.clone(),
+            })
+            .collect::<Vec<_>>()
+    };
+    match &edit.document_changes {
+        Some(lsp::DocumentChanges::Edits(edits)) => {
+            for edit in edits {
+                push(
+                    &edit.text_document.uri,
+                    &mut document_edit_texts(edit).into_iter(),
+                );
+            }
+        }
+        Some(lsp::DocumentChanges::Operations(operations)) => {
+            for operation in operations {
+                if let lsp::DocumentChangeOperation::Edit(edit) = operation {
+                    push(
+                        &edit.text_document.uri,
+                        &mut document_edit_texts(edit).into_iter(),
+                    );
+                }
+            }
+        }
+        None => {
+            // `document_changes` takes precedence when applying the edit
+            let mut changes: Vec<_> = edit.changes.iter().flatten().collect();
+            changes.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+            for (uri, edits) in changes {
+                push(uri, &mut edits.iter().cloned());
+            }
+        }
+    }
+    files
+}
+
+/// Removes the text edits of the excluded files from the workspace edit. Resource operations are
+/// kept since the edits of the other files may depend on them.
+fn filter_edit(edit: &lsp::WorkspaceEdit, excluded: &HashSet<&lsp::Url>) -> lsp::WorkspaceEdit {
+    let mut edit = edit.clone();
+    if let Some(changes) = &mut edit.changes {
+        changes.retain(|uri, _| !excluded.contains(uri));
+    }
+    match &mut edit.document_changes {
+        Some(lsp::DocumentChanges::Edits(edits)) => {
+            edits.retain(|edit| !excluded.contains(&edit.text_document.uri));
+        }
+        Some(lsp::DocumentChanges::Operations(operations)) => {
+            operations.retain(|operation| match operation {
+                lsp::DocumentChangeOperation::Edit(edit) => {
+                    !excluded.contains(&edit.text_document.uri)
+                }
+                lsp::DocumentChangeOperation::Op(_) => true,
+            });
+        }
+        None => (),
+    }
+    edit
+}
+
+/// Groups the edits by the lines they change and computes the lines after the edits.
+fn changed_lines(
+    text: &Rope,
+    edits: &[lsp::TextEdit],
+    offset_encoding: OffsetEncoding,
+) -> Vec<ChangedLines> {
+    let mut edits: Vec<_> = edits
+        .iter()
+        .filter_map(|edit| {
+            let range = lsp_range_to_range(text, edit.range, offset_encoding)?;
+            Some((range.from(), range.to(), edit.new_text.as_str()))
+        })
+        .collect();
+    edits.sort_by_key(|&(from, to, _)| (from, to));
+
+    let mut changes = Vec::new();
+    let mut edits = edits.into_iter().peekable();
+    while let Some(first) = edits.next() {
+        let first_line = text.char_to_line(first.0);
+        let mut last_line = text.char_to_line(first.1);
+        let mut group = vec![first];
+        while let Some(edit) = edits.next_if(|&(from, _, _)| text.char_to_line(from) <= last_line) {
+            last_line = last_line.max(text.char_to_line(edit.1));
+            group.push(edit);
+        }
+
+        let start = text.line_to_char(first_line);
+        let end = line_end_char_index(&text.slice(..), last_line);
+        let mut new = String::new();
+        let mut pos = start;
+        for (from, to, new_text) in group {
+            if pos < from {
+                new.extend(text.slice(pos..from).chars());
+            }
+            new.push_str(new_text);
+            pos = pos.max(to);
+        }
+        if pos < end {
+            new.extend(text.slice(pos..end).chars());
+        }
+        changes.push(ChangedLines {
+            line: first_line,
+            old: text.slice(start..end.max(start)).to_string(),
+            new,
+        });
+    }
+    changes
+}
+
+impl Component for RenamePreview {
+    fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        let Event::Key(event) = event else {
+            return EventResult::Ignored(None);
+        };
+        let close = || -> EventResult {
+            EventResult::Consumed(Some(Box::new(|compositor: &mut Compositor, _| {
+                compositor.pop();
+            })))
+        };
+        match *event {
+            key!('j') | key!(Down) | ctrl!('n') => {
+                self.cursor = (self.cursor + 1).min(self.files.len().saturating_sub(1));
+            }
+            key!('k') | key!(Up) | ctrl!('p') => self.cursor = self.cursor.saturating_sub(1),
+            key!('g') | key!(Home) => self.cursor = 0,
+            key!('G') | key!(End) => self.cursor = self.files.len().saturating_sub(1),
+            key!(' ') | key!(Tab) => {
+                if let Some(file) = self.files.get_mut(self.cursor) {
+                    file.selected = !file.selected;
+                }
+            }
+            key!('a') => {
+                let selected = !self.files.iter().all(|file| file.selected);
+                for file in &mut self.files {
+                    file.selected = selected;
+                }
+            }
+            key!(Enter) => {
+                self.apply(cx.editor);
+                return close();
+            }
+            key!('q') | key!(Esc) | ctrl!('c') => {
+                cx.editor.set_status("Rename cancelled");
+                return close();
+            }
+            _ => (),
+        }
+        EventResult::Consumed(None)
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        let theme = &cx.editor.theme;
+        let text_style = theme.get("ui.text");
+        let directory_style = theme.get("ui.text.directory");
+        let selected_style = theme.get("ui.menu.selected");
+        let line_nr_style = theme.get("ui.linenr");
+        let minus_style = theme.get("diff.minus");
+        let plus_style = theme.get("diff.plus");
+        surface.clear_with(area, theme.get("ui.background"));
+
+        // the files on the left, the changed lines of the file under the cursor on the right
+        let list_width = area.width * 2 / 5;
+        let list_area = area.with_width(list_width);
+        let preview_area = area.clip_left(list_width);
+
+        let selected = self.files.iter().filter(|file| file.selected).count();
+        let block = Block::bordered().title(format!(
+            " Rename: {selected}/{} files (space) toggle (a)ll (enter) apply ",
+            self.files.len()
+        ));
+        let inner = block.inner(list_area);
+        block.render(list_area, surface);
+
+        let height = inner.height as usize;
+        let scroll = (self.cursor + 1).saturating_sub(height);
+        for (row, (i, file)) in self
+            .files
+            .iter()
+            .enumerate()
+            .skip(scroll)
+            .take(height)
+            .enumerate()
+        {
+            let y = inner.y + row as u16;
+            let style = if i == self.cursor {
+                surface.set_style(Rect::new(inner.x, y, inner.width, 1), selected_style);
+                selected_style
+            } else {
+                text_style
+            };
+            let mark = if file.selected { "[x] " } else { "[ ] " };
+            let (x, _) = surface.set_stringn(inner.x, y, mark, inner.width as usize, style);
+            let (x, _) = surface.set_stringn(
+                x,
+                y,
+                &file.name,
+                inner.right().saturating_sub(x) as usize,
+                if i == self.cursor {
+                    style
+                } else {
+                    directory_style
+                },
+            );
+            surface.set_stringn(
+                x,
+                y,
+                &format!(" ({})", file.edit_count),
+                inner.right().saturating_sub(x) as usize,
+                style,
+            );
+        }
+
+        let block = Block::bordered();
+        let inner = block.inner(preview_area);
+        block.render(preview_area, surface);
+        let Some(file) = self.files.get(self.cursor) else {
+            return;
+        };
+        if file.changes.is_empty() {
+            surface.set_stringn(
+                inner.x,
+                inner.y,
+                "<No preview available>",
+                inner.width as usize,
+                text_style,
+            );
+            return;
+        }
+
+        let gutter_width = file
+            .changes
+            .last()
+            .map_or(1, |change| (change.line + 1).to_string().len());
+        let mut y = inner.y;
+        'changes: for change in &file.changes {
+            for (prefix, lines, style) in [
+                ("-", &change.old, minus_style),
+                ("+", &change.new, plus_style),
+            ] {
+                for (offset, line) in lines.lines().enumerate() {
+                    if y >= inner.bottom() {
+                        break 'changes;
+                    }
+                    let line_nr = if prefix == "-" {
+                        format!("{:>gutter_width$} ", change.line + offset + 1)
+                    } else {
+                        " ".repeat(gutter_width + 1)
+                    };
+                    let (x, _) = surface.set_stringn(
+                        inner.x,
+                        y,
+                        &line_nr,
+                        inner.width as usize,
+                        line_nr_style,
+                    );
+                    surface.set_stringn(
+                        x,
+                        y,
+                        &format!("{prefix} {line}"),
+                        inner.right().saturating_sub(x) as usize,
+                        style,
+                    );
+                    y += 1;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_edit(
+        (start_line, start_char): (u32, u32),
+        (end_line, end_char): (u32, u32),
+        new_text: &str,
+    ) -> lsp::TextEdit {
+        lsp::TextEdit {
+            range: lsp::Range::new(
+                lsp::Position::new(start_line, start_char),
+                lsp::Position::new(end_line, end_char),
+            ),
+            new_text: new_text.to_string(),
+        }
+    }
+
+    #[test]
+    fn changed_lines_groups_edits_by_line() {
+        let text = Rope::from("let foo = 1;\nbar(foo, foo);\n\nfoo\n");
+        let edits = [
+            text_edit((3, 0), (3, 3), "baz"),
+            text_edit((1, 4), (1, 7), "baz"),
+            text_edit((0, 4), (0, 7), "baz"),
+            text_edit((1, 9), (1, 12), "baz"),
+        ];
+        let changes = changed_lines(&text, &edits, OffsetEncoding::Utf8);
+        assert_eq!(
+            changes,
+            vec![
+                ChangedLines {
+                    line: 0,
+                    old: "let foo = 1;".into(),
+                    new: "let baz = 1;".into(),
+                },
+                ChangedLines {
+                    line: 1,
+                    old: "bar(foo, foo);".into(),
+                    new: "bar(baz, baz);".into(),
+                },
+                ChangedLines {
+                    line: 3,
+                    old: "foo".into(),
+                    new: "baz".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn filter_edit_excludes_files() {
+        let a = lsp::Url::parse("file:///a.rs").unwrap();
+        let b = lsp::Url::parse("file:///b.rs").unwrap();
+        let document_edit = |uri: &lsp::Url| {
+            lsp::DocumentChangeOperation::Edit(lsp::TextDocumentEdit {
+                text_document: lsp::OptionalVersionedTextDocumentIdentifier {
+                    uri: uri.clone(),
+                    version: None,
+                },
+                edits: vec![lsp::OneOf::Left(text_edit((0, 0), (0, 1), "x"))],
+            })
+        };
+        let rename = lsp::DocumentChangeOperation::Op(lsp::ResourceOp::Rename(lsp::RenameFile {
+            old_uri: b.clone(),
+            new_uri: lsp::Url::parse("file:///c.rs").unwrap(),
+            options: None,
+            annotation_id: None,
+        }));
+        let edit = lsp::WorkspaceEdit {
+            document_changes: Some(lsp::DocumentChanges::Operations(vec![
+                document_edit(&a),
+                document_edit(&b),
+                rename.clone(),
+            ])),
+            ..Default::default()
+        };
+
+        assert_eq!(text_edits(&edit).len(), 2);
+        let filtered = filter_edit(&edit, &HashSet::from([&b]));
+        assert_eq!(
+            filtered.document_changes,
+            Some(lsp::DocumentChanges::Operations(vec![
+                document_edit(&a),
+                rename
+            ]))
+        );
+    }
+}
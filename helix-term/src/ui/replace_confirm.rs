@@ -0,0 +1,175 @@
+//! Confirmation of the replacements of `:replace-interactive`. The matches are walked one by one
+//! and the accepted replacements are applied as a single transaction once all matches were
+//! visited or the replacement was stopped.
+
+use std::ops::Range;
+
+use helix_core::{Selection, Transaction};
+use helix_view::{
+    align_view, graphics::Rect, view::ViewPosition, Align, DocumentId, Editor, ViewId,
+};
+use tui::buffer::Buffer as Surface;
+
+use crate::{
+    compositor::{Component, Compositor, Context, Event, EventResult},
+    ctrl, key,
+};
+
+/// A match of the pattern and the text that replaces it.
+#[derive(Debug)]
+pub struct Replacement {
+    /// The char range of the match.
+    pub range: Range<usize>,
+    pub matched: String,
+    pub replacement: String,
+}
+
+pub struct ReplaceConfirm {
+    doc_id: DocumentId,
+    view_id: ViewId,
+    replacements: Vec<Replacement>,
+    /// The index of the replacement that is being confirmed.
+    current: usize,
+    accepted: Vec<usize>,
+    selection: Selection,
+    view_offset: ViewPosition,
+}
+
+impl ReplaceConfirm {
+    pub fn new(editor: &mut Editor, replacements: Vec<Replacement>) -> Self {
+        let (view, doc) = current!(editor);
+        let replace = Self {
+            doc_id: doc.id(),
+            view_id: view.id,
+            replacements,
+            current: 0,
+            accepted: Vec::new(),
+            selection: doc.selection(view.id).clone(),
+            view_offset: doc.view_offset(view.id),
+        };
+        replace.select_current(editor);
+        replace
+    }
+
+    /// Selects the current match and scrolls it into view.
+    fn select_current(&self, editor: &mut Editor) {
+        let Some(replacement) = self.replacements.get(self.current) else {
+            return;
+        };
+        let doc = doc_mut!(editor, &self.doc_id);
+        let view = view_mut!(editor, self.view_id);
+        let Range { start, end } = replacement.range;
+        doc.set_selection(view.id, Selection::single(start, end));
+        if view
+            .screen_coords_at_pos(doc, doc.text().slice(..), start)
+            .is_none()
+        {
+            align_view(doc, view, Align::Center);
+        }
+    }
+
+    /// Moves to the next match, applies the accepted replacements if it was the last one.
+    fn next(&mut self, editor: &mut Editor) -> EventResult {
+        self.current += 1;
+        if self.current < self.replacements.len() {
+            self.select_current(editor);
+            return EventResult::Consumed(None);
+        }
+        self.finish(editor)
+    }
+
+    fn finish(&mut self, editor: &mut Editor) -> EventResult {
+        let doc = doc_mut!(editor, &self.doc_id);
+        let view = view_mut!(editor, self.view_id);
+        doc.set_selection(view.id, self.selection.clone());
+        doc.set_view_offset(view.id, self.view_offset);
+        if !self.accepted.is_empty() {
+            let transaction = Transaction::change(
+                doc.text(),
+                self.accepted.iter().map(|&i| {
+                    let replacement = &self.replacements[i];
+                    (
+                        replacement.range.start,
+                        replacement.range.end,
+                        Some(replacement.replacement.as_str().into()),
+                    )
+                }),
+            );
+            doc.apply(&transaction, view.id);
+            doc.append_changes_to_history(view);
+        }
+        let (count, total) = (self.accepted.len(), self.replacements.len());
+        let plural = if total == 1 { "" } else { "es" };
+        editor.set_status(format!("Replaced {count} of {total} match{plural}"));
+        EventResult::Consumed(Some(Box::new(|compositor: &mut Compositor, _| {
+            compositor.pop();
+        })))
+    }
+}
+
+impl Component for ReplaceConfirm {
+    fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        let Event::Key(event) = event else {
+            return EventResult::Ignored(None);
+        };
+        match *event {
+            key!('y') => {
+                self.accepted.push(self.current);
+                self.next(cx.editor)
+            }
+            key!('n') => self.next(cx.editor),
+            key!('a') => {
+                self.accepted.extend(self.current..self.replacements.len());
+                self.finish(cx.editor)
+            }
+            key!('l') => {
+                self.accepted.push(self.current);
+                self.finish(cx.editor)
+            }
+            key!('q') | key!(Esc) | ctrl!('c') => self.finish(cx.editor),
+            // the matches must not change until the replacements are applied
+            _ => EventResult::Consumed(None),
+        }
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        let Some(replacement) = self.replacements.get(self.current) else {
+            return;
+        };
+        let theme = &cx.editor.theme;
+
+        // preview the replacement in place of the match
+        let view = cx.editor.tree.get(self.view_id);
+        let doc = &cx.editor.documents[&self.doc_id];
+        let inner = view.inner_area(doc);
+        if let Some(pos) =
+            view.screen_coords_at_pos(doc, doc.text().slice(..), replacement.range.start)
+        {
+            let x = inner.x + pos.col as u16;
+            let y = inner.y + pos.row as u16;
+            let width = inner.right().saturating_sub(x) as usize;
+            let style = theme.get("diff.plus");
+            surface.set_stringn(x, y, &replacement.replacement, width, style);
+            if replacement.replacement.is_empty() {
+                surface.set_stringn(x, y, &replacement.matched, width, theme.get("diff.minus"));
+            }
+        }
+
+        let message = format!(
+            "Replace '{}' with '{}'? [{}/{}] (y)es (n)o (a)ll (l)ast (q)uit",
+            replacement.matched.escape_debug(),
+            replacement.replacement.escape_debug(),
+            self.current + 1,
+            self.replacements.len()
+        );
+        let line = area.clip_top(area.height.saturating_sub(1));
+        surface.clear_with(line, theme.get("ui.background"));
+        surface.set_stringn(
+            line.x,
+            line.y,
+            &message,
+            line.width as usize,
+            theme.get("ui.text"),
+        );
+    }
+}
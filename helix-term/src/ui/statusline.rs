@@ -64,8 +64,7 @@ pub fn render(context: &mut RenderContext, viewport: Rect, surface: &mut Surface
     let config = context.editor.config();
 
     for element_id in &config.statusline.left {
-        let render = get_render_function(*element_id);
-        (render)(context, |context, span| {
+        render_element(context, element_id, |context, span| {
             append(&mut context.parts.left, span, base_style)
         });
     }
@@ -80,8 +79,7 @@ pub fn render(context: &mut RenderContext, viewport: Rect, surface: &mut Surface
     // Right side of the status line.
 
     for element_id in &config.statusline.right {
-        let render = get_render_function(*element_id);
-        (render)(context, |context, span| {
+        render_element(context, element_id, |context, span| {
             append(&mut context.parts.right, span, base_style)
         })
     }
@@ -99,8 +97,7 @@ pub fn render(context: &mut RenderContext, viewport: Rect, surface: &mut Surface
     // Center of the status line.
 
     for element_id in &config.statusline.center {
-        let render = get_render_function(*element_id);
-        (render)(context, |context, span| {
+        render_element(context, element_id, |context, span| {
             append(&mut context.parts.center, span, base_style)
         })
     }
@@ -125,7 +122,22 @@ fn append<'a>(buffer: &mut Spans<'a>, mut span: Span<'a>, base_style: Style) {
     buffer.0.push(span);
 }
 
-fn get_render_function<'a, F>(element_id: StatusLineElementID) -> impl Fn(&mut RenderContext<'a>, F)
+fn render_element<'a, F>(
+    context: &mut RenderContext<'a>,
+    element_id: &StatusLineElementID,
+    write: F,
+) where
+    F: Fn(&mut RenderContext<'a>, Span<'a>) + Copy,
+{
+    match element_id {
+        StatusLineElementID::Segment(name) => render_segment(context, name, write),
+        element_id => (get_render_function(element_id))(context, write),
+    }
+}
+
+fn get_render_function<'a, F>(
+    element_id: &StatusLineElementID,
+) -> impl Fn(&mut RenderContext<'a>, F)
 where
     F: Fn(&mut RenderContext<'a>, Span<'a>) + Copy,
 {
@@ -139,6 +151,8 @@ fn get_render_function<'a, F>(element_id: StatusLineElementID) -> impl Fn(&mut R
             render_file_modification_indicator
         }
         helix_view::editor::StatusLineElement::ReadOnlyIndicator => render_read_only_indicator,
+        helix_view::editor::StatusLineElement::RemoteIndicator => render_remote_indicator,
+        helix_view::editor::StatusLineElement::LargeFileIndicator => render_large_file_indicator,
         helix_view::editor::StatusLineElement::FileEncoding => render_file_encoding,
         helix_view::editor::StatusLineElement::FileLineEnding => render_file_line_ending,
         helix_view::editor::StatusLineElement::FileIndentStyle => render_file_indent_style,
@@ -157,6 +171,9 @@ fn get_render_function<'a, F>(element_id: StatusLineElementID) -> impl Fn(&mut R
         helix_view::editor::StatusLineElement::VersionControl => render_version_control,
         helix_view::editor::StatusLineElement::Register => render_register,
         helix_view::editor::StatusLineElement::CurrentWorkingDirectory => render_cwd,
+        helix_view::editor::StatusLineElement::Segment(_) => {
+            unreachable!("segments are rendered by `render_segment`")
+        }
     }
 }
 
@@ -446,14 +463,7 @@ fn render_file_name<'a, F>(context: &mut RenderContext<'a>, write: F)
 where
     F: Fn(&mut RenderContext<'a>, Span<'a>) + Copy,
 {
-    let title = {
-        let rel_path = context.doc.relative_path();
-        let path = rel_path
-            .as_ref()
-            .map(|p| p.to_string_lossy())
-            .unwrap_or_else(|| SCRATCH_BUFFER_NAME.into());
-        format!(" {} ", path)
-    };
+    let title = format!(" {} ", context.doc.display_name());
 
     write(context, title.into());
 }
@@ -491,7 +501,7 @@ fn render_read_only_indicator<'a, F>(context: &mut RenderContext<'a>, write: F)
 where
     F: Fn(&mut RenderContext<'a>, Span<'a>) + Copy,
 {
-    let title = if context.doc.readonly {
+    let title = if context.doc.readonly() {
         " [readonly] "
     } else {
         ""
@@ -499,6 +509,30 @@ fn render_read_only_indicator<'a, F>(context: &mut RenderContext<'a>, write: F)
     write(context, title.into());
 }
 
+fn render_remote_indicator<'a, F>(context: &mut RenderContext<'a>, write: F)
+where
+    F: Fn(&mut RenderContext<'a>, Span<'a>) + Copy,
+{
+    let title = if context.doc.remote.is_some() {
+        " [remote] "
+    } else {
+        ""
+    };
+    write(context, title.into());
+}
+
+fn render_large_file_indicator<'a, F>(context: &mut RenderContext<'a>, write: F)
+where
+    F: Fn(&mut RenderContext<'a>, Span<'a>) + Copy,
+{
+    let title = if context.doc.large_file {
+        " [large] "
+    } else {
+        ""
+    };
+    write(context, title.into());
+}
+
 fn render_file_base_name<'a, F>(context: &mut RenderContext<'a>, write: F)
 where
     F: Fn(&mut RenderContext<'a>, Span<'a>) + Copy,
@@ -554,6 +588,32 @@ fn render_register<'a, F>(context: &mut RenderContext<'a>, write: F)
     }
 }
 
+fn render_segment<'a, F>(context: &mut RenderContext<'a>, name: &str, write: F)
+where
+    F: Fn(&mut RenderContext<'a>, Span<'a>) + Copy,
+{
+    let config = context.editor.config();
+    let Some(segment) = config.statusline.segments.get(name) else {
+        return;
+    };
+    let text = match segment.register {
+        Some(register) => context
+            .editor
+            .registers
+            .first(register, context.editor)
+            .and_then(|value| value.lines().next().map(ToOwned::to_owned)),
+        None => context.editor.statusline_segments.get(name).cloned(),
+    };
+    let Some(text) = text.filter(|text| !text.is_empty()) else {
+        return;
+    };
+    let style = segment
+        .style
+        .as_deref()
+        .map_or_else(Style::default, |scope| context.editor.theme.get(scope));
+    write(context, Span::styled(format!(" {text} "), style));
+}
+
 fn render_file_indent_style<'a, F>(context: &mut RenderContext<'a>, write: F)
 where
     F: Fn(&mut RenderContext<'a>, Span<'a>) + Copy,
@@ -0,0 +1,451 @@
+//! A terminal emulator pane next to the editor views, opened with `:terminal` or `:vterminal`.
+//!
+//! The shell runs in a pseudo terminal and its output is interpreted by [vt100] on a
+//! background thread. The pane is part of the [EditorView](super::EditorView): when it is
+//! focused, all key events except [TOGGLE_FOCUS_KEY] are sent to the shell.
+
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+use helix_view::{
+    graphics::{Color, Modifier, Rect, Style},
+    input::KeyEvent,
+    keyboard::{KeyCode, KeyModifiers},
+    Editor,
+};
+use parking_lot::Mutex;
+use tui::buffer::Buffer as Surface;
+
+use crate::{
+    compositor::{Context, Event, EventResult},
+    ctrl,
+};
+
+/// The key that moves the focus between the editor and the terminal.
+pub const TOGGLE_FOCUS_KEY: KeyEvent = ctrl!('\\');
+/// The number of lines kept in the scrollback of the terminal.
+const SCROLLBACK_LINES: usize = 1000;
+
+/// Where the terminal is placed relative to the editor views.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminalLayout {
+    /// Below the views.
+    Horizontal,
+    /// To the right of the views.
+    Vertical,
+}
+
+pub struct TerminalPane {
+    pub layout: TerminalLayout,
+    focused: bool,
+    shell: String,
+    pty: pty::Pty,
+    parser: Arc<Mutex<vt100::Parser>>,
+    exited: Arc<AtomicBool>,
+    /// The size of the pseudo terminal as `(rows, cols)`.
+    size: (u16, u16),
+}
+
+impl TerminalPane {
+    /// Spawns an interactive shell in `cwd`: `$SHELL` or the first word of the `shell` setting.
+    pub fn spawn(editor: &Editor, layout: TerminalLayout, cwd: &Path) -> io::Result<Self> {
+        let shell = std::env::var("SHELL")
+            .ok()
+            .or_else(|| editor.config().shell.first().cloned())
+            .ok_or_else(|| io::Error::other("no shell configured"))?;
+        let size = (24, 80);
+        let (pty, reader) = pty::Pty::spawn(&shell, cwd, size)?;
+        let parser = Arc::new(Mutex::new(vt100::Parser::new(
+            size.0,
+            size.1,
+            SCROLLBACK_LINES,
+        )));
+        let exited = Arc::new(AtomicBool::new(false));
+        spawn_reader(reader, parser.clone(), exited.clone())?;
+        Ok(Self {
+            layout,
+            focused: true,
+            shell,
+            pty,
+            parser,
+            exited,
+            size,
+        })
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    pub fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    pub fn has_exited(&self) -> bool {
+        self.exited.load(Ordering::Relaxed)
+    }
+
+    /// Sends text to the shell as if it was typed.
+    pub fn send_text(&mut self, text: &str) -> io::Result<()> {
+        self.pty.write_all(text.replace('\n', "\r").as_bytes())
+    }
+
+    /// Sends the key events to the shell while the terminal is focused.
+    pub fn handle_event(&mut self, event: &Event, _cx: &mut Context) -> EventResult {
+        let bytes = match event {
+            Event::Key(key) if *key == TOGGLE_FOCUS_KEY => {
+                self.focused = false;
+                return EventResult::Consumed(None);
+            }
+            Event::Key(key) => {
+                let application_cursor = self.parser.lock().screen().application_cursor();
+                match key_to_bytes(*key, application_cursor) {
+                    Some(bytes) => bytes,
+                    None => return EventResult::Consumed(None),
+                }
+            }
+            Event::Paste(text) => {
+                if self.parser.lock().screen().bracketed_paste() {
+                    format!("\x1b[200~{text}\x1b[201~").into_bytes()
+                } else {
+                    text.replace('\n', "\r").into_bytes()
+                }
+            }
+            _ => return EventResult::Ignored(None),
+        };
+        if let Err(err) = self.pty.write_all(&bytes) {
+            log::error!("failed to write to the terminal: {err}");
+        }
+        EventResult::Consumed(None)
+    }
+
+    pub fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        let theme = &cx.editor.theme;
+        let title_style = if self.focused {
+            theme.get("ui.statusline")
+        } else {
+            theme.get("ui.statusline.inactive")
+        };
+        let base_style = theme.get("ui.background");
+        surface.clear_with(area, base_style);
+
+        let title = if self.has_exited() {
+            format!(" {} (exited)", self.shell)
+        } else {
+            format!(" {}", self.shell)
+        };
+        surface.set_style(area.with_height(1), title_style);
+        surface.set_stringn(area.x, area.y, &title, area.width as usize, title_style);
+
+        let inner = area.clip_top(1);
+        if inner.width == 0 || inner.height == 0 {
+            return;
+        }
+        self.resize((inner.height, inner.width));
+
+        let parser = self.parser.lock();
+        let screen = parser.screen();
+        for row in 0..inner.height {
+            for col in 0..inner.width {
+                let Some(cell) = screen.cell(row, col) else {
+                    continue;
+                };
+                if cell.is_wide_continuation() {
+                    continue;
+                }
+                let contents = cell.contents();
+                let symbol = if contents.is_empty() { " " } else { &contents };
+                surface.set_string(
+                    inner.x + col,
+                    inner.y + row,
+                    symbol,
+                    base_style.patch(cell_style(cell)),
+                );
+            }
+        }
+
+        if self.focused && !screen.hide_cursor() {
+            let (row, col) = screen.cursor_position();
+            if row < inner.height && col < inner.width {
+                let cursor = Rect::new(inner.x + col, inner.y + row, 1, 1);
+                surface.set_style(cursor, Style::default().add_modifier(Modifier::REVERSED));
+            }
+        }
+    }
+
+    fn resize(&mut self, size: (u16, u16)) {
+        if size == self.size {
+            return;
+        }
+        self.size = size;
+        self.parser.lock().set_size(size.0, size.1);
+        if let Err(err) = self.pty.resize(size) {
+            log::error!("failed to resize the terminal: {err}");
+        }
+    }
+}
+
+/// Reads the output of the shell until it exits.
+fn spawn_reader(
+    mut reader: File,
+    parser: Arc<Mutex<vt100::Parser>>,
+    exited: Arc<AtomicBool>,
+) -> io::Result<()> {
+    thread::Builder::new()
+        .name("terminal-reader".to_string())
+        .spawn(move || {
+            let mut buf = [0; 4096];
+            loop {
+                match reader.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => parser.lock().process(&buf[..n]),
+                }
+                helix_event::request_redraw();
+            }
+            exited.store(true, Ordering::Relaxed);
+            helix_event::request_redraw();
+        })?;
+    Ok(())
+}
+
+fn cell_style(cell: &vt100::Cell) -> Style {
+    fn color(color: vt100::Color) -> Option<Color> {
+        match color {
+            vt100::Color::Default => None,
+            vt100::Color::Idx(i) => Some(Color::Indexed(i)),
+            vt100::Color::Rgb(r, g, b) => Some(Color::Rgb(r, g, b)),
+        }
+    }
+
+    let mut style = Style::default();
+    if let Some(fg) = color(cell.fgcolor()) {
+        style = style.fg(fg);
+    }
+    if let Some(bg) = color(cell.bgcolor()) {
+        style = style.bg(bg);
+    }
+    let modifiers = [
+        (cell.bold(), Modifier::BOLD),
+        (cell.italic(), Modifier::ITALIC),
+        (cell.underline(), Modifier::UNDERLINED),
+        (cell.inverse(), Modifier::REVERSED),
+    ];
+    for (enabled, modifier) in modifiers {
+        if enabled {
+            style = style.add_modifier(modifier);
+        }
+    }
+    style
+}
+
+/// Encodes a key event as the input a terminal sends for it.
+fn key_to_bytes(key: KeyEvent, application_cursor: bool) -> Option<Vec<u8>> {
+    let cursor = |code: char| {
+        let prefix = if application_cursor { "\x1bO" } else { "\x1b[" };
+        format!("{prefix}{code}")
+    };
+    let text = match key.code {
+        KeyCode::Char(ch) if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            let ch = ch.to_ascii_lowercase();
+            match ch {
+                'a'..='z' => ((ch as u8 - b'a' + 1) as char).to_string(),
+                '@' | ' ' => "\0".to_string(),
+                '[' => "\x1b".to_string(),
+                '\\' => "\x1c".to_string(),
+                ']' => "\x1d".to_string(),
+                _ => return None,
+            }
+        }
+        KeyCode::Char(ch) => ch.to_string(),
+        KeyCode::Enter => "\r".to_string(),
+        KeyCode::Backspace => "\x7f".to_string(),
+        KeyCode::Tab if key.modifiers.contains(KeyModifiers::SHIFT) => "\x1b[Z".to_string(),
+        KeyCode::Tab => "\t".to_string(),
+        KeyCode::Esc => "\x1b".to_string(),
+        KeyCode::Up => cursor('A'),
+        KeyCode::Down => cursor('B'),
+        KeyCode::Right => cursor('C'),
+        KeyCode::Left => cursor('D'),
+        KeyCode::Home => cursor('H'),
+        KeyCode::End => cursor('F'),
+        KeyCode::Insert => "\x1b[2~".to_string(),
+        KeyCode::Delete => "\x1b[3~".to_string(),
+        KeyCode::PageUp => "\x1b[5~".to_string(),
+        KeyCode::PageDown => "\x1b[6~".to_string(),
+        KeyCode::F(n @ 1..=4) => format!("\x1bO{}", (b'P' + n - 1) as char),
+        KeyCode::F(n @ 5..=12) => {
+            const CODES: [u8; 8] = [15, 17, 18, 19, 20, 21, 23, 24];
+            format!("\x1b[{}~", CODES[n as usize - 5])
+        }
+        _ => return None,
+    };
+    let mut bytes = Vec::with_capacity(text.len() + 1);
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        bytes.push(0x1b);
+    }
+    bytes.extend_from_slice(text.as_bytes());
+    Some(bytes)
+}
+
+#[cfg(unix)]
+mod pty {
+    use std::{
+        fs::File,
+        io::{self, Write},
+        os::{
+            fd::{AsRawFd, FromRawFd, OwnedFd},
+            unix::process::CommandExt,
+        },
+        path::Path,
+        process::{Child, Command, Stdio},
+        ptr,
+    };
+
+    /// The master side of a pseudo terminal with a shell running on the other side.
+    pub struct Pty {
+        master: File,
+        child: Child,
+    }
+
+    impl Pty {
+        /// Spawns `shell` in a new pseudo terminal of `(rows, cols)` and returns the pseudo
+        /// terminal together with a handle to read the output of the shell.
+        pub fn spawn(shell: &str, cwd: &Path, size: (u16, u16)) -> io::Result<(Self, File)> {
+            let mut master = -1;
+            let mut slave = -1;
+            let mut winsize = winsize(size);
+            // SAFETY: the pointers are valid for the duration of the call.
+            let result = unsafe {
+                libc::openpty(
+                    &mut master,
+                    &mut slave,
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                    &mut winsize,
+                )
+            };
+            if result != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            // SAFETY: openpty succeeded, so both file descriptors are open and owned by us.
+            let (master, slave) =
+                unsafe { (OwnedFd::from_raw_fd(master), OwnedFd::from_raw_fd(slave)) };
+            // the shell must not inherit the master side
+            // SAFETY: `master` is an open file descriptor.
+            if unsafe { libc::fcntl(master.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC) } == -1 {
+                return Err(io::Error::last_os_error());
+            }
+
+            let mut command = Command::new(shell);
+            command
+                .current_dir(cwd)
+                .env("TERM", "xterm-256color")
+                .stdin(Stdio::from(slave.try_clone()?))
+                .stdout(Stdio::from(slave.try_clone()?))
+                .stderr(Stdio::from(slave));
+            // SAFETY: only async-signal-safe functions are called between fork and exec.
+            unsafe {
+                command.pre_exec(|| {
+                    // start a new session with the pseudo terminal as the controlling terminal
+                    if libc::setsid() == -1 || libc::ioctl(0, libc::TIOCSCTTY as _, 0) == -1 {
+                        return Err(io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+            }
+            let child = command.spawn()?;
+
+            let master = File::from(master);
+            let reader = master.try_clone()?;
+            Ok((Self { master, child }, reader))
+        }
+
+        pub fn write_all(&mut self, bytes: &[u8]) -> io::Result<()> {
+            self.master.write_all(bytes)?;
+            self.master.flush()
+        }
+
+        pub fn resize(&mut self, size: (u16, u16)) -> io::Result<()> {
+            let winsize = winsize(size);
+            // SAFETY: `master` is an open file descriptor and `winsize` outlives the call.
+            if unsafe { libc::ioctl(self.master.as_raw_fd(), libc::TIOCSWINSZ as _, &winsize) }
+                == -1
+            {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        }
+    }
+
+    impl Drop for Pty {
+        fn drop(&mut self) {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+
+    fn winsize((rows, cols): (u16, u16)) -> libc::winsize {
+        libc::winsize {
+            ws_row: rows,
+            ws_col: cols,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod pty {
+    use std::{fs::File, io, path::Path};
+
+    pub struct Pty;
+
+    impl Pty {
+        pub fn spawn(_shell: &str, _cwd: &Path, _size: (u16, u16)) -> io::Result<(Self, File)> {
+            Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "the terminal is not supported on this platform",
+            ))
+        }
+
+        pub fn write_all(&mut self, _bytes: &[u8]) -> io::Result<()> {
+            Ok(())
+        }
+
+        pub fn resize(&mut self, _size: (u16, u16)) -> io::Result<()> {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{alt, key, shift};
+
+    #[test]
+    fn encode_keys() {
+        let bytes = |key| key_to_bytes(key, false);
+        assert_eq!(bytes(key!('a')), Some(b"a".to_vec()));
+        assert_eq!(bytes(ctrl!('c')), Some(b"\x03".to_vec()));
+        assert_eq!(bytes(alt!('b')), Some(b"\x1bb".to_vec()));
+        assert_eq!(bytes(key!(Enter)), Some(b"\r".to_vec()));
+        assert_eq!(bytes(shift!(Tab)), Some(b"\x1b[Z".to_vec()));
+        assert_eq!(bytes(key!(Up)), Some(b"\x1b[A".to_vec()));
+        assert_eq!(key_to_bytes(key!(Up), true), Some(b"\x1bOA".to_vec()));
+        let f5 = KeyEvent {
+            code: KeyCode::F(5),
+            modifiers: KeyModifiers::NONE,
+        };
+        assert_eq!(bytes(f5), Some(b"\x1b[15~".to_vec()));
+        assert_eq!(bytes(key!(CapsLock)), None);
+    }
+}
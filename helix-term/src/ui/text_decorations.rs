@@ -6,9 +6,13 @@
 
 use crate::ui::document::{LinePos, TextRenderer};
 
+pub use blame::InlineBlame;
 pub use diagnostics::InlineDiagnostics;
+pub use inline_completion::InlineCompletionLines;
 
+mod blame;
 mod diagnostics;
+mod inline_completion;
 
 /// Decorations are the primary mechanism for extending the text rendering.
 ///
@@ -75,7 +79,9 @@ fn skip_concealed_anchor(&mut self, conceal_end_char_idx: usize) -> usize {
     ///
     /// # Returns
     ///
-    /// The char idx of the next grapheme that  this function should be called for
+    /// The char idx of the next grapheme that  this function should be called for.
+    /// Returning `grapheme.char_idx` calls this function again for the next grapheme
+    /// at the same char idx (for example the text after virtual text).
     fn decorate_grapheme(
         &mut self,
         _renderer: &mut TextRenderer,
@@ -116,7 +122,12 @@ pub fn decorate_grapheme(&mut self, renderer: &mut TextRenderer, grapheme: &Form
                         *hook_char_idx = decoration.skip_concealed_anchor(grapheme.char_idx)
                     }
                     Ordering::Equal => {
-                        *hook_char_idx = decoration.decorate_grapheme(renderer, grapheme)
+                        let next_char_idx = decoration.decorate_grapheme(renderer, grapheme);
+                        let wait = next_char_idx == *hook_char_idx;
+                        *hook_char_idx = next_char_idx;
+                        if wait {
+                            break;
+                        }
                     }
                     Ordering::Greater => break,
                 }
@@ -0,0 +1,49 @@
+use helix_core::Position;
+use helix_view::theme::Style;
+
+use crate::ui::document::{LinePos, TextRenderer};
+use crate::ui::text_decorations::Decoration;
+
+/// Renders the blame of the cursor line as virtual text after the end of the line.
+pub struct InlineBlame {
+    line: usize,
+    text: String,
+    style: Style,
+}
+
+impl InlineBlame {
+    pub fn new(line: usize, text: String, style: Style) -> Self {
+        InlineBlame { line, text, style }
+    }
+}
+
+impl Decoration for InlineBlame {
+    fn render_virt_lines(
+        &mut self,
+        renderer: &mut TextRenderer,
+        pos: LinePos,
+        virt_off: Position,
+    ) -> Position {
+        if pos.doc_line != self.line || self.text.is_empty() {
+            return Position::new(0, 0);
+        }
+        // only render on the first visual line of a soft-wrapped line
+        let text = std::mem::take(&mut self.text);
+        // leave some space after the end of the line (or the end of line diagnostic)
+        let col = virt_off.col + 4;
+        if !renderer.column_in_bounds(col, 1) {
+            return Position::new(0, 0);
+        }
+        let draw_col = (col - renderer.offset.col) as u16;
+        let (end_col, _) = renderer.set_string_truncated(
+            renderer.viewport.x + draw_col,
+            pos.visual_line,
+            &text,
+            renderer.viewport.width.saturating_sub(draw_col) as usize,
+            |_| self.style,
+            true,
+            false,
+        );
+        Position::new(0, (end_col - renderer.viewport.x - draw_col) as usize + 4)
+    }
+}
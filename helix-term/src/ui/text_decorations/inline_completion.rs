@@ -0,0 +1,99 @@
+use helix_core::doc_formatter::FormattedGrapheme;
+use helix_core::graphemes::Grapheme;
+use helix_core::unicode::segmentation::UnicodeSegmentation;
+use helix_core::Position;
+use helix_view::annotations::inline_completion::InlineCompletion;
+use helix_view::theme::Style;
+use helix_view::Document;
+
+use crate::ui::document::{LinePos, TextRenderer};
+use crate::ui::text_decorations::Decoration;
+
+/// Renders the lines of an inline completion that follow the cursor line into the virtual
+/// lines reserved by [`InlineCompletion::line_annotation`].
+pub struct InlineCompletionLines<'a> {
+    completion: &'a InlineCompletion,
+    anchor: usize,
+    reached: bool,
+    horizontal_offset: usize,
+    tab_width: u16,
+    style: Style,
+}
+
+impl<'a> InlineCompletionLines<'a> {
+    pub fn new(
+        doc: &Document,
+        completion: &'a InlineCompletion,
+        horizontal_offset: usize,
+        style: Style,
+    ) -> Self {
+        InlineCompletionLines {
+            completion,
+            anchor: completion.virtual_lines_anchor(doc.text().slice(..)),
+            reached: false,
+            horizontal_offset,
+            tab_width: doc.tab_width() as u16,
+            style,
+        }
+    }
+}
+
+impl Decoration for InlineCompletionLines<'_> {
+    fn reset_pos(&mut self, pos: usize) -> usize {
+        self.reached = false;
+        if pos <= self.anchor {
+            self.anchor
+        } else {
+            usize::MAX
+        }
+    }
+
+    fn skip_concealed_anchor(&mut self, _conceal_end_char_idx: usize) -> usize {
+        usize::MAX
+    }
+
+    fn decorate_grapheme(
+        &mut self,
+        _renderer: &mut TextRenderer,
+        grapheme: &FormattedGrapheme,
+    ) -> usize {
+        // the ghost text itself is virtual text anchored at the cursor which is usually
+        // the end of the line, wait for the line end
+        if grapheme.is_virtual() {
+            return self.anchor;
+        }
+        self.reached = true;
+        usize::MAX
+    }
+
+    fn render_virt_lines(
+        &mut self,
+        renderer: &mut TextRenderer,
+        pos: LinePos,
+        virt_off: Position,
+    ) -> Position {
+        if !std::mem::take(&mut self.reached) {
+            return Position::new(0, 0);
+        }
+        let style = renderer.text_style.patch(self.style);
+        let lines = self.completion.virtual_lines();
+        for (i, line) in lines.iter().enumerate() {
+            let row = pos.visual_line + (virt_off.row + i) as u16;
+            let mut col = 0;
+            for g in line.graphemes(true) {
+                let grapheme = Grapheme::new(g.into(), col, self.tab_width);
+                let width = grapheme.width();
+                if col >= self.horizontal_offset {
+                    renderer.draw_decoration_grapheme(
+                        grapheme,
+                        style,
+                        row,
+                        (col - self.horizontal_offset) as u16,
+                    );
+                }
+                col += width;
+            }
+        }
+        Position::new(lines.len(), 0)
+    }
+}
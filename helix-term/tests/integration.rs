@@ -19,5 +19,6 @@ async fn hello_world() -> anyhow::Result<()> {
     mod command_line;
     mod commands;
     mod movement;
+    mod session;
     mod splits;
 }
@@ -1424,3 +1424,37 @@ async fn delete_append_inside_nested_pair_multi() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn auto_close_tags() -> anyhow::Result<()> {
+    let config = Config {
+        editor: helix_view::editor::Config {
+            auto_close_tags: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let app = || {
+        AppBuilder::new()
+            .with_config(config.clone())
+            .with_file("foo.html", None)
+    };
+
+    // the closing tag is inserted after the cursor
+    test_with_config(app(), ("#[|\n]#", "i<lt>div<gt>", "<div>#[|<]#/div>\n")).await?;
+    // void elements and closed elements are left alone
+    test_with_config(app(), ("#[|\n]#", "i<lt>br<gt>", "<br>#[|\n]#")).await?;
+    test_with_config(app(), ("<p#[|<]#/p>", "i<gt>", "<p>#[|<]#/p>")).await?;
+
+    // the closing tag follows edits of the opening tag name
+    test_with_config(app(), ("<div#[|>]#</div>", "is", "<divs#[|>]#</divs>")).await?;
+    test_with_config(
+        app(),
+        ("<div#[|>]#</div>", "i<backspace>", "<di#[|>]#</di>"),
+    )
+    .await?;
+    // but not while typing attributes
+    test_with_config(app(), ("<div#[|>]#</div>", "i id", "<div id#[|>]#</div>")).await?;
+
+    Ok(())
+}
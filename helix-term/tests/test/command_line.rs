@@ -103,6 +103,21 @@ async fn shell_expansion() -> anyhow::Result<()> {
     // Shell expansion is recursive.
     test_statusline(":echo %sh{echo '%{cursor_line}'}", "1", Severity::Info).await?;
 
+    // Variables and registers are quoted for the shell.
+    test_statusline(
+        r#":set-register a hello   'world'<ret>:echo %sh{printf '%%s|' %reg{a} %{line}}"#,
+        "hello   'world'|1|",
+        Severity::Info,
+    )
+    .await?;
+    // ...but not within the quotes of the shell command.
+    test_statusline(
+        r#":set-register a hello   world<ret>:echo %sh{printf '%%s|' "%reg{a}" '%{line}'}"#,
+        "hello   world|1|",
+        Severity::Info,
+    )
+    .await?;
+
     Ok(())
 }
 
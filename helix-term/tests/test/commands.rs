@@ -221,6 +221,48 @@ async fn test_multi_selection_paste() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_paste_register_kinds() -> anyhow::Result<()> {
+    // selections on consecutive lines are pasted as a block
+    test((
+        indoc! {"\
+            #[|a]#bcd
+            #(|e)#
+            "},
+        "y,glp",
+        indoc! {"\
+            abcd#[a|]#
+            e   #(e|)#
+            "},
+    ))
+    .await?;
+
+    // a yanked last line is pasted as a whole line
+    test(("abc\n#[d|]#ef", "xyggp", "abc\n#[def\n|]#def")).await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_select_block() -> anyhow::Result<()> {
+    test((
+        indoc! {"\
+            #[a|]#bcd
+            efgh
+            ijkl
+            "},
+        "<C-v>jl",
+        indoc! {"\
+            #(ab|)#cd
+            #[ef|]#gh
+            ijkl
+            "},
+    ))
+    .await?;
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_multi_selection_shell_commands() -> anyhow::Result<()> {
     // pipe
@@ -274,6 +316,98 @@ async fn test_multi_selection_shell_commands() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_run_shell_command_output() -> anyhow::Result<()> {
+    test_key_sequences(
+        &mut AppBuilder::new().build()?,
+        vec![
+            (
+                Some(":sh echo hello<ret>"),
+                Some(&|app| {
+                    let outputs: Vec<_> = app
+                        .editor
+                        .documents()
+                        .filter(|doc| doc.text().to_string() == "hello\n")
+                        .collect();
+                    assert_eq!(1, outputs.len());
+                    assert_eq!(2, app.editor.tree.views().count());
+                    // the focus stays in the current view
+                    assert_ne!(outputs[0].id(), helix_view::doc!(app.editor).id());
+                    // the output is not a change that needs to be discarded
+                    assert!(!outputs[0].is_modified());
+                }),
+            ),
+            // commands without output don't open a buffer
+            (
+                Some("<C-w>j:q<ret>:sh true<ret>"),
+                Some(&|app| {
+                    assert_eq!(1, app.editor.tree.views().count());
+                    assert_eq!("Command run", app.editor.get_status().unwrap().0);
+                }),
+            ),
+        ],
+        false,
+    )
+    .await
+}
+
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_cancel_shell_commands() -> anyhow::Result<()> {
+    test_key_sequences(
+        &mut AppBuilder::new().with_input_text("#[|lorem]#\n").build()?,
+        vec![
+            (
+                Some(":sh sleep 10<ret><C-c>"),
+                Some(&|app| {
+                    assert!(!app.editor.shell_controller.is_running());
+                    assert_eq!(
+                        "Shell command cancelled",
+                        app.editor.get_status().unwrap().0
+                    );
+                }),
+            ),
+            (
+                Some("|sleep 10; echo foo<ret><C-c>"),
+                Some(&|app| {
+                    assert!(!app.editor.shell_controller.is_running());
+                    assert_eq!(
+                        "Shell command cancelled",
+                        app.editor.get_status().unwrap().0
+                    );
+                    assert_eq!("lorem\n", helix_view::doc!(app.editor).text().to_string());
+                }),
+            ),
+        ],
+        false,
+    )
+    .await
+}
+
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_pipe_output_discarded_when_document_changed() -> anyhow::Result<()> {
+    test_key_sequence(
+        &mut AppBuilder::new().with_input_text("#[|lorem]#\n").build()?,
+        // the document is changed while the command is running
+        Some("|sleep 1; echo foo<ret>iipsum <esc>"),
+        Some(&|app| {
+            let (status, severity) = app.editor.get_status().unwrap();
+            assert_eq!(*severity, helix_view::editor::Severity::Error);
+            assert_eq!(
+                status,
+                "The document changed while the shell command was running"
+            );
+            assert_eq!(
+                "ipsum lorem\n",
+                helix_view::doc!(app.editor).text().to_string()
+            );
+        }),
+        false,
+    )
+    .await
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_undo_redo() -> anyhow::Result<()> {
     // A jumplist selection is created at a point which is undone.
@@ -320,6 +454,48 @@ async fn test_undo_redo() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_select_prev_next() -> anyhow::Result<()> {
+    test(("#[o|]#ne two three", "ww<A-z>", "#[one |]#two three")).await?;
+    test((
+        "#[o|]#ne two three",
+        "ww<A-z><A-z><A-Z>",
+        "#[one |]#two three",
+    ))
+    .await?;
+    test(("#[o|]#ne two three", "ww2<A-z>", "#[o|]#ne two three")).await?;
+
+    // the selections follow the changes to the text
+    test((
+        "#[o|]#ne two three",
+        "wwO<esc><A-z>",
+        "\none #[two |]#three",
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_repeat_last_change() -> anyhow::Result<()> {
+    test(("#[o|]#ne two", "d.", "#[e|]# two")).await?;
+    test(("#[a|]#b", "yp.", "aa#[a|]#b")).await?;
+
+    // undo and redo aren't changes
+    test(("#[a|]#bc", "du.", "#[b|]#c")).await?;
+    test(("#[a|]#bc", "duU.", "#[c|]#")).await?;
+
+    // neither is switching to another buffer
+    test(("#[a|]#bc", "d:new<ret>ga.", "#[c|]#")).await?;
+
+    // the count of `.` replaces the count of the change
+    test(("#[a|]#b", "y2p.", "aaa#[aa|]#b")).await?;
+    test(("#[a|]#b", "y2p3.", "aaa#[aaa|]#b")).await?;
+    test(("#[a|]#b", "yp3.", "aa#[aaa|]#b")).await?;
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_extend_line() -> anyhow::Result<()> {
     // extend with line selected then count
@@ -805,6 +981,377 @@ async fn test_read_file() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_replace_interactive() -> anyhow::Result<()> {
+    // the matches are confirmed one by one once the command ran
+    async fn replace(
+        command: &str,
+        keys: &str,
+        expected: &'static str,
+        status: &'static str,
+    ) -> anyhow::Result<()> {
+        let mut app = helpers::AppBuilder::new()
+            .with_input_text("#[f|]#oo boo foo\nfoo\n")
+            .build()?;
+        test_key_sequences(
+            &mut app,
+            vec![
+                (Some(command), None),
+                (
+                    Some(keys),
+                    Some(&|app| {
+                        let (view, doc) = helix_view::current_ref!(app.editor);
+                        assert_eq!(expected, doc.text().to_string());
+                        // the selection from before the replacement is restored
+                        assert_eq!(helix_core::Selection::single(0, 1), *doc.selection(view.id));
+                        assert_eq!(app.editor.get_status().unwrap().0, status);
+                    }),
+                ),
+            ],
+            false,
+        )
+        .await
+    }
+
+    replace(
+        ":replace-interactive foo bar<ret>",
+        "yny",
+        "bar boo foo\nbar\n",
+        "Replaced 2 of 3 matches",
+    )
+    .await?;
+    replace(
+        ":replace-interactive foo bar<ret>",
+        "na",
+        "foo boo bar\nbar\n",
+        "Replaced 2 of 3 matches",
+    )
+    .await?;
+    replace(
+        ":replace-interactive foo bar<ret>",
+        "nl",
+        "foo boo bar\nfoo\n",
+        "Replaced 1 of 3 matches",
+    )
+    .await?;
+    replace(
+        ":replace-interactive foo bar<ret>",
+        "yq",
+        "bar boo foo\nfoo\n",
+        "Replaced 1 of 3 matches",
+    )
+    .await?;
+    // capture groups can be referred to in the replacement
+    replace(
+        r":replace-interactive (\w)oo ${1}ee<ret>",
+        "a",
+        "fee bee fee\nfee\n",
+        "Replaced 4 of 4 matches",
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_replace_interactive_is_a_single_change() -> anyhow::Result<()> {
+    test_key_sequences(
+        &mut helpers::AppBuilder::new()
+            .with_input_text("#[f|]#oo foo\n")
+            .build()?,
+        vec![
+            (Some(":replace-interactive foo bar<ret>"), None),
+            (
+                Some("a"),
+                Some(&|app| {
+                    assert_eq!("bar bar\n", helix_view::doc!(app.editor).text().to_string());
+                }),
+            ),
+            (
+                Some("u"),
+                Some(&|app| {
+                    assert_eq!("foo foo\n", helix_view::doc!(app.editor).text().to_string());
+                }),
+            ),
+            (
+                Some(":replace-interactive baz bar<ret>"),
+                Some(&|app| {
+                    assert!(app.editor.is_err());
+                    assert_eq!(app.editor.get_status().unwrap().0, "No matches");
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_toggle_inlay_hints_kind() -> anyhow::Result<()> {
+    test_key_sequences(
+        &mut AppBuilder::new().build()?,
+        vec![
+            (
+                Some(":toggle-inlay-hints-kind type<ret>"),
+                Some(&|app| {
+                    let inlay_hints = app.editor.config().lsp.inlay_hints;
+                    assert!(!inlay_hints.type_hints);
+                    assert!(inlay_hints.parameter_hints && inlay_hints.other_hints);
+                    assert_eq!(
+                        app.editor.get_status().unwrap().0,
+                        "type inlay hints are now hidden"
+                    );
+                }),
+            ),
+            (
+                Some(":toggle-inlay-hints-kind Type<ret>"),
+                Some(&|app| {
+                    assert!(app.editor.config().lsp.inlay_hints.type_hints);
+                    assert_eq!(
+                        app.editor.get_status().unwrap().0,
+                        "type inlay hints are now shown"
+                    );
+                }),
+            ),
+            (
+                Some(":toggle-inlay-hints-kind chaining<ret>"),
+                Some(&|app| {
+                    assert!(app.editor.is_err());
+                    assert_eq!(app.editor.config().lsp.inlay_hints, Default::default());
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_buffer_pin_and_move() -> anyhow::Result<()> {
+    let files = [
+        tempfile::NamedTempFile::new()?,
+        tempfile::NamedTempFile::new()?,
+        tempfile::NamedTempFile::new()?,
+    ];
+    let [a, b, c] = &files;
+    let buffers = |app: &Application| -> Vec<_> {
+        app.editor
+            .buffers()
+            .into_iter()
+            .map(|id| {
+                let path = app.editor.documents[&id].path().unwrap();
+                files
+                    .iter()
+                    .position(|file| helix_stdx::path::normalize(file.path()) == *path)
+                    .unwrap()
+            })
+            .collect()
+    };
+
+    test_key_sequences(
+        &mut AppBuilder::new().with_file(a.path(), None).build()?,
+        vec![
+            (
+                Some(&format!(
+                    ":o {}<ret>:o {}<ret>",
+                    b.path().to_string_lossy(),
+                    c.path().to_string_lossy()
+                )),
+                Some(&|app| assert_eq!(buffers(app), [0, 1, 2])),
+            ),
+            // moving stops at the start of the bufferline
+            (
+                Some(":buffer-move-left<ret>:buffer-move-left<ret>:buffer-move-left<ret>"),
+                Some(&|app| assert_eq!(buffers(app), [2, 0, 1])),
+            ),
+            (
+                Some(":bg 3<ret>"),
+                Some(&|app| {
+                    assert_eq!(
+                        helix_view::doc!(app.editor).path(),
+                        Some(&helix_stdx::path::normalize(b.path()))
+                    );
+                }),
+            ),
+            // pinned buffers are listed first and only move among each other
+            (
+                Some(":buffer-pin<ret>:buffer-move-right<ret>"),
+                Some(&|app| {
+                    assert!(helix_view::doc!(app.editor).pinned);
+                    assert_eq!(buffers(app), [1, 2, 0]);
+                }),
+            ),
+            (
+                Some(":bg 4<ret>"),
+                Some(&|app| {
+                    assert!(app.editor.is_err());
+                    assert_eq!(
+                        app.editor.get_status().unwrap().0,
+                        "no buffer 4, 3 buffers are open"
+                    );
+                }),
+            ),
+            // pinned buffers aren't closed
+            (
+                Some(":bg 2<ret>:buffer-close-all<ret>"),
+                Some(&|app| assert_eq!(buffers(app), [1])),
+            ),
+            (
+                Some(":buffer-pin<ret>"),
+                Some(&|app| assert!(!helix_view::doc!(app.editor).pinned)),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_picker_acts_on_marked_items() -> anyhow::Result<()> {
+    let a = tempfile::NamedTempFile::new()?;
+    let b = tempfile::NamedTempFile::new()?;
+    let c = tempfile::NamedTempFile::new()?;
+    let open = format!(
+        ":o {}<ret>:o {}<ret>",
+        b.path().to_string_lossy(),
+        c.path().to_string_lossy()
+    );
+
+    // the buffers are listed most recently used first: c, b, a
+    test_key_sequences(
+        &mut AppBuilder::new().with_file(a.path(), None).build()?,
+        vec![
+            (Some(&open), None),
+            (Some("<space>b"), None),
+            (
+                Some("<tab><down><tab><C-v>"),
+                Some(&|app| {
+                    // the marked buffers are opened rather than the selected one
+                    assert_eq!(app.editor.tree.views().count(), 3);
+                    let paths: Vec<_> = app
+                        .editor
+                        .tree
+                        .views()
+                        .map(|(view, _)| app.editor.documents[&view.doc].path().unwrap().clone())
+                        .collect();
+                    assert!(paths.contains(&helix_stdx::path::normalize(a.path())));
+                    assert!(!paths.contains(&helix_stdx::path::normalize(b.path())));
+                    assert_eq!(
+                        helix_view::doc!(app.editor).path(),
+                        Some(&helix_stdx::path::normalize(a.path()))
+                    );
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    // marking an item twice removes its mark
+    test_key_sequences(
+        &mut AppBuilder::new().with_file(a.path(), None).build()?,
+        vec![
+            (Some(&open), None),
+            (Some("<space>b"), None),
+            (
+                Some("<tab><S-tab><S-tab><down><ret>"),
+                Some(&|app| {
+                    assert_eq!(app.editor.tree.views().count(), 1);
+                    assert_eq!(
+                        helix_view::doc!(app.editor).path(),
+                        Some(&helix_stdx::path::normalize(b.path()))
+                    );
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_buffer_picker_closes_marked_buffers() -> anyhow::Result<()> {
+    let a = tempfile::NamedTempFile::new()?;
+    let b = tempfile::NamedTempFile::new()?;
+    let c = tempfile::NamedTempFile::new()?;
+
+    // the buffers are listed most recently used first: c, b, a
+    test_key_sequences(
+        &mut AppBuilder::new().with_file(a.path(), None).build()?,
+        vec![
+            (
+                Some(&format!(
+                    ":o {}<ret>ihello<esc>:o {}<ret>",
+                    b.path().to_string_lossy(),
+                    c.path().to_string_lossy()
+                )),
+                None,
+            ),
+            (Some("<space>b"), None),
+            (
+                Some("<tab><tab><A-d>"),
+                Some(&|app| {
+                    // the modified buffer is kept open and switched to
+                    assert!(app.editor.is_err());
+                    let doc = helix_view::doc!(app.editor);
+                    assert_eq!(doc.path(), Some(&helix_stdx::path::normalize(b.path())));
+                    assert!(doc.is_modified());
+                    assert_eq!(app.editor.documents().count(), 2);
+                    assert!(app.editor.document_by_path(c.path()).is_none());
+                    assert!(app.editor.document_by_path(a.path()).is_some());
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_debug_toggle_watches() -> anyhow::Result<()> {
+    test_key_sequences(
+        &mut AppBuilder::new().build()?,
+        vec![
+            (
+                Some(":debug-watch foo<ret>:debug-toggle-watches<ret>"),
+                Some(&|app| {
+                    assert!(!app.editor.show_debug_watches);
+                    assert_eq!(
+                        app.editor.get_status().unwrap().0,
+                        "Debug watch panel hidden"
+                    );
+                    // the expressions are kept while the panel is hidden
+                    assert_eq!(app.editor.debug_watches.len(), 1);
+                }),
+            ),
+            (
+                Some(":debug-toggle-watches<ret>"),
+                Some(&|app| {
+                    assert!(app.editor.show_debug_watches);
+                    assert_eq!(
+                        app.editor.get_status().unwrap().0,
+                        "Debug watch panel shown"
+                    );
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn surround_delete() -> anyhow::Result<()> {
     // Test `surround_delete` when head < anchor
@@ -814,6 +1361,66 @@ async fn surround_delete() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn surround_add_tag_and_function_call() -> anyhow::Result<()> {
+    test(("#[|abc]#", "mstdiv<ret>", "#[|<div>abc</div>]#")).await?;
+    test((
+        "#[|abc]#",
+        "mstp class=\"x\"<ret>",
+        "#[|<p class=\"x\">abc</p>]#",
+    ))
+    .await?;
+    test(("#[|abc]#", "msffoo<ret>", "#[|foo(abc)]#")).await?;
+    test(("#[|a]#\n#(|b)#", "msffoo(<ret>", "#[|foo(a)]#\n#(|foo(b))#")).await?;
+
+    // each surround is its own undo step
+    test(("#[|abc]#", "mstdiv<ret>u", "#[|abc]#")).await?;
+    test(("#[|abc]#", "msffoo<ret>u", "#[|abc]#")).await?;
+    test(("#[|abc]#", "mstdiv<ret>ix<esc>u", "#[|<div>abc</div>]#")).await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn surround_delete_function_call() -> anyhow::Result<()> {
+    test((
+        "fn main() { foo(bar(#[|x]#)); }",
+        ":lang rust<ret>mdf",
+        "fn main() { foo(#[|x]#); }",
+    ))
+    .await?;
+    // selections in the same call remove it once
+    test((
+        "fn main() { foo(#[|a]#, #(|b)#); }",
+        ":lang rust<ret>mdf",
+        "fn main() { #[|a]#, #(|b)#; }",
+    ))
+    .await?;
+    // the callee of the outer call overlaps the inner call, only the inner call is removed
+    test((
+        "fn main() { f(#[|x]#)(#(|y)#); }",
+        ":lang rust<ret>mdf",
+        "fn main() { #[|x]#(#(|y)#); }",
+    ))
+    .await?;
+
+    // the deletion is its own undo step
+    test((
+        "fn main() { foo(bar(#[|x]#)); }",
+        ":lang rust<ret>mdfu",
+        "fn main() { foo(bar(#[|x]#)); }",
+    ))
+    .await?;
+    test((
+        "fn main() { foo(bar(#[|x]#)); }",
+        ":lang rust<ret>mdfix<esc>u",
+        "fn main() { foo(#[|x]#); }",
+    ))
+    .await?;
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn surround_replace_ts() -> anyhow::Result<()> {
     const INPUT: &str = r#"\
@@ -949,3 +1556,375 @@ async fn align_selections_with_varying_columns() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn align_selections_on_pattern() -> anyhow::Result<()> {
+    test((
+        indoc! {"\
+            #[a = 1
+            long = 2
+            xy = 3|]#
+        "},
+        "<A-&>=<ret>",
+        indoc! {"\
+            #[a    = 1
+            long = 2
+            xy   = 3|]#
+        "},
+    ))
+    .await?;
+
+    // the alignment is its own undo step
+    test((
+        "#[a = 1\nlong = 2|]#\n",
+        "<A-&>=<ret>u",
+        "#[a = 1\nlong = 2|]#\n",
+    ))
+    .await?;
+    test((
+        "#[a = 1\nlong = 2|]#\n",
+        "<A-&>=<ret>ix<esc>u",
+        "#[a    = 1\nlong = 2|]#\n",
+    ))
+    .await?;
+
+    // the prompt is prefilled with the escaped primary selection, lines without a match are
+    // left as is and only the first match of a line is aligned
+    test((
+        indoc! {"\
+            #(a|)# += 1
+            #(s|)#kip
+            long += 2 #[+=|]# 3
+        "},
+        "<A-&><ret>",
+        indoc! {"\
+            #(a|)#    += 1
+            #(s|)#kip
+            long += 2 #[+=|]# 3
+        "},
+    ))
+    .await?;
+
+    // columns are visual, a tab counts as its width
+    test((
+        "#[\ta = 1\nlonger = 2|]#\n",
+        "<A-&>=<ret>",
+        "#[\ta  = 1\nlonger = 2|]#\n",
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn align_selections_on_errors() -> anyhow::Result<()> {
+    let mut app = AppBuilder::new()
+        .with_input_text("#[a = 1\nb = 2|]#\n")
+        .build()?;
+
+    test_key_sequence(
+        &mut app,
+        Some("<A-&>:<ret>"),
+        Some(&|app| {
+            assert_eq!(
+                app.editor.get_status().unwrap().0,
+                "No matches on the selected lines"
+            );
+            assert_eq!(
+                helix_view::doc!(app.editor).text().to_string(),
+                "a = 1\nb = 2\n"
+            );
+        }),
+        false,
+    )
+    .await?;
+
+    test_key_sequence(
+        &mut app,
+        Some("<A-&>(<ret>"),
+        Some(&|app| {
+            let (status, severity) = app.editor.get_status().unwrap();
+            assert!(status.starts_with("Invalid regex"));
+            assert_eq!(*severity, helix_view::editor::Severity::Error);
+        }),
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_file_explorer_file_operations() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let file = dir.path().join("a.txt");
+    std::fs::write(&file, "a")?;
+    let mut app = helpers::AppBuilder::new().with_file(&file, None).build()?;
+    let root = dir.path().to_path_buf();
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            (
+                // create a file in a new directory
+                Some("<space>.<A-c>sub/new.txt<ret>"),
+                Some(&|app| {
+                    helpers::assert_status_not_error(&app.editor);
+                    assert!(root.join("sub").join("new.txt").is_file());
+                }),
+            ),
+            (
+                // the explorer reveals the current buffer which is moved
+                Some("<esc><space>.<A-r><C-u>b.txt<ret>"),
+                Some(&|app| {
+                    helpers::assert_status_not_error(&app.editor);
+                    assert!(!root.join("a.txt").exists());
+                    assert!(root.join("b.txt").is_file());
+                    assert_eq!(
+                        helix_view::doc!(app.editor).path(),
+                        Some(helix_stdx::path::normalize(root.join("b.txt"))).as_deref()
+                    );
+                }),
+            ),
+            (
+                Some("<esc><space>.<A-x>y<ret>"),
+                Some(&|app| {
+                    helpers::assert_status_not_error(&app.editor);
+                    assert!(!root.join("b.txt").exists());
+                    assert!(root.join("sub").is_dir());
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_file_explorer_refuses_to_delete_modified_buffers() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let file = dir.path().join("a.txt");
+    std::fs::write(&file, "a")?;
+    let mut app = helpers::AppBuilder::new().with_file(&file, None).build()?;
+
+    test_key_sequence(
+        &mut app,
+        Some("ix<esc><space>.<A-x>"),
+        Some(&|app| {
+            let (status, severity) = app.editor.get_status().unwrap();
+            assert_eq!(*severity, helix_view::editor::Severity::Error);
+            assert!(status.ends_with("has unsaved changes"));
+            assert!(file.is_file());
+            assert!(helix_view::doc!(app.editor).is_modified());
+        }),
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_explorer_panel_file_operations() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let root = helix_stdx::path::canonicalize(dir.path());
+    let file = root.join("a.txt");
+    std::fs::write(&file, "a")?;
+    let mut app = helpers::AppBuilder::new().with_file(&file, None).build()?;
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            (
+                // the panel reveals the current buffer and refuses to delete it while modified
+                Some("ix<esc><space>Ed"),
+                Some(&|app| {
+                    let (status, severity) = app.editor.get_status().unwrap();
+                    assert_eq!(*severity, helix_view::editor::Severity::Error);
+                    assert!(status.ends_with("has unsaved changes"));
+                    assert!(file.is_file());
+                }),
+            ),
+            (
+                Some(":w<ret>dy<ret>"),
+                Some(&|app| {
+                    helpers::assert_status_not_error(&app.editor);
+                    assert!(!file.exists());
+                    assert!(app.editor.documents().all(|doc| doc.path() != Some(&file)));
+                }),
+            ),
+            (
+                // the created file is revealed and opened in a split
+                Some("a<C-u>sub/new.txt<ret><C-v>"),
+                Some(&|app| {
+                    helpers::assert_status_not_error(&app.editor);
+                    let new = root.join("sub").join("new.txt");
+                    assert!(new.is_file());
+                    assert_eq!(helix_view::doc!(app.editor).path(), Some(&new));
+                    assert_eq!(app.editor.tree.views().count(), 2);
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cycle_color_presentation_without_language_server() -> anyhow::Result<()> {
+    use helix_term::keymap;
+    use helix_view::document::Mode;
+
+    let mut config = Config::default();
+    config.keys.insert(
+        Mode::Normal,
+        keymap!({"Normal Mode"
+            "C-x" => cycle_color_presentation,
+        }),
+    );
+    let mut app = AppBuilder::new()
+        .with_config(config)
+        .with_input_text("color: #[#|]#ff0000;\n")
+        .build()?;
+
+    test_key_sequence(
+        &mut app,
+        Some(":lang css<ret><C-x>"),
+        Some(&|app| {
+            let (status, severity) = app.editor.get_status().unwrap();
+            assert_eq!(*severity, helix_view::editor::Severity::Error);
+            assert_eq!(
+                status,
+                "No configured language server supports document-colors"
+            );
+            // the document is left as is
+            assert_eq!(
+                helix_view::doc!(app.editor).text().to_string(),
+                "color: #ff0000;\n"
+            );
+        }),
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_open_large_file() -> anyhow::Result<()> {
+    let large = tempfile::Builder::new().suffix(".rs").tempfile()?;
+    std::fs::write(large.path(), "fn main() {\n    let a = 1;\n}\n")?;
+    let small = tempfile::Builder::new().suffix(".rs").tempfile()?;
+    std::fs::write(small.path(), "fn f() {}\n")?;
+
+    let config = Config {
+        editor: helix_view::editor::Config {
+            large_file_threshold: 16,
+            soft_wrap: helix_core::syntax::config::SoftWrap {
+                enable: Some(true),
+                ..Default::default()
+            },
+            ..helpers::test_editor_config()
+        },
+        ..helpers::test_config()
+    };
+    let mut app = AppBuilder::new()
+        .with_config(config)
+        .with_file(large.path(), None)
+        .build()?;
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            (
+                None,
+                Some(&|app| {
+                    let doc = helix_view::doc!(app.editor);
+                    assert!(doc.large_file);
+                    assert_eq!(doc.language_name(), None);
+                    assert!(doc.syntax().is_none());
+                    assert!(!doc.text_format(80, None).soft_wrap);
+                    assert_eq!(
+                        app.editor.get_status().unwrap().0,
+                        "Large file: syntax highlighting and language servers are disabled"
+                    );
+                }),
+            ),
+            // large files can still be edited
+            (
+                Some("ggiconst<space><esc>"),
+                Some(&|app| {
+                    assert_eq!(
+                        helix_view::doc!(app.editor).text().to_string(),
+                        "const fn main() {\n    let a = 1;\n}\n"
+                    );
+                }),
+            ),
+            (
+                Some(&format!(":o {}<ret>", small.path().to_string_lossy())),
+                Some(&|app| {
+                    let doc = helix_view::doc!(app.editor);
+                    assert!(!doc.large_file);
+                    assert_eq!(doc.language_name(), Some("rust"));
+                    assert!(doc.text_format(80, None).soft_wrap);
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_reload_large_file() -> anyhow::Result<()> {
+    let file = tempfile::Builder::new().suffix(".rs").tempfile()?;
+    std::fs::write(file.path(), "fn f() {}\n")?;
+
+    let config = Config {
+        editor: helix_view::editor::Config {
+            large_file_threshold: 16,
+            ..helpers::test_editor_config()
+        },
+        ..helpers::test_config()
+    };
+    let mut app = AppBuilder::new()
+        .with_config(config)
+        .with_file(file.path(), None)
+        .build()?;
+
+    // the size is checked again when the file is reloaded
+    std::fs::write(file.path(), "fn main() {\n    let a = 1;\n}\n")?;
+    test_key_sequences(
+        &mut app,
+        vec![
+            (
+                Some(":reload<ret>"),
+                Some(&|app| {
+                    let doc = helix_view::doc!(app.editor);
+                    assert!(doc.large_file);
+                    assert_eq!(doc.language_name(), None);
+                    assert!(doc.syntax().is_none());
+                    std::fs::write(file.path(), "fn f() {}\n").unwrap();
+                }),
+            ),
+            (
+                Some(":reload<ret>"),
+                Some(&|app| {
+                    let doc = helix_view::doc!(app.editor);
+                    assert!(!doc.large_file);
+                    assert_eq!(doc.language_name(), Some("rust"));
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
@@ -639,3 +639,124 @@ async fn test_indent_with_spaces() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn insert_unicode_and_digraphs() -> anyhow::Result<()> {
+    test((
+        indoc! {"\
+            #[|a]#
+            #(|b)#
+            "},
+        "i<C-v>u03bb<C-v>x41<C-v>u3b1<ret><A-k>l*<A-k>>-<esc>",
+        indoc! {"\
+            λAαλ→#[|a]#
+            λAαλ→#(|b)#
+            "},
+    ))
+    .await?;
+
+    test((
+        "#[|a]#",
+        ":insert-char U+03bb -><ret>:insert-char greek capital letter omega<ret>",
+        "λ→Ω#[|a]#",
+    ))
+    .await?;
+    // every insert in normal mode is its own undo step
+    test((
+        "#[|a]#",
+        ":insert-char U+03bb<ret>:insert-char U+03b1<ret>u",
+        "λ#[|a]#",
+    ))
+    .await?;
+    test(("#[|a]#", ":insert-char U+03bb<ret>ix<esc>u", "λ#[|a]#")).await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn expand_abbreviations() -> anyhow::Result<()> {
+    let config = Config {
+        editor: helix_view::editor::Config {
+            abbreviations: toml::from_str(indoc! {r#"
+                teh = "the"
+                fn = "function"
+
+                [rust]
+                fn = "fn"
+            "#})?,
+            ..helpers::test_editor_config()
+        },
+        ..helpers::test_config()
+    };
+    let test = |case: (&str, &str, &str)| {
+        test_with_config(AppBuilder::new().with_config(config.clone()), case)
+    };
+
+    // expanded when a word boundary is typed, also at a newline and at every cursor
+    test(("#[\n|]#", "iteh<space>cat<esc>", "the cat#[|\n]#")).await?;
+    test(("#[\n|]#", "iteh<ret><esc>", "the\n#[|\n]#")).await?;
+    test(("teh#[\n|]#teh#(\n|)#", "i,<esc>", "the,#[|\n]#the,#(|\n)#")).await?;
+    // not expanded inside of a word or while typing a word
+    test(("#[\n|]#", "itehx<space><esc>", "tehx #[|\n]#")).await?;
+    test(("#[\n|]#", "ixteh<space><esc>", "xteh #[|\n]#")).await?;
+    // a backslash keeps the abbreviation
+    test(("#[\n|]#", "i\\teh<space><esc>", "teh #[|\n]#")).await?;
+    // abbreviations of the language take precedence
+    test(("#[\n|]#", "ifn<space><esc>", "function #[|\n]#")).await?;
+    test(("#[\n|]#", ":lang rust<ret>ifn<space><esc>", "fn #[|\n]#")).await?;
+    test(("#[\n|]#", ":lang rust<ret>iteh<space><esc>", "the #[|\n]#")).await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn chord_timeout_inserts_pending_keys() -> anyhow::Result<()> {
+    use helix_term::keymap;
+    use helix_view::document::Mode;
+
+    let mut config = Config {
+        editor: helix_view::editor::Config {
+            chord_timeout: std::time::Duration::from_millis(10),
+            ..helpers::test_editor_config()
+        },
+        ..Default::default()
+    };
+    config.keys.insert(
+        Mode::Insert,
+        keymap!({"Insert Mode"
+            "j" => { "Chord"
+                "k" => normal_mode,
+            },
+        }),
+    );
+    let mut app = helpers::AppBuilder::new()
+        .with_config(config)
+        .with_input_text("#[a|]#bc\n")
+        .build()?;
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            // without the second key of the sequence the first one is inserted after the timeout
+            (
+                Some("ij"),
+                Some(&|app| {
+                    assert_eq!(app.editor.mode(), Mode::Insert);
+                    assert_eq!(helix_view::doc!(app.editor).text().to_string(), "jabc\n");
+                }),
+            ),
+            // the whole sequence runs its command and inserts nothing
+            (
+                Some("jk"),
+                Some(&|app| {
+                    assert_eq!(app.editor.mode(), Mode::Normal);
+                    assert_eq!(helix_view::doc!(app.editor).text().to_string(), "jabc\n");
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
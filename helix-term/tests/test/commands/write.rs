@@ -307,6 +307,116 @@ async fn test_write_concurrent() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_reload_keeps_readonly_choice() -> anyhow::Result<()> {
+    let file = helpers::temp_file_with_contents("hello\n")?;
+    let mut app = helpers::AppBuilder::new()
+        .with_file(file.path(), None)
+        .build()?;
+
+    test_key_sequence(
+        &mut app,
+        Some(":set-readonly<ret>"),
+        Some(&|app| assert!(doc!(app.editor).readonly())),
+        false,
+    )
+    .await?;
+
+    std::fs::write(file.path(), "changed\n")?;
+    test_key_sequence(
+        &mut app,
+        Some(":reload<ret>"),
+        Some(&|app| {
+            helpers::assert_status_not_error(&app.editor);
+            let doc = doc!(app.editor);
+            assert_eq!(doc.text().to_string(), "changed\n");
+            assert!(doc.readonly());
+        }),
+        false,
+    )
+    .await?;
+
+    // files that can't be written stay editable if the user asked for it
+    let file = helpers::new_readonly_tempfile()?;
+    let mut app = helpers::AppBuilder::new()
+        .with_file(file.path(), None)
+        .build()?;
+    test_key_sequence(
+        &mut app,
+        Some(":set-readonly false<ret>:reload<ret>"),
+        Some(&|app| assert!(!doc!(app.editor).readonly())),
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_auto_reload_files_changed_on_disk() -> anyhow::Result<()> {
+    let file = helpers::temp_file_with_contents("hello\n")?;
+    // the files are checked for changes every second, the test only continues once the editor
+    // was idle for longer than that
+    let config = Config {
+        editor: helix_view::editor::Config {
+            idle_timeout: std::time::Duration::from_millis(1500),
+            ..helpers::test_editor_config()
+        },
+        ..helpers::test_config()
+    };
+    let mut app = helpers::AppBuilder::new()
+        .with_config(config)
+        .with_file(file.path(), None)
+        .build()?;
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            (
+                None,
+                Some(&|_| std::fs::write(file.path(), "changed\n").unwrap()),
+            ),
+            // unmodified buffers are reloaded right away
+            (
+                None,
+                Some(&|app| {
+                    let doc = doc!(app.editor);
+                    assert_eq!(doc.text().to_string(), "changed\n");
+                    assert!(!doc.is_modified());
+                }),
+            ),
+            (
+                Some("ione <esc>"),
+                Some(&|app| {
+                    let doc = doc!(app.editor);
+                    assert_eq!(doc.text().to_string(), "one changed\n");
+                    assert!(doc.is_modified());
+                    std::fs::write(file.path(), "changed again\n").unwrap();
+                }),
+            ),
+            // buffers with unsaved changes are only reloaded after confirming the prompt
+            (
+                None,
+                Some(&|app| {
+                    assert_eq!(doc!(app.editor).text().to_string(), "one changed\n");
+                }),
+            ),
+            (
+                Some("y<ret>"),
+                Some(&|app| {
+                    let doc = doc!(app.editor);
+                    assert_eq!(doc.text().to_string(), "changed again\n");
+                    assert!(!doc.is_modified());
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_write_fail_mod_flag() -> anyhow::Result<()> {
     let file = helpers::new_readonly_tempfile()?;
@@ -326,6 +436,15 @@ async fn test_write_fail_mod_flag() -> anyhow::Result<()> {
             ),
             (
                 Some("ihello<esc>"),
+                Some(&|app| {
+                    // files that can't be written are read-only
+                    let doc = doc!(app.editor);
+                    assert!(doc.readonly());
+                    assert!(!doc.is_modified());
+                }),
+            ),
+            (
+                Some(":set-readonly false<ret>ihello<esc>"),
                 Some(&|app| {
                     let doc = doc!(app.editor);
                     assert!(doc.is_modified());
@@ -451,6 +570,164 @@ async fn test_write_auto_format_fails_still_writes() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_format_with_formatter_pipeline() -> anyhow::Result<()> {
+    let file = tempfile::Builder::new().suffix(".rs").tempfile()?;
+
+    let lang_conf = indoc! {r#"
+            [[language]]
+            name = "rust"
+            formatter = [
+              { command = "sed", args = [ "s/a/b/" ] },
+              { command = "sed", args = [ "s/b/c/" ] },
+            ]
+        "#};
+
+    let mut app = helpers::AppBuilder::new()
+        .with_file(file.path(), None)
+        .with_input_text("#[a|]#\nb\n")
+        .with_lang_loader(helpers::test_syntax_loader(Some(lang_conf.into())))
+        .build()?;
+
+    // each formatter receives the output of the previous one
+    test_key_sequence(
+        &mut app,
+        Some(":format<ret>"),
+        Some(&|app| {
+            helpers::assert_status_not_error(&app.editor);
+            assert_eq!("c\nc\n", doc!(app.editor).text().to_string());
+        }),
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[cfg(unix)]
+async fn test_format_with_temp_file_formatter() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("file.rs");
+    std::fs::write(&path, "let x = 0;\n")?;
+
+    let lang_conf = indoc! {r#"
+            [[language]]
+            name = "rust"
+            formatter = { command = "sed", args = [ "-i", "s/x/y/" ], mode = "temp-file" }
+        "#};
+
+    let mut app = helpers::AppBuilder::new()
+        .with_file(&path, None)
+        .with_lang_loader(helpers::test_syntax_loader(Some(lang_conf.into())))
+        .build()?;
+
+    test_key_sequence(
+        &mut app,
+        Some(":format<ret>"),
+        Some(&|app| {
+            helpers::assert_status_not_error(&app.editor);
+            assert_eq!("let y = 0;\n", doc!(app.editor).text().to_string());
+        }),
+        false,
+    )
+    .await?;
+
+    // the temporary file is removed again and the file itself is left untouched
+    let names: Vec<_> = std::fs::read_dir(dir.path())?
+        .map(|entry| entry.map(|entry| entry.file_name()))
+        .collect::<Result<_, _>>()?;
+    assert_eq!(names, ["file.rs"]);
+    assert_eq!(std::fs::read_to_string(&path)?, "let x = 0;\n");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_format_selections_with_external_formatter() -> anyhow::Result<()> {
+    let file = tempfile::Builder::new().suffix(".rs").tempfile()?;
+
+    // without range arguments only the selected text is passed to the formatter
+    let lang_conf = indoc! {r#"
+            [[language]]
+            name = "rust"
+            formatter = { command = "sed", args = [ "s/a/b/" ] }
+        "#};
+
+    let mut app = helpers::AppBuilder::new()
+        .with_file(file.path(), None)
+        .with_input_text("a\n#[a\n|]#a\n")
+        .with_lang_loader(helpers::test_syntax_loader(Some(lang_conf.into())))
+        .build()?;
+
+    test_key_sequence(
+        &mut app,
+        Some("="),
+        Some(&|app| {
+            helpers::assert_status_not_error(&app.editor);
+            assert_eq!("a\nb\na\n", doc!(app.editor).text().to_string());
+        }),
+        false,
+    )
+    .await?;
+
+    // with range arguments the whole document is passed along with the selected lines
+    let lang_conf = indoc! {r#"
+            [[language]]
+            name = "rust"
+            formatter = { command = "sed", args = [ "-e" ], range-args = [ "%{selection_line_start}s/a/b/" ] }
+        "#};
+
+    let mut app = helpers::AppBuilder::new()
+        .with_file(file.path(), None)
+        .with_input_text("a\na\n#[a\n|]#")
+        .with_lang_loader(helpers::test_syntax_loader(Some(lang_conf.into())))
+        .build()?;
+
+    test_key_sequence(
+        &mut app,
+        Some("="),
+        Some(&|app| {
+            helpers::assert_status_not_error(&app.editor);
+            assert_eq!("a\na\nb\n", doc!(app.editor).text().to_string());
+        }),
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_write_quit_with_encoding() -> anyhow::Result<()> {
+    // the file is converted even if the buffer wasn't changed
+    let file = helpers::temp_file_with_contents("é\n")?;
+    test_key_sequence(
+        &mut helpers::AppBuilder::new()
+            .with_file(file.path(), None)
+            .build()?,
+        Some(":x --encoding latin1<ret>"),
+        None,
+        true,
+    )
+    .await?;
+    assert_eq!(std::fs::read(file.path())?, b"\xe9\n");
+
+    let file = helpers::temp_file_with_contents("\n")?;
+    test_key_sequence(
+        &mut helpers::AppBuilder::new()
+            .with_file(file.path(), None)
+            .build()?,
+        Some("iあ<esc>:wq -e shift_jis<ret>"),
+        None,
+        true,
+    )
+    .await?;
+    assert_eq!(std::fs::read(file.path())?, b"\x82\xa0\n");
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_write_new_path() -> anyhow::Result<()> {
     let mut file1 = tempfile::NamedTempFile::new().unwrap();
@@ -972,3 +1249,174 @@ async fn test_move_file_when_given_dir_only() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_replace_apply() -> anyhow::Result<()> {
+    let mut file1 = helpers::temp_file_with_contents("one\ntwo\n")?;
+    let mut file2 = helpers::temp_file_with_contents("three\n")?;
+    let results = |line| {
+        format!(
+            "#[{}:2:TWO\n{}:{line}:THREE|]#\n",
+            file1.path().display(),
+            file2.path().display()
+        )
+    };
+    let (invalid, valid) = (results(5), results(1));
+
+    // the second file has no line 5, so neither file is changed or left open
+    let mut app = helpers::AppBuilder::new()
+        .with_input_text(invalid)
+        .build()?;
+    test_key_sequence(
+        &mut app,
+        Some(":replace-apply<ret>"),
+        Some(&|app| {
+            assert_eq!(
+                Some(&Severity::Error),
+                app.editor.get_status().map(|status| status.1)
+            );
+            assert!(app.editor.document_by_path(file1.path()).is_none());
+            assert!(app.editor.document_by_path(file2.path()).is_none());
+        }),
+        false,
+    )
+    .await?;
+    helpers::assert_file_has_content(&mut file1, "one\ntwo\n")?;
+    helpers::assert_file_has_content(&mut file2, "three\n")?;
+
+    let mut app = helpers::AppBuilder::new().with_input_text(valid).build()?;
+    test_key_sequence(
+        &mut app,
+        Some(":replace-apply<ret>"),
+        Some(&|app| helpers::assert_status_not_error(&app.editor)),
+        false,
+    )
+    .await?;
+    helpers::assert_file_has_content(&mut file1, "one\nTWO\n")?;
+    helpers::assert_file_has_content(&mut file2, "THREE\n")?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_replace_apply_skips_lines_changed_since_the_search() -> anyhow::Result<()> {
+    let mut file = helpers::temp_file_with_contents("one\ntwo\n")?;
+    let results = format!(
+        "#[{path}:1:ONE\n{path}:2:TWO|]#\n",
+        path = file.path().display()
+    );
+    let mut app = helpers::AppBuilder::new()
+        .with_input_text(results)
+        .build()?;
+
+    // the search listed both lines, then the second one changed on disk
+    let path = helix_stdx::path::canonicalize(file.path());
+    let originals = [(path.clone(), 0), (path, 1)]
+        .into_iter()
+        .zip(["one".to_string(), "two".to_string()])
+        .collect();
+    app.editor
+        .search_results
+        .insert(doc!(app.editor).id(), originals);
+    std::fs::write(file.path(), "one\n2\n")?;
+
+    test_key_sequence(
+        &mut app,
+        Some(":replace-apply<ret>"),
+        Some(&|app| {
+            let (status, _) = app.editor.get_status().unwrap();
+            assert_eq!(
+                status,
+                "Changed 1 line in 1 file, skipped 1 line that changed since the search"
+            );
+        }),
+        false,
+    )
+    .await?;
+    helpers::assert_file_has_content(&mut file, "ONE\n2\n")?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_move_and_delete_directory_with_open_file() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let source_dir = dir.path().join("source");
+    let target_dir = dir.path().join("target");
+    std::fs::create_dir(&source_dir)?;
+    std::fs::write(source_dir.join("file.ext"), "hello\n")?;
+
+    let mut app = helpers::AppBuilder::new()
+        .with_file(source_dir.join("file.ext"), None)
+        .build()?;
+
+    // the buffer follows its file into the renamed directory
+    app.editor.move_path(&source_dir, &target_dir)?;
+    let target_file = path::normalize(target_dir.join("file.ext"));
+    assert_eq!(Some(&target_file), doc!(app.editor).path());
+    assert!(target_file.is_file());
+
+    // the buffer of a deleted file is closed, saving it would recreate the file
+    app.editor.delete_path(&target_dir, true)?;
+    assert!(!target_dir.exists());
+    assert!(app.editor.document_by_path(&target_file).is_none());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_delete_and_rename_refuse_modified_buffers() -> anyhow::Result<()> {
+    use helix_lsp::{lsp, OffsetEncoding};
+
+    let dir = tempfile::tempdir()?;
+    let source = dir.path().join("source.ext");
+    let target = dir.path().join("target.ext");
+    std::fs::write(&source, "source\n")?;
+    std::fs::write(&target, "target\n")?;
+
+    let mut app = helpers::AppBuilder::new()
+        .with_file(&target, None)
+        .build()?;
+    test_key_sequence(&mut app, Some("ihello <esc>"), None, false).await?;
+    let target = path::normalize(&target);
+
+    // deleting the file of a modified buffer fails and keeps both
+    let err = app.editor.delete_path(&target, false).unwrap_err();
+    assert!(err.to_string().contains("unsaved changes"));
+    assert!(target.is_file());
+    assert!(app.editor.document_by_path(&target).is_some());
+
+    // so does renaming another file over it
+    let rename = lsp::WorkspaceEdit {
+        document_changes: Some(lsp::DocumentChanges::Operations(vec![
+            lsp::DocumentChangeOperation::Op(lsp::ResourceOp::Rename(lsp::RenameFile {
+                old_uri: lsp::Url::from_file_path(&source).unwrap(),
+                new_uri: lsp::Url::from_file_path(&target).unwrap(),
+                options: Some(lsp::RenameFileOptions {
+                    overwrite: Some(true),
+                    ignore_if_exists: None,
+                }),
+                annotation_id: None,
+            })),
+        ])),
+        ..Default::default()
+    };
+    assert!(app
+        .editor
+        .apply_workspace_edit(OffsetEncoding::Utf8, &rename)
+        .is_err());
+    assert!(source.is_file());
+    assert_eq!(std::fs::read_to_string(&target)?, "target\n");
+    let doc = app.editor.document_by_path(&target).unwrap();
+    assert_eq!(doc.text().to_string(), "hello target\n");
+
+    // once the changes are saved the rename replaces the buffer
+    test_key_sequence(&mut app, Some(":w<ret>"), None, false).await?;
+    app.editor
+        .apply_workspace_edit(OffsetEncoding::Utf8, &rename)
+        .unwrap();
+    assert!(!source.exists());
+    assert_eq!(std::fs::read_to_string(&target)?, "source\n");
+
+    Ok(())
+}
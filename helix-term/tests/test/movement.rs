@@ -842,3 +842,121 @@ async fn tree_sitter_motions_work_across_injections() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn vertical_motions_skip_folded_lines() -> anyhow::Result<()> {
+    // without a syntax tree `fold_toggle` folds the indented block below the cursor
+    test((
+        indoc! {"\
+            #[f|]#n a() {
+                1
+                2
+            }
+            b
+            "},
+        "zajj;",
+        indoc! {"\
+            fn a() {
+                1
+                2
+            }
+            #[b|]#
+            "},
+    ))
+    .await?;
+
+    test((
+        indoc! {"\
+            #[f|]#n a() {
+                1
+                2
+            }
+            "},
+        "zajk;",
+        indoc! {"\
+            #[f|]#n a() {
+                1
+                2
+            }
+            "},
+    ))
+    .await?;
+
+    // cursors that would be hidden by a fold are moved to the fold header
+    test((
+        indoc! {"\
+            #[a
+            b
+            c|]#
+            d
+            "},
+        "zfj;",
+        indoc! {"\
+            a
+            b
+            c
+            #[d|]#
+            "},
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn search_toggles() -> anyhow::Result<()> {
+    // whole word matching, kept by the search register for `n`
+    test((
+        "#[x|]#foobar foo foo\n",
+        "/foo<A-w><ret>",
+        "xfoobar #[foo|]# foo\n",
+    ))
+    .await?;
+    test((
+        "#[x|]#foobar foo foo\n",
+        "/foo<A-w><ret>n",
+        "xfoobar foo #[foo|]#\n",
+    ))
+    .await?;
+    // `n` skips the matches inside of words too
+    test((
+        "#[x|]# foo foobar foo\n",
+        "/foo<A-w><ret>n",
+        "x foo foobar #[foo|]#\n",
+    ))
+    .await?;
+    let mut app = helpers::AppBuilder::new().build()?;
+    test_key_sequence(
+        &mut app,
+        Some("/foo<A-w><A-c><ret>"),
+        Some(&|app| {
+            let pattern = app.editor.registers.first('/', &app.editor).unwrap();
+            assert_eq!(pattern, r"(?-i)\b(?:foo)\b");
+        }),
+        false,
+    )
+    .await?;
+    // toggling twice switches the flag off again
+    test((
+        "#[x|]#foobar foo foo\n",
+        "/foo<A-w><A-w><ret>",
+        "x#[foo|]#bar foo foo\n",
+    ))
+    .await?;
+
+    // case sensitive matching overrides smart case
+    test(("#[x|]# Foo foo\n", "/foo<ret>", "x #[Foo|]# foo\n")).await?;
+    test(("#[x|]# Foo foo\n", "/foo<A-c><ret>", "x Foo #[foo|]#\n")).await?;
+    test((
+        "#[x|]# Foo foo Foo\n",
+        "/foo<A-c><ret>n",
+        "x Foo #[foo|]# Foo\n",
+    ))
+    .await?;
+
+    // the input is a regex, the toggles wrap it without escaping it
+    test(("#[x|]# abc a.c\n", "/a.c<A-w><ret>", "x #[abc|]# a.c\n")).await?;
+    test(("#[x|]# abc a.c\n", "/a\\.c<A-w><ret>", "x abc #[a.c|]#\n")).await?;
+
+    Ok(())
+}
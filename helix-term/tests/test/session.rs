@@ -0,0 +1,41 @@
+use super::*;
+
+use helix_view::session::Session;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_session_capture_restore_round_trip() -> anyhow::Result<()> {
+    let file1 = helpers::temp_file_with_contents("one\ntwo\n")?;
+    let file2 = helpers::temp_file_with_contents("three\nfour\n")?;
+    let mut app = helpers::AppBuilder::new()
+        .with_file(file1.path(), None)
+        .build()?;
+
+    test_key_sequence(
+        &mut app,
+        Some(&format!(
+            ":vsplit {}<ret>jx\"ay",
+            file2.path().to_string_lossy()
+        )),
+        None,
+        false,
+    )
+    .await?;
+
+    let session = Session::capture(&app.editor);
+    assert_eq!(session.documents.len(), 2);
+    assert_eq!(session.focus, 1);
+    assert_eq!(
+        session.registers.get(&'a'),
+        Some(&vec!["four\n".to_string()])
+    );
+
+    // change the layout, the selection and the register
+    test_key_sequence(&mut app, Some("gg\"ay:only<ret>"), None, false).await?;
+    assert_eq!(app.editor.tree.views().count(), 1);
+
+    session.restore(&mut app.editor)?;
+    assert_eq!(app.editor.tree.views().count(), 2);
+    assert_eq!(Session::capture(&app.editor), session);
+
+    Ok(())
+}
@@ -1,4 +1,9 @@
-use crate::{backend::Backend, buffer::Cell, terminal::Config};
+use crate::{
+    backend::{downsample::ColorDownsampler, Backend},
+    buffer::Cell,
+    image::{self, Image, ImageProtocol},
+    terminal::Config,
+};
 use crossterm::{
     cursor::{Hide, MoveTo, SetCursorStyle, Show},
     event::{
@@ -14,7 +19,10 @@
     terminal::{self, Clear, ClearType},
     Command,
 };
-use helix_view::graphics::{Color, CursorKind, Modifier, Rect, UnderlineStyle};
+use helix_view::{
+    editor::{ImageProtocolConfig, TrueColorConfig},
+    graphics::{Color, CursorKind, Modifier, Rect, UnderlineStyle},
+};
 use once_cell::sync::OnceCell;
 use std::{
     fmt,
@@ -102,6 +110,7 @@ pub struct CrosstermBackend<W: Write> {
     supports_keyboard_enhancement_protocol: OnceCell<bool>,
     mouse_capture_enabled: bool,
     supports_bracketed_paste: bool,
+    downsampler: ColorDownsampler,
 }
 
 impl<W> CrosstermBackend<W>
@@ -120,6 +129,16 @@ pub fn new(buffer: W, config: Config) -> CrosstermBackend<W> {
             supports_keyboard_enhancement_protocol: OnceCell::new(),
             mouse_capture_enabled: false,
             supports_bracketed_paste: true,
+            downsampler: ColorDownsampler::default(),
+        }
+    }
+
+    /// Returns the color to send to the terminal. The Windows terminals support RGB colors, so
+    /// colors are only converted to palette colors if true color is disabled.
+    fn color(&mut self, color: Color) -> CColor {
+        match self.config.true_color {
+            TrueColorConfig::Disabled => self.downsampler.downsample(color).into(),
+            TrueColorConfig::Auto | TrueColorConfig::Enabled => color.into(),
         }
     }
 
@@ -250,10 +269,8 @@ fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
                 modifier = cell.modifier;
             }
             if cell.fg != fg || cell.bg != bg {
-                queue!(
-                    self.buffer,
-                    SetColors(Colors::new(cell.fg.into(), cell.bg.into()))
-                )?;
+                let colors = Colors::new(self.color(cell.fg), self.color(cell.bg));
+                queue!(self.buffer, SetColors(colors))?;
                 fg = cell.fg;
                 bg = cell.bg;
             }
@@ -261,7 +278,7 @@ fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
             let mut new_underline_style = cell.underline_style;
             if self.capabilities.has_extended_underlines {
                 if cell.underline_color != underline_color {
-                    let color = CColor::from(cell.underline_color);
+                    let color = self.color(cell.underline_color);
                     queue!(self.buffer, SetUnderlineColor(color))?;
                     underline_color = cell.underline_color;
                 }
@@ -324,7 +341,7 @@ fn flush(&mut self) -> io::Result<()> {
     }
 
     fn supports_true_color(&self) -> bool {
-        false
+        true
     }
 
     fn get_theme_mode(&self) -> Option<helix_view::theme::Mode> {
@@ -334,6 +351,32 @@ fn get_theme_mode(&self) -> Option<helix_view::theme::Mode> {
     fn set_background_color(&mut self, _color: Option<helix_view::theme::Color>) -> io::Result<()> {
         Ok(())
     }
+
+    fn image_protocol(&self) -> Option<ImageProtocol> {
+        // The terminals on Windows don't advertise image support, so images are only displayed
+        // when a protocol is configured explicitly.
+        match self.config.image_protocol {
+            ImageProtocolConfig::Auto | ImageProtocolConfig::Disabled => None,
+            ImageProtocolConfig::Kitty => Some(ImageProtocol::Kitty),
+            ImageProtocolConfig::Iterm2 => Some(ImageProtocol::Iterm2),
+        }
+    }
+
+    fn draw_images(&mut self, images: &[(Rect, Image)]) -> io::Result<()> {
+        let Some(protocol) = self.image_protocol() else {
+            return Ok(());
+        };
+        write!(self.buffer, "{}", image::clear_images_sequence(protocol))?;
+        for (area, image) in images {
+            queue!(self.buffer, MoveTo(area.x, area.y))?;
+            write!(
+                self.buffer,
+                "{}",
+                image.escape_sequence(protocol, area.width, area.height)
+            )?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
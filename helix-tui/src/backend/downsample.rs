@@ -0,0 +1,110 @@
+//! Conversion of 24-bit colors to the 256 color palette for terminals without true color
+//! support.
+//!
+//! The first 16 colors of the palette are configured by the user, so colors are only mapped to
+//! the 6x6x6 color cube (16-231) and the grayscale ramp (232-255) which are the same in all
+//! terminals.
+
+use std::collections::HashMap;
+
+use helix_view::theme::Color;
+
+/// The values of the red, green and blue components in the 6x6x6 color cube.
+const CUBE_LEVELS: [u8; 6] = [0x00, 0x5f, 0x87, 0xaf, 0xd7, 0xff];
+
+/// Maps RGB colors to palette colors. Themes only use a few distinct colors, so the results
+/// are cached.
+#[derive(Debug, Default)]
+pub(crate) struct ColorDownsampler {
+    cache: HashMap<(u8, u8, u8), u8>,
+}
+
+impl ColorDownsampler {
+    pub fn downsample(&mut self, color: Color) -> Color {
+        match color {
+            Color::Rgb(r, g, b) => Color::Indexed(
+                *self
+                    .cache
+                    .entry((r, g, b))
+                    .or_insert_with(|| nearest_palette_index(r, g, b)),
+            ),
+            color => color,
+        }
+    }
+}
+
+/// Returns the index of the nearest color in the color cube or the grayscale ramp.
+fn nearest_palette_index(r: u8, g: u8, b: u8) -> u8 {
+    fn cube_index(value: u8) -> usize {
+        match value {
+            0..48 => 0,
+            48..115 => 1,
+            _ => (value as usize - 35) / 40,
+        }
+    }
+
+    fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).unsigned_abs().pow(2);
+        d(r1, r2) + d(g1, g2) + d(b1, b2)
+    }
+
+    let (ri, gi, bi) = (cube_index(r), cube_index(g), cube_index(b));
+    let cube = (CUBE_LEVELS[ri], CUBE_LEVELS[gi], CUBE_LEVELS[bi]);
+    let cube_index = 16 + 36 * ri + 6 * gi + bi;
+    if cube == (r, g, b) {
+        return cube_index as u8;
+    }
+
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let gray_index = if average > 238 {
+        23
+    } else {
+        average.saturating_sub(3) / 10
+    };
+    let gray_level = (8 + 10 * gray_index) as u8;
+    let gray = (gray_level, gray_level, gray_level);
+
+    if distance(gray, (r, g, b)) < distance(cube, (r, g, b)) {
+        232 + gray_index as u8
+    } else {
+        cube_index as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn downsample_colors() {
+        let mut downsampler = ColorDownsampler::default();
+        // exact matches in the color cube
+        assert_eq!(
+            downsampler.downsample(Color::Rgb(0, 0, 0)),
+            Color::Indexed(16)
+        );
+        assert_eq!(
+            downsampler.downsample(Color::Rgb(255, 255, 255)),
+            Color::Indexed(231)
+        );
+        assert_eq!(
+            downsampler.downsample(Color::Rgb(0x5f, 0x87, 0xd7)),
+            Color::Indexed(68)
+        );
+        // nearest colors
+        assert_eq!(
+            downsampler.downsample(Color::Rgb(0x28, 0x2c, 0x34)),
+            Color::Indexed(236)
+        );
+        assert_eq!(
+            downsampler.downsample(Color::Rgb(0xe0, 0x6c, 0x75)),
+            Color::Indexed(168)
+        );
+        // other colors are left alone
+        assert_eq!(downsampler.downsample(Color::Red), Color::Red);
+        assert_eq!(
+            downsampler.downsample(Color::Indexed(42)),
+            Color::Indexed(42)
+        );
+    }
+}
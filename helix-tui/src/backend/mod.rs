@@ -2,7 +2,11 @@
 
 use std::io;
 
-use crate::{buffer::Cell, terminal::Config};
+use crate::{
+    buffer::Cell,
+    image::{Image, ImageProtocol},
+    terminal::Config,
+};
 
 use helix_view::{
     graphics::{CursorKind, Rect},
@@ -19,6 +23,8 @@
 #[cfg(all(feature = "termina", windows))]
 pub use self::crossterm::CrosstermBackend;
 
+#[cfg(feature = "termina")]
+mod downsample;
 mod test;
 pub use self::test::TestBackend;
 
@@ -49,4 +55,8 @@ fn draw<'a, I>(&mut self, content: I) -> Result<(), io::Error>
     fn supports_true_color(&self) -> bool;
     fn get_theme_mode(&self) -> Option<helix_view::theme::Mode>;
     fn set_background_color(&mut self, color: Option<Color>) -> io::Result<()>;
+    /// Returns the protocol used to display images, `None` if images can't be displayed.
+    fn image_protocol(&self) -> Option<ImageProtocol>;
+    /// Draws images over the cells, replacing all images drawn before.
+    fn draw_images(&mut self, images: &[(Rect, Image)]) -> io::Result<()>;
 }
@@ -1,7 +1,7 @@
 use std::io::{self, Write as _};
 
 use helix_view::{
-    editor::KittyKeyboardProtocolConfig,
+    editor::{ImageProtocolConfig, KittyKeyboardProtocolConfig, TrueColorConfig},
     graphics::{CursorKind, Rect, UnderlineStyle},
     theme::{self, Color, Modifier},
 };
@@ -15,9 +15,13 @@
     Event, OneBased, PlatformTerminal, Terminal as _, WindowSize,
 };
 
-use crate::{buffer::Cell, terminal::Config};
+use crate::{
+    buffer::Cell,
+    image::{self, Image, ImageProtocol},
+    terminal::Config,
+};
 
-use super::Backend;
+use super::{downsample::ColorDownsampler, Backend};
 
 // These macros are helpers to set/unset modes like bracketed paste or enter/exit the alternate
 // screen.
@@ -46,6 +50,51 @@ fn term_program() -> Option<String> {
 fn vte_version() -> Option<usize> {
     std::env::var("VTE_VERSION").ok()?.parse().ok()
 }
+/// Detects the image protocol supported by the terminal.
+fn image_protocol_from_env() -> Option<ImageProtocol> {
+    // tmux doesn't pass the escape sequences of the protocols through to the terminal
+    if std::env::var_os("TMUX").is_some() {
+        return None;
+    }
+    let term = std::env::var("TERM").unwrap_or_default();
+    if std::env::var_os("KITTY_WINDOW_ID").is_some()
+        || matches!(term.as_str(), "xterm-kitty" | "xterm-ghostty")
+    {
+        return Some(ImageProtocol::Kitty);
+    }
+    match std::env::var("TERM_PROGRAM").ok()?.as_str() {
+        "ghostty" => Some(ImageProtocol::Kitty),
+        "iTerm.app" | "WezTerm" => Some(ImageProtocol::Iterm2),
+        _ => None,
+    }
+}
+
+fn image_protocol(config: &Config) -> Option<ImageProtocol> {
+    match config.image_protocol {
+        ImageProtocolConfig::Auto => image_protocol_from_env(),
+        ImageProtocolConfig::Disabled => None,
+        ImageProtocolConfig::Kitty => Some(ImageProtocol::Kitty),
+        ImageProtocolConfig::Iterm2 => Some(ImageProtocol::Iterm2),
+    }
+}
+
+/// Detects true color support for terminals that don't respond to the DECRQSS query.
+fn true_color_from_env() -> bool {
+    if std::env::var_os("COLORTERM").is_some_and(|v| v == "truecolor" || v == "24bit")
+        || std::env::var_os("WSL_DISTRO_NAME").is_some()
+    {
+        return true;
+    }
+
+    match termini::TermInfo::from_env() {
+        Ok(t) => {
+            t.extended_cap("RGB").is_some()
+                || t.extended_cap("Tc").is_some()
+                || (t.extended_cap("setrgbf").is_some() && t.extended_cap("setrgbb").is_some())
+        }
+        Err(_) => false,
+    }
+}
 
 #[derive(Debug, Default, Clone, Copy)]
 struct Capabilities {
@@ -84,6 +133,9 @@ pub struct TerminaBackend {
     /// The terminal emulator's background color. This is queried when claiming the terminal so
     /// that custom colors set outside of Helix with OSC11 are restored when Helix exits.
     original_background_color: Option<RgbColor>,
+    /// Converts RGB colors to palette colors if true color is not supported.
+    downsampler: ColorDownsampler,
+    image_protocol: Option<ImageProtocol>,
 }
 
 impl TerminaBackend {
@@ -196,6 +248,7 @@ pub fn new(config: Config) -> io::Result<Self> {
         }
 
         capabilities.extended_underlines |= config.force_enable_extended_underlines;
+        capabilities.true_color |= true_color_from_env();
 
         let mut reset_cursor_command = String::new();
         if let Ok(t) = termini::TermInfo::from_env() {
@@ -249,15 +302,32 @@ pub fn new(config: Config) -> io::Result<Self> {
 
         Ok(Self {
             terminal,
+            image_protocol: image_protocol(&config),
             config,
             capabilities,
             reset_cursor_command,
             is_synchronized_output_set: false,
             background_color: None,
             original_background_color,
+            downsampler: ColorDownsampler::default(),
         })
     }
 
+    /// Returns the color to send to the terminal, which is the nearest palette color if the
+    /// terminal doesn't support RGB colors.
+    fn color(&mut self, color: Color) -> Color {
+        let true_color = match self.config.true_color {
+            TrueColorConfig::Auto => self.capabilities.true_color,
+            TrueColorConfig::Enabled => true,
+            TrueColorConfig::Disabled => false,
+        };
+        if true_color {
+            color
+        } else {
+            self.downsampler.downsample(color)
+        }
+    }
+
     pub fn terminal(&self) -> &PlatformTerminal {
         &self.terminal
     }
@@ -448,6 +518,7 @@ fn reconfigure(&mut self, mut config: Config) -> io::Result<()> {
             }
         }
         self.capabilities.extended_underlines |= self.config.force_enable_extended_underlines;
+        self.image_protocol = image_protocol(&self.config);
         Ok(())
     }
 
@@ -498,11 +569,11 @@ fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
 
             let mut attributes = SgrAttributes::default();
             if cell.fg != fg {
-                attributes.foreground = Some(cell.fg.into());
+                attributes.foreground = Some(self.color(cell.fg).into());
                 fg = cell.fg;
             }
             if cell.bg != bg {
-                attributes.background = Some(cell.bg.into());
+                attributes.background = Some(self.color(cell.bg).into());
                 bg = cell.bg;
             }
             if cell.modifier != modifier {
@@ -515,10 +586,11 @@ fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
             let mut new_underline_style = cell.underline_style;
             if self.capabilities.extended_underlines {
                 if cell.underline_color != underline_color {
+                    let color = self.color(cell.underline_color);
                     write!(
                         self.terminal,
                         "{}",
-                        Csi::Sgr(csi::Sgr::UnderlineColor(cell.underline_color.into()))
+                        Csi::Sgr(csi::Sgr::UnderlineColor(color.into()))
                     )?;
                     underline_color = cell.underline_color;
                 }
@@ -638,6 +710,29 @@ fn set_background_color(&mut self, color: Option<Color>) -> io::Result<()> {
             self.reset_background_color()
         }
     }
+
+    fn image_protocol(&self) -> Option<ImageProtocol> {
+        self.image_protocol
+    }
+
+    fn draw_images(&mut self, images: &[(Rect, Image)]) -> io::Result<()> {
+        let Some(protocol) = self.image_protocol else {
+            return Ok(());
+        };
+        write!(self.terminal, "{}", image::clear_images_sequence(protocol))?;
+        for (area, image) in images {
+            write!(
+                self.terminal,
+                "{}{}",
+                Csi::Cursor(csi::Cursor::Position {
+                    col: OneBased::from_zero_based(area.x),
+                    line: OneBased::from_zero_based(area.y),
+                }),
+                image.escape_sequence(protocol, area.width, area.height)
+            )?;
+        }
+        Ok(())
+    }
 }
 
 impl Drop for TerminaBackend {
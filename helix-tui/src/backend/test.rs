@@ -1,6 +1,7 @@
 use crate::{
     backend::Backend,
     buffer::{Buffer, Cell},
+    image::{Image, ImageProtocol},
     terminal::Config,
 };
 use helix_core::unicode::width::UnicodeWidthStr;
@@ -168,4 +169,12 @@ fn get_theme_mode(&self) -> Option<helix_view::theme::Mode> {
     fn set_background_color(&mut self, _color: Option<helix_view::theme::Color>) -> io::Result<()> {
         Ok(())
     }
+
+    fn image_protocol(&self) -> Option<ImageProtocol> {
+        None
+    }
+
+    fn draw_images(&mut self, _images: &[(Rect, Image)]) -> io::Result<()> {
+        Ok(())
+    }
 }
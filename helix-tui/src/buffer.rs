@@ -1,4 +1,5 @@
 //! Contents of a terminal screen. A [Buffer] is made up of [Cell]s.
+use crate::image::Image;
 use crate::text::{Span, Spans};
 use helix_core::unicode::width::{UnicodeWidthChar, UnicodeWidthStr};
 use helix_view::graphics::{Color, Modifier, Rect, Style, UnderlineStyle};
@@ -165,6 +166,8 @@ pub struct Buffer {
     /// The content of the buffer. The length of this Vec should always be equal to area.width *
     /// area.height
     pub content: Vec<Cell>,
+    /// The images drawn over the cells, `None` if the terminal can't display images.
+    images: Option<Vec<(Rect, Image)>>,
 }
 
 impl Buffer {
@@ -179,7 +182,11 @@ pub fn empty(area: Rect) -> Buffer {
     pub fn filled(area: Rect, cell: &Cell) -> Buffer {
         let size = area.area();
         let content = vec![cell.clone(); size];
-        Buffer { area, content }
+        Buffer {
+            area,
+            content,
+            images: None,
+        }
     }
 
     /// Returns a Buffer containing the given lines
@@ -647,6 +654,45 @@ pub fn reset(&mut self) {
         for c in &mut self.content {
             c.reset();
         }
+        if let Some(images) = &mut self.images {
+            images.clear();
+        }
+    }
+
+    /// Sets whether images can be drawn into the buffer, see [Buffer::set_image].
+    pub fn set_images_enabled(&mut self, enabled: bool) {
+        if enabled {
+            self.images.get_or_insert_with(Vec::new);
+        } else {
+            self.images = None;
+        }
+    }
+
+    /// Whether the terminal can display the images drawn into the buffer.
+    pub fn supports_images(&self) -> bool {
+        self.images.is_some()
+    }
+
+    /// Draws an image over `area`, the cells of the area are cleared. Does nothing if images are
+    /// not supported, components should check [Buffer::supports_images] first to show some
+    /// alternate text instead.
+    pub fn set_image(&mut self, area: Rect, image: Image) {
+        let area = area.intersection(self.area);
+        if self.images.is_none() || area.width == 0 || area.height == 0 {
+            return;
+        }
+        for x in area.left()..area.right() {
+            for y in area.top()..area.bottom() {
+                self[(x, y)].set_symbol(" ");
+            }
+        }
+        if let Some(images) = &mut self.images {
+            images.push((area, image));
+        }
+    }
+
+    pub fn images(&self) -> &[(Rect, Image)] {
+        self.images.as_deref().unwrap_or_default()
     }
 
     /// Clear an area in the buffer
@@ -0,0 +1,217 @@
+//! Images displayed with the graphics protocols of terminals.
+//!
+//! Only PNG images are supported: both the kitty graphics protocol and iTerm2's inline images
+//! accept PNG data directly, so images don't need to be decoded. The size of an image is read
+//! from its header to keep its aspect ratio when it is fitted into an area of cells.
+
+use std::{
+    fmt::{self, Write as _},
+    path::Path,
+    sync::Arc,
+};
+
+use helix_view::graphics::Rect;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+/// Images larger than this are not loaded.
+const MAX_IMAGE_SIZE: u64 = 16 * 1024 * 1024;
+/// The size of a cell in pixels. Terminals don't reliably report the size of their cells, so
+/// the common size of 8x16 pixels is assumed.
+const CELL_WIDTH: u32 = 8;
+const CELL_HEIGHT: u32 = 16;
+/// The kitty graphics protocol requires the data to be sent in chunks of at most 4096 bytes.
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+/// The protocol used to display images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageProtocol {
+    /// The [kitty graphics protocol](https://sw.kovidgoyal.net/kitty/graphics-protocol/).
+    Kitty,
+    /// The [inline images protocol](https://iterm2.com/documentation-images.html) of iTerm2.
+    Iterm2,
+}
+
+/// A PNG image. Cloning an image is cheap, clones compare equal to each other.
+#[derive(Clone)]
+pub struct Image {
+    data: Arc<[u8]>,
+    width: u32,
+    height: u32,
+}
+
+impl fmt::Debug for Image {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Image")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for Image {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.data, &other.data)
+    }
+}
+
+impl Eq for Image {}
+
+impl Image {
+    /// Creates an image from the contents of a PNG file. Returns `None` if `data` is not a PNG
+    /// image.
+    pub fn from_png(data: Vec<u8>) -> Option<Self> {
+        // the IHDR chunk, which holds the size of the image, must be the first chunk
+        if !data.starts_with(PNG_SIGNATURE) || data.get(12..16)? != b"IHDR" {
+            return None;
+        }
+        let width = u32::from_be_bytes(data.get(16..20)?.try_into().ok()?);
+        let height = u32::from_be_bytes(data.get(20..24)?.try_into().ok()?);
+        if width == 0 || height == 0 {
+            return None;
+        }
+        Some(Self {
+            data: data.into(),
+            width,
+            height,
+        })
+    }
+
+    /// Reads a PNG image from a file. Returns `None` if the file can't be read or is not a PNG
+    /// image.
+    pub fn load(path: &Path) -> Option<Self> {
+        if std::fs::metadata(path).ok()?.len() > MAX_IMAGE_SIZE {
+            return None;
+        }
+        Self::from_png(std::fs::read(path).ok()?)
+    }
+
+    /// Returns the area at the top left of `area` that the image covers when it is scaled down
+    /// to fit into `area`. Images are never scaled up.
+    pub fn fit(&self, area: Rect) -> Rect {
+        let max_width = (area.width as u32 * CELL_WIDTH).min(self.width);
+        let max_height = (area.height as u32 * CELL_HEIGHT).min(self.height);
+        // scale by the smaller factor to keep the aspect ratio
+        let (width, height) =
+            if max_width as u64 * self.height as u64 <= max_height as u64 * self.width as u64 {
+                let height = (max_width as u64 * self.height as u64 / self.width as u64) as u32;
+                (max_width, height)
+            } else {
+                let width = (max_height as u64 * self.width as u64 / self.height as u64) as u32;
+                (width, max_height)
+            };
+        let cols = width
+            .div_ceil(CELL_WIDTH)
+            .clamp(1, area.width.max(1) as u32);
+        let rows = height
+            .div_ceil(CELL_HEIGHT)
+            .clamp(1, area.height.max(1) as u32);
+        Rect::new(area.x, area.y, cols as u16, rows as u16).intersection(area)
+    }
+
+    /// Returns the escape sequence that displays the image in `cols` columns and `rows` rows
+    /// starting at the cursor.
+    pub fn escape_sequence(&self, protocol: ImageProtocol, cols: u16, rows: u16) -> String {
+        let data = base64(&self.data);
+        match protocol {
+            ImageProtocol::Kitty => {
+                let mut sequence = String::new();
+                let chunks: Vec<_> = data.as_bytes().chunks(KITTY_CHUNK_SIZE).collect();
+                for (i, chunk) in chunks.iter().enumerate() {
+                    let more = u8::from(i + 1 < chunks.len());
+                    // the chunks are base64, which is ASCII
+                    let chunk = std::str::from_utf8(chunk).unwrap();
+                    if i == 0 {
+                        // f=100: PNG data, a=T: transmit and display, q=2: no responses,
+                        // C=1: don't move the cursor
+                        let _ = write!(
+                            sequence,
+                            "\x1b_Gf=100,a=T,q=2,C=1,c={cols},r={rows},m={more};{chunk}\x1b\\"
+                        );
+                    } else {
+                        let _ = write!(sequence, "\x1b_Gm={more};{chunk}\x1b\\");
+                    }
+                }
+                sequence
+            }
+            ImageProtocol::Iterm2 => format!(
+                "\x1b]1337;File=inline=1;size={};width={cols};height={rows};preserveAspectRatio=1:{data}\x07",
+                self.data.len()
+            ),
+        }
+    }
+}
+
+/// Returns the escape sequence that removes all images displayed with `protocol`. Images of
+/// the iTerm2 protocol are part of the cells, they are removed by drawing over them.
+pub fn clear_images_sequence(protocol: ImageProtocol) -> &'static str {
+    match protocol {
+        ImageProtocol::Kitty => "\x1b_Ga=d,d=A,q=2\x1b\\",
+        ImageProtocol::Iterm2 => "",
+    }
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut data = PNG_SIGNATURE.to_vec();
+        data.extend_from_slice(&13u32.to_be_bytes());
+        data.extend_from_slice(b"IHDR");
+        data.extend_from_slice(&width.to_be_bytes());
+        data.extend_from_slice(&height.to_be_bytes());
+        data
+    }
+
+    #[test]
+    fn parse_png_size() {
+        let image = Image::from_png(png(640, 480)).unwrap();
+        assert_eq!((image.width, image.height), (640, 480));
+        assert!(Image::from_png(b"GIF89a".to_vec()).is_none());
+        assert!(Image::from_png(png(0, 480)).is_none());
+    }
+
+    #[test]
+    fn fit_image() {
+        let area = Rect::new(2, 3, 40, 10);
+        // scaled down to the height of the area
+        let image = Image::from_png(png(640, 480)).unwrap();
+        assert_eq!(image.fit(area), Rect::new(2, 3, 27, 10));
+        // scaled down to the width of the area
+        let image = Image::from_png(png(1280, 160)).unwrap();
+        assert_eq!(image.fit(area), Rect::new(2, 3, 40, 3));
+        // small images are not scaled up
+        let image = Image::from_png(png(16, 16)).unwrap();
+        assert_eq!(image.fit(area), Rect::new(2, 3, 2, 1));
+    }
+
+    #[test]
+    fn encode_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+}
@@ -2,7 +2,9 @@
 //! Frontend for [Backend]
 
 use crate::{backend::Backend, buffer::Buffer};
-use helix_view::editor::{Config as EditorConfig, KittyKeyboardProtocolConfig};
+use helix_view::editor::{
+    Config as EditorConfig, ImageProtocolConfig, KittyKeyboardProtocolConfig, TrueColorConfig,
+};
 use helix_view::graphics::{CursorKind, Rect};
 use std::io;
 
@@ -26,6 +28,8 @@ pub struct Config {
     pub enable_mouse_capture: bool,
     pub force_enable_extended_underlines: bool,
     pub kitty_keyboard_protocol: KittyKeyboardProtocolConfig,
+    pub true_color: TrueColorConfig,
+    pub image_protocol: ImageProtocolConfig,
 }
 
 impl From<&EditorConfig> for Config {
@@ -34,6 +38,8 @@ fn from(config: &EditorConfig) -> Self {
             enable_mouse_capture: config.mouse,
             force_enable_extended_underlines: config.undercurl,
             kitty_keyboard_protocol: config.kitty_keyboard_protocol,
+            true_color: config.true_color,
+            image_protocol: config.image_protocol,
         }
     }
 }
@@ -102,7 +108,7 @@ pub fn new(backend: B) -> io::Result<Terminal<B>> {
 
     /// UNSTABLE
     pub fn with_options(backend: B, options: TerminalOptions) -> io::Result<Terminal<B>> {
-        Ok(Terminal {
+        let mut terminal = Terminal {
             backend,
             buffers: [
                 Buffer::empty(options.viewport.area),
@@ -111,7 +117,9 @@ pub fn with_options(backend: B, options: TerminalOptions) -> io::Result<Terminal
             current: 0,
             cursor_kind: CursorKind::Block,
             viewport: options.viewport,
-        })
+        };
+        terminal.update_image_support();
+        Ok(terminal)
     }
 
     pub fn claim(&mut self) -> io::Result<()> {
@@ -119,7 +127,17 @@ pub fn claim(&mut self) -> io::Result<()> {
     }
 
     pub fn reconfigure(&mut self, config: Config) -> io::Result<()> {
-        self.backend.reconfigure(config)
+        self.backend.reconfigure(config)?;
+        self.update_image_support();
+        Ok(())
+    }
+
+    /// Allows images to be drawn into the buffers if the backend can display them.
+    fn update_image_support(&mut self) {
+        let enabled = self.backend.image_protocol().is_some();
+        for buffer in &mut self.buffers {
+            buffer.set_images_enabled(enabled);
+        }
     }
 
     pub fn restore(&mut self) -> io::Result<()> {
@@ -151,8 +169,25 @@ pub fn backend_mut(&mut self) -> &mut B {
     pub fn flush(&mut self) -> io::Result<()> {
         let previous_buffer = &self.buffers[1 - self.current];
         let current_buffer = &self.buffers[self.current];
-        let updates = previous_buffer.diff(current_buffer);
-        self.backend.draw(updates.into_iter())
+        let mut updates = previous_buffer.diff(current_buffer);
+        let images_changed = previous_buffer.images() != current_buffer.images();
+        if images_changed {
+            // The cells below the previous images are blank in both buffers. They are drawn
+            // again to remove images that are part of the cells.
+            for (area, _) in previous_buffer.images() {
+                let area = area.intersection(current_buffer.area);
+                for y in area.top()..area.bottom() {
+                    for x in area.left()..area.right() {
+                        updates.push((x, y, &current_buffer[(x, y)]));
+                    }
+                }
+            }
+        }
+        self.backend.draw(updates.into_iter())?;
+        if images_changed {
+            self.backend.draw_images(current_buffer.images())?;
+        }
+        Ok(())
     }
 
     /// Updates the Terminal so that internal buffers match the requested size. Requested size will
@@ -237,6 +272,7 @@ pub fn set_cursor(&mut self, x: u16, y: u16) -> io::Result<()> {
     /// Clear the terminal and force a full redraw on the next draw call.
     pub fn clear(&mut self) -> io::Result<()> {
         self.backend.clear()?;
+        self.backend.draw_images(&[])?;
         // Reset the back buffer to make sure the next update will redraw everything.
         self.buffers[1 - self.current].reset();
         Ok(())
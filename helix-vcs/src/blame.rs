@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::diff::Diff;
+
+/// The commit that last changed a range of lines of a file.
+#[derive(Debug, PartialEq, Eq)]
+pub struct BlameCommit {
+    /// The abbreviated commit id.
+    pub id: String,
+    pub author: String,
+    /// The author time in seconds since the unix epoch.
+    pub time: i64,
+    /// The first line of the commit message.
+    pub summary: String,
+}
+
+/// The blame of a line of a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineBlame<'a> {
+    Committed(&'a BlameCommit),
+    /// The line was added or changed since the blamed revision.
+    Uncommitted,
+}
+
+#[derive(Debug)]
+pub(crate) struct BlameHunk {
+    pub start: u32,
+    pub len: u32,
+    pub commit: Arc<BlameCommit>,
+}
+
+/// The blame of a file at a revision, mapping each line of the file to the commit that last
+/// changed it.
+#[derive(Debug)]
+pub struct FileBlame {
+    revision: String,
+    hunks: Vec<BlameHunk>,
+}
+
+impl FileBlame {
+    pub(crate) fn new(revision: String, mut hunks: Vec<BlameHunk>) -> Self {
+        hunks.sort_unstable_by_key(|hunk| hunk.start);
+        FileBlame { revision, hunks }
+    }
+
+    /// The revision the file was blamed at.
+    pub fn revision(&self) -> &str {
+        &self.revision
+    }
+
+    /// Returns the commit that last changed the given (zero based) line of the file at
+    /// [`FileBlame::revision`].
+    pub fn commit_at(&self, line: u32) -> Option<&BlameCommit> {
+        let pos = self.hunks.partition_point(|hunk| hunk.start <= line);
+        let hunk = self.hunks.get(pos.checked_sub(1)?)?;
+        (line < hunk.start + hunk.len).then_some(&*hunk.commit)
+    }
+
+    /// Returns the blame of a line of a document, where `diff` compares the document against
+    /// the file at [`FileBlame::revision`].
+    pub fn line_blame(&self, diff: &Diff, line: u32) -> Option<LineBlame<'_>> {
+        match diff.base_line(line) {
+            Some(base_line) => self.commit_at(base_line).map(LineBlame::Committed),
+            None => Some(LineBlame::Uncommitted),
+        }
+    }
+}
+
+/// Blames of files, kept until the revision they were computed at is no longer `HEAD`.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct BlameCache(Arc<Mutex<HashMap<PathBuf, Arc<FileBlame>>>>);
+
+impl BlameCache {
+    pub fn get(&self, file: &Path, revision: &str) -> Option<Arc<FileBlame>> {
+        self.0
+            .lock()
+            .get(file)
+            .filter(|blame| blame.revision == revision)
+            .cloned()
+    }
+
+    pub fn insert(&self, file: PathBuf, blame: Arc<FileBlame>) {
+        self.0.lock().insert(file, blame);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn commit(id: &str) -> Arc<BlameCommit> {
+        Arc::new(BlameCommit {
+            id: id.to_string(),
+            author: "author".to_string(),
+            time: 0,
+            summary: "summary".to_string(),
+        })
+    }
+
+    #[test]
+    fn commit_at_line() {
+        let blame = FileBlame::new(
+            "head".to_string(),
+            vec![
+                BlameHunk {
+                    start: 3,
+                    len: 2,
+                    commit: commit("b"),
+                },
+                BlameHunk {
+                    start: 0,
+                    len: 3,
+                    commit: commit("a"),
+                },
+            ],
+        );
+        let id = |line| blame.commit_at(line).map(|commit| commit.id.as_str());
+        assert_eq!(id(0), Some("a"));
+        assert_eq!(id(2), Some("a"));
+        assert_eq!(id(3), Some("b"));
+        assert_eq!(id(4), Some("b"));
+        assert_eq!(id(5), None);
+    }
+}
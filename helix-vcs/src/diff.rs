@@ -12,8 +12,10 @@
 use crate::diff::worker::DiffWorker;
 
 pub use imara_diff::Hunk;
+pub use word::word_diff;
 
 mod line_cache;
+mod word;
 mod worker;
 
 /// A rendering lock passed to the differ the prevents redraws from occurring
@@ -274,6 +276,127 @@ pub fn hunk_at(&self, line: u32, include_removal: bool) -> Option<u32> {
             }
         }
     }
+
+    /// Maps a line of the document to the corresponding line of the diff base. Returns `None`
+    /// if the line was added or changed.
+    pub fn base_line(&self, line: u32) -> Option<u32> {
+        let hunk_range = if self.inverted {
+            |hunk: &Hunk| hunk.before.clone()
+        } else {
+            |hunk: &Hunk| hunk.after.clone()
+        };
+
+        let pos = self
+            .diff
+            .hunks
+            .partition_point(|hunk| hunk_range(hunk).start <= line);
+        if pos == 0 {
+            return Some(line);
+        }
+        let hunk = self.nth_hunk(pos as u32 - 1);
+        if hunk.after.end > line {
+            None
+        } else {
+            Some(hunk.before.end + line - hunk.after.end)
+        }
+    }
+}
+
+/// Computes the hunks that turn `before` into `after`. Unlike [DiffHandle] the diff is computed
+/// synchronously on the calling thread.
+#[cfg(feature = "git")]
+pub(crate) fn compare_lines(before: &Rope, after: &Rope) -> Vec<Hunk> {
+    use imara_diff::{IndentHeuristic, IndentLevel, InternedInput, Interner};
+
+    let mut input = InternedInput {
+        before: Vec::with_capacity(before.len_lines()),
+        after: Vec::with_capacity(after.len_lines()),
+        interner: Interner::new(before.len_lines() + after.len_lines()),
+    };
+    input.update_before(before.lines());
+    input.update_after(after.lines());
+    let mut diff = imara_diff::Diff::default();
+    diff.compute_with(
+        ALGORITHM,
+        &input.before,
+        &input.after,
+        input.interner.num_tokens(),
+    );
+    diff.postprocess_with(
+        &input.before,
+        &input.after,
+        IndentHeuristic::new(|token| IndentLevel::for_ascii_line(input.interner[token].bytes(), 4)),
+    );
+    diff.hunks().collect()
+}
+
+/// Returns `base` with the given hunks of the diff from `base` to `doc` applied, leaving all
+/// other lines of `base` untouched. The hunks must be sorted in ascending order.
+#[cfg(feature = "git")]
+pub(crate) fn apply_hunks<'a>(
+    base: &Rope,
+    doc: &Rope,
+    hunks: impl IntoIterator<Item = &'a Hunk>,
+) -> Rope {
+    let base_lines = |start: u32, end: u32| {
+        base.slice(base.line_to_char(start as usize)..base.line_to_char(end as usize))
+    };
+    let mut res = String::with_capacity(base.len_bytes());
+    let mut line = 0;
+    for hunk in hunks {
+        res.extend(base_lines(line, hunk.before.start).chunks());
+        let after =
+            doc.line_to_char(hunk.after.start as usize)..doc.line_to_char(hunk.after.end as usize);
+        res.extend(doc.slice(after).chunks());
+        line = hunk.before.end;
+    }
+    res.extend(base_lines(line, base.len_lines() as u32).chunks());
+    Rope::from(res)
+}
+
+/// Maps (inclusive) line ranges of the document to line ranges of the base, where `hunks` are the
+/// changes from the base to the document. Changed lines map to the lines they replaced.
+#[cfg(feature = "git")]
+pub(crate) fn base_line_ranges(
+    hunks: &[Hunk],
+    line_ranges: impl Iterator<Item = (usize, usize)>,
+) -> Vec<(usize, usize)> {
+    let to_base = |line: usize, is_end: bool| {
+        let line = line as u32;
+        let pos = hunks.partition_point(|hunk| hunk.after.start <= line);
+        let Some(hunk) = pos.checked_sub(1).map(|pos| &hunks[pos]) else {
+            return line as usize;
+        };
+        let base_line = if hunk.after.end <= line {
+            hunk.before.end + line - hunk.after.end
+        } else if is_end {
+            hunk.before.end.saturating_sub(1).max(hunk.before.start)
+        } else {
+            hunk.before.start
+        };
+        base_line as usize
+    };
+    line_ranges
+        .map(|(start, end)| (to_base(start, false), to_base(end, true)))
+        .collect()
+}
+
+/// Iterates over all hunks that intersect with the given (inclusive) line ranges of the
+/// document, see [Diff::hunks_intersecting_line_ranges].
+#[cfg(feature = "git")]
+pub(crate) fn hunks_intersecting_line_ranges<I>(
+    hunks: &[Hunk],
+    line_ranges: I,
+) -> HunksInLineRangesIter<'_, I>
+where
+    I: Iterator<Item = (usize, usize)>,
+{
+    HunksInLineRangesIter {
+        hunks,
+        line_ranges: line_ranges.peekable(),
+        inverted: false,
+        cursor: 0,
+    }
 }
 
 pub struct HunksInLineRangesIter<'a, I: Iterator<Item = (usize, usize)>> {
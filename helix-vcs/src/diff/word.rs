@@ -0,0 +1,128 @@
+//! Word-level comparison of the lines of a modified hunk, used to highlight the words that
+//! changed instead of the whole lines.
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+use helix_core::chars::{char_is_whitespace, char_is_word};
+use helix_core::RopeSlice;
+use imara_diff::{Algorithm, InternedInput, Interner};
+
+/// Hunks with more lines than this on either side aren't compared word by word.
+const MAX_WORD_DIFF_LINES: usize = 64;
+
+/// Compares the text of a modified hunk word by word. Returns the char ranges of the changed
+/// words of `before` and of `after`, or `None` if one side is empty or the hunk is too large.
+pub fn word_diff(
+    before: RopeSlice,
+    after: RopeSlice,
+) -> Option<(Vec<Range<usize>>, Vec<Range<usize>>)> {
+    if before.len_chars() == 0
+        || after.len_chars() == 0
+        || before.len_lines() > MAX_WORD_DIFF_LINES
+        || after.len_lines() > MAX_WORD_DIFF_LINES
+    {
+        return None;
+    }
+    let (before, after) = (Cow::from(before), Cow::from(after));
+    let before_words: Vec<_> = words(&before).collect();
+    let after_words: Vec<_> = words(&after).collect();
+
+    let mut input = InternedInput {
+        before: Vec::with_capacity(before_words.len()),
+        after: Vec::with_capacity(after_words.len()),
+        interner: Interner::new(before_words.len() + after_words.len()),
+    };
+    input.update_before(before_words.iter().copied());
+    input.update_after(after_words.iter().copied());
+    let mut diff = imara_diff::Diff::default();
+    diff.compute_with(
+        Algorithm::Histogram,
+        &input.before,
+        &input.after,
+        input.interner.num_tokens(),
+    );
+
+    let before_offsets = char_offsets(&before_words);
+    let after_offsets = char_offsets(&after_words);
+    let mut before_ranges: Vec<Range<usize>> = Vec::new();
+    let mut after_ranges: Vec<Range<usize>> = Vec::new();
+    for hunk in diff.hunks() {
+        push_range(&mut before_ranges, &before_offsets, hunk.before);
+        push_range(&mut after_ranges, &after_offsets, hunk.after);
+    }
+    Some((before_ranges, after_ranges))
+}
+
+/// Splits text into words, runs of whitespace and single other characters.
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    let class = |c: char| {
+        if char_is_word(c) {
+            0
+        } else if char_is_whitespace(c) {
+            1
+        } else {
+            2
+        }
+    };
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        let mut chars = rest.char_indices();
+        let (_, first) = chars.next()?;
+        let end = match class(first) {
+            2 => first.len_utf8(),
+            first_class => chars
+                .find(|&(_, c)| class(c) != first_class)
+                .map_or(rest.len(), |(i, _)| i),
+        };
+        let (word, tail) = rest.split_at(end);
+        rest = tail;
+        Some(word)
+    })
+}
+
+/// The char offset of the start of each word followed by the length of the text.
+fn char_offsets(words: &[&str]) -> Vec<usize> {
+    let mut offset = 0;
+    let mut offsets = Vec::with_capacity(words.len() + 1);
+    offsets.push(0);
+    for word in words {
+        offset += word.chars().count();
+        offsets.push(offset);
+    }
+    offsets
+}
+
+/// Adds the char range of the words `words`, merging it with the previous range if they touch.
+fn push_range(ranges: &mut Vec<Range<usize>>, offsets: &[usize], words: Range<u32>) {
+    if words.is_empty() {
+        return;
+    }
+    let range = offsets[words.start as usize]..offsets[words.end as usize];
+    match ranges.last_mut() {
+        Some(last) if last.end == range.start => last.end = range.end,
+        _ => ranges.push(range),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use helix_core::Rope;
+
+    use super::*;
+
+    #[test]
+    fn changed_words() {
+        let before = Rope::from("let value = compute(a, b);\n");
+        let after = Rope::from("let result = compute(a, c);\n");
+        let (before_ranges, after_ranges) = word_diff(before.slice(..), after.slice(..)).unwrap();
+        assert_eq!(before_ranges, vec![4..9, 23..24]);
+        assert_eq!(after_ranges, vec![4..10, 24..25]);
+
+        assert_eq!(
+            words("fn foo(a)  {").collect::<Vec<_>>(),
+            ["fn", " ", "foo", "(", "a", ")", "  ", "{"]
+        );
+        assert!(word_diff(before.slice(..), Rope::new().slice(..)).is_none());
+    }
+}
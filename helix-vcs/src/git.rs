@@ -1,8 +1,9 @@
 use anyhow::{bail, Context, Result};
 use arc_swap::ArcSwap;
 use gix::filter::plumbing::driver::apply::Delay;
+use std::collections::HashMap;
 use std::io::Read;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use gix::bstr::ByteSlice;
@@ -17,6 +18,10 @@
 };
 use gix::{Commit, ObjectId, Repository, ThreadSafeRepository};
 
+use helix_core::Rope;
+
+use crate::blame::{BlameCache, BlameCommit, BlameHunk, FileBlame};
+use crate::diff::{self, Hunk};
 use crate::FileChange;
 
 #[cfg(test)]
@@ -47,18 +52,128 @@ pub fn get_diff_base(file: &Path) -> Result<Vec<u8>> {
     // This will apply the user's git config or attributes like crlf conversions.
     if let Some(work_dir) = repo.workdir() {
         let rela_path = file.strip_prefix(work_dir)?;
-        let rela_path = gix::path::try_into_bstr(rela_path)?;
-        let (mut pipeline, _) = repo.filter_pipeline(None)?;
-        let mut worktree_outcome =
-            pipeline.convert_to_worktree(&data, rela_path.as_ref(), Delay::Forbid)?;
-        let mut buf = Vec::with_capacity(data.len());
-        worktree_outcome.read_to_end(&mut buf)?;
-        Ok(buf)
+        to_worktree(&repo, rela_path, &data)
     } else {
         Ok(data)
     }
 }
 
+/// Stages the hunks of the diff between the index and `doc`, the current contents of `file`,
+/// that intersect with the given line ranges of `doc`. Returns the number of staged hunks.
+pub fn stage_hunks(file: &Path, doc: &Rope, line_ranges: &[(usize, usize)]) -> Result<usize> {
+    let (repo, rela_path) = open_worktree_file(file)?;
+    let mut index = repo.open_index()?;
+    let staged = read_index_file(&repo, &index, &rela_path)?;
+
+    let hunks = diff::compare_lines(&staged, doc);
+    let selected: Vec<_> =
+        diff::hunks_intersecting_line_ranges(&hunks, line_ranges.iter().copied()).collect();
+    if selected.is_empty() {
+        return Ok(0);
+    }
+    let staged = diff::apply_hunks(&staged, doc, selected.iter().copied());
+    write_index_file(&repo, &mut index, &rela_path, &staged)?;
+    Ok(selected.len())
+}
+
+/// Unstages the hunks of the diff between `HEAD` and the index that intersect with the given
+/// line ranges of `doc`, the current contents of `file`. Returns the number of unstaged hunks.
+pub fn unstage_hunks(file: &Path, doc: &Rope, line_ranges: &[(usize, usize)]) -> Result<usize> {
+    let (repo, rela_path) = open_worktree_file(file)?;
+    let mut index = repo.open_index()?;
+    let staged = read_index_file(&repo, &index, &rela_path)?;
+    let head = repo.head_commit()?;
+    let work_dir = repo.workdir().context("repo has no worktree")?;
+    let head_oid = find_file_in_commit(&repo, &head, &work_dir.join(&rela_path))?;
+    let committed = Rope::from_reader(&*to_worktree(
+        &repo,
+        &rela_path,
+        &repo.find_object(head_oid)?.detach().data,
+    )?)?;
+
+    // the line ranges refer to the document, map them to the index first
+    let line_ranges = diff::base_line_ranges(
+        &diff::compare_lines(&staged, doc),
+        line_ranges.iter().copied(),
+    );
+    let hunks: Vec<_> = diff::compare_lines(&committed, &staged)
+        .iter()
+        .map(Hunk::invert)
+        .collect();
+    let selected: Vec<_> =
+        diff::hunks_intersecting_line_ranges(&hunks, line_ranges.into_iter()).collect();
+    if selected.is_empty() {
+        return Ok(0);
+    }
+    let staged = diff::apply_hunks(&staged, &committed, selected.iter().copied());
+    write_index_file(&repo, &mut index, &rela_path, &staged)?;
+    Ok(selected.len())
+}
+
+/// Opens the repository containing `file` and returns it with the path of `file` relative to
+/// the worktree.
+fn open_worktree_file(file: &Path) -> Result<(Repository, PathBuf)> {
+    debug_assert!(file.is_absolute());
+    let file = gix::path::realpath(file).context("resolve symlinks")?;
+    let repo_dir = get_repo_dir(&file)?;
+    let repo = open_repo(repo_dir)
+        .context("failed to open git repo")?
+        .to_thread_local();
+    let work_dir = repo.workdir().context("repo has no worktree")?;
+    let rela_path = file.strip_prefix(work_dir)?.to_path_buf();
+    Ok((repo, rela_path))
+}
+
+/// Applies the filters of the user's git config and attributes, like crlf conversions, to the
+/// contents of a git object.
+fn to_worktree(repo: &Repository, rela_path: &Path, data: &[u8]) -> Result<Vec<u8>> {
+    let rela_path = gix::path::try_into_bstr(rela_path)?;
+    let (mut pipeline, _) = repo.filter_pipeline(None)?;
+    let mut worktree_outcome =
+        pipeline.convert_to_worktree(data, rela_path.as_ref(), Delay::Forbid)?;
+    let mut buf = Vec::with_capacity(data.len());
+    worktree_outcome.read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+/// Reads the staged contents of a file from the index.
+fn read_index_file(repo: &Repository, index: &gix::index::File, rela_path: &Path) -> Result<Rope> {
+    let path = gix::path::to_unix_separators_on_windows(gix::path::try_into_bstr(rela_path)?);
+    let entry = index
+        .entry_by_path(path.as_ref())
+        .context("file is not tracked")?;
+    let data = repo.find_object(entry.id)?.detach().data;
+    Ok(Rope::from_reader(&*to_worktree(repo, rela_path, &data)?)?)
+}
+
+/// Replaces the staged contents of a file in the index with `content`.
+fn write_index_file(
+    repo: &Repository,
+    index: &mut gix::index::File,
+    rela_path: &Path,
+    content: &Rope,
+) -> Result<()> {
+    let path = gix::path::to_unix_separators_on_windows(gix::path::try_into_bstr(rela_path)?);
+    let content = content.to_string();
+    let (mut pipeline, _) = repo.filter_pipeline(None)?;
+    let mut data = Vec::with_capacity(content.len());
+    pipeline
+        .convert_to_git(content.as_bytes(), rela_path, index)?
+        .read_to_end(&mut data)?;
+    let id = repo.write_blob(&data)?.detach();
+
+    let pos = index
+        .entry_index_by_path(path.as_ref())
+        .ok()
+        .context("file is not tracked")?;
+    let entry = &mut index.entries_mut()[pos];
+    entry.id = id;
+    // the stat no longer describes the staged content, force git to look at the file again
+    entry.stat = Default::default();
+    index.write(gix::index::write::Options::default())?;
+    Ok(())
+}
+
 pub fn get_current_head_name(file: &Path) -> Result<Arc<ArcSwap<Box<str>>>> {
     debug_assert!(!file.exists() || file.is_file());
     debug_assert!(file.is_absolute());
@@ -79,6 +194,59 @@ pub fn get_current_head_name(file: &Path) -> Result<Arc<ArcSwap<Box<str>>>> {
     Ok(Arc::new(ArcSwap::from_pointee(name.into_boxed_str())))
 }
 
+pub fn get_blame(file: &Path, cache: &BlameCache) -> Result<Arc<FileBlame>> {
+    debug_assert!(!file.exists() || file.is_file());
+    debug_assert!(file.is_absolute());
+    let file = gix::path::realpath(file).context("resolve symlinks")?;
+
+    let repo_dir = get_repo_dir(&file)?;
+    let repo = open_repo(repo_dir)
+        .context("failed to open git repo")?
+        .to_thread_local();
+    let head = repo.head_commit()?;
+    let revision = head.id.to_string();
+    if let Some(blame) = cache.get(&file, &revision) {
+        return Ok(blame);
+    }
+    // make sure the file is tracked before walking the history
+    find_file_in_commit(&repo, &head, &file)?;
+
+    let work_dir = repo.workdir().context("repo has no worktree")?;
+    let rela_path = gix::path::to_unix_separators_on_windows(gix::path::try_into_bstr(
+        file.strip_prefix(work_dir)?,
+    )?);
+    let outcome = repo.blame_file(rela_path.as_ref(), head.id, Default::default())?;
+
+    let mut commits: HashMap<ObjectId, Arc<BlameCommit>> = HashMap::new();
+    let mut hunks = Vec::with_capacity(outcome.entries.len());
+    for entry in outcome.entries {
+        let commit = match commits.get(&entry.commit_id) {
+            Some(commit) => commit.clone(),
+            None => {
+                let commit = repo.find_commit(entry.commit_id)?;
+                let author = commit.author()?;
+                let info = Arc::new(BlameCommit {
+                    id: entry.commit_id.to_hex_with_len(8).to_string(),
+                    author: author.name.to_string(),
+                    time: author.time()?.seconds,
+                    summary: commit.message()?.summary().to_string(),
+                });
+                commits.insert(entry.commit_id, info.clone());
+                info
+            }
+        };
+        hunks.push(BlameHunk {
+            start: entry.start_in_blamed_file,
+            len: entry.len.get(),
+            commit,
+        });
+    }
+
+    let blame = Arc::new(FileBlame::new(revision, hunks));
+    cache.insert(file, blame.clone());
+    Ok(blame)
+}
+
 pub fn for_each_changed_file(cwd: &Path, f: impl Fn(Result<FileChange>) -> bool) -> Result<()> {
     status(&open_repo(cwd)?.to_thread_local(), f)
 }
@@ -1,7 +1,9 @@
-use std::{fs::File, io::Write, path::Path, process::Command};
+use std::{fs::File, io::Write, path::Path, process::Command, sync::Arc};
 
+use helix_core::Rope;
 use tempfile::TempDir;
 
+use crate::blame::BlameCache;
 use crate::git;
 
 fn exec_git_cmd(args: &str, git_dir: &Path) {
@@ -150,3 +152,68 @@ fn symlink_to_git_repo() {
     assert_eq!(git::get_diff_base(&file_link).unwrap(), contents);
     assert_eq!(git::get_diff_base(&file).unwrap(), contents);
 }
+
+#[test]
+fn blame() {
+    let temp_git = empty_git_repo();
+    let file = temp_git.path().join("file.txt");
+    File::create(&file)
+        .unwrap()
+        .write_all(b"foo\nbar\n")
+        .unwrap();
+    create_commit(temp_git.path(), true);
+    File::create(&file)
+        .unwrap()
+        .write_all(b"foo\nbaz\nbar\n")
+        .unwrap();
+    exec_git_cmd("commit -a -m second", temp_git.path());
+
+    let cache = BlameCache::default();
+    let blame = git::get_blame(&file, &cache).unwrap();
+    let summary = |line| blame.commit_at(line).map(|commit| commit.summary.as_str());
+    assert_eq!(summary(0), Some("message"));
+    assert_eq!(summary(1), Some("second"));
+    assert_eq!(summary(2), Some("message"));
+    assert_eq!(summary(3), None);
+    assert_eq!(blame.commit_at(0).unwrap().author, "author");
+
+    // the blame is cached until HEAD moves
+    assert!(Arc::ptr_eq(&blame, &git::get_blame(&file, &cache).unwrap()));
+}
+
+#[test]
+fn stage_and_unstage_hunks() {
+    let temp_git = empty_git_repo();
+    let file = temp_git.path().join("file.txt");
+    File::create(&file)
+        .unwrap()
+        .write_all(b"a\nb\nc\nd\ne\n")
+        .unwrap();
+    create_commit(temp_git.path(), true);
+    let doc = Rope::from("A\nb\nc\nd\nE\n");
+    File::create(&file)
+        .unwrap()
+        .write_all(doc.to_string().as_bytes())
+        .unwrap();
+
+    let staged = || {
+        let (repo, rela_path) = git::open_worktree_file(&file).unwrap();
+        let index = repo.open_index().unwrap();
+        git::read_index_file(&repo, &index, &rela_path)
+            .unwrap()
+            .to_string()
+    };
+
+    assert_eq!(git::stage_hunks(&file, &doc, &[(4, 4)]).unwrap(), 1);
+    assert_eq!(staged(), "a\nb\nc\nd\nE\n");
+    // the staged change is no longer part of the diff between the index and the document
+    assert_eq!(git::stage_hunks(&file, &doc, &[(4, 4)]).unwrap(), 0);
+    assert_eq!(git::stage_hunks(&file, &doc, &[(0, 4)]).unwrap(), 1);
+    assert_eq!(staged(), "A\nb\nc\nd\nE\n");
+
+    assert_eq!(git::unstage_hunks(&file, &doc, &[(0, 0)]).unwrap(), 1);
+    assert_eq!(staged(), "a\nb\nc\nd\nE\n");
+    assert_eq!(git::unstage_hunks(&file, &doc, &[(0, 0)]).unwrap(), 0);
+    assert_eq!(git::unstage_hunks(&file, &doc, &[(1, 4)]).unwrap(), 1);
+    assert_eq!(staged(), "a\nb\nc\nd\ne\n");
+}
@@ -4,6 +4,7 @@
 
 use anyhow::{anyhow, bail, Result};
 use arc_swap::ArcSwap;
+use helix_core::Rope;
 use std::{
     path::{Path, PathBuf},
     sync::Arc,
@@ -12,9 +13,13 @@
 #[cfg(feature = "git")]
 mod git;
 
+mod blame;
 mod diff;
 
-pub use diff::{DiffHandle, Hunk};
+use blame::BlameCache;
+pub use blame::{BlameCommit, FileBlame, LineBlame};
+
+pub use diff::{word_diff, DiffHandle, Hunk};
 
 mod status;
 
@@ -25,6 +30,7 @@
 #[derive(Clone)]
 pub struct DiffProviderRegistry {
     providers: Vec<DiffProvider>,
+    blame_cache: BlameCache,
 }
 
 impl DiffProviderRegistry {
@@ -57,6 +63,59 @@ pub fn get_current_head_name(&self, file: &Path) -> Option<Arc<ArcSwap<Box<str>>
             })
     }
 
+    /// Blames the given file at `HEAD`. The result is cached until `HEAD` moves.
+    ///
+    /// This walks the history of the file and should not be called on the main thread.
+    pub fn get_blame(&self, file: &Path) -> Option<Arc<FileBlame>> {
+        self.providers.iter().find_map(|provider| {
+            match provider.get_blame(file, &self.blame_cache) {
+                Ok(res) => Some(res),
+                Err(err) => {
+                    log::debug!("{err:#?}");
+                    log::debug!("failed to blame {}", file.display());
+                    None
+                }
+            }
+        })
+    }
+
+    /// Stages the changes of `doc`, the current contents of `file`, that intersect with the
+    /// given (inclusive) line ranges of `doc`. Returns the number of staged changes.
+    pub fn stage_hunks(
+        &self,
+        file: &Path,
+        doc: &Rope,
+        line_ranges: &[(usize, usize)],
+    ) -> Result<usize> {
+        self.first_success(|provider| provider.stage_hunks(file, doc, line_ranges))
+    }
+
+    /// Unstages the staged changes of `file` that intersect with the given (inclusive) line
+    /// ranges of `doc`, the current contents of `file`. Returns the number of unstaged changes.
+    pub fn unstage_hunks(
+        &self,
+        file: &Path,
+        doc: &Rope,
+        line_ranges: &[(usize, usize)],
+    ) -> Result<usize> {
+        self.first_success(|provider| provider.unstage_hunks(file, doc, line_ranges))
+    }
+
+    /// Returns the result of the first provider that succeeds or the error of the first
+    /// provider if none does.
+    fn first_success<T>(&self, f: impl Fn(&DiffProvider) -> Result<T>) -> Result<T> {
+        let mut error = None;
+        for provider in &self.providers {
+            match f(provider) {
+                Ok(res) => return Ok(res),
+                Err(err) => {
+                    error.get_or_insert(err);
+                }
+            }
+        }
+        Err(error.unwrap_or_else(|| anyhow!("no diff provider available")))
+    }
+
     /// Fire-and-forget changed file iteration. Runs everything in a background task. Keeps
     /// iteration until `on_change` returns `false`.
     pub fn for_each_changed_file(
@@ -86,7 +145,10 @@ fn default() -> Self {
             DiffProvider::Git,
             DiffProvider::None,
         ];
-        DiffProviderRegistry { providers }
+        DiffProviderRegistry {
+            providers,
+            blame_cache: BlameCache::default(),
+        }
     }
 }
 
@@ -118,6 +180,40 @@ fn get_current_head_name(&self, file: &Path) -> Result<Arc<ArcSwap<Box<str>>>> {
         }
     }
 
+    fn get_blame(&self, file: &Path, cache: &BlameCache) -> Result<Arc<FileBlame>> {
+        match self {
+            #[cfg(feature = "git")]
+            Self::Git => git::get_blame(file, cache),
+            Self::None => bail!("No blame support compiled in"),
+        }
+    }
+
+    fn stage_hunks(
+        &self,
+        file: &Path,
+        doc: &Rope,
+        line_ranges: &[(usize, usize)],
+    ) -> Result<usize> {
+        match self {
+            #[cfg(feature = "git")]
+            Self::Git => git::stage_hunks(file, doc, line_ranges),
+            Self::None => bail!("No staging support compiled in"),
+        }
+    }
+
+    fn unstage_hunks(
+        &self,
+        file: &Path,
+        doc: &Rope,
+        line_ranges: &[(usize, usize)],
+    ) -> Result<usize> {
+        match self {
+            #[cfg(feature = "git")]
+            Self::Git => git::unstage_hunks(file, doc, line_ranges),
+            Self::None => bail!("No staging support compiled in"),
+        }
+    }
+
     fn for_each_changed_file(
         &self,
         cwd: &Path,
@@ -0,0 +1,240 @@
+//! Smooth scrolling: a view that jumps by more than a few lines, like after a page scroll, a goto
+//! or centering the cursor, scrolls to its new position over
+//! [crate::editor::Config::smooth_scroll] instead of teleporting there. The animations advance
+//! whenever a frame is rendered, the application renders frames while [ScrollAnimations::is_running].
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::tree::Tree;
+use crate::view::ViewPosition;
+use crate::{Document, DocumentId, ViewId};
+
+/// Jumps shorter than this are shown immediately, like scrolling with the mouse wheel.
+const MIN_ANIMATED_LINES: usize = 4;
+
+/// The first line shown by a view at the last frame.
+#[derive(Debug, Clone, Copy)]
+struct Shown {
+    doc: DocumentId,
+    /// The version of the document, the lines of a changed document can't be compared.
+    version: i32,
+    line: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ScrollAnimation {
+    from: usize,
+    target: ViewPosition,
+    target_line: usize,
+    start: Instant,
+}
+
+#[derive(Debug, Default)]
+pub struct ScrollAnimations {
+    shown: HashMap<ViewId, Shown>,
+    running: HashMap<ViewId, ScrollAnimation>,
+}
+
+impl ScrollAnimations {
+    pub fn is_running(&self) -> bool {
+        !self.running.is_empty()
+    }
+
+    /// Advances the running animations to the current frame and starts animations for the views
+    /// that jumped since the last frame. Called before rendering a frame.
+    pub fn update(
+        &mut self,
+        tree: &Tree,
+        documents: &mut BTreeMap<DocumentId, Document>,
+        duration: Duration,
+    ) {
+        let now = Instant::now();
+        self.shown.retain(|view_id, _| tree.contains(*view_id));
+        self.running.retain(|view_id, _| tree.contains(*view_id));
+
+        for (view, _) in tree.views() {
+            let Some(version) = documents.get(&view.doc).map(Document::version) else {
+                continue;
+            };
+            let shown = self.shown.get(&view.id).copied();
+            let unchanged = shown.filter(|shown| {
+                shown.doc == view.doc && shown.version == version && !duration.is_zero()
+            });
+            // the lines of a document that changed while scrolling can't be animated, the view
+            // skips to the target like when input is handled
+            if let (None, Some(shown)) = (unchanged, shown) {
+                if let Some(animation) = self.running.remove(&view.id) {
+                    if let Some(doc) = documents.get_mut(&shown.doc) {
+                        skip_to_target(doc, view.id, animation);
+                    }
+                }
+            }
+
+            let Some(doc) = documents.get_mut(&view.doc) else {
+                continue;
+            };
+            let text = doc.text().slice(..);
+            let offset = doc.view_offset(view.id);
+            let line = text.char_to_line(offset.anchor.min(text.len_chars()));
+
+            let animation = match (self.running.get(&view.id).copied(), unchanged) {
+                // the view is still where the animation left it
+                (Some(animation), Some(shown)) if shown.line == line => Some(animation),
+                // the view jumped while or since the last frame
+                (_, Some(shown)) if shown.line.abs_diff(line) >= MIN_ANIMATED_LINES => {
+                    Some(ScrollAnimation {
+                        from: shown.line,
+                        target: offset,
+                        target_line: line,
+                        start: now,
+                    })
+                }
+                _ => None,
+            };
+
+            let line = match animation {
+                Some(animation) if now < animation.start + duration => {
+                    let progress = (now - animation.start).as_secs_f64() / duration.as_secs_f64();
+                    let line = animation_line(animation.from, animation.target_line, progress);
+                    let position = ViewPosition {
+                        anchor: text.line_to_char(line),
+                        horizontal_offset: animation.target.horizontal_offset,
+                        vertical_offset: 0,
+                    };
+                    doc.set_view_offset(view.id, position);
+                    self.running.insert(view.id, animation);
+                    line
+                }
+                Some(animation) => {
+                    doc.set_view_offset(view.id, animation.target);
+                    self.running.remove(&view.id);
+                    animation.target_line
+                }
+                None => {
+                    self.running.remove(&view.id);
+                    line
+                }
+            };
+            let shown = Shown {
+                doc: doc.id(),
+                version: doc.version(),
+                line,
+            };
+            self.shown.insert(view.id, shown);
+        }
+    }
+
+    /// Moves the views to the targets of their animations immediately, so that input is handled
+    /// with the final positions.
+    pub fn finish(&mut self, documents: &mut BTreeMap<DocumentId, Document>) {
+        for (view_id, animation) in self.running.drain() {
+            let Some(shown) = self.shown.get_mut(&view_id) else {
+                continue;
+            };
+            if let Some(doc) = documents.get_mut(&shown.doc) {
+                skip_to_target(doc, view_id, animation);
+                shown.line = animation.target_line;
+            }
+        }
+    }
+}
+
+/// The first line shown at `progress`, between 0 and 1, of an animation from line `from` to
+/// line `to`. It eases out: fast at first and slowing down towards the target.
+fn animation_line(from: usize, to: usize, progress: f64) -> usize {
+    let eased = 1.0 - (1.0 - progress.clamp(0.0, 1.0)).powi(3);
+    let distance = to as f64 - from as f64;
+    (from as f64 + distance * eased).round() as usize
+}
+
+/// Moves the view to the target of its animation. The target is kept in the text if
+/// the document got shorter.
+fn skip_to_target(doc: &mut Document, view_id: ViewId, animation: ScrollAnimation) {
+    let target = ViewPosition {
+        anchor: animation.target.anchor.min(doc.text().len_chars()),
+        ..animation.target
+    };
+    doc.set_view_offset(view_id, target);
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use arc_swap::ArcSwap;
+    use helix_core::{syntax, Rope, Transaction};
+
+    use super::*;
+    use crate::editor::{Config, GutterConfig};
+    use crate::graphics::Rect;
+    use crate::View;
+
+    #[test]
+    fn animation_line_eases_out() {
+        assert_eq!(animation_line(10, 110, 0.0), 10);
+        assert_eq!(animation_line(10, 110, 0.5), 98);
+        assert_eq!(animation_line(10, 110, 1.0), 110);
+        assert_eq!(animation_line(110, 10, 0.5), 23);
+        assert_eq!(animation_line(110, 10, 2.0), 10);
+        // each step moves at most as far as the previous one
+        let lines: Vec<_> = (0..=10)
+            .map(|i| animation_line(0, 1000, i as f64 / 10.0))
+            .collect();
+        assert!(lines.windows(3).all(|l| l[1] - l[0] >= l[2] - l[1]));
+    }
+
+    fn line(documents: &BTreeMap<DocumentId, Document>, view: ViewId) -> usize {
+        let doc = documents.values().next().unwrap();
+        doc.text().char_to_line(doc.view_offset(view).anchor)
+    }
+
+    #[test]
+    fn changed_document_cancels_the_animation() {
+        let mut doc = Document::from(
+            Rope::from("line\n".repeat(200)),
+            None,
+            Arc::new(ArcSwap::new(Arc::new(Config::default()))),
+            Arc::new(ArcSwap::from_pointee(syntax::Loader::default())),
+        );
+        let mut tree = Tree::new(Rect::new(0, 0, 80, 20));
+        let view = tree.insert(View::new(doc.id(), GutterConfig::default()));
+        doc.ensure_view_init(view);
+        let mut documents = BTreeMap::from([(doc.id(), doc)]);
+        let duration = Duration::from_secs(3600);
+
+        let mut animations = ScrollAnimations::default();
+        animations.update(&tree, &mut documents, duration);
+        assert!(!animations.is_running());
+
+        // a jump starts scrolling from the shown line
+        let doc = documents.values_mut().next().unwrap();
+        let target = ViewPosition {
+            anchor: doc.text().line_to_char(100),
+            ..Default::default()
+        };
+        doc.set_view_offset(view, target);
+        animations.update(&tree, &mut documents, duration);
+        assert!(animations.is_running());
+        assert!(line(&documents, view) < 100);
+
+        // the view skips to the target once the document changes
+        let doc = documents.values_mut().next().unwrap();
+        let end = doc.text().len_chars();
+        let insert = Transaction::change(doc.text(), [(end, end, Some("end".into()))].into_iter());
+        assert!(doc.apply(&insert, view));
+        animations.update(&tree, &mut documents, duration);
+        assert!(!animations.is_running());
+        assert_eq!(line(&documents, view), 100);
+
+        // so does finishing an animation
+        let doc = documents.values_mut().next().unwrap();
+        doc.set_view_offset(view, ViewPosition::default());
+        animations.update(&tree, &mut documents, duration);
+        assert!(animations.is_running());
+        animations.finish(&mut documents);
+        assert!(!animations.is_running());
+        assert_eq!(line(&documents, view), 0);
+    }
+}
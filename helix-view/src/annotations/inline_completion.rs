@@ -0,0 +1,324 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+use helix_core::chars::{char_is_line_ending, char_is_whitespace, char_is_word};
+use helix_core::doc_formatter::FormattedGrapheme;
+use helix_core::text_annotations::{InlineAnnotation, LineAnnotation};
+use helix_core::{Position, Rope, RopeSlice, Selection, Transaction};
+
+use crate::ViewId;
+
+/// A suggestion returned by `textDocument/inlineCompletion`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlineCompletionItem {
+    /// The char range replaced by `insert_text` when the suggestion is accepted.
+    pub range: Range<usize>,
+    pub insert_text: String,
+}
+
+#[derive(Debug, Clone)]
+struct Suggestion {
+    item: InlineCompletionItem,
+    /// Byte offset into `insert_text` where the text that hasn't been typed yet starts.
+    ghost_start: usize,
+}
+
+/// Ghost text proposed by a language server after the primary cursor of a view.
+///
+/// The first line of the active suggestion is rendered inline at the cursor, any further lines
+/// are rendered as virtual lines below the cursor line.
+#[derive(Debug, Clone)]
+pub struct InlineCompletion {
+    pub view_id: ViewId,
+    /// The position of the primary cursor the suggestions were requested for.
+    pub cursor: usize,
+    suggestions: Vec<Suggestion>,
+    active: usize,
+    annotation: Vec<InlineAnnotation>,
+    virtual_lines: Vec<String>,
+}
+
+impl InlineCompletion {
+    /// Creates the ghost text for `items` at `cursor`.
+    ///
+    /// Items that don't contain the cursor or whose replaced text before the cursor isn't a
+    /// prefix of their insert text are dropped. Returns `None` if there is nothing left to show.
+    pub fn new(
+        view_id: ViewId,
+        text: RopeSlice,
+        cursor: usize,
+        items: impl IntoIterator<Item = InlineCompletionItem>,
+    ) -> Option<Self> {
+        let suggestions: Vec<_> = items
+            .into_iter()
+            .filter_map(|item| {
+                if item.range.start > cursor || item.range.end < cursor {
+                    return None;
+                }
+                let typed: Cow<str> = text.slice(item.range.start..cursor).into();
+                let ghost = item.insert_text.strip_prefix(&*typed)?;
+                if ghost.is_empty() {
+                    return None;
+                }
+                let ghost_start = item.insert_text.len() - ghost.len();
+                Some(Suggestion { item, ghost_start })
+            })
+            .collect();
+        if suggestions.is_empty() {
+            return None;
+        }
+        let mut completion = InlineCompletion {
+            view_id,
+            cursor,
+            suggestions,
+            active: 0,
+            annotation: Vec::new(),
+            virtual_lines: Vec::new(),
+        };
+        completion.layout();
+        Some(completion)
+    }
+
+    fn layout(&mut self) {
+        let mut lines = self.ghost_text().split('\n').map(|line| {
+            line.strip_suffix('\r')
+                .unwrap_or(line)
+                .replace('\t', "    ")
+        });
+        let first_line = lines.next().unwrap_or_default();
+        self.annotation = vec![InlineAnnotation::new(self.cursor, first_line)];
+        self.virtual_lines = lines.collect();
+    }
+
+    pub fn active_item(&self) -> &InlineCompletionItem {
+        &self.suggestions[self.active].item
+    }
+
+    /// The part of the active suggestion that hasn't been typed yet.
+    pub fn ghost_text(&self) -> &str {
+        let suggestion = &self.suggestions[self.active];
+        &suggestion.item.insert_text[suggestion.ghost_start..]
+    }
+
+    /// Returns the position of the active suggestion and the number of suggestions.
+    pub fn position(&self) -> (usize, usize) {
+        (self.active + 1, self.suggestions.len())
+    }
+
+    /// Switches to the next (or previous) suggestion, wrapping around at the ends.
+    pub fn cycle(&mut self, forward: bool) {
+        let len = self.suggestions.len();
+        self.active = if forward {
+            (self.active + 1) % len
+        } else {
+            (self.active + len - 1) % len
+        };
+        self.layout();
+    }
+
+    /// The first line of the ghost text, anchored at the cursor.
+    pub fn annotation(&self) -> &[InlineAnnotation] {
+        &self.annotation
+    }
+
+    /// The remaining lines of the ghost text.
+    pub fn virtual_lines(&self) -> &[String] {
+        &self.virtual_lines
+    }
+
+    /// The char index of the line end of the cursor line. Ghost lines are inserted after it.
+    pub fn virtual_lines_anchor(&self, text: RopeSlice) -> usize {
+        helix_core::line_ending::line_end_char_index(&text, text.char_to_line(self.cursor))
+    }
+
+    /// Reserves the virtual lines of the ghost text below the cursor line.
+    pub fn line_annotation(&self, text: RopeSlice) -> Box<dyn LineAnnotation + '_> {
+        Box::new(GhostLines {
+            anchor: self.virtual_lines_anchor(text),
+            height: self.virtual_lines.len(),
+            reached: false,
+        })
+    }
+
+    /// Builds the transaction replacing the range of the active suggestion with its text.
+    pub fn accept(&self, doc: &Rope) -> Transaction {
+        let item = self.active_item();
+        let end = item.range.start + item.insert_text.chars().count();
+        Transaction::change(
+            doc,
+            [(
+                item.range.start,
+                item.range.end,
+                Some(item.insert_text.as_str().into()),
+            )]
+            .into_iter(),
+        )
+        .with_selection(Selection::point(end))
+    }
+
+    /// Returns the next word of the ghost text, including the whitespace before it.
+    ///
+    /// A line break is treated as a word of its own so that multi-line suggestions are
+    /// accepted one line at a time.
+    pub fn next_word(&self) -> &str {
+        let ghost = self.ghost_text();
+        let mut chars = ghost.char_indices().peekable();
+        while chars
+            .next_if(|&(_, ch)| char_is_whitespace(ch) && !char_is_line_ending(ch))
+            .is_some()
+        {}
+        let end = match chars.next() {
+            None => ghost.len(),
+            Some((i, ch)) if char_is_line_ending(ch) => {
+                if ch == '\r' && ghost[i + 1..].starts_with('\n') {
+                    i + 2
+                } else {
+                    i + ch.len_utf8()
+                }
+            }
+            Some((_, ch)) => {
+                let is_word = char_is_word(ch);
+                while chars
+                    .next_if(|&(_, next)| {
+                        !char_is_whitespace(next) && char_is_word(next) == is_word
+                    })
+                    .is_some()
+                {}
+                chars.peek().map_or(ghost.len(), |&(i, _)| i)
+            }
+        };
+        &ghost[..end]
+    }
+
+    /// Updates the ghost text after `inserted` was inserted at the cursor, returning `None` once
+    /// the whole suggestion has been typed.
+    pub fn advance(mut self, inserted: &str) -> Option<Self> {
+        let inserted_chars = inserted.chars().count();
+        let suggestion = &mut self.suggestions[self.active];
+        if !suggestion.item.insert_text[suggestion.ghost_start..].starts_with(inserted) {
+            return None;
+        }
+        suggestion.ghost_start += inserted.len();
+        suggestion.item.range.end += inserted_chars;
+        if suggestion.ghost_start == suggestion.item.insert_text.len() {
+            return None;
+        }
+        let suggestion = self.suggestions.swap_remove(self.active);
+        self.suggestions = vec![suggestion];
+        self.active = 0;
+        self.cursor += inserted_chars;
+        self.layout();
+        Some(self)
+    }
+}
+
+struct GhostLines {
+    anchor: usize,
+    height: usize,
+    reached: bool,
+}
+
+impl LineAnnotation for GhostLines {
+    fn reset_pos(&mut self, char_idx: usize) -> usize {
+        self.reached = false;
+        if char_idx <= self.anchor {
+            self.anchor
+        } else {
+            usize::MAX
+        }
+    }
+
+    fn skip_concealed_anchors(&mut self, _conceal_end_char_idx: usize) -> usize {
+        usize::MAX
+    }
+
+    fn process_anchor(&mut self, _grapheme: &FormattedGrapheme) -> usize {
+        self.reached = true;
+        usize::MAX
+    }
+
+    fn insert_virtual_lines(
+        &mut self,
+        _line_end_char_idx: usize,
+        _line_end_visual_pos: Position,
+        _doc_line: usize,
+    ) -> Position {
+        if std::mem::take(&mut self.reached) {
+            Position::new(self.height, 0)
+        } else {
+            Position::new(0, 0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn completion(
+        text: &str,
+        cursor: usize,
+        range: Range<usize>,
+        insert: &str,
+    ) -> InlineCompletion {
+        let text = Rope::from(text);
+        InlineCompletion::new(
+            ViewId::default(),
+            text.slice(..),
+            cursor,
+            [InlineCompletionItem {
+                range,
+                insert_text: insert.to_string(),
+            }],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn ghost_text_skips_typed_prefix() {
+        let completion = completion("fn ma\n", 5, 3..5, "main() {\n    todo!()\n}");
+        assert_eq!(completion.ghost_text(), "in() {\n    todo!()\n}");
+        assert_eq!(&*completion.annotation()[0].text, "in() {");
+        assert_eq!(completion.virtual_lines(), ["    todo!()", "}"]);
+    }
+
+    #[test]
+    fn mismatched_items_are_dropped() {
+        let text = Rope::from("fn ma\n");
+        let items = [InlineCompletionItem {
+            range: 3..5,
+            insert_text: "foo".to_string(),
+        }];
+        assert!(InlineCompletion::new(ViewId::default(), text.slice(..), 5, items).is_none());
+    }
+
+    #[test]
+    fn accept_word_by_word() {
+        let completion = completion("let x\n", 5, 5..5, " = foo(bar);\nx");
+        assert_eq!(completion.next_word(), " =");
+        let completion = completion.advance(" =").unwrap();
+        assert_eq!(completion.cursor, 7);
+        assert_eq!(completion.next_word(), " foo");
+        let completion = completion.advance(" foo").unwrap();
+        assert_eq!(completion.next_word(), "(");
+        let completion = completion.advance("(").unwrap();
+        let completion = completion.advance("bar").unwrap();
+        assert_eq!(completion.next_word(), ");");
+        let completion = completion.advance(");").unwrap();
+        assert_eq!(completion.next_word(), "\n");
+        let completion = completion.advance("\n").unwrap();
+        assert_eq!(completion.next_word(), "x");
+        assert!(completion.advance("x").is_none());
+    }
+
+    #[test]
+    fn accept_replaces_range() {
+        let doc = Rope::from("fn ma()\n");
+        let completion = completion("fn ma()\n", 5, 3..7, "main()");
+        let mut doc = doc;
+        let transaction = completion.accept(&doc);
+        assert!(transaction.apply(&mut doc));
+        assert_eq!(doc, "fn main()\n");
+        assert_eq!(transaction.selection().unwrap().primary().head, 9);
+    }
+}
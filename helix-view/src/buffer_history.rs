@@ -0,0 +1,167 @@
+//! A navigation history of the buffer locations that were left by switching to another buffer.
+//! Unlike the jumplists, which belong to a view, the history is shared by all views.
+
+use std::collections::VecDeque;
+
+use helix_core::{Range, Selection};
+
+use crate::{Document, DocumentId};
+
+const BUFFER_HISTORY_CAPACITY: usize = 50;
+
+/// A selection in a document at a revision of the document's history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BufferLocation {
+    pub doc: DocumentId,
+    selection: Selection,
+    revision: usize,
+}
+
+impl BufferLocation {
+    pub fn new(doc: &mut Document, selection: Selection) -> Self {
+        Self {
+            doc: doc.id(),
+            selection,
+            revision: doc.get_current_revision(),
+        }
+    }
+
+    /// Returns the selection mapped through the changes made to `doc` since the location was
+    /// recorded.
+    pub fn selection(&self, doc: &mut Document) -> Selection {
+        let mut selection = self.selection.clone();
+        if let Some(transaction) = doc.history.get_mut().changes_since(self.revision) {
+            selection = selection.map(transaction.changes());
+        }
+        let text = doc.text().slice(..);
+        let len = text.len_chars();
+        selection
+            .transform(|range| Range::new(range.anchor.min(len), range.head.min(len)))
+            .ensure_invariants(text)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct BufferHistory {
+    locations: VecDeque<BufferLocation>,
+    current: usize,
+}
+
+impl BufferHistory {
+    /// Records a location that is being left. Consecutive locations in the same buffer are
+    /// merged and the locations after the current one are dropped.
+    pub fn push(&mut self, location: BufferLocation) {
+        self.locations.truncate(self.current);
+        if self
+            .locations
+            .back()
+            .is_some_and(|last| last.doc == location.doc)
+        {
+            self.locations.pop_back();
+        }
+        while self.locations.len() >= BUFFER_HISTORY_CAPACITY {
+            self.locations.pop_front();
+        }
+        self.locations.push_back(location);
+        self.current = self.locations.len();
+    }
+
+    /// Moves `count` locations back. `location` is the location that is being left, it is
+    /// recorded so that [BufferHistory::forward] can return to it.
+    pub fn backward(&mut self, location: BufferLocation, count: usize) -> Option<&BufferLocation> {
+        let at_tip = self.current == self.locations.len();
+        if at_tip {
+            self.push(location);
+            self.current = self.locations.len() - 1;
+        } else {
+            self.locations[self.current] = location;
+        }
+        match self.current.checked_sub(count) {
+            Some(target) if count > 0 => {
+                self.current = target;
+                self.locations.get(self.current)
+            }
+            _ => {
+                if at_tip {
+                    self.current = self.locations.len();
+                }
+                None
+            }
+        }
+    }
+
+    pub fn forward(&mut self, location: BufferLocation, count: usize) -> Option<&BufferLocation> {
+        let target = self.current + count;
+        if count == 0 || target >= self.locations.len() {
+            return None;
+        }
+        if let Some(current) = self.locations.get_mut(self.current) {
+            *current = location;
+        }
+        self.current = target;
+        self.locations.get(self.current)
+    }
+
+    /// Removes the locations in a closed document.
+    pub fn remove(&mut self, doc_id: DocumentId) {
+        let removed_before_current = self
+            .locations
+            .iter()
+            .take(self.current)
+            .filter(|location| location.doc == doc_id)
+            .count();
+        self.locations.retain(|location| location.doc != doc_id);
+        self.current = self
+            .current
+            .saturating_sub(removed_before_current)
+            .min(self.locations.len());
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &BufferLocation> {
+        self.locations.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroUsize;
+
+    use super::*;
+
+    fn location(doc: usize, pos: usize) -> BufferLocation {
+        BufferLocation {
+            doc: DocumentId(NonZeroUsize::new(doc).unwrap()),
+            selection: Selection::point(pos),
+            revision: 0,
+        }
+    }
+
+    fn doc(location: Option<&BufferLocation>) -> Option<usize> {
+        location.map(|location| location.doc.0.get())
+    }
+
+    #[test]
+    fn navigate_buffer_history() {
+        let mut history = BufferHistory::default();
+        // A -> B -> C, consecutive locations in B are merged
+        history.push(location(1, 0));
+        history.push(location(2, 0));
+        history.push(location(2, 5));
+        assert_eq!(history.iter().count(), 2);
+
+        assert_eq!(doc(history.backward(location(3, 0), 1)), Some(2));
+        assert_eq!(doc(history.backward(location(2, 5), 1)), Some(1));
+        assert_eq!(doc(history.backward(location(1, 0), 1)), None);
+        assert_eq!(doc(history.forward(location(1, 0), 2)), Some(3));
+        assert_eq!(doc(history.forward(location(3, 0), 1)), None);
+        assert_eq!(doc(history.backward(location(3, 0), 2)), Some(1));
+
+        // leaving a location drops the locations after it
+        history.push(location(1, 3));
+        assert_eq!(history.iter().count(), 1);
+        assert_eq!(doc(history.backward(location(4, 0), 1)), Some(1));
+        history.remove(DocumentId(NonZeroUsize::new(1).unwrap()));
+        assert_eq!(doc(history.forward(location(1, 0), 1)), None);
+        assert_eq!(history.iter().count(), 1);
+    }
+}
@@ -9,13 +9,14 @@
 use helix_core::diagnostic::DiagnosticProvider;
 use helix_core::doc_formatter::TextFormat;
 use helix_core::encoding::Encoding;
+use helix_core::fold::FoldMap;
 use helix_core::snippets::{ActiveSnippet, SnippetRenderCtx};
 use helix_core::syntax::config::LanguageServerFeature;
 use helix_core::text_annotations::{InlineAnnotation, Overlay};
 use helix_event::TaskController;
 use helix_lsp::util::lsp_pos_to_pos;
 use helix_stdx::faccess::{copy_metadata, readonly};
-use helix_vcs::{DiffHandle, DiffProviderRegistry};
+use helix_vcs::{DiffHandle, DiffProviderRegistry, FileBlame, LineBlame};
 use once_cell::sync::OnceCell;
 use thiserror;
 
@@ -24,29 +25,36 @@
 use serde::Serialize;
 use std::borrow::Cow;
 use std::cell::Cell;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::fmt::Display;
 use std::future::Future;
 use std::io;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::{Arc, Weak};
-use std::time::SystemTime;
+use std::time::{Duration, Instant, SystemTime};
 
 use helix_core::{
     editor_config::EditorConfig,
     encoding,
-    history::{History, State, UndoKind},
+    history::{History, RevisionSummary, State, UndoKind},
     indent::{auto_detect_indent_style, IndentStyle},
     line_ending::auto_detect_line_ending,
-    syntax::{self, config::LanguageConfiguration},
-    ChangeSet, Diagnostic, LineEnding, Range, Rope, RopeBuilder, Selection, Syntax, Transaction,
+    merge_conflict,
+    syntax::{
+        self,
+        config::{FormatterMode, LanguageConfiguration},
+    },
+    ChangeSet, Diagnostic, LineEnding, Range, Rope, RopeBuilder, RopeSlice, Selection, Syntax,
+    Transaction,
 };
 
 use crate::{
+    annotations::inline_completion::InlineCompletion,
     editor::Config,
     events::{DocumentDidChange, SelectionDidChange},
     expansion,
+    remote::RemoteFile,
     view::ViewPosition,
     DocumentId, Editor, Theme, View, ViewId,
 };
@@ -61,6 +69,26 @@
 
 pub const SCRATCH_BUFFER_NAME: &str = "[scratch]";
 
+/// Once updating the syntax tree of a document took longer than this, for example because of a
+/// large file with many injections, the tree is updated in the background instead of on each
+/// edit.
+const BACKGROUND_PARSE_THRESHOLD: Duration = Duration::from_millis(8);
+
+/// The number of selections remembered for each view by [SelectionHistory].
+const SELECTION_HISTORY_CAPACITY: usize = 50;
+
+/// An outdated syntax tree that is updated in the background.
+#[derive(Debug)]
+struct SyntaxUpdate {
+    /// The text the syntax tree was parsed from.
+    source: Rope,
+    /// The changes that haven't been parsed yet, starting from `source` or from the text of the
+    /// running parse.
+    changes: ChangeSet,
+    /// Whether a parse is running.
+    parsing: bool,
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum Mode {
     Normal = 0,
@@ -116,8 +144,11 @@ pub struct DocumentSavedEvent {
     pub revision: usize,
     pub save_time: SystemTime,
     pub doc_id: DocumentId,
-    pub path: PathBuf,
+    /// The path the document was written to, `None` for remote documents.
+    pub path: Option<PathBuf>,
     pub text: Rope,
+    /// The encoding the document was written with.
+    pub encoding: &'static Encoding,
 }
 
 pub type DocumentSavedEventResult = Result<DocumentSavedEvent, anyhow::Error>;
@@ -134,6 +165,8 @@ pub struct SavePoint {
 pub enum DocumentOpenError {
     #[error("path must be a regular file, symlink, or directory")]
     IrregularFile,
+    #[error("remote files are read in the background, open them with :open")]
+    RemoteFile,
     #[error(transparent)]
     IoError(#[from] io::Error),
 }
@@ -151,6 +184,8 @@ pub struct Document {
     pub(crate) inlay_hints: HashMap<ViewId, DocumentInlayHints>,
     /// Jump label overlays for each view.
     pub(crate) jump_labels: HashMap<ViewId, Vec<Overlay>>,
+    /// Folded ranges for each view.
+    pub(crate) folds: HashMap<ViewId, FoldMap>,
     /// LSP document highlights for each view, stored as char ranges.
     pub(crate) document_highlights: HashMap<ViewId, DocumentHighlights>,
     /// Set to `true` when the document is updated, reset to `false` on the next inlay hints
@@ -160,6 +195,9 @@ pub struct Document {
     path: Option<PathBuf>,
     relative_path: OnceCell<Option<PathBuf>>,
     encoding: &'static encoding::Encoding,
+    /// The encoding of the next write, which becomes the encoding of the document once the
+    /// write succeeded.
+    write_encoding: Option<&'static encoding::Encoding>,
     has_bom: bool,
 
     pub restore_cursor: bool,
@@ -172,6 +210,11 @@ pub struct Document {
     pub line_ending: LineEnding,
 
     pub syntax: Option<Syntax>,
+    /// Set while the syntax tree is outdated and updated in the background.
+    syntax_update: Option<SyntaxUpdate>,
+    /// Whether the syntax tree is updated in the background, see
+    /// [BACKGROUND_PARSE_THRESHOLD].
+    background_parse: bool,
     /// Corresponding language scope name. Usually `source.<lang>`.
     pub language: Option<Arc<LanguageConfiguration>>,
 
@@ -201,11 +244,29 @@ pub struct Document {
 
     diff_handle: Option<DiffHandle>,
     version_control_head: Option<Arc<ArcSwap<Box<str>>>>,
+    /// The blame of the file at `HEAD`, used for inline blame.
+    blame: Option<Arc<FileBlame>>,
 
     // when document was used for most-recent-used buffer picker
     pub focused_at: std::time::Instant,
 
-    pub readonly: bool,
+    /// Whether the user can't write the file, see [Document::detect_readonly].
+    readonly_file: bool,
+    /// Whether the document can't be edited as set by [Document::set_readonly], which takes
+    /// precedence over `readonly_file`.
+    readonly: Option<bool>,
+    /// Whether the file is larger than [Config::large_file_threshold]. Large files are opened
+    /// without detecting their language, so that tree-sitter and language servers stay
+    /// disabled, and without soft-wrap. The size is checked again by [Document::reload].
+    pub large_file: bool,
+    /// Pinned buffers are listed first in the bufferline and aren't closed by
+    /// `:buffer-close-others` and `:buffer-close-all`.
+    pub pinned: bool,
+    /// Whether merge conflict markers were found when the file was loaded. The conflicts are
+    /// only highlighted while this is set so that other files aren't scanned for markers.
+    pub has_merge_conflicts: bool,
+    /// The remote file of a document opened from a URL, see [crate::remote].
+    pub remote: Option<RemoteFile>,
 
     pub previous_diagnostic_ids: HashMap<LanguageServerId, String>,
 
@@ -213,6 +274,10 @@ pub struct Document {
     pub color_swatches: Option<DocumentColorSwatches>,
     /// Cached LSP document links for navigation (e.g. goto_file).
     pub document_links: Vec<DocumentLink>,
+    /// Semantic tokens from `textDocument/semanticTokens`, highlighted over the syntax.
+    pub semantic_tokens: Option<DocumentSemanticTokens>,
+    /// Ghost text from `textDocument/inlineCompletion` shown after the cursor of a view.
+    pub inline_completion: Option<InlineCompletion>,
     // NOTE: ideally this would live on the handler for color swatches. This is blocked on a
     // large refactor that would make `&mut Editor` available on the `DocumentDidChange` event.
     pub color_swatch_controller: TaskController,
@@ -220,6 +285,8 @@ pub struct Document {
     pub document_highlight_controllers: HashMap<ViewId, TaskController>,
     pub pull_diagnostic_controller: TaskController,
     pub document_link_controller: TaskController,
+    pub semantic_tokens_controller: TaskController,
+    pub inline_completion_controller: TaskController,
 
     // NOTE: this field should eventually go away - we should use the Editor's syn_loader instead
     // of storing a copy on every doc. Then we can remove the surrounding `Arc` and use the
@@ -249,6 +316,19 @@ pub struct DocumentLink {
     pub language_server_id: LanguageServerId,
 }
 
+/// The semantic tokens of a document provided by a language server.
+#[derive(Debug, Clone)]
+pub struct DocumentSemanticTokens {
+    pub language_server_id: LanguageServerId,
+    /// The result id of the last response, used to request only the changes of the tokens.
+    pub result_id: Option<String>,
+    /// The tokens as sent by the language server, changes are applied to them.
+    pub data: Vec<lsp::SemanticToken>,
+    /// The char ranges of the tokens with the scope they are highlighted with. The ranges are
+    /// sorted and do not overlap.
+    pub highlights: Vec<(std::ops::Range<usize>, &'static str)>,
+}
+
 /// Inlay hints for a single `(Document, View)` combo.
 ///
 /// There are `*_inlay_hints` field for each kind of hints an LSP can send since we offer the
@@ -286,6 +366,10 @@ pub struct DocumentInlayHints {
     /// added first, then the regular inlay hints, then the `after` padding.
     pub padding_before_inlay_hints: Vec<InlineAnnotation>,
     pub padding_after_inlay_hints: Vec<InlineAnnotation>,
+
+    /// The displayed hints as sent by the language server with their char index, kept to show
+    /// (and resolve) their tooltips.
+    pub lsp_inlay_hints: Vec<(usize, lsp::InlayHint)>,
 }
 
 impl DocumentInlayHints {
@@ -298,6 +382,7 @@ pub fn empty_with_id(id: DocumentInlayHintsId) -> Self {
             other_inlay_hints: Vec::new(),
             padding_before_inlay_hints: Vec::new(),
             padding_after_inlay_hints: Vec::new(),
+            lsp_inlay_hints: Vec::new(),
         }
     }
 }
@@ -727,6 +812,7 @@ pub fn from(
             path: None,
             relative_path: OnceCell::new(),
             encoding,
+            write_encoding: None,
             has_bom,
             text,
             selections: HashMap::default(),
@@ -738,6 +824,8 @@ pub fn from(
             line_ending,
             restore_cursor: false,
             syntax: None,
+            syntax_update: None,
+            background_parse: false,
             language: None,
             changes,
             old_state,
@@ -752,18 +840,29 @@ pub fn from(
             diff_handle: None,
             config,
             version_control_head: None,
+            blame: None,
             focused_at: std::time::Instant::now(),
-            readonly: false,
+            readonly_file: false,
+            readonly: None,
+            large_file: false,
+            has_merge_conflicts: false,
+            pinned: false,
+            remote: None,
             jump_labels: HashMap::new(),
+            folds: HashMap::new(),
             document_highlights: HashMap::new(),
             color_swatches: None,
             document_links: Vec::new(),
+            semantic_tokens: None,
             color_swatch_controller: TaskController::new(),
             document_highlight_controllers: HashMap::new(),
             syn_loader,
             previous_diagnostic_ids: HashMap::new(),
             pull_diagnostic_controller: TaskController::new(),
             document_link_controller: TaskController::new(),
+            semantic_tokens_controller: TaskController::new(),
+            inline_completion: None,
+            inline_completion_controller: TaskController::new(),
         }
     }
 
@@ -798,6 +897,10 @@ pub fn open(
         };
         encoding = encoding.or(editor_config.encoding);
 
+        let large_file = path
+            .metadata()
+            .is_ok_and(|metadata| metadata.len() > config.load().large_file_threshold);
+
         // Open the file if it exists, otherwise assume it is a new file (and thus empty).
         let (rope, encoding, has_bom) = if path.exists() {
             let mut file = std::fs::File::open(path)?;
@@ -815,64 +918,90 @@ pub fn open(
 
         // set the path and try detecting the language
         doc.set_path(Some(path));
-        if detect_language {
+        doc.large_file = large_file;
+        if detect_language && !large_file {
             doc.detect_language(&loader);
         }
 
         doc.editor_config = editor_config;
         doc.detect_indent_and_line_ending();
+        if !large_file {
+            doc.detect_merge_conflicts();
+        }
 
         Ok(doc)
     }
 
-    /// The same as [`format`], but only returns formatting changes if auto-formatting
-    /// is configured.
-    pub fn auto_format(
+    /// Create a new document from a remote file and its contents, which are read in the
+    /// background with [RemoteFile::load]. The contents are `None` if the file doesn't exist yet.
+    pub fn from_remote(
+        remote: RemoteFile,
+        contents: Option<Vec<u8>>,
+        config: Arc<dyn DynAccess<Config>>,
+        syn_loader: Arc<ArcSwap<syntax::Loader>>,
+    ) -> Result<Self, DocumentOpenError> {
+        let (rope, encoding, has_bom) = match contents {
+            Some(contents) => from_reader(&mut contents.as_slice(), None)?,
+            None => {
+                let line_ending: LineEnding = config.load().default_line_ending.into();
+                (Rope::from(line_ending.as_str()), encoding::UTF_8, false)
+            }
+        };
+
+        let loader = syn_loader.load();
+        let mut doc = Self::from(rope, Some((encoding, has_bom)), config, syn_loader);
+        doc.remote = Some(remote);
+        doc.detect_language(&loader);
+        doc.detect_indent_and_line_ending();
+
+        Ok(doc)
+    }
+
+    /// Returns the changes that format `range` of this document with the external formatters
+    /// of its language, or `None` if no external formatter is configured.
+    ///
+    /// If every formatter has `range-args` the whole document is passed to the formatters
+    /// with their range arguments, otherwise only the text of `range` is formatted.
+    pub fn format_selection(
         &self,
         editor: &Editor,
+        range: Range,
     ) -> Option<BoxFuture<'static, Result<Transaction, FormatterError>>> {
-        if self.language_config()?.auto_format {
-            self.format(editor)
-        } else {
-            None
-        }
+        self.format_external(editor, Some(range))
     }
 
-    /// If supported, returns the changes that should be applied to this document in order
-    /// to format it nicely.
-    // We can't use anyhow::Result here since the output of the future has to be
-    // clonable to be used as shared future. So use a custom error type.
-    pub fn format(
+    /// Runs the external formatters of the language as a pipeline, see [`format`] and
+    /// [`format_selection`].
+    fn format_external(
         &self,
         editor: &Editor,
+        range: Option<Range>,
     ) -> Option<BoxFuture<'static, Result<Transaction, FormatterError>>> {
-        if let Some((fmt_cmd, fmt_args)) = self
-            .language_config()
-            .and_then(|c| c.formatter.as_ref())
-            .and_then(|formatter| {
-                Some((
-                    helix_stdx::env::which(&formatter.command).ok()?,
-                    &formatter.args,
-                ))
-            })
-        {
-            log::debug!(
-                "formatting '{}' with command '{}', args {fmt_args:?}",
-                self.display_name(),
-                fmt_cmd.display(),
-            );
-            use std::process::Stdio;
-            let text = self.text().clone();
-
-            let mut process = tokio::process::Command::new(&fmt_cmd);
-
-            if let Some(doc_dir) = self.path.as_ref().and_then(|path| path.parent()) {
-                process.current_dir(doc_dir);
-            }
+        let formatters = &self.language_config()?.formatter;
+        if formatters.is_empty() {
+            return None;
+        }
+        let whole_document = range.is_none()
+            || formatters
+                .iter()
+                .all(|formatter| formatter.range_args.is_some());
 
-            let args = match fmt_args
+        let mut commands = Vec::with_capacity(formatters.len());
+        for formatter in formatters {
+            let Ok(command) = helix_stdx::env::which(&formatter.command) else {
+                log::warn!("formatter '{}' not found in $PATH", formatter.command);
+                return None;
+            };
+            let range_args = formatter
+                .range_args
+                .as_ref()
+                .filter(|_| range.is_some() && whole_document);
+            let args = match formatter
+                .args
                 .iter()
+                .chain(range_args.into_iter().flatten())
                 .map(|content| expansion::expand(editor, Token::expand(content)))
+                .map(|arg| arg.map(String::from))
                 .collect::<Result<Vec<_>, _>>()
             {
                 Ok(args) => args,
@@ -881,58 +1010,75 @@ pub fn format(
                     return None;
                 }
             };
+            log::debug!(
+                "formatting '{}' with command '{}', args {args:?}",
+                self.display_name(),
+                command.display(),
+            );
+            commands.push(FormatterCommand {
+                command,
+                args,
+                mode: formatter.mode,
+            });
+        }
 
-            process
-                .args(args.iter().map(AsRef::as_ref))
-                .stdin(Stdio::piped())
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped());
-
-            let formatting_future = async move {
-                let mut process = process
-                    .spawn()
-                    .map_err(|e| FormatterError::SpawningFailed {
-                        command: fmt_cmd.to_string_lossy().into(),
-                        error: e.kind(),
-                    })?;
-
-                let mut stdin = process.stdin.take().ok_or(FormatterError::BrokenStdin)?;
-                let input_text = text.clone();
-                let input_task = tokio::spawn(async move {
-                    to_writer(&mut stdin, (encoding::UTF_8, false), &input_text).await
-                    // Note that `stdin` is dropped here, causing the pipe to close. This can
-                    // avoid a deadlock with `wait_with_output` below if the process is waiting on
-                    // stdin to close before exiting.
-                });
-                let (input_result, output_result) = tokio::join! {
-                    input_task,
-                    process.wait_with_output(),
-                };
-                let _ = input_result.map_err(|_| FormatterError::BrokenStdin)?;
-                let output = output_result.map_err(|_| FormatterError::WaitForOutputFailed)?;
-
-                if !output.status.success() {
-                    if !output.stderr.is_empty() {
-                        let err = String::from_utf8_lossy(&output.stderr).to_string();
-                        log::error!("Formatter error: {}", err);
-                        return Err(FormatterError::NonZeroExitStatus(Some(err)));
-                    }
+        let text = self.text().clone();
+        let (start, end) = match range {
+            Some(range) if !whole_document => (range.from(), range.to()),
+            _ => (0, text.len_chars()),
+        };
+        let dir = self
+            .path
+            .as_ref()
+            .and_then(|path| path.parent())
+            .map(Path::to_path_buf);
+        let extension = self
+            .path
+            .as_ref()
+            .and_then(|path| path.extension())
+            .map(ToOwned::to_owned);
+
+        let formatting_future = async move {
+            let mut formatted = Rope::from(text.slice(start..end));
+            for command in commands {
+                let output = command
+                    .run(formatted, dir.as_deref(), extension.as_deref())
+                    .await?;
+                formatted = Rope::from(output);
+            }
 
-                    return Err(FormatterError::NonZeroExitStatus(None));
-                } else if !output.stderr.is_empty() {
-                    log::debug!(
-                        "Formatter printed to stderr: {}",
-                        String::from_utf8_lossy(&output.stderr)
-                    );
-                }
+            let mut new_text = text.clone();
+            new_text.remove(start..end);
+            new_text.insert(start, &String::from(formatted));
+            Ok(helix_core::diff::compare_ropes(&text, &new_text))
+        };
+        Some(formatting_future.boxed())
+    }
 
-                let str = std::str::from_utf8(&output.stdout)
-                    .map_err(|_| FormatterError::InvalidUtf8Output)?;
+    /// The same as [`format`], but only returns formatting changes if auto-formatting
+    /// is configured.
+    pub fn auto_format(
+        &self,
+        editor: &Editor,
+    ) -> Option<BoxFuture<'static, Result<Transaction, FormatterError>>> {
+        if self.language_config()?.auto_format {
+            self.format(editor)
+        } else {
+            None
+        }
+    }
 
-                Ok(helix_core::diff::compare_ropes(&text, &Rope::from(str)))
-            };
-            return Some(formatting_future.boxed());
-        };
+    /// If supported, returns the changes that should be applied to this document in order
+    /// to format it nicely.
+    // We can't use anyhow::Result here since the output of the future has to be
+    // clonable to be used as shared future. So use a custom error type.
+    pub fn format(
+        &self,
+        editor: &Editor,
+    ) -> Option<BoxFuture<'static, Result<Transaction, FormatterError>>> {
+        if let Some(formatting_future) = self.format_external(editor, None) {
+            return Some(formatting_future);
+        }
 
         let text = self.text.clone();
         // finds first language server that supports formatting and then formats
@@ -981,16 +1127,42 @@ impl Future<Output = Result<DocumentSavedEvent, anyhow::Error>> + 'static + Send
         // futures_util::future::Ready<_>,
     }
 
+    /// Encodes the text and writes it to the remote file of the document.
+    fn save_remote(
+        &mut self,
+        remote: RemoteFile,
+        force: bool,
+    ) -> BoxFuture<'static, Result<DocumentSavedEvent, anyhow::Error>> {
+        let text = self.text().clone();
+        let current_rev = self.get_current_revision();
+        let doc_id = self.id();
+        let encoding = self.write_encoding.take().unwrap_or(self.encoding);
+        let encoding_with_bom_info = (encoding, self.has_bom);
+
+        async move {
+            let mut contents = Vec::new();
+            to_writer(&mut contents, encoding_with_bom_info, &text).await?;
+            remote.save(contents, force).await?;
+
+            Ok(DocumentSavedEvent {
+                revision: current_rev,
+                save_time: SystemTime::now(),
+                doc_id,
+                path: None,
+                text,
+                encoding,
+            })
+        }
+        .boxed()
+    }
+
     /// The `Document`'s text is encoded according to its encoding and written to the file located
     /// at its `path()`.
     fn save_impl(
         &mut self,
         path: Option<PathBuf>,
         force: bool,
-    ) -> Result<
-        impl Future<Output = Result<DocumentSavedEvent, anyhow::Error>> + 'static + Send,
-        anyhow::Error,
-    > {
+    ) -> Result<BoxFuture<'static, Result<DocumentSavedEvent, anyhow::Error>>, anyhow::Error> {
         log::debug!(
             "submitting save of doc '{:?}'",
             self.path().map(|path| path.to_string_lossy())
@@ -1003,6 +1175,9 @@ impl Future<Output = Result<DocumentSavedEvent, anyhow::Error>> + 'static + Send
         let path = match path {
             Some(path) => helix_stdx::path::canonicalize(path),
             None => {
+                if let Some(remote) = self.remote.clone().filter(|_| self.path.is_none()) {
+                    return Ok(self.save_remote(remote, force));
+                }
                 if self.path.is_none() {
                     bail!("Can't save with no path set!");
                 }
@@ -1018,7 +1193,8 @@ impl Future<Output = Result<DocumentSavedEvent, anyhow::Error>> + 'static + Send
         let doc_id = self.id();
         let atomic_save = self.config.load().atomic_save;
 
-        let encoding_with_bom_info = (self.encoding, self.has_bom);
+        let encoding = self.write_encoding.take().unwrap_or(self.encoding);
+        let encoding_with_bom_info = (encoding, self.has_bom);
         let last_saved_time = self.last_saved_time;
 
         // We encode the file according to the `Document`'s encoding.
@@ -1172,8 +1348,9 @@ impl Future<Output = Result<DocumentSavedEvent, anyhow::Error>> + 'static + Send
                 revision: current_rev,
                 save_time,
                 doc_id,
-                path,
+                path: Some(path),
                 text: text.clone(),
+                encoding,
             };
 
             for language_server in language_servers {
@@ -1188,7 +1365,7 @@ impl Future<Output = Result<DocumentSavedEvent, anyhow::Error>> + 'static + Send
             Ok(event)
         };
 
-        Ok(future)
+        Ok(future.boxed())
     }
 
     /// Detect the programming language based on the file type.
@@ -1201,8 +1378,12 @@ pub fn detect_language_config(
         &self,
         loader: &syntax::Loader,
     ) -> Option<Arc<syntax::config::LanguageConfiguration>> {
+        let path = match &self.remote {
+            Some(remote) if self.path.is_none() => remote.path(),
+            _ => self.path.as_deref()?,
+        };
         let language = loader
-            .language_for_filename(self.path.as_ref()?)
+            .language_for_filename(path)
             .or_else(|| loader.language_for_shebang(self.text().slice(..)))?;
 
         Some(loader.language(language).config().clone())
@@ -1238,6 +1419,14 @@ pub fn detect_editor_config(&mut self) {
         }
     }
 
+    /// Whether the file was written by another program since it was last read or saved.
+    pub fn is_modified_externally(&self) -> bool {
+        self.path()
+            .and_then(|path| path.metadata().ok())
+            .and_then(|metadata| metadata.modified().ok())
+            .is_some_and(|mtime| mtime > self.last_saved_time)
+    }
+
     pub fn pickup_last_saved_time(&mut self) {
         self.last_saved_time = match self.path() {
             Some(path) => match path.metadata() {
@@ -1257,10 +1446,23 @@ pub fn pickup_last_saved_time(&mut self) {
         };
     }
 
+    /// Whether the document can't be edited: transactions that change its text are rejected by
+    /// [Document::apply], and undo and redo do nothing.
+    pub fn readonly(&self) -> bool {
+        self.readonly.unwrap_or(self.readonly_file)
+    }
+
+    /// Makes the document read-only or editable again. Files the user can't write are read-only
+    /// unless this was called, see [Document::detect_readonly]. The choice is kept when the
+    /// document is reloaded.
+    pub fn set_readonly(&mut self, readonly: bool) {
+        self.readonly = Some(readonly);
+    }
+
     // Detect if the file is readonly and change the readonly field if necessary (unix only)
     pub fn detect_readonly(&mut self) {
         // Allows setting the flag for files the user cannot modify, like root files
-        self.readonly = match &self.path {
+        self.readonly_file = match &self.path {
             None => false,
             Some(p) => readonly(p),
         };
@@ -1281,23 +1483,42 @@ pub fn reload(
             },
         };
 
-        // Once we have a valid path we check if its readonly status has changed
-        self.detect_readonly();
-
         let mut file = std::fs::File::open(&path)?;
+        let large_file = file.metadata()?.len() > self.config.load().large_file_threshold;
         let (rope, ..) = from_reader(&mut file, Some(encoding))?;
 
         // Calculate the difference between the buffer and source text, and apply it.
         // This is not considered a modification of the contents of the file regardless
         // of the encoding.
         let transaction = helix_core::diff::compare_ropes(self.text(), &rope);
+        // read-only documents reject changes, so the text is replaced as if it was editable and
+        // the permissions of the file are checked again afterwards
+        let readonly = self.readonly.replace(false);
         self.apply(&transaction, view.id);
         self.append_changes_to_history(view);
+        self.readonly = readonly;
+        self.detect_readonly();
         self.reset_modified();
         self.pickup_last_saved_time();
         self.detect_indent_and_line_ending();
+        // the file may have grown past the threshold or shrunk below it in the meantime
+        if large_file != self.large_file {
+            self.large_file = large_file;
+            let loader = self.syn_loader.load();
+            if large_file {
+                self.set_language(None, &loader);
+            } else {
+                self.detect_language(&loader);
+            }
+        }
+        if !self.large_file {
+            self.detect_merge_conflicts();
+        }
 
-        match provider_registry.get_diff_base(&path) {
+        match provider_registry
+            .get_diff_base(&path)
+            .filter(|_| !self.large_file)
+        {
             Some(diff_base) => self.set_diff_base(diff_base),
             None => self.diff_handle = None,
         }
@@ -1307,6 +1528,11 @@ pub fn reload(
         Ok(())
     }
 
+    /// Scans the text for merge conflict markers.
+    pub fn detect_merge_conflicts(&mut self) {
+        self.has_merge_conflicts = !merge_conflict::find_conflicts(self.text.slice(..)).is_empty();
+    }
+
     /// Sets the [`Document`]'s encoding with the encoding correspondent to `label`.
     pub fn set_encoding(&mut self, label: &str) -> Result<(), Error> {
         let encoding =
@@ -1317,6 +1543,25 @@ pub fn set_encoding(&mut self, label: &str) -> Result<(), Error> {
         Ok(())
     }
 
+    /// Sets the encoding the document is converted to by the next write. The document keeps its
+    /// encoding until the write succeeded, see [Document::set_current_encoding].
+    pub fn set_write_encoding(&mut self, label: &str) -> Result<(), Error> {
+        let encoding =
+            Encoding::for_label(label.as_bytes()).ok_or_else(|| anyhow!("unknown encoding"))?;
+        self.write_encoding = Some(encoding);
+        Ok(())
+    }
+
+    /// Discards the encoding set with [Document::set_write_encoding] if no write used it.
+    pub fn clear_write_encoding(&mut self) {
+        self.write_encoding = None;
+    }
+
+    /// Sets the encoding of the document, for example after it was written with `encoding`.
+    pub fn set_current_encoding(&mut self, encoding: &'static Encoding) {
+        self.encoding = encoding;
+    }
+
     /// Returns the [`Document`]'s current encoding.
     pub fn encoding(&self) -> &'static Encoding {
         self.encoding
@@ -1348,6 +1593,8 @@ pub fn set_language(
         loader: &syntax::Loader,
     ) {
         self.language = language_config;
+        self.syntax_update = None;
+        self.background_parse = false;
         self.syntax = self.language.as_ref().and_then(|config| {
             Syntax::new(self.text.slice(..), config.language(), loader)
                 .map_err(|err| {
@@ -1382,6 +1629,7 @@ pub fn set_selection(&mut self, view_id: ViewId, selection: Selection) {
         // TODO: use a transaction?
         self.selections
             .insert(view_id, selection.ensure_invariants(self.text().slice(..)));
+        self.reveal_selection(view_id);
         helix_event::dispatch(SelectionDidChange {
             doc: self,
             view: view_id,
@@ -1427,8 +1675,16 @@ pub fn remove_view(&mut self, view_id: ViewId) {
         self.view_data.remove(&view_id);
         self.inlay_hints.remove(&view_id);
         self.jump_labels.remove(&view_id);
+        self.folds.remove(&view_id);
         self.document_highlights.remove(&view_id);
         self.document_highlight_controllers.remove(&view_id);
+        if self
+            .inline_completion
+            .as_ref()
+            .is_some_and(|completion| completion.view_id == view_id)
+        {
+            self.inline_completion = None;
+        }
     }
 
     /// Apply a [`Transaction`] to the [`Document`] to change its text.
@@ -1476,6 +1732,9 @@ fn apply_impl(
             view_data.view_position.anchor = transaction
                 .changes()
                 .map_pos(view_data.view_position.anchor, Assoc::Before);
+            view_data
+                .selection_history
+                .apply(transaction.changes(), self.text.slice(..));
         }
 
         // generate revert to savepoint
@@ -1494,8 +1753,17 @@ fn apply_impl(
         }
 
         // update tree-sitter syntax tree
-        if let Some(syntax) = &mut self.syntax {
+        if let Some(update) = &mut self.syntax_update {
+            update.changes = mem::take(&mut update.changes).compose(changes.clone());
+        } else if self.background_parse && self.syntax.is_some() {
+            self.syntax_update = Some(SyntaxUpdate {
+                source: old_doc.clone(),
+                changes: changes.clone(),
+                parsing: false,
+            });
+        } else if let Some(syntax) = &mut self.syntax {
             let loader = self.syn_loader.load();
+            let start = Instant::now();
             if let Err(err) = syntax.update(
                 old_doc.slice(..),
                 self.text.slice(..),
@@ -1505,6 +1773,7 @@ fn apply_impl(
                 log::error!("TS parser failed, disabling TS for the current buffer: {err}");
                 self.syntax = None;
             }
+            self.background_parse = start.elapsed() > BACKGROUND_PARSE_THRESHOLD;
         }
 
         // TODO: all of that should likely just be hooks
@@ -1571,6 +1840,7 @@ fn apply_impl(
                 other_inlay_hints,
                 padding_before_inlay_hints,
                 padding_after_inlay_hints,
+                lsp_inlay_hints,
             } = text_annotation;
 
             apply_inlay_hint_changes(padding_before_inlay_hints);
@@ -1578,6 +1848,15 @@ fn apply_impl(
             apply_inlay_hint_changes(parameter_inlay_hints);
             apply_inlay_hint_changes(other_inlay_hints);
             apply_inlay_hint_changes(padding_after_inlay_hints);
+            changes.update_positions(
+                lsp_inlay_hints
+                    .iter_mut()
+                    .map(|(char_idx, _)| (char_idx, Assoc::After)),
+            );
+        }
+
+        for folds in self.folds.values_mut() {
+            folds.update(self.text.slice(..), changes);
         }
 
         for highlights in self.document_highlights.values_mut() {
@@ -1616,6 +1895,7 @@ fn apply_impl(
                 view_id,
                 selection.clone().ensure_invariants(self.text.slice(..)),
             );
+            self.reveal_selection(view_id);
             helix_event::dispatch(SelectionDidChange {
                 doc: self,
                 view: view_id,
@@ -1631,6 +1911,9 @@ fn apply_inner(
         view_id: ViewId,
         emit_lsp_notification: bool,
     ) -> bool {
+        if self.readonly() && !transaction.changes().is_empty() {
+            return false;
+        }
         // store the state just before any changes are made. This allows us to undo to the
         // state just before a transaction was applied.
         if self.changes.is_empty() && !transaction.changes().is_empty() {
@@ -1663,6 +1946,9 @@ pub fn apply_temporary(&mut self, transaction: &Transaction, view_id: ViewId) ->
     }
 
     fn undo_redo_impl(&mut self, view: &mut View, undo: bool) -> bool {
+        if self.readonly() {
+            return false;
+        }
         if undo {
             self.append_changes_to_history(view);
         } else if !self.changes.is_empty() {
@@ -1748,6 +2034,9 @@ pub fn restore(&mut self, view: &mut View, savepoint: &SavePoint, emit_lsp_notif
     }
 
     fn earlier_later_impl(&mut self, view: &mut View, uk: UndoKind, earlier: bool) -> bool {
+        if self.readonly() {
+            return false;
+        }
         if earlier {
             self.append_changes_to_history(view);
         } else if !self.changes.is_empty() {
@@ -1758,6 +2047,10 @@ fn earlier_later_impl(&mut self, view: &mut View, uk: UndoKind, earlier: bool) -
         } else {
             self.history.get_mut().later(uk)
         };
+        self.apply_history_jump(view, txns)
+    }
+
+    fn apply_history_jump(&mut self, view: &mut View, txns: Vec<Transaction>) -> bool {
         let mut success = false;
         for txn in txns {
             if self.apply_impl(&txn, view.id, true) {
@@ -1783,6 +2076,22 @@ pub fn later(&mut self, view: &mut View, uk: UndoKind) -> bool {
         self.earlier_later_impl(view, uk, false)
     }
 
+    /// Jump to the given revision of the history, which may be on another branch of the undo
+    /// tree.
+    pub fn jump_to_revision(&mut self, view: &mut View, revision: usize) -> bool {
+        self.append_changes_to_history(view);
+        let txns = self.history.get_mut().jump_to_revision(revision);
+        self.apply_history_jump(view, txns)
+    }
+
+    /// Returns a summary of every revision of the history.
+    pub fn revisions(&self) -> Vec<RevisionSummary> {
+        let history = self.history.take();
+        let revisions = history.revisions();
+        self.history.set(history);
+        revisions
+    }
+
     /// Commit pending changes to history
     pub fn append_changes_to_history(&mut self, view: &mut View) {
         if self.changes.is_empty() {
@@ -1990,10 +2299,83 @@ pub fn set_version_control_head(
         self.version_control_head = version_control_head;
     }
 
+    pub fn set_blame(&mut self, blame: Option<Arc<FileBlame>>) {
+        self.blame = blame;
+    }
+
+    /// Returns the blame of `line`, if the blame of the document was loaded.
+    pub fn line_blame(&self, line: usize) -> Option<LineBlame<'_>> {
+        let blame = self.blame.as_ref()?;
+        let diff = self.diff_handle.as_ref()?.load();
+        blame.line_blame(&diff, line as u32)
+    }
+
     #[inline]
-    /// Tree-sitter AST tree
+    /// Tree-sitter AST tree. While the tree is updated in the background this is the outdated
+    /// tree, unless the text became shorter than the text the tree was parsed from and the nodes
+    /// could end past the end of the text. Use [Self::highlight_syntax] for highlighting.
     pub fn syntax(&self) -> Option<&Syntax> {
-        self.syntax.as_ref()
+        match &self.syntax_update {
+            Some(update) if update.source.len_bytes() > self.text.len_bytes() => None,
+            _ => self.syntax.as_ref(),
+        }
+    }
+
+    /// The syntax tree to highlight the document with and the text it was parsed from. While
+    /// the tree is updated in the background the outdated tree and its text are returned.
+    pub fn highlight_syntax(&self) -> Option<(&Syntax, RopeSlice<'_>)> {
+        let syntax = self.syntax.as_ref()?;
+        let source = self
+            .syntax_update
+            .as_ref()
+            .map_or(&self.text, |update| &update.source);
+        Some((syntax, source.slice(..)))
+    }
+
+    /// Whether the syntax tree is outdated and can be updated with [Self::start_syntax_update].
+    pub fn syntax_outdated(&self) -> bool {
+        self.syntax_update
+            .as_ref()
+            .is_some_and(|update| !update.parsing)
+    }
+
+    /// Starts updating the outdated syntax tree in the background. Returns the tree, the text it
+    /// was parsed from, the current text and the changes in between, to be passed to
+    /// [Syntax::update] and then [Self::finish_syntax_update].
+    pub fn start_syntax_update(&mut self) -> Option<(Syntax, Rope, Rope, ChangeSet)> {
+        let update = self
+            .syntax_update
+            .as_mut()
+            .filter(|update| !update.parsing)?;
+        let syntax = self.syntax.clone()?;
+        update.parsing = true;
+        let changes = mem::replace(&mut update.changes, ChangeSet::new(self.text.slice(..)));
+        Some((syntax, update.source.clone(), self.text.clone(), changes))
+    }
+
+    /// Replaces the syntax tree with the one parsed from `text` in the background, or disables
+    /// the syntax tree if the parse failed. Edits are parsed on the main thread again once a
+    /// parse took less than [BACKGROUND_PARSE_THRESHOLD]. Returns whether the tree is still
+    /// outdated because the document changed during the parse.
+    pub fn finish_syntax_update(
+        &mut self,
+        syntax: Option<Syntax>,
+        text: Rope,
+        parse_time: Duration,
+    ) -> bool {
+        let Some(update) = self.syntax_update.as_mut().filter(|update| update.parsing) else {
+            // the language changed during the parse
+            return false;
+        };
+        self.background_parse = parse_time > BACKGROUND_PARSE_THRESHOLD;
+        if syntax.is_none() || update.changes.is_empty() {
+            self.syntax_update = None;
+        } else {
+            update.source = text;
+            update.parsing = false;
+        }
+        self.syntax = syntax;
+        self.syntax_update.is_some()
     }
 
     /// The width that the tab character is rendered at
@@ -2079,6 +2461,74 @@ pub fn view_offset(&self, view_id: ViewId) -> ViewPosition {
         self.view_data(view_id).view_position
     }
 
+    /// Returns the first lines of the functions and classes that contain the top of the view,
+    /// which `sticky-context` shows over the first rows of the view, see the `context.scm`
+    /// queries. The context covers at most a third of the `height` of the view.
+    pub fn sticky_context(&self, view_id: ViewId, height: u16) -> Vec<usize> {
+        let max_lines = (height / 3) as usize;
+        if !self.config.load().sticky_context || max_lines == 0 {
+            return Vec::new();
+        }
+        let (Some(syntax), Some(view_offset)) = (self.syntax(), self.get_view_offset(view_id))
+        else {
+            return Vec::new();
+        };
+        let loader = self.syn_loader.load();
+        let text = self.text().slice(..);
+        let top_line = text.char_to_line(view_offset.anchor.min(text.len_chars()));
+
+        let lines = syntax.context_lines(text, top_line, &loader);
+        if lines.is_empty() {
+            return lines;
+        }
+        // the context hides the first lines of the view, show the context of the first line
+        // below it instead
+        let first_visible_line = (top_line + lines.len().min(max_lines)).min(text.len_lines() - 1);
+        let mut lines = syntax.context_lines(text, first_visible_line, &loader);
+        // drop the outermost context if there is not enough space
+        lines.drain(..lines.len().saturating_sub(max_lines));
+        lines
+    }
+
+    /// Records the selection of a view in its selection history, unless it is the selection
+    /// that the history is at.
+    pub fn record_selection(&mut self, view_id: ViewId) {
+        let Some(selection) = self.selections.get(&view_id).cloned() else {
+            return;
+        };
+        self.view_data_mut(view_id)
+            .selection_history
+            .push(selection);
+    }
+
+    /// Restores the selection of a view from `count` steps back in its selection history,
+    /// without changing the text. Returns `false` if there is no older selection.
+    pub fn select_prev(&mut self, view_id: ViewId, count: usize) -> bool {
+        self.record_selection(view_id);
+        let history = &mut self.view_data_mut(view_id).selection_history;
+        match history.backward(count).cloned() {
+            Some(selection) => {
+                self.set_selection(view_id, selection);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Restores the selection of a view from `count` steps forward in its selection history,
+    /// undoing [Document::select_prev]. Returns `false` if there is no newer selection.
+    pub fn select_next(&mut self, view_id: ViewId, count: usize) -> bool {
+        self.record_selection(view_id);
+        let history = &mut self.view_data_mut(view_id).selection_history;
+        match history.forward(count).cloned() {
+            Some(selection) => {
+                self.set_selection(view_id, selection);
+                true
+            }
+            None => false,
+        }
+    }
+
     pub fn set_view_offset(&mut self, view_id: ViewId, new_offset: ViewPosition) {
         self.view_data_mut(view_id).view_position = new_offset;
     }
@@ -2094,8 +2544,11 @@ pub fn relative_path(&self) -> Option<&Path> {
     }
 
     pub fn display_name(&self) -> Cow<'_, str> {
-        self.relative_path()
-            .map_or_else(|| SCRATCH_BUFFER_NAME.into(), |path| path.to_string_lossy())
+        match (self.relative_path(), &self.remote) {
+            (Some(path), _) => path.to_string_lossy(),
+            (None, Some(remote)) => remote.url().into(),
+            (None, None) => SCRATCH_BUFFER_NAME.into(),
+        }
     }
 
     // transact(Fn) ?
@@ -2340,10 +2793,11 @@ pub fn text_format(&self, mut viewport_width: u16, theme: Option<&Theme>) -> Tex
             .language
             .as_ref()
             .and_then(|config| config.soft_wrap.as_ref());
-        let enable_soft_wrap = language_soft_wrap
-            .and_then(|soft_wrap| soft_wrap.enable)
-            .or(editor_soft_wrap.enable)
-            .unwrap_or(false);
+        let enable_soft_wrap = !self.large_file
+            && language_soft_wrap
+                .and_then(|soft_wrap| soft_wrap.enable)
+                .or(editor_soft_wrap.enable)
+                .unwrap_or(false);
         let max_wrap = language_soft_wrap
             .and_then(|soft_wrap| soft_wrap.max_wrap)
             .or(config.soft_wrap.max_wrap)
@@ -2385,6 +2839,45 @@ pub fn remove_jump_labels(&mut self, view_id: ViewId) {
         self.jump_labels.remove(&view_id);
     }
 
+    pub fn folds(&self, view_id: ViewId) -> Option<&FoldMap> {
+        self.folds.get(&view_id)
+    }
+
+    /// Folds the lines `header + 1..=last` below the `header` line for the given view.
+    /// Returns `false` if nothing was folded.
+    pub fn fold_lines(&mut self, view_id: ViewId, header: usize, last: usize) -> bool {
+        self.folds
+            .entry(view_id)
+            .or_default()
+            .fold_lines(self.text.slice(..), header, last)
+    }
+
+    /// Removes the fold of the given view which contains `line` or whose header is `line`.
+    /// Returns `false` if there was no such fold.
+    pub fn unfold_line(&mut self, view_id: ViewId, line: usize) -> bool {
+        let text = self.text.slice(..);
+        self.folds
+            .get_mut(&view_id)
+            .is_some_and(|folds| folds.unfold_line(text, line))
+    }
+
+    /// Removes all folds of the given view.
+    pub fn unfold_all(&mut self, view_id: ViewId) {
+        self.folds.remove(&view_id);
+    }
+
+    /// Opens all folds that hide a cursor of the view's selection so that the
+    /// cursors are always visible.
+    fn reveal_selection(&mut self, view_id: ViewId) {
+        let Some(folds) = self.folds.get_mut(&view_id) else {
+            return;
+        };
+        let text = self.text.slice(..);
+        if let Some(selection) = self.selections.get(&view_id) {
+            folds.reveal(text, selection.iter().map(|range| range.cursor(text)));
+        }
+    }
+
     pub fn set_document_highlights(
         &mut self,
         view_id: ViewId,
@@ -2438,6 +2931,163 @@ pub fn has_language_server_with_feature(&self, feature: LanguageServerFeature) -
 #[derive(Debug, Default)]
 pub struct ViewData {
     view_position: ViewPosition,
+    selection_history: SelectionHistory,
+}
+
+/// The selections of a view, so that selection changes can be undone without undoing changes
+/// to the text. The selections are mapped through the changes made to the document.
+#[derive(Debug, Default)]
+struct SelectionHistory {
+    selections: VecDeque<Selection>,
+    /// The index of the selection that the history is at.
+    current: usize,
+}
+
+impl SelectionHistory {
+    /// Records `selection` after the current selection, dropping the selections that were
+    /// undone.
+    fn push(&mut self, selection: Selection) {
+        if self.selections.get(self.current) == Some(&selection) {
+            return;
+        }
+        self.selections.truncate(self.current + 1);
+        if self.selections.len() >= SELECTION_HISTORY_CAPACITY {
+            self.selections.pop_front();
+        }
+        self.selections.push_back(selection);
+        self.current = self.selections.len() - 1;
+    }
+
+    fn backward(&mut self, count: usize) -> Option<&Selection> {
+        if self.current == 0 {
+            return None;
+        }
+        self.current = self.current.saturating_sub(count);
+        self.selections.get(self.current)
+    }
+
+    fn forward(&mut self, count: usize) -> Option<&Selection> {
+        if self.current + 1 >= self.selections.len() {
+            return None;
+        }
+        self.current = (self.current + count).min(self.selections.len() - 1);
+        self.selections.get(self.current)
+    }
+
+    fn apply(&mut self, changes: &ChangeSet, text: RopeSlice) {
+        for selection in &mut self.selections {
+            *selection = selection.clone().map(changes).ensure_invariants(text);
+        }
+    }
+}
+
+/// A stage of an external formatter pipeline with its arguments already expanded.
+struct FormatterCommand {
+    command: PathBuf,
+    args: Vec<String>,
+    mode: FormatterMode,
+}
+
+impl FormatterCommand {
+    /// Formats `text` and returns the formatted text. In temp-file mode the temporary file is
+    /// created in `dir` so that the formatter picks up the configuration of the project.
+    async fn run(
+        self,
+        text: Rope,
+        dir: Option<&Path>,
+        extension: Option<&std::ffi::OsStr>,
+    ) -> Result<String, FormatterError> {
+        use std::process::Stdio;
+
+        let mut process = tokio::process::Command::new(&self.command);
+        if let Some(dir) = dir {
+            process.current_dir(dir);
+        }
+        process
+            .args(&self.args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let spawning_failed = |err: io::Error| FormatterError::SpawningFailed {
+            command: self.command.to_string_lossy().into(),
+            error: err.kind(),
+        };
+
+        match self.mode {
+            FormatterMode::Stdin => {
+                let mut process = process
+                    .stdin(Stdio::piped())
+                    .spawn()
+                    .map_err(spawning_failed)?;
+
+                let mut stdin = process.stdin.take().ok_or(FormatterError::BrokenStdin)?;
+                let input_task = tokio::spawn(async move {
+                    to_writer(&mut stdin, (encoding::UTF_8, false), &text).await
+                    // Note that `stdin` is dropped here, causing the pipe to close. This can
+                    // avoid a deadlock with `wait_with_output` below if the process is waiting on
+                    // stdin to close before exiting.
+                });
+                let (input_result, output_result) = tokio::join! {
+                    input_task,
+                    process.wait_with_output(),
+                };
+                let _ = input_result.map_err(|_| FormatterError::BrokenStdin)?;
+                let output = output_result.map_err(|_| FormatterError::WaitForOutputFailed)?;
+                check_formatter_output(&output)?;
+
+                String::from_utf8(output.stdout).map_err(|_| FormatterError::InvalidUtf8Output)
+            }
+            FormatterMode::TempFile => {
+                let mut suffix = std::ffi::OsString::new();
+                if let Some(extension) = extension {
+                    suffix.push(".");
+                    suffix.push(extension);
+                }
+                let mut builder = tempfile::Builder::new();
+                builder.prefix(".helix-format-").suffix(&suffix);
+                let file = match dir.and_then(|dir| builder.tempfile_in(dir).ok()) {
+                    Some(file) => file,
+                    None => builder
+                        .tempfile()
+                        .map_err(|err| FormatterError::TempFileFailed(err.kind()))?,
+                };
+
+                tokio::fs::write(file.path(), text.to_string())
+                    .await
+                    .map_err(|err| FormatterError::TempFileFailed(err.kind()))?;
+
+                let output = process
+                    .arg(file.path())
+                    .stdin(Stdio::null())
+                    .output()
+                    .await
+                    .map_err(spawning_failed)?;
+                check_formatter_output(&output)?;
+
+                let formatted = tokio::fs::read(file.path())
+                    .await
+                    .map_err(|err| FormatterError::TempFileFailed(err.kind()))?;
+                String::from_utf8(formatted).map_err(|_| FormatterError::InvalidUtf8Output)
+            }
+        }
+    }
+}
+
+fn check_formatter_output(output: &std::process::Output) -> Result<(), FormatterError> {
+    if !output.status.success() {
+        if !output.stderr.is_empty() {
+            let err = String::from_utf8_lossy(&output.stderr).to_string();
+            log::error!("Formatter error: {}", err);
+            return Err(FormatterError::NonZeroExitStatus(Some(err)));
+        }
+
+        return Err(FormatterError::NonZeroExitStatus(None));
+    } else if !output.stderr.is_empty() {
+        log::debug!(
+            "Formatter printed to stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(())
 }
 
 #[derive(Clone, Debug)]
@@ -2447,6 +3097,7 @@ pub enum FormatterError {
         error: std::io::ErrorKind,
     },
     BrokenStdin,
+    TempFileFailed(std::io::ErrorKind),
     WaitForOutputFailed,
     InvalidUtf8Output,
     NonZeroExitStatus(Option<String>),
@@ -2461,6 +3112,9 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
                 write!(f, "Failed to spawn formatter {}: {:?}", command, error)
             }
             Self::BrokenStdin => write!(f, "Could not write to formatter stdin"),
+            Self::TempFileFailed(error) => {
+                write!(f, "Failed to use formatter temporary file: {:?}", error)
+            }
             Self::WaitForOutputFailed => write!(f, "Waiting for formatter output failed"),
             Self::InvalidUtf8Output => write!(f, "Invalid UTF-8 formatter output"),
             Self::NonZeroExitStatus(Some(output)) => write!(f, "Formatter error: {}", output),
@@ -2635,6 +3289,32 @@ fn changeset_to_changes() {
         );
     }
 
+    #[test]
+    fn readonly_rejects_changes() {
+        let mut doc = Document::from(
+            Rope::from("hello"),
+            None,
+            Arc::new(ArcSwap::new(Arc::new(Config::default()))),
+            Arc::new(ArcSwap::from_pointee(syntax::Loader::default())),
+        );
+        let view = ViewId::default();
+        doc.set_selection(view, Selection::single(5, 5));
+        doc.set_readonly(true);
+
+        let insert = Transaction::insert(doc.text(), doc.selection(view), " world".into());
+        assert!(!doc.apply(&insert, view));
+        assert_eq!(doc.text().to_string(), "hello");
+        assert!(!doc.is_modified());
+        // selections can still change
+        let select = Transaction::new(doc.text()).with_selection(Selection::single(0, 5));
+        assert!(doc.apply(&select, view));
+        assert_eq!(doc.selection(view), &Selection::single(0, 5));
+
+        doc.set_readonly(false);
+        assert!(doc.apply(&insert, view));
+        assert_eq!(doc.text().to_string(), "hello world");
+    }
+
     #[test]
     fn test_line_ending() {
         assert_eq!(
@@ -2648,6 +3328,68 @@ fn test_line_ending() {
         );
     }
 
+    #[test]
+    fn background_syntax_update() {
+        let loader = Arc::new(ArcSwap::from_pointee(
+            helix_core::config::default_lang_loader(),
+        ));
+        let mut doc = Document::from(
+            Rope::from("fn a() {}\n"),
+            None,
+            Arc::new(ArcSwap::new(Arc::new(Config::default()))),
+            loader.clone(),
+        );
+        doc.set_language_by_language_id("rust", &loader.load())
+            .unwrap();
+        let view = ViewId::default();
+        doc.set_selection(view, Selection::point(0));
+        let prepend = |doc: &mut Document, text: &str| {
+            let transaction =
+                Transaction::change(doc.text(), [(0, 0, Some(text.into()))].into_iter());
+            doc.apply(&transaction, view);
+        };
+        let parse = |doc: &mut Document| {
+            let (mut syntax, source, text, changes) = doc.start_syntax_update().unwrap();
+            syntax
+                .update(source.slice(..), text.slice(..), &changes, &loader.load())
+                .unwrap();
+            (syntax, text)
+        };
+
+        // pretend that parsing the document is slow
+        doc.background_parse = true;
+        prepend(&mut doc, "fn b() {}\n");
+        assert!(doc.syntax_outdated());
+        // the outdated tree stays available and is highlighted against its own text
+        assert!(doc.syntax().is_some());
+        assert_eq!(doc.highlight_syntax().unwrap().1, "fn a() {}\n");
+
+        // edits during a parse are parsed once it finished
+        let (syntax, text) = parse(&mut doc);
+        assert!(!doc.syntax_outdated());
+        assert!(doc.start_syntax_update().is_none());
+        prepend(&mut doc, "fn c() {}\n");
+        assert!(doc.finish_syntax_update(Some(syntax), text, BACKGROUND_PARSE_THRESHOLD * 2));
+        assert!(doc.syntax_outdated());
+        assert_eq!(doc.highlight_syntax().unwrap().1, "fn b() {}\nfn a() {}\n");
+
+        // a fast parse switches back to parsing on each edit
+        let (syntax, text) = parse(&mut doc);
+        assert!(!doc.finish_syntax_update(Some(syntax), text, Duration::ZERO));
+        assert!(!doc.syntax_outdated());
+        assert_eq!(doc.highlight_syntax().unwrap().1, doc.text().slice(..));
+        prepend(&mut doc, "fn d() {}\n");
+        assert!(!doc.syntax_outdated());
+
+        // the outdated tree of a shorter text could have nodes past its end
+        doc.background_parse = true;
+        let transaction = Transaction::change(doc.text(), [(0, 10, None)].into_iter());
+        doc.apply(&transaction, view);
+        assert!(doc.syntax_outdated());
+        assert!(doc.syntax().is_none());
+        assert!(doc.highlight_syntax().is_some());
+    }
+
     macro_rules! decode {
         ($name:ident, $label:expr, $label_override:expr) => {
             #[test]
@@ -1,21 +1,28 @@
 use crate::{
+    animation::ScrollAnimations,
     annotations::diagnostics::{DiagnosticFilter, InlineDiagnosticsConfig},
+    buffer_history::{BufferHistory, BufferLocation},
     clipboard::ClipboardProvider,
     document::{
-        DocumentOpenError, DocumentSavedEventFuture, DocumentSavedEventResult, Mode, SavePoint,
+        DocumentOpenError, DocumentSavedEvent, DocumentSavedEventFuture, DocumentSavedEventResult,
+        Mode, SavePoint,
     },
     events::{DocumentDidClose, DocumentDidOpen, DocumentFocusLost},
     graphics::{CursorKind, Rect},
     handlers::Handlers,
     info::Info,
     input::KeyEvent,
+    marks::Marks,
+    quickfix::Quickfix,
+    recovery,
     register::Registers,
+    remote::{self, RemoteFile},
     theme::{self, Theme},
     tree::{self, Tree},
-    Document, DocumentId, View, ViewId,
+    undo_file, Document, DocumentId, View, ViewId,
 };
-use helix_event::dispatch;
-use helix_loader::workspace_trust::TrustStatus;
+use helix_event::{dispatch, TaskController};
+use helix_loader::{watcher::Watcher, workspace_trust::TrustStatus};
 use helix_vcs::DiffProviderRegistry;
 
 use futures_util::stream::select_all::SelectAll;
@@ -46,6 +53,8 @@
 use helix_core::{
     auto_pairs::AutoPairs,
     diagnostic::DiagnosticProvider,
+    snippets::SnippetLibrary,
+    spelling::Dictionary,
     syntax::{
         self,
         config::{AutoPairConfig, IndentationHeuristic, LanguageServerFeature, SoftWrap},
@@ -65,6 +74,27 @@
 
 pub const DIR_STACK_CAP: usize = 10;
 pub const DEFAULT_AUTO_SAVE_DELAY: u64 = 3000;
+/// How often the files of open documents are checked for external changes. Polling only a
+/// handful of open files is cheap and behaves the same on all platforms and file systems,
+/// including network file systems that don't send notifications.
+const DOCUMENT_WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Merges the `[editor]` tables of directory config files into `config`. The files are ordered
+/// from the outermost to the innermost directory so that closer files take precedence.
+fn load_directory_config(config: &Config, files: &[PathBuf]) -> anyhow::Result<Config> {
+    let mut merged = toml::Value::try_from(config)?;
+    for file in files {
+        let contents = std::fs::read_to_string(file)?;
+        let value: toml::Value = toml::from_str(&contents)
+            .map_err(|err| anyhow!("{}: {}", file.display(), err.message()))?;
+        if let Some(editor) = value.get("editor") {
+            merged = helix_loader::merge_toml_values(merged, editor.clone(), 3);
+        }
+    }
+    merged
+        .try_into()
+        .map_err(|err: toml::de::Error| anyhow!("{}", err.message()))
+}
 
 fn deserialize_duration_millis<'de, D>(deserializer: D) -> Result<Duration, D::Error>
 where
@@ -302,6 +332,16 @@ pub struct Config {
     pub mouse: bool,
     /// Which register to use for mouse yank.
     pub mouse_yank_register: char,
+    /// Show the hover information of the language server for the symbol under the mouse.
+    /// Defaults to false.
+    pub mouse_hover: bool,
+    /// Time in milliseconds the mouse has to rest on a symbol before its hover information is
+    /// shown. Defaults to 500ms.
+    #[serde(
+        serialize_with = "serialize_duration_millis",
+        deserialize_with = "deserialize_duration_millis"
+    )]
+    pub mouse_hover_delay: Duration,
     /// Shell to use for shell commands. Defaults to ["cmd", "/C"] on Windows and ["sh", "-c"] otherwise.
     pub shell: Vec<String>,
     /// Line number mode.
@@ -318,6 +358,9 @@ pub struct Config {
     /// etc. Optionally, this can be a list of 2-tuples to specify a
     /// global list of characters to pair. Defaults to true.
     pub auto_pairs: AutoPairConfig,
+    /// Automatically close the tags of markup languages after typing `>` and rename closing
+    /// tags along with their opening tags. Defaults to false.
+    pub auto_close_tags: bool,
     /// Automatic auto-completion, automatically pop up without user trigger. Defaults to true.
     pub auto_completion: bool,
     /// Enable filepath completion.
@@ -328,6 +371,14 @@ pub struct Config {
     /// Configures completion of words from open buffers.
     /// Defaults to enabled with a trigger length of 7.
     pub word_completion: WordCompletion,
+    /// Words that are expanded in insert mode when a word boundary is typed after them.
+    pub abbreviations: Abbreviations,
+    /// Configures spell checking of comments, strings and markup.
+    pub spell_check: SpellCheck,
+    /// Configures the git blame shown at the end of the cursor line.
+    pub inline_blame: InlineBlameConfig,
+    /// Configures the overview column at the right edge of the views.
+    pub scrollbar: ScrollbarConfig,
     /// Automatic formatting on save. Defaults to true.
     pub auto_format: bool,
     /// Default register used for yank/paste. Defaults to '"'
@@ -346,6 +397,17 @@ pub struct Config {
         deserialize_with = "deserialize_duration_millis"
     )]
     pub idle_timeout: Duration,
+    /// Time in milliseconds over which views scroll to a new position that is more than a few
+    /// lines away, like after a page scroll or a goto. Scrolling is immediate with 0. Defaults
+    /// to 0.
+    #[serde(
+        serialize_with = "serialize_duration_millis",
+        deserialize_with = "deserialize_duration_millis"
+    )]
+    pub smooth_scroll: Duration,
+    /// Open the files picked in pickers as a preview buffer, which the next file picked replaces
+    /// until it is modified. Defaults to false.
+    pub preview_buffers: bool,
     /// Time in milliseconds after typing a word character before auto completions
     /// are shown, set to 5 for instant. Defaults to 250ms.
     #[serde(
@@ -353,6 +415,13 @@ pub struct Config {
         deserialize_with = "deserialize_duration_millis"
     )]
     pub completion_timeout: Duration,
+    /// Time in milliseconds to wait for the next key of an incomplete key sequence in insert
+    /// mode before the pending keys are inserted. Defaults to 1000ms.
+    #[serde(
+        serialize_with = "serialize_duration_millis",
+        deserialize_with = "deserialize_duration_millis"
+    )]
+    pub chord_timeout: Duration,
     /// Whether to insert the completion suggestion on hover. Defaults to true.
     pub preview_completion_insert: bool,
     pub completion_trigger_len: u8,
@@ -370,8 +439,9 @@ pub struct Config {
     pub statusline: StatusLineConfig,
     /// Shape for cursor in each mode
     pub cursor_shape: CursorShapeConfig,
-    /// Set to `true` to override automatic detection of terminal truecolor support in the event of a false negative. Defaults to `false`.
-    pub true_color: bool,
+    /// Whether colors are sent to the terminal as 24-bit RGB values. Defaults to `auto`.
+    #[serde(deserialize_with = "deserialize_true_color")]
+    pub true_color: TrueColorConfig,
     /// Set to `true` to override automatic detection of terminal undercurl support in the event of a false negative. Defaults to `false`.
     pub undercurl: bool,
     /// Search configuration.
@@ -385,6 +455,8 @@ pub struct Config {
     pub whitespace: WhitespaceConfig,
     /// Persistently display open buffers along the top
     pub bufferline: BufferLine,
+    /// Whether to show the position of each buffer in the bufferline. Defaults to `false`.
+    pub bufferline_numbers: bool,
     /// Vertical indent width guides.
     pub indent_guides: IndentGuidesConfig,
     /// Whether to color modes with different colors. Defaults to `false`.
@@ -429,8 +501,26 @@ pub struct Config {
     pub editor_config: bool,
     /// Whether to render rainbow colors for matching brackets. Defaults to `false`.
     pub rainbow_brackets: bool,
+    /// Whether to pin the first lines of the enclosing functions and classes to the top of
+    /// the view when they are scrolled past. Defaults to `false`.
+    pub sticky_context: bool,
+    /// Whether to save the undo history of files when they are closed and restore it when they
+    /// are opened again. Defaults to `false`.
+    pub persistent_undo: bool,
+    /// The size in bytes above which files are opened in large file mode, see
+    /// [Document::large_file]. Defaults to 64 MiB.
+    pub large_file_threshold: u64,
+    /// Whether to reload documents when their files are changed by another program. Documents
+    /// with unsaved changes are not reloaded without confirmation. The files are polled every
+    /// [DOCUMENT_WATCH_INTERVAL], see [helix_loader::watcher]. Defaults to `true`.
+    pub auto_reload: bool,
+    /// Whether to write the text of modified documents to recovery files while editing, see
+    /// [crate::recovery]. Defaults to `true`.
+    pub recovery: bool,
     /// Whether to enable Kitty Keyboard Protocol
     pub kitty_keyboard_protocol: KittyKeyboardProtocolConfig,
+    /// The protocol used to display images in previews and documentation
+    pub image_protocol: ImageProtocolConfig,
     pub buffer_picker: BufferPickerConfig,
     /// Whether to implicitly trust every workspace or not
     pub insecure: bool,
@@ -471,6 +561,49 @@ pub enum KittyKeyboardProtocolConfig {
     Enabled,
 }
 
+/// The protocol used to display images in the terminal.
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum ImageProtocolConfig {
+    /// Detect the protocol from the environment.
+    #[default]
+    Auto,
+    Disabled,
+    Kitty,
+    Iterm2,
+}
+
+/// Whether the terminal supports 24-bit colors. Without true color support, colors are
+/// converted to the nearest color of the 256 color palette.
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum TrueColorConfig {
+    /// Detect true color support from the terminal and the environment.
+    #[default]
+    Auto,
+    Enabled,
+    Disabled,
+}
+
+/// Accepts the boolean values of older versions as well, where `false` meant `auto`.
+fn deserialize_true_color<'de, D>(deserializer: D) -> Result<TrueColorConfig, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum TrueColorToml {
+        Bool(bool),
+        Config(TrueColorConfig),
+    }
+
+    match TrueColorToml::deserialize(deserializer)? {
+        TrueColorToml::Bool(true) => Ok(TrueColorConfig::Enabled),
+        TrueColorToml::Bool(false) => Ok(TrueColorConfig::Auto),
+        TrueColorToml::Config(config) => Ok(config),
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize, Eq, PartialOrd, Ord)]
 #[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
 pub struct SmartTabConfig {
@@ -555,6 +688,10 @@ pub struct LspConfig {
     pub display_signature_help_docs: bool,
     /// Display inlay hints
     pub display_inlay_hints: bool,
+    /// Which kinds of inlay hints are displayed
+    pub inlay_hints: InlayHintsConfig,
+    /// Highlight the semantic tokens of language servers over the syntax highlighting
+    pub display_semantic_tokens: bool,
     /// Automatically highlight symbol references at the cursor.
     pub auto_document_highlight: bool,
     /// Maximum displayed length of inlay hints (excluding the added trailing `…`).
@@ -562,10 +699,14 @@ pub struct LspConfig {
     pub inlay_hints_length_limit: Option<NonZeroU8>,
     /// Display document color swatches
     pub display_color_swatches: bool,
+    /// Automatically request inline completions (ghost text) while typing
+    pub auto_inline_completion: bool,
     /// Whether to enable snippet support
     pub snippets: bool,
     /// Whether to include declaration in the goto reference query
     pub goto_reference_include_declaration: bool,
+    /// Preview the edits of a rename and choose the files to apply them to
+    pub preview_rename: bool,
 }
 
 impl Default for LspConfig {
@@ -577,11 +718,47 @@ fn default() -> Self {
             auto_signature_help: true,
             display_signature_help_docs: true,
             display_inlay_hints: false,
+            inlay_hints: InlayHintsConfig::default(),
+            display_semantic_tokens: false,
             auto_document_highlight: false,
             inlay_hints_length_limit: None,
             snippets: true,
             goto_reference_include_declaration: true,
+            preview_rename: false,
             display_color_swatches: true,
+            auto_inline_completion: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct InlayHintsConfig {
+    /// Display hints of the `type` kind, e.g. the inferred types of variables
+    pub type_hints: bool,
+    /// Display hints of the `parameter` kind, e.g. parameter names at call sites
+    pub parameter_hints: bool,
+    /// Display hints without a kind
+    pub other_hints: bool,
+}
+
+impl Default for InlayHintsConfig {
+    fn default() -> Self {
+        Self {
+            type_hints: true,
+            parameter_hints: true,
+            other_hints: true,
+        }
+    }
+}
+
+impl InlayHintsConfig {
+    /// Whether hints of the given kind are displayed.
+    pub fn shows(&self, kind: Option<lsp::InlayHintKind>) -> bool {
+        match kind {
+            Some(lsp::InlayHintKind::TYPE) => self.type_hints,
+            Some(lsp::InlayHintKind::PARAMETER) => self.parameter_hints,
+            _ => self.other_hints,
         }
     }
 }
@@ -605,6 +782,8 @@ pub struct StatusLineConfig {
     pub mode: ModeConfig,
     pub diagnostics: Vec<Severity>,
     pub workspace_diagnostics: Vec<Severity>,
+    /// User-defined segments that are placed with the `segment` element.
+    pub segments: HashMap<String, StatusLineSegment>,
 }
 
 impl Default for StatusLineConfig {
@@ -617,6 +796,8 @@ fn default() -> Self {
                 E::Spinner,
                 E::FileName,
                 E::ReadOnlyIndicator,
+                E::RemoteIndicator,
+                E::LargeFileIndicator,
                 E::FileModificationIndicator,
             ],
             center: vec![],
@@ -631,10 +812,47 @@ fn default() -> Self {
             mode: ModeConfig::default(),
             diagnostics: vec![Severity::Warning, Severity::Error],
             workspace_diagnostics: vec![Severity::Warning, Severity::Error],
+            segments: HashMap::new(),
         }
     }
 }
 
+/// A statusline segment that shows the output of a shell command or the contents of a register.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct StatusLineSegment {
+    /// A shell command whose first line of output is shown.
+    pub command: Option<String>,
+    /// A register whose first value is shown. The clipboard registers are not allowed since
+    /// reading them runs the clipboard provider on every render.
+    #[serde(default, deserialize_with = "deserialize_segment_register")]
+    pub register: Option<char>,
+    /// The number of seconds between two runs of the command.
+    #[serde(default = "default_segment_interval")]
+    pub interval: u64,
+    /// The theme scope used to style the segment.
+    pub style: Option<String>,
+}
+
+fn default_segment_interval() -> u64 {
+    5
+}
+
+fn deserialize_segment_register<'de, D>(deserializer: D) -> Result<Option<char>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use serde::de::Error;
+
+    let register = Option::<char>::deserialize(deserializer)?;
+    if matches!(register, Some('+' | '*')) {
+        return Err(<D::Error as Error>::custom(
+            "statusline segments can't show the clipboard registers",
+        ));
+    }
+    Ok(register)
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
 pub struct ModeConfig {
@@ -653,7 +871,7 @@ fn default() -> Self {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum StatusLineElement {
     /// The editor mode (Normal, Insert, Visual/Selection)
@@ -677,6 +895,12 @@ pub enum StatusLineElement {
     /// An indicator that shows `"[readonly]"` when a file cannot be written
     ReadOnlyIndicator,
 
+    /// An indicator that shows `"[remote]"` for files opened from a remote URL
+    RemoteIndicator,
+
+    /// An indicator that shows `"[large]"` for files opened in large file mode
+    LargeFileIndicator,
+
     /// The file encoding
     FileEncoding,
 
@@ -724,6 +948,9 @@ pub enum StatusLineElement {
 
     /// The base of current working directory
     CurrentWorkingDirectory,
+
+    /// A user-defined segment of [StatusLineConfig::segments], e.g. `{ segment = "clock" }`
+    Segment(String),
 }
 
 // Cursor shape is read and used on every rendered frame and so needs
@@ -827,6 +1054,8 @@ pub enum GutterType {
     Spacer,
     /// Highlight local changes
     Diff,
+    /// Show the headers of folded lines
+    Folds,
 }
 
 impl std::str::FromStr for GutterType {
@@ -838,8 +1067,9 @@ fn from_str(s: &str) -> Result<Self, Self::Err> {
             "spacer" => Ok(Self::Spacer),
             "line-numbers" => Ok(Self::LineNumbers),
             "diff" => Ok(Self::Diff),
+            "folds" => Ok(Self::Folds),
             _ => anyhow::bail!(
-                "Gutter type can only be `diagnostics`, `spacer`, `line-numbers` or `diff`."
+                "Gutter type can only be `diagnostics`, `spacer`, `line-numbers`, `diff` or `folds`."
             ),
         }
     }
@@ -1073,11 +1303,17 @@ pub enum PopupBorderConfig {
     Menu,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
 pub struct WordCompletion {
     pub enable: bool,
     pub trigger_length: NonZeroU8,
+    /// Only complete words in documents without a language server that provides completions.
+    pub fallback: bool,
+    /// Files with one word per line whose words are completed in addition to the buffer words.
+    pub dictionaries: Vec<PathBuf>,
+    /// ctags `tags` files whose tag names are completed in addition to the buffer words.
+    pub tags: Vec<PathBuf>,
 }
 
 impl Default for WordCompletion {
@@ -1085,6 +1321,102 @@ fn default() -> Self {
         Self {
             enable: true,
             trigger_length: NonZeroU8::new(7).unwrap(),
+            fallback: false,
+            dictionaries: Vec::new(),
+            tags: Vec::new(),
+        }
+    }
+}
+
+/// Maps abbreviations to their expansions. Tables map the abbreviations of the language with the
+/// name of the table and take precedence over the abbreviations of every language.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Abbreviations(HashMap<String, Abbreviation>);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Abbreviation {
+    Expansion(String),
+    Language(HashMap<String, String>),
+}
+
+impl Abbreviations {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the expansion of `word` in a document of the given language.
+    pub fn get(&self, language: Option<&str>, word: &str) -> Option<&str> {
+        let language = language.and_then(|language| match self.0.get(language) {
+            Some(Abbreviation::Language(abbreviations)) => abbreviations.get(word),
+            _ => None,
+        });
+        language
+            .or_else(|| match self.0.get(word) {
+                Some(Abbreviation::Expansion(expansion)) => Some(expansion),
+                _ => None,
+            })
+            .map(String::as_str)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct SpellCheck {
+    pub enable: bool,
+    /// The hunspell dictionary to check against, for example `en_US`.
+    pub language: String,
+}
+
+impl Default for SpellCheck {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            language: "en_US".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct InlineBlameConfig {
+    pub enable: bool,
+    /// The text shown for the cursor line. `{author}`, `{date}`, `{summary}` and `{commit}`
+    /// are replaced with the details of the commit that last changed the line.
+    pub format: String,
+}
+
+impl Default for InlineBlameConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            format: "{author}, {date} • {summary}".to_string(),
+        }
+    }
+}
+
+/// An overview column at the right edge of the views that shows the visible part of the document
+/// as a scrollbar thumb, along with marks for the lines with diagnostics, changes and search
+/// matches of the whole document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ScrollbarConfig {
+    pub enable: bool,
+    pub diagnostics: bool,
+    /// Marks the changes of the version control diff.
+    pub changes: bool,
+    /// Marks the matches of the last search.
+    pub search: bool,
+}
+
+impl Default for ScrollbarConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            diagnostics: true,
+            changes: true,
+            search: true,
         }
     }
 }
@@ -1096,6 +1428,8 @@ fn default() -> Self {
             scroll_lines: 3,
             mouse: true,
             mouse_yank_register: '*',
+            mouse_hover: false,
+            mouse_hover_delay: Duration::from_millis(500),
             shell: if cfg!(windows) {
                 vec!["cmd".to_owned(), "/C".to_owned()]
             } else {
@@ -1107,14 +1441,22 @@ fn default() -> Self {
             gutters: GutterConfig::default(),
             middle_click_paste: true,
             auto_pairs: AutoPairConfig::default(),
+            auto_close_tags: false,
             auto_completion: true,
             path_completion: true,
             word_completion: WordCompletion::default(),
+            abbreviations: Abbreviations::default(),
+            spell_check: SpellCheck::default(),
+            inline_blame: InlineBlameConfig::default(),
+            scrollbar: ScrollbarConfig::default(),
             auto_format: true,
             default_yank_register: '"',
             auto_save: AutoSave::default(),
             idle_timeout: Duration::from_millis(250),
+            smooth_scroll: Duration::ZERO,
+            preview_buffers: false,
             completion_timeout: Duration::from_millis(250),
+            chord_timeout: Duration::from_millis(1000),
             preview_completion_insert: true,
             completion_trigger_len: 2,
             auto_info: true,
@@ -1122,7 +1464,7 @@ fn default() -> Self {
             file_explorer: FileExplorerConfig::default(),
             statusline: StatusLineConfig::default(),
             cursor_shape: CursorShapeConfig::default(),
-            true_color: false,
+            true_color: TrueColorConfig::Auto,
             undercurl: false,
             search: SearchConfig::default(),
             lsp: LspConfig::default(),
@@ -1130,6 +1472,7 @@ fn default() -> Self {
             rulers: Vec::new(),
             whitespace: WhitespaceConfig::default(),
             bufferline: BufferLine::default(),
+            bufferline_numbers: false,
             indent_guides: IndentGuidesConfig::default(),
             color_modes: false,
             soft_wrap: SoftWrap {
@@ -1154,7 +1497,13 @@ fn default() -> Self {
             clipboard_provider: ClipboardProvider::default(),
             editor_config: true,
             rainbow_brackets: false,
+            sticky_context: false,
+            persistent_undo: false,
+            large_file_threshold: 64 * 1024 * 1024,
+            auto_reload: true,
+            recovery: true,
             kitty_keyboard_protocol: Default::default(),
+            image_protocol: Default::default(),
             buffer_picker: BufferPickerConfig::default(),
             insecure: false,
         }
@@ -1172,8 +1521,9 @@ fn default() -> Self {
 
 #[derive(Debug, Clone, Default)]
 pub struct Breakpoint {
-    pub id: Option<usize>,
-    pub verified: bool,
+    /// The state of the breakpoint in each debugging session. The ids are assigned by the debug
+    /// adapters, so the ids of different sessions may collide.
+    pub sessions: HashMap<DebugAdapterId, SessionBreakpoint>,
     pub message: Option<String>,
 
     pub line: usize,
@@ -1183,6 +1533,45 @@ pub struct Breakpoint {
     pub log_message: Option<String>,
 }
 
+impl Breakpoint {
+    /// Whether a debug adapter confirmed the breakpoint.
+    pub fn is_verified(&self) -> bool {
+        self.sessions.values().any(|session| session.verified)
+    }
+
+    /// Returns the breakpoint with the id assigned by the debug adapter of `session`.
+    pub fn find_mut(
+        breakpoints: &mut [Breakpoint],
+        session: DebugAdapterId,
+        id: usize,
+    ) -> Option<&mut Breakpoint> {
+        breakpoints.iter_mut().find(|breakpoint| {
+            breakpoint
+                .sessions
+                .get(&session)
+                .is_some_and(|state| state.id == Some(id))
+        })
+    }
+}
+
+/// The state of a breakpoint in a debugging session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionBreakpoint {
+    pub id: Option<usize>,
+    pub verified: bool,
+    /// The line the debug adapter moved the breakpoint to, 0-indexed.
+    pub line: Option<usize>,
+}
+
+/// An expression of the debug watch panel, re-evaluated whenever the debuggee stops.
+#[derive(Debug, Clone)]
+pub struct WatchExpression {
+    pub expression: String,
+    /// The result of the last evaluation or the error it failed with. `None` until the
+    /// expression was evaluated.
+    pub value: Option<Result<String, String>>,
+}
+
 use futures_util::stream::{Flatten, Once};
 
 type Diagnostics = BTreeMap<Uri, Vec<(lsp::Diagnostic, DiagnosticProvider)>>;
@@ -1207,10 +1596,15 @@ pub struct Editor {
     pub macro_replaying: Vec<char>,
     pub language_servers: helix_lsp::Registry,
     pub diagnostics: Diagnostics,
+    /// The problems found in the output of the last task.
+    pub quickfix: Quickfix,
     pub diff_providers: DiffProviderRegistry,
 
     pub debug_adapters: dap::registry::Registry,
     pub breakpoints: HashMap<PathBuf, Vec<Breakpoint>>,
+    pub debug_watches: Vec<WatchExpression>,
+    /// Whether the debug watch panel is shown, toggled with `:debug-toggle-watches`.
+    pub show_debug_watches: bool,
 
     pub syn_loader: Arc<ArcSwap<syntax::Loader>>,
     pub theme_loader: Arc<theme::Loader>,
@@ -1228,6 +1622,8 @@ pub struct Editor {
 
     pub status_msg: Option<(Cow<'static, str>, Severity)>,
     pub autoinfo: Option<Info>,
+    /// The last output of the commands of the user-defined statusline segments by name.
+    pub statusline_segments: HashMap<String, String>,
 
     pub config: Arc<dyn DynAccess<Config>>,
     pub auto_pairs: Option<AutoPairs>,
@@ -1238,10 +1634,41 @@ pub struct Editor {
     pub last_completion: Option<CompleteAction>,
     pub last_cwd: Option<PathBuf>,
     pub dir_stack: VecDeque<PathBuf>,
+    /// The locations left by switching buffers, shared by all views.
+    pub buffer_history: BufferHistory,
+    /// The named marks, shared by all views.
+    pub marks: Marks,
+    /// The views that are scrolling smoothly to a new position.
+    pub scroll_animations: ScrollAnimations,
+    /// The document opened by a picker as a preview, see [Action::Preview]. It is closed when the
+    /// next file is previewed, unless it was modified by then.
+    pub preview_document: Option<DocumentId>,
+    /// The shell command started by a pipe or `:sh` that is running in the background. Starting
+    /// another command cancels it, and so does Ctrl-C.
+    pub shell_controller: TaskController,
+    /// The lines listed by the buffers of global search results, keyed by their canonical path
+    /// and zero based line. `:replace-apply` skips the lines that changed since the search.
+    pub search_results: HashMap<DocumentId, HashMap<(PathBuf, usize), String>>,
+    /// The order of the buffers in the bufferline set by moving buffers. Buffers that were
+    /// never moved follow in the order they were opened, see [Editor::buffers].
+    buffer_order: Vec<DocumentId>,
+    /// The name of the session that was last saved or restored.
+    pub session: Option<String>,
+    /// Root folders opened in addition to the workspace of the current working directory.
+    pub workspace_roots: Vec<PathBuf>,
+    /// The spell check dictionary and its language. The dictionary is `None` while it is
+    /// loaded in the background.
+    spell_dictionary: Option<(String, Option<Arc<Dictionary>>)>,
+    /// The user's snippets, loaded on first use.
+    snippet_library: Option<Arc<SnippetLibrary>>,
 
     pub exit_code: i32,
 
     pub config_events: (UnboundedSender<ConfigEvent>, UnboundedReceiver<ConfigEvent>),
+    /// Watches the files of the open documents for external changes, see
+    /// [Config::auto_reload].
+    document_watcher: Watcher,
+    changed_files: UnboundedReceiver<Vec<PathBuf>>,
     pub needs_redraw: bool,
     /// Cached position of the cursor calculated during rendering.
     /// The content of `cursor_cache` is returned by `Editor::cursor` if
@@ -1269,6 +1696,8 @@ pub enum EditorEvent {
     ConfigEvent(ConfigEvent),
     LanguageServerMessage((LanguageServerId, Call)),
     DebuggerEvent((DebugAdapterId, dap::Payload)),
+    /// Files of open documents were changed, created or removed by another program.
+    FilesChanged(Vec<PathBuf>),
     IdleTimer,
     Redraw,
 }
@@ -1306,6 +1735,9 @@ pub enum Action {
     Replace,
     HorizontalSplit,
     VerticalSplit,
+    /// Like [Action::Replace], but a file that isn't open yet is opened as the preview document,
+    /// see [Editor::preview_document].
+    Preview,
 }
 
 impl Action {
@@ -1340,6 +1772,11 @@ pub fn new(
         // HAXX: offset the render area height by 1 to account for prompt/commandline
         area.height -= 1;
 
+        let (changed_files_sender, changed_files) = unbounded_channel();
+        let document_watcher = Watcher::new(DOCUMENT_WATCH_INTERVAL, move |paths| {
+            let _ = changed_files_sender.send(paths);
+        });
+
         Self {
             mode: Mode::Normal,
             tree: Tree::new(area),
@@ -1355,9 +1792,12 @@ pub fn new(
             theme: theme_loader.default(),
             language_servers,
             diagnostics: Diagnostics::new(),
+            quickfix: Quickfix::default(),
             diff_providers: DiffProviderRegistry::default(),
             debug_adapters: dap::registry::Registry::new(),
             breakpoints: HashMap::new(),
+            debug_watches: Vec::new(),
+            show_debug_watches: true,
             syn_loader,
             theme_loader,
             last_theme: None,
@@ -1368,15 +1808,29 @@ pub fn new(
             ))),
             status_msg: None,
             autoinfo: None,
+            statusline_segments: HashMap::new(),
             idle_timer: Box::pin(sleep(conf.idle_timeout)),
             redraw_timer: Box::pin(sleep(Duration::MAX)),
             last_motion: None,
             last_completion: None,
             last_cwd: None,
+            buffer_history: BufferHistory::default(),
+            marks: Marks::default(),
+            scroll_animations: ScrollAnimations::default(),
+            preview_document: None,
+            shell_controller: TaskController::new(),
+            search_results: HashMap::new(),
+            buffer_order: Vec::new(),
+            session: None,
+            workspace_roots: Vec::new(),
+            spell_dictionary: None,
+            snippet_library: None,
             config,
             auto_pairs,
             exit_code: 0,
             config_events: unbounded_channel(),
+            document_watcher,
+            changed_files,
             needs_redraw: false,
             handlers,
             mouse_down_range: None,
@@ -1420,9 +1874,21 @@ pub fn config(&self) -> DynGuard<Config> {
     /// Call if the config has changed to let the editor update all
     /// relevant members.
     pub fn refresh_config(&mut self, old_config: &Config) {
+        let trust = helix_loader::workspace_trust::quick_query_workspace(self.config().insecure);
+        let doc_ids: Vec<_> = self.documents.keys().copied().collect();
+        for doc_id in doc_ids {
+            self.apply_directory_config(doc_id, trust);
+        }
         let config = self.config();
         self.auto_pairs = (&config.auto_pairs).into();
+        // drop the hints of the previously displayed kinds, they are recomputed on idle
+        if old_config.lsp.inlay_hints != config.lsp.inlay_hints {
+            for doc in self.documents_mut() {
+                doc.reset_all_inlay_hints();
+            }
+        }
         self.reset_idle_timer();
+        self.watch_documents();
         self._refresh();
         helix_event::dispatch(crate::events::ConfigDidChange {
             editor: self,
@@ -1543,7 +2009,7 @@ pub fn refresh_language_servers(&mut self, doc_id: DocumentId) {
     }
 
     /// moves/renames a path, invoking any event handlers (currently only lsp)
-    /// and calling `set_doc_path` if the file is open in the editor
+    /// and calling `set_doc_path` for the open files at or within the path
     pub fn move_path(&mut self, old_path: &Path, new_path: &Path) -> io::Result<()> {
         let new_path = canonicalize(new_path);
         // sanity check
@@ -1577,8 +2043,21 @@ pub fn move_path(&mut self, old_path: &Path, new_path: &Path) -> io::Result<()>
             fs::rename(old_path, &new_path)?;
         }
 
-        if let Some(doc) = self.document_by_path(old_path) {
-            self.set_doc_path(doc.id(), &new_path);
+        let old_path_canonical = canonicalize(old_path);
+        let moved_docs: Vec<_> = self
+            .documents()
+            .filter_map(|doc| {
+                let relative = doc.path()?.strip_prefix(&old_path_canonical).ok()?;
+                let path = if relative.as_os_str().is_empty() {
+                    new_path.clone()
+                } else {
+                    new_path.join(relative)
+                };
+                Some((doc.id(), path))
+            })
+            .collect();
+        for (doc_id, path) in moved_docs {
+            self.set_doc_path(doc_id, &path);
         }
         let is_dir = new_path.is_dir();
         for ls in self.language_servers.iter_clients() {
@@ -1643,8 +2122,29 @@ pub fn create_path(&mut self, path: &Path, is_dir: bool) -> io::Result<()> {
         Ok(())
     }
 
+    /// Returns an error naming the first buffer with unsaved changes whose file is `path` or
+    /// inside of it. Deleting or replacing such a file would discard the changes.
+    pub fn ensure_unmodified(&self, path: &Path) -> io::Result<()> {
+        let modified = self.documents().find(|doc| {
+            doc.is_modified()
+                && doc
+                    .path()
+                    .is_some_and(|doc_path| doc_path.starts_with(path))
+        });
+        match modified {
+            Some(doc) => Err(io::Error::other(format!(
+                "{} has unsaved changes",
+                doc.display_name()
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Deletes the file or directory at `path` and closes the buffers of the deleted files.
+    /// Fails without deleting anything if one of those buffers has unsaved changes.
     pub fn delete_path(&mut self, path: &Path, recursive: bool) -> io::Result<()> {
         let path = canonicalize(path);
+        self.ensure_unmodified(&path)?;
         let is_dir = path.is_dir();
         let language_servers: Vec<_> = self
             .language_servers
@@ -1678,6 +2178,20 @@ pub fn delete_path(&mut self, path: &Path, recursive: bool) -> io::Result<()> {
             fs::remove_file(&path)?;
         }
 
+        // buffers of deleted files would otherwise silently recreate them on save
+        let deleted_docs: Vec<_> = self
+            .documents()
+            .filter(|doc| {
+                doc.path()
+                    .is_some_and(|doc_path| doc_path.starts_with(&path))
+            })
+            .map(|doc| doc.id())
+            .collect();
+        for doc_id in deleted_docs {
+            // the buffers are unmodified, closing them can't fail
+            let _ = self.close_document(doc_id, true);
+        }
+
         for ls in self.language_servers.iter_clients() {
             if !ls.is_initialized() {
                 continue;
@@ -1709,8 +2223,57 @@ pub fn set_doc_path(&mut self, doc_id: DocumentId, path: &Path) {
         // we have fully unregistered this document from its LS
         doc.language_servers.clear();
         doc.set_path(Some(path));
+        self.refresh_directory_config(doc_id);
+        let doc = doc_mut!(self, &doc_id);
         doc.detect_editor_config();
-        self.refresh_doc_language(doc_id)
+        self.refresh_doc_language(doc_id);
+        self.watch_documents();
+    }
+
+    /// Updates the files watched for external changes to the files of the open documents.
+    fn watch_documents(&self) {
+        let paths: Vec<_> = if self.config().auto_reload {
+            self.documents
+                .values()
+                .filter_map(|doc| doc.path().map(ToOwned::to_owned))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        self.document_watcher.watch(paths);
+    }
+
+    /// Reloads a document from its file, discarding unsaved changes. See [Document::reload].
+    pub fn reload_document(&mut self, doc_id: DocumentId) -> anyhow::Result<()> {
+        let scrolloff = self.config().scrolloff;
+        let focus = self.tree.focus;
+        let doc = doc_mut!(self, &doc_id);
+        let mut view_ids: Vec<_> = doc.selections().keys().copied().collect();
+        if view_ids.is_empty() {
+            doc.ensure_view_init(focus);
+            view_ids.push(focus);
+        }
+        // the reload is committed to the history through the first view
+        let view = view_mut!(self, view_ids[0]);
+        view.sync_changes(doc);
+        let large_file = doc.large_file;
+        doc.reload(view, &self.diff_providers)?;
+        // the other views must be synced as well, their jumplists still refer to the old text
+        for view_id in view_ids {
+            let view = view_mut!(self, view_id);
+            if view.doc == doc_id {
+                view.sync_changes(doc);
+                view.ensure_cursor_in_view(doc, scrolloff);
+            }
+        }
+        let large_file_changed = doc.large_file != large_file;
+        if let Some(path) = doc.path().map(ToOwned::to_owned) {
+            self.language_servers.file_event_handler.file_changed(path);
+        }
+        if large_file_changed {
+            self.refresh_language_servers(doc_id);
+        }
+        Ok(())
     }
 
     pub fn refresh_doc_language(&mut self, doc_id: DocumentId) {
@@ -1726,11 +2289,42 @@ pub fn refresh_doc_language(&mut self, doc_id: DocumentId) {
         doc.reset_all_inlay_hints();
     }
 
+    /// Applies the `[editor]` tables of the `.helix/config.toml` files in the directories of a
+    /// document on top of the editor config. Like the config of the workspace, these files are
+    /// only loaded in trusted workspaces.
+    pub fn refresh_directory_config(&mut self, doc_id: DocumentId) {
+        let trust = helix_loader::workspace_trust::quick_query_workspace(self.config().insecure);
+        self.apply_directory_config(doc_id, trust);
+    }
+
+    fn apply_directory_config(&mut self, doc_id: DocumentId, trust: TrustStatus) {
+        let Some(doc) = self.documents.get(&doc_id) else {
+            return;
+        };
+        let files = doc
+            .path()
+            .map(|path| helix_loader::directory_config_files(path, trust))
+            .unwrap_or_default();
+        let config: Arc<dyn DynAccess<Config>> = if files.is_empty() {
+            self.config.clone()
+        } else {
+            match load_directory_config(&self.config.load(), &files) {
+                Ok(config) => Arc::new(ArcSwap::from_pointee(config)),
+                Err(err) => {
+                    self.set_error(format!("Failed to load directory config: {err}"));
+                    self.config.clone()
+                }
+            }
+        };
+        doc_mut!(self, &doc_id).config = config;
+    }
+
     /// Launch a language server for a given document
     pub fn launch_language_servers(&mut self, doc_id: DocumentId) {
         if !self.config().lsp.enable {
             return;
         }
+        let workspace_roots = self.trusted_workspace_roots();
         // if doc doesn't have a URL it's a scratch buffer, ignore it
         let Some(doc) = self.documents.get_mut(&doc_id) else {
             return;
@@ -1742,8 +2336,11 @@ pub fn launch_language_servers(&mut self, doc_id: DocumentId) {
         let config = doc.config.load();
         let root_dirs = &config.workspace_lsp_roots;
 
+        // additional workspace roots need to be trusted on their own
+        let (workspace, _) =
+            helix_lsp::find_doc_workspace(path.map(PathBuf::as_path), &self.workspace_roots);
         if let TrustStatus::Untrusted =
-            helix_loader::workspace_trust::quick_query_workspace(self.config.load().insecure)
+            helix_loader::workspace_trust::query_workspace(&workspace, self.config.load().insecure)
         {
             self.set_status(
                 "Current workspace is not trusted. Run `:workspace-trust` to enable all features.",
@@ -1754,7 +2351,13 @@ pub fn launch_language_servers(&mut self, doc_id: DocumentId) {
         // store only successfully started language servers
         let language_servers = lang.as_ref().map_or_else(HashMap::default, |language| {
             self.language_servers
-                .get(language, path, root_dirs, config.lsp.snippets)
+                .get(
+                    language,
+                    path,
+                    root_dirs,
+                    &workspace_roots,
+                    config.lsp.snippets,
+                )
                 .filter_map(|(lang, client)| match client {
                     Ok(client) => Some((lang, client)),
                     Err(err) => {
@@ -1864,7 +2467,7 @@ pub fn switch(&mut self, id: DocumentId, action: Action) {
         }
 
         let focust_lost = match action {
-            Action::Replace => {
+            Action::Replace | Action::Preview => {
                 let (view, doc) = current_ref!(self);
                 // If the current view is an empty scratch buffer and is not displayed in any other views, delete it.
                 // Boolean value is determined before the call to `view_mut` because the operation requires a borrow
@@ -1896,11 +2499,15 @@ pub fn switch(&mut self, id: DocumentId, action: Action) {
                     for (view, _) in self.tree.views_mut() {
                         view.remove_document(&id);
                     }
+                    self.buffer_history.remove(id);
                 } else {
                     let jump = (view.doc, doc.selection(view.id).clone());
                     view.push_jump(doc, jump);
                     // Set last accessed doc if it is a different document
                     if doc.id != id {
+                        let selection = doc.selection(view.id).clone();
+                        self.buffer_history
+                            .push(BufferLocation::new(doc, selection));
                         view.add_to_history(view.doc);
                         // Set last modified doc if modified and last modified doc is different
                         if std::mem::take(&mut doc.modified_since_accessed)
@@ -1968,6 +2575,7 @@ fn new_document(&mut self, mut doc: Document) -> DocumentId {
             DocumentId(unsafe { NonZeroUsize::new_unchecked(self.next_document_id.0.get() + 1) });
         doc.id = id;
         self.documents.insert(id, doc);
+        self.watch_documents();
 
         let (save_sender, save_receiver) = tokio::sync::mpsc::unbounded_channel();
         self.saves.insert(id, save_sender);
@@ -2017,10 +2625,22 @@ pub fn document_id_by_path(&self, path: &Path) -> Option<DocumentId> {
 
     // ??? possible use for integration tests
     pub fn open(&mut self, path: &Path, action: Action) -> Result<DocumentId, DocumentOpenError> {
+        if let Some(backend) = remote::backend_for_url(path) {
+            let id = self
+                .remote_document_id(backend?.url())
+                .ok_or(DocumentOpenError::RemoteFile)?;
+            self.switch(id, action);
+            return Ok(id);
+        }
         let path = helix_stdx::path::canonicalize(path);
         let id = self.document_id_by_path(&path);
+        let opened = id.is_none();
 
         let id = if let Some(id) = id {
+            // opening the preview document other than as a preview keeps it
+            if !matches!(action, Action::Preview) && self.preview_document == Some(id) {
+                self.preview_document = None;
+            }
             id
         } else {
             let mut doc = Document::open(
@@ -2035,12 +2655,31 @@ pub fn open(&mut self, path: &Path, action: Action) -> Result<DocumentId, Docume
                 Editor::doc_diagnostics(&self.language_servers, &self.diagnostics, &doc);
             doc.replace_diagnostics(diagnostics, &[], None);
 
-            if let Some(diff_base) = self.diff_providers.get_diff_base(&path) {
-                doc.set_diff_base(diff_base);
+            // diffing a large file against its base would block the editor as well
+            if !doc.large_file {
+                if let Some(diff_base) = self.diff_providers.get_diff_base(&path) {
+                    doc.set_diff_base(diff_base);
+                }
             }
             doc.set_version_control_head(self.diff_providers.get_current_head_name(&path));
+            if self.config().persistent_undo {
+                if let Err(err) = undo_file::load(&mut doc) {
+                    log::error!("Failed to restore the undo history: {err}");
+                }
+            }
 
+            let large_file = doc.large_file;
+            let has_merge_conflicts = doc.has_merge_conflicts;
             let id = self.new_document(doc);
+            self.marks.attach(doc_mut!(self, &id));
+            if large_file {
+                self.set_status(
+                    "Large file: syntax highlighting and language servers are disabled",
+                );
+            } else if has_merge_conflicts {
+                self.set_warning("File contains merge conflicts");
+            }
+            self.refresh_directory_config(id);
             self.launch_language_servers(id);
 
             helix_event::dispatch(DocumentDidOpen {
@@ -2051,6 +2690,67 @@ pub fn open(&mut self, path: &Path, action: Action) -> Result<DocumentId, Docume
             id
         };
 
+        self.switch(id, action);
+        if opened && matches!(action, Action::Preview) {
+            self.replace_preview_document(id);
+        }
+
+        Ok(id)
+    }
+
+    /// Makes `id` the preview document and closes the previous one, unless it was modified or is
+    /// still shown in a view.
+    fn replace_preview_document(&mut self, id: DocumentId) {
+        let Some(previous) = self.preview_document.replace(id) else {
+            return;
+        };
+        let keep = self
+            .documents
+            .get(&previous)
+            .is_none_or(Document::is_modified)
+            || self.tree.views().any(|(view, _)| view.doc == previous);
+        if !keep {
+            let _ = self.close_document(previous, false);
+        }
+    }
+
+    /// Returns the open document of a remote URL, see [crate::remote].
+    pub fn remote_document_id(&self, url: &str) -> Option<DocumentId> {
+        self.documents()
+            .find(|doc| {
+                doc.remote
+                    .as_ref()
+                    .is_some_and(|remote| remote.url() == url)
+            })
+            .map(|doc| doc.id)
+    }
+
+    /// Opens a remote document from the contents read by [RemoteFile::load]. Reading remote
+    /// files can take a while, so unlike [Editor::open] the file has to be read in the
+    /// background before.
+    pub fn open_remote(
+        &mut self,
+        remote: RemoteFile,
+        contents: Option<Vec<u8>>,
+        action: Action,
+    ) -> Result<DocumentId, DocumentOpenError> {
+        let id = if let Some(id) = self.remote_document_id(remote.url()) {
+            id
+        } else {
+            let doc = Document::from_remote(
+                remote,
+                contents,
+                self.config.clone(),
+                self.syn_loader.clone(),
+            )?;
+            let id = self.new_document(doc);
+            helix_event::dispatch(DocumentDidOpen {
+                editor: self,
+                doc: id,
+            });
+            id
+        };
+
         self.switch(id, action);
 
         Ok(id)
@@ -2073,15 +2773,25 @@ pub fn close_document(&mut self, doc_id: DocumentId, force: bool) -> Result<(),
         if !force && doc.is_modified() {
             return Err(CloseError::BufferModified(doc.display_name().into_owned()));
         }
+        if self.config().persistent_undo {
+            if let Err(err) = undo_file::save(doc) {
+                log::error!("Failed to save the undo history: {err}");
+            }
+        }
 
         // This will also disallow any follow-up writes
         self.saves.remove(&doc_id);
+        self.search_results.remove(&doc_id);
 
         enum Action {
             Close(ViewId),
             ReplaceDoc(ViewId, DocumentId),
         }
 
+        self.buffer_history.remove(doc_id);
+        self.buffer_order.retain(|id| *id != doc_id);
+        self.marks.detach(doc_mut!(self, &doc_id));
+
         let actions: Vec<Action> = self
             .tree
             .views_mut()
@@ -2114,6 +2824,13 @@ enum Action {
         }
 
         let doc = self.documents.remove(&doc_id).unwrap();
+        self.watch_documents();
+        // closing a modified document discards its changes on purpose
+        if let Some(path) = doc.path() {
+            if let Err(err) = recovery::remove(path) {
+                log::error!("Failed to remove the recovery file of {path:?}: {err}");
+            }
+        }
 
         // If the document we removed was visible in all views, we will have no more views. We don't
         // want to close the editor just for a simple buffer close, so we need to create a new view
@@ -2162,8 +2879,11 @@ pub fn save<P: Into<PathBuf>>(
         let handler = self.language_servers.file_event_handler.clone();
         let future = async move {
             let res = doc_save_future.await;
-            if let Ok(event) = &res {
-                handler.file_changed(event.path.clone());
+            if let Ok(DocumentSavedEvent {
+                path: Some(path), ..
+            }) = &res
+            {
+                handler.file_changed(path.clone());
             }
             res
         };
@@ -2236,6 +2956,15 @@ pub fn transpose_view(&mut self) {
         self.tree.transpose();
     }
 
+    /// Grows the focused view by `amount` cells along `layout`, see [`Tree::resize_view`].
+    pub fn resize_view(&mut self, layout: tree::Layout, amount: i32) -> bool {
+        self.tree.resize_view(self.tree.focus, layout, amount)
+    }
+
+    pub fn equalize_views(&mut self) {
+        self.tree.equalize();
+    }
+
     pub fn should_close(&self) -> bool {
         self.tree.is_empty()
     }
@@ -2262,19 +2991,66 @@ pub fn documents(&self) -> impl Iterator<Item = &Document> {
         self.documents.values()
     }
 
+    /// The ids of the buffers in the order of the bufferline: the pinned buffers followed by the
+    /// other buffers, each in the order set by [Editor::move_buffer].
+    pub fn buffers(&self) -> Vec<DocumentId> {
+        let mut buffers: Vec<_> = self
+            .buffer_order
+            .iter()
+            .copied()
+            .filter(|id| self.documents.contains_key(id))
+            .collect();
+        buffers.extend(
+            self.documents
+                .keys()
+                .filter(|id| !self.buffer_order.contains(id)),
+        );
+        buffers.sort_by_key(|id| !self.documents[id].pinned);
+        buffers
+    }
+
+    /// Moves the buffer by `offset` places in the bufferline. Buffers are only moved among the
+    /// pinned or the unpinned buffers. Returns whether the buffer was moved.
+    pub fn move_buffer(&mut self, doc_id: DocumentId, offset: isize) -> bool {
+        let mut buffers = self.buffers();
+        let Some(index) = buffers.iter().position(|id| *id == doc_id) else {
+            return false;
+        };
+        let pinned = self.documents[&doc_id].pinned;
+        let group = buffers
+            .iter()
+            .filter(|id| self.documents[id].pinned == pinned)
+            .count();
+        let group_start = if pinned { 0 } else { buffers.len() - group };
+        let target = index
+            .saturating_add_signed(offset)
+            .clamp(group_start, group_start + group - 1);
+        if target == index {
+            return false;
+        }
+        let id = buffers.remove(index);
+        buffers.insert(target, id);
+        self.buffer_order = buffers;
+        true
+    }
+
     #[inline]
     pub fn documents_mut(&mut self) -> impl Iterator<Item = &mut Document> {
         self.documents.values_mut()
     }
 
     pub fn document_by_path<P: AsRef<Path>>(&self, path: P) -> Option<&Document> {
-        self.documents()
-            .find(|doc| doc.path().is_some_and(|p| p == path.as_ref()))
+        self.documents().find(|doc| {
+            doc.path()
+                .is_some_and(|p| helix_stdx::path::same_file(p, path.as_ref()))
+        })
     }
 
     pub fn document_by_path_mut<P: AsRef<Path>>(&mut self, path: P) -> Option<&mut Document> {
-        self.documents_mut()
-            .find(|doc| doc.path().is_some_and(|p| p == path.as_ref()))
+        self.documents_mut().find(|doc| {
+            doc.path()
+                .is_some_and(|p| helix_stdx::path::same_file(p, path.as_ref()))
+        })
     }
 
     /// Returns all supported diagnostics for the document
@@ -2392,6 +3168,9 @@ pub async fn wait_event(&mut self) -> EditorEvent {
                 Some(event) = self.debug_adapters.incoming.next() => {
                     return EditorEvent::DebuggerEvent(event)
                 }
+                Some(paths) = self.changed_files.recv() => {
+                    return EditorEvent::FilesChanged(paths)
+                }
 
                 _ = helix_event::redraw_requested() => {
                     if  !self.needs_redraw{
@@ -2503,6 +3282,145 @@ pub fn get_last_cwd(&mut self) -> Option<&Path> {
         self.last_cwd.as_deref()
     }
 
+    /// Returns the dictionary of the configured spell check language.
+    ///
+    /// Returns `None` if spell checking is disabled or the dictionary is not loaded (yet), see
+    /// [Editor::load_spell_dictionary].
+    pub fn spell_dictionary(&self) -> Option<Arc<Dictionary>> {
+        let config = self.config();
+        if !config.spell_check.enable {
+            return None;
+        }
+        match &self.spell_dictionary {
+            Some((language, Some(dictionary)))
+                if *language == config.spell_check.language && !dictionary.is_empty() =>
+            {
+                Some(dictionary.clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the spell check language whose dictionary needs to be loaded and marks it as
+    /// loading. Returns `None` if spell checking is disabled or the dictionary of the language
+    /// is already loaded or loading.
+    ///
+    /// The dictionary is loaded by the caller, usually on a blocking task, and published with
+    /// [Editor::set_spell_dictionary].
+    pub fn load_spell_dictionary(&mut self) -> Option<String> {
+        let config = self.config();
+        if !config.spell_check.enable
+            || self
+                .spell_dictionary
+                .as_ref()
+                .is_some_and(|(language, _)| *language == config.spell_check.language)
+        {
+            return None;
+        }
+        let language = config.spell_check.language.clone();
+        self.spell_dictionary = Some((language.clone(), None));
+        Some(language)
+    }
+
+    /// Publishes the dictionary of `language` loaded after [Editor::load_spell_dictionary]. It
+    /// is dropped if the spell check language changed in the meantime.
+    pub fn set_spell_dictionary(&mut self, language: String, dictionary: Dictionary) {
+        if self
+            .spell_dictionary
+            .as_ref()
+            .is_some_and(|(loading, _)| *loading == language)
+        {
+            self.spell_dictionary = Some((language, Some(Arc::new(dictionary))));
+        }
+    }
+
+    /// Adds `word` to the spell check dictionary and the personal word list of its language.
+    pub fn add_spelling_word(&mut self, word: &str) -> anyhow::Result<()> {
+        let config = self.config();
+        if !config.spell_check.enable {
+            anyhow::bail!("Spell checking is disabled");
+        }
+        match self.spell_dictionary.as_mut() {
+            Some((language, Some(dictionary))) if *language == config.spell_check.language => {
+                Arc::make_mut(dictionary).add_personal_word(language, word)
+            }
+            _ => anyhow::bail!("The spell check dictionary is still loading"),
+        }
+    }
+
+    /// Returns the snippets of the user's `snippets.toml`, loading them on first use.
+    pub fn snippet_library(&mut self) -> Arc<SnippetLibrary> {
+        if let Some(library) = &self.snippet_library {
+            return library.clone();
+        }
+        let library = SnippetLibrary::load().unwrap_or_else(|err| {
+            self.set_error(format!("{err:#}"));
+            SnippetLibrary::default()
+        });
+        let library = Arc::new(library);
+        self.snippet_library = Some(library.clone());
+        library
+    }
+
+    /// Drops the loaded snippets so that `snippets.toml` is read again on the next use.
+    pub fn reload_snippets(&mut self) {
+        self.snippet_library = None;
+    }
+
+    /// Returns the workspace of the current working directory followed by all additional
+    /// workspace roots.
+    pub fn workspaces(&self) -> Vec<PathBuf> {
+        let mut roots = vec![helix_loader::find_workspace().0];
+        for root in &self.workspace_roots {
+            if !roots.contains(root) {
+                roots.push(root.clone());
+            }
+        }
+        roots
+    }
+
+    /// The additional workspace roots that are trusted. Only these are sent to language servers
+    /// as workspace folders.
+    pub fn trusted_workspace_roots(&self) -> Vec<PathBuf> {
+        let insecure = self.config().insecure;
+        self.workspace_roots
+            .iter()
+            .filter(|root| {
+                matches!(
+                    helix_loader::workspace_trust::query_workspace(root, insecure),
+                    TrustStatus::Trusted
+                )
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Opens `path` as an additional workspace root. Returns `false` if it already is one.
+    pub fn add_workspace_root(&mut self, path: &Path) -> bool {
+        let root = canonicalize(path);
+        if self.workspace_roots.contains(&root) {
+            return false;
+        }
+        if let TrustStatus::Trusted =
+            helix_loader::workspace_trust::query_workspace(&root, self.config().insecure)
+        {
+            self.language_servers.add_workspace_root(&root);
+        }
+        self.workspace_roots.push(root);
+        true
+    }
+
+    /// Closes the additional workspace root `path`. Returns `false` if it wasn't open.
+    pub fn remove_workspace_root(&mut self, path: &Path) -> bool {
+        let root = canonicalize(path);
+        let Some(idx) = self.workspace_roots.iter().position(|r| *r == root) else {
+            return false;
+        };
+        self.workspace_roots.remove(idx);
+        self.language_servers.remove_workspace_root(&root);
+        true
+    }
+
     pub fn jump_forward(&mut self, view_id: ViewId, count: usize) {
         if let Some((doc_id, selection)) = view_mut!(self, view_id).jumps.forward(count).cloned() {
             self.jump_to(view_id, doc_id, selection);
@@ -2522,6 +3440,47 @@ pub fn jump_backward(&mut self, view_id: ViewId, count: usize) {
         }
     }
 
+    /// Returns to the location that was left `count` buffer switches ago, see [BufferHistory].
+    pub fn buffer_location_backward(&mut self, count: usize) {
+        let (view, doc) = current!(self);
+        doc.append_changes_to_history(view);
+        let selection = doc.selection(view.id).clone();
+        let location = BufferLocation::new(doc, selection);
+        if let Some(location) = self.buffer_history.backward(location, count).cloned() {
+            self.goto_buffer_location(location);
+        }
+    }
+
+    pub fn buffer_location_forward(&mut self, count: usize) {
+        let (view, doc) = current!(self);
+        doc.append_changes_to_history(view);
+        let selection = doc.selection(view.id).clone();
+        let location = BufferLocation::new(doc, selection);
+        if let Some(location) = self.buffer_history.forward(location, count).cloned() {
+            self.goto_buffer_location(location);
+        }
+    }
+
+    fn goto_buffer_location(&mut self, location: BufferLocation) {
+        let Some(doc) = self.documents.get_mut(&location.doc) else {
+            return;
+        };
+        let selection = location.selection(doc);
+        let view_id = self.tree.focus;
+        let old_doc_id = view!(self).doc;
+        if old_doc_id != location.doc {
+            self.enter_normal_mode();
+            self.replace_document_in_view(view_id, location.doc);
+            dispatch(DocumentFocusLost {
+                editor: self,
+                doc: old_doc_id,
+            });
+        }
+        let (view, doc) = current!(self);
+        doc.set_selection(view_id, selection);
+        view.ensure_cursor_in_view_center(doc, self.config.load().scrolloff);
+    }
+
     fn jump_to(&mut self, view_id: ViewId, dest_doc_id: DocumentId, mut selection: Selection) {
         let view = view_mut!(self, view_id);
         let old_doc_id = view.doc;
@@ -2604,3 +3563,59 @@ pub fn reset(&self) {
         self.0.set(None)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn directory_config_overrides_editor_table() {
+        let dir = tempfile::tempdir().unwrap();
+        let outer = dir.path().join("outer.toml");
+        let inner = dir.path().join("inner.toml");
+        std::fs::write(
+            &outer,
+            "[editor]\nrulers = [80]\ntext-width = 100\n[keys.normal]\nx = \"no_op\"\n",
+        )
+        .unwrap();
+        std::fs::write(&inner, "[editor]\nrulers = [120]\n").unwrap();
+
+        let config = Config::default();
+        let merged = load_directory_config(&config, &[outer.clone(), inner]).unwrap();
+        // closer files take precedence, other options keep the values of the editor config
+        assert_eq!(merged.rulers, [120]);
+        assert_eq!(merged.text_width, 100);
+        assert_eq!(merged.scrolloff, config.scrolloff);
+
+        std::fs::write(&outer, "[editor]\nrulers = \"80\"\n").unwrap();
+        assert!(load_directory_config(&config, &[outer]).is_err());
+    }
+
+    #[test]
+    fn mouse_hover_delay_is_read_in_milliseconds() {
+        let config = Config::default();
+        assert!(!config.mouse_hover);
+        assert_eq!(config.mouse_hover_delay, Duration::from_millis(500));
+
+        let config: Config =
+            toml::from_str("mouse-hover = true\nmouse-hover-delay = 250\n").unwrap();
+        assert!(config.mouse_hover);
+        assert_eq!(config.mouse_hover_delay, Duration::from_millis(250));
+    }
+
+    #[test]
+    fn inlay_hints_config_selects_kinds() {
+        let config: Config = toml::from_str("[lsp.inlay-hints]\ntype-hints = false\n").unwrap();
+        let inlay_hints = config.lsp.inlay_hints;
+        assert!(!inlay_hints.shows(Some(lsp::InlayHintKind::TYPE)));
+        assert!(inlay_hints.shows(Some(lsp::InlayHintKind::PARAMETER)));
+        assert!(inlay_hints.shows(None));
+
+        let inlay_hints = InlayHintsConfig {
+            other_hints: false,
+            ..Default::default()
+        };
+        assert!(inlay_hints.shows(Some(lsp::InlayHintKind::TYPE)));
+        assert!(!inlay_hints.shows(None));
+    }
+}
@@ -48,6 +48,9 @@ pub enum Variable {
     SelectionLineStart,
     // The one-indexed line number of the end of the primary selection in the currently focused document.
     SelectionLineEnd,
+    /// The directory of the currently focused document. For scratch buffers this will default to
+    /// the current working directory.
+    Dirname,
 }
 
 impl Variable {
@@ -63,6 +66,7 @@ impl Variable {
         Self::Selection,
         Self::SelectionLineStart,
         Self::SelectionLineEnd,
+        Self::Dirname,
     ];
 
     pub const fn as_str(&self) -> &'static str {
@@ -78,6 +82,7 @@ pub const fn as_str(&self) -> &'static str {
             Self::Selection => "selection",
             Self::SelectionLineStart => "selection_line_start",
             Self::SelectionLineEnd => "selection_line_end",
+            Self::Dirname => "dirname",
         }
     }
 
@@ -94,6 +99,11 @@ pub fn from_name(s: &str) -> Option<Self> {
             "selection" => Some(Self::Selection),
             "selection_line_start" => Some(Self::SelectionLineStart),
             "selection_line_end" => Some(Self::SelectionLineEnd),
+            "dirname" => Some(Self::Dirname),
+            // short aliases
+            "file" => Some(Self::BufferName),
+            "line" => Some(Self::CursorLine),
+            "column" => Some(Self::CursorColumn),
             _ => None,
         }
     }
@@ -126,7 +136,7 @@ pub fn expand<'a>(editor: &Editor, token: Token<'a>) -> Result<Cow<'a, str>> {
                 ))
             }
         }
-        TokenKind::Expand => expand_inner(editor, token.content),
+        TokenKind::Expand => expand_inner(editor, token.content, false),
         TokenKind::Expansion(ExpansionKind::Shell) => expand_shell(editor, token.content),
         TokenKind::Expansion(ExpansionKind::Register) => expand_register(editor, token.content),
         // Note: see the docs for this variant.
@@ -141,7 +151,7 @@ pub fn expand_shell<'a>(editor: &Editor, content: Cow<'a, str>) -> Result<Cow<'a
     use std::process::{Command, Stdio};
 
     // Recursively expand the expansion's content before executing the shell command.
-    let content = expand_inner(editor, content)?;
+    let content = expand_inner(editor, content, true)?;
 
     let config = editor.config();
     let shell = &config.shell;
@@ -200,10 +210,82 @@ pub fn expand_register<'a>(editor: &Editor, content: Cow<'a, str>) -> Result<Cow
     }
 }
 
-/// Expand a token's contents recursively.
-fn expand_inner<'a>(editor: &Editor, content: Cow<'a, str>) -> Result<Cow<'a, str>> {
+/// Expands a token of the command line of a shell command, see `Signature::shell`.
+///
+/// Bare variables and registers are quoted for the shell, also within the content of the
+/// token. Expansions within the quotes of the shell command are not quoted, so that
+/// `:sh echo "%{buffer_name}"` passes the same text to the shell as before quoting was added.
+/// Shell expansions are not quoted so that their output can be split into multiple words.
+pub fn expand_shell_command<'a>(editor: &Editor, token: Token<'a>) -> Result<Cow<'a, str>> {
+    match token.kind {
+        TokenKind::Expansion(ExpansionKind::Variable | ExpansionKind::Register) => {
+            Ok(shell_quote(expand(editor, token)?))
+        }
+        TokenKind::Expand => expand_inner(editor, token.content, true),
+        _ => expand(editor, token),
+    }
+}
+
+/// Quotes `value` so that the shell treats it as a single word. Values that only consist of
+/// characters without a special meaning to the shell are not quoted.
+pub fn shell_quote(value: Cow<'_, str>) -> Cow<'_, str> {
+    quote_word(value, cfg!(windows))
+}
+
+/// Quotes `value` for a POSIX shell or, if `windows` is set, for `cmd.exe`. `cmd.exe` has no
+/// escapes within double quotes, a double quote is doubled instead which programs read as a
+/// literal double quote. Note that `cmd.exe` still expands `%VAR%` within double quotes.
+fn quote_word(value: Cow<'_, str>, windows: bool) -> Cow<'_, str> {
+    let is_plain = |ch: char| ch.is_alphanumeric() || "_-+=.,:/@".contains(ch);
+    if !value.is_empty() && value.chars().all(is_plain) {
+        return value;
+    }
+    if windows {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Owned(format!("'{}'", value.replace('\'', r"'\''")))
+    }
+}
+
+/// The quotes of a shell command that the text seen so far ends in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum ShellQuote {
+    #[default]
+    None,
+    Single,
+    Double,
+}
+
+impl ShellQuote {
+    /// Returns the quotes at the end of `text` when `text` starts within the quotes `self`. If
+    /// `windows` is set the rules of `cmd.exe` are used, which only has double quotes.
+    fn after(self, text: &str, windows: bool) -> Self {
+        let mut quote = self;
+        let mut chars = text.chars();
+        while let Some(ch) = chars.next() {
+            quote = match (quote, ch) {
+                (Self::None, '"') => Self::Double,
+                (Self::Double, '"') => Self::None,
+                (Self::None, '\'') if !windows => Self::Single,
+                (Self::Single, '\'') => Self::None,
+                (Self::None | Self::Double, '\\') if !windows => {
+                    // the escaped character has no special meaning
+                    chars.next();
+                    quote
+                }
+                _ => quote,
+            };
+        }
+        quote
+    }
+}
+
+/// Expand a token's contents recursively. Variables and registers outside of the quotes of the
+/// shell command are quoted for the shell if `quote` is set.
+fn expand_inner<'a>(editor: &Editor, content: Cow<'a, str>, quote: bool) -> Result<Cow<'a, str>> {
     let mut escaped = String::new();
     let mut start = 0;
+    let mut quotes = ShellQuote::None;
 
     while let Some(offset) = content[start..].find('%') {
         let idx = start + offset;
@@ -216,6 +298,7 @@ fn expand_inner<'a>(editor: &Editor, content: Cow<'a, str>) -> Result<Cow<'a, st
             // Otherwise interpret the percent as an expansion. Push up to (but not
             // including) the percent token.
             escaped.push_str(&content[start..idx]);
+            quotes = quotes.after(&content[start..idx], cfg!(windows));
             // Then parse the expansion,
             let mut tokenizer = Tokenizer::new(&content[idx..], true);
             let token = tokenizer
@@ -223,7 +306,11 @@ fn expand_inner<'a>(editor: &Editor, content: Cow<'a, str>) -> Result<Cow<'a, st
                 .unwrap()
                 .map_err(|err| anyhow!("{err}"))?;
             // expand it (this is the recursive part),
-            let expanded = expand(editor, token)?;
+            let expanded = if quote && quotes == ShellQuote::None {
+                expand_shell_command(editor, token)?
+            } else {
+                expand(editor, token)?
+            };
             escaped.push_str(expanded.as_ref());
             // and move forward to the end of the expansion.
             start = idx + tokenizer.pos();
@@ -303,5 +390,51 @@ fn expand_variable(editor: &Editor, variable: Variable) -> Result<Cow<'static, s
             let end_line = doc.selection(view.id).primary().line_range(text).1;
             Ok(Cow::Owned((end_line + 1).to_string()))
         }
+        Variable::Dirname => {
+            let dir = match doc.path().and_then(|path| path.parent()) {
+                Some(dir) => dir.to_owned(),
+                None => helix_stdx::env::current_working_dir(),
+            };
+            Ok(Cow::Owned(dir.to_string_lossy().into_owned()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn quote_words() {
+        let quote = |value, windows| quote_word(Cow::Borrowed(value), windows).into_owned();
+        assert_eq!(quote("src/main.rs", false), "src/main.rs");
+        assert_eq!(quote("src/main.rs", true), "src/main.rs");
+        assert_eq!(quote("", false), "''");
+        assert_eq!(quote("", true), r#""""#);
+        assert_eq!(quote("my file.txt", false), "'my file.txt'");
+        assert_eq!(quote("my file.txt", true), r#""my file.txt""#);
+        assert_eq!(quote("it's", false), r"'it'\''s'");
+        assert_eq!(quote(r#"say "hi""#, false), r#"'say "hi"'"#);
+        assert_eq!(quote(r#"say "hi""#, true), r#""say ""hi""""#);
+        assert_eq!(quote("100%", true), r#""100%""#);
+        assert_eq!(quote("$HOME", false), "'$HOME'");
+    }
+
+    #[test]
+    fn track_shell_quotes() {
+        let after = |text, windows| ShellQuote::None.after(text, windows);
+        assert_eq!(after("echo ", false), ShellQuote::None);
+        assert_eq!(after("echo \"", false), ShellQuote::Double);
+        assert_eq!(after("echo \"a\" ", false), ShellQuote::None);
+        assert_eq!(after("echo '", false), ShellQuote::Single);
+        assert_eq!(after(r#"echo '\'"#, false), ShellQuote::None);
+        assert_eq!(after(r#"echo "it's "#, false), ShellQuote::Double);
+        assert_eq!(after(r#"echo \" "#, false), ShellQuote::None);
+        assert_eq!(after(r#"echo "\" "#, false), ShellQuote::Double);
+        assert_eq!(ShellQuote::Double.after(" b\"", false), ShellQuote::None);
+        // cmd.exe only has double quotes and no escapes
+        assert_eq!(after("echo '", true), ShellQuote::None);
+        assert_eq!(after(r#"echo \""#, true), ShellQuote::Double);
+        assert_eq!(after(r#"echo "it's "#, true), ShellQuote::Double);
     }
 }
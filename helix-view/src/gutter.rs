@@ -32,6 +32,7 @@ pub fn style<'doc>(
             GutterType::LineNumbers => line_numbers(editor, doc, view, theme, is_focused),
             GutterType::Spacer => padding(editor, doc, view, theme, is_focused),
             GutterType::Diff => diff(editor, doc, view, theme, is_focused),
+            GutterType::Folds => folds(editor, doc, view, theme, is_focused),
         }
     }
 
@@ -41,6 +42,7 @@ pub fn width(self, view: &View, doc: &Document) -> usize {
             GutterType::LineNumbers => line_numbers_width(view, doc),
             GutterType::Spacer => 1,
             GutterType::Diff => 1,
+            GutterType::Folds => 1,
         }
     }
 }
@@ -139,6 +141,34 @@ pub fn diff<'doc>(
     }
 }
 
+pub fn folds<'doc>(
+    _editor: &'doc Editor,
+    doc: &'doc Document,
+    view: &View,
+    theme: &Theme,
+    _is_focused: bool,
+) -> GutterFn<'doc> {
+    let style = theme.get("ui.virtual.fold");
+    let Some(folds) = doc.folds(view.id).filter(|folds| !folds.is_empty()) else {
+        return Box::new(move |_, _, _, _| None);
+    };
+    let text = doc.text().slice(..);
+    Box::new(
+        move |line: usize, _selected: bool, first_visual_line: bool, out: &mut String| {
+            if !first_visual_line || line + 1 >= text.len_lines() {
+                return None;
+            }
+            let next_line_start = text.line_to_char(line + 1);
+            folds
+                .folds()
+                .binary_search_by_key(&next_line_start, |fold| fold.start)
+                .ok()?;
+            write!(out, "▸").unwrap();
+            Some(style)
+        },
+    )
+}
+
 pub fn line_numbers<'doc>(
     editor: &'doc Editor,
     doc: &'doc Document,
@@ -264,7 +294,11 @@ pub fn breakpoints<'doc>(
                 breakpoint_style
             };
 
-            let sym = if breakpoint.verified { "●" } else { "◯" };
+            let sym = if breakpoint.is_verified() {
+                "●"
+            } else {
+                "◯"
+            };
             write!(out, "{}", sym).unwrap();
             Some(style)
         },
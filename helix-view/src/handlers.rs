@@ -11,6 +11,19 @@
 pub mod lsp;
 pub mod word_index;
 
+/// Requests spell checking a document.
+pub struct SpellCheckEvent(pub DocumentId);
+
+/// Requests (re)loading the blame of a document.
+pub struct BlameEvent(pub DocumentId);
+
+/// Requests writing the recovery file of a modified document, see [crate::recovery].
+pub struct RecoveryEvent(pub DocumentId);
+
+/// Requests updating the outdated syntax tree of a document in the background, see
+/// [crate::Document::start_syntax_update].
+pub struct SyntaxEvent(pub DocumentId);
+
 #[derive(Debug)]
 pub enum AutoSaveEvent {
     DocumentChanged { save_after: u64 },
@@ -24,6 +37,12 @@ pub struct Handlers {
     pub auto_save: Sender<AutoSaveEvent>,
     pub document_colors: Sender<lsp::DocumentColorsEvent>,
     pub document_links: Sender<lsp::DocumentLinksEvent>,
+    pub semantic_tokens: Sender<lsp::SemanticTokensEvent>,
+    pub inline_completions: Sender<lsp::InlineCompletionEvent>,
+    pub spell_check: Sender<SpellCheckEvent>,
+    pub blame: Sender<BlameEvent>,
+    pub recovery: Sender<RecoveryEvent>,
+    pub syntax: Sender<SyntaxEvent>,
     pub word_index: word_index::Handler,
     pub pull_diagnostics: Sender<lsp::PullDiagnosticsEvent>,
     pub pull_all_documents_diagnostics: Sender<lsp::PullAllDocumentsDiagnosticsEvent>,
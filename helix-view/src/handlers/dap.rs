@@ -1,4 +1,4 @@
-use crate::editor::{Action, Breakpoint};
+use crate::editor::{Action, Breakpoint, SessionBreakpoint};
 use crate::{align_view, Align, Editor};
 use anyhow::bail;
 use dap::requests::DisconnectArguments;
@@ -125,17 +125,7 @@ pub fn breakpoints_changed(
     let request = debugger.set_breakpoints(path, source_breakpoints);
     match block_on(request) {
         Ok(Some(dap_breakpoints)) => {
-            for (breakpoint, dap_breakpoint) in breakpoints.iter_mut().zip(dap_breakpoints) {
-                breakpoint.id = dap_breakpoint.id;
-                breakpoint.verified = dap_breakpoint.verified;
-                breakpoint.message = dap_breakpoint.message;
-                // TODO: handle breakpoint.message
-                // TODO: verify source matches
-                breakpoint.line = dap_breakpoint.line.unwrap_or(0).saturating_sub(1); // convert to 0-indexing
-                                                                                      // TODO: no unwrap
-                breakpoint.column = dap_breakpoint.column;
-                // TODO: verify end_linef/col instruction reference, offset
-            }
+            update_breakpoints(debugger.id(), breakpoints, dap_breakpoints);
         }
         Err(e) => anyhow::bail!("Failed to set breakpoints: {}", e),
         _ => {}
@@ -143,7 +133,107 @@ pub fn breakpoints_changed(
     Ok(())
 }
 
+/// Records the breakpoints of the `setBreakpoints` response of `session`, which are in the same
+/// order as the breakpoints that were sent.
+fn update_breakpoints(
+    session: DebugAdapterId,
+    breakpoints: &mut [Breakpoint],
+    dap_breakpoints: Vec<dap::Breakpoint>,
+) {
+    for (breakpoint, dap_breakpoint) in breakpoints.iter_mut().zip(dap_breakpoints) {
+        breakpoint.sessions.insert(
+            session,
+            SessionBreakpoint {
+                id: dap_breakpoint.id,
+                verified: dap_breakpoint.verified,
+                // convert to 0-indexing
+                line: dap_breakpoint.line.map(|line| line.saturating_sub(1)),
+            },
+        );
+        if dap_breakpoint.message.is_some() {
+            breakpoint.message = dap_breakpoint.message;
+        }
+        // TODO: verify source matches
+    }
+}
+
+/// Applies a `changed` breakpoint event of `session` to the breakpoint that has the id
+/// `breakpoint_id` in that session.
+fn update_changed_breakpoint(
+    breakpoints: &mut [Breakpoint],
+    session: DebugAdapterId,
+    breakpoint_id: usize,
+    dap_breakpoint: &dap::Breakpoint,
+) {
+    let Some(breakpoint) = Breakpoint::find_mut(breakpoints, session, breakpoint_id) else {
+        return;
+    };
+    if let Some(message) = &dap_breakpoint.message {
+        breakpoint.message = Some(message.clone());
+    }
+    breakpoint.column = dap_breakpoint.column.or(breakpoint.column);
+    if let Some(state) = breakpoint.sessions.get_mut(&session) {
+        state.verified = dap_breakpoint.verified;
+        if let Some(line) = dap_breakpoint.line {
+            state.line = Some(line.saturating_sub(1));
+        }
+    }
+}
+
+/// Applies a `removed` breakpoint event of `session`: the breakpoint that has the id
+/// `breakpoint_id` in that session forgets its state in the session, like when the session is
+/// terminated. The breakpoint itself and its state in the other sessions are kept.
+fn remove_session_breakpoint(
+    breakpoints: &mut [Breakpoint],
+    session: DebugAdapterId,
+    breakpoint_id: usize,
+) {
+    if let Some(breakpoint) = Breakpoint::find_mut(breakpoints, session, breakpoint_id) {
+        breakpoint.sessions.remove(&session);
+    }
+}
+
 impl Editor {
+    /// Sends the breakpoints of `path` to every debugging session. A session that fails doesn't
+    /// keep the breakpoints from the other sessions, the errors of all sessions are returned
+    /// together.
+    pub fn sync_breakpoints(&mut self, path: PathBuf) -> Result<(), anyhow::Error> {
+        let Some(breakpoints) = self.breakpoints.get_mut(&path) else {
+            return Ok(());
+        };
+        let several = self.debug_adapters.len() > 1;
+        let mut errors = Vec::new();
+        for (_, debugger) in self.debug_adapters.iter_mut() {
+            if let Err(err) = breakpoints_changed(debugger, path.clone(), breakpoints) {
+                match &debugger.name {
+                    Some(name) if several => errors.push(format!("{name}: {err}")),
+                    _ => errors.push(err.to_string()),
+                }
+            }
+        }
+        if !errors.is_empty() {
+            bail!("{}", errors.join(", "));
+        }
+        Ok(())
+    }
+
+    /// Evaluates the watch expressions in the current stack frame of the active debugger.
+    pub async fn refresh_debug_watches(&mut self) {
+        let Some(debugger) = self.debug_adapters.get_active_client() else {
+            return;
+        };
+        let frame_id = debugger.current_stack_frame().map(|frame| frame.id);
+        for watch in &mut self.debug_watches {
+            watch.value = Some(
+                debugger
+                    .watch(watch.expression.clone(), frame_id)
+                    .await
+                    .map(|response| response.result)
+                    .map_err(|err| err.to_string()),
+            );
+        }
+    }
+
     pub async fn handle_debugger_message(
         &mut self,
         id: DebugAdapterId,
@@ -173,6 +263,8 @@ pub async fn handle_debugger_message(
                         all_threads_stopped,
                         ..
                     }) => {
+                        // the session that stopped becomes the one shown
+                        self.debug_adapters.set_active_client(id);
                         let debugger = match self.debug_adapters.get_client_mut(id) {
                             Some(debugger) => debugger,
                             None => return false,
@@ -214,6 +306,7 @@ pub async fn handle_debugger_message(
                             status.push_str(" (all threads stopped)");
                         }
 
+                        self.refresh_debug_watches().await;
                         self.set_status(status);
                     }
                     Event::Continued(events::ContinuedBody { thread_id, .. }) => {
@@ -251,9 +344,13 @@ pub async fn handle_debugger_message(
                                         warn!("DAP breakpoint event missing line");
                                         return false;
                                     };
-                                    self.breakpoints.entry(path).or_default().push(Breakpoint {
+                                    let session = SessionBreakpoint {
                                         id: breakpoint.id,
                                         verified: breakpoint.verified,
+                                        line: None,
+                                    };
+                                    self.breakpoints.entry(path).or_default().push(Breakpoint {
+                                        sessions: [(id, session)].into(),
                                         message: breakpoint.message.clone(),
                                         line: line.saturating_sub(1),
                                         column: breakpoint.column,
@@ -265,7 +362,7 @@ pub async fn handle_debugger_message(
                                 }
                             }
                             "changed" => {
-                                let Some(id) = breakpoint.id else {
+                                let Some(breakpoint_id) = breakpoint.id else {
                                     warn!("DAP breakpoint event missing id");
                                     return false;
                                 };
@@ -278,37 +375,24 @@ pub async fn handle_debugger_message(
                                     warn!("DAP breakpoint event missing line");
                                 }
                                 for breakpoints in self.breakpoints.values_mut() {
-                                    if let Some(i) =
-                                        breakpoints.iter().position(|b| b.id == Some(id))
-                                    {
-                                        breakpoints[i].verified = breakpoint.verified;
-                                        breakpoints[i].message = breakpoint
-                                            .message
-                                            .clone()
-                                            .or_else(|| breakpoints[i].message.take());
-                                        breakpoints[i].line =
-                                            breakpoint.line.map_or(breakpoints[i].line, |line| {
-                                                line.saturating_sub(1)
-                                            });
-                                        breakpoints[i].column =
-                                            breakpoint.column.or(breakpoints[i].column);
-                                    }
+                                    update_changed_breakpoint(
+                                        breakpoints,
+                                        id,
+                                        breakpoint_id,
+                                        &breakpoint,
+                                    );
                                 }
                                 if let Some(message) = &breakpoint.message {
                                     self.set_status(format!("Breakpoint: {}", message));
                                 }
                             }
                             "removed" => {
-                                let Some(id) = breakpoint.id else {
+                                let Some(breakpoint_id) = breakpoint.id else {
                                     warn!("DAP breakpoint event missing id");
                                     return false;
                                 };
                                 for breakpoints in self.breakpoints.values_mut() {
-                                    if let Some(i) =
-                                        breakpoints.iter().position(|b| b.id == Some(id))
-                                    {
-                                        breakpoints.remove(i);
-                                    }
+                                    remove_session_breakpoint(breakpoints, id, breakpoint_id);
                                 }
                             }
                             reason => {
@@ -427,16 +511,15 @@ pub async fn handle_debugger_message(
                         match restart_arg {
                             Some(Value::Bool(false)) | None => {
                                 self.debug_adapters.remove_client(id);
-                                self.debug_adapters.unset_active_client();
                                 self.set_status(
                                     "Terminated debugging session and disconnected debugger.",
                                 );
 
-                                // Go through all breakpoints and set verfified to false
-                                // this should update the UI to show the breakpoints are no longer connected
+                                // forget the state of the breakpoints in the session, which
+                                // shows them as unverified once no session verifies them
                                 for breakpoints in self.breakpoints.values_mut() {
                                     for breakpoint in breakpoints.iter_mut() {
-                                        breakpoint.verified = false;
+                                        breakpoint.sessions.remove(&id);
                                     }
                                 }
                             }
@@ -584,3 +667,95 @@ pub async fn handle_debugger_message(
         true
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use slotmap::{Key, KeyData};
+
+    fn session(n: u64) -> DebugAdapterId {
+        DebugAdapterId::from(KeyData::from_ffi((1 << 32) | n))
+    }
+
+    fn dap_breakpoint(id: usize, verified: bool, line: usize) -> dap::Breakpoint {
+        dap::Breakpoint {
+            id: Some(id),
+            verified,
+            message: None,
+            source: None,
+            line: Some(line),
+            column: None,
+            end_line: None,
+            end_column: None,
+            instruction_reference: None,
+            offset: None,
+        }
+    }
+
+    #[test]
+    fn breakpoints_are_kept_per_session() {
+        let (first, second) = (session(1), session(2));
+        assert!(!first.is_null() && first != second);
+        let mut breakpoints = vec![
+            Breakpoint {
+                line: 4,
+                ..Default::default()
+            },
+            Breakpoint {
+                line: 9,
+                ..Default::default()
+            },
+        ];
+
+        // both adapters number their breakpoints from 1
+        update_breakpoints(
+            first,
+            &mut breakpoints,
+            vec![dap_breakpoint(1, true, 5), dap_breakpoint(2, false, 10)],
+        );
+        update_breakpoints(
+            second,
+            &mut breakpoints,
+            vec![dap_breakpoint(2, false, 6), dap_breakpoint(1, true, 12)],
+        );
+        assert_eq!(
+            breakpoints[0].sessions[&first],
+            SessionBreakpoint {
+                id: Some(1),
+                verified: true,
+                line: Some(4),
+            }
+        );
+        assert_eq!(breakpoints[0].sessions[&second].id, Some(2));
+        assert_eq!(breakpoints[0].sessions[&second].line, Some(5));
+        assert_eq!(breakpoints[1].sessions[&second].line, Some(11));
+        assert!(breakpoints[0].is_verified());
+        assert!(breakpoints[1].is_verified());
+        // the line set by the user is not moved by either adapter
+        assert_eq!(breakpoints[0].line, 4);
+
+        // ids are looked up in the session that sent the event
+        update_changed_breakpoint(&mut breakpoints, second, 1, &dap_breakpoint(1, false, 12));
+        assert!(breakpoints[0].sessions[&first].verified);
+        assert!(!breakpoints[1].sessions[&second].verified);
+        assert!(!breakpoints[1].is_verified());
+        assert_eq!(
+            Breakpoint::find_mut(&mut breakpoints, first, 2).map(|b| b.line),
+            Some(9)
+        );
+
+        // a breakpoint removed by one session is kept with the state of the other session
+        remove_session_breakpoint(&mut breakpoints, second, 2);
+        assert_eq!(breakpoints.len(), 2);
+        assert!(!breakpoints[0].sessions.contains_key(&second));
+        assert!(breakpoints[0].sessions[&first].verified);
+        assert!(breakpoints[1].sessions.contains_key(&second));
+
+        // a terminated session leaves the state of the other session
+        for breakpoint in &mut breakpoints {
+            breakpoint.sessions.remove(&second);
+        }
+        assert!(breakpoints[0].is_verified());
+        assert_eq!(breakpoints[0].sessions.len(), 1);
+    }
+}
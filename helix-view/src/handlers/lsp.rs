@@ -1,12 +1,13 @@
 use std::collections::btree_map::Entry;
 use std::collections::HashSet;
 use std::fmt::Display;
+use std::path::Path;
 
 use crate::editor::Action;
 use crate::events::{
     DiagnosticsDidChange, DocumentDidChange, DocumentDidClose, LanguageServerInitialized,
 };
-use crate::{DocumentId, Editor};
+use crate::{DocumentId, Editor, ViewId};
 use helix_core::diagnostic::DiagnosticProvider;
 use helix_core::Uri;
 use helix_event::register_hook;
@@ -17,6 +18,12 @@
 
 pub struct DocumentColorsEvent(pub DocumentId);
 pub struct DocumentLinksEvent(pub DocumentId);
+pub struct SemanticTokensEvent(pub DocumentId);
+
+pub struct InlineCompletionEvent {
+    pub doc: DocumentId,
+    pub view: ViewId,
+}
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum SignatureHelpInvoked {
@@ -79,6 +86,13 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     }
 }
 
+fn already_exists(path: &Path) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::AlreadyExists,
+        format!("{} already exists", path.display()),
+    )
+}
+
 impl Editor {
     fn apply_text_edits(
         &mut self,
@@ -236,11 +250,28 @@ fn apply_document_resource_op(
             ResourceOp::Create(op) => {
                 let uri = Uri::try_from(&op.uri)?;
                 let path = uri.as_path().expect("URIs are valid paths");
-                let ignore_if_exists = op.options.as_ref().is_some_and(|options| {
-                    !options.overwrite.unwrap_or(false) && options.ignore_if_exists.unwrap_or(false)
-                });
-                if !ignore_if_exists || !path.exists() {
-                    self.create_path(path, false)?;
+                let overwrite = op
+                    .options
+                    .as_ref()
+                    .is_some_and(|options| options.overwrite.unwrap_or(false));
+                let ignore_if_exists = op
+                    .options
+                    .as_ref()
+                    .is_some_and(|options| options.ignore_if_exists.unwrap_or(false));
+                if path.exists() && !overwrite {
+                    if ignore_if_exists {
+                        return Ok(());
+                    }
+                    return Err(already_exists(path).into());
+                }
+                // the buffer of an overwritten file is reloaded below
+                self.ensure_unmodified(path)?;
+                self.create_path(path, false)?;
+                // an overwritten file may already be open, don't keep its stale contents
+                if let Some(doc_id) = self.document_by_path(path).map(|doc| doc.id()) {
+                    if let Err(err) = self.reload_document(doc_id) {
+                        log::error!("failed to reload {path:?} after creating it: {err}");
+                    }
                 }
             }
             ResourceOp::Delete(op) => {
@@ -265,12 +296,30 @@ fn apply_document_resource_op(
                 let from = from_uri.as_path().expect("URIs are valid paths");
                 let to_uri = Uri::try_from(&op.new_uri)?;
                 let to = to_uri.as_path().expect("URIs are valid paths");
-                let ignore_if_exists = op.options.as_ref().is_some_and(|options| {
-                    !options.overwrite.unwrap_or(false) && options.ignore_if_exists.unwrap_or(false)
-                });
-                if !ignore_if_exists || !to.exists() {
-                    self.move_path(from, to)?;
+                let overwrite = op
+                    .options
+                    .as_ref()
+                    .is_some_and(|options| options.overwrite.unwrap_or(false));
+                let ignore_if_exists = op
+                    .options
+                    .as_ref()
+                    .is_some_and(|options| options.ignore_if_exists.unwrap_or(false));
+                if from != to && to.exists() && !overwrite {
+                    if ignore_if_exists {
+                        return Ok(());
+                    }
+                    return Err(already_exists(to).into());
+                }
+                // the renamed document replaces the one open at the destination
+                if let Some(doc_id) = self
+                    .document_by_path(to)
+                    .filter(|_| from != to)
+                    .map(|doc| doc.id())
+                {
+                    self.ensure_unmodified(to)?;
+                    let _ = self.close_document(doc_id, true);
                 }
+                self.move_path(from, to)?;
             }
         }
         Ok(())
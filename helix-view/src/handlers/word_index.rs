@@ -1,9 +1,17 @@
 //! Indexing of words from open buffers.
 //!
 //! This provides an eventually consistent set of words used in any open buffers. This set is
-//! later used for lexical completion.
-
-use std::{borrow::Cow, collections::HashMap, iter, mem, sync::Arc, time::Duration};
+//! later used for lexical completion, together with the words of the dictionaries and tags files
+//! configured in [WordCompletion].
+
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    iter, mem,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
 use helix_core::{
     chars::char_is_word, fuzzy::fuzzy_match, movement, ChangeSet, Range, Rope, RopeSlice,
@@ -14,6 +22,7 @@
 use tokio::{sync::mpsc, time::Instant};
 
 use crate::{
+    editor::WordCompletion,
     events::{ConfigDidChange, DocumentDidChange, DocumentDidClose, DocumentDidOpen},
     DocumentId,
 };
@@ -32,6 +41,11 @@ enum Event {
     Insert(Rope),
     Update(DocumentId, Change),
     Delete(DocumentId, Rope),
+    /// Replace the words of the dictionaries and tags files with the words of these files.
+    LoadFiles {
+        dictionaries: Vec<PathBuf>,
+        tags: Vec<PathBuf>,
+    },
     /// Clear the entire word index.
     /// This is used to clear memory when the feature is turned off.
     Clear,
@@ -51,10 +65,13 @@ pub struct Handler {
 }
 
 impl Handler {
-    pub fn spawn() -> Self {
+    pub fn spawn(config: &WordCompletion) -> Self {
         let index = WordIndex::default();
         let (tx, rx) = mpsc::unbounded_channel();
         tokio::spawn(index.clone().run(rx));
+        if config.enable {
+            tx.send(Event::load_files(config)).unwrap();
+        }
         Self {
             hook: Hook {
                 changes: HashMap::default(),
@@ -67,6 +84,15 @@ pub fn spawn() -> Self {
     }
 }
 
+impl Event {
+    fn load_files(config: &WordCompletion) -> Self {
+        Event::LoadFiles {
+            dictionaries: config.dictionaries.clone(),
+            tags: config.tags.clone(),
+        }
+    }
+}
+
 #[derive(Debug)]
 struct Hook {
     changes: HashMap<DocumentId, Change>,
@@ -111,7 +137,9 @@ fn handle_event(&mut self, event: Self::Event, timeout: Option<Instant>) -> Opti
                 }
                 timeout
             }
-            Event::Clear => unreachable!("clear is sent to the worker directly"),
+            Event::LoadFiles { .. } | Event::Clear => {
+                unreachable!("files and clears are sent to the worker directly")
+            }
         }
     }
 
@@ -129,6 +157,17 @@ fn finish_debounce(&mut self) {
 
 type Word = kstring::KString;
 
+/// Where an indexed word was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WordSource {
+    /// An open buffer.
+    Buffer,
+    /// One of the configured dictionaries.
+    Dictionary,
+    /// One of the configured tags files.
+    Tags,
+}
+
 #[derive(Debug, Default)]
 struct WordIndexInner {
     /// Reference counted storage for words.
@@ -137,6 +176,8 @@ struct WordIndexInner {
     /// reference count of times a word is used. When the reference count drops to zero the word
     /// is removed from the index.
     words: HashMap<Word, u32>,
+    /// The words of the dictionaries and tags files, which don't change with the buffers.
+    files: HashMap<Word, WordSource>,
 }
 
 impl WordIndexInner {
@@ -170,6 +211,16 @@ fn remove(&mut self, word: RopeSlice) {
 
     fn clear(&mut self) {
         std::mem::take(&mut self.words);
+        std::mem::take(&mut self.files);
+    }
+}
+
+/// A candidate of [WordIndex::matches].
+struct Candidate<'a>(&'a Word, WordSource);
+
+impl AsRef<str> for Candidate<'_> {
+    fn as_ref(&self) -> &str {
+        self.0
     }
 }
 
@@ -179,13 +230,23 @@ pub struct WordIndex {
 }
 
 impl WordIndex {
-    pub fn matches(&self, pattern: &str) -> Vec<String> {
+    /// Returns the words that match `pattern` with their source. Words that are both in a buffer
+    /// and in a file are only returned once, as buffer words.
+    pub fn matches(&self, pattern: &str) -> Vec<(String, WordSource)> {
         let inner = self.inner.read();
-        let mut matches = fuzzy_match(pattern, inner.words(), false);
+        let buffer_words = inner
+            .words()
+            .map(|word| Candidate(word, WordSource::Buffer));
+        let file_words = inner
+            .files
+            .iter()
+            .filter(|(word, _)| !inner.words.contains_key(*word))
+            .map(|(word, source)| Candidate(word, *source));
+        let mut matches = fuzzy_match(pattern, buffer_words.chain(file_words), false);
         matches.sort_unstable_by_key(|(_, score)| *score);
         matches
             .into_iter()
-            .map(|(word, _)| word.to_string())
+            .map(|(Candidate(word, source), _)| (word.to_string(), source))
             .collect()
     }
 
@@ -221,6 +282,36 @@ fn clear(&self) {
         inner.clear();
     }
 
+    fn load_files(&self, dictionaries: &[PathBuf], tags: &[PathBuf]) {
+        let mut files = HashMap::new();
+        let workspace = helix_loader::find_workspace().0;
+        let read = |path: &Path| {
+            // relative paths like `tags` refer to the files of the workspace
+            let path = workspace.join(helix_stdx::path::expand_tilde(path));
+            std::fs::read_to_string(&path)
+                .inspect_err(|err| {
+                    log::error!(
+                        "failed to read word completion file {}: {err}",
+                        path.display()
+                    )
+                })
+                .ok()
+        };
+        for text in dictionaries.iter().filter_map(|path| read(path)) {
+            for word in dictionary_words(&text) {
+                files.insert(Word::from_ref(word), WordSource::Dictionary);
+            }
+        }
+        for text in tags.iter().filter_map(|path| read(path)) {
+            for word in tag_words(&text) {
+                files
+                    .entry(Word::from_ref(word))
+                    .or_insert(WordSource::Tags);
+            }
+        }
+        self.inner.write().files = files;
+    }
+
     /// Coordinate the indexing of documents.
     ///
     /// This task wraps a MPSC queue and spawns blocking tasks which update the index. Updates
@@ -247,6 +338,9 @@ async fn run(self, mut events: mpsc::UnboundedReceiver<Event>) {
                 Event::Delete(_doc, text) => {
                     this.remove_document(&text);
                 }
+                Event::LoadFiles { dictionaries, tags } => {
+                    this.load_files(&dictionaries, &tags);
+                }
                 Event::Clear => {
                     this.clear();
                 }
@@ -302,6 +396,30 @@ fn words(text: RopeSlice) -> impl Iterator<Item = RopeSlice> {
     })
 }
 
+/// Whether a word of a dictionary or tags file is completed, like the words of [words].
+fn is_file_word(word: &str) -> bool {
+    word.chars().all(char_is_word)
+        && (MIN_WORD_GRAPHEMES..=MAX_WORD_LEN).contains(&word.chars().count())
+}
+
+/// The words of a dictionary: one word per line, like `/usr/share/dict/words`, optionally
+/// followed by `/` and affix flags like in Hunspell's `.dic` files.
+fn dictionary_words(text: &str) -> impl Iterator<Item = &str> {
+    text.lines()
+        .filter_map(|line| line.split(['/', '\t']).next())
+        .map(str::trim)
+        .filter(|word| is_file_word(word))
+}
+
+/// The tag names of a ctags `tags` file: the first field of each line, except for the
+/// `!_TAG_` pseudo tags.
+fn tag_words(text: &str) -> impl Iterator<Item = &str> {
+    text.lines()
+        .filter(|line| !line.starts_with("!_"))
+        .filter_map(|line| line.split('\t').next())
+        .filter(|word| is_file_word(word))
+}
+
 /// Finds areas of the old and new texts around each operation in `changes`.
 ///
 /// The window is larger than the changed area and can encompass multiple insert/delete operations
@@ -415,13 +533,14 @@ pub(crate) fn register_hooks(handlers: &Handlers) {
 
     let coordinator = handlers.word_index.coordinator.clone();
     register_hook!(move |event: &mut ConfigDidChange<'_>| {
+        let (old, new) = (&event.old.word_completion, &event.new.word_completion);
         // The feature has been turned off. Clear the index and reclaim any used memory.
-        if event.old.word_completion.enable && !event.new.word_completion.enable {
+        if old.enable && !new.enable {
             coordinator.send(Event::Clear).unwrap();
         }
 
         // The feature has been turned on. Index open documents.
-        if !event.old.word_completion.enable && event.new.word_completion.enable {
+        if !old.enable && new.enable {
             for doc in event.editor.documents() {
                 if doc.word_completion_enabled() {
                     coordinator.send(Event::Insert(doc.text().clone())).unwrap();
@@ -429,6 +548,12 @@ pub(crate) fn register_hooks(handlers: &Handlers) {
             }
         }
 
+        if new.enable
+            && (!old.enable || old.dictionaries != new.dictionaries || old.tags != new.tags)
+        {
+            coordinator.send(Event::load_files(new)).unwrap();
+        }
+
         Ok(())
     });
 }
@@ -506,4 +631,34 @@ fn diff() {
         assert_diff("one two three", "one t{o three", ["two"], []);
         assert_diff("one foo three", "one fooo three", ["foo"], ["fooo"]);
     }
+
+    #[test]
+    fn files() {
+        let dictionary = "3\nhello/MS\nworld\nab\n";
+        assert_eq!(
+            dictionary_words(dictionary).collect::<Vec<_>>(),
+            ["hello", "world"]
+        );
+        let tags = "!_TAG_FILE_FORMAT\t2\t//\nhelper_fn\tsrc/lib.rs\t/^fn helper_fn() {$/;\"\tf\n";
+        assert_eq!(tag_words(tags).collect::<Vec<_>>(), ["helper_fn"]);
+
+        let index = WordIndex::default();
+        index.add_document(&Rope::from_str("hello there"));
+        index.inner.write().files.extend(
+            [
+                ("hello", WordSource::Dictionary),
+                ("help", WordSource::Tags),
+            ]
+            .map(|(word, source)| (Word::from_ref(word), source)),
+        );
+        let mut matches = index.matches("hel");
+        matches.sort_by(|(a, _), (b, _)| a.cmp(b));
+        assert_eq!(
+            matches,
+            [
+                ("hello".to_string(), WordSource::Buffer),
+                ("help".to_string(), WordSource::Tags)
+            ]
+        );
+    }
 }
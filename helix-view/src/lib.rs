@@ -1,7 +1,9 @@
 #[macro_use]
 pub mod macros;
 
+pub mod animation;
 pub mod annotations;
+pub mod buffer_history;
 pub mod clipboard;
 pub mod document;
 pub mod editor;
@@ -13,9 +15,15 @@
 pub mod info;
 pub mod input;
 pub mod keyboard;
+pub mod marks;
+pub mod quickfix;
+pub mod recovery;
 pub mod register;
+pub mod remote;
+pub mod session;
 pub mod theme;
 pub mod tree;
+pub mod undo_file;
 pub mod view;
 
 use std::num::NonZeroUsize;
@@ -0,0 +1,302 @@
+//! Named marks that record a position in a file. Unlike the jumplists, marks are shared by all
+//! views, and they are persisted across sessions in [helix_loader::marks_file].
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use helix_core::line_ending::line_end_char_index;
+use helix_core::{Assoc, Position};
+use serde::{Deserialize, Serialize};
+
+use crate::{Document, DocumentId};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum MarkLocation {
+    /// A char position in an open document at a revision of the document's history.
+    Document {
+        doc: DocumentId,
+        pos: usize,
+        revision: usize,
+    },
+    /// A position in a file that isn't open.
+    File(Position),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mark {
+    path: PathBuf,
+    location: MarkLocation,
+}
+
+impl Mark {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the position of the mark in `doc`, mapped through the changes made to `doc` since
+    /// the mark was set.
+    pub fn pos(&self, doc: &mut Document) -> usize {
+        let pos = match self.location {
+            MarkLocation::Document {
+                doc: doc_id,
+                pos,
+                revision,
+            } if doc_id == doc.id() => match doc.history.get_mut().changes_since(revision) {
+                Some(transaction) => transaction.changes().map_pos(pos, Assoc::Before),
+                None => pos,
+            },
+            MarkLocation::Document { pos, .. } => pos,
+            MarkLocation::File(position) => {
+                let text = doc.text().slice(..);
+                let line = position.row.min(text.len_lines().saturating_sub(1));
+                let line_start = text.line_to_char(line);
+                let line_end = line_end_char_index(&text, line);
+                (line_start + position.col).min(line_end)
+            }
+        };
+        pos.min(doc.text().len_chars())
+    }
+
+    /// Returns the line and column of the mark, `doc` must be the document of the mark's file if
+    /// it is open.
+    pub fn position(&self, doc: Option<&mut Document>) -> Position {
+        match (&self.location, doc) {
+            (_, Some(doc)) => {
+                let pos = self.pos(doc);
+                let text = doc.text().slice(..);
+                let line = text.char_to_line(pos);
+                Position::new(line, pos - text.line_to_char(line))
+            }
+            (MarkLocation::File(position), None) => *position,
+            (MarkLocation::Document { .. }, None) => Position::default(),
+        }
+    }
+}
+
+/// The form of a mark in [helix_loader::marks_file].
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct SavedMark {
+    path: PathBuf,
+    line: usize,
+    column: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct Marks {
+    marks: BTreeMap<char, Mark>,
+    /// The marks removed by this instance, they are removed from [helix_loader::marks_file] as
+    /// well when saving.
+    removed: HashSet<char>,
+}
+
+impl Marks {
+    /// Sets the mark `name` to `pos` in `doc`. Returns `false` if `doc` isn't backed by a file.
+    pub fn set(&mut self, name: char, doc: &mut Document, pos: usize) -> bool {
+        let Some(path) = doc.path().map(Path::to_path_buf) else {
+            return false;
+        };
+        let location = MarkLocation::Document {
+            doc: doc.id(),
+            pos,
+            revision: doc.get_current_revision(),
+        };
+        self.marks.insert(name, Mark { path, location });
+        self.removed.remove(&name);
+        true
+    }
+
+    pub fn get(&self, name: char) -> Option<&Mark> {
+        self.marks.get(&name)
+    }
+
+    pub fn remove(&mut self, name: char) -> Option<Mark> {
+        let mark = self.marks.remove(&name)?;
+        self.removed.insert(name);
+        Some(mark)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (char, &Mark)> {
+        self.marks.iter().map(|(name, mark)| (*name, mark))
+    }
+
+    /// Ties the marks in the file of a document that was just opened to the document, so that
+    /// they follow the changes made to it.
+    pub fn attach(&mut self, doc: &mut Document) {
+        let Some(path) = doc.path().map(Path::to_path_buf) else {
+            return;
+        };
+        let revision = doc.get_current_revision();
+        for mark in self
+            .marks
+            .values_mut()
+            .filter(|mark| helix_stdx::path::same_file(&mark.path, &path))
+        {
+            let pos = mark.pos(doc);
+            mark.location = MarkLocation::Document {
+                doc: doc.id(),
+                pos,
+                revision,
+            };
+        }
+    }
+
+    /// Records the line and column of the marks in a document that is closed or saved to
+    /// [helix_loader::marks_file].
+    pub fn detach(&mut self, doc: &mut Document) {
+        let doc_id = doc.id();
+        for mark in self.marks.values_mut() {
+            if matches!(mark.location, MarkLocation::Document { doc, .. } if doc == doc_id) {
+                mark.location = MarkLocation::File(mark.position(Some(&mut *doc)));
+            }
+        }
+    }
+
+    /// Restores the marks saved by [Marks::save] from [helix_loader::marks_file]. A missing file
+    /// is not an error.
+    pub fn load(&mut self) -> Result<()> {
+        self.load_from(&helix_loader::marks_file())
+    }
+
+    fn load_from(&mut self, path: &Path) -> Result<()> {
+        for (name, mark) in read_marks(path)? {
+            let location = MarkLocation::File(Position::new(mark.line, mark.column));
+            self.marks.entry(name).or_insert(Mark {
+                path: mark.path,
+                location,
+            });
+        }
+        Ok(())
+    }
+
+    /// Saves the marks to [helix_loader::marks_file]. The marks in open documents must have been
+    /// detached with [Marks::detach] first. Other instances may have saved marks in the
+    /// meantime: the saved marks are kept unless this instance set or removed a mark with the
+    /// same name.
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&helix_loader::marks_file())
+    }
+
+    fn save_to(&self, path: &Path) -> Result<()> {
+        // a corrupted file is replaced instead of failing every time
+        let mut marks = read_marks(path).unwrap_or_default();
+        marks.retain(|name, _| !self.removed.contains(name));
+        for (name, mark) in &self.marks {
+            if let MarkLocation::File(position) = mark.location {
+                marks.insert(
+                    *name,
+                    SavedMark {
+                        path: mark.path.clone(),
+                        line: position.row,
+                        column: position.col,
+                    },
+                );
+            }
+        }
+        helix_loader::write_state_file(path, serde_json::to_string(&marks)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+fn read_marks(path: &Path) -> Result<BTreeMap<char, SavedMark>> {
+    match fs::read_to_string(path) {
+        Ok(json) => Ok(serde_json::from_str(&json)?),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use arc_swap::ArcSwap;
+    use helix_core::{syntax, Rope, Selection, Transaction};
+
+    use super::*;
+    use crate::editor::{Config, GutterConfig};
+    use crate::View;
+
+    #[test]
+    fn marks_follow_changes() {
+        let mut doc = Document::from(
+            Rope::from("one\ntwo\nthree\n"),
+            None,
+            Arc::new(ArcSwap::new(Arc::new(Config::default()))),
+            Arc::new(ArcSwap::from_pointee(syntax::Loader::default())),
+        );
+        let mut view = View::new(doc.id(), GutterConfig::default());
+        doc.set_selection(view.id, Selection::point(0));
+        let mut marks = Marks::default();
+        // scratch buffers can't be marked
+        assert!(!marks.set('A', &mut doc, 4));
+        doc.set_path(Some(Path::new("marks.txt")));
+        assert!(marks.set('A', &mut doc, 5));
+
+        let transaction = Transaction::insert(doc.text(), &Selection::point(0), "zero\n".into());
+        doc.apply(&transaction, view.id);
+        doc.append_changes_to_history(&mut view);
+        assert_eq!(marks.get('A').unwrap().pos(&mut doc), 10);
+
+        // closing the document keeps the line and column of the mark
+        marks.detach(&mut doc);
+        let mark = marks.get('A').unwrap();
+        assert_eq!(mark.position(None), Position::new(2, 1));
+        marks.attach(&mut doc);
+        assert_eq!(marks.get('A').unwrap().pos(&mut doc), 10);
+    }
+
+    #[test]
+    fn saving_marks_keeps_the_marks_of_other_instances() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join("marks.json");
+        let saved = |path: &str, line| SavedMark {
+            path: PathBuf::from(path),
+            line,
+            column: 0,
+        };
+        let set = |marks: &mut Marks, name, path: &str, line| {
+            marks.marks.insert(
+                name,
+                Mark {
+                    path: PathBuf::from(path),
+                    location: MarkLocation::File(Position::new(line, 0)),
+                },
+            );
+            marks.removed.remove(&name);
+        };
+
+        let mut first = Marks::default();
+        first.load_from(&path).unwrap();
+        set(&mut first, 'A', "a.txt", 1);
+        set(&mut first, 'Q', "a.txt", 2);
+        first.save_to(&path).unwrap();
+
+        // another instance started before the first one saved its marks
+        let mut second = Marks::default();
+        set(&mut second, 'B', "b.txt", 3);
+        set(&mut second, 'Q', "b.txt", 4);
+        second.save_to(&path).unwrap();
+        assert_eq!(
+            read_marks(&path).unwrap(),
+            BTreeMap::from([
+                ('A', saved("a.txt", 1)),
+                ('B', saved("b.txt", 3)),
+                ('Q', saved("b.txt", 4)),
+            ])
+        );
+
+        // removing a mark removes the saved mark as well
+        let mut third = Marks::default();
+        third.load_from(&path).unwrap();
+        assert_eq!(third.get('Q').unwrap().path(), Path::new("b.txt"));
+        assert!(third.remove('A').is_some());
+        third.save_to(&path).unwrap();
+        assert_eq!(
+            read_marks(&path).unwrap(),
+            BTreeMap::from([('B', saved("b.txt", 3)), ('Q', saved("b.txt", 4))])
+        );
+    }
+}
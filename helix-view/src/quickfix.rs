@@ -0,0 +1,187 @@
+//! The quickfix list holds the problems found in the output of a task, see `:task`. The
+//! problems are found by problem matchers: regexes with named groups that match a line of
+//! output.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::bail;
+use helix_core::{diagnostic::Severity, regex::Regex};
+
+/// A problem reported by a task. The line and column are zero based.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuickfixEntry {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Quickfix {
+    entries: Vec<QuickfixEntry>,
+    /// The index of the entry that was jumped to last, `None` before the first jump.
+    current: Option<usize>,
+}
+
+impl Quickfix {
+    pub fn set(&mut self, entries: Vec<QuickfixEntry>) {
+        self.entries = entries;
+        self.current = None;
+    }
+
+    pub fn entries(&self) -> &[QuickfixEntry] {
+        &self.entries
+    }
+
+    /// The index of the entry that was jumped to last.
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+
+    /// Moves `count` entries forward. The first jump moves to the `count`th entry.
+    pub fn next(&mut self, count: usize) -> Option<&QuickfixEntry> {
+        let index = match self.current {
+            Some(current) => current + count,
+            None => count.saturating_sub(1),
+        };
+        if index >= self.entries.len() {
+            return None;
+        }
+        self.current = Some(index);
+        self.entries.get(index)
+    }
+
+    pub fn prev(&mut self, count: usize) -> Option<&QuickfixEntry> {
+        let index = self.current?.checked_sub(count)?;
+        self.current = Some(index);
+        self.entries.get(index)
+    }
+}
+
+/// A regex that finds problems in a line of output. The `file` and `line` groups are
+/// required, `column`, `severity` and `message` are optional.
+#[derive(Debug, Clone)]
+pub struct ProblemMatcher {
+    regex: Regex,
+}
+
+impl ProblemMatcher {
+    pub fn new(pattern: &str) -> anyhow::Result<Self> {
+        let regex = Regex::new(pattern)?;
+        for group in ["file", "line"] {
+            if !regex.capture_names().flatten().any(|name| name == group) {
+                bail!("problem matcher '{pattern}' has no '{group}' group");
+            }
+        }
+        Ok(Self { regex })
+    }
+
+    /// Parses a line of output, relative paths are resolved against `cwd`.
+    fn parse_line(&self, line: &str, cwd: &Path) -> Option<QuickfixEntry> {
+        let captures = self.regex.captures(line)?;
+        let number = |group| -> Option<usize> {
+            let number: usize = captures.name(group)?.as_str().parse().ok()?;
+            Some(number.saturating_sub(1))
+        };
+        let severity = match captures.name("severity") {
+            Some(severity) => parse_severity(severity.as_str()),
+            None => Severity::Error,
+        };
+        Some(QuickfixEntry {
+            path: cwd.join(captures.name("file")?.as_str()),
+            line: number("line")?,
+            column: number("column").unwrap_or(0),
+            severity,
+            message: captures
+                .name("message")
+                .map_or(line, |message| message.as_str())
+                .trim()
+                .to_string(),
+        })
+    }
+}
+
+fn parse_severity(severity: &str) -> Severity {
+    let severity = severity.to_ascii_lowercase();
+    if severity.starts_with("warn") {
+        Severity::Warning
+    } else if severity.starts_with("info") || severity == "note" {
+        Severity::Info
+    } else if severity == "hint" || severity == "help" {
+        Severity::Hint
+    } else {
+        Severity::Error
+    }
+}
+
+/// Returns the problems in the output of a task. Each line is matched by the first matching
+/// problem matcher.
+pub fn match_problems(output: &str, matchers: &[ProblemMatcher], cwd: &Path) -> Vec<QuickfixEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            matchers
+                .iter()
+                .find_map(|matcher| matcher.parse_line(line, cwd))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn match_problems_in_output() {
+        let matchers = [ProblemMatcher::new(
+            r"^(?P<file>[^:\s]+):(?P<line>\d+):(?P<column>\d+): (?P<severity>\w+): (?P<message>.*)$",
+        )
+        .unwrap()];
+        let output = "cc -c main.c\n\
+            main.c:12:5: warning: unused variable 'x'\n\
+            /src/util.c:3:1: error: expected ';'\n\
+            1 warning and 1 error generated.\n";
+        let problems = match_problems(output, &matchers, Path::new("/project"));
+        assert_eq!(
+            problems,
+            vec![
+                QuickfixEntry {
+                    path: PathBuf::from("/project/main.c"),
+                    line: 11,
+                    column: 4,
+                    severity: Severity::Warning,
+                    message: "unused variable 'x'".to_string(),
+                },
+                QuickfixEntry {
+                    path: PathBuf::from("/src/util.c"),
+                    line: 2,
+                    column: 0,
+                    severity: Severity::Error,
+                    message: "expected ';'".to_string(),
+                },
+            ]
+        );
+
+        assert!(ProblemMatcher::new(r"(?P<file>\S+): (?P<message>.*)").is_err());
+    }
+
+    #[test]
+    fn navigate_quickfix() {
+        let entry = |line| QuickfixEntry {
+            path: PathBuf::from("main.c"),
+            line,
+            column: 0,
+            severity: Severity::Error,
+            message: String::new(),
+        };
+        let mut quickfix = Quickfix::default();
+        quickfix.set(vec![entry(1), entry(2), entry(3)]);
+        assert_eq!(quickfix.prev(1), None);
+        assert_eq!(quickfix.next(1).map(|entry| entry.line), Some(1));
+        assert_eq!(quickfix.next(2).map(|entry| entry.line), Some(3));
+        assert_eq!(quickfix.next(1), None);
+        assert_eq!(quickfix.prev(1).map(|entry| entry.line), Some(2));
+        assert_eq!(quickfix.current(), Some(1));
+    }
+}
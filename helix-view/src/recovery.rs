@@ -0,0 +1,255 @@
+//! Recovery files for documents with unsaved changes.
+//!
+//! The text of modified documents is written to [`helix_loader::recovery_dir`] in the
+//! background while editing and the file is removed again once the document is saved or closed.
+//! Recovery files that are left over after a crash can be restored with `:recover`. Each
+//! document is stored as two files named after a hash of its path, see
+//! [`helix_stdx::path::stable_hash`]: a `.txt` file with the text and a `.path` file with the
+//! instance that wrote it and the path of the document.
+//!
+//! Every instance that writes recovery files holds a lock on its own `.lock` file while it runs.
+//! The recovery files of instances that are still running aren't left over: they are neither
+//! listed nor removed by other instances.
+
+use std::{
+    fs,
+    io::{self, BufWriter, ErrorKind, Write},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use helix_core::Rope;
+
+/// A recovery file that was left over.
+#[derive(Debug, Clone)]
+pub struct RecoveryFile {
+    /// The path of the document.
+    pub path: PathBuf,
+    /// The file with the text of the document.
+    pub text_file: PathBuf,
+    /// When the recovery file was last written.
+    pub modified: SystemTime,
+}
+
+impl RecoveryFile {
+    /// Reads the text of the document.
+    pub fn read(&self) -> io::Result<Rope> {
+        Rope::from_reader(fs::File::open(&self.text_file)?)
+    }
+
+    /// Removes the recovery file.
+    pub fn remove(&self) -> io::Result<()> {
+        remove_files(&self.text_file)
+    }
+}
+
+/// The lock held by a running instance.
+struct Instance {
+    id: String,
+    _lock: fs::File,
+}
+
+impl Instance {
+    fn lock(dir: &Path) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        // the process id alone could be reused by a later instance
+        let started = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let id = format!("{}-{started}", std::process::id());
+        let lock = fs::File::create(dir.join(format!("{id}.lock")))?;
+        lock.try_lock()?;
+        Ok(Self { id, _lock: lock })
+    }
+}
+
+/// The lock of this instance, taken when it writes its first recovery file.
+static INSTANCE: OnceLock<Instance> = OnceLock::new();
+
+fn instance() -> io::Result<&'static Instance> {
+    if let Some(instance) = INSTANCE.get() {
+        return Ok(instance);
+    }
+    let instance = Instance::lock(&helix_loader::recovery_dir())?;
+    Ok(INSTANCE.get_or_init(|| instance))
+}
+
+fn own_id() -> Option<&'static str> {
+    INSTANCE.get().map(|instance| instance.id.as_str())
+}
+
+/// Whether the instance `id` is still running, checked by trying to take its lock. The lock
+/// files of instances that exited are removed.
+fn is_running(dir: &Path, id: &str, own: Option<&str>) -> bool {
+    if own == Some(id) {
+        return true;
+    }
+    let lock_file = dir.join(format!("{id}.lock"));
+    let Ok(lock) = fs::File::open(&lock_file) else {
+        return false;
+    };
+    match lock.try_lock() {
+        Ok(()) => {
+            drop(lock);
+            let _ = fs::remove_file(lock_file);
+            false
+        }
+        Err(fs::TryLockError::WouldBlock) => true,
+        Err(fs::TryLockError::Error(_)) => false,
+    }
+}
+
+fn recovery_files(dir: &Path, path: &Path) -> (PathBuf, PathBuf) {
+    let stem = format!("{:016x}", helix_stdx::path::stable_hash(path));
+    (
+        dir.join(format!("{stem}.txt")),
+        dir.join(format!("{stem}.path")),
+    )
+}
+
+/// Reads the instance that wrote a recovery file and the path of its document from its `.path`
+/// file. Files written before the instance was recorded have no instance.
+fn read_path_file(path_file: &Path) -> io::Result<(Option<String>, PathBuf)> {
+    let contents = fs::read_to_string(path_file)?;
+    Ok(match contents.split_once('\n') {
+        Some((id, path)) => (Some(id.to_string()), PathBuf::from(path)),
+        None => (None, PathBuf::from(contents)),
+    })
+}
+
+/// Writes the text of the document at `path` to its recovery file.
+pub fn write(path: &Path, text: &Rope) -> io::Result<()> {
+    write_in(&helix_loader::recovery_dir(), &instance()?.id, path, text)
+}
+
+fn write_in(dir: &Path, id: &str, path: &Path, text: &Rope) -> io::Result<()> {
+    let (text_file, path_file) = recovery_files(dir, path);
+    fs::create_dir_all(dir)?;
+    // write to a temporary file first so that a crash while writing doesn't corrupt the
+    // previous recovery file
+    let tmp_file = text_file.with_extension("tmp");
+    let mut writer = BufWriter::new(fs::File::create(&tmp_file)?);
+    text.write_to(&mut writer)?;
+    // the error of the last write only shows up when flushing
+    writer.flush()?;
+    drop(writer);
+    fs::rename(tmp_file, text_file)?;
+    fs::write(path_file, format!("{id}\n{}", path.to_string_lossy()))
+}
+
+/// Removes the recovery file of the document at `path`, unless another instance that is still
+/// running wrote it. A missing recovery file is not an error.
+pub fn remove(path: &Path) -> io::Result<()> {
+    remove_in(&helix_loader::recovery_dir(), own_id(), path)
+}
+
+fn remove_in(dir: &Path, own: Option<&str>, path: &Path) -> io::Result<()> {
+    let (text_file, path_file) = recovery_files(dir, path);
+    match read_path_file(&path_file) {
+        Ok((Some(id), _)) if own != Some(id.as_str()) && is_running(dir, &id, own) => Ok(()),
+        _ => remove_files(&text_file),
+    }
+}
+
+/// Removes the `.path` and `.txt` files of a recovery file.
+fn remove_files(text_file: &Path) -> io::Result<()> {
+    for file in [text_file.with_extension("path"), text_file.to_path_buf()] {
+        match fs::remove_file(file) {
+            Err(err) if err.kind() != ErrorKind::NotFound => return Err(err),
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// Returns the recovery files that were left over by instances that are no longer running, the
+/// most recently written first.
+pub fn list() -> Vec<RecoveryFile> {
+    list_in(&helix_loader::recovery_dir(), own_id())
+}
+
+fn list_in(dir: &Path, own: Option<&str>) -> Vec<RecoveryFile> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<_> = entries
+        .filter_map(|entry| {
+            let path_file = entry.ok()?.path();
+            if path_file.extension()? != "path" {
+                return None;
+            }
+            let (id, path) = read_path_file(&path_file).ok()?;
+            if id.is_some_and(|id| is_running(dir, &id, own)) {
+                return None;
+            }
+            let text_file = path_file.with_extension("txt");
+            let modified = fs::metadata(&text_file).ok()?.modified().ok()?;
+            Some(RecoveryFile {
+                path,
+                text_file,
+                modified,
+            })
+        })
+        .collect();
+    files.sort_by_key(|file| std::cmp::Reverse(file.modified));
+    files
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn recovery_files_of_running_instances_are_kept() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let dir = dir.path();
+        let path = Path::new("/home/user/file.rs");
+        let text = Rope::from("unsaved\n");
+
+        let first = Instance::lock(dir)?;
+        write_in(dir, &first.id, path, &text)?;
+        let stem = format!("{:016x}", helix_stdx::path::stable_hash(path));
+        assert!(dir.join(format!("{stem}.txt")).is_file());
+
+        // neither the instance itself nor another one lists or removes the file
+        let second = Instance::lock(dir)?;
+        assert!(list_in(dir, Some(&first.id)).is_empty());
+        assert!(list_in(dir, Some(&second.id)).is_empty());
+        remove_in(dir, Some(&second.id), path)?;
+        assert!(dir.join(format!("{stem}.txt")).is_file());
+
+        // once the instance exited the file is left over
+        drop(first);
+        let files = list_in(dir, Some(&second.id));
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, path);
+        assert_eq!(files[0].read()?, text);
+        files[0].remove()?;
+        assert!(list_in(dir, None).is_empty());
+        // only the lock of the running instance is left
+        let names: Vec<_> = fs::read_dir(dir)?
+            .map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().into_owned()))
+            .collect::<io::Result<_>>()?;
+        assert_eq!(names, [format!("{}.lock", second.id)]);
+        Ok(())
+    }
+
+    #[test]
+    fn recovery_files_without_instance_are_left_over() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let dir = dir.path();
+        let path = Path::new("/home/user/file.rs");
+        let (text_file, path_file) = recovery_files(dir, path);
+        fs::write(&text_file, "unsaved\n")?;
+        fs::write(&path_file, path.to_string_lossy().as_bytes())?;
+
+        let files = list_in(dir, None);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, path);
+        remove_in(dir, None, path)?;
+        assert!(!text_file.exists() && !path_file.exists());
+        Ok(())
+    }
+}
@@ -1,4 +1,11 @@
-use std::{borrow::Cow, collections::HashMap, iter};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    fs,
+    io::ErrorKind,
+    iter,
+    path::Path,
+};
 
 use anyhow::Result;
 use arc_swap::access::DynAccess;
@@ -9,6 +16,35 @@
     Editor,
 };
 
+/// The register holding the history of the command prompt.
+const COMMAND_HISTORY_REGISTER: char = ':';
+/// The maximum number of commands that are persisted across restarts.
+const MAX_PERSISTED_COMMANDS: usize = 1000;
+/// The number of yanks kept in the yank history.
+const YANK_HISTORY_SIZE: usize = 50;
+
+/// How the values of a register are pasted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RegisterKind {
+    /// The values are pasted at the selections. Values ending with a line ending are pasted as
+    /// whole lines.
+    #[default]
+    Charwise,
+    /// The values are whole lines and are pasted above or below the selections, even when the
+    /// last line has no line ending.
+    Linewise,
+    /// The values are the rows of a rectangular block. Pasted at a single selection, the rows are
+    /// inserted at the same column of consecutive lines.
+    Blockwise,
+}
+
+/// The values of a yank recorded in the yank history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Yank {
+    pub values: Vec<String>,
+    pub kind: RegisterKind,
+}
+
 /// A key-value store for saving sets of values.
 ///
 /// Each register corresponds to a `char`. Most chars can be used to store any set of
@@ -27,6 +63,18 @@ pub struct Registers {
     /// The order is reversed again in `Registers::read`. This allows us to
     /// efficiently prepend new values in `Registers::push`.
     inner: HashMap<char, Vec<String>>,
+    /// The registers holding a recorded macro, these are persisted across restarts.
+    macros: HashSet<char>,
+    /// The registers that held a macro which was overwritten, their saved macros are removed.
+    removed_macros: HashSet<char>,
+    /// The number of command history entries restored by [Registers::load_command_history],
+    /// the entries after them were added by this instance.
+    loaded_commands: usize,
+    /// The kinds of the registers written by [Registers::write_yank]. Registers without an
+    /// entry are [RegisterKind::Charwise].
+    kinds: HashMap<char, RegisterKind>,
+    /// The most recent yanks, newest first.
+    yank_history: VecDeque<Yank>,
     clipboard_provider: Box<dyn DynAccess<ClipboardProvider>>,
     pub last_search_register: char,
 }
@@ -35,6 +83,11 @@ impl Registers {
     pub fn new(clipboard_provider: Box<dyn DynAccess<ClipboardProvider>>) -> Self {
         Self {
             inner: Default::default(),
+            macros: Default::default(),
+            removed_macros: Default::default(),
+            loaded_commands: 0,
+            kinds: Default::default(),
+            yank_history: Default::default(),
             clipboard_provider,
             last_search_register: '/',
         }
@@ -78,6 +131,8 @@ pub fn read<'a>(&'a self, name: char, editor: &'a Editor) -> Option<RegisterValu
     }
 
     pub fn write(&mut self, name: char, mut values: Vec<String>) -> Result<()> {
+        self.forget_macro(name);
+        self.kinds.remove(&name);
         match name {
             '_' => Ok(()),
             '#' | '.' | '%' => Err(anyhow::anyhow!("Register {name} does not support writing")),
@@ -103,6 +158,8 @@ pub fn write(&mut self, name: char, mut values: Vec<String>) -> Result<()> {
     }
 
     pub fn push(&mut self, name: char, mut value: String) -> Result<()> {
+        self.forget_macro(name);
+        self.kinds.remove(&name);
         match name {
             '_' => Ok(()),
             '#' | '.' | '%' => Err(anyhow::anyhow!("Register {name} does not support pushing")),
@@ -140,6 +197,68 @@ pub fn push(&mut self, name: char, mut value: String) -> Result<()> {
         }
     }
 
+    /// Writes values to a register and records how they should be pasted.
+    pub fn write_with_kind(
+        &mut self,
+        name: char,
+        values: Vec<String>,
+        kind: RegisterKind,
+    ) -> Result<()> {
+        self.write(name, values)?;
+        if kind != RegisterKind::Charwise && name != '_' {
+            self.kinds.insert(name, kind);
+        }
+        Ok(())
+    }
+
+    /// Writes yanked values to a register like [Registers::write_with_kind] and adds them to the
+    /// yank history. Yanks to the black hole register are discarded.
+    pub fn write_yank(
+        &mut self,
+        name: char,
+        values: Vec<String>,
+        kind: RegisterKind,
+    ) -> Result<()> {
+        self.write_with_kind(name, values.clone(), kind)?;
+        if name == '_' {
+            return Ok(());
+        }
+
+        let yank = Yank { values, kind };
+        self.yank_history.retain(|entry| *entry != yank);
+        self.yank_history.push_front(yank);
+        self.yank_history.truncate(YANK_HISTORY_SIZE);
+        Ok(())
+    }
+
+    /// Returns how the values of a register should be pasted.
+    pub fn kind(&self, name: char) -> RegisterKind {
+        let Some(&kind) = self.kinds.get(&name) else {
+            return RegisterKind::Charwise;
+        };
+        if let '*' | '+' = name {
+            // another application may have changed the clipboard since the yank
+            let clipboard_type = match name {
+                '+' => ClipboardType::Clipboard,
+                _ => ClipboardType::Selection,
+            };
+            let saved_values = self.inner.get(&name).map(Vec::as_slice).unwrap_or_default();
+            match self.clipboard_provider.load().get_contents(&clipboard_type) {
+                Ok(contents) if !contents_are_saved(saved_values, &contents) => {
+                    return RegisterKind::Charwise
+                }
+                Err(ClipboardError::ReadingNotSupported) | Ok(_) => (),
+                Err(_) => return RegisterKind::Charwise,
+            }
+        }
+        kind
+    }
+
+    /// The most recent yanks written with [Registers::write_yank], newest first.
+    pub fn yank_history(&self) -> impl Iterator<Item = &Yank> {
+        self.yank_history.iter()
+    }
+
     pub fn first<'a>(&'a self, name: char, editor: &'a Editor) -> Option<Cow<'a, str>> {
         self.read(name, editor).and_then(|mut values| values.next())
     }
@@ -149,6 +268,97 @@ pub fn last<'a>(&'a self, name: char, editor: &'a Editor) -> Option<Cow<'a, str>
             .and_then(|mut values| values.next_back())
     }
 
+    /// Restores the command history saved by [Registers::save_command_history] from
+    /// [helix_loader::command_history_file]. A missing file is not an error.
+    pub fn load_command_history(&mut self) -> Result<()> {
+        self.load_command_history_from(&helix_loader::command_history_file())
+    }
+
+    fn load_command_history_from(&mut self, path: &Path) -> Result<()> {
+        let history = read_command_history(path)?;
+        self.loaded_commands = history.len();
+        // the history is saved oldest first which matches the internal order
+        self.inner.insert(COMMAND_HISTORY_REGISTER, history);
+        Ok(())
+    }
+
+    /// Saves the most recent entries of the command history to
+    /// [helix_loader::command_history_file]. The commands added by this instance are appended
+    /// to the saved history, so the commands saved by other instances in the meantime are kept.
+    pub fn save_command_history(&self) -> Result<()> {
+        self.save_command_history_to(&helix_loader::command_history_file())
+    }
+
+    fn save_command_history_to(&self, path: &Path) -> Result<()> {
+        let Some(history) = self.inner.get(&COMMAND_HISTORY_REGISTER) else {
+            return Ok(());
+        };
+        let added = history.get(self.loaded_commands..).unwrap_or(history);
+        if added.is_empty() {
+            return Ok(());
+        }
+        // a corrupted file is replaced instead of failing every time
+        let mut saved = read_command_history(path).unwrap_or_default();
+        saved.extend_from_slice(added);
+        let saved = &saved[saved.len().saturating_sub(MAX_PERSISTED_COMMANDS)..];
+        helix_loader::write_state_file(path, serde_json::to_string(saved)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Writes the keys of a recorded macro to a register. Unlike other values, macros are
+    /// saved by [Registers::save_macros].
+    pub fn write_macro(&mut self, name: char, keys: String) -> Result<()> {
+        self.write(name, vec![keys])?;
+        if !is_special_register(name) {
+            self.macros.insert(name);
+            self.removed_macros.remove(&name);
+        }
+        Ok(())
+    }
+
+    /// Records that the macro of a register, if any, was overwritten.
+    fn forget_macro(&mut self, name: char) {
+        if self.macros.remove(&name) {
+            self.removed_macros.insert(name);
+        }
+    }
+
+    /// Restores the macros saved by [Registers::save_macros] from [helix_loader::macros_file].
+    /// A missing file is not an error.
+    pub fn load_macros(&mut self) -> Result<()> {
+        self.load_macros_from(&helix_loader::macros_file())
+    }
+
+    fn load_macros_from(&mut self, path: &Path) -> Result<()> {
+        for (name, keys) in read_macros(path)? {
+            if !is_special_register(name) {
+                self.inner.insert(name, vec![keys]);
+                self.macros.insert(name);
+            }
+        }
+        Ok(())
+    }
+
+    /// Saves the registers that still hold a recorded macro to [helix_loader::macros_file].
+    /// Other instances may have saved macros in the meantime: the saved macros are kept unless
+    /// this instance recorded a macro with the same name or overwrote the macro.
+    pub fn save_macros(&self) -> Result<()> {
+        self.save_macros_to(&helix_loader::macros_file())
+    }
+
+    fn save_macros_to(&self, path: &Path) -> Result<()> {
+        // a corrupted file is replaced instead of failing every time
+        let mut macros = read_macros(path).unwrap_or_default();
+        macros.retain(|name, _| !self.removed_macros.contains(name));
+        for name in &self.macros {
+            if let Some([keys]) = self.inner.get(name).map(Vec::as_slice) {
+                macros.insert(*name, keys.clone());
+            }
+        }
+        helix_loader::write_state_file(path, serde_json::to_string(&macros)?.as_bytes())?;
+        Ok(())
+    }
+
     pub fn iter_preview(&self) -> impl Iterator<Item = (char, &str)> {
         self.inner
             .iter()
@@ -176,12 +386,17 @@ pub fn iter_preview(&self) -> impl Iterator<Item = (char, &str)> {
     }
 
     pub fn clear(&mut self) {
+        self.removed_macros.extend(self.macros.drain());
+        self.kinds.clear();
+        self.yank_history.clear();
         self.clear_clipboard(ClipboardType::Clipboard);
         self.clear_clipboard(ClipboardType::Selection);
         self.inner.clear()
     }
 
     pub fn remove(&mut self, name: char) -> bool {
+        self.forget_macro(name);
+        self.kinds.remove(&name);
         match name {
             '*' | '+' => {
                 self.clear_clipboard(match name {
@@ -219,6 +434,30 @@ pub fn clipboard_provider_name(&self) -> String {
     }
 }
 
+/// Reads the command history saved by [Registers::save_command_history]. A missing file has no
+/// history.
+fn read_command_history(path: &Path) -> Result<Vec<String>> {
+    match fs::read_to_string(path) {
+        Ok(json) => Ok(serde_json::from_str(&json)?),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Reads the macros saved by [Registers::save_macros]. A missing file has no macros.
+fn read_macros(path: &Path) -> Result<BTreeMap<char, String>> {
+    match fs::read_to_string(path) {
+        Ok(json) => Ok(serde_json::from_str(&json)?),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Whether the register is one of the special registers listed in [Registers].
+fn is_special_register(name: char) -> bool {
+    matches!(name, '_' | '#' | '.' | '%' | '*' | '+')
+}
+
 fn read_from_clipboard<'a>(
     provider: &ClipboardProvider,
     saved_values: Option<&'a Vec<String>>,
@@ -333,3 +572,122 @@ fn len(&self) -> usize {
 trait DoubleEndedExactSizeIterator: DoubleEndedIterator + ExactSizeIterator {}
 
 impl<I: DoubleEndedIterator + ExactSizeIterator> DoubleEndedExactSizeIterator for I {}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use arc_swap::ArcSwap;
+
+    use super::*;
+
+    fn registers() -> Registers {
+        Registers::new(Box::new(ArcSwap::new(Arc::new(
+            ClipboardProvider::default(),
+        ))))
+    }
+
+    #[test]
+    fn command_history_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history").join("commands.json");
+
+        // a missing file leaves the history empty
+        let mut loaded = registers();
+        loaded.load_command_history_from(&path).unwrap();
+        assert!(!loaded.inner.contains_key(&COMMAND_HISTORY_REGISTER));
+
+        let mut registers = registers();
+        let history: Vec<_> = (0..MAX_PERSISTED_COMMANDS + 5)
+            .map(|i| format!("open file{i}"))
+            .collect();
+        for command in &history {
+            registers
+                .push(COMMAND_HISTORY_REGISTER, command.clone())
+                .unwrap();
+        }
+        registers.save_command_history_to(&path).unwrap();
+
+        // only the most recent commands are kept, in the same order
+        loaded.load_command_history_from(&path).unwrap();
+        assert_eq!(
+            loaded.inner[&COMMAND_HISTORY_REGISTER],
+            history[5..].to_vec()
+        );
+        assert_eq!(
+            loaded.inner[&COMMAND_HISTORY_REGISTER].len(),
+            MAX_PERSISTED_COMMANDS
+        );
+    }
+
+    #[test]
+    fn saving_command_history_keeps_the_commands_of_other_instances() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("command_history.json");
+        std::fs::write(&path, r#"["write"]"#).unwrap();
+
+        let mut first = registers();
+        first.load_command_history_from(&path).unwrap();
+        let mut second = registers();
+        second.load_command_history_from(&path).unwrap();
+
+        first
+            .push(COMMAND_HISTORY_REGISTER, "open a".into())
+            .unwrap();
+        second
+            .push(COMMAND_HISTORY_REGISTER, "open b".into())
+            .unwrap();
+        first.save_command_history_to(&path).unwrap();
+        second.save_command_history_to(&path).unwrap();
+        assert_eq!(
+            read_command_history(&path).unwrap(),
+            ["write", "open a", "open b"]
+        );
+
+        // an instance without new commands leaves the history as is
+        let mut third = registers();
+        third
+            .load_command_history_from(&dir.path().join("missing.json"))
+            .unwrap();
+        third.save_command_history_to(&path).unwrap();
+        assert_eq!(read_command_history(&path).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn saving_macros_keeps_the_macros_of_other_instances() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join("macros.json");
+
+        let mut first = registers();
+        first.load_macros_from(&path).unwrap();
+        first.write_macro('a', "ihello<esc>".to_string()).unwrap();
+        first.write_macro('q', "dd".to_string()).unwrap();
+        first.save_macros_to(&path).unwrap();
+
+        // another instance started before the first one saved its macros
+        let mut second = registers();
+        second.write_macro('b', "x".to_string()).unwrap();
+        second.write_macro('q', "yy".to_string()).unwrap();
+        second.save_macros_to(&path).unwrap();
+        assert_eq!(
+            read_macros(&path).unwrap(),
+            BTreeMap::from([
+                ('a', "ihello<esc>".to_string()),
+                ('b', "x".to_string()),
+                ('q', "yy".to_string()),
+            ])
+        );
+
+        // overwriting a register removes its saved macro
+        let mut third = registers();
+        third.load_macros_from(&path).unwrap();
+        assert_eq!(third.inner[&'q'], vec!["yy".to_string()]);
+        third.write('q', vec!["text".to_string()]).unwrap();
+        third.remove('a');
+        third.save_macros_to(&path).unwrap();
+        assert_eq!(
+            read_macros(&path).unwrap(),
+            BTreeMap::from([('b', "x".to_string())])
+        );
+    }
+}
@@ -0,0 +1,304 @@
+//! Documents that are stored on another machine instead of the local file system.
+//!
+//! A remote document has no path, its contents are read and written through a
+//! [DocumentBackend]. Remote documents are opened by passing a URL instead of a path, for example
+//! `hx sftp://user@host/etc/hosts`.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::Arc,
+};
+
+use anyhow::bail;
+use futures_util::{future::BoxFuture, FutureExt};
+use parking_lot::Mutex;
+use tokio::io::AsyncWriteExt;
+
+/// The size and modification time of a remote file as reported by the server.
+///
+/// The modification time is the one listed by `ls -ln`, which only has minute resolution for
+/// recent files. A change made by another program in the same minute that keeps the size of the
+/// file is not noticed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteMetadata {
+    pub len: u64,
+    pub modified: String,
+}
+
+/// Reads and writes the contents of a remote document.
+pub trait DocumentBackend: Send + Sync {
+    /// The URL the document was opened with.
+    fn url(&self) -> &str;
+    /// The path of the file on the remote machine, used to detect the language.
+    fn path(&self) -> &Path;
+    fn read(&self) -> BoxFuture<'static, io::Result<Vec<u8>>>;
+    fn write(&self, contents: Vec<u8>) -> BoxFuture<'static, io::Result<()>>;
+    /// Returns the metadata of the file or `None` if it does not exist.
+    fn stat(&self) -> BoxFuture<'static, io::Result<Option<RemoteMetadata>>>;
+}
+
+/// Returns the backend for a remote URL or `None` if `path` is not a remote URL.
+pub fn backend_for_url(path: &Path) -> Option<io::Result<Arc<dyn DocumentBackend>>> {
+    let url = path.to_str()?;
+    if !url.starts_with("sftp://") {
+        return None;
+    }
+    let backend = SftpBackend::parse(url)
+        .map(|backend| Arc::new(backend) as Arc<dyn DocumentBackend>)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid URL '{url}', expected sftp://[user@]host[:port]/path"),
+            )
+        });
+    Some(backend)
+}
+
+/// The remote file of a document together with the metadata it had when it was last read or
+/// written, which is used to detect changes made by other programs.
+#[derive(Clone)]
+pub struct RemoteFile {
+    backend: Arc<dyn DocumentBackend>,
+    metadata: Arc<Mutex<Option<RemoteMetadata>>>,
+}
+
+impl RemoteFile {
+    /// Reads the remote file. The contents are `None` if the file does not exist yet.
+    pub async fn load(backend: Arc<dyn DocumentBackend>) -> io::Result<(Self, Option<Vec<u8>>)> {
+        let metadata = backend.stat().await?;
+        let contents = match metadata {
+            Some(_) => Some(backend.read().await?),
+            None => None,
+        };
+        let file = RemoteFile {
+            backend,
+            metadata: Arc::new(Mutex::new(metadata)),
+        };
+        Ok((file, contents))
+    }
+
+    pub fn url(&self) -> &str {
+        self.backend.url()
+    }
+
+    pub fn path(&self) -> &Path {
+        self.backend.path()
+    }
+
+    /// Writes `contents` to the remote file. Unless `force` is set, saving fails if the file
+    /// was changed since it was last read or written.
+    pub fn save(
+        &self,
+        contents: Vec<u8>,
+        force: bool,
+    ) -> impl std::future::Future<Output = anyhow::Result<()>> + Send + 'static {
+        let backend = self.backend.clone();
+        let metadata = self.metadata.clone();
+        async move {
+            if !force {
+                let current = backend.stat().await?;
+                if current.is_some() && current != *metadata.lock() {
+                    bail!("file modified by an external process, use :w! to overwrite");
+                }
+            }
+            backend.write(contents).await?;
+            *metadata.lock() = backend.stat().await?;
+            Ok(())
+        }
+    }
+}
+
+impl std::fmt::Debug for RemoteFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("RemoteFile").field(&self.url()).finish()
+    }
+}
+
+/// A file accessed with the `sftp` program of OpenSSH in batch mode. Batch mode disables
+/// password prompts, so the host must be reachable with key based authentication.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SftpBackend {
+    url: String,
+    /// The `[user@]host` to connect to.
+    destination: String,
+    port: Option<u16>,
+    path: PathBuf,
+}
+
+impl SftpBackend {
+    /// Parses a `sftp://[user@]host[:port]/path` URL.
+    pub fn parse(url: &str) -> Option<Self> {
+        let rest = url.strip_prefix("sftp://")?;
+        let (authority, path) = rest.split_at(rest.find('/')?);
+        let (destination, port) = match authority.rsplit_once(':') {
+            Some((destination, port)) => (destination, Some(port.parse().ok()?)),
+            None => (authority, None),
+        };
+        // the destination is passed as an argument, it must not be mistaken for an option
+        if destination.is_empty() || destination.starts_with('-') || path.len() < 2 {
+            return None;
+        }
+        // a newline would end the batch command, the rest of the path would run as another
+        // command (`!command` runs a shell command)
+        if url.chars().any(char::is_control) {
+            return None;
+        }
+        Some(Self {
+            url: url.to_string(),
+            destination: destination.to_string(),
+            port,
+            path: PathBuf::from(path),
+        })
+    }
+
+    /// Runs `sftp` with the batch `commands` and returns its output.
+    fn batch(&self, commands: String) -> BoxFuture<'static, io::Result<String>> {
+        let mut command = tokio::process::Command::new("sftp");
+        command.args(["-q", "-b", "-"]);
+        if let Some(port) = self.port {
+            command.arg("-P").arg(port.to_string());
+        }
+        command
+            .arg(&self.destination)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        async move {
+            let mut process = command.spawn()?;
+            let mut stdin = process
+                .stdin
+                .take()
+                .ok_or_else(|| io::Error::other("failed to open the stdin of sftp"))?;
+            stdin.write_all(commands.as_bytes()).await?;
+            drop(stdin);
+
+            let output = process.wait_with_output().await?;
+            if !output.status.success() {
+                return Err(io::Error::other(format!(
+                    "sftp failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )));
+            }
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        }
+        .boxed()
+    }
+}
+
+impl DocumentBackend for SftpBackend {
+    fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn read(&self) -> BoxFuture<'static, io::Result<Vec<u8>>> {
+        let local = match tempfile::NamedTempFile::new() {
+            Ok(local) => local.into_temp_path(),
+            Err(err) => return async move { Err(err) }.boxed(),
+        };
+        let batch = self.batch(format!("get {} {}\n", quote(&self.path), quote(&local)));
+        async move {
+            batch.await?;
+            tokio::fs::read(&local).await
+        }
+        .boxed()
+    }
+
+    fn write(&self, contents: Vec<u8>) -> BoxFuture<'static, io::Result<()>> {
+        let local = match tempfile::NamedTempFile::new() {
+            Ok(local) => local.into_temp_path(),
+            Err(err) => return async move { Err(err) }.boxed(),
+        };
+        let batch = self.batch(format!("put {} {}\n", quote(&local), quote(&self.path)));
+        async move {
+            tokio::fs::write(&local, contents).await?;
+            batch.await?;
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn stat(&self) -> BoxFuture<'static, io::Result<Option<RemoteMetadata>>> {
+        // the leading `-` ignores the failure of `ls` for a file that does not exist
+        let batch = self.batch(format!("-ls -ln {}\n", quote(&self.path)));
+        async move { Ok(parse_ls_output(&batch.await?)) }.boxed()
+    }
+}
+
+/// Quotes a path for an sftp batch command. Glob characters are escaped as well because the
+/// arguments of `get` and `put` are globbed.
+fn quote(path: &Path) -> String {
+    let mut quoted = String::from("\"");
+    for ch in path.to_string_lossy().chars() {
+        if matches!(ch, '"' | '\\' | '*' | '?' | '[' | ']') {
+            quoted.push('\\');
+        }
+        quoted.push(ch);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Parses the metadata of a file from the output of `ls -ln` in batch mode, where the commands
+/// are echoed with an `sftp>` prompt.
+fn parse_ls_output(output: &str) -> Option<RemoteMetadata> {
+    output
+        .lines()
+        .filter(|line| !line.starts_with("sftp>"))
+        .find_map(|line| {
+            let fields: Vec<_> = line.split_whitespace().collect();
+            if fields.len() < 9 || !fields[0].starts_with('-') {
+                return None;
+            }
+            Some(RemoteMetadata {
+                len: fields[4].parse().ok()?,
+                modified: fields[5..8].join(" "),
+            })
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_sftp_url() {
+        let backend = SftpBackend::parse("sftp://admin@example.com:2222/etc/hosts").unwrap();
+        assert_eq!(backend.destination, "admin@example.com");
+        assert_eq!(backend.port, Some(2222));
+        assert_eq!(backend.path, Path::new("/etc/hosts"));
+
+        let backend = SftpBackend::parse("sftp://example.com/srv/app.toml").unwrap();
+        assert_eq!(backend.destination, "example.com");
+        assert_eq!(backend.port, None);
+
+        assert!(SftpBackend::parse("sftp://example.com").is_none());
+        assert!(SftpBackend::parse("sftp://example.com/").is_none());
+        assert!(SftpBackend::parse("sftp://-oProxyCommand=x/etc/hosts").is_none());
+        assert!(SftpBackend::parse("sftp://example.com:ssh/etc/hosts").is_none());
+        assert!(SftpBackend::parse("sftp://example.com/tmp/a\n!touch /tmp/pwned").is_none());
+        assert!(SftpBackend::parse("sftp://example.com/tmp/a\rb").is_none());
+        assert!(SftpBackend::parse("sftp://exa\tmple.com/etc/hosts").is_none());
+    }
+
+    #[test]
+    fn parse_ls() {
+        let output = "sftp> -ls -ln \"/etc/hosts\"\n\
+            -rw-r--r--    1 0        0             221 Oct  3 12:07 /etc/hosts\n";
+        assert_eq!(
+            parse_ls_output(output),
+            Some(RemoteMetadata {
+                len: 221,
+                modified: "Oct 3 12:07".to_string(),
+            })
+        );
+        assert_eq!(parse_ls_output("sftp> -ls -ln \"/missing\"\n"), None);
+        assert_eq!(quote(Path::new("/a \"b\"*")), r#""/a \"b\"\*""#);
+    }
+}
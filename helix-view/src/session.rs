@@ -0,0 +1,454 @@
+//! Persistent editor sessions.
+//!
+//! A [`Session`] records the buffers backed by a file, the window layout with
+//! the selections and jumplists of every view, the registers and the working
+//! directory. Sessions are stored as JSON files named after the session in
+//! [`helix_loader::session_dir`].
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use helix_core::{Range, RopeSlice, Selection};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    align_view,
+    editor::Action,
+    tree::{Layout, LayoutTree},
+    view::JumpList,
+    Align, DocumentId, Editor,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Session {
+    pub working_directory: PathBuf,
+    /// All buffers backed by a file in the order they were opened.
+    pub documents: Vec<PathBuf>,
+    pub layout: SessionLayout,
+    /// Index of the focused view in the traversal order of `layout`.
+    pub focus: usize,
+    pub registers: HashMap<char, Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SessionLayout {
+    View(SessionView),
+    Container {
+        layout: Layout,
+        children: Vec<SessionLayout>,
+        /// The weights of the children, see [`crate::tree::DEFAULT_WEIGHT`].
+        #[serde(default)]
+        weights: Vec<u32>,
+    },
+}
+
+/// A named window arrangement: the layout of the views and their buffers without the rest of a
+/// [`Session`]. Layouts are stored as JSON files in the `layouts` directory of
+/// [`helix_loader::session_dir`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct WindowLayout {
+    pub layout: SessionLayout,
+    /// Index of the focused view in the traversal order of `layout`.
+    pub focus: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SessionView {
+    /// The path of the displayed buffer, `None` for scratch buffers.
+    pub document: Option<PathBuf>,
+    pub selection: SessionSelection,
+    pub jumps: Vec<(PathBuf, SessionSelection)>,
+}
+
+/// A [`Selection`] stored as `(anchor, head)` pairs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionSelection {
+    pub ranges: Vec<(usize, usize)>,
+    pub primary: usize,
+}
+
+impl From<&Selection> for SessionSelection {
+    fn from(selection: &Selection) -> Self {
+        Self {
+            ranges: selection
+                .ranges()
+                .iter()
+                .map(|range| (range.anchor, range.head))
+                .collect(),
+            primary: selection.primary_index(),
+        }
+    }
+}
+
+impl SessionSelection {
+    /// Converts back to a [`Selection`], clamping the ranges to `text` in
+    /// case the file changed since the session was saved.
+    pub fn to_selection(&self, text: RopeSlice) -> Selection {
+        let len = text.len_chars();
+        let ranges: Vec<_> = self
+            .ranges
+            .iter()
+            .map(|&(anchor, head)| Range::new(anchor.min(len), head.min(len)))
+            .collect();
+        if ranges.is_empty() {
+            return Selection::point(0);
+        }
+        let primary = self.primary.min(ranges.len() - 1);
+        Selection::new(ranges.into(), primary).ensure_invariants(text)
+    }
+}
+
+/// Returns the JSON file called `name` in `dir`.
+fn json_file(dir: PathBuf, kind: &str, name: &str) -> Result<PathBuf> {
+    if name.is_empty() || name.contains(std::path::is_separator) {
+        bail!("invalid {kind} name '{name}'");
+    }
+    Ok(dir.join(format!("{name}.json")))
+}
+
+/// Returns the names of the JSON files in `dir`.
+fn json_file_names(dir: PathBuf) -> Vec<String> {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| {
+                    let path = entry.ok()?.path();
+                    (path.extension()? == "json")
+                        .then(|| path.file_stem().unwrap().to_string_lossy().into_owned())
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn load_json<T: for<'de> Deserialize<'de>>(path: &Path, kind: &str) -> Result<T> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("failed to read {kind} file {}", path.display()))?;
+    serde_json::from_str(&contents)
+        .with_context(|| format!("failed to parse {kind} file {}", path.display()))
+}
+
+fn save_json<T: Serialize>(value: &T, path: &Path, kind: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(value)?)
+        .with_context(|| format!("failed to write {kind} file {}", path.display()))
+}
+
+impl Session {
+    /// Returns the file that stores the session called `name`.
+    pub fn file(name: &str) -> Result<PathBuf> {
+        json_file(helix_loader::session_dir(), "session", name)
+    }
+
+    /// Returns the names of all saved sessions.
+    pub fn read_names() -> Vec<String> {
+        json_file_names(helix_loader::session_dir())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        load_json(path, "session")
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        save_json(self, path, "session")
+    }
+
+    /// Records the current state of the `editor`.
+    ///
+    /// Unsaved changes are not part of the session.
+    pub fn capture(editor: &Editor) -> Self {
+        let documents = editor
+            .documents()
+            .filter_map(|doc| doc.path().map(Path::to_path_buf))
+            .collect();
+        let registers = editor
+            .registers
+            .iter_preview()
+            .map(|(name, _)| name)
+            // special registers are computed from the editor state
+            .filter(|name| !matches!(name, '_' | '#' | '.' | '%' | '*' | '+'))
+            .filter_map(|name| {
+                let values = editor.registers.read(name, editor)?;
+                Some((name, values.map(|value| value.into_owned()).collect()))
+            })
+            .collect();
+
+        let WindowLayout { layout, focus } = WindowLayout::capture(editor);
+        Self {
+            working_directory: helix_stdx::env::current_working_dir(),
+            documents,
+            layout,
+            focus,
+            registers,
+        }
+    }
+
+    /// Restores the session into the `editor`, replacing all views.
+    ///
+    /// Buffers whose files no longer exist are skipped.
+    pub fn restore(&self, editor: &mut Editor) -> Result<()> {
+        editor.set_cwd(&self.working_directory).with_context(|| {
+            format!(
+                "could not change working directory to '{}'",
+                self.working_directory.display()
+            )
+        })?;
+
+        for path in &self.documents {
+            if !path.is_file() {
+                continue;
+            }
+            if let Err(err) = editor.open(path, Action::Load) {
+                log::warn!("failed to open {} from session: {err}", path.display());
+            }
+        }
+
+        restore_views(editor, &self.layout, self.focus);
+
+        for (&name, values) in &self.registers {
+            if let Err(err) = editor.registers.write(name, values.clone()) {
+                log::warn!("failed to restore register '{name}': {err}");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl WindowLayout {
+    /// Returns the file that stores the layout called `name`.
+    pub fn file(name: &str) -> Result<PathBuf> {
+        json_file(helix_loader::session_dir().join("layouts"), "layout", name)
+    }
+
+    /// Returns the names of all saved layouts.
+    pub fn read_names() -> Vec<String> {
+        json_file_names(helix_loader::session_dir().join("layouts"))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        load_json(path, "layout")
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        save_json(self, path, "layout")
+    }
+
+    /// Records the views of the `editor`.
+    pub fn capture(editor: &Editor) -> Self {
+        let focus = editor
+            .tree
+            .traverse()
+            .position(|(id, _)| id == editor.tree.focus)
+            .unwrap_or(0);
+        Self {
+            layout: capture_layout(editor, editor.tree.layout_tree()),
+            focus,
+        }
+    }
+
+    /// Restores the layout into the `editor`, replacing all views.
+    ///
+    /// Buffers that are not open are opened, files that no longer exist are
+    /// replaced with scratch buffers.
+    pub fn restore(&self, editor: &mut Editor) {
+        let mut paths = Vec::new();
+        collect_paths(&self.layout, &mut paths);
+        for path in paths {
+            if editor.document_id_by_path(path).is_some() || !path.is_file() {
+                continue;
+            }
+            if let Err(err) = editor.open(path, Action::Load) {
+                log::warn!("failed to open {} from layout: {err}", path.display());
+            }
+        }
+        restore_views(editor, &self.layout, self.focus);
+    }
+}
+
+fn collect_paths<'a>(layout: &'a SessionLayout, paths: &mut Vec<&'a Path>) {
+    match layout {
+        SessionLayout::View(view) => paths.extend(view.document.as_deref()),
+        SessionLayout::Container { children, .. } => {
+            for child in children {
+                collect_paths(child, paths);
+            }
+        }
+    }
+}
+
+/// Replaces all views of the `editor` with `layout` and focuses the view at
+/// index `focus` in traversal order.
+fn restore_views(editor: &mut Editor, layout: &SessionLayout, focus: usize) {
+    let focused = editor.tree.focus;
+    let views: Vec<_> = editor
+        .tree
+        .views()
+        .map(|(view, _)| view.id)
+        .filter(|&id| id != focused)
+        .collect();
+    for view_id in views {
+        editor.close(view_id);
+    }
+    restore_layout(editor, layout);
+
+    if let Some((view_id, _)) = editor.tree.traverse().nth(focus) {
+        editor.focus(view_id);
+    }
+}
+
+fn capture_layout(editor: &Editor, layout: LayoutTree) -> SessionLayout {
+    match layout {
+        LayoutTree::View(view_id) => {
+            let view = editor.tree.get(view_id);
+            let doc = &editor.documents[&view.doc];
+            let jumps = view
+                .jumps
+                .iter()
+                .filter_map(|(doc_id, selection)| {
+                    let path = editor.documents.get(doc_id)?.path()?;
+                    Some((path.to_path_buf(), selection.into()))
+                })
+                .collect();
+            SessionLayout::View(SessionView {
+                document: doc.path().map(Path::to_path_buf),
+                selection: doc.selection(view_id).into(),
+                jumps,
+            })
+        }
+        LayoutTree::Container(layout, children) => {
+            let (children, weights) = children
+                .into_iter()
+                .map(|(child, weight)| (capture_layout(editor, child), weight))
+                .unzip();
+            SessionLayout::Container {
+                layout,
+                children,
+                weights,
+            }
+        }
+    }
+}
+
+/// Restores `layout` in place of the focused view.
+fn restore_layout(editor: &mut Editor, layout: &SessionLayout) {
+    match layout {
+        SessionLayout::View(view) => restore_view(editor, view),
+        SessionLayout::Container {
+            layout,
+            children,
+            weights,
+        } => {
+            let action = match layout {
+                Layout::Horizontal => Action::HorizontalSplit,
+                Layout::Vertical => Action::VerticalSplit,
+            };
+            // create a view for every child first so that splitting the children
+            // does not change the layout of this container
+            let mut slots = vec![editor.tree.focus];
+            for _ in 1..children.len() {
+                let doc_id = view!(editor).doc;
+                editor.switch(doc_id, action);
+                slots.push(editor.tree.focus);
+            }
+            // splitting a child later on passes its weight on to the new container
+            for (&view_id, &weight) in slots.iter().zip(weights) {
+                editor.tree.set_weight(view_id, weight);
+            }
+            for (view_id, child) in slots.into_iter().zip(children) {
+                editor.focus(view_id);
+                restore_layout(editor, child);
+            }
+        }
+    }
+}
+
+fn restore_view(editor: &mut Editor, session_view: &SessionView) {
+    let doc_id = session_view
+        .document
+        .as_ref()
+        .and_then(|path| editor.document_id_by_path(path));
+    let doc_id = match doc_id {
+        Some(doc_id) => {
+            editor.switch(doc_id, Action::Replace);
+            doc_id
+        }
+        None => editor.new_file(Action::Replace),
+    };
+
+    let jumps: Vec<(DocumentId, Selection)> = session_view
+        .jumps
+        .iter()
+        .filter_map(|(path, selection)| {
+            let doc = editor.document_by_path(path)?;
+            Some((doc.id(), selection.to_selection(doc.text().slice(..))))
+        })
+        .collect();
+
+    let (view, doc) = current!(editor);
+    let selection = session_view.selection.to_selection(doc.text().slice(..));
+    doc.set_selection(view.id, selection);
+    align_view(doc, view, Align::Center);
+
+    let mut jumps = jumps.into_iter();
+    if let Some(first) = jumps.next() {
+        view.jumps = JumpList::new(first);
+        for jump in jumps {
+            view.jumps.push(jump);
+        }
+    }
+    debug_assert_eq!(view.doc, doc_id);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use helix_core::Rope;
+
+    #[test]
+    fn selection_round_trip() {
+        let text = Rope::from("hello\nworld\n");
+        let selection = Selection::new(
+            [Range::new(0, 5), Range::new(8, 6)].into_iter().collect(),
+            1,
+        );
+        let session_selection = SessionSelection::from(&selection);
+        assert_eq!(session_selection.ranges, vec![(0, 5), (8, 6)]);
+        assert_eq!(session_selection.to_selection(text.slice(..)), selection);
+    }
+
+    #[test]
+    fn selection_is_clamped() {
+        let text = Rope::from("hi\n");
+        let session_selection = SessionSelection {
+            ranges: vec![(1, 20)],
+            primary: 5,
+        };
+        let selection = session_selection.to_selection(text.slice(..));
+        assert_eq!(selection.primary_index(), 0);
+        assert_eq!(selection.ranges(), &[Range::new(1, 3)]);
+
+        let empty = SessionSelection {
+            ranges: Vec::new(),
+            primary: 0,
+        };
+        assert_eq!(empty.to_selection(text.slice(..)), Selection::point(0));
+    }
+
+    #[test]
+    fn file_names() {
+        assert!(Session::file("work").is_ok());
+        assert!(Session::file("").is_err());
+        assert!(Session::file("a/b").is_err());
+    }
+}
@@ -128,6 +128,7 @@ pub fn load_with_warnings(&self, name: &str) -> Result<(Theme, Vec<String>)> {
 
         let theme = Theme {
             name: name.into(),
+            paths: visited_paths.into_iter().collect(),
             ..theme
         };
         Ok((theme, warnings))
@@ -281,6 +282,8 @@ pub struct Theme {
     /// is called many times per frame, so we optimize lookups.
     scope_index: HashMap<String, Highlight>,
     rainbow_length: usize,
+    /// The files the theme and the themes it inherits from were loaded from.
+    paths: Vec<PathBuf>,
 }
 
 impl From<Value> for Theme {
@@ -423,6 +426,11 @@ pub fn name(&self) -> &str {
         &self.name
     }
 
+    /// Returns the files the theme was loaded from, which is empty for the built-in themes.
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
     pub fn get(&self, scope: &str) -> Style {
         self.try_get(scope).unwrap_or_default()
     }
@@ -1,4 +1,5 @@
 use crate::{graphics::Rect, View, ViewId};
+use serde::{Deserialize, Serialize};
 use slotmap::SlotMap;
 
 // the dimensions are recomputed on window resize/tree change.
@@ -17,10 +18,15 @@ pub struct Tree {
     stack: Vec<(ViewId, Rect)>,
 }
 
+/// The weight of a node that was not resized. The children of a container share its area
+/// proportionally to their weights.
+pub const DEFAULT_WEIGHT: u32 = 100;
+
 #[derive(Debug)]
 pub struct Node {
     parent: ViewId,
     content: Content,
+    weight: u32,
 }
 
 #[derive(Debug)]
@@ -34,6 +40,7 @@ pub fn container(layout: Layout) -> Self {
         Self {
             parent: ViewId::default(),
             content: Content::Container(Box::new(Container::new(layout))),
+            weight: DEFAULT_WEIGHT,
         }
     }
 
@@ -41,17 +48,27 @@ pub fn view(view: View) -> Self {
         Self {
             parent: ViewId::default(),
             content: Content::View(Box::new(view)),
+            weight: DEFAULT_WEIGHT,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Layout {
     Horizontal,
     Vertical,
     // could explore stacked/tabbed
 }
 
+/// The shape of a [`Tree`], see [`Tree::layout_tree`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutTree {
+    View(ViewId),
+    /// A container with its children and their weights.
+    Container(Layout, Vec<(LayoutTree, u32)>),
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum Direction {
     Up,
@@ -173,6 +190,8 @@ pub fn split(&mut self, view: View, layout: Layout) -> ViewId {
         } else {
             let mut split = Node::container(layout);
             split.parent = parent;
+            // the new container takes the place of the focused view, including its size
+            split.weight = std::mem::replace(&mut self.nodes[focus].weight, DEFAULT_WEIGHT);
             let split = self.nodes.insert(split);
 
             let container = match &mut self.nodes[split] {
@@ -263,6 +282,7 @@ pub fn remove(&mut self, index: ViewId) {
             // Lets merge the only child back to its grandparent so that Views
             // are equally spaced.
             let sibling = parent_container.children.pop().unwrap();
+            self.nodes[sibling].weight = self.nodes[parent].weight;
             self.remove_or_replace(parent, Some(sibling));
         }
 
@@ -368,6 +388,14 @@ pub fn recalculate(&mut self) {
         // b) node is container, calculate areas for each child and push them on the stack
 
         while let Some((key, area)) = self.stack.pop() {
+            let weights: Vec<_> = match &self.nodes[key].content {
+                Content::Container(container) => container
+                    .children
+                    .iter()
+                    .map(|&child| self.nodes[child].weight)
+                    .collect(),
+                Content::View(_) => Vec::new(),
+            };
             let node = &mut self.nodes[key];
 
             match &mut node.content {
@@ -383,11 +411,12 @@ pub fn recalculate(&mut self) {
                         Layout::Horizontal => {
                             let len = container.children.len();
 
-                            let height = area.height / len as u16;
+                            let heights = weighted_sizes(area.height, &weights);
 
                             let mut child_y = area.y;
 
                             for (i, child) in container.children.iter().enumerate() {
+                                let height = heights[i];
                                 let mut area = Rect::new(
                                     container.area.x,
                                     child_y,
@@ -413,11 +442,12 @@ pub fn recalculate(&mut self) {
                             let total_gap = inner_gap * len_u16.saturating_sub(2);
 
                             let used_area = area.width.saturating_sub(total_gap);
-                            let width = used_area / len_u16;
+                            let widths = weighted_sizes(used_area, &weights);
 
                             let mut child_x = area.x;
 
                             for (i, child) in container.children.iter().enumerate() {
+                                let width = widths[i];
                                 let mut area = Rect::new(
                                     child_x,
                                     container.area.y,
@@ -445,6 +475,100 @@ pub fn traverse(&self) -> Traverse<'_> {
         Traverse::new(self)
     }
 
+    /// Returns the nesting of containers and views starting at the root container.
+    /// Views are visited in the same order as [`Tree::traverse`].
+    pub fn layout_tree(&self) -> LayoutTree {
+        self.layout_tree_at(self.root)
+    }
+
+    fn layout_tree_at(&self, id: ViewId) -> LayoutTree {
+        match &self.nodes[id].content {
+            Content::View(_) => LayoutTree::View(id),
+            Content::Container(container) => LayoutTree::Container(
+                container.layout,
+                container
+                    .children
+                    .iter()
+                    .map(|&child| (self.layout_tree_at(child), self.nodes[child].weight))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Sets the weight of a view or container, see [`DEFAULT_WEIGHT`].
+    pub fn set_weight(&mut self, id: ViewId, weight: u32) {
+        self.nodes[id].weight = weight.max(1);
+        self.recalculate();
+    }
+
+    /// Grows the view by `amount` cells (shrinks it if negative) along `layout`: its width for
+    /// [`Layout::Vertical`] and its height for [`Layout::Horizontal`]. The space is taken from the
+    /// next view or container in the closest container with that layout, or from the previous one
+    /// for the last child. Both keep at least a few cells.
+    ///
+    /// Returns `false` if the view could not be resized.
+    pub fn resize_view(&mut self, id: ViewId, layout: Layout, amount: i32) -> bool {
+        const MIN_SIZE: u64 = 2;
+
+        // find the closest container that splits along the layout
+        let mut child = id;
+        let (children, size) = loop {
+            let parent = self.nodes[child].parent;
+            if parent == child {
+                return false;
+            }
+            match &self.nodes[parent].content {
+                Content::Container(container)
+                    if container.layout == layout && container.children.len() > 1 =>
+                {
+                    let size = match layout {
+                        Layout::Vertical => container.area.width,
+                        Layout::Horizontal => container.area.height,
+                    };
+                    break (container.children.clone(), size.max(1) as u64);
+                }
+                _ => child = parent,
+            }
+        };
+        let pos = children.iter().position(|&c| c == child).unwrap();
+        let neighbour = if pos + 1 < children.len() {
+            children[pos + 1]
+        } else {
+            children[pos - 1]
+        };
+
+        // convert the cells into weight
+        let total: u64 = children.iter().map(|&c| self.nodes[c].weight as u64).sum();
+        let mut delta = amount as i64 * total as i64 / size as i64;
+        if delta == 0 {
+            delta = amount.signum() as i64;
+        }
+        let min_weight = (MIN_SIZE * total / size).max(1) as i64;
+        let child_weight = self.nodes[child].weight as i64;
+        let neighbour_weight = self.nodes[neighbour].weight as i64;
+        let (min, max) = (min_weight - child_weight, neighbour_weight - min_weight);
+        if min > max {
+            return false;
+        }
+        let delta = delta.clamp(min, max);
+        if delta == 0 {
+            return false;
+        }
+        self.nodes[child].weight = (child_weight + delta) as u32;
+        self.nodes[neighbour].weight = (neighbour_weight - delta) as u32;
+        self.recalculate();
+        true
+    }
+
+    /// Gives all views and containers the same weight so that the children of every container
+    /// are equally sized.
+    pub fn equalize(&mut self) {
+        for (_, node) in self.nodes.iter_mut() {
+            node.weight = DEFAULT_WEIGHT;
+        }
+        self.recalculate();
+    }
+
     // Finds the split in the given direction if it exists
     pub fn find_split_in_direction(&self, id: ViewId, direction: Direction) -> Option<ViewId> {
         let parent = self.nodes[id].parent;
@@ -618,6 +742,8 @@ pub fn swap_split_in_direction(&mut self, direction: Direction) -> Option<()> {
                         .position(|id| target_view.id == *id)?;
                     // swap node positions so that traversal order is kept
                     parent.children[focus_pos] = target_view.id;
+                    // the sizes stay in place
+                    std::mem::swap(&mut focus.weight, &mut target.weight);
                     parent.children[target_pos] = focus_view.id;
                     // swap area so that views rendered at the correct location
                     std::mem::swap(&mut focus_view.area, &mut target_view.area);
@@ -656,6 +782,7 @@ pub fn swap_split_in_direction(&mut self, direction: Direction) -> Option<()> {
                         &mut target_parent.children[target_pos],
                     );
                     std::mem::swap(&mut focus.parent, &mut target.parent);
+                    std::mem::swap(&mut focus.weight, &mut target.weight);
                     // swap area so that views rendered at the correct location
                     std::mem::swap(&mut focus_view.area, &mut target_view.area);
 
@@ -671,6 +798,19 @@ pub fn area(&self) -> Rect {
     }
 }
 
+/// Splits `size` between nodes proportionally to their `weights`, rounding down.
+fn weighted_sizes(size: u16, weights: &[u32]) -> Vec<u16> {
+    let total = weights
+        .iter()
+        .map(|&weight| weight as u64)
+        .sum::<u64>()
+        .max(1);
+    weights
+        .iter()
+        .map(|&weight| (size as u64 * weight as u64 / total) as u16)
+        .collect()
+}
+
 #[derive(Debug)]
 pub struct Traverse<'a> {
     tree: &'a Tree,
@@ -965,4 +1105,88 @@ fn vsplit_gap_rounding() {
                 .collect::<Vec<_>>()
         );
     }
+
+    #[test]
+    fn layout_tree() {
+        let mut tree = Tree::new(Rect::new(0, 0, 180, 80));
+        let view = View::new(DocumentId::default(), GutterConfig::default());
+        tree.insert(view);
+        let l0 = tree.focus;
+
+        let view = View::new(DocumentId::default(), GutterConfig::default());
+        tree.split(view, Layout::Vertical);
+        let r0 = tree.focus;
+
+        tree.focus = l0;
+        let view = View::new(DocumentId::default(), GutterConfig::default());
+        tree.split(view, Layout::Horizontal);
+        let l1 = tree.focus;
+
+        assert_eq!(
+            tree.layout_tree(),
+            LayoutTree::Container(
+                Layout::Vertical,
+                vec![
+                    (
+                        LayoutTree::Container(
+                            Layout::Horizontal,
+                            vec![
+                                (LayoutTree::View(l0), DEFAULT_WEIGHT),
+                                (LayoutTree::View(l1), DEFAULT_WEIGHT)
+                            ]
+                        ),
+                        DEFAULT_WEIGHT
+                    ),
+                    (LayoutTree::View(r0), DEFAULT_WEIGHT),
+                ]
+            )
+        );
+        assert_eq!(
+            tree.traverse().map(|(id, _)| id).collect::<Vec<_>>(),
+            vec![l0, l1, r0]
+        );
+    }
+    #[test]
+    fn resize_view() {
+        let mut tree = Tree::new(Rect::new(0, 0, 100, 80));
+        let view = View::new(DocumentId::default(), GutterConfig::default());
+        tree.insert(view);
+        let left = tree.focus;
+        let view = View::new(DocumentId::default(), GutterConfig::default());
+        tree.split(view, Layout::Vertical);
+        let right = tree.focus;
+        assert_eq!(tree.get(left).area.width, 50);
+        assert_eq!(tree.get(right).area.width, 49);
+
+        assert!(tree.resize_view(left, Layout::Vertical, 10));
+        assert_eq!(tree.get(left).area.width, 60);
+        assert_eq!(tree.get(right).area.width, 39);
+
+        // the last view takes the space from the previous one
+        assert!(tree.resize_view(right, Layout::Vertical, 20));
+        assert_eq!(tree.get(left).area.width, 40);
+        assert_eq!(tree.get(right).area.width, 59);
+
+        // there is no horizontal split
+        assert!(!tree.resize_view(left, Layout::Horizontal, 5));
+
+        // splitting a resized view keeps the size of the container
+        tree.focus = right;
+        let view = View::new(DocumentId::default(), GutterConfig::default());
+        tree.split(view, Layout::Horizontal);
+        let bottom = tree.focus;
+        assert_eq!(tree.get(bottom).area.width, 59);
+        assert_eq!(tree.get(bottom).area.height, 40);
+        assert!(tree.resize_view(bottom, Layout::Vertical, -10));
+        assert_eq!(tree.get(left).area.width, 50);
+        assert_eq!(tree.get(right).area.width, 49);
+
+        // views can't shrink below the minimum size
+        assert!(tree.resize_view(left, Layout::Vertical, -1000));
+        assert_eq!(tree.get(left).area.width, 2);
+
+        tree.equalize();
+        assert_eq!(tree.get(left).area.width, 50);
+        assert_eq!(tree.get(bottom).area.height, 40);
+    }
 }
@@ -0,0 +1,95 @@
+//! Persistent undo. The history of a document is saved to a file in
+//! [`helix_loader::undo_dir`] named after a hash of the document's path, together with a hash of
+//! the text that the current revision corresponds to. The history is only restored if the file
+//! still has that text when it is opened again.
+
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use helix_core::{
+    history::{History, PersistentHistory},
+    Rope,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::Document;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct UndoFile {
+    /// The path of the document, to detect collisions of the hashes of the paths.
+    path: PathBuf,
+    text_hash: u64,
+    history: PersistentHistory,
+}
+
+fn undo_file_path(path: &Path) -> PathBuf {
+    let hash = helix_stdx::path::stable_hash(path);
+    helix_loader::undo_dir().join(format!("{hash:016x}.json"))
+}
+
+/// A hash of the text that stays the same across runs, the same 64-bit FNV-1a as
+/// [helix_stdx::path::stable_hash].
+fn text_hash(text: &Rope) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Saves the history of `doc`. Documents with unsaved changes are skipped since the current
+/// revision of their history doesn't match the file on disk.
+pub fn save(doc: &Document) -> Result<()> {
+    let Some(path) = doc.path() else {
+        return Ok(());
+    };
+    if doc.is_modified() {
+        return Ok(());
+    }
+    let undo_file = undo_file_path(path);
+    // don't leave a stale history behind for documents without changes
+    if doc.revisions().len() == 1 {
+        return match fs::remove_file(&undo_file) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        };
+    }
+    if let Some(dir) = undo_file.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let history = doc.history.take();
+    let contents = UndoFile {
+        path: path.to_path_buf(),
+        text_hash: text_hash(doc.text()),
+        history: history.to_persistent(),
+    };
+    doc.history.set(history);
+    fs::write(undo_file, serde_json::to_string(&contents)?)?;
+    Ok(())
+}
+
+/// Restores the history of `doc` saved by [save] if the text of the document didn't change in
+/// the meantime. A missing or outdated history is not an error.
+pub fn load(doc: &mut Document) -> Result<()> {
+    let Some(path) = doc.path() else {
+        return Ok(());
+    };
+    let json = match fs::read_to_string(undo_file_path(path)) {
+        Ok(json) => json,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+    let undo_file: UndoFile = serde_json::from_str(&json)?;
+    if undo_file.path != path || undo_file.text_hash != text_hash(doc.text()) {
+        return Ok(());
+    }
+    let Some(history) = History::from_persistent(undo_file.history) else {
+        anyhow::bail!("invalid undo history for {}", path.display());
+    };
+    doc.history.set(history);
+    doc.reset_modified();
+    Ok(())
+}
@@ -157,6 +157,8 @@ pub struct View {
     pub last_modified_docs: [Option<DocumentId>; 2],
     /// used to store previous selections of tree-sitter objects
     pub object_selections: Vec<Selection>,
+    /// The visual coordinates of the corner opposite of the cursor while selecting a block
+    pub block_anchor: Option<Position>,
     /// all gutter-related configuration settings, used primarily for gutter rendering
     pub gutters: GutterConfig,
     /// A mapping between documents and the last history revision the view was updated at.
@@ -194,6 +196,7 @@ pub fn new(doc: DocumentId, gutters: GutterConfig) -> Self {
             docs_access_history: Vec::new(),
             last_modified_docs: [None, None],
             object_selections: Vec::new(),
+            block_anchor: None,
             gutters,
             doc_revisions: HashMap::new(),
             diagnostics_handler: DiagnosticsHandler::new(),
@@ -208,7 +211,10 @@ pub fn add_to_history(&mut self, id: DocumentId) {
     }
 
     pub fn inner_area(&self, doc: &Document) -> Rect {
-        self.area.clip_left(self.gutter_offset(doc)).clip_bottom(1) // -1 for statusline
+        self.area
+            .clip_left(self.gutter_offset(doc))
+            .clip_right(self.scrollbar_width(doc))
+            .clip_bottom(1) // -1 for statusline
     }
 
     pub fn inner_height(&self) -> usize {
@@ -216,13 +222,52 @@ pub fn inner_height(&self) -> usize {
     }
 
     pub fn inner_width(&self, doc: &Document) -> u16 {
-        self.area.clip_left(self.gutter_offset(doc)).width
+        self.area
+            .clip_left(self.gutter_offset(doc))
+            .clip_right(self.scrollbar_width(doc))
+            .width
     }
 
     pub fn gutters(&self) -> &[GutterType] {
         &self.gutters.layout
     }
 
+    /// The width of the overview column at the right edge of the view, see
+    /// [crate::editor::ScrollbarConfig].
+    pub fn scrollbar_width(&self, doc: &Document) -> u16 {
+        let enable = doc.config.load().scrollbar.enable;
+        // the text keeps at least one column
+        u16::from(enable && self.area.width > self.gutter_offset(doc) + 1)
+    }
+
+    /// The area of the overview column, without the statusline.
+    pub fn scrollbar_area(&self, doc: &Document) -> Option<Rect> {
+        let area = self.area.clip_bottom(1);
+        if self.scrollbar_width(doc) == 0 || area.height == 0 {
+            return None;
+        }
+        Some(area.clip_left(area.width - 1))
+    }
+
+    /// The line shown at `row` of the overview column if the screen coordinates are inside of
+    /// it, see [scrollbar_line].
+    pub fn scrollbar_line_at_screen_coords(
+        &self,
+        doc: &Document,
+        row: u16,
+        column: u16,
+    ) -> Option<usize> {
+        let area = self.scrollbar_area(doc)?;
+        if column != area.x || row < area.top() || row >= area.bottom() {
+            return None;
+        }
+        Some(scrollbar_line(
+            row - area.y,
+            doc.text().len_lines(),
+            area.height,
+        ))
+    }
+
     pub fn gutter_offset(&self, doc: &Document) -> u16 {
         let total_width = self
             .gutters
@@ -237,6 +282,18 @@ pub fn gutter_offset(&self, doc: &Document) -> u16 {
         }
     }
 
+    /// The gutter at `column`, relative to the left edge of the view.
+    pub fn gutter_at(&self, doc: &Document, column: usize) -> Option<GutterType> {
+        if column >= self.gutter_offset(doc) as usize {
+            return None;
+        }
+        let mut right = 0;
+        self.gutters().iter().copied().find(|gutter| {
+            right += gutter.width(self, doc);
+            column < right
+        })
+    }
+
     //
     pub fn offset_coords_to_in_view(
         &self,
@@ -261,10 +318,14 @@ pub fn offset_coords_to_in_view_center<const CENTERING: bool>(
         let (scrolloff_top, scrolloff_bottom) = if CENTERING {
             (0, 0)
         } else {
+            let scrolloff_bottom = scrolloff.min(viewport.height as usize / 2);
+            // the sticky context covers the first rows of the view
+            let context = doc.sticky_context(self.id, viewport.height).len();
             (
                 // - 1 from the top so we have at least one gap in the middle.
-                scrolloff.min(viewport.height.saturating_sub(1) as usize / 2),
-                scrolloff.min(viewport.height as usize / 2),
+                (scrolloff.min(viewport.height.saturating_sub(1) as usize / 2) + context)
+                    .min((viewport.height as usize).saturating_sub(scrolloff_bottom + 1)),
+                scrolloff_bottom,
             )
         };
         let (scrolloff_left, scrolloff_right) = if CENTERING {
@@ -474,6 +535,7 @@ pub fn text_annotations<'a>(
             other_inlay_hints,
             padding_before_inlay_hints,
             padding_after_inlay_hints,
+            lsp_inlay_hints: _,
         }) = doc.inlay_hints.get(&self.id)
         {
             let type_style = theme.and_then(|t| t.find_highlight("ui.virtual.inlay-hint.type"));
@@ -509,6 +571,27 @@ pub fn text_annotations<'a>(
             }
         }
 
+        if let Some(folds) = doc.folds(self.id) {
+            let style = theme.and_then(|t| t.find_highlight("ui.virtual.fold"));
+            text_annotations
+                .set_folds(folds.folds())
+                .add_inline_annotations(folds.markers(), style);
+        }
+
+        if let Some(completion) = doc
+            .inline_completion
+            .as_ref()
+            .filter(|completion| completion.view_id == self.id)
+        {
+            let text = doc.text().slice(..);
+            if completion.cursor == doc.selection(self.id).primary().cursor(text) {
+                let style = theme.and_then(|t| t.find_highlight("ui.virtual.inline-completion"));
+                text_annotations
+                    .add_inline_annotations(completion.annotation(), style)
+                    .add_line_annotation(completion.line_annotation(text));
+            }
+        }
+
         let width = self.inner_width(doc);
         let enable_cursor_line = self
             .diagnostics_handler
@@ -550,6 +633,12 @@ pub fn text_pos_at_screen_coords(
             return None;
         }
 
+        // the rows of the sticky context show the start of the context lines
+        let context = doc.sticky_context(self.id, inner.height);
+        if let Some(&line) = context.get((row - inner.y) as usize) {
+            return Some(doc.text().line_to_char(line));
+        }
+
         self.text_pos_at_visual_coords(
             doc,
             row - inner.y,
@@ -710,6 +799,22 @@ pub fn push_jump(&mut self, doc: &mut Document, jump: (DocumentId, Selection)) {
     }
 }
 
+/// The row of an overview column of `height` rows that covers `line` of a document of
+/// `len_lines` lines. Each row covers an equal share of the lines.
+pub fn scrollbar_row(line: usize, len_lines: usize, height: u16) -> u16 {
+    let row = line * height as usize / len_lines.max(1);
+    row.min(height.saturating_sub(1) as usize) as u16
+}
+
+/// The first line covered by `row` of an overview column of `height` rows, the inverse of
+/// [scrollbar_row]. Rows that cover no line, when the document is shorter than the column, show
+/// the next line.
+pub fn scrollbar_line(row: u16, len_lines: usize, height: u16) -> usize {
+    let height = height.max(1) as usize;
+    let line = (row as usize * len_lines).div_ceil(height);
+    line.min(len_lines.saturating_sub(1))
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -959,6 +1064,42 @@ fn test_text_pos_at_screen_coords_without_any_gutters() {
         );
     }
 
+    #[test]
+    fn test_gutter_at() {
+        let view = View::new(DocumentId::default(), GutterConfig::default());
+        let doc = Document::from(
+            Rope::from_str("abc\n\tdef"),
+            None,
+            Arc::new(ArcSwap::new(Arc::new(Config::default()))),
+            Arc::new(ArcSwap::from_pointee(syntax::Loader::default())),
+        );
+        let gutters: Vec<_> = (0..DEFAULT_GUTTER_OFFSET as usize + 1)
+            .map(|column| view.gutter_at(&doc, column))
+            .collect();
+        assert_eq!(
+            gutters,
+            [
+                Some(GutterType::Diagnostics),
+                Some(GutterType::Spacer),
+                Some(GutterType::LineNumbers),
+                Some(GutterType::LineNumbers),
+                Some(GutterType::LineNumbers),
+                Some(GutterType::Spacer),
+                Some(GutterType::Diff),
+                None,
+            ]
+        );
+
+        let view = View::new(
+            DocumentId::default(),
+            GutterConfig {
+                layout: vec![],
+                line_numbers: GutterLineNumbersConfig::default(),
+            },
+        );
+        assert_eq!(view.gutter_at(&doc, 0), None);
+    }
+
     #[test]
     fn test_text_pos_at_screen_coords_cjk() {
         let mut view = View::new(DocumentId::default(), GutterConfig::default());
@@ -1252,4 +1393,40 @@ fn jumplist_push_keeps_doc_revisions_in_sync() {
             "jumplist selection must stay within document bounds after sync",
         );
     }
+
+    #[test]
+    fn scrollbar_rows_and_lines() {
+        for (len_lines, height) in [(3, 2), (10, 3), (100, 7), (1000, 40), (40, 40)] {
+            // a click on a row goes to a line shown in that row
+            for row in 0..height {
+                let line = scrollbar_line(row, len_lines, height);
+                assert_eq!(scrollbar_row(line, len_lines, height), row);
+            }
+            assert_eq!(scrollbar_row(0, len_lines, height), 0);
+            assert_eq!(scrollbar_row(len_lines - 1, len_lines, height), height - 1);
+        }
+        // rows below the end of a short document go to the last line
+        assert_eq!(scrollbar_row(1, 2, 4), 2);
+        assert_eq!(scrollbar_line(3, 2, 4), 1);
+    }
+
+    #[test]
+    fn scrollbar_line_at_screen_coords() {
+        let mut config = Config::default();
+        config.scrollbar.enable = true;
+        let config = Arc::new(ArcSwap::new(Arc::new(config)));
+        let loader = Arc::new(ArcSwap::from_pointee(syntax::Loader::default()));
+        let text = Rope::from_str(&"line\n".repeat(99));
+        let doc = Document::from(text, None, config, loader);
+        let mut view = View::new(doc.id(), GutterConfig::default());
+        view.area = Rect::new(10, 5, 40, 11);
+
+        // the column is the last one of the view, without the statusline
+        assert_eq!(view.scrollbar_area(&doc), Some(Rect::new(49, 5, 1, 10)));
+        assert_eq!(view.scrollbar_line_at_screen_coords(&doc, 5, 49), Some(0));
+        assert_eq!(view.scrollbar_line_at_screen_coords(&doc, 10, 49), Some(50));
+        assert_eq!(view.scrollbar_line_at_screen_coords(&doc, 14, 49), Some(90));
+        assert_eq!(view.scrollbar_line_at_screen_coords(&doc, 15, 49), None);
+        assert_eq!(view.scrollbar_line_at_screen_coords(&doc, 5, 48), None);
+    }
 }
@@ -0,0 +1,5 @@
+[
+  (abstract_class_declaration)
+  (interface_declaration)
+  (enum_declaration)
+] @context
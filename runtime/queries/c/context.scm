@@ -0,0 +1,10 @@
+[
+  (function_definition)
+  (struct_specifier)
+  (union_specifier)
+  (enum_specifier)
+  (if_statement)
+  (for_statement)
+  (while_statement)
+  (do_statement)
+] @context
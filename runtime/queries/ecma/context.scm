@@ -0,0 +1,16 @@
+[
+  (function_declaration)
+  (generator_function_declaration)
+  (function_expression)
+  (class_declaration)
+  (method_definition)
+  (if_statement)
+  (else_clause)
+  (for_statement)
+  (for_in_statement)
+  (while_statement)
+  (do_statement)
+  (switch_statement)
+  (catch_clause)
+  (finally_clause)
+] @context
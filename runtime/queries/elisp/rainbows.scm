@@ -0,0 +1,13 @@
+[
+  (list)
+  (vector)
+  (bytecode)
+  (special_form)
+  (function_definition)
+  (macro_definition)
+] @rainbow.scope
+
+[
+  "(" ")"
+  "#[" "[" "]"
+] @rainbow.bracket
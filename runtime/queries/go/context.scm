@@ -0,0 +1,8 @@
+[
+  (function_declaration)
+  (method_declaration)
+  (type_declaration)
+  (expression_switch_statement)
+  (type_switch_statement)
+  (select_statement)
+] @context
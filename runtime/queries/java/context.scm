@@ -0,0 +1,13 @@
+[
+  (class_declaration)
+  (interface_declaration)
+  (enum_declaration)
+  (record_declaration)
+  (method_declaration)
+  (constructor_declaration)
+  (lambda_expression)
+  (if_statement)
+  (for_statement)
+  (enhanced_for_statement)
+  (while_statement)
+] @context
@@ -0,0 +1,16 @@
+[
+  (function_item)
+  (impl_item)
+  (trait_item)
+  (mod_item)
+  (struct_item)
+  (enum_item)
+  (union_item)
+  (macro_definition)
+  (closure_expression)
+  (if_expression)
+  (match_expression)
+  (for_expression)
+  (while_expression)
+  (loop_expression)
+] @context
@@ -38,6 +38,8 @@ pub fn querycheck(languages: impl Iterator<Item = String>) -> Result<(), DynErro
             LanguageData::compile_textobject_query(grammar, config)?;
             LanguageData::compile_tag_query(grammar, config)?;
             LanguageData::compile_rainbow_query(grammar, config)?;
+            LanguageData::compile_context_query(grammar, config)?;
+            LanguageData::compile_fold_query(grammar, config)?;
         }
 
         println!("Query check succeeded");